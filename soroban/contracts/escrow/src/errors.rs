@@ -0,0 +1,44 @@
+use soroban_sdk::contracterror;
+
+/// Typed error codes for the escrow contract.
+///
+/// **Never reorder or remove codes** once deployed — error codes are part of
+/// the on-chain ABI. Only add new variants at the end.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ContractError {
+    /// `initialize()` was called on a contract that is already set up.
+    AlreadyInitialized = 1,
+
+    /// A method that requires admin was called before `initialize()`.
+    NotInitialized = 2,
+
+    /// `deposit()` was called for an `invoice_id` that already has an escrow.
+    EscrowAlreadyExists = 3,
+
+    /// No escrow exists for the `invoice_id`.
+    EscrowNotFound = 4,
+
+    /// `invoice_id` was empty.
+    InvalidInvoiceId = 5,
+
+    /// `amount` was zero or negative.
+    InvalidAmount = 6,
+
+    /// `deadline` was not in the future.
+    InvalidDeadline = 7,
+
+    /// The escrow is not [`EscrowStatus::Held`](crate::storage::EscrowStatus::Held):
+    /// it was already released, refunded or put in dispute.
+    EscrowNotHeld = 8,
+
+    /// `resolve_dispute()` was called on an escrow without an open dispute.
+    NoOpenDispute = 9,
+
+    /// `claim_timeout_refund()` was called before the escrow's deadline.
+    DeadlineNotReached = 10,
+
+    /// `sweep()` found no balance beyond what open escrows are owed.
+    NothingToSweep = 11,
+}
@@ -0,0 +1,72 @@
+use soroban_sdk::{contractevent, Address, Env, String};
+
+use crate::storage::Escrow;
+
+#[contractevent]
+pub struct EscrowFunded {
+    pub escrow: Escrow,
+}
+
+#[contractevent]
+pub struct EscrowReleased {
+    pub invoice_id: String,
+    pub merchant: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct DisputeOpened {
+    pub invoice_id: String,
+}
+
+/// Funds returned to the payer, either by [`claim_timeout_refund`] or by the
+/// admin settling a dispute.
+///
+/// [`claim_timeout_refund`]: crate::EscrowContract::claim_timeout_refund
+#[contractevent]
+pub struct EscrowRefunded {
+    pub invoice_id: String,
+    pub payer: Address,
+    pub amount: i128,
+    /// `true` if the payer reclaimed the funds after the deadline.
+    pub timeout: bool,
+}
+
+/// Residual balance not owed to any escrow was moved out by the admin.
+#[contractevent]
+pub struct Swept {
+    pub token: Address,
+    pub to: Address,
+    pub amount: i128,
+}
+
+pub fn emit_escrow_funded(env: &Env, escrow: Escrow) {
+    EscrowFunded { escrow }.publish(env);
+}
+
+pub fn emit_escrow_released(env: &Env, escrow: &Escrow) {
+    EscrowReleased {
+        invoice_id: escrow.invoice_id.clone(),
+        merchant: escrow.merchant.clone(),
+        amount: escrow.amount,
+    }
+    .publish(env);
+}
+
+pub fn emit_dispute_opened(env: &Env, invoice_id: String) {
+    DisputeOpened { invoice_id }.publish(env);
+}
+
+pub fn emit_escrow_refunded(env: &Env, escrow: &Escrow, timeout: bool) {
+    EscrowRefunded {
+        invoice_id: escrow.invoice_id.clone(),
+        payer: escrow.payer.clone(),
+        amount: escrow.amount,
+        timeout,
+    }
+    .publish(env);
+}
+
+pub fn emit_swept(env: &Env, token: Address, to: Address, amount: i128) {
+    Swept { token, to, amount }.publish(env);
+}
@@ -0,0 +1,279 @@
+#![no_std]
+use soroban_sdk::{contract, contractimpl, token, Address, Env, String};
+
+pub mod errors;
+pub mod events;
+pub mod storage;
+
+pub use errors::ContractError;
+pub use storage::{DataKey, Escrow, EscrowStatus};
+
+use events::{
+    emit_dispute_opened, emit_escrow_funded, emit_escrow_refunded, emit_escrow_released, emit_swept,
+};
+use storage::{
+    add_held, get_admin, get_escrow, get_held, has_admin, has_escrow, set_admin, set_escrow,
+};
+
+// Contract
+
+/// # Invoisio Escrow Contract
+///
+/// Holds the payment of an escrowed invoice until the merchant is paid out,
+/// a dispute is settled, or the deadline passes. Unlike `invoice-payment`,
+/// which only logs payments made elsewhere, this contract takes custody of
+/// funds, so it is kept separate.
+///
+/// ## Module layout
+/// | Module       | Responsibility                            |
+/// |--------------|-------------------------------------------|
+/// | `errors.rs`  | `#[contracterror]` typed error codes      |
+/// | `storage.rs` | `DataKey`, `Escrow`, TTL helpers          |
+/// | `events.rs`  | Soroban event types and emit helpers      |
+/// | `lib.rs`     | Contract entry-points (this file)         |
+///
+/// ## Design decisions
+/// - **No stuck funds:** once the deadline passes, anyone can call
+///   `claim_timeout_refund` for an escrow that is still held, and the funds
+///   go back to the payer. Neither the merchant nor the admin needs to be
+///   around.
+/// - **Disputes freeze the timeout:** an open dispute can only be settled by
+///   the admin, so a payer cannot dispute and then reclaim on timeout.
+/// - **One escrow per invoice:** keyed by `invoice_id`, never reused.
+/// - **Sweepable residue:** the contract tracks what it owes open escrows
+///   per token, so the admin can `sweep` dust and accidental transfers
+///   without ever touching escrowed funds.
+///
+/// ## Typical flow
+/// 1. The payer calls `deposit(invoice_id, payer, merchant, token, amount, deadline)`.
+/// 2. The merchant delivers and calls `release(invoice_id)` before the deadline.
+/// 3. Otherwise the payer either opens a dispute with `open_dispute(invoice_id)`
+///    or, after the deadline, reclaims with `claim_timeout_refund(invoice_id)`.
+#[contract]
+pub struct EscrowContract;
+
+#[contractimpl]
+impl EscrowContract {
+    // Lifecycle
+
+    /// Initialise the contract and register the `admin`, who settles
+    /// disputes.
+    ///
+    /// Returns [`ContractError::AlreadyInitialized`] if called a second time.
+    pub fn initialize(env: Env, admin: Address) -> Result<(), ContractError> {
+        if has_admin(&env) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        set_admin(&env, &admin);
+        Ok(())
+    }
+
+    // Write
+
+    /// Move `amount` of `token` from `payer` into escrow for `invoice_id`,
+    /// payable to `merchant`. Signed by the payer. Emits `escrow_funded`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidInvoiceId`] — `invoice_id` is empty
+    /// - [`ContractError::InvalidAmount`] — `amount` ≤ 0
+    /// - [`ContractError::InvalidDeadline`] — `deadline` is not in the future
+    /// - [`ContractError::EscrowAlreadyExists`]
+    pub fn deposit(
+        env: Env,
+        invoice_id: String,
+        payer: Address,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+        deadline: u64,
+    ) -> Result<(), ContractError> {
+        payer.require_auth();
+
+        if invoice_id.is_empty() {
+            return Err(ContractError::InvalidInvoiceId);
+        }
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if deadline <= env.ledger().timestamp() {
+            return Err(ContractError::InvalidDeadline);
+        }
+        if has_escrow(&env, &invoice_id) {
+            return Err(ContractError::EscrowAlreadyExists);
+        }
+
+        token::Client::new(&env, &token).transfer(&payer, env.current_contract_address(), &amount);
+        add_held(&env, &token, amount);
+        let escrow = Escrow {
+            invoice_id,
+            payer,
+            merchant,
+            token,
+            amount,
+            deadline,
+            status: EscrowStatus::Held,
+        };
+        set_escrow(&env, &escrow);
+        emit_escrow_funded(&env, escrow);
+        Ok(())
+    }
+
+    /// Pay the held funds out to the merchant. Signed by the merchant; still
+    /// possible after the deadline as long as nobody claimed the refund.
+    /// Emits `escrow_released`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::EscrowNotFound`]
+    /// - [`ContractError::EscrowNotHeld`] — already settled or in dispute
+    pub fn release(env: Env, invoice_id: String) -> Result<(), ContractError> {
+        let mut escrow = get_escrow(&env, &invoice_id)?;
+        escrow.merchant.require_auth();
+        if escrow.status != EscrowStatus::Held {
+            return Err(ContractError::EscrowNotHeld);
+        }
+
+        pay_out(&env, &mut escrow, EscrowStatus::Released);
+        emit_escrow_released(&env, &escrow);
+        Ok(())
+    }
+
+    /// Put held funds in dispute, e.g. the merchant never delivered. Signed
+    /// by the payer. Stops both `release` and `claim_timeout_refund` until
+    /// the admin calls `resolve_dispute`. Emits `dispute_opened`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::EscrowNotFound`]
+    /// - [`ContractError::EscrowNotHeld`] — already settled or in dispute
+    pub fn open_dispute(env: Env, invoice_id: String) -> Result<(), ContractError> {
+        let mut escrow = get_escrow(&env, &invoice_id)?;
+        escrow.payer.require_auth();
+        if escrow.status != EscrowStatus::Held {
+            return Err(ContractError::EscrowNotHeld);
+        }
+
+        escrow.status = EscrowStatus::Disputed;
+        set_escrow(&env, &escrow);
+        emit_dispute_opened(&env, invoice_id);
+        Ok(())
+    }
+
+    /// Settle a dispute: refund the payer if `refund_payer`, otherwise pay
+    /// the merchant. Admin only. Emits `escrow_refunded` or
+    /// `escrow_released`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::EscrowNotFound`]
+    /// - [`ContractError::NoOpenDispute`]
+    pub fn resolve_dispute(
+        env: Env,
+        invoice_id: String,
+        refund_payer: bool,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let mut escrow = get_escrow(&env, &invoice_id)?;
+        if escrow.status != EscrowStatus::Disputed {
+            return Err(ContractError::NoOpenDispute);
+        }
+
+        if refund_payer {
+            pay_out(&env, &mut escrow, EscrowStatus::Refunded);
+            emit_escrow_refunded(&env, &escrow, false);
+        } else {
+            pay_out(&env, &mut escrow, EscrowStatus::Released);
+            emit_escrow_released(&env, &escrow);
+        }
+        Ok(())
+    }
+
+    /// Return held funds to the payer once the deadline has passed and the
+    /// merchant never released them. Permissionless: anyone may call it, the
+    /// funds only ever go to the payer. Emits `escrow_refunded`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::EscrowNotFound`]
+    /// - [`ContractError::EscrowNotHeld`] — released, refunded or in dispute
+    /// - [`ContractError::DeadlineNotReached`]
+    pub fn claim_timeout_refund(env: Env, invoice_id: String) -> Result<(), ContractError> {
+        let mut escrow = get_escrow(&env, &invoice_id)?;
+        if escrow.status != EscrowStatus::Held {
+            return Err(ContractError::EscrowNotHeld);
+        }
+        if env.ledger().timestamp() < escrow.deadline {
+            return Err(ContractError::DeadlineNotReached);
+        }
+
+        pay_out(&env, &mut escrow, EscrowStatus::Refunded);
+        emit_escrow_refunded(&env, &escrow, true);
+        Ok(())
+    }
+
+    // Read
+
+    /// Returns [`ContractError::EscrowNotFound`] if there is no escrow for
+    /// `invoice_id`.
+    pub fn get_escrow(env: Env, invoice_id: String) -> Result<Escrow, ContractError> {
+        get_escrow(&env, &invoice_id)
+    }
+
+    /// Return the amount of `token` owed to escrows that are held or in
+    /// dispute.
+    pub fn held(env: Env, token: Address) -> i128 {
+        get_held(&env, &token)
+    }
+
+    /// Return the current admin address.
+    pub fn admin(env: Env) -> Result<Address, ContractError> {
+        get_admin(&env)
+    }
+
+    /// Transfer the balance of `token` beyond what open escrows are owed to
+    /// `to`, and return the amount moved. Admin only. Emits `swept`.
+    ///
+    /// Returns [`ContractError::NothingToSweep`] if there is no such
+    /// residue.
+    pub fn sweep(env: Env, token: Address, to: Address) -> Result<i128, ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let client = token::Client::new(&env, &token);
+        let residue = client.balance(&env.current_contract_address()) - get_held(&env, &token);
+        if residue <= 0 {
+            return Err(ContractError::NothingToSweep);
+        }
+        client.transfer(&env.current_contract_address(), &to, &residue);
+        emit_swept(&env, token, to, residue);
+        Ok(residue)
+    }
+
+    /// Transfer admin rights. Both the current and the new admin must sign.
+    pub fn set_admin(env: Env, new_admin: Address) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        new_admin.require_auth();
+        set_admin(&env, &new_admin);
+        Ok(())
+    }
+}
+
+// Helpers
+
+/// Transfer the held funds to the merchant (`Released`) or back to the payer
+/// (`Refunded`) and store the new status.
+fn pay_out(env: &Env, escrow: &mut Escrow, status: EscrowStatus) {
+    let to = match status {
+        EscrowStatus::Released => &escrow.merchant,
+        _ => &escrow.payer,
+    };
+    token::Client::new(env, &escrow.token).transfer(
+        &env.current_contract_address(),
+        to,
+        &escrow.amount,
+    );
+    add_held(env, &escrow.token, -escrow.amount);
+    escrow.status = status;
+    set_escrow(env, escrow);
+}
+
+mod test;
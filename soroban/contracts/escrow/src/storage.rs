@@ -0,0 +1,128 @@
+use soroban_sdk::{contracttype, Address, Env, String};
+
+use crate::errors::ContractError;
+
+// TTL budget — same policy as `invoice-payment`:
+//   MIN_TTL  = 17 280 ledgers ≈ 1 day
+//   BUMP_TTL = 518 400 ledgers ≈ 30 days
+
+pub const MIN_TTL: u32 = 17_280;
+pub const BUMP_TTL: u32 = 518_400;
+
+// Storage keys
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    /// Admin [`Address`] in **instance** storage.
+    Admin,
+    /// An [`Escrow`] indexed by `invoice_id` in **persistent** storage.
+    Escrow(String),
+    /// Total (`i128`) of a token owed to held or disputed escrows, in
+    /// **instance** storage. Absent = 0.
+    Held(Address),
+}
+
+// Data structures
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EscrowStatus {
+    /// Funds are held by the contract.
+    Held,
+    /// The payer opened a dispute; only the admin can settle it.
+    Disputed,
+    /// Funds went to the merchant.
+    Released,
+    /// Funds went back to the payer.
+    Refunded,
+}
+
+/// Funds held for one invoice.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Escrow {
+    pub invoice_id: String,
+    pub payer: Address,
+    /// Receives the funds on release.
+    pub merchant: Address,
+    /// Stellar asset (SAC) or other SEP-41 token contract.
+    pub token: Address,
+    /// Amount in the token's smallest unit (> 0).
+    pub amount: i128,
+    /// Unix timestamp from which the payer can reclaim funds that are
+    /// still held.
+    pub deadline: u64,
+    pub status: EscrowStatus,
+}
+
+// Admin helpers (instance storage)
+
+pub fn has_admin(env: &Env) -> bool {
+    env.storage().instance().has(&DataKey::Admin)
+}
+
+/// Returns [`ContractError::NotInitialized`] if `initialize()` was never called.
+pub fn get_admin(env: &Env) -> Result<Address, ContractError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(ContractError::NotInitialized)
+}
+
+pub fn set_admin(env: &Env, admin: &Address) {
+    env.storage().instance().set(&DataKey::Admin, admin);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return the amount of `token` owed to escrows that are not settled yet.
+pub fn get_held(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::Held(token.clone()))
+        .unwrap_or(0)
+}
+
+/// Add `delta` (negative when paying out) to the amount of `token` owed to
+/// escrows and extend instance TTL.
+pub fn add_held(env: &Env, token: &Address, delta: i128) {
+    let key = DataKey::Held(token.clone());
+    let held = get_held(env, token) + delta;
+    if held == 0 {
+        env.storage().instance().remove(&key);
+    } else {
+        env.storage().instance().set(&key, &held);
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+// Escrow helpers (persistent storage)
+
+pub fn has_escrow(env: &Env, invoice_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Escrow(invoice_id.clone()))
+}
+
+/// Returns [`ContractError::EscrowNotFound`] if there is no escrow for
+/// `invoice_id`.
+pub fn get_escrow(env: &Env, invoice_id: &String) -> Result<Escrow, ContractError> {
+    let key = DataKey::Escrow(invoice_id.clone());
+    let escrow: Escrow = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::EscrowNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(escrow)
+}
+
+pub fn set_escrow(env: &Env, escrow: &Escrow) {
+    let key = DataKey::Escrow(escrow.invoice_id.clone());
+    env.storage().persistent().set(&key, escrow);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
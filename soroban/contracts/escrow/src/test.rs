@@ -0,0 +1,190 @@
+#![cfg(test)]
+#![allow(clippy::all)]
+
+use super::*;
+use soroban_sdk::testutils::{Address as _, Ledger as _};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{Address, Env, String};
+
+const START: u64 = 1_700_000_000;
+const DEADLINE: u64 = START + 7 * 86_400;
+const AMOUNT: i128 = 10_000_000;
+
+// Helpers
+
+struct Setup<'a> {
+    client: EscrowContractClient<'a>,
+    token: TokenClient<'a>,
+    payer: Address,
+    merchant: Address,
+}
+
+fn setup(env: &Env) -> Setup<'_> {
+    env.mock_all_auths();
+    set_time(env, START);
+    let contract_id = env.register(EscrowContract, ());
+    let client = EscrowContractClient::new(env, &contract_id);
+    client.initialize(&Address::generate(env));
+
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(env));
+    let payer = Address::generate(env);
+    StellarAssetClient::new(env, &sac.address()).mint(&payer, &AMOUNT);
+
+    Setup {
+        client,
+        token: TokenClient::new(env, &sac.address()),
+        payer,
+        merchant: Address::generate(env),
+    }
+}
+
+fn set_time(env: &Env, timestamp: u64) {
+    env.ledger().with_mut(|li| li.timestamp = timestamp);
+}
+
+fn id(env: &Env) -> String {
+    String::from_str(env, "invoisio-esc-1")
+}
+
+fn deposit(env: &Env, s: &Setup) {
+    s.client.deposit(
+        &id(env),
+        &s.payer,
+        &s.merchant,
+        &s.token.address,
+        &AMOUNT,
+        &DEADLINE,
+    );
+}
+
+// deposit / release
+
+#[test]
+fn test_deposit_holds_funds_until_release() {
+    let env = Env::default();
+    let s = setup(&env);
+    deposit(&env, &s);
+
+    assert_eq!(s.token.balance(&s.payer), 0);
+    assert_eq!(s.token.balance(&s.client.address), AMOUNT);
+    assert_eq!(s.client.get_escrow(&id(&env)).status, EscrowStatus::Held);
+
+    s.client.release(&id(&env));
+    assert_eq!(s.token.balance(&s.merchant), AMOUNT);
+    assert_eq!(s.client.get_escrow(&id(&env)).status, EscrowStatus::Released);
+    assert_eq!(
+        s.client.try_claim_timeout_refund(&id(&env)),
+        Err(Ok(ContractError::EscrowNotHeld))
+    );
+}
+
+#[test]
+fn test_deposit_rejects_bad_input_and_duplicates() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    let deposit_with = |amount: i128, deadline: u64| {
+        s.client.try_deposit(
+            &id(&env),
+            &s.payer,
+            &s.merchant,
+            &s.token.address,
+            &amount,
+            &deadline,
+        )
+    };
+    assert_eq!(deposit_with(0, DEADLINE), Err(Ok(ContractError::InvalidAmount)));
+    assert_eq!(deposit_with(AMOUNT, START), Err(Ok(ContractError::InvalidDeadline)));
+
+    deposit(&env, &s);
+    assert_eq!(
+        deposit_with(AMOUNT, DEADLINE),
+        Err(Ok(ContractError::EscrowAlreadyExists))
+    );
+}
+
+// Timeout refund
+
+#[test]
+fn test_timeout_refund_returns_funds_after_deadline() {
+    let env = Env::default();
+    let s = setup(&env);
+    deposit(&env, &s);
+
+    set_time(&env, DEADLINE - 1);
+    assert_eq!(
+        s.client.try_claim_timeout_refund(&id(&env)),
+        Err(Ok(ContractError::DeadlineNotReached))
+    );
+
+    // Permissionless: no auth is needed.
+    set_time(&env, DEADLINE);
+    env.set_auths(&[]);
+    s.client.claim_timeout_refund(&id(&env));
+    assert_eq!(s.token.balance(&s.payer), AMOUNT);
+    assert_eq!(s.client.get_escrow(&id(&env)).status, EscrowStatus::Refunded);
+}
+
+// Disputes
+
+#[test]
+fn test_open_dispute_blocks_timeout_until_resolved() {
+    let env = Env::default();
+    let s = setup(&env);
+    deposit(&env, &s);
+    s.client.open_dispute(&id(&env));
+
+    set_time(&env, DEADLINE);
+    assert_eq!(
+        s.client.try_claim_timeout_refund(&id(&env)),
+        Err(Ok(ContractError::EscrowNotHeld))
+    );
+    assert_eq!(
+        s.client.try_release(&id(&env)),
+        Err(Ok(ContractError::EscrowNotHeld))
+    );
+
+    s.client.resolve_dispute(&id(&env), &true);
+    assert_eq!(s.token.balance(&s.payer), AMOUNT);
+    assert_eq!(
+        s.client.try_resolve_dispute(&id(&env), &false),
+        Err(Ok(ContractError::NoOpenDispute))
+    );
+}
+
+// Admin
+
+#[test]
+fn test_initialize_twice_fails() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    assert_eq!(
+        s.client.try_initialize(&Address::generate(&env)),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn test_sweep_moves_only_residue_not_owed_to_escrows() {
+    let env = Env::default();
+    let s = setup(&env);
+    deposit(&env, &s);
+    let treasury = Address::generate(&env);
+
+    assert_eq!(
+        s.client.try_sweep(&s.token.address, &treasury),
+        Err(Ok(ContractError::NothingToSweep))
+    );
+
+    // Someone sends tokens straight to the contract by mistake.
+    StellarAssetClient::new(&env, &s.token.address).mint(&s.client.address, &42);
+    assert_eq!(s.client.sweep(&s.token.address, &treasury), 42);
+    assert_eq!(s.token.balance(&treasury), 42);
+    assert_eq!(s.client.held(&s.token.address), AMOUNT);
+
+    // The escrow is still fully funded.
+    s.client.release(&id(&env));
+    assert_eq!(s.token.balance(&s.merchant), AMOUNT);
+    assert_eq!(s.client.held(&s.token.address), 0);
+}
@@ -0,0 +1,88 @@
+use soroban_sdk::{Env, Map};
+
+use crate::storage::{get_daily_totals, set_daily_totals, Asset, PeriodTotals};
+
+/// Seconds in a UTC day.
+pub const SECS_PER_DAY: u64 = 86_400;
+
+/// Day number (days since the Unix epoch, UTC) of ledger time `timestamp`.
+pub fn day_of(timestamp: u64) -> u32 {
+    (timestamp / SECS_PER_DAY) as u32
+}
+
+/// Calendar `(year, month)` of day number `day`, month 1–12.
+///
+/// Howard Hinnant's `civil_from_days`, restricted to days on or after the
+/// epoch so it needs no signed arithmetic.
+pub fn year_month_of(day: u32) -> (u32, u32) {
+    // Shift the epoch to 0000-03-01 so leap days fall at the end of a year.
+    let z = day + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u32::from(month <= 2);
+    (year, month)
+}
+
+/// Day number of the first day of `month` (1–12) of `year`, or `None` if
+/// it is before the epoch or past the last `u32` day.
+///
+/// The inverse of [`year_month_of`]: Hinnant's `days_from_civil`.
+pub fn first_day_of(year: u32, month: u32) -> Option<u32> {
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+    let year = u64::from(year) - u64::from(month <= 2);
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * u64::from((month + 9) % 12) + 2) / 5;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    (era * 146_097 + day_of_era)
+        .checked_sub(719_468)
+        .and_then(|day| u32::try_from(day).ok())
+}
+
+/// Add a payment of `amount` in `asset`, `tax` of it tax, recorded at
+/// `timestamp` to the daily totals it falls in.
+pub fn add_payment(env: &Env, asset: &Asset, amount: i128, tax: i128, timestamp: u64) {
+    let day = day_of(timestamp);
+    let mut daily = get_daily_totals(env, day);
+    let mut totals = daily.get(asset.clone()).unwrap_or_default();
+    totals.count += 1;
+    totals.amount = totals.amount.saturating_add(amount);
+    totals.tax = totals.tax.saturating_add(tax);
+    daily.set(asset.clone(), totals);
+    set_daily_totals(env, day, &daily);
+}
+
+/// Sum the daily totals of `month` (1–12) of `year`. Empty for a month
+/// outside the range [`first_day_of`] covers.
+///
+/// Months are summed when read rather than kept beside the days, so
+/// recording a payment writes one totals entry instead of two.
+pub fn monthly_totals(env: &Env, year: u32, month: u32) -> Map<Asset, PeriodTotals> {
+    let mut monthly = Map::new(env);
+    let Some(first) = first_day_of(year, month) else {
+        return monthly;
+    };
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    let end = first_day_of(next_year, next_month).unwrap_or(u32::MAX);
+    for day in first..end {
+        for (asset, day_totals) in get_daily_totals(env, day).iter() {
+            let mut totals = monthly.get(asset.clone()).unwrap_or_default();
+            totals.count += day_totals.count;
+            totals.amount = totals.amount.saturating_add(day_totals.amount);
+            totals.tax = totals.tax.saturating_add(day_totals.tax);
+            monthly.set(asset, totals);
+        }
+    }
+    monthly
+}
@@ -0,0 +1,53 @@
+use soroban_sdk::{Env, Map, Vec};
+
+use crate::storage::{get_count, get_payment, get_payment_seq, get_refunded_total, InvariantReport};
+
+/// Check the payments with sequence numbers `from..from + limit` (clipped to
+/// the counter) against the contract's storage invariants.
+///
+/// Read-only apart from the TTL bumps every read performs. Meant to be
+/// simulated, so auditors can run it at any ledger in slices that fit the
+/// read budget.
+pub fn check_invariants(env: &Env, from: u32, limit: u32) -> InvariantReport {
+    let count = get_count(env);
+    let to = from.saturating_add(limit).min(count);
+    let mut report = InvariantReport {
+        from,
+        to: to.max(from),
+        count,
+        indexed: 0,
+        counter_matches_index: get_payment_seq(env, count).is_none(),
+        totals: Map::new(env),
+        dangling: Vec::new(env),
+        invalid_amounts: Vec::new(env),
+        over_refunded: Vec::new(env),
+        ok: false,
+    };
+
+    for seq in from..to {
+        let Some(invoice_id) = get_payment_seq(env, seq) else {
+            continue;
+        };
+        report.indexed += 1;
+        let Ok(record) = get_payment(env, &invoice_id) else {
+            report.dangling.push_back(seq);
+            continue;
+        };
+        if record.amount <= 0 {
+            report.invalid_amounts.push_back(invoice_id.clone());
+        }
+        if get_refunded_total(env, &invoice_id) > record.amount {
+            report.over_refunded.push_back(invoice_id);
+        }
+        let total = report.totals.get(record.asset.clone()).unwrap_or(0);
+        report
+            .totals
+            .set(record.asset, total.saturating_add(record.amount));
+    }
+
+    report.ok = report.counter_matches_index
+        && report.dangling.is_empty()
+        && report.invalid_amounts.is_empty()
+        && report.over_refunded.is_empty();
+    report
+}
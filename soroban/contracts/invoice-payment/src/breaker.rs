@@ -0,0 +1,46 @@
+use soroban_sdk::Env;
+
+use crate::errors::ContractError;
+use crate::events::emit_circuit_tripped;
+use crate::storage::{
+    get_breaker_window, get_circuit_breaker, is_paused, set_breaker_window, set_paused,
+    BreakerWindow,
+};
+
+/// Returns [`ContractError::Paused`] while writes are paused.
+pub fn require_not_paused(env: &Env) -> Result<(), ContractError> {
+    if is_paused(env) {
+        return Err(ContractError::Paused);
+    }
+    Ok(())
+}
+
+/// Count one recorded payment of `amount` against the current window and
+/// trip the breaker if a threshold is now exceeded.
+///
+/// The payment that crosses a threshold is still recorded: failing it would
+/// roll the pause back with it. Every write after it fails until the admin
+/// calls `resume`.
+pub fn track_payment(env: &Env, amount: i128) {
+    let Some(breaker) = get_circuit_breaker(env) else {
+        return;
+    };
+
+    let ledger = env.ledger().sequence();
+    let mut window = get_breaker_window(env);
+    if window.count == 0 || ledger >= window.start_ledger.saturating_add(breaker.window_ledgers) {
+        window = BreakerWindow {
+            start_ledger: ledger,
+            count: 0,
+            volume: 0,
+        };
+    }
+    window.count += 1;
+    window.volume = window.volume.saturating_add(amount);
+    set_breaker_window(env, &window);
+
+    if window.count > breaker.max_count || window.volume > breaker.max_volume {
+        set_paused(env, true);
+        emit_circuit_tripped(env, window);
+    }
+}
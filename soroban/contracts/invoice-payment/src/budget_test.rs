@@ -0,0 +1,389 @@
+#![cfg(test)]
+
+//! Resource budget regression tests.
+//!
+//! Each test runs one entry point and checks what the host metered for it
+//! against a ceiling in [`BUDGETS`]. Run `make bench` to print the measured
+//! numbers.
+//!
+//! The contract runs natively here, so `instructions` and `mem_bytes` only
+//! cover host work and understate the WASM cost; ledger footprint, write
+//! bytes and event size are exact. Entry counts are therefore pinned to the
+//! measured counts, so every new read or write on a path is a deliberate
+//! edit here. Instruction, memory and byte ceilings are the measured numbers
+//! rounded up with some headroom, and sit well under the network
+//! per-transaction limits, so a change that grows an operation noticeably
+//! fails here before it gets near them on-chain.
+
+extern crate std;
+
+use soroban_sdk::testutils::{Address as _, EnvTestConfig, Ledger as _};
+use soroban_sdk::{Address, Env, String, Vec};
+
+use super::*;
+use crate::testutils::{record_xlm, setup, ONE_XLM};
+
+/// Upper bounds for one invocation.
+#[derive(Clone, Copy, Debug)]
+struct Budget {
+    name: &'static str,
+    instructions: i64,
+    mem_bytes: i64,
+    read_entries: u32,
+    write_entries: u32,
+    write_bytes: u32,
+    events_bytes: u32,
+}
+
+const RECORD_PAYMENT: Budget = Budget {
+    name: "record_payment",
+    instructions: 500_000,
+    mem_bytes: 75_000,
+    // Three for the admin's auth (its nonce, and natively its account
+    // contract). Then the instance, the record, its compacted summary and
+    // the invoice's external and cross-chain records (duplicate check), the
+    // payer's blocklist entry, the invoice's preauthorization, accepted
+    // assets and tax, the compaction index entry, the record's keeper
+    // freshness marker, and the day's totals. Monthly totals are summed
+    // from the days when read, so they cost nothing here.
+    read_entries: 15,
+    // The nonce, the instance, the record, the index entry, the freshness
+    // marker and the totals.
+    write_entries: 6,
+    write_bytes: 2_048,
+    events_bytes: 512,
+};
+
+const REPORT_PENDING: Budget = Budget {
+    name: "report_pending_payment",
+    instructions: 250_000,
+    mem_bytes: 40_000,
+    read_entries: 12,
+    write_entries: 3,
+    write_bytes: 1_024,
+    events_bytes: 512,
+};
+
+const CONFIRM_PENDING: Budget = Budget {
+    name: "confirm_pending_payment",
+    instructions: 500_000,
+    mem_bytes: 75_000,
+    // What `record_payment` reads, plus the pending record it consumes.
+    read_entries: 16,
+    write_entries: 7,
+    write_bytes: 2_048,
+    events_bytes: 512,
+};
+
+const GET_PAYMENT: Budget = Budget {
+    name: "get_payment",
+    instructions: 75_000,
+    mem_bytes: 10_000,
+    // The instance and the record. Extending the record's TTL is paid as
+    // rent, not as a write.
+    read_entries: 2,
+    write_entries: 0,
+    write_bytes: 0,
+    events_bytes: 0,
+};
+
+const PAYMENT_COUNT: Budget = Budget {
+    name: "payment_count",
+    instructions: 50_000,
+    mem_bytes: 10_000,
+    read_entries: 1,
+    write_entries: 0,
+    write_bytes: 0,
+    events_bytes: 0,
+};
+
+/// Records per batch in the batch budgets below.
+const BATCH: u32 = 10;
+
+const IMPORT_PAYMENTS: Budget = Budget {
+    name: "import_payments",
+    instructions: 4_000_000,
+    mem_bytes: 700_000,
+    // Three for the admin's auth and the instance, then per record its
+    // entry, compacted summary, external and cross-chain records (duplicate
+    // check), its index slot and freshness marker, and the day's totals
+    // once.
+    read_entries: 65,
+    // The nonce, the instance, each record with its index entry and
+    // freshness marker, and the totals.
+    write_entries: 33,
+    write_bytes: 8_192,
+    events_bytes: 5_120,
+};
+
+const FIND_PAYMENTS: Budget = Budget {
+    name: "find_payments",
+    instructions: 500_000,
+    mem_bytes: 50_000,
+    // The instance and one entry per id.
+    read_entries: 11,
+    write_entries: 0,
+    write_bytes: 0,
+    events_bytes: 0,
+};
+
+const BUDGETS: &[Budget] = &[
+    RECORD_PAYMENT,
+    REPORT_PENDING,
+    CONFIRM_PENDING,
+    GET_PAYMENT,
+    PAYMENT_COUNT,
+    IMPORT_PAYMENTS,
+    FIND_PAYMENTS,
+];
+
+/// Check the resources of the last invocation against `budget`, and print
+/// them when run by `make bench` (which sets `INVOISIO_BENCH`).
+fn assert_within(env: &Env, budget: &Budget) {
+    let r = env.cost_estimate().resources();
+    let read_entries = r.disk_read_entries + r.memory_read_entries;
+    if std::env::var_os("INVOISIO_BENCH").is_some() {
+        std::println!(
+            "{:<24} instructions={:>9} mem_bytes={:>8} reads={} writes={} write_bytes={} events_bytes={}",
+            budget.name,
+            r.instructions,
+            r.mem_bytes,
+            read_entries,
+            r.write_entries,
+            r.write_bytes,
+            r.contract_events_size_bytes,
+        );
+    }
+
+    assert!(
+        r.instructions <= budget.instructions,
+        "{}: {} instructions > {}",
+        budget.name,
+        r.instructions,
+        budget.instructions
+    );
+    assert!(
+        r.mem_bytes <= budget.mem_bytes,
+        "{}: {} mem bytes > {}",
+        budget.name,
+        r.mem_bytes,
+        budget.mem_bytes
+    );
+    assert!(
+        read_entries <= budget.read_entries,
+        "{}: {} read entries > {}",
+        budget.name,
+        read_entries,
+        budget.read_entries
+    );
+    assert!(
+        r.write_entries <= budget.write_entries,
+        "{}: {} write entries > {}",
+        budget.name,
+        r.write_entries,
+        budget.write_entries
+    );
+    assert!(
+        r.write_bytes <= budget.write_bytes,
+        "{}: {} write bytes > {}",
+        budget.name,
+        r.write_bytes,
+        budget.write_bytes
+    );
+    assert!(
+        r.contract_events_size_bytes <= budget.events_bytes,
+        "{}: {} event bytes > {}",
+        budget.name,
+        r.contract_events_size_bytes,
+        budget.events_bytes
+    );
+}
+
+/// An env that writes no test snapshot: the resources are what is checked
+/// here, not the ledger the tests leave behind.
+fn bench_env() -> Env {
+    let env = Env::new_with_config(EnvTestConfig {
+        capture_snapshot_at_drop: false,
+    });
+    env.mock_all_auths();
+    env
+}
+
+/// A 64-character invoice id: longer than any Invoisio id, so the ceilings
+/// hold for real traffic.
+fn long_id(env: &Env, n: u32) -> String {
+    let id = std::format!("invoisio-{n:0>55}");
+    String::from_str(env, &id)
+}
+
+#[test]
+fn budget_record_payment() {
+    let env = bench_env();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+
+    client.record_payment(
+        &long_id(&env, 1),
+        &payer,
+        &String::from_str(&env, "USDC"),
+        &String::from_str(&env, testutils::USDC_TESTNET_ISSUER),
+        &i128::MAX,
+    );
+    assert_within(&env, &RECORD_PAYMENT);
+}
+
+#[test]
+fn budget_record_payment_is_flat_as_history_grows() {
+    let env = bench_env();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+
+    client.record_payment(
+        &long_id(&env, 0),
+        &payer,
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &ONE_XLM,
+    );
+    let first = env.cost_estimate().resources();
+
+    // Batch of records; none of them may make the next one more expensive.
+    for n in 1..100 {
+        record_xlm(&env, &client, &std::format!("invoisio-batch-{n}"), &payer, ONE_XLM);
+    }
+    client.record_payment(
+        &long_id(&env, 100),
+        &payer,
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &ONE_XLM,
+    );
+    let last = env.cost_estimate().resources();
+
+    // Only the footprint is compared. Natively, every storage access costs
+    // more instructions as the test ledger grows, which says nothing about
+    // the contract: on-chain, an invocation only sees its own footprint.
+    assert_eq!(
+        last.disk_read_entries + last.memory_read_entries,
+        first.disk_read_entries + first.memory_read_entries
+    );
+    assert_eq!(last.write_entries, first.write_entries);
+    assert_eq!(last.write_bytes, first.write_bytes);
+}
+
+#[test]
+fn budget_interned_usdc_writes_less() {
+    let env = bench_env();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let issuer = String::from_str(&env, testutils::USDC_TESTNET_ISSUER);
+    client.set_usdc_issuer(&issuer, &client.admin_nonce());
+    let record = |n: u32, issuer: &str| {
+        client.record_payment(
+            &long_id(&env, n),
+            &payer,
+            &String::from_str(&env, testutils::USDC),
+            &String::from_str(&env, issuer),
+            &ONE_XLM,
+        );
+        env.cost_estimate().resources().write_bytes
+    };
+
+    // Same instance entry in both; only the payment entry differs.
+    let interned = record(10, testutils::USDC_TESTNET_ISSUER);
+    let full = record(11, "GAP5LETOV6YIE62YAM56STDANPRDO7ZFDBGSNHJQIYGGKSMOZAHOOS2S");
+    assert!(interned + 56 <= full, "{interned} bytes interned vs {full} in full");
+}
+
+#[test]
+fn budget_pending_report_and_confirm() {
+    let env = bench_env();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = long_id(&env, 2);
+
+    client.report_pending_payment(
+        &id,
+        &payer,
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &ONE_XLM,
+    );
+    assert_within(&env, &REPORT_PENDING);
+
+    client.confirm_pending_payment(&id);
+    assert_within(&env, &CONFIRM_PENDING);
+}
+
+#[test]
+fn budget_reads() {
+    let env = bench_env();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = long_id(&env, 3);
+    client.record_payment(
+        &id,
+        &payer,
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &ONE_XLM,
+    );
+
+    // Let the record's TTL drop below the threshold so the read pays for
+    // the extension too — the worst case. The instance is kept alive.
+    env.as_contract(&client.address, || {
+        env.storage()
+            .instance()
+            .extend_ttl(storage::BUMP_TTL * 2, storage::BUMP_TTL * 2)
+    });
+    env.ledger()
+        .with_mut(|li| li.sequence_number += storage::BUMP_TTL - storage::MIN_TTL + 1);
+    client.get_payment(&id);
+    assert_within(&env, &GET_PAYMENT);
+
+    client.payment_count();
+    assert_within(&env, &PAYMENT_COUNT);
+}
+
+#[test]
+fn budget_batches() {
+    let env = bench_env();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let mut records = Vec::new(&env);
+    let mut ids = Vec::new(&env);
+    for n in 0..BATCH {
+        let id = long_id(&env, 1_000 + n);
+        records.push_back(PaymentRecord {
+            invoice_id: id.clone(),
+            payer: payer.clone(),
+            payer_mux_id: None,
+            asset: Asset::Native,
+            amount: ONE_XLM,
+            timestamp: 1_600_000_000,
+        });
+        ids.push_back(id);
+    }
+
+    client.import_payments(&records);
+    assert_within(&env, &IMPORT_PAYMENTS);
+
+    client.find_payments(&ids);
+    assert_within(&env, &FIND_PAYMENTS);
+}
+
+#[test]
+fn budget_ceilings_stay_under_network_limits() {
+    // Per-transaction network limits (protocol 23).
+    const TX_INSTRUCTIONS: i64 = 100_000_000;
+    const TX_MEM_BYTES: i64 = 40 * 1024 * 1024;
+    const TX_WRITE_ENTRIES: u32 = 50;
+    const TX_WRITE_BYTES: u32 = 132 * 1024;
+
+    for b in BUDGETS {
+        assert!(b.instructions * 10 <= TX_INSTRUCTIONS, "{}", b.name);
+        assert!(b.mem_bytes * 10 <= TX_MEM_BYTES, "{}", b.name);
+        assert!(b.write_entries <= TX_WRITE_ENTRIES, "{}", b.name);
+        assert!(b.write_bytes * 10 <= TX_WRITE_BYTES, "{}", b.name);
+    }
+}
@@ -30,11 +30,188 @@ pub enum ContractError {
     /// `amount` was zero or negative. All payments must be strictly positive.
     InvalidAmount = 5,
 
-    /// `invoice_id` was an empty string. Every payment must reference a
-    /// non-empty invoice identifier.
+    /// `invoice_id` was an empty string or longer than
+    /// [`MAX_INVOICE_ID_LEN`](crate::storage::MAX_INVOICE_ID_LEN) bytes.
+    /// Every payment must reference a non-empty invoice identifier.
     InvalidInvoiceId = 6,
 
-    /// `asset_code` was empty, or a non-XLM asset was supplied without an
-    /// `asset_issuer`. Every payment must identify the asset unambiguously.
+    /// `asset_code` was not 1–12 ASCII letters and digits, or a non-XLM
+    /// asset was supplied without an `asset_issuer` or with one that is not
+    /// a valid `G...` strkey. Every payment must identify the asset
+    /// unambiguously.
     InvalidAsset = 7,
+
+    /// `report_pending_payment()` was called for an `invoice_id` that already
+    /// has an unexpired pending record.
+    PaymentAlreadyPending = 8,
+
+    /// No pending record exists for the `invoice_id` — it was never reported,
+    /// was already confirmed or discarded, or its temporary entry expired.
+    PendingPaymentNotFound = 9,
+
+    /// `record_merchant_payment()` was called but no merchant registry is
+    /// configured (see `set_merchant_registry()`).
+    MerchantRegistryNotSet = 10,
+
+    /// The merchant registry has no merchant with this `merchant_id`.
+    UnknownMerchant = 11,
+
+    /// The merchant is registered but deactivated.
+    MerchantInactive = 12,
+
+    /// The compliance policy requires KYC and the payer is not flagged
+    /// `kyc_verified`.
+    PayerNotVerified = 13,
+
+    /// The compliance policy rejects high-risk payers and the payer is
+    /// flagged `high_risk`.
+    PayerHighRisk = 14,
+
+    /// The payer is on the blocklist (`block_payer()`), e.g. after a
+    /// sanctions screening hit.
+    PayerBlocked = 15,
+
+    /// The payment would take the payer's volume of this asset over the
+    /// configured rolling 24 h limit.
+    LimitExceeded = 16,
+
+    /// Writes are paused, by the admin or by the circuit breaker tripping.
+    Paused = 17,
+
+    /// `set_usdc_issuer()` was called after the USDC issuer was already set.
+    /// Stored records refer to it, so it can never change.
+    UsdcIssuerAlreadySet = 18,
+
+    /// The metadata blob passed to `set_metadata()` is longer than
+    /// `MAX_METADATA_LEN` bytes.
+    MetadataTooLarge = 19,
+
+    /// The payment was recorded but its full record has been replaced by a
+    /// [`CompactedPayment`](crate::storage::CompactedPayment) summary by
+    /// `compact_old_records()`. Read it with `compacted_payment()`.
+    PaymentCompacted = 20,
+
+    /// Terms acceptance is required (see `set_terms_required()`) and the
+    /// payer has not called `accept_terms()` for this `invoice_id`.
+    TermsNotAccepted = 21,
+
+    /// No quote exists with this `quote_id`.
+    QuoteNotFound = 22,
+
+    /// `accept_quote()` was called at or after the quote's `expires_at`, or
+    /// `issue_quote()` was given an `expires_at` that has already passed.
+    QuoteExpired = 23,
+
+    /// The quote was already accepted; it can be neither accepted again nor
+    /// re-issued.
+    QuoteAlreadyAccepted = 24,
+
+    /// The refund would take the invoice's refunded total over the amount
+    /// originally recorded.
+    RefundExceedsPayment = 25,
+
+    /// A payment schedule (e.g. a deposit invoice) already exists for this
+    /// `invoice_id`.
+    InvoiceScheduleExists = 26,
+
+    /// No payment schedule exists for this `invoice_id`.
+    InvoiceScheduleNotFound = 27,
+
+    /// The payment would take the invoice past its total, or the invoice is
+    /// already fully paid.
+    InvoiceOverpaid = 28,
+
+    /// The installment schedule is empty, longer than `MAX_INSTALLMENTS`, or
+    /// its due dates are not strictly increasing; or a payout schedule has
+    /// `every_days` of 0.
+    InvalidSchedule = 29,
+
+    /// The invoice expired unpaid and no longer accepts payments.
+    InvoiceExpired = 30,
+
+    /// `expire_invoice` was called on an invoice without a deadline, before
+    /// its deadline, or after its deposit was paid.
+    InvoiceNotExpirable = 31,
+
+    /// A timestamp argument lies in the future, e.g. the `paid_at` of a
+    /// backdated payment.
+    InvalidTimestamp = 32,
+
+    /// `add_observer` was called with `MAX_OBSERVERS` already registered.
+    TooManyObservers = 33,
+
+    /// The registered validator contract rejected the payment, or failed.
+    PaymentRejected = 34,
+
+    /// The payment does not match the payer's preauthorization of the
+    /// invoice, or needs one and there is none.
+    NotPreauthorized = 35,
+
+    /// `record_payment_signed` was called with no signing key configured.
+    SigningKeyNotSet = 36,
+
+    /// The `nonce` passed to an admin config change or transfer is not the
+    /// admin's current [`admin_nonce`](crate::InvoicePaymentContract::admin_nonce).
+    InvalidNonce = 37,
+
+    /// `collect()` was called for an invoice whose funds were already
+    /// collected.
+    AlreadyCollected = 38,
+
+    /// The merchant's unsettled balance already covers
+    /// `MAX_BATCH_INVOICES` invoices; `settle()` it first.
+    BatchFull = 39,
+
+    /// `settle()` was called for a merchant with no collected funds.
+    NothingToSettle = 40,
+
+    /// No settlement exists with the id.
+    SettlementNotFound = 41,
+
+    /// The merchant's payout schedule does not release funds today, or
+    /// already did.
+    OutsidePayoutWindow = 42,
+
+    /// No credit note exists with the id.
+    CreditNoteNotFound = 43,
+
+    /// A credit note with the id was already issued.
+    CreditNoteExists = 44,
+
+    /// The credit note was fully applied or voided.
+    CreditNoteClosed = 45,
+
+    /// The amount exceeds the credit left on the note.
+    CreditExceedsRemaining = 46,
+
+    /// The invoice's terms were accepted, or it was preauthorized, by another
+    /// payer than the one acknowledging, preauthorizing or applying a credit
+    /// note to it, or accepting its terms.
+    PayerMismatch = 47,
+
+    /// An invoice tax has a rate over 100 %, a negative amount or one above
+    /// the invoice total, or a malformed jurisdiction code.
+    InvalidTax = 48,
+
+    /// The payment is not the amount the invoice expects in its asset, or
+    /// `collect()` was given another amount than the payment recorded.
+    AmountMismatch = 49,
+
+    /// A chargeback was already flagged on the invoice.
+    ChargebackExists = 50,
+
+    /// No chargeback was flagged on the invoice.
+    ChargebackNotFound = 51,
+
+    /// The chargeback was already resolved.
+    ChargebackResolved = 52,
+
+    /// `collect()` was given another token than the Stellar Asset Contract
+    /// of the asset the payment was recorded in.
+    TokenMismatch = 53,
+
+    /// A SEP-31 remittance has an empty anchor, or a `transaction_id` that
+    /// is empty or longer than
+    /// [`MAX_INVOICE_ID_LEN`](crate::storage::MAX_INVOICE_ID_LEN) bytes.
+    InvalidRemittance = 54,
 }
@@ -1,11 +1,366 @@
-use soroban_sdk::{contractevent, Env};
-use crate::storage::PaymentRecord;
+use soroban_sdk::{contractevent, Address, BytesN, Env, Map, String};
+use crate::storage::{
+    Asset, BreakerWindow, ChainPaymentRecord, ChargebackStatus, Conversion, CreditNote,
+    DepositInvoice, ExternalPaymentRecord, FxRate, HashedPaymentRecord, InstallmentPlan,
+    InvoiceTax, PayerFlags, PaymentRecord, PayoutSchedule, Preauthorization, Quote, Reconciliation,
+    Remittance, Settlement, TaxSplit,
+};
 
 #[contractevent]
 pub struct PaymentRecorded {
     pub record: PaymentRecord,
 }
 
+/// Follows `payment_recorded` for a payment recorded after the fact: the
+/// record's `timestamp` is `recorded_at`, the customer paid at `paid_at`.
+#[contractevent]
+pub struct PaymentBackdated {
+    pub invoice_id: String,
+    pub paid_at: u64,
+    pub recorded_at: u64,
+    /// `paid_at` is earlier than `recorded_at`.
+    pub backdated: bool,
+}
+
+/// A historical payment copied in by `import_payments()`, with its original
+/// payer and timestamp.
+#[contractevent]
+pub struct PaymentImported {
+    pub record: PaymentRecord,
+}
+
+/// The invoice hash is a topic, so indexers can filter for one invoice.
+#[contractevent]
+pub struct HashedPaymentRecorded {
+    #[topic]
+    pub invoice_hash: BytesN<32>,
+    pub payer: Address,
+    pub asset: Asset,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+#[contractevent]
+pub struct ExternalPaymentRecorded {
+    pub record: ExternalPaymentRecord,
+}
+
+#[contractevent]
+pub struct ChainPaymentRecorded {
+    #[topic]
+    pub chain_id: String,
+    pub record: ChainPaymentRecord,
+}
+
+#[contractevent]
+pub struct RemittanceRecorded {
+    #[topic]
+    pub invoice_id: String,
+    pub remittance: Remittance,
+}
+
+/// Carries only the invoice id; the blob itself is read with `get_metadata`.
+#[contractevent]
+pub struct MetadataUpdated {
+    pub invoice_id: String,
+}
+
+#[contractevent]
+pub struct RecordsCompacted {
+    pub compacted: u32,
+    /// Sequence number the next `compact_old_records` call starts from.
+    pub next_seq: u32,
+}
+
+#[contractevent]
+pub struct PendingPaymentReported {
+    pub record: PaymentRecord,
+}
+
+#[contractevent]
+pub struct PayerFlagsUpdated {
+    pub payer: Address,
+    pub flags: PayerFlags,
+}
+
+#[contractevent]
+pub struct CircuitTripped {
+    pub window: BreakerWindow,
+}
+
+#[contractevent]
+pub struct Paused {
+    pub admin: Address,
+}
+
+#[contractevent]
+pub struct Resumed {
+    pub admin: Address,
+}
+
+#[contractevent]
+pub struct DepositInvoiceIssued {
+    pub invoice_id: String,
+    pub invoice: DepositInvoice,
+}
+
+/// The payments applied to a deposit invoice reached its deposit.
+#[contractevent]
+pub struct DepositPaid {
+    pub invoice_id: String,
+    /// Payment that completed the deposit.
+    pub payment_id: String,
+    pub paid: i128,
+    pub balance_due: i128,
+}
+
+#[contractevent]
+pub struct InstallmentPlanIssued {
+    pub invoice_id: String,
+    pub plan: InstallmentPlan,
+}
+
+/// A payment completed installment `index` of an installment plan.
+#[contractevent]
+pub struct InstallmentPaid {
+    pub invoice_id: String,
+    pub payment_id: String,
+    pub index: u32,
+}
+
+/// A keeper was paid the bounty for a maintenance call.
+#[contractevent]
+pub struct KeeperRewarded {
+    #[topic]
+    pub keeper: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+/// `funder` added `amount` of `token` to the keeper pool.
+#[contractevent]
+pub struct KeeperPoolFunded {
+    #[topic]
+    pub funder: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+/// A registered observer failed to handle `on_payment_recorded` for the
+/// payment of `invoice_id`; the payment was recorded regardless.
+#[contractevent]
+pub struct ObserverFailed {
+    #[topic]
+    pub observer: Address,
+    pub invoice_id: String,
+}
+
+/// An unpaid deposit invoice passed its deadline and was closed.
+#[contractevent]
+pub struct InvoiceExpired {
+    pub invoice_id: String,
+    /// Paid towards the deposit before it expired, if anything.
+    pub paid: i128,
+}
+
+/// A deposit invoice or installment plan is paid in full.
+#[contractevent]
+pub struct BalancePaid {
+    pub invoice_id: String,
+    /// Payment that settled the balance.
+    pub payment_id: String,
+    pub paid: i128,
+}
+
+#[contractevent]
+pub struct PaymentRefunded {
+    pub invoice_id: String,
+    /// Amount of this refund, in the payment's asset.
+    pub amount: i128,
+    /// All refunds against the invoice so far, this one included.
+    pub refunded_total: i128,
+}
+
+#[contractevent]
+pub struct RedirectedRefund {
+    pub invoice_id: String,
+    /// Original payer, who co-authorised the redirect.
+    pub payer: Address,
+    /// Address the refund was sent to instead.
+    pub recipient: Address,
+    pub amount: i128,
+    pub refunded_total: i128,
+}
+
+#[contractevent]
+pub struct QuoteIssued {
+    pub quote_id: String,
+    pub quote: Quote,
+}
+
+#[contractevent]
+pub struct QuoteAccepted {
+    pub quote_id: String,
+    pub invoice_id: String,
+    pub payer: Address,
+}
+
+#[contractevent]
+pub struct InvoiceAcknowledged {
+    pub invoice_id: String,
+    pub payer: Address,
+}
+
+#[contractevent]
+pub struct TermsAccepted {
+    pub invoice_id: String,
+    pub payer: Address,
+    pub terms_hash: BytesN<32>,
+}
+
+#[contractevent]
+pub struct InvoicePreauthorized {
+    pub invoice_id: String,
+    pub payer: Address,
+    pub asset: Asset,
+    pub max_amount: i128,
+}
+
+#[contractevent]
+pub struct PayerBlocked {
+    pub payer: Address,
+}
+
+#[contractevent]
+pub struct PayerUnblocked {
+    pub payer: Address,
+}
+
+#[contractevent]
+pub struct FxRateCaptured {
+    pub invoice_id: String,
+    pub rate: FxRate,
+}
+
+#[contractevent]
+pub struct MerchantPaymentRecorded {
+    pub merchant_id: String,
+    pub invoice_id: String,
+    pub receipt_number: u64,
+}
+
+#[contractevent]
+pub struct PaymentCollected {
+    #[topic]
+    pub merchant_id: String,
+    pub invoice_id: String,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct MerchantSettled {
+    pub settlement: Settlement,
+}
+
+#[contractevent]
+pub struct PayoutScheduleUpdated {
+    #[topic]
+    pub merchant_id: String,
+    pub schedule: Option<PayoutSchedule>,
+}
+
+/// The next day (days since the Unix epoch) a merchant's schedule releases
+/// its balance.
+#[contractevent]
+pub struct PayoutScheduled {
+    #[topic]
+    pub merchant_id: String,
+    pub day: u32,
+}
+
+#[contractevent]
+pub struct CreditNoteIssued {
+    #[topic]
+    pub credit_id: String,
+    pub note: CreditNote,
+}
+
+/// Credit applied to an invoice, and what is left on the note.
+#[contractevent]
+pub struct CreditNoteApplied {
+    #[topic]
+    pub credit_id: String,
+    pub invoice_id: String,
+    pub amount: i128,
+    pub remaining: i128,
+}
+
+/// The unapplied credit that lapsed when the note was voided.
+#[contractevent]
+pub struct CreditNoteVoided {
+    #[topic]
+    pub credit_id: String,
+    pub lapsed: i128,
+}
+
+#[contractevent]
+pub struct InvoiceTaxUpdated {
+    #[topic]
+    pub invoice_id: String,
+    pub tax: Option<InvoiceTax>,
+}
+
+/// Net and tax of a recorded payment whose invoice has an [`InvoiceTax`].
+#[contractevent]
+pub struct PaymentTaxed {
+    #[topic]
+    pub invoice_id: String,
+    pub split: TaxSplit,
+}
+
+/// The assets an invoice accepts and the amount expected in each; empty
+/// once the restriction is removed.
+#[contractevent]
+pub struct AcceptedAssetsUpdated {
+    #[topic]
+    pub invoice_id: String,
+    pub assets: Map<Asset, i128>,
+}
+
+/// Both legs and the effective rate of a payment made in another asset than
+/// invoiced; follows its `payment_recorded`.
+#[contractevent]
+pub struct PaymentConverted {
+    #[topic]
+    pub invoice_id: String,
+    pub conversion: Conversion,
+}
+
+#[contractevent]
+pub struct ChargebackFlagged {
+    #[topic]
+    pub invoice_id: String,
+    pub reference: String,
+}
+
+/// Outcome of a flagged chargeback: `Upheld` or `Overturned`.
+#[contractevent]
+pub struct ChargebackResolved {
+    #[topic]
+    pub invoice_id: String,
+    pub status: ChargebackStatus,
+}
+
+/// A recorded payment moved between [`Reconciliation`] states.
+#[contractevent]
+pub struct ReconciliationUpdated {
+    #[topic]
+    pub invoice_id: String,
+    pub from: Reconciliation,
+    pub to: Reconciliation,
+}
+
 /// Emit a `("payment", "recorded")` Soroban event carrying the full
 /// [`PaymentRecord`] as event data.
 ///
@@ -37,3 +392,413 @@ pub struct PaymentRecorded {
 pub fn emit_payment_recorded(env: &Env, record: PaymentRecord) {
     PaymentRecorded { record }.publish(env);
 }
+
+/// Emit a `payment_backdated` event.
+pub fn emit_payment_backdated(env: &Env, invoice_id: String, paid_at: u64, recorded_at: u64) {
+    PaymentBackdated {
+        invoice_id,
+        paid_at,
+        recorded_at,
+        backdated: paid_at < recorded_at,
+    }
+    .publish(env);
+}
+
+/// Emit a `payment_imported` event.
+pub fn emit_payment_imported(env: &Env, record: PaymentRecord) {
+    PaymentImported { record }.publish(env);
+}
+
+/// Emit a `hashed_payment_recorded` event with the invoice hash as a topic.
+pub fn emit_hashed_payment_recorded(env: &Env, record: HashedPaymentRecord) {
+    HashedPaymentRecorded {
+        invoice_hash: record.invoice_hash,
+        payer: record.payer,
+        asset: record.asset,
+        amount: record.amount,
+        timestamp: record.timestamp,
+    }
+    .publish(env);
+}
+
+/// Emit an `external_payment_recorded` event. Indexers must not treat it as
+/// a Stellar payment: nothing moved on-chain.
+pub fn emit_external_payment_recorded(env: &Env, record: ExternalPaymentRecord) {
+    ExternalPaymentRecorded { record }.publish(env);
+}
+
+/// Emit a `metadata_updated` event.
+pub fn emit_metadata_updated(env: &Env, invoice_id: String) {
+    MetadataUpdated { invoice_id }.publish(env);
+}
+
+/// Emit a `records_compacted` event after a `compact_old_records` batch.
+pub fn emit_records_compacted(env: &Env, compacted: u32, next_seq: u32) {
+    RecordsCompacted {
+        compacted,
+        next_seq,
+    }
+    .publish(env);
+}
+
+/// Emit a `pending_payment_reported` event carrying the provisional
+/// [`PaymentRecord`].
+///
+/// Indexers should treat this as a *candidate* only; the authoritative signal
+/// is the `payment_recorded` event emitted when the candidate is confirmed.
+pub fn emit_pending_payment_reported(env: &Env, record: PaymentRecord) {
+    PendingPaymentReported { record }.publish(env);
+}
+
+/// Emit a `merchant_payment_recorded` event linking `invoice_id` to
+/// `merchant_id` under its `receipt_number`. Follows the `payment_recorded`
+/// event of the same payment.
+pub fn emit_merchant_payment_recorded(
+    env: &Env,
+    merchant_id: String,
+    invoice_id: String,
+    receipt_number: u64,
+) {
+    MerchantPaymentRecorded {
+        merchant_id,
+        invoice_id,
+        receipt_number,
+    }
+    .publish(env);
+}
+
+/// Emit an `fx_rate_captured` event with the USD rate stored for
+/// `invoice_id`. Follows the `payment_recorded` event of the same payment.
+pub fn emit_fx_rate_captured(env: &Env, invoice_id: String, rate: FxRate) {
+    FxRateCaptured { invoice_id, rate }.publish(env);
+}
+
+/// Emit a `payer_flags_updated` event so screening changes are auditable.
+pub fn emit_payer_flags_updated(env: &Env, payer: Address, flags: PayerFlags) {
+    PayerFlagsUpdated { payer, flags }.publish(env);
+}
+
+/// Emit a `deposit_invoice_issued` event.
+pub fn emit_deposit_invoice_issued(env: &Env, invoice_id: String, invoice: DepositInvoice) {
+    DepositInvoiceIssued {
+        invoice_id,
+        invoice,
+    }
+    .publish(env);
+}
+
+/// Emit a `deposit_paid` event when a payment completes the deposit.
+pub fn emit_deposit_paid(
+    env: &Env,
+    invoice_id: String,
+    payment_id: String,
+    invoice: &DepositInvoice,
+) {
+    DepositPaid {
+        invoice_id,
+        payment_id,
+        paid: invoice.paid,
+        balance_due: invoice.balance_due(),
+    }
+    .publish(env);
+}
+
+/// Emit an `installment_plan_issued` event.
+pub fn emit_installment_plan_issued(env: &Env, invoice_id: String, plan: InstallmentPlan) {
+    InstallmentPlanIssued { invoice_id, plan }.publish(env);
+}
+
+/// Emit an `installment_paid` event for each installment a payment completes.
+pub fn emit_installment_paid(env: &Env, invoice_id: String, payment_id: String, index: u32) {
+    InstallmentPaid {
+        invoice_id,
+        payment_id,
+        index,
+    }
+    .publish(env);
+}
+
+/// Emit a `keeper_rewarded` event with `keeper` as a topic.
+pub fn emit_keeper_rewarded(env: &Env, keeper: Address, token: Address, amount: i128) {
+    KeeperRewarded {
+        keeper,
+        token,
+        amount,
+    }
+    .publish(env);
+}
+
+/// Emit a `keeper_pool_funded` event with `funder` as a topic.
+pub fn emit_keeper_pool_funded(env: &Env, funder: Address, token: Address, amount: i128) {
+    KeeperPoolFunded {
+        funder,
+        token,
+        amount,
+    }
+    .publish(env);
+}
+
+/// Emit an `observer_failed` event.
+pub fn emit_observer_failed(env: &Env, observer: Address, invoice_id: String) {
+    ObserverFailed {
+        observer,
+        invoice_id,
+    }
+    .publish(env);
+}
+
+/// Emit an `invoice_expired` event.
+pub fn emit_invoice_expired(env: &Env, invoice_id: String, paid: i128) {
+    InvoiceExpired { invoice_id, paid }.publish(env);
+}
+
+/// Emit a `balance_paid` event when a payment settles the invoice.
+pub fn emit_balance_paid(env: &Env, invoice_id: String, payment_id: String, paid: i128) {
+    BalancePaid {
+        invoice_id,
+        payment_id,
+        paid,
+    }
+    .publish(env);
+}
+
+/// Emit a `payment_refunded` event for a (possibly partial) refund.
+pub fn emit_payment_refunded(env: &Env, invoice_id: String, amount: i128, refunded_total: i128) {
+    PaymentRefunded {
+        invoice_id,
+        amount,
+        refunded_total,
+    }
+    .publish(env);
+}
+
+/// Emit a `redirected_refund` event for a refund sent to an address other
+/// than the original payer.
+pub fn emit_redirected_refund(
+    env: &Env,
+    invoice_id: String,
+    payer: Address,
+    recipient: Address,
+    amount: i128,
+    refunded_total: i128,
+) {
+    RedirectedRefund {
+        invoice_id,
+        payer,
+        recipient,
+        amount,
+        refunded_total,
+    }
+    .publish(env);
+}
+
+/// Emit a `quote_issued` event when the merchant issues or revises a quote.
+pub fn emit_quote_issued(env: &Env, quote_id: String, quote: Quote) {
+    QuoteIssued { quote_id, quote }.publish(env);
+}
+
+/// Emit a `quote_accepted` event when a quote converts into an invoice.
+pub fn emit_quote_accepted(env: &Env, quote_id: String, invoice_id: String, payer: Address) {
+    QuoteAccepted {
+        quote_id,
+        invoice_id,
+        payer,
+    }
+    .publish(env);
+}
+
+/// Emit an `invoice_acknowledged` event the first time a payer acknowledges
+/// an invoice.
+pub fn emit_invoice_acknowledged(env: &Env, invoice_id: String, payer: Address) {
+    InvoiceAcknowledged { invoice_id, payer }.publish(env);
+}
+
+/// Emit a `terms_accepted` event when a payer accepts an invoice's terms.
+pub fn emit_terms_accepted(env: &Env, invoice_id: String, payer: Address, terms_hash: BytesN<32>) {
+    TermsAccepted {
+        invoice_id,
+        payer,
+        terms_hash,
+    }
+    .publish(env);
+}
+
+/// Emit an `invoice_preauthorized` event.
+pub fn emit_invoice_preauthorized(env: &Env, invoice_id: String, preauth: &Preauthorization) {
+    InvoicePreauthorized {
+        invoice_id,
+        payer: preauth.payer.clone(),
+        asset: preauth.asset.clone(),
+        max_amount: preauth.max_amount,
+    }
+    .publish(env);
+}
+
+/// Emit a `payer_blocked` event. From this ledger on every write path
+/// rejects the payer with `PayerBlocked`.
+pub fn emit_payer_blocked(env: &Env, payer: Address) {
+    PayerBlocked { payer }.publish(env);
+}
+
+/// Emit a `payer_unblocked` event.
+pub fn emit_payer_unblocked(env: &Env, payer: Address) {
+    PayerUnblocked { payer }.publish(env);
+}
+
+/// Emit a `circuit_tripped` event with the window that exceeded a threshold.
+/// Monitoring should page on this: writes stay paused until `resume`.
+pub fn emit_circuit_tripped(env: &Env, window: BreakerWindow) {
+    CircuitTripped { window }.publish(env);
+}
+
+/// Emit a `paused` event for a manual pause.
+pub fn emit_paused(env: &Env, admin: Address) {
+    Paused { admin }.publish(env);
+}
+
+/// Emit a `resumed` event.
+pub fn emit_resumed(env: &Env, admin: Address) {
+    Resumed { admin }.publish(env);
+}
+
+/// Emit a `payment_collected` event: `amount` of `token` for `invoice_id`
+/// now counts towards the merchant's next settlement.
+pub fn emit_payment_collected(
+    env: &Env,
+    merchant_id: String,
+    invoice_id: String,
+    token: Address,
+    amount: i128,
+) {
+    PaymentCollected {
+        merchant_id,
+        invoice_id,
+        token,
+        amount,
+    }
+    .publish(env);
+}
+
+/// Emit a `merchant_settled` event carrying the full [`Settlement`].
+pub fn emit_merchant_settled(env: &Env, settlement: Settlement) {
+    MerchantSettled { settlement }.publish(env);
+}
+
+/// Emit a `payout_schedule_updated` event.
+pub fn emit_payout_schedule_updated(
+    env: &Env,
+    merchant_id: String,
+    schedule: Option<PayoutSchedule>,
+) {
+    PayoutScheduleUpdated {
+        merchant_id,
+        schedule,
+    }
+    .publish(env);
+}
+
+/// Emit a `payout_scheduled` event announcing the next release `day`.
+pub fn emit_payout_scheduled(env: &Env, merchant_id: String, day: u32) {
+    PayoutScheduled { merchant_id, day }.publish(env);
+}
+
+/// Emit a `credit_note_issued` event carrying the new [`CreditNote`].
+pub fn emit_credit_note_issued(env: &Env, credit_id: String, note: CreditNote) {
+    CreditNoteIssued { credit_id, note }.publish(env);
+}
+
+/// Emit a `credit_note_applied` event.
+pub fn emit_credit_note_applied(
+    env: &Env,
+    credit_id: String,
+    invoice_id: String,
+    amount: i128,
+    remaining: i128,
+) {
+    CreditNoteApplied {
+        credit_id,
+        invoice_id,
+        amount,
+        remaining,
+    }
+    .publish(env);
+}
+
+/// Emit a `credit_note_voided` event.
+pub fn emit_credit_note_voided(env: &Env, credit_id: String, lapsed: i128) {
+    CreditNoteVoided { credit_id, lapsed }.publish(env);
+}
+
+/// Emit an `invoice_tax_updated` event; `tax` is `None` once removed.
+pub fn emit_invoice_tax_updated(env: &Env, invoice_id: String, tax: Option<InvoiceTax>) {
+    InvoiceTaxUpdated { invoice_id, tax }.publish(env);
+}
+
+/// Emit a `payment_taxed` event carrying the payment's [`TaxSplit`].
+pub fn emit_payment_taxed(env: &Env, invoice_id: String, split: TaxSplit) {
+    PaymentTaxed { invoice_id, split }.publish(env);
+}
+
+/// Emit an `accepted_assets_updated` event.
+pub fn emit_accepted_assets_updated(env: &Env, invoice_id: String, assets: Map<Asset, i128>) {
+    AcceptedAssetsUpdated { invoice_id, assets }.publish(env);
+}
+
+/// Emit a `payment_converted` event carrying the [`Conversion`].
+pub fn emit_payment_converted(env: &Env, invoice_id: String, conversion: Conversion) {
+    PaymentConverted {
+        invoice_id,
+        conversion,
+    }
+    .publish(env);
+}
+
+/// Emit a `chargeback_flagged` event.
+pub fn emit_chargeback_flagged(env: &Env, invoice_id: String, reference: String) {
+    ChargebackFlagged {
+        invoice_id,
+        reference,
+    }
+    .publish(env);
+}
+
+/// Emit a `chargeback_resolved` event.
+pub fn emit_chargeback_resolved(env: &Env, invoice_id: String, status: ChargebackStatus) {
+    ChargebackResolved { invoice_id, status }.publish(env);
+}
+
+/// Emit a `reconciliation_updated` event.
+pub fn emit_reconciliation_updated(
+    env: &Env,
+    invoice_id: String,
+    from: Reconciliation,
+    to: Reconciliation,
+) {
+    ReconciliationUpdated {
+        invoice_id,
+        from,
+        to,
+    }
+    .publish(env);
+}
+
+/// Emit a `("chain_payment_recorded", chain_id)` event, so indexers can
+/// follow one chain. Like `external_payment_recorded`, nothing moved on
+/// Stellar.
+pub fn emit_chain_payment_recorded(env: &Env, record: ChainPaymentRecord) {
+    ChainPaymentRecorded {
+        chain_id: record.chain_id.clone(),
+        record,
+    }
+    .publish(env);
+}
+
+/// Emit a `("remittance_recorded", invoice_id)` event after the
+/// `payment_recorded` of a SEP-31 payment.
+pub fn emit_remittance_recorded(env: &Env, invoice_id: String, remittance: Remittance) {
+    RemittanceRecorded {
+        invoice_id,
+        remittance,
+    }
+    .publish(env);
+}
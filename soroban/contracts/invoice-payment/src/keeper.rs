@@ -0,0 +1,46 @@
+use soroban_sdk::{token, Address, Env, String};
+
+use crate::events::emit_keeper_rewarded;
+use crate::storage::{
+    get_keeper_bounty, get_keeper_pool, is_ttl_fresh, set_keeper_pool, set_ttl_fresh,
+};
+
+/// Pay the configured [`KeeperBounty`](crate::storage::KeeperBounty) to
+/// `keeper` from the keeper pool, and return the amount paid.
+///
+/// Pays nothing (and returns 0) when no bounty is set or the pool cannot
+/// cover it, so maintenance never fails for lack of funds. Only the pool is
+/// ever paid out: other funds the contract holds, such as collected merchant
+/// balances, are not.
+pub fn reward(env: &Env, keeper: &Address) -> i128 {
+    let Some(bounty) = get_keeper_bounty(env) else {
+        return 0;
+    };
+    let pool = get_keeper_pool(env, &bounty.token);
+    if pool < bounty.amount {
+        return 0;
+    }
+    set_keeper_pool(env, &bounty.token, pool - bounty.amount);
+    token::Client::new(env, &bounty.token).transfer(
+        &env.current_contract_address(),
+        keeper,
+        &bounty.amount,
+    );
+    emit_keeper_rewarded(env, keeper.clone(), bounty.token, bounty.amount);
+    bounty.amount
+}
+
+/// Note a bump of the TTL of the record of `invoice_id` and [`reward`]
+/// `keeper`, if any, for it, and return the amount paid.
+///
+/// Only a bump the record needed is noted and rewarded: one made once its
+/// TTL has fallen below `MIN_TTL`, `KEEPER_COOLDOWN_TTL` ledgers after it
+/// was written or last bumped here. Bumping a fresh record, or the same one
+/// over and over, pays nothing, so the pool cannot be drained that way.
+pub fn reward_ttl_bump(env: &Env, invoice_id: &String, keeper: Option<Address>) -> i128 {
+    if is_ttl_fresh(env, invoice_id) {
+        return 0;
+    }
+    set_ttl_fresh(env, invoice_id);
+    keeper.map_or(0, |keeper| reward(env, &keeper))
+}
@@ -1,18 +1,110 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, Address, Env, String};
+use soroban_sdk::{
+    contract, contractimpl, contractmeta, token, xdr::ToXdr, Address, Bytes, BytesN, Env, Map,
+    MuxedAddress, String, Symbol, Vec,
+};
 
+pub mod aggregates;
+pub mod audit;
+pub mod breaker;
 pub mod errors;
 pub mod events;
+pub mod keeper;
+pub mod limits;
+pub mod observer;
+pub mod oracle;
+pub mod payout;
+pub mod receipt;
+pub mod registry;
+pub mod retention;
+pub mod schedule;
 pub mod storage;
+pub mod tax;
+pub mod validation;
+
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils;
 
 // Re-export the main types so `use super::*` in test.rs picks them up.
 pub use errors::ContractError;
-pub use storage::{Asset, DataKey, PaymentRecord};
+pub use storage::{
+    Acknowledgement, AmountDue, Asset, BreakerWindow, ChainAsset, ChainPaymentRecord, Chargeback,
+    ChargebackStatus, CircuitBreaker, CompactedPayment, CompliancePolicy, ContractInfo, Conversion,
+    CreditNote, CreditNoteStatus, DataKey, DeploymentInfo, DepositInvoice, DepositStatus,
+    ExternalPaymentRecord, FxRate, HashedPaymentRecord, Installment, InstallmentPlan,
+    InstallmentStatus, InvariantReport, InvoiceTax, KeeperBounty, KeyFamily, MerchantBalance,
+    PayerFlags, PaymentReceipt, PaymentRecord, PayoutSchedule, PeriodTotals, Preauthorization,
+    Quote, Reconciliation, Remittance, Settlement, SignedPayment, StorageStats, TaxSplit,
+    TermsAcceptance,
+};
 
-use events::emit_payment_recorded;
+use aggregates::{add_payment, day_of};
+use audit::check_invariants;
+use breaker::{require_not_paused, track_payment};
+use events::{
+    emit_accepted_assets_updated, emit_chain_payment_recorded, emit_chargeback_flagged,
+    emit_chargeback_resolved, emit_credit_note_applied, emit_credit_note_issued,
+    emit_credit_note_voided, emit_deposit_invoice_issued, emit_external_payment_recorded,
+    emit_fx_rate_captured, emit_hashed_payment_recorded, emit_installment_plan_issued,
+    emit_invoice_acknowledged, emit_invoice_expired, emit_invoice_preauthorized,
+    emit_invoice_tax_updated, emit_keeper_pool_funded, emit_merchant_payment_recorded,
+    emit_merchant_settled, emit_metadata_updated, emit_paused, emit_payer_blocked,
+    emit_payer_flags_updated, emit_payer_unblocked, emit_payment_backdated, emit_payment_collected,
+    emit_payment_converted, emit_payment_imported, emit_payment_recorded, emit_payment_refunded,
+    emit_payment_taxed, emit_payout_schedule_updated, emit_payout_scheduled,
+    emit_pending_payment_reported, emit_quote_accepted, emit_quote_issued,
+    emit_reconciliation_updated, emit_records_compacted, emit_redirected_refund,
+    emit_remittance_recorded, emit_resumed, emit_terms_accepted,
+};
+use keeper::{reward, reward_ttl_bump};
+use limits::{consume_volume, rolling_volume};
+use observer::{notify_observers, MAX_OBSERVERS};
+use oracle::capture_rate;
+use receipt::mint_receipt;
+use registry::{merchant_payout, require_active_merchant};
+use retention::{compact_before, export_page, index_payment};
+use schedule::{amount_due, apply_payment, has_schedule, invoice_total, MAX_INTEREST_RATE_BPS};
 use storage::{
-    bump_count, get_admin, get_count, get_payment, has_admin, has_payment, set_admin, set_payment,
+    add_received, bump_count, bump_entry_count, bump_pending_count, extend_payment_ttl,
+    get_accepted_assets, get_acknowledgement, get_admin, get_admin_nonce, get_breaker_window,
+    get_chain_payment, get_chargeback, get_circuit_breaker, get_compact_cursor,
+    get_compacted_payment, get_compliance_policy, get_conversion, get_count, get_credit_note,
+    get_daily_totals, get_deployment_info, get_deposit_invoice, get_external_payment, get_fx_oracle,
+    get_hashed_payment, get_installment_plan, get_interest_rate_bps, get_invoice_tax,
+    get_keeper_bounty, get_keeper_pool, get_merchant_balance, get_merchant_registry, get_metadata,
+    get_observers, get_paid_at, get_payer_flags, get_payment, get_payment_merchant,
+    get_payment_rate, get_payment_tax, get_payout_schedule, get_pending_payment,
+    get_preauth_threshold, get_preauthorization, get_quote, get_receipt_contract, get_receipt_count,
+    get_receipt_number, get_received_totals, get_reconciliation, get_refunded_total, get_remittance,
+    get_remittance_invoice, get_settlement, get_settlement_count, get_signing_key,
+    get_terms_acceptance, get_usdc_issuer, get_validator, get_volume_limit, has_admin,
+    has_chain_payment, has_credit_note, has_external_payment, has_hashed_payment, has_payment,
+    has_pending_payment, is_collected, is_paused, is_payer_blocked, is_terms_required,
+    next_receipt_number, remove_pending_payment, set_accepted_assets, set_acknowledgement,
+    set_admin, set_admin_nonce, set_breaker_window, set_chain_payment, set_chargeback,
+    set_circuit_breaker, set_compliance_policy, set_conversion, set_credit_note,
+    set_deployment_info, set_deposit_invoice, set_external_payment, set_fx_oracle,
+    set_hashed_payment, set_installment_plan, set_interest_rate_bps, set_invoice_tax,
+    set_keeper_bounty, set_keeper_pool, set_merchant_registry, set_metadata, set_observers,
+    set_paid_at, set_paused, set_payer_blocked, set_payer_flags, set_payment, set_payment_merchant,
+    set_payment_rate, set_payment_tax, set_payout_schedule, set_pending_payment,
+    set_preauth_threshold, set_preauthorization, set_quote, set_receipt_contract,
+    set_receipt_number, set_reconciliation, set_refunded_total, set_remittance, set_signing_key,
+    set_terms_acceptance, set_terms_required, set_usdc_issuer, set_validator, set_volume_limit,
+    storage_stats, CONVERSION_RATE_SCALE, MAX_ACCEPTED_ASSETS, MAX_INSTALLMENTS, MAX_METADATA_LEN,
 };
+use tax::{split_payment, validate_tax};
+use validation::{
+    asset_contract, build_asset, check_accepted_asset, check_payer, check_preauthorization,
+    check_terms, check_validator, validate_id, validate_payment, validate_transfer,
+};
+
+contractmeta!(key = "name", val = "invoice-payment");
+// Keep in sync with `CONTRACT_VERSION`.
+contractmeta!(key = "version", val = "0.1.0");
+
+/// Package version reported by `info()`.
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 // Contract
 
@@ -24,12 +116,22 @@ use storage::{
 /// Horizon.
 ///
 /// ## Module layout
-/// | Module        | Responsibility                              |
-/// |---------------|---------------------------------------------|
-/// | `errors.rs`   | `#[contracterror]` typed error codes        |
-/// | `storage.rs`  | `DataKey`, `PaymentRecord`, TTL helpers     |
-/// | `events.rs`   | `emit_payment_recorded` Soroban event helper|
-/// | `lib.rs`      | Contract entry-points (this file)           |
+/// | Module          | Responsibility                             |
+/// |-----------------|--------------------------------------------|
+/// | `errors.rs`     | `#[contracterror]` typed error codes       |
+/// | `storage.rs`    | `DataKey`, `PaymentRecord`, TTL helpers    |
+/// | `events.rs`     | Soroban event types and emit helpers       |
+/// | `validation.rs` | Argument checks shared by write paths      |
+/// | `breaker.rs`    | Pause state and the volume circuit breaker |
+/// | `limits.rs`     | Rolling 24 h per-payer volume limits       |
+/// | `oracle.rs`     | Reflector-style price oracle client        |
+/// | `payout.rs`     | Merchant balances and batched settlements  |
+/// | `receipt.rs`    | Client for the optional receipts contract  |
+/// | `registry.rs`   | Client for the merchant registry contract  |
+/// | `retention.rs`  | Compaction of old records into summaries   |
+/// | `tax.rs`        | Invoice tax checks and net/tax splits      |
+/// | `testutils.rs`  | Fixtures, behind the `testutils` feature   |
+/// | `lib.rs`        | Contract entry-points (this file)          |
 ///
 /// ## Design decisions
 /// - **Admin-gated writes:** only the admin (backend service account) can call
@@ -38,11 +140,69 @@ use storage::{
 ///   preventing double-counting in reconciliation.
 /// - **Persistent storage with TTL bumping:** records survive ledger archival;
 ///   TTLs are extended on every read and write.
+/// - **Archived ≠ absent:** a record whose TTL lapsed is archived, never
+///   silently reported as missing. Reads of an archived record fail at the
+///   host level (or are auto-restored when the transaction footprint asks for
+///   it), so `PaymentNotFound` / `false` always mean "never recorded".
 /// - **Typed errors:** `#[contracterror]` returns structured `ScError::Contract`
 ///   values that appear in Horizon responses and are matchable in tests.
+/// - **Temporary storage for candidates:** `report_pending_payment` parks
+///   unconfirmed payments in cheap, auto-expiring storage; only confirmed
+///   payments pay persistent rent.
 /// - **Soroban events:** every `record_payment` emits a `("payment","recorded")`
 ///   event carrying the full `PaymentRecord` so off-chain indexers don't need
 ///   to poll state.
+/// - **Optional receipts:** when the admin configures a receipts contract,
+///   every recorded payment also mints a non-transferable receipt to the
+///   payer in the same invocation.
+/// - **Payer screening:** the admin stores compliance flags per payer; an
+///   optional [`CompliancePolicy`] turns them into hard checks on every write
+///   path. Blocked payers (e.g. sanctions hits) are always rejected.
+/// - **Volume limits on-chain:** an optional per-asset cap on what one payer
+///   may pay in a rolling 24 h window is enforced here, not only by the
+///   backend. Buckets live in temporary storage and expire on their own.
+/// - **Circuit breaker:** if more payments or more volume than configured are
+///   recorded within a window of ledgers, writes pause themselves and a
+///   `circuit_tripped` event is emitted, bounding the damage a compromised
+///   recorder key can do before a human responds.
+/// - **Compact records:** a stored payment omits the `invoice_id` already in
+///   its key and stores XLM and the configured USDC as bare tags, which
+///   matters for rent with tens of thousands of records. `get_payment` and
+///   events still return the full [`PaymentRecord`].
+/// - **Hashed invoice ids:** `record_hashed_payment` keys a payment by a
+///   `BytesN<32>` hash instead of a string, for a fixed-size key and an
+///   event topic indexers can filter on. With a salted commitment
+///   `sha256(invoice_id || salt)` as the hash this doubles as privacy mode:
+///   the invoice id stays off-chain until `reveal` is handed the salt.
+/// - **Off-chain rails on the same ledger:** invoices settled by bank
+///   transfer or card get an `ExternalPaymentRecord` and their own event,
+///   kept apart from Stellar payments so reconciliation never mixes them.
+///   Invoices paid on another blockchain get a `ChainPaymentRecord` with a
+///   CAIP-2 chain id, the transaction reference and a chain-neutral asset.
+/// - **Retention by compaction:** `compact_old_records` replaces old full
+///   records with an amount and a hash of the record, cutting rent while
+///   keeping every invoice provably recorded and never re-recordable.
+/// - **FX rate at recording time:** with a price oracle configured, the USD
+///   rate of the payment's asset is captured next to the record, so the
+///   conversion used for a USD invoice is fixed on-chain. Best-effort: a
+///   missing or stale price never blocks recording.
+/// - **Merchants live in a registry:** `record_merchant_payment` validates
+///   `merchant_id` against the `merchant-registry` contract, so onboarding
+///   and offboarding never touch this contract.
+/// - **Batched payouts:** funds collected for merchant payments accumulate
+///   per merchant and leave in one `settle` per merchant, whose
+///   [`Settlement`] lists every invoice it covers. A merchant with a
+///   [`PayoutSchedule`] is settled by anyone, on its release days only.
+/// - **Credits are not refunds:** a [`CreditNote`] lowers the amount due on
+///   a payer's invoices without touching any payment record, while refunds
+///   only ever net down what was paid.
+/// - **Tax kept, not reconstructed:** an invoice's [`InvoiceTax`] splits
+///   each payment recorded for it into net and tax at recording time, and
+///   the daily and monthly totals carry the tax beside the gross amount.
+/// - **Admin nonces:** config changes and admin transfers take the admin's
+///   current `admin_nonce`, which each one increments. A transaction signed
+///   but never submitted cannot be replayed once a later one lands, nor
+///   after the key is rotated away and back.
 ///
 /// ## Typical backend flow
 /// 1. Deploy + call `initialize(admin)` once.
@@ -86,13 +246,15 @@ impl InvoicePaymentContract {
     ///
     /// ## Idempotency
     /// Each `invoice_id` may be recorded **only once**.
-    /// Returns [`ContractError::PaymentAlreadyRecorded`] on duplicates.
+    /// Returns [`ContractError::PaymentAlreadyRecorded`] on duplicates, and
+    /// for invoices already settled with [`record_external_payment`] or
+    /// [`record_chain_payment`].
     ///
     /// ## Emitted event
     /// | Field  | Value                                   |
     /// |--------|-----------------------------------------|
     /// | Topics | `(Symbol "payment", Symbol "recorded")` |
-    /// | Data   | Full [`PaymentRecord`] struct            |
+    /// | Data   | Full [`PaymentRecord`] struct           |
     ///
     /// Subscribe via:
     /// ```sh
@@ -101,140 +263,2866 @@ impl InvoicePaymentContract {
     ///
     /// ## Parameters
     /// - `invoice_id`   — unique invoice identifier (e.g. `"invoisio-abc123"`)
-    /// - `payer`        — Stellar account address that sent the payment
+    /// - `payer`        — Stellar account that sent the payment; a muxed
+    ///   (`M...`) address keeps the customer's mux id, e.g. for deposits
+    ///   into an exchange omnibus account
     /// - `asset_code`   — `"XLM"` or token code (e.g. `"USDC"`)
     /// - `asset_issuer` — issuer public key for tokens; `""` for native XLM
     /// - `amount`       — payment amount in smallest denomination (must be > 0)
     ///
     /// ## Errors
     /// - [`ContractError::NotInitialized`] — contract was never initialised
-    /// - [`ContractError::InvalidInvoiceId`] — `invoice_id` is an empty string
-    /// - [`ContractError::InvalidAsset`] — `asset_code` is empty, or a non-XLM asset has no `asset_issuer`
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::InvalidInvoiceId`] — `invoice_id` is empty or longer
+    ///   than [`storage::MAX_INVOICE_ID_LEN`] bytes
+    /// - [`ContractError::InvalidAsset`] — `asset_code` is not 1–12 alphanumerics, or a non-XLM asset has no valid `G...` `asset_issuer`
     /// - [`ContractError::InvalidAmount`] — `amount` ≤ 0
-    /// - [`ContractError::PaymentAlreadyRecorded`] — `invoice_id` already on-chain
+    /// - [`ContractError::PaymentAlreadyRecorded`] — `invoice_id` already
+    ///   recorded, on Stellar, externally or on another chain
+    /// - [`ContractError::PayerBlocked`] — `payer` is on the blocklist
+    /// - [`ContractError::PayerNotVerified`] / [`ContractError::PayerHighRisk`]
+    ///   — rejected by the compliance policy
+    /// - [`ContractError::TermsNotAccepted`] — terms are required and `payer`
+    ///   has not accepted them
+    /// - [`ContractError::NotPreauthorized`] — does not match the payer's
+    ///   preauthorization, or one is required and missing
+    /// - [`ContractError::InvalidAsset`] / [`ContractError::AmountMismatch`] —
+    ///   not one of the invoice's [`accepted_assets`], or not the amount
+    ///   expected in it
+    /// - [`ContractError::LimitExceeded`] — over the payer's 24 h volume limit
+    /// - [`ContractError::PaymentRejected`] — refused by the registered
+    ///   validator contract
     pub fn record_payment(
         env: Env,
         invoice_id: String,
-        payer: Address,
+        payer: MuxedAddress,
         asset_code: String,
         asset_issuer: String,
         amount: i128,
     ) -> Result<(), ContractError> {
-        // 1. Admin authorisation.
+        // 1. Admin authorisation; writes must not be paused.
         let admin = get_admin(&env)?;
         admin.require_auth();
+        require_not_paused(&env)?;
+        let (payer, payer_mux_id) = (payer.address(), payer.id());
 
         // 2. Input guards — reject obviously malformed arguments early so they
         //    never reach persistent storage.
+        let asset = validate_payment(&env, &invoice_id, &asset_code, &asset_issuer, amount)?;
 
-        // invoice_id must be non-empty.
-        if invoice_id.len() == 0 {
-            return Err(ContractError::InvalidInvoiceId);
+        // 3. Idempotency guard.
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
         }
 
-        // asset_code must be non-empty.
-        if asset_code.len() == 0 {
-            return Err(ContractError::InvalidAsset);
-        }
+        // 4. Payer screening: blocklist, then compliance policy, then the
+        //    rolling volume limit (which also counts this payment).
+        check_payer(&env, &payer)?;
+        check_terms(&env, &invoice_id, &payer)?;
+        check_preauthorization(&env, &invoice_id, &payer, &asset, amount)?;
+        check_accepted_asset(&env, &invoice_id, &asset, amount)?;
+        consume_volume(&env, &payer, &asset, amount)?;
 
-        // Asset validation:
-        // - XLM (native) must have an empty issuer
-        // - Non-XLM assets (tokens) must have a non-empty issuer
-        let is_xlm = asset_code == String::from_str(&env, "XLM");
-        let issuer_empty = asset_issuer.len() == 0;
-        
-        if is_xlm && !issuer_empty {
-            // XLM with issuer is invalid
-            return Err(ContractError::InvalidAsset);
-        }
-        if !is_xlm && issuer_empty {
-            // Token without issuer is invalid
-            return Err(ContractError::InvalidAsset);
-        }
+        // 5. Build and persist the record, bump the counter, emit the event.
+        let record = PaymentRecord {
+            invoice_id,
+            payer,
+            payer_mux_id,
+            asset,
+            amount,
+            timestamp: env.ledger().timestamp(),
+        };
+        check_validator(&env, &record)?;
+        commit_payment(&env, record);
 
-        // 3. Amount guard.
-        if amount <= 0 {
-            return Err(ContractError::InvalidAmount);
-        }
+        Ok(())
+    }
+
+    /// Record a payment authorized by an ed25519 signature from the
+    /// configured signing key instead of the admin's auth, so anyone (e.g. a
+    /// fee-bump relayer) can submit it while the key stays offline.
+    ///
+    /// `signature` covers the XDR of `(contract address, payment)`; binding
+    /// the contract address keeps a signature from being replayed on another
+    /// deployment, and `invoice_id` idempotency stops replays on this one.
+    /// Otherwise the same as [`record_payment`].
+    ///
+    /// An invalid signature aborts the invocation rather than returning an
+    /// error.
+    ///
+    /// ## Errors
+    /// - [`ContractError::SigningKeyNotSet`] — no signing key configured
+    /// - Every error of [`record_payment`]
+    pub fn record_payment_signed(
+        env: Env,
+        payment: SignedPayment,
+        signature: BytesN<64>,
+    ) -> Result<(), ContractError> {
+        require_not_paused(&env)?;
+        let key = get_signing_key(&env).ok_or(ContractError::SigningKeyNotSet)?;
+        let message = (env.current_contract_address(), payment.clone()).to_xdr(&env);
+        env.crypto().ed25519_verify(&key, &message, &signature);
 
-        // 4. Idempotency guard.
-        if has_payment(&env, &invoice_id) {
+        let SignedPayment {
+            invoice_id,
+            payer,
+            asset_code,
+            asset_issuer,
+            amount,
+        } = payment;
+        let asset = validate_payment(&env, &invoice_id, &asset_code, &asset_issuer, amount)?;
+        if is_settled(&env, &invoice_id) {
             return Err(ContractError::PaymentAlreadyRecorded);
         }
+        check_payer(&env, &payer)?;
+        check_terms(&env, &invoice_id, &payer)?;
+        check_preauthorization(&env, &invoice_id, &payer, &asset, amount)?;
+        check_accepted_asset(&env, &invoice_id, &asset, amount)?;
+        consume_volume(&env, &payer, &asset, amount)?;
 
-        // 5. Build the asset enum based on parameters.
-        let asset = if is_xlm {
-            Asset::Native
-        } else {
-            Asset::Token(asset_code.clone(), asset_issuer.clone())
-        };
-
-        // 6. Build and persist the record (also bumps persistent TTL).
         let record = PaymentRecord {
             invoice_id,
             payer,
+            payer_mux_id: None,
             asset,
             amount,
             timestamp: env.ledger().timestamp(),
         };
-        set_payment(&env, &record);
+        check_validator(&env, &record)?;
+        commit_payment(&env, record);
 
-        // 7. Increment running counter (also bumps instance TTL).
-        bump_count(&env);
+        Ok(())
+    }
+
+    /// Record a payment for `invoice_id` on behalf of `merchant_id`.
+    ///
+    /// Same as [`record_payment`], after checking with the configured merchant
+    /// registry that `merchant_id` is registered and active. The link is
+    /// readable with [`payment_merchant`] and announced by a
+    /// `merchant_payment_recorded` event after `payment_recorded`.
+    ///
+    /// The payment is issued the merchant's next receipt number, readable
+    /// with [`receipt_number`] and carried by the event. Numbers start at 1
+    /// per merchant and have no gaps: a call that fails takes none.
+    ///
+    /// ## Errors
+    /// - Every error of [`record_payment`]
+    /// - [`ContractError::InvalidInvoiceId`] — `merchant_id` is empty or too
+    ///   long
+    /// - [`ContractError::MerchantRegistryNotSet`] — no registry configured
+    /// - [`ContractError::UnknownMerchant`] — `merchant_id` is not registered
+    /// - [`ContractError::MerchantInactive`] — the merchant was deactivated
+    pub fn record_merchant_payment(
+        env: Env,
+        merchant_id: String,
+        invoice_id: String,
+        payer: MuxedAddress,
+        asset_code: String,
+        asset_issuer: String,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+        let (payer, payer_mux_id) = (payer.address(), payer.id());
+
+        let asset = validate_payment(&env, &invoice_id, &asset_code, &asset_issuer, amount)?;
+        validate_id(&merchant_id)?;
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+        require_active_merchant(&env, &merchant_id)?;
+        check_payer(&env, &payer)?;
+        check_terms(&env, &invoice_id, &payer)?;
+        check_preauthorization(&env, &invoice_id, &payer, &asset, amount)?;
+        check_accepted_asset(&env, &invoice_id, &asset, amount)?;
+        consume_volume(&env, &payer, &asset, amount)?;
 
-        // 8. Emit Soroban event — off-chain indexers subscribe to these topics.
-        emit_payment_recorded(&env, record);
+        let record = PaymentRecord {
+            invoice_id: invoice_id.clone(),
+            payer,
+            payer_mux_id,
+            asset,
+            amount,
+            timestamp: env.ledger().timestamp(),
+        };
+        check_validator(&env, &record)?;
+        commit_payment(&env, record);
+        set_payment_merchant(&env, &invoice_id, &merchant_id);
+        let receipt_number = next_receipt_number(&env, &merchant_id);
+        set_receipt_number(&env, &invoice_id, receipt_number);
+        emit_merchant_payment_recorded(&env, merchant_id, invoice_id, receipt_number);
 
         Ok(())
     }
 
-    // Read
+    /// Create a retainer invoice of `total` in the given asset of which
+    /// `deposit_bps` basis points (1–10 000) are due upfront. Admin only.
+    /// Emits `deposit_invoice_issued`.
+    ///
+    /// The invoice is paid through [`record_invoice_payment`], which tracks
+    /// the deposit and the balance separately. If the deposit is still due
+    /// after `deposit_due_at`, anyone may close the invoice with
+    /// [`expire_invoice`].
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidInvoiceId`], [`ContractError::InvalidAsset`],
+    ///   [`ContractError::InvalidAmount`] — as for [`record_payment`], with
+    ///   `total` as the amount; also `InvalidAmount` if `deposit_bps` is out
+    ///   of range or rounds the deposit down to 0
+    /// - [`ContractError::InvoiceScheduleExists`] — `invoice_id` already has one
+    /// - [`ContractError::PaymentAlreadyRecorded`] — `invoice_id` was already
+    ///   paid, on Stellar, externally or on another chain
+    pub fn issue_deposit_invoice(
+        env: Env,
+        invoice_id: String,
+        asset_code: String,
+        asset_issuer: String,
+        total: i128,
+        deposit_bps: u32,
+        deposit_due_at: Option<u64>,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
 
-    /// Return the [`PaymentRecord`] for `invoice_id`.
+        let asset = validate_payment(&env, &invoice_id, &asset_code, &asset_issuer, total)?;
+        let deposit = total
+            .checked_mul(deposit_bps as i128)
+            .map(|d| d / 10_000)
+            .unwrap_or(0);
+        if deposit_bps > 10_000 || deposit <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if has_schedule(&env, &invoice_id) {
+            return Err(ContractError::InvoiceScheduleExists);
+        }
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+
+        let invoice = DepositInvoice {
+            asset,
+            total,
+            deposit,
+            deposit_due_at,
+            paid: 0,
+            status: DepositStatus::DepositDue,
+        };
+        set_deposit_invoice(&env, &invoice_id, &invoice);
+        bump_entry_count(&env, KeyFamily::DepositInvoice);
+        emit_deposit_invoice_issued(&env, invoice_id, invoice);
+        Ok(())
+    }
+
+    /// Close deposit invoice `invoice_id` whose deposit is still due after
+    /// its `deposit_due_at`. Callable by anyone, so keepers can run routine
+    /// expiries without the admin key. Emits `invoice_expired`. If a
+    /// [`KeeperBounty`] is set, `keeper` is paid it from the keeper pool.
     ///
-    /// Returns [`ContractError::PaymentNotFound`] if nothing has been recorded.
-    /// Use [`has_payment`] first if existence is uncertain.
-    pub fn get_payment(env: Env, invoice_id: String) -> Result<PaymentRecord, ContractError> {
-        get_payment(&env, &invoice_id)
+    /// Until this is called a late deposit is still accepted; afterwards
+    /// [`record_invoice_payment`] fails with `InvoiceExpired`. Partial
+    /// deposit payments stay recorded and can be refunded as usual.
+    ///
+    /// ## Errors
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::InvoiceScheduleNotFound`] — no deposit invoice
+    /// - [`ContractError::InvoiceNotExpirable`] — no deadline, deadline not
+    ///   yet passed, or the deposit is paid (or it already expired)
+    pub fn expire_invoice(
+        env: Env,
+        invoice_id: String,
+        keeper: Option<Address>,
+    ) -> Result<(), ContractError> {
+        require_not_paused(&env)?;
+        let mut invoice = get_deposit_invoice(&env, &invoice_id)?;
+        let past_due = invoice
+            .deposit_due_at
+            .is_some_and(|due_at| env.ledger().timestamp() > due_at);
+        if invoice.status != DepositStatus::DepositDue || !past_due {
+            return Err(ContractError::InvoiceNotExpirable);
+        }
+
+        invoice.status = DepositStatus::Expired;
+        set_deposit_invoice(&env, &invoice_id, &invoice);
+        emit_invoice_expired(&env, invoice_id, invoice.paid);
+        if let Some(keeper) = keeper {
+            reward(&env, &keeper);
+        }
+        Ok(())
     }
 
-    /// Return `true` if a payment has been recorded for `invoice_id`.
-    pub fn has_payment(env: Env, invoice_id: String) -> bool {
-        has_payment(&env, &invoice_id)
+    /// Create an invoice payable in installments. `schedule` lists each
+    /// installment as `(amount, due_at)`, with strictly increasing ledger
+    /// timestamps. Admin only. Emits `installment_plan_issued`.
+    ///
+    /// The invoice is paid through [`record_invoice_payment`], which fills
+    /// the installments in due order.
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidInvoiceId`], [`ContractError::InvalidAsset`] —
+    ///   as for [`record_payment`]
+    /// - [`ContractError::InvalidAmount`] — an installment amount ≤ 0, or the
+    ///   total overflows
+    /// - [`ContractError::InvalidSchedule`] — no installments, more than
+    ///   `MAX_INSTALLMENTS`, or due dates out of order
+    /// - [`ContractError::InvoiceScheduleExists`] — `invoice_id` already has one
+    /// - [`ContractError::PaymentAlreadyRecorded`] — `invoice_id` was already
+    ///   paid, on Stellar, externally or on another chain
+    pub fn issue_installment_plan(
+        env: Env,
+        invoice_id: String,
+        asset_code: String,
+        asset_issuer: String,
+        schedule: Vec<(i128, u64)>,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        if schedule.is_empty() || schedule.len() > MAX_INSTALLMENTS {
+            return Err(ContractError::InvalidSchedule);
+        }
+        let mut installments = Vec::new(&env);
+        let mut total: i128 = 0;
+        let mut last_due_at = 0;
+        for (amount, due_at) in schedule.iter() {
+            if amount <= 0 {
+                return Err(ContractError::InvalidAmount);
+            }
+            if due_at <= last_due_at {
+                return Err(ContractError::InvalidSchedule);
+            }
+            total = total.checked_add(amount).ok_or(ContractError::InvalidAmount)?;
+            last_due_at = due_at;
+            installments.push_back(Installment {
+                amount,
+                due_at,
+                paid: 0,
+            });
+        }
+        let asset = validate_payment(&env, &invoice_id, &asset_code, &asset_issuer, total)?;
+        if has_schedule(&env, &invoice_id) {
+            return Err(ContractError::InvoiceScheduleExists);
+        }
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+
+        let plan = InstallmentPlan {
+            asset,
+            installments,
+        };
+        set_installment_plan(&env, &invoice_id, &plan);
+        bump_entry_count(&env, KeyFamily::InstallmentPlan);
+        emit_installment_plan_issued(&env, invoice_id, plan);
+        Ok(())
     }
 
-    /// Return the total number of payments recorded in this contract instance.
-    pub fn payment_count(env: Env) -> u32 {
-        get_count(&env)
+    /// Record payment `payment_id` and apply it to the deposit invoice or
+    /// installment plan of `invoice_id`.
+    ///
+    /// The payment is stored and checked exactly like [`record_payment`]
+    /// under `payment_id`, its own idempotency key. Terms acceptance is
+    /// checked against `invoice_id`. Emits `payment_recorded`, then one
+    /// event per milestone reached: `deposit_paid` when a deposit invoice's
+    /// deposit is covered, `installment_paid` for each installment
+    /// completed, and `balance_paid` once the invoice is paid in full.
+    ///
+    /// ## Errors
+    /// - Every error of [`record_payment`], for `payment_id`
+    /// - [`ContractError::PaymentAlreadyRecorded`] — `invoice_id` itself was
+    ///   already paid, on Stellar, externally or on another chain
+    /// - [`ContractError::InvoiceScheduleNotFound`] — `invoice_id` has no
+    ///   deposit invoice or installment plan
+    /// - [`ContractError::InvalidAsset`] — paid in another asset than invoiced
+    /// - [`ContractError::InvoiceOverpaid`] — over the outstanding balance
+    pub fn record_invoice_payment(
+        env: Env,
+        invoice_id: String,
+        payment_id: String,
+        payer: MuxedAddress,
+        asset_code: String,
+        asset_issuer: String,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+        let (payer, payer_mux_id) = (payer.address(), payer.id());
+
+        let asset = validate_payment(&env, &payment_id, &asset_code, &asset_issuer, amount)?;
+        if is_settled(&env, &payment_id) || is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+        let scheduled = apply_payment(&env, &invoice_id, &asset, amount)?;
+        check_payer(&env, &payer)?;
+        check_terms(&env, &invoice_id, &payer)?;
+        check_preauthorization(&env, &invoice_id, &payer, &asset, amount)?;
+        consume_volume(&env, &payer, &asset, amount)?;
+
+        let record = PaymentRecord {
+            invoice_id: payment_id.clone(),
+            payer,
+            payer_mux_id,
+            asset,
+            amount,
+            timestamp: env.ledger().timestamp(),
+        };
+        check_validator(&env, &record)?;
+        let (gross, paid) = scheduled.totals();
+        let tax = split_payment(&env, &invoice_id, gross, paid - amount, amount);
+        commit_taxed_payment(&env, record, tax);
+        scheduled.commit(&env, invoice_id, payment_id);
+
+        Ok(())
     }
 
-    // Admin
+    /// Record a payment keyed by `invoice_hash`, a 32-byte hash of the
+    /// backend's invoice id (e.g. SHA-256 of its UUID), instead of an
+    /// `invoice_id` string.
+    ///
+    /// Fixed-size keys are cheaper to store and cannot be abused with long
+    /// ids. Hashed payments share the payment counter, screening, volume
+    /// limits and circuit breaker with [`record_payment`], but live in their
+    /// own key space and are read with [`get_hashed_payment`]. Receipts, FX
+    /// rates and merchant links are keyed by `invoice_id` and are not
+    /// available in this mode. Emits `hashed_payment_recorded` with
+    /// `invoice_hash` as a topic.
+    ///
+    /// The validator and observers are not called either: their interfaces
+    /// take a [`PaymentRecord`], whose `invoice_id` a hashed payment does
+    /// not have and is meant to keep off-chain.
+    ///
+    /// ## Errors
+    /// - Every error of [`record_payment`] except `InvalidInvoiceId` and
+    ///   `PaymentRejected`
+    /// - [`ContractError::PaymentAlreadyRecorded`] — `invoice_hash` already on-chain
+    pub fn record_hashed_payment(
+        env: Env,
+        invoice_hash: BytesN<32>,
+        payer: Address,
+        asset_code: String,
+        asset_issuer: String,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
 
-    /// Return the current admin address.
+        let asset = validate_transfer(&env, &asset_code, &asset_issuer, amount)?;
+        if has_hashed_payment(&env, &invoice_hash) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+        check_payer(&env, &payer)?;
+        consume_volume(&env, &payer, &asset, amount)?;
+
+        let record = HashedPaymentRecord {
+            invoice_hash,
+            payer,
+            asset,
+            amount,
+            timestamp: env.ledger().timestamp(),
+        };
+        set_hashed_payment(&env, &record);
+        bump_entry_count(&env, KeyFamily::HashedPayment);
+        add_received(&env, &record.asset, amount);
+        add_payment(&env, &record.asset, amount, 0, record.timestamp);
+        bump_count(&env);
+        emit_hashed_payment_recorded(&env, record);
+        track_payment(&env, amount);
+
+        Ok(())
+    }
+
+    /// Record a payment discovered late, stating when the customer actually
+    /// paid. Same as [`record_payment`]; the record's `timestamp` stays the
+    /// ledger time it was recorded at, and `paid_at` is stored beside it
+    /// (read it with [`paid_at`]). Emits `payment_backdated` after
+    /// `payment_recorded`, with `backdated: true` when `paid_at` is earlier.
     ///
-    /// Returns [`ContractError::NotInitialized`] if the contract has not been
-    /// initialised yet.
-    pub fn admin(env: Env) -> Result<Address, ContractError> {
-        get_admin(&env)
+    /// ## Errors
+    /// - Every error of [`record_payment`]
+    /// - [`ContractError::InvalidTimestamp`] — `paid_at` is in the future
+    pub fn record_backdated_payment(
+        env: Env,
+        invoice_id: String,
+        payer: MuxedAddress,
+        asset_code: String,
+        asset_issuer: String,
+        amount: i128,
+        paid_at: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+        let (payer, payer_mux_id) = (payer.address(), payer.id());
+
+        let asset = validate_payment(&env, &invoice_id, &asset_code, &asset_issuer, amount)?;
+        let recorded_at = env.ledger().timestamp();
+        if paid_at > recorded_at {
+            return Err(ContractError::InvalidTimestamp);
+        }
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+        check_payer(&env, &payer)?;
+        check_terms(&env, &invoice_id, &payer)?;
+        check_preauthorization(&env, &invoice_id, &payer, &asset, amount)?;
+        check_accepted_asset(&env, &invoice_id, &asset, amount)?;
+        consume_volume(&env, &payer, &asset, amount)?;
+
+        let record = PaymentRecord {
+            invoice_id: invoice_id.clone(),
+            payer,
+            payer_mux_id,
+            asset,
+            amount,
+            timestamp: recorded_at,
+        };
+        check_validator(&env, &record)?;
+        commit_payment(&env, record);
+        if paid_at < recorded_at {
+            set_paid_at(&env, &invoice_id, paid_at);
+        }
+        emit_payment_backdated(&env, invoice_id, paid_at, recorded_at);
+
+        Ok(())
     }
 
-    /// Transfer admin rights to `new_admin`.
+    /// Record a cross-border payment delivered through SEP-31 anchors,
+    /// together with its SEP-31 transaction id and both anchors, so the
+    /// settlement can be matched to the anchors' own records.
     ///
-    /// The **current admin** must authorise this call.
+    /// The Stellar leg is recorded exactly like [`record_payment`]. The
+    /// [`Remittance`] is stored beside it (read it with [`remittance`], or
+    /// go back from the transaction id with [`remittance_invoice`]) and
+    /// emitted as `remittance_recorded` after `payment_recorded`.
     ///
-    /// Returns [`ContractError::NotInitialized`] if the contract has not been
-    /// initialised yet.
-    pub fn set_admin(env: Env, new_admin: Address) -> Result<(), ContractError> {
-        let current = get_admin(&env)?;
-        // Both the current admin (authorising the transfer out) AND the new
-        // admin (consenting to receive the role) must sign this transaction.
-        // This prevents accidentally transferring to an address that can never
-        // produce a valid signature.
-        current.require_auth();
-        new_admin.require_auth();
-        set_admin(&env, &new_admin);
+    /// ## Errors
+    /// - Every error of [`record_payment`]
+    /// - [`ContractError::InvalidRemittance`] — a field of `remittance` is
+    ///   empty, or its `transaction_id` is too long
+    /// - [`ContractError::PaymentAlreadyRecorded`] — the SEP-31 transaction
+    ///   already settled an invoice
+    pub fn record_remittance_payment(
+        env: Env,
+        invoice_id: String,
+        payer: MuxedAddress,
+        asset_code: String,
+        asset_issuer: String,
+        amount: i128,
+        remittance: Remittance,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+        let (payer, payer_mux_id) = (payer.address(), payer.id());
+
+        let asset = validate_payment(&env, &invoice_id, &asset_code, &asset_issuer, amount)?;
+        if validate_id(&remittance.transaction_id).is_err()
+            || remittance.sending_anchor.is_empty()
+            || remittance.receiving_anchor.is_empty()
+        {
+            return Err(ContractError::InvalidRemittance);
+        }
+        if is_settled(&env, &invoice_id)
+            || get_remittance_invoice(&env, &remittance.transaction_id).is_some()
+        {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+        check_payer(&env, &payer)?;
+        check_terms(&env, &invoice_id, &payer)?;
+        check_preauthorization(&env, &invoice_id, &payer, &asset, amount)?;
+        check_accepted_asset(&env, &invoice_id, &asset, amount)?;
+        consume_volume(&env, &payer, &asset, amount)?;
+
+        let record = PaymentRecord {
+            invoice_id: invoice_id.clone(),
+            payer,
+            payer_mux_id,
+            asset,
+            amount,
+            timestamp: env.ledger().timestamp(),
+        };
+        check_validator(&env, &record)?;
+        commit_payment(&env, record);
+        set_remittance(&env, &invoice_id, &remittance);
+        bump_entry_count(&env, KeyFamily::RemittanceInvoice);
+        emit_remittance_recorded(&env, invoice_id, remittance);
+
         Ok(())
     }
+
+    /// Copy payments recorded by a previous deployment into this one, keeping
+    /// each record's payer, asset, amount and original `timestamp`. Admin
+    /// only. Emits one `payment_imported` per record and returns how many
+    /// were imported.
+    ///
+    /// Imports are history, not new payments: they are not screened,
+    /// counted against volume limits or the circuit breaker, and mint no
+    /// receipt. They do count in [`payment_count`] and [`totals`], and like
+    /// every [`PaymentRecord`] each is checked by the validator and sent to
+    /// observers. The batch is all-or-nothing; keep it within the write
+    /// budget.
+    ///
+    /// ## Errors
+    /// - [`ContractError::NotInitialized`]
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::InvalidInvoiceId`], [`ContractError::InvalidAsset`],
+    ///   [`ContractError::InvalidAmount`] — a record fails the checks of
+    ///   [`record_payment`]
+    /// - [`ContractError::PaymentAlreadyRecorded`] — an `invoice_id` is
+    ///   already recorded, on Stellar, externally or on another chain, or
+    ///   repeated in the batch
+    /// - [`ContractError::PaymentRejected`] — the validator rejected a record
+    pub fn import_payments(env: Env, records: Vec<PaymentRecord>) -> Result<u32, ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        for record in records.iter() {
+            let (code, issuer) = match &record.asset {
+                Asset::Native => (String::from_str(&env, "XLM"), String::from_str(&env, "")),
+                Asset::Token(code, issuer) => (code.clone(), issuer.clone()),
+            };
+            // Stored in canonical form, e.g. `Token("XLM", "")` as `Native`.
+            let asset = validate_payment(&env, &record.invoice_id, &code, &issuer, record.amount)?;
+            let record = PaymentRecord { asset, ..record };
+            if is_settled(&env, &record.invoice_id) {
+                return Err(ContractError::PaymentAlreadyRecorded);
+            }
+            check_validator(&env, &record)?;
+
+            set_payment(&env, &record);
+            index_payment(&env, &record.invoice_id);
+            add_received(&env, &record.asset, record.amount);
+            add_payment(&env, &record.asset, record.amount, 0, record.timestamp);
+            bump_count(&env);
+            emit_payment_imported(&env, record.clone());
+            notify_observers(&env, &record);
+        }
+
+        Ok(records.len())
+    }
+
+    /// Record an invoice settled **off Stellar** (bank transfer, card, another
+    /// network), so the contract stays the ledger of record for every
+    /// invoice. Admin only. Emits `external_payment_recorded`.
+    ///
+    /// The entry is a distinct [`ExternalPaymentRecord`]: it is never
+    /// returned by [`get_payment`], counted in [`payment_count`] or fed to
+    /// screening, volume limits or the circuit breaker, since no Stellar
+    /// payer or asset is involved. An invoice already paid on Stellar cannot
+    /// also be recorded as settled externally.
+    ///
+    /// ## Errors
+    /// - [`ContractError::NotInitialized`]
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::InvalidInvoiceId`] — `invoice_id` is empty or too
+    ///   long, or `reference` is empty
+    /// - [`ContractError::InvalidAmount`] — `amount` ≤ 0
+    /// - [`ContractError::PaymentAlreadyRecorded`] — `invoice_id` already
+    ///   recorded, on Stellar, externally or on another chain
+    pub fn record_external_payment(
+        env: Env,
+        invoice_id: String,
+        rail: Symbol,
+        reference: String,
+        amount: i128,
+        currency: Symbol,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        validate_id(&invoice_id)?;
+        if reference.is_empty() {
+            return Err(ContractError::InvalidInvoiceId);
+        }
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+
+        let record = ExternalPaymentRecord {
+            invoice_id,
+            rail,
+            reference,
+            amount,
+            currency,
+            timestamp: env.ledger().timestamp(),
+        };
+        set_external_payment(&env, &record);
+        bump_entry_count(&env, KeyFamily::ExternalPayment);
+        emit_external_payment_recorded(&env, record);
+
+        Ok(())
+    }
+
+    /// Record an invoice paid **on another blockchain** (an EVM chain,
+    /// Bitcoin) and relayed by a partner. Admin only. Emits
+    /// `chain_payment_recorded` with `chain_id` as a topic.
+    ///
+    /// `chain_id` is a CAIP-2 id such as `eip155:1`, and the asset is a
+    /// [`ChainAsset`], so no Stellar address or asset is assumed. Like
+    /// [`record_external_payment`], the [`ChainPaymentRecord`] stays apart
+    /// from Stellar payments: it is not returned by [`get_payment`] nor fed
+    /// to screening, volume limits or the circuit breaker.
+    ///
+    /// ## Errors
+    /// - [`ContractError::NotInitialized`]
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::InvalidInvoiceId`] — `invoice_id` is empty or too
+    ///   long, or `chain_id` or `tx_ref` is empty
+    /// - [`ContractError::InvalidAsset`] — `asset.asset_ref` is empty
+    /// - [`ContractError::InvalidAmount`] — `amount` ≤ 0
+    /// - [`ContractError::PaymentAlreadyRecorded`] — `invoice_id` already
+    ///   recorded, on Stellar, externally or on another chain
+    pub fn record_chain_payment(
+        env: Env,
+        invoice_id: String,
+        chain_id: String,
+        tx_ref: String,
+        payer: Option<String>,
+        asset: ChainAsset,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        validate_id(&invoice_id)?;
+        if chain_id.is_empty() || tx_ref.is_empty() {
+            return Err(ContractError::InvalidInvoiceId);
+        }
+        if asset.asset_ref.is_empty() {
+            return Err(ContractError::InvalidAsset);
+        }
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+
+        let record = ChainPaymentRecord {
+            invoice_id,
+            chain_id,
+            tx_ref,
+            payer,
+            asset,
+            amount,
+            timestamp: env.ledger().timestamp(),
+        };
+        set_chain_payment(&env, &record);
+        bump_entry_count(&env, KeyFamily::ChainPayment);
+        emit_chain_payment_recorded(&env, record);
+
+        Ok(())
+    }
+
+    /// Attach `tax` to `invoice_id`, replace it, or remove it with `None`.
+    /// Admin only. Emits `invoice_tax_updated`.
+    ///
+    /// Every payment then recorded for the invoice, including each payment
+    /// towards its deposit invoice or installment plan, stores its
+    /// [`TaxSplit`] (see [`payment_tax`]), emits `payment_taxed` and adds its
+    /// tax to [`daily_totals`] and [`monthly_totals`]. The invoice's tax is
+    /// allocated across partial payments in proportion to what they pay.
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidInvoiceId`] — `invoice_id` is empty or too long
+    /// - [`ContractError::InvalidTax`] — see [`tax::validate_tax`]; the amount
+    ///   is checked against the deposit invoice or installment plan total
+    /// - [`ContractError::PaymentAlreadyRecorded`] — the invoice is already paid
+    pub fn set_invoice_tax(
+        env: Env,
+        invoice_id: String,
+        tax: Option<InvoiceTax>,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+        validate_id(&invoice_id)?;
+        if let Some(tax) = &tax {
+            validate_tax(tax, invoice_total(&env, &invoice_id))?;
+        }
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+
+        set_invoice_tax(&env, &invoice_id, &tax);
+        emit_invoice_tax_updated(&env, invoice_id, tax);
+        Ok(())
+    }
+
+    /// Let `invoice_id` be paid in any one of several assets, each with the
+    /// amount expected in it, e.g. 100 USDC or 950 XLM. `assets` lists
+    /// `(asset_code, asset_issuer, amount)`; an empty list lifts the
+    /// restriction. Admin only. Emits `accepted_assets_updated`.
+    ///
+    /// A payment recorded for the invoice must then be in a listed asset and
+    /// for exactly its amount, whichever asset the payer chose.
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidInvoiceId`] — `invoice_id` is empty or too long
+    /// - [`ContractError::InvalidAsset`] — a malformed or repeated asset, or
+    ///   more than [`storage::MAX_ACCEPTED_ASSETS`]
+    /// - [`ContractError::InvalidAmount`] — an amount ≤ 0
+    /// - [`ContractError::InvoiceScheduleExists`] — deposit invoices and
+    ///   installment plans are paid in their own asset
+    /// - [`ContractError::PaymentAlreadyRecorded`] — the invoice is already paid
+    pub fn set_accepted_assets(
+        env: Env,
+        invoice_id: String,
+        assets: Vec<(String, String, i128)>,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        validate_id(&invoice_id)?;
+        if assets.len() > MAX_ACCEPTED_ASSETS {
+            return Err(ContractError::InvalidAsset);
+        }
+        let mut accepted = Map::new(&env);
+        for (asset_code, asset_issuer, amount) in assets.iter() {
+            let asset = validate_transfer(&env, &asset_code, &asset_issuer, amount)?;
+            if accepted.contains_key(asset.clone()) {
+                return Err(ContractError::InvalidAsset);
+            }
+            accepted.set(asset, amount);
+        }
+        if has_schedule(&env, &invoice_id) {
+            return Err(ContractError::InvoiceScheduleExists);
+        }
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+
+        set_accepted_assets(&env, &invoice_id, &accepted);
+        emit_accepted_assets_updated(&env, invoice_id, accepted);
+        Ok(())
+    }
+
+    /// Attach an opaque metadata blob to `invoice_id`, replace it, or remove
+    /// it with `None`. Admin only. Emits `metadata_updated`.
+    ///
+    /// The contract never interprets the blob. It is meant for sensitive
+    /// fields encrypted off-chain to the merchant's key, so new fields do
+    /// not need a record schema change. The invoice does not have to be paid
+    /// yet.
+    ///
+    /// ## Errors
+    /// - [`ContractError::NotInitialized`]
+    /// - [`ContractError::InvalidInvoiceId`] — `invoice_id` is empty or too long
+    /// - [`ContractError::MetadataTooLarge`] — over [`storage::MAX_METADATA_LEN`] bytes
+    pub fn set_metadata(
+        env: Env,
+        invoice_id: String,
+        blob: Option<Bytes>,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        validate_id(&invoice_id)?;
+        if matches!(&blob, Some(b) if b.len() > MAX_METADATA_LEN) {
+            return Err(ContractError::MetadataTooLarge);
+        }
+        set_metadata(&env, &invoice_id, &blob);
+        emit_metadata_updated(&env, invoice_id);
+        Ok(())
+    }
+
+    /// Report a **candidate** payment that the backend has seen but not yet
+    /// matched against Horizon.
+    ///
+    /// The provisional record lives in temporary storage for
+    /// [`storage::PENDING_TTL`] ledgers. It costs no persistent rent and is
+    /// never counted in [`payment_count`]; if it is not confirmed with
+    /// [`confirm_pending_payment`] before it expires, it simply vanishes.
+    ///
+    /// Takes the same parameters and applies the same validation as
+    /// [`record_payment`]. Emits a `pending_payment_reported` event.
+    ///
+    /// ## Errors
+    /// - Every error of [`record_payment`]
+    /// - [`ContractError::PaymentAlreadyPending`] — an unexpired candidate
+    ///   already exists for `invoice_id`
+    pub fn report_pending_payment(
+        env: Env,
+        invoice_id: String,
+        payer: MuxedAddress,
+        asset_code: String,
+        asset_issuer: String,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+        let (payer, payer_mux_id) = (payer.address(), payer.id());
+
+        let asset = validate_payment(&env, &invoice_id, &asset_code, &asset_issuer, amount)?;
+
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+        if has_pending_payment(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyPending);
+        }
+        check_payer(&env, &payer)?;
+        check_terms(&env, &invoice_id, &payer)?;
+        check_preauthorization(&env, &invoice_id, &payer, &asset, amount)?;
+        check_accepted_asset(&env, &invoice_id, &asset, amount)?;
+
+        let record = PaymentRecord {
+            invoice_id,
+            payer,
+            payer_mux_id,
+            asset,
+            amount,
+            timestamp: env.ledger().timestamp(),
+        };
+        set_pending_payment(&env, &record);
+        bump_pending_count(&env);
+        emit_pending_payment_reported(&env, record);
+
+        Ok(())
+    }
+
+    /// Promote a pending candidate to a permanent [`PaymentRecord`].
+    ///
+    /// The stored record keeps the `timestamp` of the original report, i.e.
+    /// when the backend first observed the payment. Behaves like
+    /// [`record_payment`] from here on: persists the record, increments the
+    /// counter and emits `payment_recorded`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::NotInitialized`] — contract was never initialised
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::PendingPaymentNotFound`] — no live candidate
+    /// - [`ContractError::PaymentAlreadyRecorded`] — `invoice_id` already
+    ///   recorded, on Stellar, externally or on another chain
+    /// - [`ContractError::PayerBlocked`], [`ContractError::PayerNotVerified`],
+    ///   [`ContractError::PayerHighRisk`] — the payer was blocked or
+    ///   re-screened, or the policy changed, since the report
+    /// - [`ContractError::TermsNotAccepted`] — terms are required and the
+    ///   payer has not accepted them
+    /// - [`ContractError::LimitExceeded`] — over the payer's 24 h volume
+    ///   limit, counted at confirmation time
+    pub fn confirm_pending_payment(env: Env, invoice_id: String) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        let record = get_pending_payment(&env, &invoice_id)?;
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+        check_payer(&env, &record.payer)?;
+        check_terms(&env, &invoice_id, &record.payer)?;
+        check_preauthorization(&env, &invoice_id, &record.payer, &record.asset, record.amount)?;
+        check_accepted_asset(&env, &invoice_id, &record.asset, record.amount)?;
+        consume_volume(&env, &record.payer, &record.asset, record.amount)?;
+        check_validator(&env, &record)?;
+
+        remove_pending_payment(&env, &invoice_id);
+        commit_payment(&env, record);
+
+        Ok(())
+    }
+
+    /// Drop a pending candidate before it expires (e.g. the Horizon match
+    /// failed). Admin only.
+    ///
+    /// Returns [`ContractError::PendingPaymentNotFound`] if there is nothing
+    /// to discard.
+    pub fn discard_pending_payment(env: Env, invoice_id: String) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        if !has_pending_payment(&env, &invoice_id) {
+            return Err(ContractError::PendingPaymentNotFound);
+        }
+        remove_pending_payment(&env, &invoice_id);
+        Ok(())
+    }
+
+    /// Record a refund of `amount` (in the payment's asset) against the
+    /// payment of `invoice_id`. Admin only. Emits `payment_refunded`.
+    ///
+    /// Refunds may be partial and repeated until their total reaches the
+    /// recorded amount; see [`net_amount`]. The payment record itself is
+    /// unchanged.
+    ///
+    /// ## Errors
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::InvalidAmount`] — `amount` ≤ 0
+    /// - [`ContractError::PaymentNotFound`] / [`ContractError::PaymentCompacted`]
+    ///   — no full record for `invoice_id`
+    /// - [`ContractError::RefundExceedsPayment`] — refunds would exceed the
+    ///   recorded amount
+    pub fn record_refund(env: Env, invoice_id: String, amount: i128) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        let (_, refunded_total) = apply_refund(&env, &invoice_id, amount)?;
+        emit_payment_refunded(&env, invoice_id, amount, refunded_total);
+        Ok(())
+    }
+
+    /// Record a refund of `amount` against `invoice_id` sent to `recipient`
+    /// instead of the original payer (e.g. the customer's new wallet).
+    /// Needs the admin's **and** the original payer's authorisation. Emits
+    /// `redirected_refund` instead of `payment_refunded`.
+    ///
+    /// Counts towards the same refunded total as [`record_refund`].
+    ///
+    /// ## Errors
+    /// - Every error of [`record_refund`]
+    pub fn record_redirected_refund(
+        env: Env,
+        invoice_id: String,
+        amount: i128,
+        recipient: Address,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        let (record, refunded_total) = apply_refund(&env, &invoice_id, amount)?;
+        record.payer.require_auth();
+        emit_redirected_refund(
+            &env,
+            invoice_id,
+            record.payer,
+            recipient,
+            amount,
+            refunded_total,
+        );
+        Ok(())
+    }
+
+    /// Flag a chargeback on the payment recorded for `invoice_id`: the card
+    /// payment that funded its on-ramp was disputed with the card network
+    /// under case `reference`. Admin only. Emits `chargeback_flagged`.
+    ///
+    /// The flag is informational: the payment record, its refunds and the
+    /// totals are unchanged until [`resolve_chargeback`] decides the case,
+    /// and even an upheld chargeback only records the outcome.
+    ///
+    /// ## Errors
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::InvalidInvoiceId`] — `invoice_id` is empty or too
+    ///   long, or `reference` is empty
+    /// - [`ContractError::PaymentNotFound`] / [`ContractError::PaymentCompacted`]
+    ///   — no full record for `invoice_id`
+    /// - [`ContractError::ChargebackExists`] — the invoice already has one
+    pub fn flag_chargeback(
+        env: Env,
+        invoice_id: String,
+        reference: String,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        validate_id(&invoice_id)?;
+        if reference.is_empty() {
+            return Err(ContractError::InvalidInvoiceId);
+        }
+        get_payment(&env, &invoice_id)?;
+        if get_chargeback(&env, &invoice_id).is_some() {
+            return Err(ContractError::ChargebackExists);
+        }
+
+        let chargeback = Chargeback {
+            reference: reference.clone(),
+            status: ChargebackStatus::Open,
+            flagged_at: env.ledger().timestamp(),
+            resolved_at: None,
+        };
+        set_chargeback(&env, &invoice_id, &chargeback);
+        emit_chargeback_flagged(&env, invoice_id, reference);
+        Ok(())
+    }
+
+    /// Resolve the open chargeback on `invoice_id`: `upheld` if the funds
+    /// went back to the cardholder, otherwise overturned in the merchant's
+    /// favour. Admin only. Emits `chargeback_resolved`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::ChargebackNotFound`] — nothing was flagged
+    /// - [`ContractError::ChargebackResolved`] — already resolved
+    pub fn resolve_chargeback(
+        env: Env,
+        invoice_id: String,
+        upheld: bool,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        let mut chargeback =
+            get_chargeback(&env, &invoice_id).ok_or(ContractError::ChargebackNotFound)?;
+        if chargeback.status != ChargebackStatus::Open {
+            return Err(ContractError::ChargebackResolved);
+        }
+
+        chargeback.status = if upheld {
+            ChargebackStatus::Upheld
+        } else {
+            ChargebackStatus::Overturned
+        };
+        chargeback.resolved_at = Some(env.ledger().timestamp());
+        set_chargeback(&env, &invoice_id, &chargeback);
+        emit_chargeback_resolved(&env, invoice_id, chargeback.status);
+        Ok(())
+    }
+
+    /// Move the payment recorded for `invoice_id` to `status` once the
+    /// backend has checked it against the payment operation on Horizon.
+    /// Admin only. Emits `reconciliation_updated` with the previous and new
+    /// status; setting the current status again is a no-op.
+    ///
+    /// Any transition is allowed, so a `ManualReview` can be cleared to
+    /// `Matched`, or a record sent back to `Unverified` for another pass.
+    ///
+    /// ## Errors
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::PaymentNotFound`] / [`ContractError::PaymentCompacted`]
+    ///   — no full record for `invoice_id`
+    pub fn set_reconciliation(
+        env: Env,
+        invoice_id: String,
+        status: Reconciliation,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        get_payment(&env, &invoice_id)?;
+        let previous = get_reconciliation(&env, &invoice_id);
+        if previous == status {
+            return Ok(());
+        }
+        set_reconciliation(&env, &invoice_id, status);
+        emit_reconciliation_updated(&env, invoice_id, previous, status);
+        Ok(())
+    }
+
+    /// Issue credit note `credit_id`: `amount` of credit owed to `payer`,
+    /// e.g. for a returned item or a billing error, which the payer can
+    /// spend on any of their invoices with [`apply_credit_note`]. Admin
+    /// only. Emits `credit_note_issued`.
+    ///
+    /// Unlike a refund, no funds go back to the payer and no payment record
+    /// changes: the credit lowers what is still due.
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidInvoiceId`], [`ContractError::InvalidAsset`],
+    ///   [`ContractError::InvalidAmount`] — as for [`record_payment`], with
+    ///   `credit_id` as the id
+    /// - [`ContractError::CreditNoteExists`]
+    pub fn issue_credit_note(
+        env: Env,
+        credit_id: String,
+        payer: Address,
+        asset_code: String,
+        asset_issuer: String,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        let asset = validate_payment(&env, &credit_id, &asset_code, &asset_issuer, amount)?;
+        if has_credit_note(&env, &credit_id) {
+            return Err(ContractError::CreditNoteExists);
+        }
+
+        let note = CreditNote {
+            payer,
+            asset,
+            amount,
+            remaining: amount,
+            applied: Map::new(&env),
+            status: CreditNoteStatus::Open,
+            issued_at: env.ledger().timestamp(),
+        };
+        set_credit_note(&env, &credit_id, &note);
+        emit_credit_note_issued(&env, credit_id, note);
+        Ok(())
+    }
+
+    /// Apply `amount` of credit note `credit_id` to the deposit invoice or
+    /// installment plan of `invoice_id`, lowering its amount due as a
+    /// payment would. Signed by the note's payer. A note can be split
+    /// across several invoices until its credit is used up.
+    ///
+    /// Emits `credit_note_applied`, then the milestone events of
+    /// [`record_invoice_payment`] with `credit_id` as the payment id.
+    ///
+    /// ## Errors
+    /// - [`ContractError::CreditNoteNotFound`]
+    /// - [`ContractError::CreditNoteClosed`] — fully applied or voided
+    /// - [`ContractError::InvalidAmount`] — `amount` ≤ 0
+    /// - [`ContractError::CreditExceedsRemaining`]
+    /// - [`ContractError::PayerMismatch`] — another payer accepted the
+    ///   invoice's terms or preauthorized it
+    /// - [`ContractError::InvoiceScheduleNotFound`],
+    ///   [`ContractError::InvalidAsset`], [`ContractError::InvoiceOverpaid`],
+    ///   [`ContractError::InvoiceExpired`] — as for [`record_invoice_payment`]
+    pub fn apply_credit_note(
+        env: Env,
+        credit_id: String,
+        invoice_id: String,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        require_not_paused(&env)?;
+        let mut note = get_credit_note(&env, &credit_id)?;
+        note.payer.require_auth();
+        if note.status != CreditNoteStatus::Open {
+            return Err(ContractError::CreditNoteClosed);
+        }
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if amount > note.remaining {
+            return Err(ContractError::CreditExceedsRemaining);
+        }
+        if invoice_payer(&env, &invoice_id).is_some_and(|payer| payer != note.payer) {
+            return Err(ContractError::PayerMismatch);
+        }
+        let scheduled = apply_payment(&env, &invoice_id, &note.asset, amount)?;
+
+        note.remaining -= amount;
+        let applied = note.applied.get(invoice_id.clone()).unwrap_or(0) + amount;
+        note.applied.set(invoice_id.clone(), applied);
+        if note.remaining == 0 {
+            note.status = CreditNoteStatus::Applied;
+        }
+        set_credit_note(&env, &credit_id, &note);
+        emit_credit_note_applied(
+            &env,
+            credit_id.clone(),
+            invoice_id.clone(),
+            amount,
+            note.remaining,
+        );
+        scheduled.commit(&env, invoice_id, credit_id);
+        Ok(())
+    }
+
+    /// Void credit note `credit_id`: the credit not applied yet lapses, and
+    /// what was applied stays applied. Admin only. Emits
+    /// `credit_note_voided`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::CreditNoteNotFound`]
+    /// - [`ContractError::CreditNoteClosed`] — fully applied or already voided
+    pub fn void_credit_note(env: Env, credit_id: String) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        let mut note = get_credit_note(&env, &credit_id)?;
+        if note.status != CreditNoteStatus::Open {
+            return Err(ContractError::CreditNoteClosed);
+        }
+        let lapsed = note.remaining;
+        note.remaining = 0;
+        note.status = CreditNoteStatus::Voided;
+        set_credit_note(&env, &credit_id, &note);
+        emit_credit_note_voided(&env, credit_id, lapsed);
+        Ok(())
+    }
+
+    /// Accept quote `quote_id`, converting it into invoice `invoice_id`
+    /// with the quoted terms: the quote's `terms_hash` is stored as the
+    /// invoice's [`TermsAcceptance`] and the quote is marked accepted, in
+    /// one call. Signed by the quote's payer. Emits `quote_accepted`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::QuoteNotFound`] — no quote `quote_id`
+    /// - [`ContractError::QuoteAlreadyAccepted`] — it was already accepted
+    /// - [`ContractError::QuoteExpired`] — its `expires_at` has passed
+    /// - [`ContractError::PaymentAlreadyRecorded`] — the invoice is already paid
+    pub fn accept_quote(env: Env, quote_id: String) -> Result<(), ContractError> {
+        require_not_paused(&env)?;
+        let mut quote = get_quote(&env, &quote_id)?;
+        quote.payer.require_auth();
+
+        if quote.accepted {
+            return Err(ContractError::QuoteAlreadyAccepted);
+        }
+        if env.ledger().timestamp() >= quote.expires_at {
+            return Err(ContractError::QuoteExpired);
+        }
+        if is_settled(&env, &quote.invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+
+        let acceptance = TermsAcceptance {
+            payer: quote.payer.clone(),
+            terms_hash: quote.terms_hash.clone(),
+            timestamp: env.ledger().timestamp(),
+        };
+        set_terms_acceptance(&env, &quote.invoice_id, &acceptance);
+        quote.accepted = true;
+        set_quote(&env, &quote_id, &quote);
+        emit_quote_accepted(&env, quote_id, quote.invoice_id, quote.payer);
+        Ok(())
+    }
+
+    /// Record that `payer` has received and acknowledged `invoice_id`: an
+    /// on-chain read receipt for dunning and disputes. Signed by the payer.
+    /// Emits `invoice_acknowledged`.
+    ///
+    /// Once the invoice names its payer, by accepted terms or a
+    /// preauthorization, only that payer may acknowledge it, and their
+    /// acknowledgement replaces one made by anyone else before. Otherwise
+    /// only the first acknowledgement is kept; repeating it is a no-op that
+    /// emits nothing. An [`Acknowledgement`] does not affect recording and
+    /// never identifies the invoice's payer.
+    ///
+    /// ## Errors
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::InvalidInvoiceId`] — `invoice_id` is empty or too long
+    /// - [`ContractError::PaymentAlreadyRecorded`] — the invoice is already paid
+    /// - [`ContractError::PayerMismatch`] — another payer accepted the
+    ///   invoice's terms or preauthorized it
+    pub fn acknowledge_invoice(
+        env: Env,
+        invoice_id: String,
+        payer: Address,
+    ) -> Result<(), ContractError> {
+        payer.require_auth();
+        require_not_paused(&env)?;
+
+        validate_id(&invoice_id)?;
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+        let named = invoice_payer(&env, &invoice_id);
+        if named.as_ref().is_some_and(|named| *named != payer) {
+            return Err(ContractError::PayerMismatch);
+        }
+        if get_acknowledgement(&env, &invoice_id)
+            .is_some_and(|ack| named.is_none() || ack.payer == payer)
+        {
+            return Ok(());
+        }
+
+        let ack = Acknowledgement {
+            payer: payer.clone(),
+            timestamp: env.ledger().timestamp(),
+        };
+        set_acknowledgement(&env, &invoice_id, &ack);
+        emit_invoice_acknowledged(&env, invoice_id, payer);
+        Ok(())
+    }
+
+    /// Record that `payer` accepted the commercial terms of `invoice_id`,
+    /// identified by `terms_hash` (e.g. SHA-256 of the signed PDF). Signed
+    /// by the payer, not the admin. Emits `terms_accepted`.
+    ///
+    /// The payer who accepted may accept again, replacing the acceptance,
+    /// e.g. after the terms were revised; no other payer can, nor anyone but
+    /// the payer who preauthorized `invoice_id`. While
+    /// [`terms_required`] is `true`, payments for `invoice_id` are only
+    /// recorded from the payer who accepted.
+    ///
+    /// ## Errors
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::InvalidInvoiceId`] — `invoice_id` is empty or too long
+    /// - [`ContractError::PaymentAlreadyRecorded`] — the invoice is already
+    ///   paid; terms must be accepted before funds move
+    /// - [`ContractError::PayerMismatch`] — another payer already accepted
+    ///   the terms of `invoice_id` or preauthorized it
+    pub fn accept_terms(
+        env: Env,
+        invoice_id: String,
+        payer: Address,
+        terms_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        payer.require_auth();
+        require_not_paused(&env)?;
+
+        validate_id(&invoice_id)?;
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+        if invoice_payer(&env, &invoice_id).is_some_and(|named| named != payer) {
+            return Err(ContractError::PayerMismatch);
+        }
+
+        let acceptance = TermsAcceptance {
+            payer: payer.clone(),
+            terms_hash: terms_hash.clone(),
+            timestamp: env.ledger().timestamp(),
+        };
+        set_terms_acceptance(&env, &invoice_id, &acceptance);
+        emit_terms_accepted(&env, invoice_id, payer, terms_hash);
+        Ok(())
+    }
+
+    /// Record that `payer` intends to pay `invoice_id` in the given asset,
+    /// up to `max_amount`. Signed by the payer, not the admin. Emits
+    /// `invoice_preauthorized`.
+    ///
+    /// Together with the admin's later `record_payment` this gives a
+    /// two-sided agreement: once preauthorized, a payment for `invoice_id`
+    /// is only recorded from `payer`, in that asset, for at most
+    /// `max_amount`. The payer may preauthorize again, replacing the
+    /// previous one; no other payer can, nor anyone but the payer who
+    /// accepted the invoice's terms.
+    ///
+    /// ## Errors
+    /// - [`ContractError::Paused`] — writes are paused
+    /// - [`ContractError::InvalidInvoiceId`] — `invoice_id` is empty or too long
+    /// - [`ContractError::InvalidAsset`] — malformed asset
+    /// - [`ContractError::InvalidAmount`] — `max_amount` ≤ 0
+    /// - [`ContractError::PaymentAlreadyRecorded`] — the invoice is already
+    ///   paid
+    /// - [`ContractError::PayerMismatch`] — another payer already
+    ///   preauthorized `invoice_id` or accepted its terms
+    pub fn preauthorize(
+        env: Env,
+        invoice_id: String,
+        payer: Address,
+        asset_code: String,
+        asset_issuer: String,
+        max_amount: i128,
+    ) -> Result<(), ContractError> {
+        payer.require_auth();
+        require_not_paused(&env)?;
+
+        let asset = validate_payment(&env, &invoice_id, &asset_code, &asset_issuer, max_amount)?;
+        if is_settled(&env, &invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+        if invoice_payer(&env, &invoice_id).is_some_and(|named| named != payer) {
+            return Err(ContractError::PayerMismatch);
+        }
+
+        let preauth = Preauthorization {
+            payer,
+            asset,
+            max_amount,
+            timestamp: env.ledger().timestamp(),
+        };
+        set_preauthorization(&env, &invoice_id, &preauth);
+        emit_invoice_preauthorized(&env, invoice_id, &preauth);
+        Ok(())
+    }
+
+    // Read
+
+    /// Return the [`PaymentRecord`] for `invoice_id`.
+    ///
+    /// Returns [`ContractError::PaymentNotFound`] if nothing has been recorded.
+    /// Use [`has_payment`] first if existence is uncertain.
+    ///
+    /// ## Archived records
+    /// If the record exists but its persistent entry has been archived, this
+    /// call never returns `PaymentNotFound`. Simulation marks the key for
+    /// restore, so a submitted invocation restores the entry and returns the
+    /// original record (the test host does the same). An invocation whose
+    /// footprint does not mark it aborts with a host storage error — the
+    /// client's `Error::EntryArchived` — never a `ContractError`.
+    pub fn get_payment(env: Env, invoice_id: String) -> Result<PaymentRecord, ContractError> {
+        get_payment(&env, &invoice_id)
+    }
+
+    /// Return the [`PaymentRecord`] for `invoice_id`, or `None` if nothing has
+    /// been recorded or the record has been compacted.
+    ///
+    /// Same as [`get_payment`] without the error, for callers that fetch and
+    /// check existence in one simulation. Archived records behave as for
+    /// [`get_payment`].
+    pub fn get_payment_opt(env: Env, invoice_id: String) -> Option<PaymentRecord> {
+        get_payment(&env, &invoice_id).ok()
+    }
+
+    /// Look up several invoices at once. Every id in `invoice_ids` maps to
+    /// its [`PaymentRecord`], or to `None` as for [`get_payment_opt`].
+    ///
+    /// Meant for simulation: each id is one ledger read, so keep batches
+    /// within the network's per-transaction read limit.
+    pub fn find_payments(env: Env, invoice_ids: Vec<String>) -> Map<String, Option<PaymentRecord>> {
+        let mut found = Map::new(&env);
+        for invoice_id in invoice_ids.iter() {
+            let record = get_payment(&env, &invoice_id).ok();
+            found.set(invoice_id, record);
+        }
+        found
+    }
+
+    /// Return the pending candidate for `invoice_id`.
+    ///
+    /// Returns [`ContractError::PendingPaymentNotFound`] if none is live.
+    pub fn get_pending_payment(
+        env: Env,
+        invoice_id: String,
+    ) -> Result<PaymentRecord, ContractError> {
+        get_pending_payment(&env, &invoice_id)
+    }
+
+    /// Return `true` if a payment has been recorded for `invoice_id`.
+    ///
+    /// An archived record is **not** reported as `false`: it is restored and
+    /// reported as `true`, or the call aborts; see [`get_payment`].
+    pub fn has_payment(env: Env, invoice_id: String) -> bool {
+        has_payment(&env, &invoice_id)
+    }
+
+    /// Return the [`DepositInvoice`] for `invoice_id`, including its
+    /// `status` and what has been paid.
+    ///
+    /// Returns [`ContractError::InvoiceScheduleNotFound`] if there is none.
+    pub fn get_deposit_invoice(
+        env: Env,
+        invoice_id: String,
+    ) -> Result<DepositInvoice, ContractError> {
+        get_deposit_invoice(&env, &invoice_id)
+    }
+
+    /// Return the [`InstallmentPlan`] for `invoice_id`, with what has been
+    /// paid towards each installment.
+    ///
+    /// Returns [`ContractError::InvoiceScheduleNotFound`] if there is none.
+    pub fn get_installment_plan(
+        env: Env,
+        invoice_id: String,
+    ) -> Result<InstallmentPlan, ContractError> {
+        get_installment_plan(&env, &invoice_id)
+    }
+
+    /// Return the [`InstallmentStatus`] of each installment of `invoice_id`
+    /// at the current ledger time, in due order. An installment not paid in
+    /// full is `Overdue` once its `due_at` has passed.
+    ///
+    /// Returns [`ContractError::InvoiceScheduleNotFound`] if there is no plan.
+    pub fn installment_statuses(
+        env: Env,
+        invoice_id: String,
+    ) -> Result<Vec<InstallmentStatus>, ContractError> {
+        let plan = get_installment_plan(&env, &invoice_id)?;
+        let now = env.ledger().timestamp();
+        let mut statuses = Vec::new(&env);
+        for installment in plan.installments.iter() {
+            statuses.push_back(installment.status(now));
+        }
+        Ok(statuses)
+    }
+
+    /// Return what is owed on the deposit invoice or installment plan of
+    /// `invoice_id`: the unpaid principal, and the interest accrued on
+    /// overdue installments at the current ledger time (see
+    /// [`set_interest_rate`]).
+    ///
+    /// Interest is computed on demand and never stored, so the result
+    /// changes with the ledger timestamp.
+    ///
+    /// Returns [`ContractError::InvoiceScheduleNotFound`] if there is none.
+    pub fn get_amount_due(env: Env, invoice_id: String) -> Result<AmountDue, ContractError> {
+        amount_due(&env, &invoice_id)
+    }
+
+    /// Return the annual interest rate on overdue installments, in basis
+    /// points (0 = no interest).
+    pub fn interest_rate(env: Env) -> u32 {
+        get_interest_rate_bps(&env)
+    }
+
+    /// Return the total refunded against `invoice_id` (0 if none).
+    pub fn refunded_total(env: Env, invoice_id: String) -> i128 {
+        get_refunded_total(&env, &invoice_id)
+    }
+
+    /// Return the recorded amount of `invoice_id` minus its refunds.
+    ///
+    /// Returns the same errors as [`get_payment`].
+    pub fn net_amount(env: Env, invoice_id: String) -> Result<i128, ContractError> {
+        let record = get_payment(&env, &invoice_id)?;
+        Ok(record.amount - get_refunded_total(&env, &invoice_id))
+    }
+
+    /// Return how far the payment recorded for `invoice_id` was verified
+    /// against Horizon; `Unverified` until [`set_reconciliation`] says
+    /// otherwise.
+    ///
+    /// Returns the same errors as [`get_payment`].
+    pub fn reconciliation(env: Env, invoice_id: String) -> Result<Reconciliation, ContractError> {
+        get_payment(&env, &invoice_id)?;
+        Ok(get_reconciliation(&env, &invoice_id))
+    }
+
+    /// Return the chargeback flagged on `invoice_id`, if any.
+    pub fn chargeback(env: Env, invoice_id: String) -> Option<Chargeback> {
+        get_chargeback(&env, &invoice_id)
+    }
+
+    /// Return the assets `invoice_id` accepts and the amount expected in
+    /// each, or `None` if any asset is accepted.
+    pub fn accepted_assets(env: Env, invoice_id: String) -> Option<Map<Asset, i128>> {
+        get_accepted_assets(&env, &invoice_id)
+    }
+
+    /// Return both legs of the payment recorded for `invoice_id`, or `None`
+    /// if it was paid in the invoiced asset.
+    pub fn conversion(env: Env, invoice_id: String) -> Option<Conversion> {
+        get_conversion(&env, &invoice_id)
+    }
+
+    /// Return the SEP-31 identifiers of the payment recorded for
+    /// `invoice_id`, or `None` if it was not a SEP-31 payment.
+    pub fn remittance(env: Env, invoice_id: String) -> Option<Remittance> {
+        get_remittance(&env, &invoice_id)
+    }
+
+    /// Return the `invoice_id` settled by SEP-31 transaction
+    /// `transaction_id`, if any.
+    pub fn remittance_invoice(env: Env, transaction_id: String) -> Option<String> {
+        get_remittance_invoice(&env, &transaction_id)
+    }
+
+    /// Return the tax attached to `invoice_id`, if any.
+    pub fn invoice_tax(env: Env, invoice_id: String) -> Option<InvoiceTax> {
+        get_invoice_tax(&env, &invoice_id)
+    }
+
+    /// Return the net/tax split of the payment recorded for `invoice_id`, or
+    /// `None` if it was recorded without an [`InvoiceTax`].
+    pub fn payment_tax(env: Env, invoice_id: String) -> Option<TaxSplit> {
+        get_payment_tax(&env, &invoice_id)
+    }
+
+    /// Return the [`CreditNote`] stored under `credit_id`.
+    ///
+    /// Returns [`ContractError::CreditNoteNotFound`] if there is none.
+    pub fn get_credit_note(env: Env, credit_id: String) -> Result<CreditNote, ContractError> {
+        get_credit_note(&env, &credit_id)
+    }
+
+    /// Return the [`Quote`] stored under `quote_id`.
+    ///
+    /// Returns [`ContractError::QuoteNotFound`] if there is none.
+    pub fn get_quote(env: Env, quote_id: String) -> Result<Quote, ContractError> {
+        get_quote(&env, &quote_id)
+    }
+
+    /// Return the [`Acknowledgement`] of `invoice_id`, or `None` if the payer
+    /// has not acknowledged it.
+    pub fn acknowledgement(env: Env, invoice_id: String) -> Option<Acknowledgement> {
+        get_acknowledgement(&env, &invoice_id)
+    }
+
+    /// Return the payer's [`Preauthorization`] of `invoice_id`, if any.
+    pub fn preauthorization(env: Env, invoice_id: String) -> Option<Preauthorization> {
+        get_preauthorization(&env, &invoice_id)
+    }
+
+    /// Return the amount of an asset from which payments need a
+    /// [`Preauthorization`], if set.
+    pub fn preauth_threshold(
+        env: Env,
+        asset_code: String,
+        asset_issuer: String,
+    ) -> Result<Option<i128>, ContractError> {
+        let asset = build_asset(&env, &asset_code, &asset_issuer)?;
+        Ok(get_preauth_threshold(&env, &asset))
+    }
+
+    /// Return the payer's [`TermsAcceptance`] for `invoice_id`, if any.
+    pub fn terms_acceptance(env: Env, invoice_id: String) -> Option<TermsAcceptance> {
+        get_terms_acceptance(&env, &invoice_id)
+    }
+
+    /// Return `true` while payments require the payer's terms acceptance.
+    pub fn terms_required(env: Env) -> bool {
+        is_terms_required(&env)
+    }
+
+    /// Return the summary left of `invoice_id` by `compact_old_records`, or
+    /// `None` if it was not compacted (or never recorded).
+    pub fn compacted_payment(env: Env, invoice_id: String) -> Option<CompactedPayment> {
+        get_compacted_payment(&env, &invoice_id)
+    }
+
+    /// Return the [`ExternalPaymentRecord`] for `invoice_id`.
+    ///
+    /// Returns [`ContractError::PaymentNotFound`] if the invoice was not
+    /// recorded as settled externally.
+    pub fn get_external_payment(
+        env: Env,
+        invoice_id: String,
+    ) -> Result<ExternalPaymentRecord, ContractError> {
+        get_external_payment(&env, &invoice_id)
+    }
+
+    /// Return `true` if `invoice_id` was recorded as settled externally.
+    pub fn has_external_payment(env: Env, invoice_id: String) -> bool {
+        has_external_payment(&env, &invoice_id)
+    }
+
+    /// Return the [`ChainPaymentRecord`] for `invoice_id`.
+    ///
+    /// Returns [`ContractError::PaymentNotFound`] if it was not recorded as
+    /// paid on another chain.
+    pub fn get_chain_payment(
+        env: Env,
+        invoice_id: String,
+    ) -> Result<ChainPaymentRecord, ContractError> {
+        get_chain_payment(&env, &invoice_id)
+    }
+
+    /// Return `true` if `invoice_id` was recorded as paid on another chain.
+    pub fn has_chain_payment(env: Env, invoice_id: String) -> bool {
+        has_chain_payment(&env, &invoice_id)
+    }
+
+    /// Return the [`HashedPaymentRecord`] for `invoice_hash`.
+    ///
+    /// Returns [`ContractError::PaymentNotFound`] if nothing has been recorded.
+    /// Archived records behave as for [`get_payment`].
+    pub fn get_hashed_payment(
+        env: Env,
+        invoice_hash: BytesN<32>,
+    ) -> Result<HashedPaymentRecord, ContractError> {
+        get_hashed_payment(&env, &invoice_hash)
+    }
+
+    /// Return `true` if a payment has been recorded for `invoice_hash`.
+    pub fn has_hashed_payment(env: Env, invoice_hash: BytesN<32>) -> bool {
+        has_hashed_payment(&env, &invoice_hash)
+    }
+
+    /// Prove that the hashed payment committed to as
+    /// `sha256(invoice_id || salt)` belongs to `invoice_id`, by returning it.
+    ///
+    /// This is the reveal half of privacy mode: the backend records with
+    /// [`record_hashed_payment`] using that commitment as `invoice_hash`, so
+    /// the invoice id never appears on the public ledger, and later hands
+    /// `invoice_id` and `salt` to whoever needs the link. Call it by
+    /// simulation to keep them off the ledger.
+    ///
+    /// Returns [`ContractError::PaymentNotFound`] if no payment was recorded
+    /// under the commitment (wrong id, wrong salt or nothing recorded).
+    pub fn reveal(
+        env: Env,
+        invoice_id: String,
+        salt: BytesN<32>,
+    ) -> Result<HashedPaymentRecord, ContractError> {
+        get_hashed_payment(&env, &invoice_commitment(&env, &invoice_id, &salt))
+    }
+
+    /// Return the metadata blob attached to `invoice_id`, if any.
+    pub fn get_metadata(env: Env, invoice_id: String) -> Option<Bytes> {
+        get_metadata(&env, &invoice_id)
+    }
+
+    /// Return when the customer paid `invoice_id`: the `paid_at` given to
+    /// [`record_backdated_payment`], or the record's `timestamp` otherwise.
+    ///
+    /// Returns the [`get_payment`] error if nothing was recorded.
+    pub fn paid_at(env: Env, invoice_id: String) -> Result<u64, ContractError> {
+        match get_paid_at(&env, &invoice_id) {
+            Some(paid_at) => Ok(paid_at),
+            None => Ok(get_payment(&env, &invoice_id)?.timestamp),
+        }
+    }
+
+    /// Return the [`PaymentReceipt`] of `invoice_id`: the recorded payment
+    /// with its refunds, payment time, merchant and receipt number.
+    ///
+    /// Returns the same errors as [`get_payment`].
+    pub fn get_receipt(env: Env, invoice_id: String) -> Result<PaymentReceipt, ContractError> {
+        let record = get_payment(&env, &invoice_id)?;
+        Ok(PaymentReceipt {
+            contract: env.current_contract_address(),
+            refunded: get_refunded_total(&env, &invoice_id),
+            paid_at: get_paid_at(&env, &invoice_id).unwrap_or(record.timestamp),
+            recorded_at: record.timestamp,
+            merchant_id: get_payment_merchant(&env, &invoice_id),
+            receipt_number: get_receipt_number(&env, &invoice_id),
+            invoice_id,
+            payer: record.payer,
+            payer_mux_id: record.payer_mux_id,
+            asset: record.asset,
+            amount: record.amount,
+        })
+    }
+
+    /// Return the `merchant_id` that `invoice_id` was recorded for, or `None`
+    /// if it was recorded without one (or not at all).
+    pub fn payment_merchant(env: Env, invoice_id: String) -> Option<String> {
+        get_payment_merchant(&env, &invoice_id)
+    }
+
+    /// Return the receipt number `invoice_id` was issued by its merchant, or
+    /// `None` if it was recorded without one (or not at all).
+    pub fn receipt_number(env: Env, invoice_id: String) -> Option<u64> {
+        get_receipt_number(&env, &invoice_id)
+    }
+
+    /// Return how many receipt numbers `merchant_id` was issued, which is
+    /// also its latest one.
+    pub fn receipt_count(env: Env, merchant_id: String) -> u64 {
+        get_receipt_count(&env, &merchant_id)
+    }
+
+    /// Return the compliance flags of `payer` (all `false` if never screened).
+    pub fn payer_flags(env: Env, payer: Address) -> PayerFlags {
+        get_payer_flags(&env, &payer)
+    }
+
+    /// Return `true` if `payer` is blocked.
+    pub fn is_payer_blocked(env: Env, payer: Address) -> bool {
+        is_payer_blocked(&env, &payer)
+    }
+
+    /// Return the rolling 24 h volume limit per payer for an asset, or `None`
+    /// if it is unlimited.
+    ///
+    /// Returns [`ContractError::InvalidAsset`] for a malformed asset.
+    pub fn volume_limit(
+        env: Env,
+        asset_code: String,
+        asset_issuer: String,
+    ) -> Result<Option<i128>, ContractError> {
+        let asset = build_asset(&env, &asset_code, &asset_issuer)?;
+        Ok(get_volume_limit(&env, &asset))
+    }
+
+    /// Return what `payer` paid in an asset over the rolling 24 h window.
+    ///
+    /// Only tracked while a limit is configured for the asset.
+    pub fn payer_volume(
+        env: Env,
+        payer: Address,
+        asset_code: String,
+        asset_issuer: String,
+    ) -> Result<i128, ContractError> {
+        let asset = build_asset(&env, &asset_code, &asset_issuer)?;
+        Ok(rolling_volume(&env, &payer, &asset))
+    }
+
+    /// Return the compliance policy in force.
+    pub fn compliance_policy(env: Env) -> CompliancePolicy {
+        get_compliance_policy(&env)
+    }
+
+    /// Return the USD rate captured when `invoice_id` was recorded, or `None`
+    /// if no oracle was configured or it had no fresh price.
+    pub fn payment_rate(env: Env, invoice_id: String) -> Option<FxRate> {
+        get_payment_rate(&env, &invoice_id)
+    }
+
+    /// Return the total number of payments recorded in this contract instance.
+    pub fn payment_count(env: Env) -> u32 {
+        get_count(&env)
+    }
+
+    /// Return the net amount received per asset: every recorded payment
+    /// (hashed ones included) minus refunds. External payments are not in
+    /// a Stellar asset and are left out.
+    ///
+    /// Kept up to date on every write, so month-end totals need one read
+    /// instead of an event replay.
+    pub fn totals(env: Env) -> Map<Asset, i128> {
+        get_received_totals(&env)
+    }
+
+    /// Return the count, amount and tax of payments recorded per asset on
+    /// `date`, given as days since the Unix epoch (UTC), i.e.
+    /// `timestamp / 86_400`.
+    ///
+    /// Buckets follow each record's `timestamp` (for imports, the original
+    /// one) and are not reduced by refunds.
+    pub fn daily_totals(env: Env, date: u32) -> Map<Asset, PeriodTotals> {
+        get_daily_totals(&env, date)
+    }
+
+    /// Return the count and amount of payments recorded per asset in
+    /// `month` (1–12) of `year`, UTC: the [`daily_totals`] of its days
+    /// summed, so it reads up to 31 entries. Empty for a month outside
+    /// 1–12.
+    pub fn monthly_totals(env: Env, year: u32, month: u32) -> Map<Asset, PeriodTotals> {
+        aggregates::monthly_totals(&env, year, month)
+    }
+
+    /// Export full payment records in recording order, `limit` sequence
+    /// numbers at a time, starting at `cursor` (0 for the first page).
+    /// Returns the records and the cursor of the next page, or `None` after
+    /// the last one.
+    ///
+    /// Meant to be simulated: a fresh indexer can rebuild its database from
+    /// contract state alone, without historical events. Hashed and
+    /// compacted payments have no full record here and are skipped, so a
+    /// page may be short. Keep `limit` within the read budget, e.g. 50.
+    ///
+    /// Returns [`ContractError::PaymentNotFound`] if an index entry points at
+    /// no record, which [`check_invariants`] reports as dangling.
+    pub fn export_page(
+        env: Env,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<(Vec<PaymentRecord>, Option<u32>), ContractError> {
+        export_page(&env, cursor, limit)
+    }
+
+    /// Return per-family entry counts and the configured TTL parameters.
+    ///
+    /// One read call gives ops everything needed to estimate rent and track
+    /// growth; see [`StorageStats`] for field semantics.
+    pub fn storage_stats(env: Env) -> StorageStats {
+        storage_stats(&env)
+    }
+
+    /// Check the payments with sequence numbers `from..from + limit` against
+    /// the contract's storage invariants and return an [`InvariantReport`].
+    ///
+    /// Permissionless and meant to be simulated: auditors can run it at any
+    /// ledger and walk the whole history in slices that fit the read budget.
+    /// It checks that the counter has not fallen behind the index, that
+    /// every index entry points at a full record, and that no record has a
+    /// non-positive amount or refunds over its amount. It also sums the
+    /// amounts per asset.
+    pub fn check_invariants(env: Env, from: u32, limit: u32) -> InvariantReport {
+        check_invariants(&env, from, limit)
+    }
+
+    // Maintenance
+
+    /// Extend the TTL of the record for `invoice_id` so it is not archived,
+    /// along with the entries recorded beside it (merchant, receipt number,
+    /// FX rate, payment time, refunds, tax split, conversion and remittance).
+    ///
+    /// Permissionless: anyone may pay to keep a record live. Does not emit an
+    /// event and does not change the record. If a [`KeeperBounty`] is set,
+    /// `keeper` is paid it from the keeper pool when the bump was needed:
+    /// once the record's TTL has fallen below `MIN_TTL`, i.e.
+    /// `KEEPER_COOLDOWN_TTL` ledgers (≈ 29 days) after it was recorded or
+    /// last bumped here. That payment emits `keeper_rewarded`. Reads also
+    /// extend a record's TTL but are not tracked, so the first bump after a
+    /// read that extended it may still be rewarded.
+    ///
+    /// Returns [`ContractError::PaymentNotFound`] if nothing has been recorded.
+    pub fn extend_payment_ttl(
+        env: Env,
+        invoice_id: String,
+        keeper: Option<Address>,
+    ) -> Result<(), ContractError> {
+        extend_payment_ttl(&env, &invoice_id)?;
+        reward_ttl_bump(&env, &invoice_id, keeper);
+        Ok(())
+    }
+
+    /// Return the bounty paid to keepers for maintenance calls, if any.
+    pub fn keeper_bounty(env: Env) -> Option<KeeperBounty> {
+        get_keeper_bounty(&env)
+    }
+
+    /// Set the [`KeeperBounty`] paid for [`expire_invoice`] and
+    /// [`extend_payment_ttl`], or turn it off with `None`. Admin only.
+    ///
+    /// Bounties are paid from the keeper pool of `bounty.token`, funded
+    /// with [`fund_keeper_pool`]. Calls still succeed, unrewarded, once it
+    /// runs dry.
+    ///
+    /// Returns [`ContractError::InvalidAmount`] if `amount` ≤ 0.
+    pub fn set_keeper_bounty(
+        env: Env,
+        bounty: Option<KeeperBounty>,
+        nonce: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        if matches!(&bounty, Some(b) if b.amount <= 0) {
+            return Err(ContractError::InvalidAmount);
+        }
+        set_keeper_bounty(&env, &bounty);
+        Ok(())
+    }
+
+    /// Return the balance of `token` left in the keeper pool.
+    pub fn keeper_pool(env: Env, token: Address) -> i128 {
+        get_keeper_pool(&env, &token)
+    }
+
+    /// Move `amount` of `token` from `funder` into this contract and add it
+    /// to the keeper pool that bounties are paid from. Anyone may fund it.
+    /// Emits `keeper_pool_funded`.
+    ///
+    /// Tokens sent to the contract by a plain transfer do not count.
+    ///
+    /// Returns [`ContractError::InvalidAmount`] if `amount` ≤ 0.
+    pub fn fund_keeper_pool(
+        env: Env,
+        funder: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        funder.require_auth();
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        token::Client::new(&env, &token).transfer(&funder, env.current_contract_address(), &amount);
+        set_keeper_pool(&env, &token, get_keeper_pool(&env, &token) + amount);
+        emit_keeper_pool_funded(&env, funder, token, amount);
+        Ok(())
+    }
+
+    // Admin
+
+    /// Return the receipts contract minted to on every recorded payment, or
+    /// `None` if receipts are disabled.
+    pub fn receipt_contract(env: Env) -> Option<Address> {
+        get_receipt_contract(&env)
+    }
+
+    /// Enable receipts by pointing at a deployed `receipts` contract, or
+    /// disable them with `None`. Admin only.
+    ///
+    /// The receipts contract must list this contract as its minter, otherwise
+    /// every subsequent `record_payment` fails.
+    pub fn set_receipt_contract(
+        env: Env,
+        receipt_contract: Option<Address>,
+        nonce: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_receipt_contract(&env, &receipt_contract);
+        Ok(())
+    }
+
+    /// Store the screening result for `payer`. Admin only. Emits
+    /// `payer_flags_updated`.
+    pub fn set_payer_flags(
+        env: Env,
+        payer: Address,
+        kyc_verified: bool,
+        high_risk: bool,
+        nonce: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+
+        let flags = PayerFlags {
+            kyc_verified,
+            high_risk,
+            updated_at: env.ledger().timestamp(),
+        };
+        set_payer_flags(&env, &payer, &flags);
+        emit_payer_flags_updated(&env, payer, flags);
+        Ok(())
+    }
+
+    /// Block `payer`: every write path rejects it with
+    /// [`ContractError::PayerBlocked`] until [`unblock_payer`]. Admin only.
+    /// Emits `payer_blocked`.
+    pub fn block_payer(env: Env, payer: Address, nonce: u64) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_payer_blocked(&env, &payer, true);
+        emit_payer_blocked(&env, payer);
+        Ok(())
+    }
+
+    /// Lift a block set by [`block_payer`]. Admin only. Emits
+    /// `payer_unblocked`.
+    pub fn unblock_payer(env: Env, payer: Address, nonce: u64) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_payer_blocked(&env, &payer, false);
+        emit_payer_unblocked(&env, payer);
+        Ok(())
+    }
+
+    /// Cap what one payer may pay in an asset over a rolling 24 h window, or
+    /// lift the cap with `None`. Admin only.
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidAsset`] — malformed asset
+    /// - [`ContractError::InvalidAmount`] — `max_amount` ≤ 0
+    pub fn set_volume_limit(
+        env: Env,
+        asset_code: String,
+        asset_issuer: String,
+        max_amount: Option<i128>,
+        nonce: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+
+        let asset = build_asset(&env, &asset_code, &asset_issuer)?;
+        if matches!(max_amount, Some(max) if max <= 0) {
+            return Err(ContractError::InvalidAmount);
+        }
+        set_volume_limit(&env, &asset, &max_amount);
+        Ok(())
+    }
+
+    /// Choose which payer flags block recording. Admin only.
+    ///
+    /// Applies to every later write, including confirming candidates that
+    /// were reported under an older policy.
+    pub fn set_compliance_policy(
+        env: Env,
+        policy: CompliancePolicy,
+        nonce: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_compliance_policy(&env, &policy);
+        Ok(())
+    }
+
+    /// Require a [`preauthorize`] from the payer for payments of at least
+    /// `threshold` in an asset, or lift the requirement with `None`. Admin
+    /// only. Preauthorizations given below the threshold still apply.
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidAsset`] — malformed asset
+    /// - [`ContractError::InvalidAmount`] — `threshold` ≤ 0
+    pub fn set_preauth_threshold(
+        env: Env,
+        asset_code: String,
+        asset_issuer: String,
+        threshold: Option<i128>,
+        nonce: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+
+        let asset = build_asset(&env, &asset_code, &asset_issuer)?;
+        if matches!(threshold, Some(min) if min <= 0) {
+            return Err(ContractError::InvalidAmount);
+        }
+        set_preauth_threshold(&env, &asset, &threshold);
+        Ok(())
+    }
+
+    /// Require (or stop requiring) that the payer called [`accept_terms`]
+    /// before a payment is recorded, reported or confirmed. Admin only.
+    /// Hashed payments have no `invoice_id` and are not affected.
+    pub fn set_terms_required(env: Env, required: bool, nonce: u64) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_terms_required(&env, required);
+        Ok(())
+    }
+
+    /// Set the annual interest rate, in basis points, accrued on the overdue
+    /// principal of installment plans. Admin only. `0` turns interest off.
+    ///
+    /// Simple interest, per installment, from its due date. It applies to
+    /// existing plans too, since [`get_amount_due`] computes it on demand.
+    ///
+    /// Returns [`ContractError::InvalidAmount`] above `MAX_INTEREST_RATE_BPS`
+    /// (100 %).
+    pub fn set_interest_rate(env: Env, rate_bps: u32, nonce: u64) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        if rate_bps > MAX_INTEREST_RATE_BPS {
+            return Err(ContractError::InvalidAmount);
+        }
+        set_interest_rate_bps(&env, rate_bps);
+        Ok(())
+    }
+
+    /// Replace the full records of payments recorded before `before_ts` with
+    /// a [`CompactedPayment`] (amount and record hash), visiting at most
+    /// `limit` payments oldest first. Admin only. Emits `records_compacted`
+    /// and returns how many were compacted.
+    ///
+    /// Call repeatedly with the same `before_ts` until it returns 0; each
+    /// call resumes where the last one stopped. Compacted invoices still
+    /// count as recorded ([`has_payment`] is `true`, re-recording fails) but
+    /// [`get_payment`] returns [`ContractError::PaymentCompacted`]. Archived
+    /// records in the batch must be restored first.
+    pub fn compact_old_records(
+        env: Env,
+        before_ts: u64,
+        limit: u32,
+        nonce: u64,
+    ) -> Result<u32, ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+
+        let compacted = compact_before(&env, before_ts, limit)?;
+        emit_records_compacted(&env, compacted, get_compact_cursor(&env));
+        Ok(compacted)
+    }
+
+    /// Return `true` while writes are paused.
+    pub fn is_paused(env: Env) -> bool {
+        is_paused(&env)
+    }
+
+    /// Return the circuit breaker thresholds, or `None` if it is off.
+    pub fn circuit_breaker(env: Env) -> Option<CircuitBreaker> {
+        get_circuit_breaker(&env)
+    }
+
+    /// Return what the circuit breaker counted in its current window.
+    pub fn breaker_window(env: Env) -> BreakerWindow {
+        get_breaker_window(&env)
+    }
+
+    /// Turn the circuit breaker on with `breaker`'s thresholds, or off with
+    /// `None`. Starts a fresh window. Admin only.
+    ///
+    /// Returns [`ContractError::InvalidAmount`] if `window_ledgers` is 0 or
+    /// `max_volume` ≤ 0.
+    pub fn set_circuit_breaker(
+        env: Env,
+        breaker: Option<CircuitBreaker>,
+        nonce: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        if matches!(&breaker, Some(b) if b.window_ledgers == 0 || b.max_volume <= 0) {
+            return Err(ContractError::InvalidAmount);
+        }
+        set_circuit_breaker(&env, &breaker);
+        set_breaker_window(&env, &BreakerWindow::default());
+        Ok(())
+    }
+
+    /// Pause all payment writes. Admin only. Emits `paused`.
+    pub fn pause(env: Env, nonce: u64) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_paused(&env, true);
+        emit_paused(&env, admin);
+        Ok(())
+    }
+
+    /// Resume writes after a manual pause or a tripped breaker, starting a
+    /// fresh breaker window. Admin only. Emits `resumed`.
+    pub fn resume(env: Env, nonce: u64) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_paused(&env, false);
+        set_breaker_window(&env, &BreakerWindow::default());
+        emit_resumed(&env, admin);
+        Ok(())
+    }
+
+    /// Return the issuer whose USDC is stored in compact form, if set.
+    pub fn usdc_issuer(env: Env) -> Option<String> {
+        get_usdc_issuer(&env)
+    }
+
+    /// Intern `USDC` issued by `issuer`: payments in it are stored as a tag
+    /// instead of a code and issuer, shrinking each record. Admin only; can
+    /// only be set once, since stored records refer to it.
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidAsset`] — `issuer` is not a valid `G...` strkey
+    /// - [`ContractError::UsdcIssuerAlreadySet`]
+    pub fn set_usdc_issuer(env: Env, issuer: String, nonce: u64) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        if get_usdc_issuer(&env).is_some() {
+            return Err(ContractError::UsdcIssuerAlreadySet);
+        }
+        build_asset(&env, &String::from_str(&env, "USDC"), &issuer)?;
+        set_usdc_issuer(&env, &issuer);
+        Ok(())
+    }
+
+    /// Return the price oracle rates are captured from, if any.
+    pub fn fx_oracle(env: Env) -> Option<Address> {
+        get_fx_oracle(&env)
+    }
+
+    /// Point at a Reflector-style price oracle quoting USD, or stop capturing
+    /// rates with `None`. Admin only.
+    pub fn set_fx_oracle(
+        env: Env,
+        oracle: Option<Address>,
+        nonce: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_fx_oracle(&env, &oracle);
+        Ok(())
+    }
+
+    /// Return the ed25519 public key authorizing `record_payment_signed`, if
+    /// any.
+    pub fn signing_key(env: Env) -> Option<BytesN<32>> {
+        get_signing_key(&env)
+    }
+
+    /// Set the ed25519 public key whose signatures [`record_payment_signed`]
+    /// accepts, or disable signed payments with `None`. Admin only.
+    pub fn set_signing_key(
+        env: Env,
+        key: Option<BytesN<32>>,
+        nonce: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_signing_key(&env, &key);
+        Ok(())
+    }
+
+    /// Return the validator contract consulted before every payment, if any.
+    pub fn validator(env: Env) -> Option<Address> {
+        get_validator(&env)
+    }
+
+    /// Register a contract implementing
+    /// [`PaymentValidator`](validation::PaymentValidator) whose
+    /// `validate(record)` must return `true` for any payment to be recorded,
+    /// or remove it with `None`. Admin only.
+    ///
+    /// Consulted after the built-in checks, so it only sees payments this
+    /// contract would accept. A validator that fails rejects the payment with
+    /// [`ContractError::PaymentRejected`], so clear it before retiring it.
+    pub fn set_validator(
+        env: Env,
+        validator: Option<Address>,
+        nonce: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_validator(&env, &validator);
+        Ok(())
+    }
+
+    /// Return the contracts notified of every recorded payment, in the order
+    /// they are called.
+    pub fn observers(env: Env) -> Vec<Address> {
+        get_observers(&env)
+    }
+
+    /// Register a contract implementing
+    /// [`PaymentObserver`](observer::PaymentObserver) to be called with
+    /// every payment recorded from now on. Admin only. Adding an observer
+    /// twice is a no-op.
+    ///
+    /// An observer that fails cannot block payments: its call is rolled back
+    /// and reported with `observer_failed`. Each observer still costs budget
+    /// on every payment, so at most [`MAX_OBSERVERS`] can be registered.
+    ///
+    /// ## Errors
+    /// - [`ContractError::TooManyObservers`] — `MAX_OBSERVERS` are registered.
+    pub fn add_observer(env: Env, observer: Address, nonce: u64) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        let mut observers = get_observers(&env);
+        if observers.contains(&observer) {
+            return Ok(());
+        }
+        if observers.len() >= MAX_OBSERVERS {
+            return Err(ContractError::TooManyObservers);
+        }
+        observers.push_back(observer);
+        set_observers(&env, &observers);
+        Ok(())
+    }
+
+    /// Stop notifying `observer`. Admin only. No-op if it is not registered.
+    pub fn remove_observer(env: Env, observer: Address, nonce: u64) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        let mut observers = get_observers(&env);
+        if let Some(index) = observers.first_index_of(&observer) {
+            observers.remove(index);
+            set_observers(&env, &observers);
+        }
+        Ok(())
+    }
+
+    /// Return the merchant registry used by `record_merchant_payment`, if any.
+    pub fn merchant_registry(env: Env) -> Option<Address> {
+        get_merchant_registry(&env)
+    }
+
+    /// Point at a deployed `merchant-registry` contract, or clear it with
+    /// `None` (which disables `record_merchant_payment`). Admin only.
+    pub fn set_merchant_registry(
+        env: Env,
+        registry: Option<Address>,
+        nonce: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_merchant_registry(&env, &registry);
+        Ok(())
+    }
+
+    /// Move `amount` of `token` collected for the merchant payment
+    /// `invoice_id` from the admin into this contract, adding it to the
+    /// merchant's unsettled balance. Admin only. Emits `payment_collected`.
+    ///
+    /// `token` must be the Stellar Asset Contract of the recorded asset and
+    /// `amount` the recorded amount. Each invoice is collected at most once.
+    /// Collected funds are only paid out by [`settle`], never as keeper
+    /// bounties.
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidAmount`] — `amount` ≤ 0
+    /// - [`ContractError::PaymentNotFound`] — not recorded with
+    ///   [`record_merchant_payment`]
+    /// - [`ContractError::AlreadyCollected`]
+    /// - [`ContractError::TokenMismatch`] / [`ContractError::AmountMismatch`]
+    /// - [`ContractError::BatchFull`] — settle the merchant first
+    pub fn collect(
+        env: Env,
+        invoice_id: String,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        require_not_paused(&env)?;
+
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let merchant_id =
+            get_payment_merchant(&env, &invoice_id).ok_or(ContractError::PaymentNotFound)?;
+        if is_collected(&env, &invoice_id) {
+            return Err(ContractError::AlreadyCollected);
+        }
+        let record = get_payment(&env, &invoice_id)?;
+        if token != asset_contract(&env, &record.asset)? {
+            return Err(ContractError::TokenMismatch);
+        }
+        if amount != record.amount {
+            return Err(ContractError::AmountMismatch);
+        }
+
+        token::Client::new(&env, &token).transfer(&admin, env.current_contract_address(), &amount);
+        payout::credit(&env, &merchant_id, &invoice_id, &token, amount)?;
+        emit_payment_collected(&env, merchant_id, invoice_id, token, amount);
+        Ok(())
+    }
+
+    /// Pay the unsettled balance of `merchant_id` to its payout address in
+    /// the merchant registry, in one transfer per token, and return the id
+    /// of the [`Settlement`] recorded. Emits `merchant_settled`.
+    ///
+    /// Admin only, unless the merchant set a [`PayoutSchedule`]: then anyone
+    /// may call it, but only on a release day and once per day, and it also
+    /// emits `payout_scheduled` with the next release day.
+    ///
+    /// ## Errors
+    /// - [`ContractError::OutsidePayoutWindow`]
+    /// - [`ContractError::NothingToSettle`]
+    /// - [`ContractError::MerchantRegistryNotSet`] / [`ContractError::UnknownMerchant`]
+    pub fn settle(env: Env, merchant_id: String) -> Result<u32, ContractError> {
+        require_not_paused(&env)?;
+        let next_release = match get_payout_schedule(&env, &merchant_id) {
+            Some(schedule) => Some(payout::enter_window(&env, &merchant_id, &schedule)?),
+            None => {
+                get_admin(&env)?.require_auth();
+                None
+            }
+        };
+
+        let settlement = payout::settle(&env, &merchant_id)?;
+        let id = settlement.id;
+        emit_merchant_settled(&env, settlement);
+        if let Some(day) = next_release {
+            emit_payout_scheduled(&env, merchant_id, day);
+        }
+        Ok(id)
+    }
+
+    /// Return the payout schedule of `merchant_id`, if it set one.
+    pub fn payout_schedule(env: Env, merchant_id: String) -> Option<PayoutSchedule> {
+        get_payout_schedule(&env, &merchant_id)
+    }
+
+    /// Have `merchant_id` paid out on `schedule`, or only by the admin again
+    /// with `None`. Signed by the merchant's payout address in the registry.
+    /// Emits `payout_schedule_updated`, then `payout_scheduled` with the
+    /// first release day.
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidSchedule`] — `every_days` is 0
+    /// - [`ContractError::MerchantRegistryNotSet`] / [`ContractError::UnknownMerchant`]
+    pub fn set_payout_schedule(
+        env: Env,
+        merchant_id: String,
+        schedule: Option<PayoutSchedule>,
+    ) -> Result<(), ContractError> {
+        merchant_payout(&env, &merchant_id)?.require_auth();
+        require_not_paused(&env)?;
+        if matches!(schedule, Some(schedule) if schedule.every_days == 0) {
+            return Err(ContractError::InvalidSchedule);
+        }
+
+        set_payout_schedule(&env, &merchant_id, &schedule);
+        emit_payout_schedule_updated(&env, merchant_id.clone(), schedule);
+        if let Some(schedule) = schedule {
+            let today = day_of(env.ledger().timestamp());
+            let day = payout::next_release_day(&schedule, today);
+            emit_payout_scheduled(&env, merchant_id, day);
+        }
+        Ok(())
+    }
+
+    /// Return the funds collected for `merchant_id` since its last
+    /// settlement, if any.
+    pub fn merchant_balance(env: Env, merchant_id: String) -> Option<MerchantBalance> {
+        get_merchant_balance(&env, &merchant_id)
+    }
+
+    /// Returns [`ContractError::SettlementNotFound`] if there is no
+    /// settlement `id`.
+    pub fn get_settlement(env: Env, id: u32) -> Result<Settlement, ContractError> {
+        get_settlement(&env, id)
+    }
+
+    /// Return the number of settlements made; ids run from 0 to this minus 1.
+    pub fn settlement_count(env: Env) -> u32 {
+        get_settlement_count(&env)
+    }
+
+    /// Return the contract's name and version with the admin-set
+    /// [`DeploymentInfo`], to tell deployments apart.
+    pub fn info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: String::from_str(&env, "invoice-payment"),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            deployment: get_deployment_info(&env),
+        }
+    }
+
+    /// Replace the [`DeploymentInfo`] returned by `info()`. Admin only.
+    pub fn set_info(env: Env, deployment: DeploymentInfo, nonce: u64) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_deployment_info(&env, &deployment);
+        Ok(())
+    }
+
+    /// Return the current admin address.
+    ///
+    /// Returns [`ContractError::NotInitialized`] if the contract has not been
+    /// initialised yet.
+    pub fn admin(env: Env) -> Result<Address, ContractError> {
+        get_admin(&env)
+    }
+
+    /// Return the nonce the current admin must pass to its next config
+    /// change or admin transfer. Starts at 0 and increments with each one.
+    ///
+    /// Returns [`ContractError::NotInitialized`] if the contract has not been
+    /// initialised yet.
+    pub fn admin_nonce(env: Env) -> Result<u64, ContractError> {
+        Ok(get_admin_nonce(&env, &get_admin(&env)?))
+    }
+
+    /// Transfer admin rights to `new_admin`.
+    ///
+    /// The **current admin** must authorise this call, passing its
+    /// [`admin_nonce`].
+    ///
+    /// ## Errors
+    /// - [`ContractError::NotInitialized`] — not initialised yet
+    /// - [`ContractError::InvalidNonce`] — `nonce` is not the current one
+    pub fn set_admin(env: Env, new_admin: Address, nonce: u64) -> Result<(), ContractError> {
+        let current = get_admin(&env)?;
+        // Both the current admin (authorising the transfer out) AND the new
+        // admin (consenting to receive the role) must sign this transaction.
+        // This prevents accidentally transferring to an address that can never
+        // produce a valid signature.
+        current.require_auth();
+        new_admin.require_auth();
+        use_admin_nonce(&env, &current, nonce)?;
+        set_admin(&env, &new_admin);
+        Ok(())
+    }
+}
+
+/// Entry points that take more arguments than clippy's `too_many_arguments`
+/// allows. `#[contractimpl]` also generates an args helper for each of them,
+/// which a `#[allow]` on the function itself does not reach, so the allow is
+/// scoped to this module instead.
+#[allow(clippy::too_many_arguments)]
+mod wide {
+    use super::*;
+
+    #[contractimpl]
+    impl InvoicePaymentContract {
+        /// Record a payment that reached the invoice in `asset_code` but that
+        /// the payer made in another asset, e.g. through a path payment: the
+        /// payer sent `sent_amount` of `sent_asset_code`, and `amount` arrived.
+        ///
+        /// The received leg is recorded exactly like [`record_payment`], so
+        /// every check, total and event applies to it. Both legs and the
+        /// effective rate are stored beside it as a [`Conversion`] (read it with
+        /// [`conversion`]) and emitted as `payment_converted` after
+        /// `payment_recorded`.
+        ///
+        /// ## Errors
+        /// - Every error of [`record_payment`]
+        /// - [`ContractError::InvalidAsset`] — the sent asset is malformed, or is
+        ///   the received asset
+        /// - [`ContractError::InvalidAmount`] — `sent_amount` ≤ 0
+        pub fn record_converted_payment(
+            env: Env,
+            invoice_id: String,
+            payer: MuxedAddress,
+            asset_code: String,
+            asset_issuer: String,
+            amount: i128,
+            sent_asset_code: String,
+            sent_asset_issuer: String,
+            sent_amount: i128,
+        ) -> Result<(), ContractError> {
+            let admin = get_admin(&env)?;
+            admin.require_auth();
+            require_not_paused(&env)?;
+            let (payer, payer_mux_id) = (payer.address(), payer.id());
+
+            let asset = validate_payment(&env, &invoice_id, &asset_code, &asset_issuer, amount)?;
+            let sent_asset =
+                validate_transfer(&env, &sent_asset_code, &sent_asset_issuer, sent_amount)?;
+            if sent_asset == asset {
+                return Err(ContractError::InvalidAsset);
+            }
+            let rate = amount
+                .checked_mul(CONVERSION_RATE_SCALE)
+                .ok_or(ContractError::InvalidAmount)?
+                / sent_amount;
+            if is_settled(&env, &invoice_id) {
+                return Err(ContractError::PaymentAlreadyRecorded);
+            }
+            check_payer(&env, &payer)?;
+            check_terms(&env, &invoice_id, &payer)?;
+            check_preauthorization(&env, &invoice_id, &payer, &asset, amount)?;
+            check_accepted_asset(&env, &invoice_id, &asset, amount)?;
+            consume_volume(&env, &payer, &asset, amount)?;
+
+            let record = PaymentRecord {
+                invoice_id: invoice_id.clone(),
+                payer,
+                payer_mux_id,
+                asset: asset.clone(),
+                amount,
+                timestamp: env.ledger().timestamp(),
+            };
+            check_validator(&env, &record)?;
+            commit_payment(&env, record);
+            let conversion = Conversion {
+                sent_asset,
+                sent_amount,
+                received_asset: asset,
+                received_amount: amount,
+                rate,
+            };
+            set_conversion(&env, &invoice_id, &conversion);
+            emit_payment_converted(&env, invoice_id, conversion);
+
+            Ok(())
+        }
+
+        /// Issue (or revise) quote `quote_id` for `payer`: an estimate that
+        /// converts into invoice `invoice_id` when the payer accepts it before
+        /// `expires_at`. Admin only. Emits `quote_issued`.
+        ///
+        /// ## Errors
+        /// - [`ContractError::InvalidInvoiceId`] — `quote_id` or `invoice_id` is
+        ///   empty or too long
+        /// - [`ContractError::InvalidAsset`] / [`ContractError::InvalidAmount`] —
+        ///   as for [`record_payment`]
+        /// - [`ContractError::QuoteExpired`] — `expires_at` is not in the future
+        /// - [`ContractError::QuoteAlreadyAccepted`] — `quote_id` was accepted
+        /// - [`ContractError::PaymentAlreadyRecorded`] — `invoice_id` is already paid
+        pub fn issue_quote(
+            env: Env,
+            quote_id: String,
+            invoice_id: String,
+            payer: Address,
+            asset_code: String,
+            asset_issuer: String,
+            amount: i128,
+            terms_hash: BytesN<32>,
+            expires_at: u64,
+        ) -> Result<(), ContractError> {
+            let admin = get_admin(&env)?;
+            admin.require_auth();
+            require_not_paused(&env)?;
+
+            validate_id(&quote_id)?;
+            let asset = validate_payment(&env, &invoice_id, &asset_code, &asset_issuer, amount)?;
+            if expires_at <= env.ledger().timestamp() {
+                return Err(ContractError::QuoteExpired);
+            }
+            if matches!(get_quote(&env, &quote_id), Ok(quote) if quote.accepted) {
+                return Err(ContractError::QuoteAlreadyAccepted);
+            }
+            if is_settled(&env, &invoice_id) {
+                return Err(ContractError::PaymentAlreadyRecorded);
+            }
+
+            let quote = Quote {
+                invoice_id,
+                payer,
+                asset,
+                amount,
+                terms_hash,
+                expires_at,
+                accepted: false,
+            };
+            set_quote(&env, &quote_id, &quote);
+            emit_quote_issued(&env, quote_id, quote);
+            Ok(())
+        }
+    }
+}
+
+/// Consume `nonce`, which must be `admin`'s current operation nonce.
+fn use_admin_nonce(env: &Env, admin: &Address, nonce: u64) -> Result<(), ContractError> {
+    if nonce != get_admin_nonce(env, admin) {
+        return Err(ContractError::InvalidNonce);
+    }
+    set_admin_nonce(env, admin, nonce + 1);
+    Ok(())
+}
+
+/// Whether `invoice_id` was recorded as paid on Stellar, externally or on
+/// another chain.
+fn is_settled(env: &Env, invoice_id: &String) -> bool {
+    has_payment(env, invoice_id)
+        || has_external_payment(env, invoice_id)
+        || has_chain_payment(env, invoice_id)
+}
+
+/// The payer who accepted the terms of `invoice_id` or preauthorized it, if
+/// any. Both are bound to one payer once given; acknowledgements are not,
+/// so they do not count.
+fn invoice_payer(env: &Env, invoice_id: &String) -> Option<Address> {
+    get_terms_acceptance(env, invoice_id)
+        .map(|acceptance| acceptance.payer)
+        .or_else(|| get_preauthorization(env, invoice_id).map(|preauth| preauth.payer))
+}
+
+/// `sha256(invoice_id || salt)`, the commitment [`InvoicePaymentContract::reveal`]
+/// checks.
+fn invoice_commitment(env: &Env, invoice_id: &String, salt: &BytesN<32>) -> BytesN<32> {
+    let mut preimage = invoice_id.to_bytes();
+    preimage.append(&Bytes::from(salt));
+    env.crypto().sha256(&preimage).to_bytes()
+}
+
+/// Add a refund of `amount` to the refunded total of `invoice_id` and
+/// return the payment with the new total. Nothing is written on error.
+///
+/// Shared by both refund paths. Callers are responsible for authorisation.
+fn apply_refund(
+    env: &Env,
+    invoice_id: &String,
+    amount: i128,
+) -> Result<(PaymentRecord, i128), ContractError> {
+    if amount <= 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+    let record = get_payment(env, invoice_id)?;
+    let refunded_total = get_refunded_total(env, invoice_id)
+        .checked_add(amount)
+        .filter(|total| *total <= record.amount)
+        .ok_or(ContractError::RefundExceedsPayment)?;
+
+    set_refunded_total(env, invoice_id, refunded_total);
+    add_received(env, &record.asset, -amount);
+    Ok((record, refunded_total))
+}
+
+/// Persist `record`, bump the payment counter, emit `payment_recorded`,
+/// capture the FX rate and mint a receipt if enabled, notify observers, and
+/// feed the circuit breaker.
+///
+/// Shared tail of every path that turns a payment into a permanent record.
+/// Callers are responsible for authorisation, validation and idempotency.
+fn commit_payment(env: &Env, record: PaymentRecord) {
+    // The payment settles its invoice in full.
+    let tax = split_payment(env, &record.invoice_id, record.amount, 0, record.amount);
+    commit_taxed_payment(env, record, tax);
+}
+
+/// [`commit_payment`] with the payment's net/tax split, if its invoice has
+/// an [`InvoiceTax`]; the split is stored beside the record and emitted as
+/// `payment_taxed`.
+fn commit_taxed_payment(env: &Env, record: PaymentRecord, tax: Option<TaxSplit>) {
+    // Persist the record (also bumps persistent TTL) and index it for
+    // compaction under the pre-bump count.
+    set_payment(env, &record);
+    index_payment(env, &record.invoice_id);
+    add_received(env, &record.asset, record.amount);
+    let tax_amount = tax.as_ref().map_or(0, |split| split.tax);
+    add_payment(env, &record.asset, record.amount, tax_amount, record.timestamp);
+
+    // Increment running counter (also bumps instance TTL).
+    bump_count(env);
+
+    // Mint before the event consumes the record; no-op unless enabled.
+    mint_receipt(env, &record);
+
+    // Best-effort; `None` unless an oracle is configured and has a price.
+    let rate = capture_rate(env, &record);
+    let invoice_id = record.invoice_id.clone();
+    let amount = record.amount;
+
+    // Emit Soroban event — off-chain indexers subscribe to these topics.
+    emit_payment_recorded(env, record.clone());
+
+    if let Some(rate) = rate {
+        set_payment_rate(env, &invoice_id, &rate);
+        emit_fx_rate_captured(env, invoice_id.clone(), rate);
+    }
+    if let Some(split) = tax {
+        set_payment_tax(env, &invoice_id, &split);
+        emit_payment_taxed(env, invoice_id, split);
+    }
+
+    // Once the payment is fully recorded; failures are isolated.
+    notify_observers(env, &record);
+
+    // Last, so `circuit_tripped` follows the payment that tripped it.
+    track_payment(env, amount);
 }
 
 mod test;
+mod prop_test;
+mod budget_test;
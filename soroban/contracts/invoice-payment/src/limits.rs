@@ -0,0 +1,67 @@
+use soroban_sdk::{Address, Env, Map};
+
+use crate::errors::ContractError;
+use crate::storage::{get_payer_volume, get_volume_limit, set_payer_volume, Asset};
+
+/// Width of one volume bucket in seconds.
+pub const VOLUME_BUCKET_SECS: u64 = 3_600;
+
+/// Buckets in the rolling window: 24 × 1 h ≈ the last 24 hours, at hour
+/// granularity.
+pub const VOLUME_WINDOW_BUCKETS: u64 = 24;
+
+/// Count `amount` of `asset` against `payer`'s rolling 24 h volume.
+///
+/// A no-op unless a limit is configured for `asset`. Buckets that fell out of
+/// the window are dropped on the way, so the stored map never grows past
+/// [`VOLUME_WINDOW_BUCKETS`] entries.
+///
+/// Returns [`ContractError::LimitExceeded`] if the payment would take the
+/// payer's volume over the limit; nothing is counted in that case.
+pub fn consume_volume(
+    env: &Env,
+    payer: &Address,
+    asset: &Asset,
+    amount: i128,
+) -> Result<(), ContractError> {
+    let Some(limit) = get_volume_limit(env, asset) else {
+        return Ok(());
+    };
+
+    let (mut buckets, used) = window(env, payer, asset);
+    let total = used
+        .checked_add(amount)
+        .ok_or(ContractError::LimitExceeded)?;
+    if total > limit {
+        return Err(ContractError::LimitExceeded);
+    }
+
+    let now = current_bucket(env);
+    buckets.set(now, buckets.get(now).unwrap_or(0) + amount);
+    set_payer_volume(env, payer, asset, &buckets);
+    Ok(())
+}
+
+/// `payer`'s volume of `asset` over the rolling window.
+pub fn rolling_volume(env: &Env, payer: &Address, asset: &Asset) -> i128 {
+    window(env, payer, asset).1
+}
+
+/// The buckets still inside the window, and their sum.
+fn window(env: &Env, payer: &Address, asset: &Asset) -> (Map<u64, i128>, i128) {
+    let oldest = current_bucket(env).saturating_sub(VOLUME_WINDOW_BUCKETS - 1);
+
+    let mut kept = Map::new(env);
+    let mut total: i128 = 0;
+    for (bucket, amount) in get_payer_volume(env, payer, asset).iter() {
+        if bucket >= oldest {
+            kept.set(bucket, amount);
+            total = total.saturating_add(amount);
+        }
+    }
+    (kept, total)
+}
+
+fn current_bucket(env: &Env) -> u64 {
+    env.ledger().timestamp() / VOLUME_BUCKET_SECS
+}
@@ -0,0 +1,29 @@
+use soroban_sdk::{contractclient, Env};
+
+use crate::events::emit_observer_failed;
+use crate::storage::{get_observers, PaymentRecord};
+
+/// Most observers that can be registered, so every recorded payment stays
+/// within the per-invocation call budget.
+pub const MAX_OBSERVERS: u32 = 5;
+
+/// What an observer contract implements to be told about recorded payments.
+#[contractclient(name = "PaymentObserverClient")]
+pub trait PaymentObserver {
+    fn on_payment_recorded(env: Env, record: PaymentRecord);
+}
+
+/// Call `on_payment_recorded` on every registered observer, in registration
+/// order.
+///
+/// Failures are isolated: an observer that fails (or is not a contract) has
+/// its changes rolled back and is reported with `observer_failed`, and the
+/// payment is recorded regardless.
+pub fn notify_observers(env: &Env, record: &PaymentRecord) {
+    for observer in get_observers(env).iter() {
+        let notified = PaymentObserverClient::new(env, &observer).try_on_payment_recorded(record);
+        if !matches!(notified, Ok(Ok(()))) {
+            emit_observer_failed(env, observer, record.invoice_id.clone());
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use soroban_sdk::{contractclient, contracttype, Address, Env, Symbol};
+
+use crate::storage::{get_fx_oracle, Asset, FxRate, PaymentRecord};
+
+/// Oldest oracle price, in seconds, that is still captured. Older prices are
+/// treated as unavailable rather than recorded as if current.
+pub const MAX_RATE_AGE_SECS: u64 = 900;
+
+/// Longest asset code that can be quoted (Stellar codes are at most 12).
+const MAX_CODE_LEN: usize = 12;
+
+// Reflector-compatible interface
+//
+// Mirrors the types of Reflector's SEP-40 price feed; same encoding. The
+// "external" feed quotes tickers (`Other`) in USD, which is what invoices
+// are denominated in.
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum OracleAsset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PriceData {
+    /// Price scaled by `10^decimals()`.
+    pub price: i128,
+    /// Unix timestamp of the price.
+    pub timestamp: u64,
+}
+
+/// The part of a Reflector-style oracle this contract calls.
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    fn lastprice(env: Env, asset: OracleAsset) -> Option<PriceData>;
+    fn decimals(env: Env) -> u32;
+}
+
+/// Ask the configured oracle for the USD rate of `record`'s asset.
+///
+/// Returns `None` when no oracle is configured, the oracle has no fresh price
+/// for the asset, or the call fails — capturing the rate is best-effort and
+/// never blocks recording a payment.
+pub fn capture_rate(env: &Env, record: &PaymentRecord) -> Option<FxRate> {
+    let oracle = PriceOracleClient::new(env, &get_fx_oracle(env)?);
+    let ticker = ticker(env, &record.asset)?;
+
+    let price = oracle.try_lastprice(&OracleAsset::Other(ticker)).ok()?.ok()??;
+    if price.price <= 0 || price.timestamp.saturating_add(MAX_RATE_AGE_SECS) < env.ledger().timestamp() {
+        return None;
+    }
+    let decimals = oracle.try_decimals().ok()?.ok()?;
+
+    Some(FxRate {
+        price: price.price,
+        decimals,
+        timestamp: price.timestamp,
+    })
+}
+
+/// The oracle ticker for `asset`: `XLM` for native, the code for tokens.
+/// `None` if the code cannot be a [`Symbol`].
+fn ticker(env: &Env, asset: &Asset) -> Option<Symbol> {
+    let code = match asset {
+        Asset::Native => return Some(Symbol::new(env, "XLM")),
+        Asset::Token(code, _) => code,
+    };
+    let len = code.len() as usize;
+    if len > MAX_CODE_LEN {
+        return None;
+    }
+    let mut buf = [0u8; MAX_CODE_LEN];
+    code.copy_into_slice(&mut buf[..len]);
+    let code = core::str::from_utf8(&buf[..len]).ok()?;
+    if !code.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(Symbol::new(env, code))
+}
+
@@ -0,0 +1,98 @@
+use soroban_sdk::{token, Address, Env, Map, String, Vec};
+
+use crate::aggregates::day_of;
+use crate::errors::ContractError;
+use crate::registry::merchant_payout;
+use crate::storage::{
+    add_settlement, get_last_payout_day, get_merchant_balance, get_settlement_count,
+    set_collected, set_last_payout_day, set_merchant_balance, MerchantBalance, PayoutSchedule,
+    Settlement,
+};
+
+/// Most invoices one settlement can cover, so a settlement stays within the
+/// entry size limit.
+pub const MAX_BATCH_INVOICES: u32 = 100;
+
+/// Add `amount` of `token`, already held by this contract, to the unsettled
+/// balance of `merchant_id` for `invoice_id`.
+///
+/// Returns [`ContractError::BatchFull`] if the balance already covers
+/// [`MAX_BATCH_INVOICES`] invoices.
+pub fn credit(
+    env: &Env,
+    merchant_id: &String,
+    invoice_id: &String,
+    token: &Address,
+    amount: i128,
+) -> Result<(), ContractError> {
+    let mut balance = get_merchant_balance(env, merchant_id).unwrap_or(MerchantBalance {
+        amounts: Map::new(env),
+        invoice_ids: Vec::new(env),
+    });
+    if balance.invoice_ids.len() >= MAX_BATCH_INVOICES {
+        return Err(ContractError::BatchFull);
+    }
+    let held = balance.amounts.get(token.clone()).unwrap_or(0);
+    balance.amounts.set(token.clone(), held + amount);
+    balance.invoice_ids.push_back(invoice_id.clone());
+    set_merchant_balance(env, merchant_id, &Some(balance));
+    set_collected(env, invoice_id);
+    Ok(())
+}
+
+/// Transfer the whole unsettled balance of `merchant_id` to its payout
+/// address and store the [`Settlement`].
+///
+/// ## Errors
+/// - [`ContractError::NothingToSettle`]
+/// - [`ContractError::MerchantRegistryNotSet`] / [`ContractError::UnknownMerchant`]
+pub fn settle(env: &Env, merchant_id: &String) -> Result<Settlement, ContractError> {
+    let balance = get_merchant_balance(env, merchant_id).ok_or(ContractError::NothingToSettle)?;
+    let payout = merchant_payout(env, merchant_id)?;
+
+    for (token, amount) in balance.amounts.iter() {
+        token::Client::new(env, &token).transfer(&env.current_contract_address(), &payout, &amount);
+    }
+    let settlement = Settlement {
+        id: get_settlement_count(env),
+        merchant_id: merchant_id.clone(),
+        payout,
+        amounts: balance.amounts,
+        invoice_ids: balance.invoice_ids,
+        settled_at: env.ledger().timestamp(),
+    };
+    add_settlement(env, &settlement);
+    set_merchant_balance(env, merchant_id, &None);
+    Ok(settlement)
+}
+
+/// First day on or after `day` on which `schedule` releases funds.
+pub fn next_release_day(schedule: &PayoutSchedule, day: u32) -> u32 {
+    if day <= schedule.anchor_day {
+        return schedule.anchor_day;
+    }
+    match (day - schedule.anchor_day) % schedule.every_days {
+        0 => day,
+        since => day + (schedule.every_days - since),
+    }
+}
+
+/// Admit one settlement of `merchant_id` today under its `schedule`, and
+/// return the day of the next release.
+///
+/// Returns [`ContractError::OutsidePayoutWindow`] unless today is a release
+/// day on which the merchant was not settled yet.
+pub fn enter_window(
+    env: &Env,
+    merchant_id: &String,
+    schedule: &PayoutSchedule,
+) -> Result<u32, ContractError> {
+    let today = day_of(env.ledger().timestamp());
+    if next_release_day(schedule, today) != today
+        || get_last_payout_day(env, merchant_id) == Some(today)
+    {
+        return Err(ContractError::OutsidePayoutWindow);
+    }
+    set_last_payout_day(env, merchant_id, today);
+    Ok(today + schedule.every_days)
+}
@@ -0,0 +1,345 @@
+#![cfg(test)]
+
+//! Property-based tests.
+//!
+//! Random call sequences are replayed against both the contract and a
+//! minimal in-memory model of it; every call must return what the model
+//! predicts, and the stored state must match the model afterwards. Inputs
+//! cover empty, duplicate and very long invoice ids, every asset code/issuer
+//! combination, and the `i128` extremes.
+
+extern crate std;
+
+use std::collections::BTreeMap;
+use std::string::String as StdString;
+
+use proptest::prelude::*;
+use soroban_sdk::{
+    testutils::{Address as _, EnvTestConfig},
+    Address, Env, String,
+};
+
+use super::*;
+use crate::storage::MAX_INVOICE_ID_LEN;
+
+// Strategies
+
+#[derive(Clone, Debug)]
+struct Args {
+    invoice_id: StdString,
+    asset_code: StdString,
+    asset_issuer: StdString,
+    amount: i128,
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    Record(Args),
+    ReportPending(Args),
+    Confirm(StdString),
+    Discard(StdString),
+}
+
+/// A well-formed `G...` strkey; the contract checks the checksum.
+fn issuer() -> impl Strategy<Value = StdString> {
+    any::<[u8; 32]>().prop_map(|key| stellar_strkey::ed25519::PublicKey(key).to_string())
+}
+
+fn invoice_id() -> impl Strategy<Value = StdString> {
+    prop_oneof![
+        1 => Just(StdString::new()),
+        // A small pool so sequences hit the same invoice repeatedly.
+        6 => "invoisio-[0-3]",
+        2 => "[ -~]{1,1024}",
+    ]
+}
+
+/// Ids for confirm / discard. These look an entry up, and only ids that
+/// `record_payment` accepts can have one.
+fn lookup_id() -> impl Strategy<Value = StdString> {
+    invoice_id().prop_filter("longer than any stored id", |id| {
+        id.len() <= MAX_INVOICE_ID_LEN as usize
+    })
+}
+
+fn asset() -> impl Strategy<Value = (StdString, StdString)> {
+    prop_oneof![
+        3 => Just(("XLM".into(), StdString::new())),
+        1 => issuer().prop_map(|i| ("XLM".into(), i)),
+        3 => ("[A-Z0-9]{1,12}", issuer()),
+        1 => "[A-Z0-9]{1,12}".prop_map(|c| (c, StdString::new())),
+        1 => issuer().prop_map(|i| (StdString::new(), i)),
+    ]
+}
+
+fn amount() -> impl Strategy<Value = i128> {
+    prop_oneof![
+        Just(i128::MIN),
+        Just(-1),
+        Just(0),
+        Just(1),
+        Just(i128::MAX),
+        any::<i128>(),
+    ]
+}
+
+fn args() -> impl Strategy<Value = Args> {
+    (invoice_id(), asset(), amount()).prop_map(|(invoice_id, (asset_code, asset_issuer), amount)| {
+        Args {
+            invoice_id,
+            asset_code,
+            asset_issuer,
+            amount,
+        }
+    })
+}
+
+/// Arguments `record_payment` accepts for a fresh invoice.
+fn valid_args() -> impl Strategy<Value = Args> {
+    let asset = prop_oneof![
+        Just(("XLM".into(), StdString::new())),
+        ("[A-Z0-9]{1,12}", issuer()).prop_filter("XLM takes no issuer", |(c, _)| c != "XLM"),
+    ];
+    ("invoisio-[a-z0-9]{1,32}", asset, 1..=i128::MAX).prop_map(
+        |(invoice_id, (asset_code, asset_issuer), amount)| Args {
+            invoice_id,
+            asset_code,
+            asset_issuer,
+            amount,
+        },
+    )
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        4 => args().prop_map(Op::Record),
+        2 => args().prop_map(Op::ReportPending),
+        1 => lookup_id().prop_map(Op::Confirm),
+        1 => lookup_id().prop_map(Op::Discard),
+    ]
+}
+
+// Model
+
+#[derive(Default)]
+struct Model {
+    recorded: BTreeMap<StdString, Args>,
+    pending: BTreeMap<StdString, Args>,
+}
+
+impl Model {
+    fn validate(&self, a: &Args) -> Result<(), ContractError> {
+        if a.invoice_id.is_empty() || a.invoice_id.len() > MAX_INVOICE_ID_LEN as usize {
+            return Err(ContractError::InvalidInvoiceId);
+        }
+        let is_xlm = a.asset_code == "XLM";
+        if a.asset_code.is_empty() || is_xlm != a.asset_issuer.is_empty() {
+            return Err(ContractError::InvalidAsset);
+        }
+        if a.amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if self.recorded.contains_key(&a.invoice_id) {
+            return Err(ContractError::PaymentAlreadyRecorded);
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, op: &Op) -> Result<(), ContractError> {
+        match op {
+            Op::Record(a) => {
+                self.validate(a)?;
+                self.recorded.insert(a.invoice_id.clone(), a.clone());
+            }
+            Op::ReportPending(a) => {
+                self.validate(a)?;
+                if self.pending.contains_key(&a.invoice_id) {
+                    return Err(ContractError::PaymentAlreadyPending);
+                }
+                self.pending.insert(a.invoice_id.clone(), a.clone());
+            }
+            Op::Confirm(id) => {
+                let Some(a) = self.pending.get(id) else {
+                    return Err(ContractError::PendingPaymentNotFound);
+                };
+                if self.recorded.contains_key(id) {
+                    return Err(ContractError::PaymentAlreadyRecorded);
+                }
+                let a = a.clone();
+                self.pending.remove(id);
+                self.recorded.insert(id.clone(), a);
+            }
+            Op::Discard(id) => {
+                if self.pending.remove(id).is_none() {
+                    return Err(ContractError::PendingPaymentNotFound);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Harness
+
+/// An env that writes no test snapshot: every property runs dozens of cases,
+/// and their snapshots are never compared.
+fn new_env() -> Env {
+    Env::new_with_config(EnvTestConfig {
+        capture_snapshot_at_drop: false,
+    })
+}
+
+fn s(env: &Env, v: &str) -> String {
+    String::from_str(env, v)
+}
+
+/// Run `op` against the contract, flattening the `try_` result.
+fn call(
+    env: &Env,
+    client: &InvoicePaymentContractClient,
+    payer: &Address,
+    op: &Op,
+) -> Result<(), ContractError> {
+    let result = match op {
+        Op::Record(a) => client.try_record_payment(
+            &s(env, &a.invoice_id),
+            payer,
+            &s(env, &a.asset_code),
+            &s(env, &a.asset_issuer),
+            &a.amount,
+        ),
+        Op::ReportPending(a) => client.try_report_pending_payment(
+            &s(env, &a.invoice_id),
+            payer,
+            &s(env, &a.asset_code),
+            &s(env, &a.asset_issuer),
+            &a.amount,
+        ),
+        Op::Confirm(id) => client.try_confirm_pending_payment(&s(env, id)),
+        Op::Discard(id) => client.try_discard_pending_payment(&s(env, id)),
+    };
+    match result {
+        Ok(Ok(())) => Ok(()),
+        Err(Ok(err)) => Err(err),
+        other => panic!("unexpected host failure for {op:?}: {other:?}"),
+    }
+}
+
+fn setup(env: &Env) -> InvoicePaymentContractClient<'_> {
+    env.mock_all_auths();
+    env.cost_estimate().budget().reset_unlimited();
+    let contract_id = env.register(InvoicePaymentContract, ());
+    let client = InvoicePaymentContractClient::new(env, &contract_id);
+    client.initialize(&Address::generate(env));
+    client
+}
+
+// Properties
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    /// Any interleaving of writes behaves like the model: same result per
+    /// call, `payment_count` equals the number of successful records, and
+    /// every stored record has a positive amount and the first-written data.
+    #[test]
+    fn prop_call_sequences_match_model(ops in prop::collection::vec(op(), 1..24)) {
+        let env = new_env();
+        let client = setup(&env);
+        let payer = Address::generate(&env);
+        let mut model = Model::default();
+
+        for op in &ops {
+            prop_assert_eq!(call(&env, &client, &payer, op), model.apply(op), "op {:?}", op);
+        }
+
+        prop_assert_eq!(client.payment_count() as usize, model.recorded.len());
+        for (id, a) in &model.recorded {
+            let record = client.get_payment(&s(&env, id));
+            prop_assert!(record.amount > 0);
+            prop_assert_eq!(record.amount, a.amount);
+            prop_assert_eq!(record.payer, payer.clone());
+        }
+    }
+
+    /// Recording the same invoice again, with any arguments, never changes
+    /// the stored record or the count.
+    #[test]
+    fn prop_record_is_idempotent(first in valid_args(), retries in prop::collection::vec(args(), 1..8)) {
+        let env = new_env();
+        let client = setup(&env);
+        let payer = Address::generate(&env);
+
+        call(&env, &client, &payer, &Op::Record(first.clone())).unwrap();
+        let id = s(&env, &first.invoice_id);
+        let stored = client.get_payment(&id);
+
+        for mut retry in retries {
+            retry.invoice_id = first.invoice_id.clone();
+            let result = call(&env, &client, &payer, &Op::Record(retry));
+            prop_assert!(result.is_err());
+            prop_assert_eq!(client.get_payment(&id), stored.clone());
+            prop_assert_eq!(client.payment_count(), 1);
+        }
+    }
+
+    /// Non-positive amounts are rejected and leave no trace, whatever the
+    /// other arguments.
+    #[test]
+    fn prop_non_positive_amount_is_never_stored(
+        id in "[ -~]{1,128}",
+        (code, issuer) in asset(),
+        amount in prop_oneof![Just(i128::MIN), Just(0), i128::MIN..=0],
+    ) {
+        let env = new_env();
+        let client = setup(&env);
+        let payer = Address::generate(&env);
+        let args = Args { invoice_id: id.clone(), asset_code: code, asset_issuer: issuer, amount };
+
+        prop_assert!(call(&env, &client, &payer, &Op::Record(args)).is_err());
+        prop_assert!(!client.has_payment(&s(&env, &id)));
+        prop_assert_eq!(client.payment_count(), 0);
+    }
+
+    /// Ids up to the maximum length and extreme positive amounts round-trip
+    /// exactly.
+    #[test]
+    fn prop_record_round_trips(id in "[ -~]{1,128}", amount in 1..=i128::MAX) {
+        let env = new_env();
+        let client = setup(&env);
+        let payer = Address::generate(&env);
+        let args = Args {
+            invoice_id: id.clone(),
+            asset_code: "XLM".into(),
+            asset_issuer: StdString::new(),
+            amount,
+        };
+
+        call(&env, &client, &payer, &Op::Record(args)).unwrap();
+        let record = client.get_payment(&s(&env, &id));
+        prop_assert_eq!(record.invoice_id, s(&env, &id));
+        prop_assert_eq!(record.asset, Asset::Native);
+        prop_assert_eq!(record.amount, amount);
+    }
+
+    /// Ids over the maximum length are rejected before they reach a
+    /// storage key.
+    #[test]
+    fn prop_overlong_id_is_rejected(id in "[ -~]{129,1024}") {
+        let env = new_env();
+        let client = setup(&env);
+        let payer = Address::generate(&env);
+        let args = Args {
+            invoice_id: id,
+            asset_code: "XLM".into(),
+            asset_issuer: StdString::new(),
+            amount: 1,
+        };
+
+        for op in [Op::Record(args.clone()), Op::ReportPending(args)] {
+            let result = call(&env, &client, &payer, &op);
+            prop_assert_eq!(result, Err(ContractError::InvalidInvoiceId));
+        }
+        prop_assert_eq!(client.payment_count(), 0);
+    }
+}
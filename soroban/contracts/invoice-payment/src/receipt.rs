@@ -0,0 +1,37 @@
+use soroban_sdk::{contractclient, Address, Env, String};
+
+use crate::storage::{get_receipt_contract, Asset, PaymentRecord};
+
+/// The part of the companion `receipts` contract this contract calls.
+///
+/// Declared here instead of importing the `receipts` crate so the two
+/// contracts can be built and deployed independently; `Asset` has the same
+/// encoding on both sides.
+#[contractclient(name = "ReceiptClient")]
+pub trait ReceiptInterface {
+    fn mint(
+        env: Env,
+        invoice_id: String,
+        owner: Address,
+        asset: Asset,
+        amount: i128,
+        paid_at: u64,
+    ) -> u32;
+}
+
+/// Mint a receipt for `record` to its payer if a receipts contract is
+/// configured.
+///
+/// A failing mint aborts the whole invocation, so a payment is never recorded
+/// without its receipt once receipts are enabled.
+pub fn mint_receipt(env: &Env, record: &PaymentRecord) {
+    if let Some(receipt_contract) = get_receipt_contract(env) {
+        ReceiptClient::new(env, &receipt_contract).mint(
+            &record.invoice_id,
+            &record.payer,
+            &record.asset,
+            &record.amount,
+            &record.timestamp,
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use soroban_sdk::{contractclient, contracttype, Address, BytesN, Env, String};
+
+use crate::errors::ContractError;
+use crate::storage::get_merchant_registry;
+
+/// Mirror of the merchant registry's `MerchantStatus`; same encoding.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MerchantStatus {
+    Unknown,
+    Active,
+    Inactive,
+}
+
+/// Mirror of the merchant registry's `Merchant`; same encoding.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Merchant {
+    pub merchant_id: String,
+    pub payout: Address,
+    pub metadata_hash: BytesN<32>,
+    pub active: bool,
+    pub onboarded_at: u64,
+}
+
+/// The part of the `merchant-registry` contract this contract calls.
+#[contractclient(name = "MerchantRegistryClient")]
+pub trait MerchantRegistryInterface {
+    fn merchant_status(env: Env, merchant_id: String) -> MerchantStatus;
+    fn get_merchant(env: Env, merchant_id: String) -> Merchant;
+}
+
+/// Check `merchant_id` against the configured registry.
+///
+/// ## Errors
+/// - [`ContractError::MerchantRegistryNotSet`]
+/// - [`ContractError::UnknownMerchant`]
+/// - [`ContractError::MerchantInactive`]
+pub fn require_active_merchant(env: &Env, merchant_id: &String) -> Result<(), ContractError> {
+    let registry = get_merchant_registry(env).ok_or(ContractError::MerchantRegistryNotSet)?;
+    match MerchantRegistryClient::new(env, &registry).merchant_status(merchant_id) {
+        MerchantStatus::Active => Ok(()),
+        MerchantStatus::Inactive => Err(ContractError::MerchantInactive),
+        MerchantStatus::Unknown => Err(ContractError::UnknownMerchant),
+    }
+}
+
+/// Look up the payout address of `merchant_id` in the configured registry.
+/// Inactive merchants are still paid out.
+///
+/// ## Errors
+/// - [`ContractError::MerchantRegistryNotSet`]
+/// - [`ContractError::UnknownMerchant`]
+pub fn merchant_payout(env: &Env, merchant_id: &String) -> Result<Address, ContractError> {
+    let registry = get_merchant_registry(env).ok_or(ContractError::MerchantRegistryNotSet)?;
+    match MerchantRegistryClient::new(env, &registry).try_get_merchant(merchant_id) {
+        Ok(Ok(merchant)) => Ok(merchant.payout),
+        _ => Err(ContractError::UnknownMerchant),
+    }
+}
@@ -0,0 +1,78 @@
+use soroban_sdk::{xdr::ToXdr, Env, String, Vec};
+
+use crate::errors::ContractError;
+use crate::storage::{
+    bump_entry_count, get_compact_cursor, get_count, get_payment, get_payment_seq,
+    remove_payment, remove_payment_seq, set_compact_cursor, set_compacted_payment,
+    set_payment_seq, CompactedPayment, KeyFamily, PaymentRecord,
+};
+
+/// Index a payment about to be recorded so compaction and export can find
+/// it later.
+/// Must run before the payment counter is bumped.
+pub fn index_payment(env: &Env, invoice_id: &String) {
+    set_payment_seq(env, get_count(env), invoice_id);
+}
+
+/// Replace the full records of payments recorded before `before_ts` with a
+/// [`CompactedPayment`], oldest first, visiting at most `limit` sequence
+/// numbers. Returns how many were compacted.
+///
+/// Resumes where the previous call stopped and stops at the first payment
+/// that is too recent. Sequence numbers used by hashed payments have no
+/// index entry and are skipped.
+pub fn compact_before(env: &Env, before_ts: u64, limit: u32) -> Result<u32, ContractError> {
+    let end = get_count(env);
+    let mut seq = get_compact_cursor(env);
+    let mut compacted = 0;
+
+    for _ in 0..limit {
+        if seq >= end {
+            break;
+        }
+        if let Some(invoice_id) = get_payment_seq(env, seq) {
+            let record = get_payment(env, &invoice_id)?;
+            if record.timestamp >= before_ts {
+                break;
+            }
+            let summary = CompactedPayment {
+                amount: record.amount,
+                record_hash: env.crypto().sha256(&record.to_xdr(env)).to_bytes(),
+            };
+            set_compacted_payment(env, &invoice_id, &summary);
+            remove_payment(env, &invoice_id);
+            remove_payment_seq(env, seq);
+            bump_entry_count(env, KeyFamily::CompactedPayment);
+            compacted += 1;
+        }
+        seq += 1;
+    }
+
+    set_compact_cursor(env, seq);
+    Ok(compacted)
+}
+
+/// Return the full records of the payments with sequence numbers
+/// `cursor..cursor + limit`, oldest first, and the cursor of the next page
+/// (`None` once the counter is reached).
+///
+/// Sequence numbers with no index entry (hashed and compacted payments) are
+/// skipped, so a page may hold fewer than `limit` records.
+pub fn export_page(
+    env: &Env,
+    cursor: u32,
+    limit: u32,
+) -> Result<(Vec<PaymentRecord>, Option<u32>), ContractError> {
+    let end = get_count(env);
+    let to = cursor.saturating_add(limit).min(end);
+    let mut records = Vec::new(env);
+
+    for seq in cursor..to {
+        if let Some(invoice_id) = get_payment_seq(env, seq) {
+            records.push_back(get_payment(env, &invoice_id)?);
+        }
+    }
+
+    let next = if to < end { Some(to) } else { None };
+    Ok((records, next))
+}
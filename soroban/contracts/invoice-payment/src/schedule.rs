@@ -0,0 +1,138 @@
+use core::ops::Range;
+
+use soroban_sdk::{Env, String};
+
+use crate::errors::ContractError;
+use crate::events::{emit_balance_paid, emit_deposit_paid, emit_installment_paid};
+use crate::storage::{
+    get_deposit_invoice, get_installment_plan, get_interest_rate_bps, has_deposit_invoice,
+    has_installment_plan, set_deposit_invoice, set_installment_plan, AmountDue, Asset,
+    DepositInvoice, DepositStatus, InstallmentPlan, InstallmentStatus,
+};
+
+/// Seconds in the 365-day year interest rates are quoted over.
+pub const SECS_PER_YEAR: u64 = 31_536_000;
+
+/// Highest accepted annual interest rate: 100 %.
+pub const MAX_INTEREST_RATE_BPS: u32 = 10_000;
+
+/// A payment applied to the schedule of an invoice, not yet persisted.
+pub enum ScheduledPayment {
+    /// The updated invoice, and its status before the payment.
+    Deposit(DepositInvoice, DepositStatus),
+    /// The updated plan, and the installments the payment completed.
+    Installments(InstallmentPlan, Range<u32>),
+}
+
+/// Whether `invoice_id` has a deposit invoice or an installment plan.
+pub fn has_schedule(env: &Env, invoice_id: &String) -> bool {
+    has_deposit_invoice(env, invoice_id) || has_installment_plan(env, invoice_id)
+}
+
+/// Apply a payment of `amount` in `asset` to the deposit invoice or
+/// installment plan of `invoice_id`. Nothing is written until
+/// [`ScheduledPayment::commit`].
+///
+/// Returns [`ContractError::InvoiceScheduleNotFound`] if `invoice_id` has
+/// neither, or the error of the schedule's `apply`.
+pub fn apply_payment(
+    env: &Env,
+    invoice_id: &String,
+    asset: &Asset,
+    amount: i128,
+) -> Result<ScheduledPayment, ContractError> {
+    if has_deposit_invoice(env, invoice_id) {
+        let mut invoice = get_deposit_invoice(env, invoice_id)?;
+        let before = invoice.status;
+        invoice.apply(asset, amount)?;
+        Ok(ScheduledPayment::Deposit(invoice, before))
+    } else {
+        let mut plan = get_installment_plan(env, invoice_id)?;
+        let completed = plan.apply(asset, amount)?;
+        Ok(ScheduledPayment::Installments(plan, completed))
+    }
+}
+
+/// Return the total of the deposit invoice or installment plan of
+/// `invoice_id`, if it has one.
+pub fn invoice_total(env: &Env, invoice_id: &String) -> Option<i128> {
+    if has_deposit_invoice(env, invoice_id) {
+        get_deposit_invoice(env, invoice_id).ok().map(|invoice| invoice.total)
+    } else {
+        get_installment_plan(env, invoice_id).ok().map(|plan| plan.total())
+    }
+}
+
+/// Return the unpaid principal of `invoice_id` and the interest accrued on
+/// it at the current ledger time.
+///
+/// Returns [`ContractError::InvoiceScheduleNotFound`] if `invoice_id` has
+/// neither a deposit invoice nor an installment plan.
+pub fn amount_due(env: &Env, invoice_id: &String) -> Result<AmountDue, ContractError> {
+    if has_deposit_invoice(env, invoice_id) {
+        // Deposit invoices have no due date, so they never accrue interest.
+        let invoice = get_deposit_invoice(env, invoice_id)?;
+        return Ok(AmountDue {
+            principal: invoice.balance_due(),
+            interest: 0,
+        });
+    }
+    let plan = get_installment_plan(env, invoice_id)?;
+    let interest = accrued_interest(&plan, get_interest_rate_bps(env), env.ledger().timestamp());
+    Ok(AmountDue {
+        principal: plan.balance_due(),
+        interest,
+    })
+}
+
+/// Simple interest at `rate_bps` a year on the unpaid part of each overdue
+/// installment of `plan`, from its `due_at` to `now`. Rounds down.
+pub fn accrued_interest(plan: &InstallmentPlan, rate_bps: u32, now: u64) -> i128 {
+    let denominator = 10_000 * SECS_PER_YEAR as i128;
+    plan.installments
+        .iter()
+        .filter(|i| i.status(now) == InstallmentStatus::Overdue)
+        .map(|i| {
+            (i.amount - i.paid)
+                .saturating_mul(rate_bps as i128)
+                .saturating_mul((now - i.due_at) as i128)
+                / denominator
+        })
+        .fold(0, i128::saturating_add)
+}
+
+impl ScheduledPayment {
+    /// The invoice total, and the part of it paid including this payment.
+    pub fn totals(&self) -> (i128, i128) {
+        match self {
+            Self::Deposit(invoice, _) => (invoice.total, invoice.paid),
+            Self::Installments(plan, _) => (plan.total(), plan.paid()),
+        }
+    }
+
+    /// Persist the updated schedule of `invoice_id` and emit the events for
+    /// the milestones reached by payment `payment_id`.
+    pub fn commit(self, env: &Env, invoice_id: String, payment_id: String) {
+        match self {
+            Self::Deposit(invoice, before) => {
+                set_deposit_invoice(env, &invoice_id, &invoice);
+                let deposit_due = invoice.status == DepositStatus::DepositDue;
+                if before == DepositStatus::DepositDue && !deposit_due {
+                    emit_deposit_paid(env, invoice_id.clone(), payment_id.clone(), &invoice);
+                }
+                if invoice.status == DepositStatus::Paid {
+                    emit_balance_paid(env, invoice_id, payment_id, invoice.paid);
+                }
+            }
+            Self::Installments(plan, completed) => {
+                set_installment_plan(env, &invoice_id, &plan);
+                for index in completed {
+                    emit_installment_paid(env, invoice_id.clone(), payment_id.clone(), index);
+                }
+                if plan.balance_due() == 0 {
+                    emit_balance_paid(env, invoice_id, payment_id, plan.paid());
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use soroban_sdk::{contracttype, Address, Env, String};
+use core::ops::Range;
+
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, Env, Map, String, Symbol, Vec};
 
 use crate::errors::ContractError;
 
@@ -6,9 +8,43 @@ use crate::errors::ContractError;
 // At ~5-second ledger close times:
 //   MIN_TTL  = 17 280 ledgers ≈ 1 day   (extend when remaining TTL falls below this)
 //   BUMP_TTL = 518 400 ledgers ≈ 30 days (target TTL after extension)
+//
+// A persistent entry whose TTL runs out is *archived*, not deleted: it keeps
+// its key and value off-ledger and can be brought back with a restore
+// operation. An archived payment must therefore never be reported as
+// "never recorded" — see `has_payment` / `get_payment` in `lib.rs`.
+
+pub const MIN_TTL: u32 = 17_280;
+pub const BUMP_TTL: u32 = 518_400;
+
+// Pending payments live in **temporary** storage and are never restored once
+// they expire: PENDING_TTL = 17 280 ledgers ≈ 1 day to confirm a candidate.
+pub const PENDING_TTL: u32 = 17_280;
+
+// Per-payer volume buckets also live in **temporary** storage. They only
+// matter for 24 hours, so VOLUME_TTL = 34 560 ledgers ≈ 2 days leaves a
+// wide margin for slower ledger close times.
+pub const VOLUME_TTL: u32 = 34_560;
+
+// A record written or bumped to BUMP_TTL only needs another bump once its
+// TTL falls below MIN_TTL again, KEEPER_COOLDOWN_TTL = 501 120 ledgers
+// ≈ 29 days later. Keepers are only rewarded for bumps after that.
+pub const KEEPER_COOLDOWN_TTL: u32 = BUMP_TTL - MIN_TTL;
+
+// Invoice ids are part of every per-invoice storage key, and the host rejects
+// keys over 250 bytes, so ids are capped well below that.
+pub const MAX_INVOICE_ID_LEN: u32 = 128;
+
+// Metadata blobs are rented per byte, so they are capped.
+pub const MAX_METADATA_LEN: u32 = 1_024;
+
+// Installment plans are read and rewritten whole on every payment, so their
+// length is capped.
+pub const MAX_INSTALLMENTS: u32 = 60;
 
-const MIN_TTL: u32 = 17_280;
-const BUMP_TTL: u32 = 518_400;
+// Accepted-asset lists are read on every payment of their invoice, so they
+// are capped.
+pub const MAX_ACCEPTED_ASSETS: u32 = 10;
 
 // Storage keys
 
@@ -23,8 +59,196 @@ pub enum DataKey {
     Admin,
     /// Running count of recorded payments in **instance** storage.
     PaymentCount,
-    /// A [`PaymentRecord`] indexed by `invoice_id` in **persistent** storage.
+    /// Net amount received per asset (`Map<Asset, i128>`): payments minus
+    /// refunds, in **instance** storage.
+    ReceivedTotals,
+    /// A [`PaymentRecord`] indexed by `invoice_id` in **persistent** storage,
+    /// stored as a [`StoredPayment`].
     Payment(String),
+    /// A provisional [`PaymentRecord`] indexed by `invoice_id` in **temporary**
+    /// storage, awaiting confirmation.
+    PendingPayment(String),
+    /// Lifetime count of pending candidates reported, in **instance** storage.
+    PendingCount,
+    /// Optional receipts contract [`Address`] in **instance** storage; when
+    /// set, every recorded payment mints a receipt to the payer.
+    ReceiptContract,
+    /// Merchant registry [`Address`] in **instance** storage, used to
+    /// validate `merchant_id`s.
+    MerchantRegistry,
+    /// `merchant_id` a payment was recorded for, indexed by `invoice_id` in
+    /// **persistent** storage. Absent for payments recorded without one.
+    PaymentMerchant(String),
+    /// Receipt numbers issued to `merchant_id` so far (`u64`), in
+    /// **persistent** storage.
+    ReceiptCount(String),
+    /// Receipt number of the merchant payment recorded for `invoice_id`
+    /// (`u64`), in **persistent** storage.
+    ReceiptNumber(String),
+    /// Optional price oracle [`Address`] in **instance** storage.
+    FxOracle,
+    /// [`FxRate`] captured when `invoice_id` was recorded, in **persistent**
+    /// storage. Absent if no oracle was configured or it had no fresh price.
+    PaymentRate(String),
+    /// [`CompliancePolicy`] in **instance** storage. Absent = no enforcement.
+    CompliancePolicy,
+    /// [`PayerFlags`] indexed by payer in **persistent** storage.
+    PayerFlags(Address),
+    /// Present (`true`) while a payer is blocked, in **persistent** storage.
+    BlockedPayer(Address),
+    /// Max rolling 24 h volume per payer for an [`Asset`], in **instance**
+    /// storage. Absent = unlimited.
+    VolumeLimit(Asset),
+    /// Hourly volume buckets of a payer in an [`Asset`], in **temporary**
+    /// storage.
+    PayerVolume(Address, Asset),
+    /// [`CircuitBreaker`] thresholds in **instance** storage. Absent = off.
+    CircuitBreaker,
+    /// Current [`BreakerWindow`] in **instance** storage.
+    BreakerWindow,
+    /// `true` while writes are paused, in **instance** storage.
+    Paused,
+    /// A payment keyed by a 32-byte invoice hash instead of an `invoice_id`,
+    /// in **persistent** storage, stored as a [`StoredPayment`].
+    HashedPayment(BytesN<32>),
+    /// An [`ExternalPaymentRecord`] indexed by `invoice_id` in **persistent**
+    /// storage.
+    ExternalPayment(String),
+    /// A [`ChainPaymentRecord`] indexed by `invoice_id` in **persistent**
+    /// storage.
+    ChainPayment(String),
+    /// Opaque metadata blob (ciphertext) attached to an `invoice_id`, in
+    /// **persistent** storage.
+    Metadata(String),
+    /// `invoice_id` of the payment with a given sequence number (its
+    /// `payment_count` before it was recorded), in **persistent** storage.
+    /// Lets `compact_old_records` walk payments oldest first; removed once
+    /// the payment is compacted.
+    PaymentSeq(u32),
+    /// [`CompactedPayment`] that replaced a full record, indexed by
+    /// `invoice_id` in **persistent** storage.
+    CompactedPayment(String),
+    /// Sequence number `compact_old_records` resumes from, in **instance**
+    /// storage.
+    CompactCursor,
+    /// Issuer of the USDC interned as [`StoredAsset::Usdc`], in **instance**
+    /// storage. Set once.
+    UsdcIssuer,
+    /// Admin-set [`DeploymentInfo`], in **instance** storage.
+    DeploymentInfo,
+    /// [`TermsAcceptance`] indexed by `invoice_id` in **persistent** storage.
+    TermsAcceptance(String),
+    /// Present (`true`) while payments require a [`TermsAcceptance`], in
+    /// **instance** storage.
+    TermsRequired,
+    /// [`Acknowledgement`] indexed by `invoice_id` in **persistent** storage.
+    Acknowledgement(String),
+    /// [`Quote`] indexed by `quote_id` in **persistent** storage.
+    Quote(String),
+    /// Total refunded (`i128`) against the payment of an `invoice_id`, in
+    /// **persistent** storage. Absent = nothing refunded.
+    RefundedTotal(String),
+    /// [`DepositInvoice`] indexed by `invoice_id` in **persistent** storage.
+    DepositInvoice(String),
+    /// [`InstallmentPlan`] indexed by `invoice_id` in **persistent** storage.
+    InstallmentPlan(String),
+    /// Annual interest rate on overdue installments, in basis points
+    /// (`u32`), in **instance** storage. Absent = no interest.
+    InterestRateBps,
+    /// [`KeeperBounty`] paid for maintenance calls, in **instance** storage.
+    /// Absent = no bounty.
+    KeeperBounty,
+    /// Marker (`bool`) in **temporary** storage that the record of an
+    /// `invoice_id` was written or had its TTL bumped by
+    /// `extend_payment_ttl`, so its TTL is still at least `MIN_TTL`;
+    /// expires after `KEEPER_COOLDOWN_TTL` ledgers.
+    TtlFresh(String),
+    /// Balance (`i128`) of a token set aside for keeper bounties, indexed by
+    /// the token contract in **instance** storage. Absent = 0.
+    KeeperPool(Address),
+    /// When the customer actually paid (`u64` timestamp) a payment recorded
+    /// late, indexed by `invoice_id` in **persistent** storage. Absent = paid
+    /// when recorded.
+    PaidAt(String),
+    /// Per-asset [`PeriodTotals`] of the payments recorded on a day (days
+    /// since the Unix epoch, UTC), in **persistent** storage.
+    DailyTotals(u32),
+    /// Contracts (`Vec<Address>`) notified of every recorded payment, in
+    /// **instance** storage. Absent = none.
+    Observers,
+    /// Validator contract [`Address`] consulted before every payment is
+    /// recorded, in **instance** storage. Absent = none.
+    Validator,
+    /// [`Preauthorization`] indexed by `invoice_id` in **persistent** storage.
+    Preauthorization(String),
+    /// Amount of an [`Asset`] from which payments need a
+    /// [`Preauthorization`], in **instance** storage. Absent = never.
+    PreauthThreshold(Asset),
+    /// Ed25519 public key (`BytesN<32>`) whose signature authorizes
+    /// `record_payment_signed`, in **instance** storage. Absent = disabled.
+    SigningKey,
+    /// Next operation nonce (`u64`) of an admin address, in **persistent**
+    /// storage. Absent = 0.
+    AdminNonce(Address),
+    /// Unsettled [`MerchantBalance`] indexed by `merchant_id` in
+    /// **persistent** storage. Absent = nothing to settle.
+    MerchantBalance(String),
+    /// `true` once the funds of an invoice were collected, indexed by
+    /// `invoice_id` in **persistent** storage.
+    Collected(String),
+    /// [`Settlement`] indexed by its sequential id in **persistent** storage.
+    Settlement(u32),
+    /// Number of settlements (`u32`) ever made, in **instance** storage.
+    SettlementCount,
+    /// [`PayoutSchedule`] indexed by `merchant_id` in **persistent**
+    /// storage. Absent = settled by the admin at any time.
+    PayoutSchedule(String),
+    /// Day number (`u32`) of a merchant's last scheduled settlement, in
+    /// **persistent** storage.
+    LastPayoutDay(String),
+    /// [`CreditNote`] indexed by `credit_id` in **persistent** storage.
+    CreditNote(String),
+    /// [`InvoiceTax`] indexed by `invoice_id` in **persistent** storage.
+    InvoiceTax(String),
+    /// [`TaxSplit`] of a recorded payment, indexed by its `invoice_id` in
+    /// **persistent** storage.
+    PaymentTax(String),
+    /// Expected amount per accepted [`Asset`] (`Map<Asset, i128>`) of an
+    /// invoice, indexed by `invoice_id` in **persistent** storage. Absent =
+    /// any asset.
+    AcceptedAssets(String),
+    /// [`Conversion`] of a payment paid in another asset than invoiced,
+    /// indexed by `invoice_id` in **persistent** storage.
+    Conversion(String),
+    /// [`Chargeback`] indexed by `invoice_id` in **persistent** storage.
+    Chargeback(String),
+    /// [`Reconciliation`] of the payment recorded for `invoice_id`, in
+    /// **persistent** storage. Absent while `Unverified`.
+    Reconciliation(String),
+    /// [`Remittance`] of a SEP-31 payment, indexed by `invoice_id` in
+    /// **persistent** storage.
+    Remittance(String),
+    /// `invoice_id` settled by a SEP-31 transaction, indexed by the
+    /// transaction id in **persistent** storage.
+    RemittanceInvoice(String),
+    /// Number of entries (`u32`) ever written to a [`KeyFamily`] that
+    /// [`PaymentCount`](DataKey::PaymentCount) does not already count, in
+    /// **instance** storage. Absent = 0.
+    EntryCount(KeyFamily),
+}
+
+/// Persistent key families counted under [`DataKey::EntryCount`] for
+/// `storage_stats()`.
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyFamily {
+    HashedPayment,
+    CompactedPayment,
+    ExternalPayment,
+    ChainPayment,
+    DepositInvoice,
+    InstallmentPlan,
+    RemittanceInvoice,
 }
 
 // Data structures
@@ -65,6 +289,11 @@ pub struct PaymentRecord {
     /// Stellar account address that sent the payment.
     pub payer: Address,
 
+    /// Mux id of the payer, when it paid from a muxed (`M...`) address such
+    /// as an exchange omnibus account with per-customer ids. `None` for
+    /// plain addresses.
+    pub payer_mux_id: Option<u64>,
+
     /// Asset type and details.
     pub asset: Asset,
 
@@ -75,6 +304,737 @@ pub struct PaymentRecord {
     pub timestamp: u64,
 }
 
+/// Proof of payment assembled by `get_receipt()` from a [`PaymentRecord`]
+/// and what is stored beside it.
+///
+/// Its XDR is canonical (struct fields encode in sorted order), so a
+/// signature over it can be checked by anyone holding the receipt, and the
+/// receipt itself re-fetched from `contract` to confirm it.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaymentReceipt {
+    /// This contract, so a receipt cannot be replayed against another
+    /// deployment.
+    pub contract: Address,
+    pub invoice_id: String,
+    pub payer: Address,
+    pub payer_mux_id: Option<u64>,
+    pub asset: Asset,
+    pub amount: i128,
+    /// Refunded against the payment so far.
+    pub refunded: i128,
+    /// When the customer paid; `recorded_at` unless recorded backdated.
+    pub paid_at: u64,
+    pub recorded_at: u64,
+    pub merchant_id: Option<String>,
+    pub receipt_number: Option<u64>,
+}
+
+/// What is left of a payment after `compact_old_records`: its amount and
+/// the SHA-256 of the full [`PaymentRecord`]'s XDR, so an off-chain copy of
+/// the record can still be proven against the chain.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompactedPayment {
+    pub amount: i128,
+    pub record_hash: BytesN<32>,
+}
+
+/// Compact form of an [`Asset`] inside a [`StoredPayment`].
+///
+/// Well-known assets are stored as a bare tag instead of a code and a
+/// 56-character issuer.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum StoredAsset {
+    Native,
+    /// `USDC` issued by [`DataKey::UsdcIssuer`].
+    Usdc,
+    Token(String, String),
+}
+
+/// On-ledger form of a [`PaymentRecord`] under [`DataKey::Payment`].
+///
+/// `invoice_id` is already in the key, so it is not repeated, and the asset
+/// is interned. [`get_payment`] rebuilds the full record.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredPayment {
+    pub payer: Address,
+    pub payer_mux_id: Option<u64>,
+    pub asset: StoredAsset,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// A payment recorded with `record_hashed_payment`, keyed by a 32-byte hash
+/// of the backend's invoice id (e.g. SHA-256 of its UUID) instead of an
+/// `invoice_id` string.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct HashedPaymentRecord {
+    pub invoice_hash: BytesN<32>,
+    pub payer: Address,
+    pub asset: Asset,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+/// A payment authorized off-chain by the signing key, submitted with
+/// `record_payment_signed()`. Same fields as the `record_payment` arguments;
+/// `payer` cannot be muxed.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignedPayment {
+    pub invoice_id: String,
+    pub payer: Address,
+    pub asset_code: String,
+    pub asset_issuer: String,
+    pub amount: i128,
+}
+
+/// Audit entry for an invoice settled off Stellar (bank transfer, card,
+/// another network), recorded with `record_external_payment`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExternalPaymentRecord {
+    pub invoice_id: String,
+    /// Payment rail, e.g. `sepa`, `ach`, `card`.
+    pub rail: Symbol,
+    /// The rail's own reference (bank transfer id, card charge id, tx hash).
+    pub reference: String,
+    /// Amount in the currency's minor unit (must be > 0).
+    pub amount: i128,
+    /// ISO 4217 code or ticker, e.g. `USD`.
+    pub currency: Symbol,
+    /// Unix timestamp (seconds) sourced from the ledger at recording time.
+    pub timestamp: u64,
+}
+
+/// Asset of a [`ChainPaymentRecord`], described without assuming Stellar.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainAsset {
+    /// Ticker, e.g. `BTC`, `USDC`.
+    pub code: Symbol,
+    /// CAIP-19 asset namespace and reference on the payment's chain, e.g.
+    /// `slip44:0` for bitcoin or `erc20:0xA0b8…eB48` for USDC on Ethereum.
+    pub asset_ref: String,
+    /// Decimal places of the amount, e.g. 8 for BTC, 6 for USDC.
+    pub decimals: u32,
+}
+
+/// Audit entry for an invoice paid on another blockchain (an EVM chain,
+/// Bitcoin) and relayed by a partner, recorded with `record_chain_payment`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainPaymentRecord {
+    pub invoice_id: String,
+    /// CAIP-2 chain id, e.g. `eip155:1` (Ethereum), `eip155:8453` (Base) or
+    /// `bip122:000000000019d6689c085ae165831e93` (Bitcoin).
+    pub chain_id: String,
+    /// Transaction hash on that chain, with `:<log index>` or `:<vout>` when
+    /// one transaction carries several payments.
+    pub tx_ref: String,
+    /// Payer address as the chain writes it; `None` when there is no single
+    /// sender, as with a multi-input Bitcoin transaction.
+    pub payer: Option<String>,
+    pub asset: ChainAsset,
+    /// Amount in the asset's smallest unit (must be > 0).
+    pub amount: i128,
+    /// Unix timestamp (seconds) sourced from the ledger at recording time.
+    pub timestamp: u64,
+}
+
+/// USD rate of a payment's asset, captured from the price oracle at the
+/// recording ledger and never changed afterwards.
+///
+/// The USD value of the payment is `amount * price / 10^decimals`, in the
+/// asset's smallest unit.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FxRate {
+    /// USD per whole unit of the asset, scaled by `10^decimals`.
+    pub price: i128,
+    pub decimals: u32,
+    /// Unix timestamp of the oracle price (not of the payment).
+    pub timestamp: u64,
+}
+
+/// Scale of [`Conversion::rate`]: 7 decimals, like Stellar amounts.
+pub const CONVERSION_RATE_SCALE: i128 = 10_000_000;
+
+/// Both legs of a payment the payer made in another asset than the invoice's,
+/// e.g. through a path payment, stored by `record_converted_payment()`. The
+/// received leg is also the payment's [`PaymentRecord`].
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conversion {
+    /// What the payer sent.
+    pub sent_asset: Asset,
+    pub sent_amount: i128,
+    /// What arrived, in the invoice's asset.
+    pub received_asset: Asset,
+    pub received_amount: i128,
+    /// Effective rate: received units per sent unit, scaled by
+    /// [`CONVERSION_RATE_SCALE`] and rounded down.
+    pub rate: i128,
+}
+
+/// SEP-31 identifiers of a cross-border payment, stored beside its
+/// [`PaymentRecord`] by `record_remittance_payment()`.
+///
+/// Anchors are named by their home domain, as in their `stellar.toml`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Remittance {
+    /// Transaction id assigned by the receiving anchor.
+    pub transaction_id: String,
+    /// Anchor the sender paid in, e.g. `sender-anchor.example`.
+    pub sending_anchor: String,
+    /// Anchor that delivered the payment on Stellar.
+    pub receiving_anchor: String,
+}
+
+/// Admin-managed screening results for one payer.
+///
+/// Unscreened payers read as all-`false` with `updated_at == 0`.
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PayerFlags {
+    /// The payer passed KYC with the compliance provider.
+    pub kyc_verified: bool,
+    /// The compliance provider rated the payer high risk.
+    pub high_risk: bool,
+    /// Unix timestamp of the last change.
+    pub updated_at: u64,
+}
+
+/// Which [`PayerFlags`] block recording a payment. The default enforces
+/// nothing; flags are then informational only.
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompliancePolicy {
+    /// Reject payers that are not `kyc_verified`.
+    pub require_kyc: bool,
+    /// Reject payers flagged `high_risk`.
+    pub reject_high_risk: bool,
+}
+
+/// Thresholds that auto-pause writes when exceeded within one window of
+/// `window_ledgers` ledgers.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CircuitBreaker {
+    /// Length of a window in ledgers (> 0).
+    pub window_ledgers: u32,
+    /// Most payments that may be recorded in one window.
+    pub max_count: u32,
+    /// Largest sum of `amount`s that may be recorded in one window. Amounts
+    /// are summed in raw units across assets.
+    pub max_volume: i128,
+}
+
+/// Bounty paid to whoever performs a permissionless maintenance call, from
+/// the keeper pool of `token` (see `fund_keeper_pool()`).
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeeperBounty {
+    /// Token contract the bounty is paid in.
+    pub token: Address,
+    /// Paid per rewarded call, in the token's smallest unit (> 0).
+    pub amount: i128,
+}
+
+/// What the circuit breaker has counted in the current window.
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BreakerWindow {
+    /// Sequence of the first ledger of the window.
+    pub start_ledger: u32,
+    pub count: u32,
+    pub volume: i128,
+}
+
+/// Payer-signed acceptance of an invoice's commercial terms, stored by
+/// `accept_terms()`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TermsAcceptance {
+    pub payer: Address,
+    /// Hash of the terms document the payer accepted, computed off-chain.
+    pub terms_hash: BytesN<32>,
+    /// Ledger Unix timestamp (seconds) of the acceptance.
+    pub timestamp: u64,
+}
+
+/// Payer-signed intent to pay an invoice, stored by `preauthorize()`. The
+/// payment later recorded for the invoice must come from `payer`, in
+/// `asset`, for at most `max_amount`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preauthorization {
+    pub payer: Address,
+    pub asset: Asset,
+    pub max_amount: i128,
+    /// Ledger Unix timestamp (seconds) of the preauthorization.
+    pub timestamp: u64,
+}
+
+/// Funds collected for a merchant since its last settlement.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MerchantBalance {
+    /// Amount held per token contract.
+    pub amounts: Map<Address, i128>,
+    /// Invoices whose funds make up `amounts`, in collection order.
+    pub invoice_ids: Vec<String>,
+}
+
+/// One payout of a [`MerchantBalance`] to the merchant's payout address.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settlement {
+    /// Sequential id, starting at 0.
+    pub id: u32,
+    pub merchant_id: String,
+    /// Payout address read from the merchant registry at settlement time.
+    pub payout: Address,
+    /// Amount transferred per token contract.
+    pub amounts: Map<Address, i128>,
+    /// Invoices covered by this settlement.
+    pub invoice_ids: Vec<String>,
+    /// Ledger Unix timestamp (seconds) of the settlement.
+    pub settled_at: u64,
+}
+
+/// When a merchant is paid out: every `every_days` days starting on day
+/// `anchor_day` (days since the Unix epoch, UTC). Weekly on Mondays is
+/// `every_days: 7` with any Monday as the anchor.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PayoutSchedule {
+    pub every_days: u32,
+    pub anchor_day: u32,
+}
+
+/// Where a [`DepositInvoice`] stands.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DepositStatus {
+    /// Less than the deposit has been paid.
+    DepositDue,
+    /// The deposit is paid; the balance is outstanding.
+    BalanceDue,
+    /// The invoice is paid in full.
+    Paid,
+    /// The deposit was not paid by `deposit_due_at`, and `expire_invoice()`
+    /// closed the invoice. No further payments are accepted.
+    Expired,
+}
+
+/// Retainer invoice requiring an upfront deposit, created with
+/// `issue_deposit_invoice()` and paid through `record_invoice_payment()`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepositInvoice {
+    pub asset: Asset,
+    /// Invoice total in the asset's smallest unit.
+    pub total: i128,
+    /// Part of `total` due upfront.
+    pub deposit: i128,
+    /// Ledger timestamp (seconds) after which an unpaid deposit lets anyone
+    /// expire the invoice. `None` = the offer never expires.
+    pub deposit_due_at: Option<u64>,
+    /// Sum of the payments and credit notes applied so far.
+    pub paid: i128,
+    pub status: DepositStatus,
+}
+
+impl DepositInvoice {
+    /// Apply a payment of `amount` in `asset` and update `status`.
+    ///
+    /// Returns [`ContractError::InvoiceExpired`] once expired,
+    /// [`ContractError::InvalidAsset`] for a payment in another asset and
+    /// [`ContractError::InvoiceOverpaid`] if it would exceed `total`; `self`
+    /// is unchanged on error.
+    pub fn apply(&mut self, asset: &Asset, amount: i128) -> Result<(), ContractError> {
+        if self.status == DepositStatus::Expired {
+            return Err(ContractError::InvoiceExpired);
+        }
+        if *asset != self.asset {
+            return Err(ContractError::InvalidAsset);
+        }
+        self.paid = self
+            .paid
+            .checked_add(amount)
+            .filter(|paid| *paid <= self.total)
+            .ok_or(ContractError::InvoiceOverpaid)?;
+        self.status = if self.paid == self.total {
+            DepositStatus::Paid
+        } else if self.paid >= self.deposit {
+            DepositStatus::BalanceDue
+        } else {
+            DepositStatus::DepositDue
+        };
+        Ok(())
+    }
+
+    /// Amount still owed on the invoice.
+    pub fn balance_due(&self) -> i128 {
+        self.total - self.paid
+    }
+}
+
+/// Where an [`Installment`] stands at a given ledger time.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InstallmentStatus {
+    /// Not paid in full, and not yet due.
+    Open,
+    /// Not paid in full, and past its due date.
+    Overdue,
+    /// Paid in full.
+    Paid,
+}
+
+/// One installment of an [`InstallmentPlan`].
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Installment {
+    pub amount: i128,
+    /// Ledger timestamp (seconds) by which `amount` is due.
+    pub due_at: u64,
+    /// Part of `amount` paid so far.
+    pub paid: i128,
+}
+
+impl Installment {
+    /// Status of the installment at ledger timestamp `now`.
+    pub fn status(&self, now: u64) -> InstallmentStatus {
+        if self.paid == self.amount {
+            InstallmentStatus::Paid
+        } else if now > self.due_at {
+            InstallmentStatus::Overdue
+        } else {
+            InstallmentStatus::Open
+        }
+    }
+}
+
+/// Invoice paid in installments, created with `issue_installment_plan()`
+/// and paid through `record_invoice_payment()`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct InstallmentPlan {
+    pub asset: Asset,
+    /// Installments in due order.
+    pub installments: Vec<Installment>,
+}
+
+impl InstallmentPlan {
+    /// Apply a payment of `amount` in `asset` to the open installments in
+    /// due order, spilling into the next one. Returns the indices of the
+    /// installments it completed.
+    ///
+    /// Returns [`ContractError::InvalidAsset`] for a payment in another
+    /// asset and [`ContractError::InvoiceOverpaid`] if it exceeds the
+    /// balance due; `self` is unchanged on error.
+    pub fn apply(&mut self, asset: &Asset, amount: i128) -> Result<Range<u32>, ContractError> {
+        if *asset != self.asset {
+            return Err(ContractError::InvalidAsset);
+        }
+        if amount > self.balance_due() {
+            return Err(ContractError::InvoiceOverpaid);
+        }
+        let start = self.next_open();
+        let mut left = amount;
+        for index in start..self.installments.len() {
+            if left == 0 {
+                break;
+            }
+            let mut installment = self.installments.get_unchecked(index);
+            let part = left.min(installment.amount - installment.paid);
+            installment.paid += part;
+            left -= part;
+            self.installments.set(index, installment);
+        }
+        Ok(start..self.next_open())
+    }
+
+    /// Index of the first installment not paid in full, or the number of
+    /// installments once all are.
+    pub fn next_open(&self) -> u32 {
+        self.installments
+            .iter()
+            .position(|i| i.paid < i.amount)
+            .map_or(self.installments.len(), |index| index as u32)
+    }
+
+    /// Sum of all installments.
+    pub fn total(&self) -> i128 {
+        self.installments.iter().map(|i| i.amount).sum()
+    }
+
+    /// Sum of the payments and credit notes applied so far.
+    pub fn paid(&self) -> i128 {
+        self.installments.iter().map(|i| i.paid).sum()
+    }
+
+    /// Amount still owed across all installments.
+    pub fn balance_due(&self) -> i128 {
+        self.installments.iter().map(|i| i.amount - i.paid).sum()
+    }
+}
+
+/// What is owed on a deposit invoice or installment plan, returned by
+/// `get_amount_due()`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AmountDue {
+    /// Unpaid part of the invoice total.
+    pub principal: i128,
+    /// Interest accrued on overdue installments at the current ledger time.
+    /// Informational: payments are applied to `principal` only.
+    pub interest: i128,
+}
+
+/// Payments recorded in one asset over a day or month, returned by
+/// `daily_totals()` and `monthly_totals()`.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PeriodTotals {
+    /// Number of payments.
+    pub count: u32,
+    /// Sum of their amounts, before refunds.
+    pub amount: i128,
+    /// Part of `amount` that is tax, from the [`TaxSplit`] of each payment.
+    /// `amount - tax` is the net.
+    pub tax: i128,
+}
+
+/// Tax charged on an invoice, attached with `set_invoice_tax()` before it
+/// is paid.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoiceTax {
+    /// Tax rate in basis points, e.g. 2_000 for 20 % VAT.
+    pub rate_bps: u32,
+    /// Tax included in the invoice total, in the asset's smallest unit.
+    pub amount: i128,
+    /// Taxing jurisdiction, e.g. `DE` or `US-CA`.
+    pub jurisdiction: String,
+}
+
+/// How a recorded payment divides into net and tax, stored with the
+/// payment when its invoice has an [`InvoiceTax`].
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TaxSplit {
+    pub net: i128,
+    pub tax: i128,
+    pub rate_bps: u32,
+    pub jurisdiction: String,
+}
+
+/// Estimate issued by the merchant with `issue_quote()`. Accepting it with
+/// `accept_quote()` turns it into the terms of invoice `invoice_id`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quote {
+    /// Invoice the quote converts into once accepted.
+    pub invoice_id: String,
+    /// Only this address can accept the quote.
+    pub payer: Address,
+    pub asset: Asset,
+    /// Quoted amount in the asset's smallest unit.
+    pub amount: i128,
+    /// Hash of the quoted terms, stored as the invoice's [`TermsAcceptance`]
+    /// on acceptance.
+    pub terms_hash: BytesN<32>,
+    /// Ledger Unix timestamp (seconds) from which the quote can no longer be
+    /// accepted.
+    pub expires_at: u64,
+    pub accepted: bool,
+}
+
+/// How far the backend has verified a recorded payment against Horizon.
+/// Recording alone does not verify anything.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Reconciliation {
+    /// Recorded, not yet checked against the ledger's payment operation.
+    Unverified,
+    /// The payment operation was found and matches the record.
+    Matched,
+    /// The check found a discrepancy that needs a human.
+    ManualReview,
+}
+
+/// Where a [`Chargeback`] stands.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChargebackStatus {
+    /// Disputed with the card network or ramp, not decided yet.
+    Open,
+    /// The reversal stands: the funds went back to the cardholder.
+    Upheld,
+    /// The reversal was overturned in the merchant's favour.
+    Overturned,
+}
+
+/// Reversal of the card payment that funded an invoice's on-ramp, flagged
+/// with `flag_chargeback()`. Informational: the payment record, refunds and
+/// totals are unchanged.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Chargeback {
+    /// Case reference of the card network or ramp provider.
+    pub reference: String,
+    pub status: ChargebackStatus,
+    /// Ledger Unix timestamp (seconds) of the flag.
+    pub flagged_at: u64,
+    /// Ledger Unix timestamp (seconds) of the resolution, once resolved.
+    pub resolved_at: Option<u64>,
+}
+
+/// Where a [`CreditNote`] stands.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CreditNoteStatus {
+    /// Part of the credit is still available.
+    Open,
+    /// The whole credit was applied to invoices.
+    Applied,
+    /// Closed by the admin with `void_credit_note()`; the unapplied rest
+    /// lapsed.
+    Voided,
+}
+
+/// Credit owed to a payer, issued with `issue_credit_note()`. The payer
+/// spends it on their deposit invoices and installment plans with
+/// `apply_credit_note()`, which lowers the amount due like a payment
+/// without any funds moving.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreditNote {
+    /// Only this address can apply the credit.
+    pub payer: Address,
+    pub asset: Asset,
+    /// Credit issued, in the asset's smallest unit.
+    pub amount: i128,
+    /// Part of `amount` not applied yet.
+    pub remaining: i128,
+    /// Amount applied per invoice.
+    pub applied: Map<String, i128>,
+    pub status: CreditNoteStatus,
+    /// Ledger Unix timestamp (seconds) of issue.
+    pub issued_at: u64,
+}
+
+/// Payer-signed read receipt for an invoice, stored by
+/// `acknowledge_invoice()`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Acknowledgement {
+    pub payer: Address,
+    /// Ledger Unix timestamp (seconds) of the first acknowledgement.
+    pub timestamp: u64,
+}
+
+/// Admin-set labels that tell deployments apart, set with `set_info()`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeploymentInfo {
+    /// Free-form instance label, e.g. `acme-eu-1`.
+    pub label: Option<String>,
+    /// Environment tag, e.g. `testnet`, `staging`, `prod`.
+    pub environment: Option<Symbol>,
+    /// SHA-256 of the merchant's display name; the name itself stays off
+    /// chain.
+    pub merchant_name_hash: Option<BytesN<32>>,
+}
+
+/// Identity of a deployment returned by `info()`: the build's name and
+/// version (also in the WASM's `contractmeta`) plus its [`DeploymentInfo`].
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractInfo {
+    pub name: String,
+    pub version: String,
+    pub deployment: DeploymentInfo,
+}
+
+/// Snapshot of storage usage returned by `storage_stats()`.
+///
+/// Counts are per key family; multiply by the average entry size and the
+/// network's rent rate to estimate rent liabilities. TTLs are in ledgers.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StorageStats {
+    /// Payments ever recorded, including hashed ones and ones since
+    /// compacted into a [`DataKey::CompactedPayment`].
+    pub payments: u32,
+    /// [`DataKey::HashedPayment`] entries.
+    pub hashed_payments: u32,
+    /// [`DataKey::CompactedPayment`] entries, each of which replaced a full
+    /// record.
+    pub compacted_payments: u32,
+    /// [`DataKey::ExternalPayment`] entries.
+    pub external_payments: u32,
+    /// [`DataKey::ChainPayment`] entries.
+    pub chain_payments: u32,
+    /// [`DataKey::DepositInvoice`] entries.
+    pub deposit_invoices: u32,
+    /// [`DataKey::InstallmentPlan`] entries.
+    pub installment_plans: u32,
+    /// Live [`DataKey::PaymentSeq`] entries: payments that are neither
+    /// hashed nor compacted yet.
+    pub payment_index: u32,
+    /// [`DataKey::RemittanceInvoice`] entries.
+    pub remittance_index: u32,
+    /// Cumulative number of candidates ever written to
+    /// [`DataKey::PendingPayment`]. Never decreases: temporary entries expire
+    /// on their own, so live ones cannot be counted.
+    pub pending_reported_total: u32,
+    /// Persistent / instance entries are extended when their TTL drops below this.
+    pub min_ttl: u32,
+    /// TTL persistent / instance entries are extended to.
+    pub bump_ttl: u32,
+    /// Fixed TTL of temporary pending entries.
+    pub pending_ttl: u32,
+}
+
+/// Result of `check_invariants()` over the payments with sequence numbers
+/// `from..to`. Every list is empty and every flag `true` on a consistent
+/// contract; `ok` summarises them.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvariantReport {
+    /// First sequence number checked.
+    pub from: u32,
+    /// One past the last sequence number checked (at most `count`).
+    pub to: u32,
+    /// Payment counter at the time of the check.
+    pub count: u32,
+    /// Sequence numbers in range with an index entry. The others belong to
+    /// hashed payments or to records since compacted.
+    pub indexed: u32,
+    /// No index entry exists under the counter's own value, i.e. the
+    /// counter has not fallen behind the index.
+    pub counter_matches_index: bool,
+    /// Sum of `amount` per asset over the indexed records in range, for
+    /// comparison with off-chain totals.
+    pub totals: Map<Asset, i128>,
+    /// Sequence numbers whose index entry points at no full record.
+    pub dangling: Vec<u32>,
+    /// Invoices in range whose stored amount is not positive.
+    pub invalid_amounts: Vec<String>,
+    /// Invoices in range refunded beyond their recorded amount.
+    pub over_refunded: Vec<String>,
+    pub ok: bool,
+}
+
 // Admin helpers (instance storage)
 
 /// Return `true` if the contract has been initialised.
@@ -98,43 +1058,1040 @@ pub fn set_admin(env: &Env, admin: &Address) {
     env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
 }
 
-// Payment helpers (persistent storage)
+/// Return the nonce `admin` must pass to its next config change or transfer.
+pub fn get_admin_nonce(env: &Env, admin: &Address) -> u64 {
+    let key = DataKey::AdminNonce(admin.clone());
+    let nonce: Option<u64> = env.storage().persistent().get(&key);
+    match nonce {
+        Some(nonce) => {
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+            nonce
+        }
+        None => 0,
+    }
+}
 
-/// Return `true` if a [`PaymentRecord`] exists for `invoice_id`.
-pub fn has_payment(env: &Env, invoice_id: &String) -> bool {
+/// Persist the next operation nonce of `admin`.
+pub fn set_admin_nonce(env: &Env, admin: &Address, nonce: u64) {
+    let key = DataKey::AdminNonce(admin.clone());
+    env.storage().persistent().set(&key, &nonce);
     env.storage()
         .persistent()
-        .has(&DataKey::Payment(invoice_id.clone()))
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
 }
 
-/// Read a stored [`PaymentRecord`].
+// Receipt contract helpers (instance storage)
+
+/// Return the configured receipts contract, if any.
+pub fn get_receipt_contract(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::ReceiptContract)
+}
+
+/// Set or clear the receipts contract and extend instance TTL.
+pub fn set_receipt_contract(env: &Env, receipt_contract: &Option<Address>) {
+    match receipt_contract {
+        Some(address) => env
+            .storage()
+            .instance()
+            .set(&DataKey::ReceiptContract, address),
+        None => env.storage().instance().remove(&DataKey::ReceiptContract),
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+// Merchant helpers
+
+/// Return the configured merchant registry, if any.
+pub fn get_merchant_registry(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::MerchantRegistry)
+}
+
+/// Set or clear the merchant registry and extend instance TTL.
+pub fn set_merchant_registry(env: &Env, registry: &Option<Address>) {
+    match registry {
+        Some(address) => env
+            .storage()
+            .instance()
+            .set(&DataKey::MerchantRegistry, address),
+        None => env.storage().instance().remove(&DataKey::MerchantRegistry),
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return the `merchant_id` recorded with `invoice_id`, if any.
+pub fn get_payment_merchant(env: &Env, invoice_id: &String) -> Option<String> {
+    let key = DataKey::PaymentMerchant(invoice_id.clone());
+    let merchant_id: Option<String> = env.storage().persistent().get(&key);
+    if merchant_id.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    merchant_id
+}
+
+/// Link `invoice_id` to `merchant_id` and bump the entry's TTL.
+pub fn set_payment_merchant(env: &Env, invoice_id: &String, merchant_id: &String) {
+    let key = DataKey::PaymentMerchant(invoice_id.clone());
+    env.storage().persistent().set(&key, merchant_id);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+/// Return how many receipt numbers `merchant_id` was issued (0 if none).
+pub fn get_receipt_count(env: &Env, merchant_id: &String) -> u64 {
+    let key = DataKey::ReceiptCount(merchant_id.clone());
+    let count: Option<u64> = env.storage().persistent().get(&key);
+    if count.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    count.unwrap_or(0)
+}
+
+/// Issue the next receipt number of `merchant_id`, starting at 1.
+pub fn next_receipt_number(env: &Env, merchant_id: &String) -> u64 {
+    let number = get_receipt_count(env, merchant_id) + 1;
+    let key = DataKey::ReceiptCount(merchant_id.clone());
+    env.storage().persistent().set(&key, &number);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    number
+}
+
+/// Return the receipt number of the merchant payment for `invoice_id`.
+pub fn get_receipt_number(env: &Env, invoice_id: &String) -> Option<u64> {
+    let key = DataKey::ReceiptNumber(invoice_id.clone());
+    let number: Option<u64> = env.storage().persistent().get(&key);
+    if number.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    number
+}
+
+pub fn set_receipt_number(env: &Env, invoice_id: &String, number: u64) {
+    let key = DataKey::ReceiptNumber(invoice_id.clone());
+    env.storage().persistent().set(&key, &number);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+/// Return when the customer paid `invoice_id`, if it was recorded backdated.
+pub fn get_paid_at(env: &Env, invoice_id: &String) -> Option<u64> {
+    let key = DataKey::PaidAt(invoice_id.clone());
+    let paid_at: Option<u64> = env.storage().persistent().get(&key);
+    if paid_at.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    paid_at
+}
+
+/// Store the customer payment time of a backdated `invoice_id` and bump the
+/// entry's TTL.
+pub fn set_paid_at(env: &Env, invoice_id: &String, paid_at: u64) {
+    let key = DataKey::PaidAt(invoice_id.clone());
+    env.storage().persistent().set(&key, &paid_at);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+// Metadata helpers
+
+/// Return the metadata blob attached to `invoice_id`, if any.
+pub fn get_metadata(env: &Env, invoice_id: &String) -> Option<Bytes> {
+    let key = DataKey::Metadata(invoice_id.clone());
+    let blob: Option<Bytes> = env.storage().persistent().get(&key);
+    if blob.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    blob
+}
+
+/// Attach, replace or (with `None`) remove the metadata blob of `invoice_id`.
+pub fn set_metadata(env: &Env, invoice_id: &String, blob: &Option<Bytes>) {
+    let key = DataKey::Metadata(invoice_id.clone());
+    match blob {
+        Some(blob) => {
+            env.storage().persistent().set(&key, blob);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+        }
+        None => env.storage().persistent().remove(&key),
+    }
+}
+
+// FX rate helpers
+
+/// Return the configured price oracle, if any.
+pub fn get_fx_oracle(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::FxOracle)
+}
+
+/// Set or clear the price oracle and extend instance TTL.
+pub fn set_fx_oracle(env: &Env, oracle: &Option<Address>) {
+    match oracle {
+        Some(address) => env.storage().instance().set(&DataKey::FxOracle, address),
+        None => env.storage().instance().remove(&DataKey::FxOracle),
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+// Observer helpers
+
+/// Return the registered observer contracts (empty if none).
+pub fn get_observers(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Observers)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Replace the registered observers and extend instance TTL.
+pub fn set_observers(env: &Env, observers: &Vec<Address>) {
+    if observers.is_empty() {
+        env.storage().instance().remove(&DataKey::Observers);
+    } else {
+        env.storage().instance().set(&DataKey::Observers, observers);
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return the key authorizing signed payments, if any.
+pub fn get_signing_key(env: &Env) -> Option<BytesN<32>> {
+    env.storage().instance().get(&DataKey::SigningKey)
+}
+
+/// Set or clear the signing key and extend instance TTL.
+pub fn set_signing_key(env: &Env, key: &Option<BytesN<32>>) {
+    match key {
+        Some(key) => env.storage().instance().set(&DataKey::SigningKey, key),
+        None => env.storage().instance().remove(&DataKey::SigningKey),
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return the registered validator contract, if any.
+pub fn get_validator(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKey::Validator)
+}
+
+/// Set or clear the validator contract and extend instance TTL.
+pub fn set_validator(env: &Env, validator: &Option<Address>) {
+    match validator {
+        Some(address) => env.storage().instance().set(&DataKey::Validator, address),
+        None => env.storage().instance().remove(&DataKey::Validator),
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return the issuer interned as [`StoredAsset::Usdc`], if set.
+pub fn get_usdc_issuer(env: &Env) -> Option<String> {
+    env.storage().instance().get(&DataKey::UsdcIssuer)
+}
+
+/// Store the USDC issuer and extend instance TTL. Callers must make sure it
+/// is only ever set once.
+pub fn set_usdc_issuer(env: &Env, issuer: &String) {
+    env.storage().instance().set(&DataKey::UsdcIssuer, issuer);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return the [`DeploymentInfo`], with every field `None` if never set.
+pub fn get_deployment_info(env: &Env) -> DeploymentInfo {
+    env.storage()
+        .instance()
+        .get(&DataKey::DeploymentInfo)
+        .unwrap_or(DeploymentInfo {
+            label: None,
+            environment: None,
+            merchant_name_hash: None,
+        })
+}
+
+pub fn set_deployment_info(env: &Env, info: &DeploymentInfo) {
+    env.storage().instance().set(&DataKey::DeploymentInfo, info);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return the [`FxRate`] captured for `invoice_id`, if any.
+pub fn get_payment_rate(env: &Env, invoice_id: &String) -> Option<FxRate> {
+    let key = DataKey::PaymentRate(invoice_id.clone());
+    let rate: Option<FxRate> = env.storage().persistent().get(&key);
+    if rate.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    rate
+}
+
+/// Persist the [`FxRate`] for `invoice_id` and bump its TTL.
+pub fn set_payment_rate(env: &Env, invoice_id: &String, rate: &FxRate) {
+    let key = DataKey::PaymentRate(invoice_id.clone());
+    env.storage().persistent().set(&key, rate);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+// Compliance helpers
+
+/// Return the compliance policy (the default, enforcing nothing, if unset).
+pub fn get_compliance_policy(env: &Env) -> CompliancePolicy {
+    env.storage()
+        .instance()
+        .get(&DataKey::CompliancePolicy)
+        .unwrap_or_default()
+}
+
+/// Persist the compliance policy and extend instance TTL.
+pub fn set_compliance_policy(env: &Env, policy: &CompliancePolicy) {
+    env.storage()
+        .instance()
+        .set(&DataKey::CompliancePolicy, policy);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return the [`PayerFlags`] of `payer` (all `false` if never screened).
+pub fn get_payer_flags(env: &Env, payer: &Address) -> PayerFlags {
+    let key = DataKey::PayerFlags(payer.clone());
+    let flags: Option<PayerFlags> = env.storage().persistent().get(&key);
+    match flags {
+        Some(flags) => {
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+            flags
+        }
+        None => PayerFlags::default(),
+    }
+}
+
+/// Persist the [`PayerFlags`] of `payer` and bump their TTL.
+pub fn set_payer_flags(env: &Env, payer: &Address, flags: &PayerFlags) {
+    let key = DataKey::PayerFlags(payer.clone());
+    env.storage().persistent().set(&key, flags);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+/// Return `true` if `payer` is on the blocklist.
+pub fn is_payer_blocked(env: &Env, payer: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::BlockedPayer(payer.clone()))
+}
+
+/// Add `payer` to or remove it from the blocklist.
+pub fn set_payer_blocked(env: &Env, payer: &Address, blocked: bool) {
+    let key = DataKey::BlockedPayer(payer.clone());
+    if blocked {
+        env.storage().persistent().set(&key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+// Circuit breaker helpers (instance storage)
+
+/// Return the circuit breaker thresholds, if the breaker is on.
+pub fn get_circuit_breaker(env: &Env) -> Option<CircuitBreaker> {
+    env.storage().instance().get(&DataKey::CircuitBreaker)
+}
+
+/// Set or clear the circuit breaker and extend instance TTL.
+pub fn set_circuit_breaker(env: &Env, breaker: &Option<CircuitBreaker>) {
+    match breaker {
+        Some(breaker) => env
+            .storage()
+            .instance()
+            .set(&DataKey::CircuitBreaker, breaker),
+        None => env.storage().instance().remove(&DataKey::CircuitBreaker),
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+// Keeper helpers
+
+pub fn get_keeper_bounty(env: &Env) -> Option<KeeperBounty> {
+    env.storage().instance().get(&DataKey::KeeperBounty)
+}
+
+/// Set or clear the keeper bounty and extend instance TTL.
+pub fn set_keeper_bounty(env: &Env, bounty: &Option<KeeperBounty>) {
+    match bounty {
+        Some(bounty) => env
+            .storage()
+            .instance()
+            .set(&DataKey::KeeperBounty, bounty),
+        None => env.storage().instance().remove(&DataKey::KeeperBounty),
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Whether the record of `invoice_id` was written or bumped less than
+/// `KEEPER_COOLDOWN_TTL` ledgers ago.
+pub fn is_ttl_fresh(env: &Env, invoice_id: &String) -> bool {
+    env.storage()
+        .temporary()
+        .has(&DataKey::TtlFresh(invoice_id.clone()))
+}
+
+/// Return the balance of `token` set aside for keeper bounties.
+pub fn get_keeper_pool(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::KeeperPool(token.clone()))
+        .unwrap_or(0)
+}
+
+/// Store the keeper pool balance of `token` and extend instance TTL.
+pub fn set_keeper_pool(env: &Env, token: &Address, amount: i128) {
+    env.storage()
+        .instance()
+        .set(&DataKey::KeeperPool(token.clone()), &amount);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Mark the record of `invoice_id` as freshly bumped to `BUMP_TTL` for
+/// `KEEPER_COOLDOWN_TTL` ledgers.
+pub fn set_ttl_fresh(env: &Env, invoice_id: &String) {
+    let key = DataKey::TtlFresh(invoice_id.clone());
+    env.storage().temporary().set(&key, &true);
+    env.storage()
+        .temporary()
+        .extend_ttl(&key, KEEPER_COOLDOWN_TTL, KEEPER_COOLDOWN_TTL);
+}
+
+/// Return the current breaker window (empty if nothing was counted yet).
+pub fn get_breaker_window(env: &Env) -> BreakerWindow {
+    env.storage()
+        .instance()
+        .get(&DataKey::BreakerWindow)
+        .unwrap_or_default()
+}
+
+pub fn set_breaker_window(env: &Env, window: &BreakerWindow) {
+    env.storage().instance().set(&DataKey::BreakerWindow, window);
+}
+
+/// Return `true` while writes are paused.
+pub fn is_paused(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::Paused)
+        .unwrap_or(false)
+}
+
+pub fn set_paused(env: &Env, paused: bool) {
+    env.storage().instance().set(&DataKey::Paused, &paused);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+// Terms helpers
+
+/// Return the [`TermsAcceptance`] for `invoice_id`, if any.
+pub fn get_terms_acceptance(env: &Env, invoice_id: &String) -> Option<TermsAcceptance> {
+    let key = DataKey::TermsAcceptance(invoice_id.clone());
+    let acceptance: Option<TermsAcceptance> = env.storage().persistent().get(&key);
+    if acceptance.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    acceptance
+}
+
+/// Persist the [`TermsAcceptance`] for `invoice_id` and bump its TTL.
+pub fn set_terms_acceptance(env: &Env, invoice_id: &String, acceptance: &TermsAcceptance) {
+    let key = DataKey::TermsAcceptance(invoice_id.clone());
+    env.storage().persistent().set(&key, acceptance);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+// Preauthorization helpers
+
+/// Return the [`Preauthorization`] for `invoice_id`, if any.
+pub fn get_preauthorization(env: &Env, invoice_id: &String) -> Option<Preauthorization> {
+    let key = DataKey::Preauthorization(invoice_id.clone());
+    let preauth: Option<Preauthorization> = env.storage().persistent().get(&key);
+    if preauth.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    preauth
+}
+
+/// Persist the [`Preauthorization`] for `invoice_id` and bump its TTL.
+pub fn set_preauthorization(env: &Env, invoice_id: &String, preauth: &Preauthorization) {
+    let key = DataKey::Preauthorization(invoice_id.clone());
+    env.storage().persistent().set(&key, preauth);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+/// Return the preauthorization threshold for `asset`, if any.
+pub fn get_preauth_threshold(env: &Env, asset: &Asset) -> Option<i128> {
+    env.storage()
+        .instance()
+        .get(&DataKey::PreauthThreshold(asset.clone()))
+}
+
+/// Set or clear the preauthorization threshold for `asset` and extend
+/// instance TTL.
+pub fn set_preauth_threshold(env: &Env, asset: &Asset, threshold: &Option<i128>) {
+    let key = DataKey::PreauthThreshold(asset.clone());
+    match threshold {
+        Some(threshold) => env.storage().instance().set(&key, threshold),
+        None => env.storage().instance().remove(&key),
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+pub fn has_deposit_invoice(env: &Env, invoice_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::DepositInvoice(invoice_id.clone()))
+}
+
+/// Read the [`DepositInvoice`] for `invoice_id`.
+///
+/// Returns [`ContractError::InvoiceScheduleNotFound`] if there is none.
+pub fn get_deposit_invoice(
+    env: &Env,
+    invoice_id: &String,
+) -> Result<DepositInvoice, ContractError> {
+    let key = DataKey::DepositInvoice(invoice_id.clone());
+    let invoice = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::InvoiceScheduleNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(invoice)
+}
+
+/// Persist the [`DepositInvoice`] for `invoice_id` and bump its TTL.
+pub fn set_deposit_invoice(env: &Env, invoice_id: &String, invoice: &DepositInvoice) {
+    let key = DataKey::DepositInvoice(invoice_id.clone());
+    env.storage().persistent().set(&key, invoice);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+pub fn has_installment_plan(env: &Env, invoice_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::InstallmentPlan(invoice_id.clone()))
+}
+
+/// Read the [`InstallmentPlan`] for `invoice_id`.
+///
+/// Returns [`ContractError::InvoiceScheduleNotFound`] if there is none.
+pub fn get_installment_plan(
+    env: &Env,
+    invoice_id: &String,
+) -> Result<InstallmentPlan, ContractError> {
+    let key = DataKey::InstallmentPlan(invoice_id.clone());
+    let plan = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::InvoiceScheduleNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(plan)
+}
+
+/// Persist the [`InstallmentPlan`] for `invoice_id` and bump its TTL.
+pub fn set_installment_plan(env: &Env, invoice_id: &String, plan: &InstallmentPlan) {
+    let key = DataKey::InstallmentPlan(invoice_id.clone());
+    env.storage().persistent().set(&key, plan);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+/// Return the total refunded against `invoice_id` (0 if none).
+pub fn get_refunded_total(env: &Env, invoice_id: &String) -> i128 {
+    let key = DataKey::RefundedTotal(invoice_id.clone());
+    let total: Option<i128> = env.storage().persistent().get(&key);
+    if total.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    total.unwrap_or(0)
+}
+
+/// Persist the refunded total of `invoice_id` and bump its TTL.
+pub fn set_refunded_total(env: &Env, invoice_id: &String, total: i128) {
+    let key = DataKey::RefundedTotal(invoice_id.clone());
+    env.storage().persistent().set(&key, &total);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+/// Read the [`Quote`] stored under `quote_id`.
+///
+/// Returns [`ContractError::QuoteNotFound`] if there is none.
+pub fn get_quote(env: &Env, quote_id: &String) -> Result<Quote, ContractError> {
+    let key = DataKey::Quote(quote_id.clone());
+    let quote = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::QuoteNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(quote)
+}
+
+/// Persist the [`Quote`] under `quote_id` and bump its TTL.
+pub fn set_quote(env: &Env, quote_id: &String, quote: &Quote) {
+    let key = DataKey::Quote(quote_id.clone());
+    env.storage().persistent().set(&key, quote);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+/// Return the [`Acknowledgement`] of `invoice_id`, if any.
+pub fn get_acknowledgement(env: &Env, invoice_id: &String) -> Option<Acknowledgement> {
+    let key = DataKey::Acknowledgement(invoice_id.clone());
+    let ack: Option<Acknowledgement> = env.storage().persistent().get(&key);
+    if ack.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    ack
+}
+
+/// Persist the [`Acknowledgement`] of `invoice_id` and bump its TTL.
+pub fn set_acknowledgement(env: &Env, invoice_id: &String, ack: &Acknowledgement) {
+    let key = DataKey::Acknowledgement(invoice_id.clone());
+    env.storage().persistent().set(&key, ack);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+pub fn is_terms_required(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::TermsRequired)
+        .unwrap_or(false)
+}
+
+pub fn set_terms_required(env: &Env, required: bool) {
+    env.storage()
+        .instance()
+        .set(&DataKey::TermsRequired, &required);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return the annual interest rate on overdue installments, in basis
+/// points (0 if unset).
+pub fn get_interest_rate_bps(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::InterestRateBps)
+        .unwrap_or(0)
+}
+
+pub fn set_interest_rate_bps(env: &Env, rate_bps: u32) {
+    env.storage()
+        .instance()
+        .set(&DataKey::InterestRateBps, &rate_bps);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+// Volume limit helpers
+
+/// Return the volume limit for `asset`, if any.
+pub fn get_volume_limit(env: &Env, asset: &Asset) -> Option<i128> {
+    env.storage()
+        .instance()
+        .get(&DataKey::VolumeLimit(asset.clone()))
+}
+
+/// Set or clear the volume limit for `asset` and extend instance TTL.
+pub fn set_volume_limit(env: &Env, asset: &Asset, limit: &Option<i128>) {
+    let key = DataKey::VolumeLimit(asset.clone());
+    match limit {
+        Some(limit) => env.storage().instance().set(&key, limit),
+        None => env.storage().instance().remove(&key),
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return `payer`'s volume buckets for `asset` (bucket index → amount);
+/// empty if none are live.
+pub fn get_payer_volume(env: &Env, payer: &Address, asset: &Asset) -> Map<u64, i128> {
+    env.storage()
+        .temporary()
+        .get(&DataKey::PayerVolume(payer.clone(), asset.clone()))
+        .unwrap_or_else(|| Map::new(env))
+}
+
+/// Persist `payer`'s volume buckets for `asset` with a fresh [`VOLUME_TTL`].
+pub fn set_payer_volume(env: &Env, payer: &Address, asset: &Asset, buckets: &Map<u64, i128>) {
+    let key = DataKey::PayerVolume(payer.clone(), asset.clone());
+    env.storage().temporary().set(&key, buckets);
+    env.storage()
+        .temporary()
+        .extend_ttl(&key, VOLUME_TTL, VOLUME_TTL);
+}
+
+// Payment helpers (persistent storage)
+
+/// Return `true` if a payment was recorded for `invoice_id`, whether its
+/// full [`PaymentRecord`] is still stored or it has been compacted.
+pub fn has_payment(env: &Env, invoice_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Payment(invoice_id.clone()))
+        || has_compacted_payment(env, invoice_id)
+}
+
+/// Read a stored [`PaymentRecord`].
+///
+/// Returns [`ContractError::PaymentCompacted`] if only its summary is left,
+/// and [`ContractError::PaymentNotFound`] if nothing has been recorded for
+/// `invoice_id`.
+pub fn get_payment(env: &Env, invoice_id: &String) -> Result<PaymentRecord, ContractError> {
+    let key = DataKey::Payment(invoice_id.clone());
+    let stored: Option<StoredPayment> = env.storage().persistent().get(&key);
+    match stored {
+        Some(s) => {
+            // Extend TTL every time we read so hot records stay alive.
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+            Ok(PaymentRecord {
+                invoice_id: invoice_id.clone(),
+                payer: s.payer,
+                payer_mux_id: s.payer_mux_id,
+                asset: expand_asset(env, s.asset),
+                amount: s.amount,
+                timestamp: s.timestamp,
+            })
+        }
+        None if has_compacted_payment(env, invoice_id) => Err(ContractError::PaymentCompacted),
+        None => Err(ContractError::PaymentNotFound),
+    }
+}
+
+/// Delete the full record of `invoice_id`.
+pub fn remove_payment(env: &Env, invoice_id: &String) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::Payment(invoice_id.clone()));
+}
+
+/// Extend the TTL of a stored [`PaymentRecord`], and of the entries written
+/// beside it when it was recorded, without reading them.
+///
+/// Returns [`ContractError::PaymentNotFound`] if nothing has been recorded for
+/// `invoice_id`.
+pub fn extend_payment_ttl(env: &Env, invoice_id: &String) -> Result<(), ContractError> {
+    let key = DataKey::Payment(invoice_id.clone());
+    if !env.storage().persistent().has(&key) {
+        if has_compacted_payment(env, invoice_id) {
+            return Err(ContractError::PaymentCompacted);
+        }
+        return Err(ContractError::PaymentNotFound);
+    }
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    for key in [
+        DataKey::PaymentMerchant(invoice_id.clone()),
+        DataKey::ReceiptNumber(invoice_id.clone()),
+        DataKey::PaymentRate(invoice_id.clone()),
+        DataKey::PaidAt(invoice_id.clone()),
+        DataKey::RefundedTotal(invoice_id.clone()),
+        DataKey::PaymentTax(invoice_id.clone()),
+        DataKey::Conversion(invoice_id.clone()),
+    ] {
+        extend_if_present(env, &key);
+    }
+    // Reading the remittance bumps it; its transaction index goes with it.
+    if let Some(remittance) = get_remittance(env, invoice_id) {
+        extend_if_present(env, &DataKey::RemittanceInvoice(remittance.transaction_id));
+    }
+    Ok(())
+}
+
+/// Extend the TTL of persistent entry `key`, if it exists.
+fn extend_if_present(env: &Env, key: &DataKey) {
+    if env.storage().persistent().has(key) {
+        env.storage()
+            .persistent()
+            .extend_ttl(key, MIN_TTL, BUMP_TTL);
+    }
+}
+
+/// Persist a new [`PaymentRecord`] as a [`StoredPayment`], bump its TTL and
+/// mark it fresh.
+pub fn set_payment(env: &Env, record: &PaymentRecord) {
+    let key = DataKey::Payment(record.invoice_id.clone());
+    let stored = StoredPayment {
+        payer: record.payer.clone(),
+        payer_mux_id: record.payer_mux_id,
+        asset: intern_asset(env, &record.asset),
+        amount: record.amount,
+        timestamp: record.timestamp,
+    };
+    env.storage().persistent().set(&key, &stored);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    set_ttl_fresh(env, &record.invoice_id);
+}
+
+fn intern_asset(env: &Env, asset: &Asset) -> StoredAsset {
+    match asset {
+        Asset::Native => StoredAsset::Native,
+        Asset::Token(code, issuer)
+            if *code == String::from_str(env, "USDC")
+                && get_usdc_issuer(env).as_ref() == Some(issuer) =>
+        {
+            StoredAsset::Usdc
+        }
+        Asset::Token(code, issuer) => StoredAsset::Token(code.clone(), issuer.clone()),
+    }
+}
+
+fn expand_asset(env: &Env, asset: StoredAsset) -> Asset {
+    match asset {
+        StoredAsset::Native => Asset::Native,
+        StoredAsset::Usdc => Asset::Token(
+            String::from_str(env, "USDC"),
+            get_usdc_issuer(env).expect("USDC issuer is never removed once set"),
+        ),
+        StoredAsset::Token(code, issuer) => Asset::Token(code, issuer),
+    }
+}
+
+// Hashed payment helpers (persistent storage)
+
+/// Return `true` if a [`HashedPaymentRecord`] exists for `invoice_hash`.
+pub fn has_hashed_payment(env: &Env, invoice_hash: &BytesN<32>) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::HashedPayment(invoice_hash.clone()))
+}
+
+/// Read a [`HashedPaymentRecord`], extending its TTL like [`get_payment`].
+///
+/// Returns [`ContractError::PaymentNotFound`] if nothing has been recorded for
+/// `invoice_hash`.
+pub fn get_hashed_payment(
+    env: &Env,
+    invoice_hash: &BytesN<32>,
+) -> Result<HashedPaymentRecord, ContractError> {
+    let key = DataKey::HashedPayment(invoice_hash.clone());
+    let stored: StoredPayment = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::PaymentNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(HashedPaymentRecord {
+        invoice_hash: invoice_hash.clone(),
+        payer: stored.payer,
+        asset: expand_asset(env, stored.asset),
+        amount: stored.amount,
+        timestamp: stored.timestamp,
+    })
+}
+
+/// Persist a new [`HashedPaymentRecord`] as a [`StoredPayment`] and bump its
+/// TTL.
+pub fn set_hashed_payment(env: &Env, record: &HashedPaymentRecord) {
+    let key = DataKey::HashedPayment(record.invoice_hash.clone());
+    let stored = StoredPayment {
+        payer: record.payer.clone(),
+        payer_mux_id: None,
+        asset: intern_asset(env, &record.asset),
+        amount: record.amount,
+        timestamp: record.timestamp,
+    };
+    env.storage().persistent().set(&key, &stored);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+// External payment helpers (persistent storage)
+
+/// Return `true` if an [`ExternalPaymentRecord`] exists for `invoice_id`.
+pub fn has_external_payment(env: &Env, invoice_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::ExternalPayment(invoice_id.clone()))
+}
+
+/// Read an [`ExternalPaymentRecord`], extending its TTL like [`get_payment`].
+///
+/// Returns [`ContractError::PaymentNotFound`] if none was recorded for
+/// `invoice_id`.
+pub fn get_external_payment(
+    env: &Env,
+    invoice_id: &String,
+) -> Result<ExternalPaymentRecord, ContractError> {
+    let key = DataKey::ExternalPayment(invoice_id.clone());
+    let record = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::PaymentNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(record)
+}
+
+/// Persist a new [`ExternalPaymentRecord`] and bump its TTL.
+pub fn set_external_payment(env: &Env, record: &ExternalPaymentRecord) {
+    let key = DataKey::ExternalPayment(record.invoice_id.clone());
+    env.storage().persistent().set(&key, record);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+// Chain payment helpers (persistent storage)
+
+/// Return `true` if a [`ChainPaymentRecord`] exists for `invoice_id`.
+pub fn has_chain_payment(env: &Env, invoice_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::ChainPayment(invoice_id.clone()))
+}
+
+/// Read a [`ChainPaymentRecord`], extending its TTL like [`get_payment`].
 ///
-/// Returns [`ContractError::PaymentNotFound`] if nothing has been recorded for
+/// Returns [`ContractError::PaymentNotFound`] if none was recorded for
 /// `invoice_id`.
-pub fn get_payment(env: &Env, invoice_id: &String) -> Result<PaymentRecord, ContractError> {
-    let key = DataKey::Payment(invoice_id.clone());
-    let record: Option<PaymentRecord> = env.storage().persistent().get(&key);
-    match record {
-        Some(r) => {
-            // Extend TTL every time we read so hot records stay alive.
-            env.storage()
-                .persistent()
-                .extend_ttl(&key, MIN_TTL, BUMP_TTL);
-            Ok(r)
-        }
-        None => Err(ContractError::PaymentNotFound),
-    }
+pub fn get_chain_payment(
+    env: &Env,
+    invoice_id: &String,
+) -> Result<ChainPaymentRecord, ContractError> {
+    let key = DataKey::ChainPayment(invoice_id.clone());
+    let record = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::PaymentNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(record)
 }
 
-/// Persist a new [`PaymentRecord`] and bump its TTL.
-pub fn set_payment(env: &Env, record: &PaymentRecord) {
-    let key = DataKey::Payment(record.invoice_id.clone());
+/// Persist a new [`ChainPaymentRecord`] and bump its TTL.
+pub fn set_chain_payment(env: &Env, record: &ChainPaymentRecord) {
+    let key = DataKey::ChainPayment(record.invoice_id.clone());
     env.storage().persistent().set(&key, record);
     env.storage()
         .persistent()
         .extend_ttl(&key, MIN_TTL, BUMP_TTL);
 }
 
+// Retention helpers
+
+/// Return the `invoice_id` recorded with sequence number `seq`, if it is
+/// still indexed.
+pub fn get_payment_seq(env: &Env, seq: u32) -> Option<String> {
+    env.storage().persistent().get(&DataKey::PaymentSeq(seq))
+}
+
+/// Index `invoice_id` under `seq` and bump the entry's TTL.
+pub fn set_payment_seq(env: &Env, seq: u32, invoice_id: &String) {
+    let key = DataKey::PaymentSeq(seq);
+    env.storage().persistent().set(&key, invoice_id);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+pub fn remove_payment_seq(env: &Env, seq: u32) {
+    env.storage().persistent().remove(&DataKey::PaymentSeq(seq));
+}
+
+pub fn has_compacted_payment(env: &Env, invoice_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::CompactedPayment(invoice_id.clone()))
+}
+
+/// Return the [`CompactedPayment`] of `invoice_id`, if it was compacted.
+pub fn get_compacted_payment(env: &Env, invoice_id: &String) -> Option<CompactedPayment> {
+    let key = DataKey::CompactedPayment(invoice_id.clone());
+    let summary: Option<CompactedPayment> = env.storage().persistent().get(&key);
+    if summary.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    summary
+}
+
+/// Persist the [`CompactedPayment`] of `invoice_id` and bump its TTL.
+pub fn set_compacted_payment(env: &Env, invoice_id: &String, summary: &CompactedPayment) {
+    let key = DataKey::CompactedPayment(invoice_id.clone());
+    env.storage().persistent().set(&key, summary);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+/// Return the sequence number compaction resumes from (0 if never run).
+pub fn get_compact_cursor(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::CompactCursor)
+        .unwrap_or(0)
+}
+
+pub fn set_compact_cursor(env: &Env, seq: u32) {
+    env.storage().instance().set(&DataKey::CompactCursor, &seq);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
 // Payment counter helpers (instance storage)
 
 /// Return the current payment count (0 if not yet set).
@@ -145,6 +2102,55 @@ pub fn get_count(env: &Env) -> u32 {
         .unwrap_or(0u32)
 }
 
+/// Return the net amount received per asset (empty if nothing was recorded).
+pub fn get_received_totals(env: &Env) -> Map<Asset, i128> {
+    env.storage()
+        .instance()
+        .get(&DataKey::ReceivedTotals)
+        .unwrap_or_else(|| Map::new(env))
+}
+
+/// Add `delta` (negative for a refund) to the total received in `asset`
+/// and extend instance TTL.
+pub fn add_received(env: &Env, asset: &Asset, delta: i128) {
+    let mut totals = get_received_totals(env);
+    let total = totals.get(asset.clone()).unwrap_or(0);
+    totals.set(asset.clone(), total.saturating_add(delta));
+    env.storage()
+        .instance()
+        .set(&DataKey::ReceivedTotals, &totals);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return the per-asset totals of day `day` (empty if nothing was recorded).
+pub fn get_daily_totals(env: &Env, day: u32) -> Map<Asset, PeriodTotals> {
+    get_period_totals(env, &DataKey::DailyTotals(day))
+}
+
+pub fn set_daily_totals(env: &Env, day: u32, totals: &Map<Asset, PeriodTotals>) {
+    set_period_totals(env, &DataKey::DailyTotals(day), totals);
+}
+
+fn get_period_totals(env: &Env, key: &DataKey) -> Map<Asset, PeriodTotals> {
+    let totals: Option<Map<Asset, PeriodTotals>> = env.storage().persistent().get(key);
+    match totals {
+        Some(totals) => {
+            env.storage()
+                .persistent()
+                .extend_ttl(key, MIN_TTL, BUMP_TTL);
+            totals
+        }
+        None => Map::new(env),
+    }
+}
+
+fn set_period_totals(env: &Env, key: &DataKey, totals: &Map<Asset, PeriodTotals>) {
+    env.storage().persistent().set(key, totals);
+    env.storage()
+        .persistent()
+        .extend_ttl(key, MIN_TTL, BUMP_TTL);
+}
+
 /// Increment the payment counter and extend instance TTL.
 pub fn bump_count(env: &Env) {
     let count = get_count(env);
@@ -153,3 +2159,442 @@ pub fn bump_count(env: &Env) {
         .set(&DataKey::PaymentCount, &(count + 1u32));
     env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
 }
+
+// Pending payment helpers (temporary storage)
+
+/// Return `true` if an unexpired pending record exists for `invoice_id`.
+pub fn has_pending_payment(env: &Env, invoice_id: &String) -> bool {
+    env.storage()
+        .temporary()
+        .has(&DataKey::PendingPayment(invoice_id.clone()))
+}
+
+/// Read a pending [`PaymentRecord`].
+///
+/// Returns [`ContractError::PendingPaymentNotFound`] if nothing was reported
+/// for `invoice_id` or the entry has already expired.
+pub fn get_pending_payment(env: &Env, invoice_id: &String) -> Result<PaymentRecord, ContractError> {
+    env.storage()
+        .temporary()
+        .get(&DataKey::PendingPayment(invoice_id.clone()))
+        .ok_or(ContractError::PendingPaymentNotFound)
+}
+
+/// Persist a pending [`PaymentRecord`] with a fixed [`PENDING_TTL`].
+///
+/// The TTL is deliberately **not** extended on read: a candidate that is not
+/// confirmed in time should simply disappear.
+pub fn set_pending_payment(env: &Env, record: &PaymentRecord) {
+    let key = DataKey::PendingPayment(record.invoice_id.clone());
+    env.storage().temporary().set(&key, record);
+    env.storage()
+        .temporary()
+        .extend_ttl(&key, PENDING_TTL, PENDING_TTL);
+}
+
+/// Delete a pending record (after promotion or explicit discard).
+pub fn remove_pending_payment(env: &Env, invoice_id: &String) {
+    env.storage()
+        .temporary()
+        .remove(&DataKey::PendingPayment(invoice_id.clone()));
+}
+
+/// Return the number of pending candidates ever reported.
+pub fn get_pending_count(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::PendingCount)
+        .unwrap_or(0u32)
+}
+
+/// Increment the pending candidate counter and extend instance TTL.
+pub fn bump_pending_count(env: &Env) {
+    let count = get_pending_count(env);
+    env.storage()
+        .instance()
+        .set(&DataKey::PendingCount, &(count + 1u32));
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+// Payout helpers
+
+/// Return the funds collected for `merchant_id` since its last settlement.
+pub fn get_merchant_balance(env: &Env, merchant_id: &String) -> Option<MerchantBalance> {
+    let key = DataKey::MerchantBalance(merchant_id.clone());
+    let balance: Option<MerchantBalance> = env.storage().persistent().get(&key);
+    if balance.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    balance
+}
+
+/// Store the balance of `merchant_id`, or remove it with `None` once
+/// settled.
+pub fn set_merchant_balance(env: &Env, merchant_id: &String, balance: &Option<MerchantBalance>) {
+    let key = DataKey::MerchantBalance(merchant_id.clone());
+    match balance {
+        Some(balance) => {
+            env.storage().persistent().set(&key, balance);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+        }
+        None => env.storage().persistent().remove(&key),
+    }
+}
+
+/// Return `true` if the funds of `invoice_id` were already collected.
+pub fn is_collected(env: &Env, invoice_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Collected(invoice_id.clone()))
+}
+
+pub fn set_collected(env: &Env, invoice_id: &String) {
+    let key = DataKey::Collected(invoice_id.clone());
+    env.storage().persistent().set(&key, &true);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+/// Returns [`ContractError::SettlementNotFound`] if there is no settlement
+/// `id`.
+pub fn get_settlement(env: &Env, id: u32) -> Result<Settlement, ContractError> {
+    let key = DataKey::Settlement(id);
+    let settlement: Settlement = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::SettlementNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(settlement)
+}
+
+/// Store `settlement` under its id and count it.
+pub fn add_settlement(env: &Env, settlement: &Settlement) {
+    let key = DataKey::Settlement(settlement.id);
+    env.storage().persistent().set(&key, settlement);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    env.storage()
+        .instance()
+        .set(&DataKey::SettlementCount, &(settlement.id + 1));
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return the number of settlements ever made (the id of the next one).
+pub fn get_settlement_count(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::SettlementCount)
+        .unwrap_or(0u32)
+}
+
+/// Return the payout schedule of `merchant_id`, if it set one.
+pub fn get_payout_schedule(env: &Env, merchant_id: &String) -> Option<PayoutSchedule> {
+    let key = DataKey::PayoutSchedule(merchant_id.clone());
+    let schedule: Option<PayoutSchedule> = env.storage().persistent().get(&key);
+    if schedule.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    schedule
+}
+
+/// Set the payout schedule of `merchant_id`, or remove it with `None`.
+pub fn set_payout_schedule(env: &Env, merchant_id: &String, schedule: &Option<PayoutSchedule>) {
+    let key = DataKey::PayoutSchedule(merchant_id.clone());
+    match schedule {
+        Some(schedule) => {
+            env.storage().persistent().set(&key, schedule);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+        }
+        None => env.storage().persistent().remove(&key),
+    }
+}
+
+/// Return the day of the last scheduled settlement of `merchant_id`.
+pub fn get_last_payout_day(env: &Env, merchant_id: &String) -> Option<u32> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::LastPayoutDay(merchant_id.clone()))
+}
+
+pub fn set_last_payout_day(env: &Env, merchant_id: &String, day: u32) {
+    let key = DataKey::LastPayoutDay(merchant_id.clone());
+    env.storage().persistent().set(&key, &day);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+// Credit note helpers (persistent storage)
+
+/// Read the [`CreditNote`] stored under `credit_id`.
+///
+/// Returns [`ContractError::CreditNoteNotFound`] if there is none.
+pub fn get_credit_note(env: &Env, credit_id: &String) -> Result<CreditNote, ContractError> {
+    let key = DataKey::CreditNote(credit_id.clone());
+    let note = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::CreditNoteNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(note)
+}
+
+pub fn has_credit_note(env: &Env, credit_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::CreditNote(credit_id.clone()))
+}
+
+/// Persist the [`CreditNote`] under `credit_id` and bump its TTL.
+pub fn set_credit_note(env: &Env, credit_id: &String, note: &CreditNote) {
+    let key = DataKey::CreditNote(credit_id.clone());
+    env.storage().persistent().set(&key, note);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+// Tax helpers (persistent storage)
+
+/// Return the [`InvoiceTax`] of `invoice_id`, if any.
+pub fn get_invoice_tax(env: &Env, invoice_id: &String) -> Option<InvoiceTax> {
+    let key = DataKey::InvoiceTax(invoice_id.clone());
+    let tax: Option<InvoiceTax> = env.storage().persistent().get(&key);
+    if tax.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    tax
+}
+
+pub fn set_invoice_tax(env: &Env, invoice_id: &String, tax: &Option<InvoiceTax>) {
+    let key = DataKey::InvoiceTax(invoice_id.clone());
+    match tax {
+        Some(tax) => {
+            env.storage().persistent().set(&key, tax);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+        }
+        None => env.storage().persistent().remove(&key),
+    }
+}
+
+/// Return the [`TaxSplit`] of the payment recorded for `invoice_id`, if any.
+pub fn get_payment_tax(env: &Env, invoice_id: &String) -> Option<TaxSplit> {
+    let key = DataKey::PaymentTax(invoice_id.clone());
+    let split: Option<TaxSplit> = env.storage().persistent().get(&key);
+    if split.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    split
+}
+
+pub fn set_payment_tax(env: &Env, invoice_id: &String, split: &TaxSplit) {
+    let key = DataKey::PaymentTax(invoice_id.clone());
+    env.storage().persistent().set(&key, split);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+// Accepted asset helpers (persistent storage)
+
+/// Return the assets `invoice_id` accepts and the amount expected in each,
+/// if it restricts them.
+pub fn get_accepted_assets(env: &Env, invoice_id: &String) -> Option<Map<Asset, i128>> {
+    let key = DataKey::AcceptedAssets(invoice_id.clone());
+    let assets: Option<Map<Asset, i128>> = env.storage().persistent().get(&key);
+    if assets.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    assets
+}
+
+/// Store the accepted assets of `invoice_id`; an empty map removes them.
+pub fn set_accepted_assets(env: &Env, invoice_id: &String, assets: &Map<Asset, i128>) {
+    let key = DataKey::AcceptedAssets(invoice_id.clone());
+    if assets.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, assets);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+}
+
+// Conversion helpers (persistent storage)
+
+/// Return the [`Conversion`] of the payment recorded for `invoice_id`, if
+/// it was converted.
+pub fn get_conversion(env: &Env, invoice_id: &String) -> Option<Conversion> {
+    let key = DataKey::Conversion(invoice_id.clone());
+    let conversion: Option<Conversion> = env.storage().persistent().get(&key);
+    if conversion.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    conversion
+}
+
+pub fn set_conversion(env: &Env, invoice_id: &String, conversion: &Conversion) {
+    let key = DataKey::Conversion(invoice_id.clone());
+    env.storage().persistent().set(&key, conversion);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+// Remittance helpers (persistent storage)
+
+/// Return the [`Remittance`] of the payment recorded for `invoice_id`, if
+/// it was a SEP-31 payment.
+pub fn get_remittance(env: &Env, invoice_id: &String) -> Option<Remittance> {
+    let key = DataKey::Remittance(invoice_id.clone());
+    let remittance: Option<Remittance> = env.storage().persistent().get(&key);
+    if remittance.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    remittance
+}
+
+/// Return the `invoice_id` settled by SEP-31 transaction `transaction_id`.
+pub fn get_remittance_invoice(env: &Env, transaction_id: &String) -> Option<String> {
+    let key = DataKey::RemittanceInvoice(transaction_id.clone());
+    let invoice_id: Option<String> = env.storage().persistent().get(&key);
+    if invoice_id.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    invoice_id
+}
+
+/// Store `remittance` for `invoice_id` and index the invoice by the
+/// remittance's transaction id.
+pub fn set_remittance(env: &Env, invoice_id: &String, remittance: &Remittance) {
+    let key = DataKey::Remittance(invoice_id.clone());
+    env.storage().persistent().set(&key, remittance);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    let key = DataKey::RemittanceInvoice(remittance.transaction_id.clone());
+    env.storage().persistent().set(&key, invoice_id);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+// Chargeback helpers (persistent storage)
+
+/// Return the [`Chargeback`] of `invoice_id`, if one was flagged.
+pub fn get_chargeback(env: &Env, invoice_id: &String) -> Option<Chargeback> {
+    let key = DataKey::Chargeback(invoice_id.clone());
+    let chargeback: Option<Chargeback> = env.storage().persistent().get(&key);
+    if chargeback.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    chargeback
+}
+
+pub fn set_chargeback(env: &Env, invoice_id: &String, chargeback: &Chargeback) {
+    let key = DataKey::Chargeback(invoice_id.clone());
+    env.storage().persistent().set(&key, chargeback);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+// Reconciliation helpers (persistent storage)
+
+/// Return the [`Reconciliation`] of the payment recorded for `invoice_id`.
+pub fn get_reconciliation(env: &Env, invoice_id: &String) -> Reconciliation {
+    let key = DataKey::Reconciliation(invoice_id.clone());
+    let status: Option<Reconciliation> = env.storage().persistent().get(&key);
+    if status.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    status.unwrap_or(Reconciliation::Unverified)
+}
+
+pub fn set_reconciliation(env: &Env, invoice_id: &String, status: Reconciliation) {
+    let key = DataKey::Reconciliation(invoice_id.clone());
+    if status == Reconciliation::Unverified {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &status);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+}
+
+// Stats
+
+/// Return the number of entries ever written to `family`.
+pub fn get_entry_count(env: &Env, family: KeyFamily) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::EntryCount(family))
+        .unwrap_or(0)
+}
+
+/// Count one more entry in `family` and extend instance TTL.
+pub fn bump_entry_count(env: &Env, family: KeyFamily) {
+    let count = get_entry_count(env, family);
+    env.storage()
+        .instance()
+        .set(&DataKey::EntryCount(family), &(count + 1));
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Assemble a [`StorageStats`] snapshot from the instance counters.
+pub fn storage_stats(env: &Env) -> StorageStats {
+    let payments = get_count(env);
+    let hashed_payments = get_entry_count(env, KeyFamily::HashedPayment);
+    let compacted_payments = get_entry_count(env, KeyFamily::CompactedPayment);
+    StorageStats {
+        payments,
+        hashed_payments,
+        compacted_payments,
+        external_payments: get_entry_count(env, KeyFamily::ExternalPayment),
+        chain_payments: get_entry_count(env, KeyFamily::ChainPayment),
+        deposit_invoices: get_entry_count(env, KeyFamily::DepositInvoice),
+        installment_plans: get_entry_count(env, KeyFamily::InstallmentPlan),
+        payment_index: payments - hashed_payments - compacted_payments,
+        remittance_index: get_entry_count(env, KeyFamily::RemittanceInvoice),
+        pending_reported_total: get_pending_count(env),
+        min_ttl: MIN_TTL,
+        bump_ttl: BUMP_TTL,
+        pending_ttl: PENDING_TTL,
+    }
+}
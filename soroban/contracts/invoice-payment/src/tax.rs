@@ -0,0 +1,66 @@
+use soroban_sdk::{Env, String};
+
+use crate::errors::ContractError;
+use crate::storage::{get_invoice_tax, InvoiceTax, TaxSplit};
+
+/// Highest accepted tax rate: 100 %.
+pub const MAX_TAX_RATE_BPS: u32 = 10_000;
+
+/// Longest jurisdiction code, e.g. `DE` or the subdivision `US-CA`.
+const MAX_JURISDICTION_LEN: usize = 8;
+
+/// Check `tax` before it is attached to an invoice whose total, if the
+/// contract knows it, is `total`.
+///
+/// Returns [`ContractError::InvalidTax`] for a rate over 100 %, a negative
+/// amount or one above `total`, or a jurisdiction that is not 2–8 upper-case
+/// ASCII letters, digits and `-`.
+pub fn validate_tax(tax: &InvoiceTax, total: Option<i128>) -> Result<(), ContractError> {
+    let within_total = total.is_none_or(|total| tax.amount <= total);
+    if tax.rate_bps > MAX_TAX_RATE_BPS
+        || tax.amount < 0
+        || !within_total
+        || !is_jurisdiction_code(&tax.jurisdiction)
+    {
+        return Err(ContractError::InvalidTax);
+    }
+    Ok(())
+}
+
+/// Split a payment of `amount` towards invoice `invoice_id` into net and
+/// tax, given the invoice's `gross` total and the `paid_before` part of it
+/// already paid. `None` if the invoice has no [`InvoiceTax`].
+///
+/// The tax is allocated in proportion to the cumulative amount paid, so the
+/// shares of an invoice paid in several parts add up to its tax exactly.
+pub fn split_payment(
+    env: &Env,
+    invoice_id: &String,
+    gross: i128,
+    paid_before: i128,
+    amount: i128,
+) -> Option<TaxSplit> {
+    let invoice_tax = get_invoice_tax(env, invoice_id)?;
+    let total_tax = invoice_tax.amount.min(gross);
+    let share = |paid: i128| total_tax.saturating_mul(paid) / gross;
+    let tax = share(paid_before + amount) - share(paid_before);
+    Some(TaxSplit {
+        net: amount - tax,
+        tax,
+        rate_bps: invoice_tax.rate_bps,
+        jurisdiction: invoice_tax.jurisdiction,
+    })
+}
+
+fn is_jurisdiction_code(code: &String) -> bool {
+    let len = code.len() as usize;
+    if !(2..=MAX_JURISDICTION_LEN).contains(&len) {
+        return false;
+    }
+    let mut buf = [0u8; MAX_JURISDICTION_LEN];
+    let bytes = &mut buf[..len];
+    code.copy_into_slice(bytes);
+    bytes
+        .iter()
+        .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || *b == b'-')
+}
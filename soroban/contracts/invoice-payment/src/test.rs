@@ -1,36 +1,12 @@
 #![cfg(test)]
 #![allow(clippy::all)]
 
+extern crate std;
+
 use super::*;
 use soroban_sdk::{testutils::Address as _, Address, Env, IntoVal, String};
 
-// TTL / Helpers
-
-/// Deploy the contract and call `initialize`, returning the client and admin.
-fn setup(env: &Env) -> (InvoicePaymentContractClient<'_>, Address) {
-    let admin = Address::generate(env);
-    let contract_id = env.register(InvoicePaymentContract, ());
-    let client = InvoicePaymentContractClient::new(env, &contract_id);
-    client.initialize(&admin);
-    (client, admin)
-}
-
-/// XLM payment helper: 1 XLM = 10_000_000 stroops.
-fn record_xlm(
-    env: &Env,
-    client: &InvoicePaymentContractClient,
-    invoice_id: &str,
-    payer: &Address,
-    stroops: i128,
-) {
-    client.record_payment(
-        &String::from_str(env, invoice_id),
-        payer,
-        &String::from_str(env, "XLM"),
-        &String::from_str(env, ""), // no issuer for native asset
-        &stroops,
-    );
-}
+use crate::testutils::{last_event, mock_auths, record_xlm, setup, AuthCall};
 
 // Initialisation
 
@@ -209,8 +185,60 @@ fn test_get_payment_absent_returns_error() {
     assert_eq!(result, Err(Ok(ContractError::PaymentNotFound)));
 }
 
+#[test]
+fn test_get_payment_opt_returns_record_or_none() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-001", &payer, 10_000_000);
+
+    let id = String::from_str(&env, "invoisio-001");
+    assert_eq!(client.get_payment_opt(&id), Some(client.get_payment(&id)));
+    assert_eq!(client.get_payment_opt(&String::from_str(&env, "invoisio-missing")), None);
+}
+
+#[test]
+fn test_find_payments_reports_hits_and_misses() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-001", &payer, 10_000_000);
+
+    let hit = String::from_str(&env, "invoisio-001");
+    let miss = String::from_str(&env, "invoisio-missing");
+    let found = client.find_payments(&soroban_sdk::vec![&env, hit.clone(), miss.clone()]);
+
+    assert_eq!(found.len(), 2);
+    assert_eq!(found.get(hit.clone()), Some(Some(client.get_payment(&hit))));
+    assert_eq!(found.get(miss), Some(None));
+}
+
 // Admin management
 
+#[test]
+fn test_info_reports_version_and_deployment_labels() {
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let info = client.info();
+    assert_eq!(info.name, String::from_str(&env, "invoice-payment"));
+    assert_eq!(info.version, String::from_str(&env, CONTRACT_VERSION));
+    assert_eq!(info.deployment.label, None);
+
+    let deployment = DeploymentInfo {
+        label: Some(String::from_str(&env, "acme-eu-1")),
+        environment: Some(Symbol::new(&env, "staging")),
+        merchant_name_hash: Some(invoice_hash(&env, "Acme Corp")),
+    };
+    client.set_info(&deployment, &client.admin_nonce());
+    assert_eq!(client.info().deployment, deployment);
+}
+
 #[test]
 fn test_set_admin_updates_admin() {
     let env = Env::default();
@@ -218,7 +246,7 @@ fn test_set_admin_updates_admin() {
     let (client, _old_admin) = setup(&env);
 
     let new_admin = Address::generate(&env);
-    client.set_admin(&new_admin);
+    client.set_admin(&new_admin, &client.admin_nonce());
 
     assert_eq!(client.admin(), new_admin);
 }
@@ -230,7 +258,7 @@ fn test_new_admin_can_record_payment() {
     let (client, _old_admin) = setup(&env);
 
     let new_admin = Address::generate(&env);
-    client.set_admin(&new_admin);
+    client.set_admin(&new_admin, &client.admin_nonce());
 
     // With mock_all_auths the new admin's require_auth passes automatically.
     let payer = Address::generate(&env);
@@ -293,6 +321,65 @@ fn test_token_without_issuer_returns_error() {
     assert_eq!(result, Err(Ok(ContractError::InvalidAsset)));
 }
 
+#[test]
+fn test_non_classic_asset_code_returns_error() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+
+    for code in ["USD C", "USDC-X", "💵", "ABCDEFGHIJKLM"] {
+        let result = client.try_record_payment(
+            &String::from_str(&env, "invoisio-bad-code"),
+            &payer,
+            &String::from_str(&env, code),
+            &String::from_str(&env, crate::testutils::USDC_TESTNET_ISSUER),
+            &1,
+        );
+        assert_eq!(result, Err(Ok(ContractError::InvalidAsset)), "{code}");
+    }
+
+    // Both classic lengths are accepted.
+    for (id, code) in [("invoisio-code-4", "EURT"), ("invoisio-code-12", "ABCDEFGHIJ12")] {
+        client.record_payment(
+            &String::from_str(&env, id),
+            &payer,
+            &String::from_str(&env, code),
+            &String::from_str(&env, crate::testutils::USDC_TESTNET_ISSUER),
+            &1,
+        );
+    }
+}
+
+#[test]
+fn test_malformed_issuer_returns_error() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+
+    for issuer in [
+        // Too short.
+        "GABC123",
+        // Last character changed: checksum mismatch.
+        "GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA6",
+        // Lower case is not base32.
+        "gbbd47if6lwk7p7mdevscwr7dpuwv3ny3dtqevfl4nat4aqh3zllfla5",
+        // Contract strkey, not an account.
+        "CBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5",
+    ] {
+        let result = client.try_record_payment(
+            &String::from_str(&env, "invoisio-bad-issuer"),
+            &payer,
+            &String::from_str(&env, "USDC"),
+            &String::from_str(&env, issuer),
+            &50_000_000i128,
+        );
+        assert_eq!(result, Err(Ok(ContractError::InvalidAsset)), "{issuer}");
+    }
+    assert!(!client.has_payment(&String::from_str(&env, "invoisio-bad-issuer")));
+}
+
 // Events
 
 #[test]
@@ -328,6 +415,7 @@ fn test_record_payment_emits_payment_recorded_event() {
     let expected_record = PaymentRecord {
         invoice_id: invoice_id.clone(),
         payer: payer.clone(),
+        payer_mux_id: None,
         asset: Asset::Native,
         amount: 10_000_000i128,
         timestamp: env.ledger().timestamp(),
@@ -362,25 +450,103 @@ fn test_set_admin_requires_new_admin_auth() {
     let new_admin = Address::generate(&env);
 
     // Only mock the current admin's auth — new_admin does NOT co-sign.
-    env.mock_auths(&[soroban_sdk::testutils::MockAuth {
-        address: &old_admin,
-        invoke: &soroban_sdk::testutils::MockAuthInvoke {
-            contract: &client.address,
-            fn_name: "set_admin",
-            args: (new_admin.clone(),).into_val(&env),
-            sub_invokes: &[],
-        },
-    }]);
+    mock_auths(&env, &[AuthCall::set_admin(&env, &client, &old_admin, &new_admin, 0)]);
 
     // Without new_admin's auth the host must reject the call.
-    let result = client.try_set_admin(&new_admin);
+    let result = client.try_set_admin(&new_admin, &client.admin_nonce());
     assert!(result.is_err());
+
+    // With both signatures it goes through.
+    mock_auths(
+        &env,
+        &[
+            AuthCall::set_admin(&env, &client, &old_admin, &new_admin, 0),
+            AuthCall::set_admin(&env, &client, &new_admin, &new_admin, 0),
+        ],
+    );
+    client.set_admin(&new_admin, &client.admin_nonce());
+    assert_eq!(client.admin(), new_admin);
+}
+
+#[test]
+fn test_admin_nonce_is_required_and_incremented() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, old_admin) = setup(&env);
+    assert_eq!(client.admin_nonce(), 0);
+
+    client.set_terms_required(&true, &0);
+    assert_eq!(client.admin_nonce(), 1);
+    assert_eq!(
+        client.try_set_terms_required(&false, &0),
+        Err(Ok(ContractError::InvalidNonce))
+    );
+    assert_eq!(
+        client.try_set_terms_required(&false, &2),
+        Err(Ok(ContractError::InvalidNonce))
+    );
+
+    // Nonces are per admin: rotating away and back does not revive the
+    // transfer signed with nonce 1.
+    let new_admin = Address::generate(&env);
+    client.set_admin(&new_admin, &1);
+    assert_eq!(client.admin_nonce(), 0);
+    client.set_admin(&old_admin, &0);
+    assert_eq!(client.admin_nonce(), 2);
+    assert_eq!(
+        client.try_set_admin(&new_admin, &1),
+        Err(Ok(ContractError::InvalidNonce))
+    );
+}
+
+#[test]
+fn test_operational_admin_calls_take_a_nonce() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let stale = Err(Ok(ContractError::InvalidNonce));
+
+    client.pause(&0);
+    assert_eq!(client.try_pause(&0), stale);
+    client.resume(&1);
+    client.block_payer(&payer, &2);
+    assert_eq!(client.try_unblock_payer(&payer, &2), stale);
+    client.unblock_payer(&payer, &3);
+    client.set_payer_flags(&payer, &true, &false, &4);
+    assert_eq!(
+        client.try_compact_old_records(&0, &1, &4),
+        Err(Ok(ContractError::InvalidNonce))
+    );
+    client.compact_old_records(&0, &1, &5);
+    let issuer = String::from_str(&env, crate::testutils::USDC_TESTNET_ISSUER);
+    assert_eq!(client.try_set_usdc_issuer(&issuer, &5), stale);
+    client.set_usdc_issuer(&issuer, &6);
+    assert_eq!(client.admin_nonce(), 7);
+
+    // Per-invoice writes take none.
+    client.set_metadata(&String::from_str(&env, "invoisio-n1"), &None);
+    assert_eq!(client.admin_nonce(), 7);
+}
+
+#[test]
+fn test_record_payment_with_exact_admin_auth() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let payer = Address::generate(&env);
+
+    mock_auths(
+        &env,
+        &[AuthCall::record_xlm(&env, &client, &admin, "invoisio-auth", &payer, 10_000_000)],
+    );
+    record_xlm(&env, &client, "invoisio-auth", &payer, 10_000_000);
+    assert_eq!(client.payment_count(), 1);
 }
 // Multi-asset support tests
 
 #[test]
 fn test_asset_enum_native_xlm() {
-    let env = Env::default();
+    let _env = Env::default();
     let native = Asset::Native;
     
     // Verify Native variant doesn't have code/issuer fields
@@ -553,4 +719,3799 @@ fn test_asset_enum_serialization_deserialization() {
         }
         Asset::Native => panic!("Expected Token variant"),
     }
-}
\ No newline at end of file
+}
+// Accepted assets
+
+#[test]
+fn test_invoice_accepts_any_listed_asset_for_its_amount() {
+    use crate::testutils::{USDC, USDC_TESTNET_ISSUER};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-mc1");
+    let usdc = String::from_str(&env, USDC);
+    let issuer = String::from_str(&env, USDC_TESTNET_ISSUER);
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+    let assets = soroban_sdk::vec![
+        &env,
+        (usdc.clone(), issuer.clone(), 100_0000000i128),
+        (xlm.clone(), none.clone(), 950_0000000)
+    ];
+    let twice = soroban_sdk::vec![
+        &env,
+        (xlm.clone(), none.clone(), 1i128),
+        (xlm.clone(), none.clone(), 2)
+    ];
+    assert_eq!(
+        client.try_set_accepted_assets(&id, &twice),
+        Err(Ok(ContractError::InvalidAsset))
+    );
+    client.set_accepted_assets(&id, &assets);
+    assert_eq!(client.accepted_assets(&id).unwrap().get(Asset::Native), Some(950_0000000));
+
+    let try_pay = |code: &String, issuer: &String, amount: i128| {
+        client.try_record_payment(&id, &payer, code, issuer, &amount)
+    };
+    let eurc = String::from_str(&env, "EURC");
+    assert_eq!(try_pay(&eurc, &issuer, 100_0000000), Err(Ok(ContractError::InvalidAsset)));
+    assert_eq!(try_pay(&xlm, &none, 900_0000000), Err(Ok(ContractError::AmountMismatch)));
+    assert!(try_pay(&xlm, &none, 950_0000000).is_ok());
+    assert_eq!(client.get_payment(&id).asset, Asset::Native);
+
+    // Other invoices are unrestricted.
+    record_xlm(&env, &client, "invoisio-mc2", &payer, 1);
+}
+
+// Muxed payers
+
+#[test]
+fn test_muxed_payer_keeps_mux_id() {
+    use soroban_sdk::testutils::MuxedAddress as _;
+    use soroban_sdk::MuxedAddress;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let omnibus = Address::from_str(&env, GOLDEN_PAYER);
+    let xlm = String::from_str(&env, "XLM");
+    let none = String::from_str(&env, "");
+
+    let id = String::from_str(&env, "invoisio-mux-1");
+    client.record_payment(&id, MuxedAddress::new(omnibus.clone(), 42), &xlm, &none, &1);
+    let record = client.get_payment(&id);
+    assert_eq!(record.payer, omnibus);
+    assert_eq!(record.payer_mux_id, Some(42));
+
+    // Plain addresses still work and carry no mux id.
+    record_xlm(&env, &client, "invoisio-mux-2", &omnibus, 1);
+    let record = client.get_payment(&String::from_str(&env, "invoisio-mux-2"));
+    assert_eq!(record.payer_mux_id, None);
+
+    // A candidate keeps its mux id through confirmation.
+    let id = String::from_str(&env, "invoisio-mux-3");
+    client.report_pending_payment(&id, MuxedAddress::new(omnibus.clone(), 7), &xlm, &none, &1);
+    client.confirm_pending_payment(&id);
+    assert_eq!(client.get_payment(&id).payer_mux_id, Some(7));
+}
+
+#[test]
+fn test_muxed_payer_is_screened_by_account() {
+    use soroban_sdk::testutils::MuxedAddress as _;
+    use soroban_sdk::MuxedAddress;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let omnibus = Address::from_str(&env, GOLDEN_PAYER);
+
+    client.block_payer(&omnibus, &client.admin_nonce());
+    assert_eq!(
+        client.try_record_payment(
+            &String::from_str(&env, "invoisio-mux-1"),
+            MuxedAddress::new(omnibus, 42),
+            &String::from_str(&env, "XLM"),
+            &String::from_str(&env, ""),
+            &1,
+        ),
+        Err(Ok(ContractError::PayerBlocked))
+    );
+}
+
+// Compact storage
+
+#[test]
+fn test_usdc_is_stored_compactly_and_read_back_in_full() {
+    use crate::storage::{StoredAsset, StoredPayment};
+    use crate::testutils::{record_token, USDC, USDC_TESTNET_ISSUER};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let issuer = String::from_str(&env, USDC_TESTNET_ISSUER);
+    let other = "GAP5LETOV6YIE62YAM56STDANPRDO7ZFDBGSNHJQIYGGKSMOZAHOOS2S";
+
+    client.set_usdc_issuer(&issuer, &client.admin_nonce());
+    assert_eq!(client.usdc_issuer(), Some(issuer.clone()));
+    record_token(&env, &client, "invoisio-c1", &payer, USDC, USDC_TESTNET_ISSUER, 5);
+    record_token(&env, &client, "invoisio-c2", &payer, USDC, other, 5);
+
+    let stored = |id: &str| -> StoredPayment {
+        env.as_contract(&client.address, || {
+            env.storage()
+                .persistent()
+                .get(&DataKey::Payment(String::from_str(&env, id)))
+                .unwrap()
+        })
+    };
+    assert_eq!(stored("invoisio-c1").asset, StoredAsset::Usdc);
+    assert_eq!(
+        stored("invoisio-c2").asset,
+        StoredAsset::Token(String::from_str(&env, USDC), String::from_str(&env, other))
+    );
+
+    let record = client.get_payment(&String::from_str(&env, "invoisio-c1"));
+    assert_eq!(record.invoice_id, String::from_str(&env, "invoisio-c1"));
+    assert_eq!(record.asset, Asset::Token(String::from_str(&env, USDC), issuer));
+    assert_eq!(record.amount, 5);
+}
+
+#[test]
+fn test_set_usdc_issuer_is_validated_and_set_once() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    assert_eq!(
+        client.try_set_usdc_issuer(&String::from_str(&env, "GABC123"), &client.admin_nonce()),
+        Err(Ok(ContractError::InvalidAsset))
+    );
+    let issuer = String::from_str(&env, crate::testutils::USDC_TESTNET_ISSUER);
+    client.set_usdc_issuer(&issuer, &client.admin_nonce());
+    let other = String::from_str(&env, "GAP5LETOV6YIE62YAM56STDANPRDO7ZFDBGSNHJQIYGGKSMOZAHOOS2S");
+    assert_eq!(
+        client.try_set_usdc_issuer(&other, &client.admin_nonce()),
+        Err(Ok(ContractError::UsdcIssuerAlreadySet))
+    );
+}
+
+// External payments
+
+fn record_sepa(
+    env: &Env,
+    client: &InvoicePaymentContractClient,
+    invoice_id: &str,
+    amount: i128,
+) -> Result<(), ContractError> {
+    match client.try_record_external_payment(
+        &String::from_str(env, invoice_id),
+        &soroban_sdk::Symbol::new(env, "sepa"),
+        &String::from_str(env, "SEPA-2024-0001"),
+        &amount,
+        &soroban_sdk::Symbol::new(env, "EUR"),
+    ) {
+        Ok(_) => Ok(()),
+        Err(Ok(e)) => Err(e),
+        Err(Err(e)) => panic!("host error: {e:?}"),
+    }
+}
+
+#[test]
+fn test_record_external_payment_is_a_separate_record() {
+    use soroban_sdk::{Symbol, Val, Vec};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let id = String::from_str(&env, "invoisio-ext-1");
+
+    record_sepa(&env, &client, "invoisio-ext-1", 12_500).unwrap();
+
+    let (_, topics, _) = last_event(&env);
+    let expected: Vec<Val> =
+        soroban_sdk::vec![&env, Symbol::new(&env, "external_payment_recorded").into_val(&env)];
+    assert_eq!(topics, expected);
+
+    let record = client.get_external_payment(&id);
+    assert_eq!(record.rail, Symbol::new(&env, "sepa"));
+    assert_eq!(record.reference, String::from_str(&env, "SEPA-2024-0001"));
+    assert_eq!(record.amount, 12_500);
+    assert_eq!(record.currency, Symbol::new(&env, "EUR"));
+    assert!(client.has_external_payment(&id));
+    assert!(!client.has_payment(&id));
+    assert_eq!(client.payment_count(), 0);
+}
+
+#[test]
+fn test_record_external_payment_rejects_settled_and_invalid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    record_xlm(&env, &client, "invoisio-ext-1", &Address::generate(&env), 1);
+
+    assert_eq!(
+        record_sepa(&env, &client, "invoisio-ext-1", 1),
+        Err(ContractError::PaymentAlreadyRecorded)
+    );
+    record_sepa(&env, &client, "invoisio-ext-2", 1).unwrap();
+    assert_eq!(
+        record_sepa(&env, &client, "invoisio-ext-2", 1),
+        Err(ContractError::PaymentAlreadyRecorded)
+    );
+    assert_eq!(record_sepa(&env, &client, "", 1), Err(ContractError::InvalidInvoiceId));
+    assert_eq!(
+        record_sepa(&env, &client, "invoisio-ext-3", 0),
+        Err(ContractError::InvalidAmount)
+    );
+    assert_eq!(
+        client.try_get_external_payment(&String::from_str(&env, "invoisio-ext-3")),
+        Err(Ok(ContractError::PaymentNotFound))
+    );
+}
+
+#[test]
+fn test_stellar_record_paths_reject_externally_settled_invoices() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-ext-1");
+    let xlm = String::from_str(&env, "XLM");
+    let none = String::from_str(&env, "");
+    let scheduled = String::from_str(&env, "invoisio-ext-2");
+    client.issue_deposit_invoice(&scheduled, &xlm, &none, &1_000, &3_000, &None);
+    record_sepa(&env, &client, "invoisio-ext-1", 1).unwrap();
+    record_sepa(&env, &client, "invoisio-ext-2", 1).unwrap();
+
+    let settled = Err(Ok(ContractError::PaymentAlreadyRecorded));
+    assert_eq!(client.try_record_payment(&id, &payer, &xlm, &none, &1), settled);
+    assert_eq!(
+        client.try_record_backdated_payment(&id, &payer, &xlm, &none, &1, &0),
+        settled
+    );
+    assert_eq!(client.try_report_pending_payment(&id, &payer, &xlm, &none, &1), settled);
+    assert_eq!(
+        client.try_issue_deposit_invoice(&id, &xlm, &none, &1_000, &3_000, &None),
+        settled
+    );
+    let payment_id = String::from_str(&env, "invoisio-ext-2-1");
+    assert_eq!(
+        client.try_record_invoice_payment(&scheduled, &payment_id, &payer, &xlm, &none, &1),
+        settled
+    );
+    assert!(!client.has_payment(&id));
+    assert_eq!(client.payment_count(), 0);
+}
+
+// Payments on other chains
+
+const BASE_TX: &str = "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060:3";
+const BASE_PAYER: &str = "0x4838B106FCe9647Bdf1E7877BF73cE8B0BAD5f97";
+
+fn usdc_on_base(env: &Env) -> ChainAsset {
+    ChainAsset {
+        code: soroban_sdk::Symbol::new(env, "USDC"),
+        asset_ref: String::from_str(env, "erc20:0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"),
+        decimals: 6,
+    }
+}
+
+fn record_on_base(
+    env: &Env,
+    client: &InvoicePaymentContractClient,
+    invoice_id: &str,
+    asset: &ChainAsset,
+    amount: i128,
+) -> Result<(), ContractError> {
+    match client.try_record_chain_payment(
+        &String::from_str(env, invoice_id),
+        &String::from_str(env, "eip155:8453"),
+        &String::from_str(env, BASE_TX),
+        &Some(String::from_str(env, BASE_PAYER)),
+        asset,
+        &amount,
+    ) {
+        Ok(_) => Ok(()),
+        Err(Ok(e)) => Err(e),
+        Err(Err(e)) => panic!("host error: {e:?}"),
+    }
+}
+
+#[test]
+fn test_record_chain_payment_is_a_separate_record() {
+    use soroban_sdk::{Symbol, Val, Vec};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let id = String::from_str(&env, "invoisio-evm-1");
+
+    let usdc = usdc_on_base(&env);
+    record_on_base(&env, &client, "invoisio-evm-1", &usdc, 25_000_000).unwrap();
+
+    let (_, topics, _) = last_event(&env);
+    let expected: Vec<Val> = soroban_sdk::vec![
+        &env,
+        Symbol::new(&env, "chain_payment_recorded").into_val(&env),
+        String::from_str(&env, "eip155:8453").into_val(&env),
+    ];
+    assert_eq!(topics, expected);
+
+    let record = client.get_chain_payment(&id);
+    assert_eq!(record.chain_id, String::from_str(&env, "eip155:8453"));
+    assert_eq!(record.tx_ref, String::from_str(&env, BASE_TX));
+    assert_eq!(record.payer, Some(String::from_str(&env, BASE_PAYER)));
+    assert_eq!(record.asset, usdc);
+    assert_eq!(record.amount, 25_000_000);
+    assert!(client.has_chain_payment(&id));
+    assert!(!client.has_payment(&id));
+    assert_eq!(client.payment_count(), 0);
+}
+
+#[test]
+fn test_record_chain_payment_rejects_settled_and_invalid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let usdc = usdc_on_base(&env);
+    record_xlm(&env, &client, "invoisio-evm-1", &Address::generate(&env), 1);
+    record_sepa(&env, &client, "invoisio-evm-2", 1).unwrap();
+
+    assert_eq!(
+        record_on_base(&env, &client, "invoisio-evm-1", &usdc, 1),
+        Err(ContractError::PaymentAlreadyRecorded)
+    );
+    assert_eq!(
+        record_on_base(&env, &client, "invoisio-evm-2", &usdc, 1),
+        Err(ContractError::PaymentAlreadyRecorded)
+    );
+    record_on_base(&env, &client, "invoisio-evm-3", &usdc, 1).unwrap();
+    assert_eq!(
+        record_sepa(&env, &client, "invoisio-evm-3", 1),
+        Err(ContractError::PaymentAlreadyRecorded)
+    );
+    // Settled on Base, the invoice can no longer be paid on Stellar.
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-evm-3");
+    let xlm = String::from_str(&env, "XLM");
+    let none = String::from_str(&env, "");
+    assert_eq!(
+        client.try_record_payment(&id, &payer, &xlm, &none, &1),
+        Err(Ok(ContractError::PaymentAlreadyRecorded))
+    );
+    assert_eq!(
+        client.try_report_pending_payment(&id, &payer, &xlm, &none, &1),
+        Err(Ok(ContractError::PaymentAlreadyRecorded))
+    );
+    assert!(!client.has_payment(&id));
+
+    assert_eq!(
+        record_on_base(&env, &client, "", &usdc, 1),
+        Err(ContractError::InvalidInvoiceId)
+    );
+    assert_eq!(
+        record_on_base(&env, &client, "invoisio-evm-4", &usdc, 0),
+        Err(ContractError::InvalidAmount)
+    );
+    let unnamed = ChainAsset {
+        asset_ref: String::from_str(&env, ""),
+        ..usdc
+    };
+    assert_eq!(
+        record_on_base(&env, &client, "invoisio-evm-4", &unnamed, 1),
+        Err(ContractError::InvalidAsset)
+    );
+}
+
+#[test]
+fn test_id_taking_entry_points_reject_over_long_ids() {
+    use soroban_sdk::BytesN;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let long = "i".repeat(crate::storage::MAX_INVOICE_ID_LEN as usize + 1);
+    let id = String::from_str(&env, &long);
+    let invalid = Err(Ok(ContractError::InvalidInvoiceId));
+
+    assert_eq!(
+        record_sepa(&env, &client, &long, 1),
+        Err(ContractError::InvalidInvoiceId)
+    );
+    assert_eq!(
+        record_on_base(&env, &client, &long, &usdc_on_base(&env), 1),
+        Err(ContractError::InvalidInvoiceId)
+    );
+    let hash = BytesN::from_array(&env, &[7; 32]);
+    assert_eq!(client.try_accept_terms(&id, &payer, &hash), invalid);
+    assert_eq!(client.try_acknowledge_invoice(&id, &payer), invalid);
+    assert_eq!(client.try_set_metadata(&id, &None), invalid);
+    assert_eq!(client.try_set_invoice_tax(&id, &None), invalid);
+    assert_eq!(
+        client.try_set_accepted_assets(&id, &soroban_sdk::vec![&env]),
+        invalid
+    );
+    assert_eq!(
+        client.try_flag_chargeback(&id, &String::from_str(&env, "CB-1")),
+        invalid
+    );
+    assert_eq!(
+        client.try_issue_quote(
+            &id,
+            &String::from_str(&env, "invoisio-quote-1"),
+            &payer,
+            &String::from_str(&env, "XLM"),
+            &String::from_str(&env, ""),
+            &1,
+            &hash,
+            &(env.ledger().timestamp() + 60),
+        ),
+        invalid
+    );
+}
+
+// Metadata
+
+#[test]
+fn test_metadata_attach_replace_remove() {
+    use soroban_sdk::Bytes;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let id = String::from_str(&env, "invoisio-meta-1");
+
+    // Attaching before the invoice is paid is allowed.
+    assert_eq!(client.get_metadata(&id), None);
+    client.set_metadata(&id, &Some(Bytes::from_slice(&env, &[0xde, 0xad])));
+    assert_eq!(client.get_metadata(&id), Some(Bytes::from_slice(&env, &[0xde, 0xad])));
+
+    client.set_metadata(&id, &Some(Bytes::from_slice(&env, &[0xbe, 0xef])));
+    assert_eq!(client.get_metadata(&id), Some(Bytes::from_slice(&env, &[0xbe, 0xef])));
+
+    client.set_metadata(&id, &None);
+    assert_eq!(client.get_metadata(&id), None);
+}
+
+#[test]
+fn test_metadata_rejects_oversized_blob_and_empty_id() {
+    use soroban_sdk::Bytes;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let id = String::from_str(&env, "invoisio-meta-1");
+    let max = storage::MAX_METADATA_LEN as usize;
+
+    client.set_metadata(&id, &Some(Bytes::from_slice(&env, &std::vec![1u8; max])));
+    assert_eq!(
+        client.try_set_metadata(&id, &Some(Bytes::from_slice(&env, &std::vec![1u8; max + 1]))),
+        Err(Ok(ContractError::MetadataTooLarge))
+    );
+    assert_eq!(
+        client.try_set_metadata(&String::from_str(&env, ""), &None),
+        Err(Ok(ContractError::InvalidInvoiceId))
+    );
+}
+
+// Retention
+
+#[test]
+fn test_compact_old_records_summarises_and_resumes() {
+    use soroban_sdk::testutils::Ledger as _;
+    use soroban_sdk::xdr::ToXdr;
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+
+    for (i, ts) in [100u64, 200, 300, 400].iter().enumerate() {
+        env.ledger().with_mut(|li| li.timestamp = *ts);
+        record_xlm(&env, &client, &std::format!("invoisio-old-{i}"), &payer, 10_000_000);
+    }
+    let first = String::from_str(&env, "invoisio-old-0");
+    let record = client.get_payment(&first);
+
+    // Batches resume from the cursor and stop at the first recent record.
+    assert_eq!(client.compact_old_records(&300, &1, &client.admin_nonce()), 1);
+    assert_eq!(client.compact_old_records(&300, &10, &client.admin_nonce()), 1);
+    assert_eq!(client.compact_old_records(&300, &10, &client.admin_nonce()), 0);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "records_compacted").into_val(&env)]
+    );
+
+    let summary = client.compacted_payment(&first).unwrap();
+    assert_eq!(summary.amount, 10_000_000);
+    assert_eq!(summary.record_hash, env.crypto().sha256(&record.to_xdr(&env)).to_bytes());
+    assert_eq!(client.try_get_payment(&first), Err(Ok(ContractError::PaymentCompacted)));
+    assert!(client.has_payment(&first));
+
+    let recent = String::from_str(&env, "invoisio-old-2");
+    assert_eq!(client.compacted_payment(&recent), None);
+    assert_eq!(client.get_payment(&recent).timestamp, 300);
+    assert_eq!(client.payment_count(), 4);
+}
+
+#[test]
+fn test_compacted_invoice_cannot_be_recorded_again() {
+    use soroban_sdk::testutils::Ledger as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    record_xlm(&env, &client, "invoisio-old-0", &payer, 10_000_000);
+    assert_eq!(client.compact_old_records(&u64::MAX, &10, &client.admin_nonce()), 1);
+
+    let result = client.try_record_payment(
+        &String::from_str(&env, "invoisio-old-0"),
+        &payer,
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &10_000_000,
+    );
+    assert_eq!(result, Err(Ok(ContractError::PaymentAlreadyRecorded)));
+}
+
+#[test]
+fn test_export_page_walks_records_in_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+
+    record_xlm(&env, &client, "invoisio-exp-0", &payer, 1);
+    record_xlm(&env, &client, "invoisio-exp-1", &payer, 2);
+    // Hashed payments take a sequence number but have no full record.
+    client.record_hashed_payment(&invoice_hash(&env, "invoice-1"), &payer, &xlm, &none, &3);
+    record_xlm(&env, &client, "invoisio-exp-3", &payer, 4);
+
+    let (page, next) = client.export_page(&0, &2);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(1).unwrap().invoice_id, String::from_str(&env, "invoisio-exp-1"));
+    assert_eq!(next, Some(2));
+
+    let (page, next) = client.export_page(&2, &2);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().amount, 4);
+    assert_eq!(next, None);
+}
+
+#[test]
+fn test_import_payments_keeps_history_and_rejects_duplicates() {
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let imported = |id: &str, amount: i128| PaymentRecord {
+        invoice_id: String::from_str(&env, id),
+        payer: payer.clone(),
+        payer_mux_id: None,
+        asset: Asset::Native,
+        amount,
+        timestamp: 1_600_000_000,
+    };
+
+    let batch = soroban_sdk::vec![&env, imported("old-1", 10), imported("old-2", 20)];
+    assert_eq!(client.import_payments(&batch), 2);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "payment_imported").into_val(&env)]
+    );
+    assert_eq!(client.get_payment(&String::from_str(&env, "old-2")), imported("old-2", 20));
+    assert_eq!(client.payment_count(), 2);
+    assert_eq!(client.totals().get(Asset::Native), Some(30));
+
+    // All-or-nothing: a repeated id rejects the whole batch.
+    let batch = soroban_sdk::vec![&env, imported("old-3", 30), imported("old-1", 10)];
+    assert_eq!(
+        client.try_import_payments(&batch),
+        Err(Ok(ContractError::PaymentAlreadyRecorded))
+    );
+    assert!(!client.has_payment(&String::from_str(&env, "old-3")));
+    let batch = soroban_sdk::vec![&env, imported("old-4", 0)];
+    assert_eq!(
+        client.try_import_payments(&batch),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+}
+
+#[test]
+fn test_import_payments_stores_native_xlm_in_canonical_form() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-new", &payer, 5);
+    let id = String::from_str(&env, "old-xlm");
+    let xlm = Asset::Token(String::from_str(&env, "XLM"), String::from_str(&env, ""));
+    let record = PaymentRecord {
+        invoice_id: id.clone(),
+        payer,
+        payer_mux_id: None,
+        asset: xlm.clone(),
+        amount: 10,
+        timestamp: 1_600_000_000,
+    };
+
+    client.import_payments(&soroban_sdk::vec![&env, record]);
+
+    assert_eq!(client.get_payment(&id).asset, Asset::Native);
+    let totals = client.totals();
+    assert_eq!(totals.get(Asset::Native), Some(15));
+    assert_eq!(totals.get(xlm), None);
+}
+
+#[test]
+fn test_backdated_payment_keeps_both_timestamps() {
+    use soroban_sdk::testutils::Ledger as _;
+    use soroban_sdk::{Symbol, TryFromVal, Val};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 5_000);
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-late");
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+
+    assert_eq!(
+        client.try_record_backdated_payment(&id, &payer, &xlm, &none, &10, &5_001),
+        Err(Ok(ContractError::InvalidTimestamp))
+    );
+    client.record_backdated_payment(&id, &payer, &xlm, &none, &10, &1_000);
+    let (_, topics, data) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "payment_backdated").into_val(&env)]
+    );
+    let data = soroban_sdk::Map::<Symbol, Val>::try_from_val(&env, &data).unwrap();
+    let backdated = data.get(Symbol::new(&env, "backdated")).unwrap();
+    assert!(bool::try_from_val(&env, &backdated).unwrap());
+
+    assert_eq!(client.get_payment(&id).timestamp, 5_000);
+    assert_eq!(client.paid_at(&id), 1_000);
+    record_xlm(&env, &client, "invoisio-on-time", &payer, 10);
+    assert_eq!(client.paid_at(&String::from_str(&env, "invoisio-on-time")), 5_000);
+}
+
+#[test]
+fn test_converted_payment_records_both_legs() {
+    use crate::testutils::{USDC, USDC_TESTNET_ISSUER};
+    use soroban_sdk::{Symbol, Val, Vec};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-pp1");
+    let usdc = String::from_str(&env, USDC);
+    let issuer = String::from_str(&env, USDC_TESTNET_ISSUER);
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+
+    assert_eq!(
+        client.try_record_converted_payment(&id, &payer, &xlm, &none, &1, &xlm, &none, &1),
+        Err(Ok(ContractError::InvalidAsset))
+    );
+    // 950 XLM sent, 100 USDC received.
+    client.record_converted_payment(
+        &id,
+        &payer,
+        &usdc,
+        &issuer,
+        &1_000_000_000,
+        &xlm,
+        &none,
+        &9_500_000_000,
+    );
+
+    let (_, topics, _) = last_event(&env);
+    let expected: Vec<Val> = soroban_sdk::vec![
+        &env,
+        Symbol::new(&env, "payment_converted").into_val(&env),
+        id.into_val(&env)
+    ];
+    assert_eq!(topics, expected);
+    let record = client.get_payment(&id);
+    assert_eq!(record.amount, 1_000_000_000);
+    let conversion = client.conversion(&id).unwrap();
+    assert_eq!(conversion.received_asset, record.asset);
+    assert_eq!(conversion.sent_asset, Asset::Native);
+    assert_eq!(conversion.sent_amount, 9_500_000_000);
+    assert_eq!(conversion.rate, 1_052_631);
+}
+
+#[test]
+fn test_conversion_lives_as_long_as_its_payment() {
+    use crate::testutils::{USDC, USDC_TESTNET_ISSUER};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    pin_instance(&env, &client);
+    let id = String::from_str(&env, "invoisio-pp1");
+    let key = DataKey::Conversion(id.clone());
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+    client.record_converted_payment(
+        &id,
+        &Address::generate(&env),
+        &String::from_str(&env, USDC),
+        &String::from_str(&env, USDC_TESTNET_ISSUER),
+        &1_000_000_000,
+        &xlm,
+        &none,
+        &9_500_000_000,
+    );
+
+    advance_ledger(&env, storage::BUMP_TTL - storage::MIN_TTL + 1);
+    client.extend_payment_ttl(&id, &None);
+    assert_eq!(entry_ttl(&env, &client, &key), storage::BUMP_TTL);
+
+    advance_ledger(&env, storage::BUMP_TTL - storage::MIN_TTL + 1);
+    client.conversion(&id);
+    assert_eq!(entry_ttl(&env, &client, &key), storage::BUMP_TTL);
+}
+
+#[test]
+fn test_remittance_payment_is_indexed_both_ways() {
+    use crate::testutils::{USDC, USDC_TESTNET_ISSUER};
+    use soroban_sdk::{Symbol, Val, Vec};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-sep31-1");
+    let usdc = String::from_str(&env, USDC);
+    let issuer = String::from_str(&env, USDC_TESTNET_ISSUER);
+    let remittance = Remittance {
+        transaction_id: String::from_str(&env, "82fhs729f63dh0v4"),
+        sending_anchor: String::from_str(&env, "sender.example"),
+        receiving_anchor: String::from_str(&env, "receiver.example"),
+    };
+
+    client.record_remittance_payment(&id, &payer, &usdc, &issuer, &500_000_000, &remittance);
+
+    let (_, topics, _) = last_event(&env);
+    let expected: Vec<Val> = soroban_sdk::vec![
+        &env,
+        Symbol::new(&env, "remittance_recorded").into_val(&env),
+        id.into_val(&env)
+    ];
+    assert_eq!(topics, expected);
+    assert_eq!(client.get_payment(&id).amount, 500_000_000);
+    assert_eq!(client.remittance(&id), Some(remittance.clone()));
+    let other = String::from_str(&env, "invoisio-sep31-2");
+    assert_eq!(client.remittance(&other), None);
+    let tx = remittance.transaction_id.clone();
+    assert_eq!(client.remittance_invoice(&tx), Some(id));
+
+    // One SEP-31 transaction settles one invoice.
+    assert_eq!(
+        client.try_record_remittance_payment(&other, &payer, &usdc, &issuer, &1, &remittance),
+        Err(Ok(ContractError::PaymentAlreadyRecorded))
+    );
+    let unnamed = Remittance {
+        transaction_id: String::from_str(&env, ""),
+        ..remittance.clone()
+    };
+    assert_eq!(
+        client.try_record_remittance_payment(&other, &payer, &usdc, &issuer, &1, &unnamed),
+        Err(Ok(ContractError::InvalidRemittance))
+    );
+    let anonymous = Remittance {
+        receiving_anchor: String::from_str(&env, ""),
+        ..remittance
+    };
+    assert_eq!(
+        client.try_record_remittance_payment(&other, &payer, &usdc, &issuer, &1, &anonymous),
+        Err(Ok(ContractError::InvalidRemittance))
+    );
+}
+
+#[test]
+fn test_remittance_lives_as_long_as_its_payment() {
+    use crate::testutils::{USDC, USDC_TESTNET_ISSUER};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    pin_instance(&env, &client);
+    let id = String::from_str(&env, "invoisio-sep31-1");
+    let tx = String::from_str(&env, "82fhs729f63dh0v4");
+    let remittance = Remittance {
+        transaction_id: tx.clone(),
+        sending_anchor: String::from_str(&env, "sender.example"),
+        receiving_anchor: String::from_str(&env, "receiver.example"),
+    };
+    client.record_remittance_payment(
+        &id,
+        &Address::generate(&env),
+        &String::from_str(&env, USDC),
+        &String::from_str(&env, USDC_TESTNET_ISSUER),
+        &500_000_000,
+        &remittance,
+    );
+    let by_invoice = DataKey::Remittance(id.clone());
+    let by_tx = DataKey::RemittanceInvoice(tx.clone());
+
+    advance_ledger(&env, storage::BUMP_TTL - storage::MIN_TTL + 1);
+    client.extend_payment_ttl(&id, &None);
+    assert_eq!(entry_ttl(&env, &client, &by_invoice), storage::BUMP_TTL);
+    assert_eq!(entry_ttl(&env, &client, &by_tx), storage::BUMP_TTL);
+
+    advance_ledger(&env, storage::BUMP_TTL - storage::MIN_TTL + 1);
+    client.remittance(&id);
+    client.remittance_invoice(&tx);
+    assert_eq!(entry_ttl(&env, &client, &by_invoice), storage::BUMP_TTL);
+    assert_eq!(entry_ttl(&env, &client, &by_tx), storage::BUMP_TTL);
+}
+
+// Signed payments
+
+#[test]
+fn test_record_payment_signed_verifies_the_signing_key() {
+    use ed25519_dalek::{Signer as _, SigningKey};
+    use soroban_sdk::xdr::ToXdr;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let key = SigningKey::from_bytes(&[9; 32]);
+    let payment = SignedPayment {
+        invoice_id: String::from_str(&env, "invoisio-sig-1"),
+        payer: Address::generate(&env),
+        asset_code: String::from_str(&env, "XLM"),
+        asset_issuer: String::from_str(&env, ""),
+        amount: 10_000_000,
+    };
+    let sign = |payment: &SignedPayment| {
+        let message = (client.address.clone(), payment.clone()).to_xdr(&env);
+        let mut buf = std::vec![0u8; message.len() as usize];
+        message.copy_into_slice(&mut buf);
+        BytesN::from_array(&env, &key.sign(&buf).to_bytes())
+    };
+    let signature = sign(&payment);
+    assert_eq!(
+        client.try_record_payment_signed(&payment, &signature),
+        Err(Ok(ContractError::SigningKeyNotSet))
+    );
+    let public_key = BytesN::from_array(&env, &key.verifying_key().to_bytes());
+    client.set_signing_key(&Some(public_key), &client.admin_nonce());
+
+    // No account authorizes the call; the signature does.
+    mock_auths(&env, &[]);
+    client.record_payment_signed(&payment, &signature);
+    assert_eq!(client.get_payment(&payment.invoice_id).amount, 10_000_000);
+    assert_eq!(
+        client.try_record_payment_signed(&payment, &signature),
+        Err(Ok(ContractError::PaymentAlreadyRecorded))
+    );
+
+    // A signature only covers the payment it was made for.
+    let mut other = payment.clone();
+    other.invoice_id = String::from_str(&env, "invoisio-sig-2");
+    assert!(client.try_record_payment_signed(&other, &signature).is_err());
+    assert!(client.try_record_payment_signed(&other, &sign(&other)).is_ok());
+}
+
+// Hashed invoice ids
+
+fn invoice_hash(env: &Env, uuid: &str) -> soroban_sdk::BytesN<32> {
+    env.crypto()
+        .sha256(&soroban_sdk::Bytes::from_slice(env, uuid.as_bytes()))
+        .to_bytes()
+}
+
+#[test]
+fn test_record_hashed_payment_stores_and_emits_topic() {
+    use soroban_sdk::{Symbol, Val, Vec};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let hash = invoice_hash(&env, "6f1c2a4e-8d3b-4f7a-9c1e-2b5d8e0a3f6c");
+
+    assert!(!client.has_hashed_payment(&hash));
+    client.record_hashed_payment(
+        &hash,
+        &payer,
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &10_000_000,
+    );
+
+    let (_, topics, _) = last_event(&env);
+    let expected: Vec<Val> = soroban_sdk::vec![
+        &env,
+        Symbol::new(&env, "hashed_payment_recorded").into_val(&env),
+        hash.into_val(&env)
+    ];
+    assert_eq!(topics, expected);
+
+    let record = client.get_hashed_payment(&hash);
+    assert_eq!(record.invoice_hash, hash);
+    assert_eq!(record.payer, payer);
+    assert_eq!(record.asset, Asset::Native);
+    assert_eq!(record.amount, 10_000_000);
+    assert!(client.has_hashed_payment(&hash));
+    assert_eq!(client.payment_count(), 1);
+}
+
+#[test]
+fn test_record_hashed_payment_is_idempotent_and_validated() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let hash = invoice_hash(&env, "invoice-1");
+    let other = invoice_hash(&env, "invoice-2");
+    let xlm = String::from_str(&env, "XLM");
+    let none = String::from_str(&env, "");
+
+    client.record_hashed_payment(&hash, &payer, &xlm, &none, &1);
+    assert_eq!(
+        client.try_record_hashed_payment(&hash, &payer, &xlm, &none, &1),
+        Err(Ok(ContractError::PaymentAlreadyRecorded))
+    );
+    assert_eq!(
+        client.try_record_hashed_payment(&other, &payer, &xlm, &none, &0),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_get_hashed_payment(&other),
+        Err(Ok(ContractError::PaymentNotFound))
+    );
+}
+
+#[test]
+fn test_reveal_links_commitment_to_invoice_id() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let salt = soroban_sdk::BytesN::from_array(&env, &[9; 32]);
+    let mut preimage = soroban_sdk::Bytes::from_slice(&env, b"invoisio-private-1");
+    preimage.append(&soroban_sdk::Bytes::from(&salt));
+    let commitment = env.crypto().sha256(&preimage).to_bytes();
+
+    client.record_hashed_payment(
+        &commitment,
+        &Address::generate(&env),
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &25,
+    );
+
+    let id = String::from_str(&env, "invoisio-private-1");
+    let record = client.reveal(&id, &salt);
+    assert_eq!(record.invoice_hash, commitment);
+    assert_eq!(record.amount, 25);
+
+    let wrong_salt = soroban_sdk::BytesN::from_array(&env, &[8; 32]);
+    assert_eq!(client.try_reveal(&id, &wrong_salt), Err(Ok(ContractError::PaymentNotFound)));
+    assert_eq!(
+        client.try_reveal(&String::from_str(&env, "invoisio-private-2"), &salt),
+        Err(Ok(ContractError::PaymentNotFound))
+    );
+}
+
+// Archival / TTL
+
+/// Advance the ledger sequence by `ledgers` (≈5 s per ledger).
+fn advance_ledger(env: &Env, ledgers: u32) {
+    use soroban_sdk::testutils::Ledger as _;
+
+    env.ledger().with_mut(|li| {
+        li.sequence_number += ledgers;
+        li.timestamp += ledgers as u64 * 5;
+    });
+}
+
+/// Remaining TTL (in ledgers) of the persistent entry for `invoice_id`.
+fn payment_ttl(env: &Env, client: &InvoicePaymentContractClient, invoice_id: &str) -> u32 {
+    entry_ttl(env, client, &DataKey::Payment(String::from_str(env, invoice_id)))
+}
+
+fn entry_ttl(env: &Env, client: &InvoicePaymentContractClient, key: &DataKey) -> u32 {
+    use soroban_sdk::testutils::storage::Persistent as _;
+
+    env.as_contract(&client.address, || env.storage().persistent().get_ttl(key))
+}
+
+/// Keep the contract instance alive well past a payment's TTL so tests can
+/// archive a single record without archiving the whole contract.
+fn pin_instance(env: &Env, client: &InvoicePaymentContractClient) {
+    env.as_contract(&client.address, || {
+        env.storage()
+            .instance()
+            .extend_ttl(storage::BUMP_TTL * 2, storage::BUMP_TTL * 2)
+    });
+}
+
+#[test]
+fn test_record_payment_sets_bump_ttl() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-ttl", &payer, 10_000_000);
+
+    assert_eq!(payment_ttl(&env, &client, "invoisio-ttl"), storage::BUMP_TTL);
+}
+
+#[test]
+fn test_get_payment_extends_ttl_below_threshold() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-ttl-read", &payer, 10_000_000);
+
+    // Drop the remaining TTL just below MIN_TTL so the next read must extend it.
+    advance_ledger(&env, storage::BUMP_TTL - storage::MIN_TTL + 1);
+    assert!(payment_ttl(&env, &client, "invoisio-ttl-read") < storage::MIN_TTL);
+
+    client.get_payment(&String::from_str(&env, "invoisio-ttl-read"));
+    assert_eq!(
+        payment_ttl(&env, &client, "invoisio-ttl-read"),
+        storage::BUMP_TTL
+    );
+}
+
+#[test]
+fn test_extend_payment_ttl_restores_full_budget() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-ttl-poke", &payer, 10_000_000);
+
+    advance_ledger(&env, storage::BUMP_TTL - storage::MIN_TTL + 1);
+    client.extend_payment_ttl(&String::from_str(&env, "invoisio-ttl-poke"), &None);
+
+    assert_eq!(
+        payment_ttl(&env, &client, "invoisio-ttl-poke"),
+        storage::BUMP_TTL
+    );
+}
+
+#[test]
+fn test_extend_payment_ttl_absent_returns_error() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+
+    let result = client.try_extend_payment_ttl(&String::from_str(&env, "invoisio-ghost"), &None);
+    assert_eq!(result, Err(Ok(ContractError::PaymentNotFound)));
+}
+
+#[test]
+fn test_keeper_bounty_paid_from_pool_once_per_cooldown() {
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    let token = TokenClient::new(&env, &sac.address());
+    let asset = StellarAssetClient::new(&env, &sac.address());
+    // Fund the pool for one and a half bounties. Tokens transferred to the
+    // contract directly are not part of it.
+    let funder = Address::generate(&env);
+    asset.mint(&funder, &150);
+    client.fund_keeper_pool(&funder, &sac.address(), &150);
+    asset.mint(&client.address, &1_000);
+    assert_eq!(client.keeper_pool(&sac.address()), 150);
+    let bounty = KeeperBounty {
+        token: sac.address(),
+        amount: 100,
+    };
+    client.set_keeper_bounty(&Some(bounty), &client.admin_nonce());
+
+    let keeper = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-ttl-poke", &Address::generate(&env), 10_000_000);
+    record_xlm(&env, &client, "invoisio-ttl-poke-2", &Address::generate(&env), 10_000_000);
+    let id = String::from_str(&env, "invoisio-ttl-poke");
+    advance_ledger(&env, storage::KEEPER_COOLDOWN_TTL + 1);
+    client.extend_payment_ttl(&id, &Some(keeper.clone()));
+    assert_eq!(token.balance(&keeper), 100);
+
+    // Bumping the same record again is not rewarded within the cooldown,
+    // and an empty pool skips the reward without failing the call.
+    client.extend_payment_ttl(&id, &Some(keeper.clone()));
+    let other_id = String::from_str(&env, "invoisio-ttl-poke-2");
+    client.extend_payment_ttl(&other_id, &Some(keeper.clone()));
+    assert_eq!(token.balance(&keeper), 100);
+    assert_eq!(client.keeper_pool(&sac.address()), 50);
+    assert_eq!(token.balance(&client.address), 1_050);
+}
+
+#[test]
+fn test_keeper_bounty_not_paid_for_a_fresh_record() {
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    let token = TokenClient::new(&env, &sac.address());
+    let funder = Address::generate(&env);
+    StellarAssetClient::new(&env, &sac.address()).mint(&funder, &300);
+    client.fund_keeper_pool(&funder, &sac.address(), &300);
+    let bounty = KeeperBounty {
+        token: sac.address(),
+        amount: 100,
+    };
+    client.set_keeper_bounty(&Some(bounty), &client.admin_nonce());
+
+    // Just recorded, the TTL is far above MIN_TTL: nothing to reward.
+    let keeper = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-fresh", &Address::generate(&env), 10_000_000);
+    let id = String::from_str(&env, "invoisio-fresh");
+    client.extend_payment_ttl(&id, &Some(keeper.clone()));
+    advance_ledger(&env, storage::KEEPER_COOLDOWN_TTL);
+    client.extend_payment_ttl(&id, &Some(keeper.clone()));
+    assert_eq!(token.balance(&keeper), 0);
+
+    // A bump without a keeper still counts: the next one is not rewarded.
+    advance_ledger(&env, 1);
+    client.extend_payment_ttl(&id, &None);
+    client.extend_payment_ttl(&id, &Some(keeper.clone()));
+    assert_eq!(token.balance(&keeper), 0);
+    assert_eq!(client.keeper_pool(&sac.address()), 300);
+}
+
+#[test]
+fn test_fund_keeper_pool_needs_funder_auth_and_a_positive_amount() {
+    use soroban_sdk::token::StellarAssetClient;
+    use soroban_sdk::{Symbol, Val, Vec};
+
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    let funder = Address::generate(&env);
+    env.mock_all_auths();
+    StellarAssetClient::new(&env, &sac.address()).mint(&funder, &100);
+    env.set_auths(&[]);
+
+    assert!(client
+        .try_fund_keeper_pool(&funder, &sac.address(), &100)
+        .is_err());
+    env.mock_all_auths();
+    assert_eq!(
+        client.try_fund_keeper_pool(&funder, &sac.address(), &0),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    client.fund_keeper_pool(&funder, &sac.address(), &100);
+    let (contract, topics, _) = last_event(&env);
+    assert_eq!(contract, client.address);
+    let expected: Vec<Val> = soroban_sdk::vec![
+        &env,
+        Symbol::new(&env, "keeper_pool_funded").into_val(&env),
+        funder.into_val(&env),
+    ];
+    assert_eq!(topics, expected);
+    assert_eq!(client.keeper_pool(&sac.address()), 100);
+}
+
+#[test]
+fn test_archived_payment_is_not_reported_absent() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-archived", &payer, 10_000_000);
+    pin_instance(&env, &client);
+
+    // Let the record's TTL lapse; the instance stays live.
+    advance_ledger(&env, storage::BUMP_TTL + 1);
+
+    let invoice_id = String::from_str(&env, "invoisio-archived");
+
+    // The host restores the archived entry on read, so both calls see the
+    // original record rather than claiming it was never recorded.
+    assert_eq!(client.try_has_payment(&invoice_id), Ok(Ok(true)));
+    let record = client.try_get_payment(&invoice_id).unwrap().unwrap();
+    assert_eq!(record.invoice_id, invoice_id);
+    assert_eq!(record.payer, payer);
+    assert_eq!(record.asset, Asset::Native);
+    assert_eq!(record.amount, 10_000_000);
+}
+
+#[test]
+fn test_archived_payment_cannot_be_re_recorded() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-archived-dup", &payer, 10_000_000);
+    pin_instance(&env, &client);
+    advance_ledger(&env, storage::BUMP_TTL + 1);
+
+    // The duplicate check restores the archived record and rejects the second
+    // one, so the original is never silently overwritten.
+    let result = client.try_record_payment(
+        &String::from_str(&env, "invoisio-archived-dup"),
+        &payer,
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &10_000_000i128,
+    );
+    assert_eq!(result, Err(Ok(ContractError::PaymentAlreadyRecorded)));
+    assert_eq!(client.payment_count(), 1);
+}
+
+// Pending payments (temporary storage)
+
+/// Report an XLM candidate payment.
+fn report_xlm(
+    env: &Env,
+    client: &InvoicePaymentContractClient,
+    invoice_id: &str,
+    payer: &Address,
+    stroops: i128,
+) {
+    client.report_pending_payment(
+        &String::from_str(env, invoice_id),
+        payer,
+        &String::from_str(env, "XLM"),
+        &String::from_str(env, ""),
+        &stroops,
+    );
+}
+
+#[test]
+fn test_report_pending_payment_is_not_recorded() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    report_xlm(&env, &client, "invoisio-pending", &payer, 10_000_000);
+
+    let invoice_id = String::from_str(&env, "invoisio-pending");
+    let pending = client.get_pending_payment(&invoice_id);
+    assert_eq!(pending.payer, payer);
+    assert_eq!(pending.amount, 10_000_000i128);
+
+    assert!(!client.has_payment(&invoice_id));
+    assert_eq!(client.payment_count(), 0);
+}
+
+#[test]
+fn test_confirm_pending_payment_promotes_record() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    report_xlm(&env, &client, "invoisio-promote", &payer, 10_000_000);
+    let reported_at = env.ledger().timestamp();
+
+    advance_ledger(&env, 10);
+    let invoice_id = String::from_str(&env, "invoisio-promote");
+    client.confirm_pending_payment(&invoice_id);
+
+    let record = client.get_payment(&invoice_id);
+    assert_eq!(record.asset, Asset::Native);
+    assert_eq!(record.timestamp, reported_at);
+    assert_eq!(client.payment_count(), 1);
+    assert_eq!(
+        client.try_get_pending_payment(&invoice_id),
+        Err(Ok(ContractError::PendingPaymentNotFound))
+    );
+}
+
+#[test]
+fn test_report_pending_payment_twice_returns_error() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    report_xlm(&env, &client, "invoisio-pending-dup", &payer, 10_000_000);
+
+    let result = client.try_report_pending_payment(
+        &String::from_str(&env, "invoisio-pending-dup"),
+        &payer,
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &10_000_000i128,
+    );
+    assert_eq!(result, Err(Ok(ContractError::PaymentAlreadyPending)));
+}
+
+#[test]
+fn test_report_pending_for_recorded_invoice_returns_error() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-already", &payer, 10_000_000);
+
+    let result = client.try_report_pending_payment(
+        &String::from_str(&env, "invoisio-already"),
+        &payer,
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &10_000_000i128,
+    );
+    assert_eq!(result, Err(Ok(ContractError::PaymentAlreadyRecorded)));
+}
+
+#[test]
+fn test_report_pending_payment_validates_arguments() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    let result = client.try_report_pending_payment(
+        &String::from_str(&env, "invoisio-pending-neg"),
+        &payer,
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &0i128,
+    );
+    assert_eq!(result, Err(Ok(ContractError::InvalidAmount)));
+}
+
+#[test]
+fn test_pending_payment_expires() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    report_xlm(&env, &client, "invoisio-expired", &payer, 10_000_000);
+
+    advance_ledger(&env, storage::PENDING_TTL + 1);
+
+    let invoice_id = String::from_str(&env, "invoisio-expired");
+    assert_eq!(
+        client.try_confirm_pending_payment(&invoice_id),
+        Err(Ok(ContractError::PendingPaymentNotFound))
+    );
+    assert_eq!(client.payment_count(), 0);
+}
+
+#[test]
+fn test_discard_pending_payment_removes_candidate() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    report_xlm(&env, &client, "invoisio-noise", &payer, 10_000_000);
+
+    let invoice_id = String::from_str(&env, "invoisio-noise");
+    client.discard_pending_payment(&invoice_id);
+
+    assert_eq!(
+        client.try_discard_pending_payment(&invoice_id),
+        Err(Ok(ContractError::PendingPaymentNotFound))
+    );
+}
+
+// Storage stats
+
+#[test]
+fn test_storage_stats_reports_counts_and_ttls() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-stats-1", &payer, 10_000_000);
+    record_xlm(&env, &client, "invoisio-stats-2", &payer, 10_000_000);
+    report_xlm(&env, &client, "invoisio-stats-3", &payer, 10_000_000);
+
+    let stats = client.storage_stats();
+    assert_eq!(
+        stats,
+        StorageStats {
+            payments: 2,
+            hashed_payments: 0,
+            compacted_payments: 0,
+            external_payments: 0,
+            chain_payments: 0,
+            deposit_invoices: 0,
+            installment_plans: 0,
+            payment_index: 2,
+            remittance_index: 0,
+            pending_reported_total: 1,
+            min_ttl: storage::MIN_TTL,
+            bump_ttl: storage::BUMP_TTL,
+            pending_ttl: storage::PENDING_TTL,
+        }
+    );
+}
+
+#[test]
+fn test_storage_stats_counts_each_key_family() {
+    use crate::testutils::{USDC, USDC_TESTNET_ISSUER};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+
+    record_xlm(&env, &client, "invoisio-stats-1", &payer, 1);
+    record_xlm(&env, &client, "invoisio-stats-2", &payer, 1);
+    client.record_hashed_payment(&invoice_hash(&env, "invoice-1"), &payer, &xlm, &none, &1);
+    record_sepa(&env, &client, "invoisio-stats-ext", 1).unwrap();
+    record_on_base(&env, &client, "invoisio-stats-evm", &usdc_on_base(&env), 1).unwrap();
+    let deposit = String::from_str(&env, "invoisio-stats-dep");
+    client.issue_deposit_invoice(&deposit, &xlm, &none, &1_000, &3_000, &None);
+    let plan = String::from_str(&env, "invoisio-stats-plan");
+    let schedule = soroban_sdk::vec![&env, (100i128, 2_000u64), (100, 3_000)];
+    client.issue_installment_plan(&plan, &xlm, &none, &schedule);
+    let remittance = Remittance {
+        transaction_id: String::from_str(&env, "82fhs729f63dh0v4"),
+        sending_anchor: String::from_str(&env, "sender.example"),
+        receiving_anchor: String::from_str(&env, "receiver.example"),
+    };
+    client.record_remittance_payment(
+        &String::from_str(&env, "invoisio-stats-sep31"),
+        &payer,
+        &String::from_str(&env, USDC),
+        &String::from_str(&env, USDC_TESTNET_ISSUER),
+        &1,
+        &remittance,
+    );
+    client.compact_old_records(&u64::MAX, &1, &client.admin_nonce());
+
+    let stats = client.storage_stats();
+    assert_eq!(stats.payments, 4);
+    assert_eq!(stats.hashed_payments, 1);
+    assert_eq!(stats.compacted_payments, 1);
+    assert_eq!(stats.external_payments, 1);
+    assert_eq!(stats.chain_payments, 1);
+    assert_eq!(stats.deposit_invoices, 1);
+    assert_eq!(stats.installment_plans, 1);
+    // The second Stellar payment and the remittance are still indexed.
+    assert_eq!(stats.payment_index, 2);
+    assert_eq!(stats.remittance_index, 1);
+}
+
+#[test]
+fn test_storage_stats_confirmed_pending_counts_as_payment() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    let payer = Address::generate(&env);
+    report_xlm(&env, &client, "invoisio-stats-pending", &payer, 10_000_000);
+    client.confirm_pending_payment(&String::from_str(&env, "invoisio-stats-pending"));
+
+    let stats = client.storage_stats();
+    assert_eq!(stats.payments, 1);
+    assert_eq!(stats.payment_index, 1);
+    // Cumulative: the confirmed candidate is gone but still counted.
+    assert_eq!(stats.pending_reported_total, 1);
+}
+
+#[test]
+fn test_totals_track_net_received_per_asset() {
+    use crate::testutils::{record_token, USDC, USDC_TESTNET_ISSUER};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    assert_eq!(client.totals().len(), 0);
+
+    record_xlm(&env, &client, "invoisio-tot-1", &payer, 10_000_000);
+    record_xlm(&env, &client, "invoisio-tot-2", &payer, 5_000_000);
+    record_token(&env, &client, "invoisio-tot-3", &payer, USDC, USDC_TESTNET_ISSUER, 700);
+    client.record_refund(&String::from_str(&env, "invoisio-tot-1"), &2_000_000);
+
+    let usdc = Asset::Token(
+        String::from_str(&env, USDC),
+        String::from_str(&env, USDC_TESTNET_ISSUER),
+    );
+    let totals = client.totals();
+    assert_eq!(totals.get(Asset::Native), Some(13_000_000));
+    assert_eq!(totals.get(usdc), Some(700));
+}
+
+#[test]
+fn test_daily_and_monthly_totals_bucket_by_utc_date() {
+    use soroban_sdk::testutils::Ledger as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+
+    // 2026-01-31 and 2026-02-01, days 20_484 and 20_485.
+    env.ledger().with_mut(|li| li.timestamp = 20_484 * 86_400 + 3_600);
+    record_xlm(&env, &client, "invoisio-day-1", &payer, 10_000_000);
+    record_xlm(&env, &client, "invoisio-day-2", &payer, 5_000_000);
+    env.ledger().with_mut(|li| li.timestamp = 20_485 * 86_400);
+    record_xlm(&env, &client, "invoisio-day-3", &payer, 2_000_000);
+    client.record_refund(&String::from_str(&env, "invoisio-day-1"), &1_000_000);
+
+    let jan_31 = client.daily_totals(&20_484).get(Asset::Native).unwrap();
+    assert_eq!((jan_31.count, jan_31.amount), (2, 15_000_000));
+    let feb_1 = client.daily_totals(&20_485).get(Asset::Native).unwrap();
+    assert_eq!((feb_1.count, feb_1.amount), (1, 2_000_000));
+    assert_eq!(client.daily_totals(&20_486).len(), 0);
+
+    let january = client.monthly_totals(&2026, &1).get(Asset::Native).unwrap();
+    assert_eq!((january.count, january.amount), (2, 15_000_000));
+    let february = client.monthly_totals(&2026, &2).get(Asset::Native).unwrap();
+    assert_eq!((february.count, february.amount), (1, 2_000_000));
+    assert_eq!(client.monthly_totals(&2026, &3).len(), 0);
+    assert_eq!(client.monthly_totals(&2026, &13).len(), 0);
+}
+
+// Tax
+
+fn vat(env: &Env, rate_bps: u32, amount: i128, jurisdiction: &str) -> InvoiceTax {
+    InvoiceTax {
+        rate_bps,
+        amount,
+        jurisdiction: String::from_str(env, jurisdiction),
+    }
+}
+
+#[test]
+fn test_invoice_tax_splits_payment_and_totals() {
+    use soroban_sdk::{Symbol, Val, Vec};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let id = String::from_str(&env, "invoisio-t1");
+    assert_eq!(
+        client.try_set_invoice_tax(&id, &Some(vat(&env, 2_000, 200_000, "de"))),
+        Err(Ok(ContractError::InvalidTax))
+    );
+    client.set_invoice_tax(&id, &Some(vat(&env, 2_000, 200_000, "DE")));
+
+    record_xlm(&env, &client, "invoisio-t1", &Address::generate(&env), 1_200_000);
+
+    let (_, topics, _) = last_event(&env);
+    let expected: Vec<Val> = soroban_sdk::vec![
+        &env,
+        Symbol::new(&env, "payment_taxed").into_val(&env),
+        id.into_val(&env)
+    ];
+    assert_eq!(topics, expected);
+    let split = client.payment_tax(&id).unwrap();
+    assert_eq!((split.net, split.tax), (1_000_000, 200_000));
+    assert_eq!(split.jurisdiction, String::from_str(&env, "DE"));
+    let today = client.daily_totals(&0).get(Asset::Native).unwrap();
+    assert_eq!((today.amount, today.tax), (1_200_000, 200_000));
+    assert_eq!(
+        client.try_set_invoice_tax(&id, &None),
+        Err(Ok(ContractError::PaymentAlreadyRecorded))
+    );
+
+    // Payments of invoices without tax carry no split.
+    record_xlm(&env, &client, "invoisio-t2", &Address::generate(&env), 1);
+    assert_eq!(client.payment_tax(&String::from_str(&env, "invoisio-t2")), None);
+}
+
+#[test]
+fn test_tax_split_lives_as_long_as_its_payment() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    pin_instance(&env, &client);
+    let id = String::from_str(&env, "invoisio-t1");
+    let key = DataKey::PaymentTax(id.clone());
+    client.set_invoice_tax(&id, &Some(vat(&env, 2_000, 200_000, "DE")));
+    record_xlm(&env, &client, "invoisio-t1", &Address::generate(&env), 1_200_000);
+
+    // Kept alive with the payment by a keeper...
+    advance_ledger(&env, storage::BUMP_TTL - storage::MIN_TTL + 1);
+    client.extend_payment_ttl(&id, &None);
+    assert_eq!(entry_ttl(&env, &client, &key), storage::BUMP_TTL);
+
+    // ...and by reads of the split.
+    advance_ledger(&env, storage::BUMP_TTL - storage::MIN_TTL + 1);
+    client.payment_tax(&id);
+    assert_eq!(entry_ttl(&env, &client, &key), storage::BUMP_TTL);
+}
+
+#[test]
+fn test_invoice_tax_is_allocated_across_installments() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-t3");
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+    let schedule = soroban_sdk::vec![&env, (100i128, 2_000u64), (100, 3_000), (100, 4_000)];
+    client.issue_installment_plan(&id, &xlm, &none, &schedule);
+    assert_eq!(
+        client.try_set_invoice_tax(&id, &Some(vat(&env, 2_000, 301, "US-CA"))),
+        Err(Ok(ContractError::InvalidTax))
+    );
+    client.set_invoice_tax(&id, &Some(vat(&env, 2_000, 50, "US-CA")));
+
+    let mut taxes = std::vec::Vec::new();
+    for payment_id in ["invoisio-t3-1", "invoisio-t3-2", "invoisio-t3-3"] {
+        let payment_id = String::from_str(&env, payment_id);
+        client.record_invoice_payment(&id, &payment_id, &payer, &xlm, &none, &100);
+        taxes.push(client.payment_tax(&payment_id).unwrap().tax);
+    }
+    assert_eq!(taxes, [16, 17, 17]);
+    assert_eq!(client.daily_totals(&0).get(Asset::Native).unwrap().tax, 50);
+}
+
+// Invariants
+
+#[test]
+fn test_check_invariants_reports_totals_and_dangling_index() {
+    use crate::testutils::{record_token, USDC, USDC_TESTNET_ISSUER};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-inv-1", &payer, 10_000_000);
+    record_xlm(&env, &client, "invoisio-inv-2", &payer, 5_000_000);
+    record_token(&env, &client, "invoisio-inv-3", &payer, USDC, USDC_TESTNET_ISSUER, 700);
+    client.record_refund(&String::from_str(&env, "invoisio-inv-1"), &1_000_000);
+
+    let report = client.check_invariants(&0, &100);
+    assert!(report.ok);
+    assert_eq!((report.from, report.to, report.count, report.indexed), (0, 3, 3, 3));
+    assert_eq!(report.totals.get(Asset::Native), Some(15_000_000));
+    assert_eq!(report.totals.len(), 2);
+
+    // A record lost behind the index's back is flagged; other slices pass.
+    env.as_contract(&client.address, || {
+        storage::remove_payment(&env, &String::from_str(&env, "invoisio-inv-2"));
+    });
+    let report = client.check_invariants(&0, &100);
+    assert!(!report.ok);
+    assert_eq!(report.dangling, soroban_sdk::vec![&env, 1u32]);
+    assert!(client.check_invariants(&2, &1).ok);
+}
+
+// Receipts
+
+fn setup_receipts(
+    env: &Env,
+    client: &InvoicePaymentContractClient,
+) -> receipts::ReceiptContractClient<'static> {
+    let contract_id = env.register(receipts::ReceiptContract, ());
+    let receipts = receipts::ReceiptContractClient::new(env, &contract_id);
+    // The payment contract is the minter.
+    receipts.initialize(&Address::generate(env), &client.address);
+    receipts
+}
+
+#[test]
+fn test_receipts_disabled_by_default() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+
+    assert_eq!(client.receipt_contract(), None);
+}
+
+#[test]
+fn test_record_payment_mints_receipt_when_enabled() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    env.mock_all_auths();
+    let receipts = setup_receipts(&env, &client);
+    client.set_receipt_contract(&Some(receipts.address.clone()), &client.admin_nonce());
+    assert_eq!(client.receipt_contract(), Some(receipts.address.clone()));
+
+    // Only the admin signs: the payment contract authorises the mint itself
+    // as the direct invoker.
+    let payer = Address::generate(&env);
+    mock_auths(
+        &env,
+        &[AuthCall::record_xlm(&env, &client, &admin, "invoisio-rcpt", &payer, 10_000_000)],
+    );
+    record_xlm(&env, &client, "invoisio-rcpt", &payer, 10_000_000);
+
+    let receipt = receipts.receipt_for(&String::from_str(&env, "invoisio-rcpt"));
+    assert_eq!(receipt.owner, payer);
+    assert_eq!(receipt.asset, receipts::Asset::Native);
+    assert_eq!(receipt.amount, 10_000_000);
+    assert_eq!(receipt.paid_at, env.ledger().timestamp());
+    assert_eq!(receipts.balance(&payer), 1);
+}
+
+#[test]
+fn test_confirm_pending_payment_mints_receipt() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let receipts = setup_receipts(&env, &client);
+    client.set_receipt_contract(&Some(receipts.address.clone()), &client.admin_nonce());
+
+    let payer = Address::generate(&env);
+    report_xlm(&env, &client, "invoisio-rcpt-pending", &payer, 5);
+    assert_eq!(receipts.total_supply(), 0);
+
+    client.confirm_pending_payment(&String::from_str(&env, "invoisio-rcpt-pending"));
+    assert_eq!(receipts.owner_of(&0), payer);
+}
+
+#[test]
+fn test_disabling_receipts_stops_minting() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let receipts = setup_receipts(&env, &client);
+    let payer = Address::generate(&env);
+
+    client.set_receipt_contract(&Some(receipts.address.clone()), &client.admin_nonce());
+    record_xlm(&env, &client, "invoisio-rcpt-1", &payer, 1);
+    client.set_receipt_contract(&None, &client.admin_nonce());
+    record_xlm(&env, &client, "invoisio-rcpt-2", &payer, 1);
+
+    assert_eq!(receipts.total_supply(), 1);
+    assert_eq!(client.payment_count(), 2);
+}
+
+#[test]
+fn test_record_payment_fails_if_not_receipt_minter() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    env.mock_all_auths();
+    let contract_id = env.register(receipts::ReceiptContract, ());
+    let receipts = receipts::ReceiptContractClient::new(&env, &contract_id);
+    // Some other address is the minter.
+    receipts.initialize(&Address::generate(&env), &Address::generate(&env));
+    client.set_receipt_contract(&Some(receipts.address.clone()), &client.admin_nonce());
+
+    let payer = Address::generate(&env);
+    mock_auths(
+        &env,
+        &[AuthCall::record_xlm(&env, &client, &admin, "invoisio-rcpt", &payer, 1)],
+    );
+    let result = client.try_record_payment(
+        &String::from_str(&env, "invoisio-rcpt"),
+        &payer,
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &1,
+    );
+    assert!(result.is_err());
+    assert_eq!(client.payment_count(), 0);
+}
+
+// Merchants
+
+fn setup_registry(env: &Env, client: &InvoicePaymentContractClient) -> Address {
+    let contract_id = env.register(merchant_registry::MerchantRegistryContract, ());
+    let registry = merchant_registry::MerchantRegistryContractClient::new(env, &contract_id);
+    registry.initialize(&Address::generate(env));
+    for id in ["acme", "dormant"] {
+        registry.onboard(
+            &String::from_str(env, id),
+            &Address::generate(env),
+            &soroban_sdk::BytesN::from_array(env, &[0; 32]),
+        );
+    }
+    registry.set_active(&String::from_str(env, "dormant"), &false);
+    client.set_merchant_registry(&Some(contract_id.clone()), &client.admin_nonce());
+    contract_id
+}
+
+fn record_for_merchant(
+    env: &Env,
+    client: &InvoicePaymentContractClient,
+    merchant_id: &str,
+    invoice_id: &str,
+) -> Result<(), ContractError> {
+    match client.try_record_merchant_payment(
+        &String::from_str(env, merchant_id),
+        &String::from_str(env, invoice_id),
+        &Address::generate(env),
+        &String::from_str(env, "XLM"),
+        &String::from_str(env, ""),
+        &10_000_000i128,
+    ) {
+        Ok(_) => Ok(()),
+        Err(Ok(e)) => Err(e),
+        Err(Err(e)) => panic!("host error: {e:?}"),
+    }
+}
+
+#[test]
+fn test_record_merchant_payment_links_merchant() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let registry = setup_registry(&env, &client);
+    assert_eq!(client.merchant_registry(), Some(registry));
+
+    record_for_merchant(&env, &client, "acme", "invoisio-m1").unwrap();
+
+    let invoice_id = String::from_str(&env, "invoisio-m1");
+    assert!(client.has_payment(&invoice_id));
+    assert_eq!(client.payment_merchant(&invoice_id), Some(String::from_str(&env, "acme")));
+    assert_eq!(client.payment_count(), 1);
+}
+
+#[test]
+fn test_record_merchant_payment_rejects_unknown_and_inactive() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    setup_registry(&env, &client);
+
+    assert_eq!(
+        record_for_merchant(&env, &client, "ghost", "invoisio-m1"),
+        Err(ContractError::UnknownMerchant)
+    );
+    assert_eq!(
+        record_for_merchant(&env, &client, "dormant", "invoisio-m1"),
+        Err(ContractError::MerchantInactive)
+    );
+    assert_eq!(client.payment_count(), 0);
+}
+
+#[test]
+fn test_record_merchant_payment_requires_registry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    assert_eq!(
+        record_for_merchant(&env, &client, "acme", "invoisio-m1"),
+        Err(ContractError::MerchantRegistryNotSet)
+    );
+}
+
+// Payouts
+
+/// Record a merchant payment of `amount` in the asset of the test token
+/// `sac`, so its funds can be collected through `sac`.
+fn record_sac_for_merchant(
+    env: &Env,
+    client: &InvoicePaymentContractClient,
+    sac: &soroban_sdk::testutils::StellarAssetContract,
+    merchant_id: &str,
+    invoice_id: &str,
+    amount: i128,
+) {
+    // Test tokens wrap the asset `aaa` of their issuer.
+    client.record_merchant_payment(
+        &String::from_str(env, merchant_id),
+        &String::from_str(env, invoice_id),
+        &Address::generate(env),
+        &String::from_str(env, "aaa"),
+        &sac.issuer().address().to_string(),
+        &amount,
+    );
+}
+
+#[test]
+fn test_settle_pays_collected_funds_to_merchant_payout() {
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let registry_id = setup_registry(&env, &client);
+    let registry = merchant_registry::MerchantRegistryContractClient::new(&env, &registry_id);
+    let acme = String::from_str(&env, "acme");
+    let payout = registry.get_merchant(&acme).payout;
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    let token = TokenClient::new(&env, &sac.address());
+    StellarAssetClient::new(&env, &sac.address()).mint(&admin, &300);
+
+    record_sac_for_merchant(&env, &client, &sac, "acme", "invoisio-p1", 100);
+    record_sac_for_merchant(&env, &client, &sac, "acme", "invoisio-p2", 150);
+    let (p1, p2) = (String::from_str(&env, "invoisio-p1"), String::from_str(&env, "invoisio-p2"));
+    client.collect(&p1, &token.address, &100);
+    client.collect(&p2, &token.address, &150);
+    assert_eq!(
+        client.try_collect(&p1, &token.address, &100),
+        Err(Ok(ContractError::AlreadyCollected))
+    );
+    assert_eq!(token.balance(&client.address), 250);
+
+    assert_eq!(client.settle(&acme), 0);
+    assert_eq!(token.balance(&payout), 250);
+    assert_eq!(token.balance(&client.address), 0);
+    let settlement = client.get_settlement(&0);
+    assert_eq!(settlement.payout, payout);
+    assert_eq!(settlement.amounts.get(token.address.clone()), Some(250));
+    assert_eq!(settlement.invoice_ids, soroban_sdk::vec![&env, p1, p2]);
+    assert_eq!(client.settlement_count(), 1);
+    assert_eq!(client.merchant_balance(&acme), None);
+    assert_eq!(client.try_settle(&acme), Err(Ok(ContractError::NothingToSettle)));
+}
+
+#[test]
+fn test_collect_checks_token_and_amount_against_the_payment() {
+    use soroban_sdk::token::StellarAssetClient;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    setup_registry(&env, &client);
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    let other = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    StellarAssetClient::new(&env, &sac.address()).mint(&admin, &200);
+    StellarAssetClient::new(&env, &other.address()).mint(&admin, &200);
+    record_sac_for_merchant(&env, &client, &sac, "acme", "invoisio-c1", 100);
+    record_for_merchant(&env, &client, "acme", "invoisio-c2").unwrap();
+    let (c1, c2) = (String::from_str(&env, "invoisio-c1"), String::from_str(&env, "invoisio-c2"));
+
+    assert_eq!(
+        client.try_collect(&c1, &other.address(), &100),
+        Err(Ok(ContractError::TokenMismatch))
+    );
+    assert_eq!(
+        client.try_collect(&c2, &sac.address(), &10_000_000),
+        Err(Ok(ContractError::TokenMismatch))
+    );
+    assert_eq!(
+        client.try_collect(&c1, &sac.address(), &99),
+        Err(Ok(ContractError::AmountMismatch))
+    );
+    client.collect(&c1, &sac.address(), &100);
+    assert_eq!(
+        client.try_collect(&c1, &sac.address(), &100),
+        Err(Ok(ContractError::AlreadyCollected))
+    );
+}
+
+#[test]
+fn test_keeper_bounties_never_pay_out_collected_funds() {
+    use soroban_sdk::token::{StellarAssetClient, TokenClient};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let registry_id = setup_registry(&env, &client);
+    let registry = merchant_registry::MerchantRegistryContractClient::new(&env, &registry_id);
+    let acme = String::from_str(&env, "acme");
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    let token = TokenClient::new(&env, &sac.address());
+    StellarAssetClient::new(&env, &sac.address()).mint(&admin, &500);
+    let bounty = KeeperBounty {
+        token: sac.address(),
+        amount: 100,
+    };
+    client.set_keeper_bounty(&Some(bounty), &client.admin_nonce());
+
+    record_sac_for_merchant(&env, &client, &sac, "acme", "invoisio-k1", 500);
+    client.collect(&String::from_str(&env, "invoisio-k1"), &sac.address(), &500);
+
+    // The contract holds the merchant's 500, but the keeper pool is empty.
+    let keeper = Address::generate(&env);
+    advance_ledger(&env, storage::KEEPER_COOLDOWN_TTL + 1);
+    client.extend_payment_ttl(&String::from_str(&env, "invoisio-k1"), &Some(keeper.clone()));
+    assert_eq!(token.balance(&keeper), 0);
+    client.settle(&acme);
+    assert_eq!(token.balance(&registry.get_merchant(&acme).payout), 500);
+}
+
+#[test]
+fn test_collect_requires_a_merchant_payment() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    record_xlm(&env, &client, "invoisio-plain", &Address::generate(&env), 10_000_000);
+    let token = Address::generate(&env);
+
+    assert_eq!(
+        client.try_collect(&String::from_str(&env, "invoisio-plain"), &token, &100),
+        Err(Ok(ContractError::PaymentNotFound))
+    );
+    assert_eq!(
+        client.try_collect(&String::from_str(&env, "invoisio-plain"), &token, &0),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+}
+
+#[test]
+fn test_payout_schedule_limits_settle_to_release_days() {
+    use soroban_sdk::testutils::Ledger as _;
+    use soroban_sdk::token::StellarAssetClient;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    setup_registry(&env, &client);
+    let acme = String::from_str(&env, "acme");
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    StellarAssetClient::new(&env, &sac.address()).mint(&admin, &300);
+    let set_day = |day: u64| env.ledger().with_mut(|li| li.timestamp = day * 86_400 + 3_600);
+    let collect = |invoice_id: &str| {
+        record_sac_for_merchant(&env, &client, &sac, "acme", invoice_id, 100);
+        client.collect(&String::from_str(&env, invoice_id), &sac.address(), &100);
+    };
+
+    set_day(19_998);
+    let weekly = PayoutSchedule {
+        every_days: 7,
+        anchor_day: 20_000,
+    };
+    assert_eq!(
+        client.try_set_payout_schedule(&acme, &Some(PayoutSchedule { every_days: 0, ..weekly })),
+        Err(Ok(ContractError::InvalidSchedule))
+    );
+    client.set_payout_schedule(&acme, &Some(weekly));
+    assert_eq!(client.payout_schedule(&acme), Some(weekly));
+
+    collect("invoisio-s1");
+    assert_eq!(client.try_settle(&acme), Err(Ok(ContractError::OutsidePayoutWindow)));
+
+    // On a release day anyone may settle, once.
+    set_day(20_000);
+    env.set_auths(&[]);
+    assert_eq!(client.settle(&acme), 0);
+    env.mock_all_auths();
+    collect("invoisio-s2");
+    assert_eq!(client.try_settle(&acme), Err(Ok(ContractError::OutsidePayoutWindow)));
+    set_day(20_003);
+    assert_eq!(client.try_settle(&acme), Err(Ok(ContractError::OutsidePayoutWindow)));
+    set_day(20_007);
+    assert_eq!(client.settle(&acme), 1);
+
+    // Without a schedule the admin settles at will again.
+    client.set_payout_schedule(&acme, &None);
+    collect("invoisio-s3");
+    assert_eq!(client.settle(&acme), 2);
+}
+
+#[test]
+fn test_record_payment_has_no_merchant() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    record_xlm(&env, &client, "invoisio-plain", &Address::generate(&env), 1);
+
+    assert_eq!(client.payment_merchant(&String::from_str(&env, "invoisio-plain")), None);
+}
+
+#[test]
+fn test_record_merchant_payment_emits_link_event() {
+    use soroban_sdk::{Map, Symbol, Val, Vec};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    setup_registry(&env, &client);
+
+    record_for_merchant(&env, &client, "acme", "invoisio-m1").unwrap();
+
+    let (contract, topics, data) = last_event(&env);
+    assert_eq!(contract, client.address);
+    let expected: Vec<Val> =
+        soroban_sdk::vec![&env, Symbol::new(&env, "merchant_payment_recorded").into_val(&env)];
+    assert_eq!(topics, expected);
+    let data: Map<Symbol, Val> = data.into_val(&env);
+    let field = |name: &str| data.get(Symbol::new(&env, name)).unwrap();
+    let invoice_id: String = field("invoice_id").into_val(&env);
+    let merchant_id: String = field("merchant_id").into_val(&env);
+    let receipt_number: u64 = field("receipt_number").into_val(&env);
+    assert_eq!(invoice_id, String::from_str(&env, "invoisio-m1"));
+    assert_eq!(merchant_id, String::from_str(&env, "acme"));
+    assert_eq!(receipt_number, 1);
+}
+
+#[test]
+fn test_receipt_numbers_are_sequential_per_merchant() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let registry = setup_registry(&env, &client);
+    merchant_registry::MerchantRegistryContractClient::new(&env, &registry).onboard(
+        &String::from_str(&env, "globex"),
+        &Address::generate(&env),
+        &soroban_sdk::BytesN::from_array(&env, &[0; 32]),
+    );
+    let number = |id: &str| client.receipt_number(&String::from_str(&env, id));
+
+    record_for_merchant(&env, &client, "acme", "invoisio-r1").unwrap();
+    record_for_merchant(&env, &client, "globex", "invoisio-r2").unwrap();
+    // A failed recording takes no number.
+    assert_eq!(
+        record_for_merchant(&env, &client, "acme", "invoisio-r1"),
+        Err(ContractError::PaymentAlreadyRecorded)
+    );
+    record_for_merchant(&env, &client, "acme", "invoisio-r3").unwrap();
+    record_xlm(&env, &client, "invoisio-r4", &Address::generate(&env), 1);
+
+    assert_eq!(number("invoisio-r1"), Some(1));
+    assert_eq!(number("invoisio-r2"), Some(1));
+    assert_eq!(number("invoisio-r3"), Some(2));
+    assert_eq!(number("invoisio-r4"), None);
+    assert_eq!(client.receipt_count(&String::from_str(&env, "acme")), 2);
+    assert_eq!(client.receipt_count(&String::from_str(&env, "globex")), 1);
+}
+
+#[test]
+fn test_get_receipt_assembles_payment_proof() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    setup_registry(&env, &client);
+    let id = String::from_str(&env, "invoisio-rcpt");
+
+    assert_eq!(client.try_get_receipt(&id), Err(Ok(ContractError::PaymentNotFound)));
+    record_for_merchant(&env, &client, "acme", "invoisio-rcpt").unwrap();
+    client.record_refund(&id, &1_000_000);
+
+    let record = client.get_payment(&id);
+    let receipt = client.get_receipt(&id);
+    assert_eq!(receipt.contract, client.address);
+    assert_eq!(receipt.invoice_id, id);
+    assert_eq!(receipt.payer, record.payer);
+    assert_eq!(receipt.asset, Asset::Native);
+    assert_eq!(receipt.amount, 10_000_000);
+    assert_eq!(receipt.refunded, 1_000_000);
+    assert_eq!(receipt.paid_at, record.timestamp);
+    assert_eq!(receipt.merchant_id, Some(String::from_str(&env, "acme")));
+    assert_eq!(receipt.receipt_number, Some(1));
+}
+
+// Compliance
+
+fn try_record(
+    env: &Env,
+    client: &InvoicePaymentContractClient,
+    invoice_id: &str,
+    payer: &Address,
+) -> Result<(), ContractError> {
+    match client.try_record_payment(
+        &String::from_str(env, invoice_id),
+        payer,
+        &String::from_str(env, "XLM"),
+        &String::from_str(env, ""),
+        &1,
+    ) {
+        Ok(_) => Ok(()),
+        Err(Ok(e)) => Err(e),
+        Err(Err(e)) => panic!("host error: {e:?}"),
+    }
+}
+
+#[test]
+fn test_payer_flags_default_and_update() {
+    use soroban_sdk::testutils::Ledger as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+
+    assert_eq!(client.payer_flags(&payer), PayerFlags::default());
+    assert_eq!(client.compliance_policy(), CompliancePolicy::default());
+
+    client.set_payer_flags(&payer, &true, &false, &client.admin_nonce());
+    assert_eq!(
+        client.payer_flags(&payer),
+        PayerFlags {
+            kyc_verified: true,
+            high_risk: false,
+            updated_at: 1_700_000_000,
+        }
+    );
+}
+
+#[test]
+fn test_flags_without_policy_are_informational() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    client.set_payer_flags(&payer, &false, &true, &client.admin_nonce());
+
+    assert_eq!(try_record(&env, &client, "invoisio-c1", &payer), Ok(()));
+}
+
+#[test]
+fn test_policy_blocks_flagged_payers_on_every_write_path() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let verified = Address::generate(&env);
+    let risky = Address::generate(&env);
+    let unknown = Address::generate(&env);
+    client.set_payer_flags(&verified, &true, &false, &client.admin_nonce());
+    client.set_payer_flags(&risky, &true, &true, &client.admin_nonce());
+
+    let policy = CompliancePolicy {
+        require_kyc: true,
+        reject_high_risk: true,
+    };
+    client.set_compliance_policy(&policy, &client.admin_nonce());
+
+    assert_eq!(try_record(&env, &client, "invoisio-c1", &verified), Ok(()));
+    assert_eq!(
+        try_record(&env, &client, "invoisio-c2", &unknown),
+        Err(ContractError::PayerNotVerified)
+    );
+    assert_eq!(
+        try_record(&env, &client, "invoisio-c3", &risky),
+        Err(ContractError::PayerHighRisk)
+    );
+    assert_eq!(
+        client.try_report_pending_payment(
+            &String::from_str(&env, "invoisio-c4"),
+            &risky,
+            &String::from_str(&env, "XLM"),
+            &String::from_str(&env, ""),
+            &1,
+        ),
+        Err(Ok(ContractError::PayerHighRisk))
+    );
+    assert_eq!(client.payment_count(), 1);
+}
+
+#[test]
+fn test_confirm_rechecks_payer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    client.set_payer_flags(&payer, &true, &false, &client.admin_nonce());
+    let policy = CompliancePolicy {
+        require_kyc: false,
+        reject_high_risk: true,
+    };
+    client.set_compliance_policy(&policy, &client.admin_nonce());
+    report_xlm(&env, &client, "invoisio-c5", &payer, 1);
+
+    // Screening result changed while the candidate was pending.
+    client.set_payer_flags(&payer, &true, &true, &client.admin_nonce());
+    let invoice_id = String::from_str(&env, "invoisio-c5");
+    assert_eq!(
+        client.try_confirm_pending_payment(&invoice_id),
+        Err(Ok(ContractError::PayerHighRisk))
+    );
+    assert!(!client.has_payment(&invoice_id));
+}
+
+#[test]
+fn test_blocked_payer_is_rejected_regardless_of_policy() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    client.set_payer_flags(&payer, &true, &false, &client.admin_nonce());
+
+    client.block_payer(&payer, &client.admin_nonce());
+    assert!(client.is_payer_blocked(&payer));
+    assert_eq!(
+        try_record(&env, &client, "invoisio-b1", &payer),
+        Err(ContractError::PayerBlocked)
+    );
+
+    client.unblock_payer(&payer, &client.admin_nonce());
+    assert!(!client.is_payer_blocked(&payer));
+    assert_eq!(try_record(&env, &client, "invoisio-b1", &payer), Ok(()));
+}
+
+#[test]
+fn test_payer_blocked_while_pending_cannot_be_confirmed() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    report_xlm(&env, &client, "invoisio-b2", &payer, 1);
+
+    client.block_payer(&payer, &client.admin_nonce());
+    assert_eq!(
+        client.try_confirm_pending_payment(&String::from_str(&env, "invoisio-b2")),
+        Err(Ok(ContractError::PayerBlocked))
+    );
+}
+
+#[test]
+fn test_block_payer_emits_event() {
+    use soroban_sdk::{Map, Symbol, Val, Vec};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+
+    client.block_payer(&payer, &client.admin_nonce());
+
+    let (contract, topics, data) = last_event(&env);
+    assert_eq!(contract, client.address);
+    let expected: Vec<Val> =
+        soroban_sdk::vec![&env, Symbol::new(&env, "payer_blocked").into_val(&env)];
+    assert_eq!(topics, expected);
+    let data: Map<Symbol, Address> = data.into_val(&env);
+    assert_eq!(data, soroban_sdk::map![&env, (Symbol::new(&env, "payer"), payer)]);
+}
+
+// Refunds
+
+#[test]
+fn test_partial_refunds_track_net_amount() {
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-r1", &payer, 10_000_000);
+    let id = String::from_str(&env, "invoisio-r1");
+
+    assert_eq!(client.net_amount(&id), 10_000_000);
+    client.record_refund(&id, &3_000_000);
+    client.record_refund(&id, &2_000_000);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "payment_refunded").into_val(&env)]
+    );
+    assert_eq!(client.refunded_total(&id), 5_000_000);
+    assert_eq!(client.net_amount(&id), 5_000_000);
+
+    assert_eq!(
+        client.try_record_refund(&id, &5_000_001),
+        Err(Ok(ContractError::RefundExceedsPayment))
+    );
+    client.record_refund(&id, &5_000_000);
+    assert_eq!(client.net_amount(&id), 0);
+}
+
+#[test]
+fn test_refund_needs_a_recorded_payment_and_positive_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let id = String::from_str(&env, "invoisio-r1");
+
+    assert_eq!(
+        client.try_record_refund(&id, &1),
+        Err(Ok(ContractError::PaymentNotFound))
+    );
+    record_xlm(&env, &client, "invoisio-r1", &Address::generate(&env), 10_000_000);
+    assert_eq!(
+        client.try_record_refund(&id, &0),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(client.refunded_total(&id), 0);
+}
+
+#[test]
+fn test_redirected_refund_needs_admin_and_payer() {
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let new_wallet = Address::generate(&env);
+    record_xlm(&env, &client, "invoisio-r1", &payer, 10_000_000);
+    let id = String::from_str(&env, "invoisio-r1");
+
+    // The admin alone cannot redirect a refund.
+    let args = (id.clone(), 4_000_000i128, new_wallet.clone()).into_val(&env);
+    mock_auths(&env, &[AuthCall::new(&admin, &client.address, "record_redirected_refund", args)]);
+    assert!(client.try_record_redirected_refund(&id, &4_000_000, &new_wallet).is_err());
+
+    env.mock_all_auths();
+    client.record_redirected_refund(&id, &4_000_000, &new_wallet);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "redirected_refund").into_val(&env)]
+    );
+    assert_eq!(client.net_amount(&id), 6_000_000);
+}
+
+// Acknowledgement
+
+#[test]
+fn test_acknowledge_invoice_keeps_first_read_receipt() {
+    use soroban_sdk::testutils::Ledger as _;
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-ack");
+    assert_eq!(client.acknowledgement(&id), None);
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    client.acknowledge_invoice(&id, &payer);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "invoice_acknowledged").into_val(&env)]
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    client.acknowledge_invoice(&id, &payer);
+    assert_eq!(
+        client.acknowledgement(&id),
+        Some(Acknowledgement {
+            payer: payer.clone(),
+            timestamp: 100,
+        })
+    );
+
+    record_xlm(&env, &client, "invoisio-ack", &payer, 10_000_000);
+    assert_eq!(
+        client.try_acknowledge_invoice(&id, &payer),
+        Err(Ok(ContractError::PaymentAlreadyRecorded))
+    );
+}
+
+#[test]
+fn test_acknowledgement_is_bound_to_the_invoice_payer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let xlm = String::from_str(&env, "XLM");
+    let none = String::from_str(&env, "");
+
+    let id = String::from_str(&env, "invoisio-ack-1");
+    client.preauthorize(&id, &payer, &xlm, &none, &10_000_000);
+    assert_eq!(
+        client.try_acknowledge_invoice(&id, &stranger),
+        Err(Ok(ContractError::PayerMismatch))
+    );
+    assert_eq!(
+        client.try_accept_terms(&id, &stranger, &terms_hash(&env)),
+        Err(Ok(ContractError::PayerMismatch))
+    );
+    client.acknowledge_invoice(&id, &payer);
+    assert_eq!(client.acknowledgement(&id).unwrap().payer, payer);
+
+    // An acknowledgement made before the invoice named its payer gives way
+    // to the payer's own.
+    let id = String::from_str(&env, "invoisio-ack-2");
+    client.acknowledge_invoice(&id, &stranger);
+    client.accept_terms(&id, &payer, &terms_hash(&env));
+    client.acknowledge_invoice(&id, &payer);
+    assert_eq!(client.acknowledgement(&id).unwrap().payer, payer);
+    assert_eq!(client.terms_acceptance(&id).unwrap().payer, payer);
+}
+
+// Terms acceptance
+
+fn terms_hash(env: &Env) -> soroban_sdk::BytesN<32> {
+    soroban_sdk::BytesN::from_array(env, &[7; 32])
+}
+
+#[test]
+fn test_required_terms_must_be_accepted_by_the_payer() {
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let other = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-t1");
+
+    assert_eq!(try_record(&env, &client, "invoisio-t0", &payer), Ok(()));
+    client.set_terms_required(&true, &client.admin_nonce());
+    assert!(client.terms_required());
+    assert_eq!(
+        try_record(&env, &client, "invoisio-t1", &payer),
+        Err(ContractError::TermsNotAccepted)
+    );
+
+    client.accept_terms(&id, &other, &terms_hash(&env));
+    assert_eq!(
+        try_record(&env, &client, "invoisio-t1", &payer),
+        Err(ContractError::TermsNotAccepted)
+    );
+
+    let id = String::from_str(&env, "invoisio-t2");
+    client.accept_terms(&id, &payer, &terms_hash(&env));
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "terms_accepted").into_val(&env)]
+    );
+    assert_eq!(client.terms_acceptance(&id).unwrap().payer, payer);
+    assert_eq!(try_record(&env, &client, "invoisio-t2", &payer), Ok(()));
+}
+
+#[test]
+fn test_accepted_terms_cannot_be_taken_over_by_another_payer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-t1");
+    let revised = soroban_sdk::BytesN::from_array(&env, &[8; 32]);
+    client.set_terms_required(&true, &client.admin_nonce());
+    client.accept_terms(&id, &payer, &terms_hash(&env));
+
+    assert_eq!(
+        client.try_accept_terms(&id, &stranger, &revised),
+        Err(Ok(ContractError::PayerMismatch))
+    );
+    assert_eq!(client.terms_acceptance(&id).unwrap().payer, payer);
+    assert_eq!(
+        try_record(&env, &client, "invoisio-t1", &stranger),
+        Err(ContractError::TermsNotAccepted)
+    );
+
+    // The payer may still accept revised terms.
+    client.accept_terms(&id, &payer, &revised);
+    assert_eq!(client.terms_acceptance(&id).unwrap().terms_hash, revised);
+    assert_eq!(try_record(&env, &client, "invoisio-t1", &payer), Ok(()));
+}
+
+#[test]
+fn test_accept_terms_needs_payer_auth_and_an_unpaid_invoice() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-t1");
+    let hash = terms_hash(&env);
+
+    // The admin cannot accept on the payer's behalf.
+    let args = (id.clone(), payer.clone(), hash.clone()).into_val(&env);
+    mock_auths(&env, &[AuthCall::new(&admin, &client.address, "accept_terms", args)]);
+    assert!(client.try_accept_terms(&id, &payer, &hash).is_err());
+
+    env.mock_all_auths();
+    record_xlm(&env, &client, "invoisio-t1", &payer, 10_000_000);
+    assert_eq!(
+        client.try_accept_terms(&id, &payer, &hash),
+        Err(Ok(ContractError::PaymentAlreadyRecorded))
+    );
+}
+
+// Preauthorization
+
+#[test]
+fn test_preauthorized_invoice_only_accepts_matching_payment() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let other = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-pa1");
+    let xlm = String::from_str(&env, "XLM");
+    let none = String::from_str(&env, "");
+
+    client.preauthorize(&id, &payer, &xlm, &none, &50_000_000);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "invoice_preauthorized").into_val(&env)]
+    );
+    assert_eq!(client.preauthorization(&id).unwrap().max_amount, 50_000_000);
+
+    let record = |payer: &Address, amount: i128| {
+        client.try_record_payment(&id, payer, &xlm, &none, &amount)
+    };
+    assert_eq!(record(&other, 1), Err(Ok(ContractError::NotPreauthorized)));
+    assert_eq!(record(&payer, 50_000_001), Err(Ok(ContractError::NotPreauthorized)));
+    let usdc = String::from_str(&env, crate::testutils::USDC);
+    let issuer = String::from_str(&env, crate::testutils::USDC_TESTNET_ISSUER);
+    assert_eq!(
+        client.try_record_payment(&id, &payer, &usdc, &issuer, &1),
+        Err(Ok(ContractError::NotPreauthorized))
+    );
+    assert!(record(&payer, 50_000_000).is_ok());
+
+    assert_eq!(
+        client.try_preauthorize(&id, &payer, &xlm, &none, &1),
+        Err(Ok(ContractError::PaymentAlreadyRecorded))
+    );
+}
+
+#[test]
+fn test_preauth_threshold_requires_preauthorization_for_large_payments() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let xlm = String::from_str(&env, "XLM");
+    let none = String::from_str(&env, "");
+    client.set_preauth_threshold(&xlm, &none, &Some(100_000_000), &client.admin_nonce());
+    assert_eq!(client.preauth_threshold(&xlm, &none), Some(100_000_000));
+
+    record_xlm(&env, &client, "invoisio-pa2", &payer, 99_999_999);
+    let id = String::from_str(&env, "invoisio-pa3");
+    assert_eq!(
+        client.try_record_payment(&id, &payer, &xlm, &none, &100_000_000),
+        Err(Ok(ContractError::NotPreauthorized))
+    );
+    client.preauthorize(&id, &payer, &xlm, &none, &100_000_000);
+    client.record_payment(&id, &payer, &xlm, &none, &100_000_000);
+
+    assert_eq!(
+        client.try_set_preauth_threshold(&xlm, &none, &Some(0), &client.admin_nonce()),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+}
+
+#[test]
+fn test_preauthorize_needs_payer_auth() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-pa4");
+    let xlm = String::from_str(&env, "XLM");
+    let none = String::from_str(&env, "");
+
+    let args = (id.clone(), payer.clone(), xlm.clone(), none.clone(), 1i128).into_val(&env);
+    mock_auths(&env, &[AuthCall::new(&admin, &client.address, "preauthorize", args)]);
+    assert!(client.try_preauthorize(&id, &payer, &xlm, &none, &1).is_err());
+}
+
+#[test]
+fn test_preauthorization_cannot_be_replaced_by_a_stranger() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-pa5");
+    let xlm = String::from_str(&env, "XLM");
+    let none = String::from_str(&env, "");
+    client.preauthorize(&id, &payer, &xlm, &none, &10_000_000);
+
+    assert_eq!(
+        client.try_preauthorize(&id, &stranger, &xlm, &none, &1),
+        Err(Ok(ContractError::PayerMismatch))
+    );
+    assert_eq!(client.preauthorization(&id).unwrap().payer, payer);
+
+    // The payer may update their own preauthorization.
+    client.preauthorize(&id, &payer, &xlm, &none, &20_000_000);
+    assert_eq!(client.preauthorization(&id).unwrap().max_amount, 20_000_000);
+    client.record_payment(&id, &payer, &xlm, &none, &20_000_000);
+
+    // Accepted terms name the payer too.
+    let id = String::from_str(&env, "invoisio-pa6");
+    client.accept_terms(&id, &payer, &terms_hash(&env));
+    assert_eq!(
+        client.try_preauthorize(&id, &stranger, &xlm, &none, &1),
+        Err(Ok(ContractError::PayerMismatch))
+    );
+}
+
+// Quotes
+
+fn issue_quote(
+    env: &Env,
+    client: &InvoicePaymentContractClient,
+    quote_id: &str,
+    payer: &Address,
+    expires_at: u64,
+) {
+    client.issue_quote(
+        &String::from_str(env, quote_id),
+        &String::from_str(env, "invoisio-q1"),
+        payer,
+        &String::from_str(env, "XLM"),
+        &String::from_str(env, ""),
+        &10_000_000,
+        &terms_hash(env),
+        &expires_at,
+    );
+}
+
+#[test]
+fn test_accepted_quote_converts_into_invoice_terms() {
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let quote_id = String::from_str(&env, "quote-1");
+    client.set_terms_required(&true, &client.admin_nonce());
+
+    issue_quote(&env, &client, "quote-1", &payer, 1_000);
+    assert!(!client.get_quote(&quote_id).accepted);
+
+    client.accept_quote(&quote_id);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "quote_accepted").into_val(&env)]
+    );
+    assert!(client.get_quote(&quote_id).accepted);
+    let acceptance = client.terms_acceptance(&String::from_str(&env, "invoisio-q1")).unwrap();
+    assert_eq!(acceptance.payer, payer);
+    assert_eq!(acceptance.terms_hash, terms_hash(&env));
+    assert_eq!(try_record(&env, &client, "invoisio-q1", &payer), Ok(()));
+
+    assert_eq!(
+        client.try_accept_quote(&quote_id),
+        Err(Ok(ContractError::QuoteAlreadyAccepted))
+    );
+}
+
+#[test]
+fn test_quote_cannot_be_accepted_after_expiry() {
+    use soroban_sdk::testutils::Ledger as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 500);
+
+    assert_eq!(
+        client.try_accept_quote(&String::from_str(&env, "quote-1")),
+        Err(Ok(ContractError::QuoteNotFound))
+    );
+    issue_quote(&env, &client, "quote-1", &payer, 1_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    assert_eq!(
+        client.try_accept_quote(&String::from_str(&env, "quote-1")),
+        Err(Ok(ContractError::QuoteExpired))
+    );
+}
+
+// Deposit invoices
+
+#[test]
+fn test_deposit_invoice_tracks_deposit_then_balance() {
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-d1");
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+
+    // 30% deposit on 100 XLM.
+    client.issue_deposit_invoice(&id, &xlm, &none, &1_000_000_000, &3_000, &None);
+    let invoice = client.get_deposit_invoice(&id);
+    assert_eq!(invoice.deposit, 300_000_000);
+    assert_eq!(invoice.status, DepositStatus::DepositDue);
+
+    let pay = |payment_id: &str, amount: i128| {
+        let payment_id = String::from_str(&env, payment_id);
+        client.record_invoice_payment(&id, &payment_id, &payer, &xlm, &none, &amount);
+    };
+    pay("invoisio-d1-1", 100_000_000);
+    assert_eq!(client.get_deposit_invoice(&id).status, DepositStatus::DepositDue);
+
+    pay("invoisio-d1-2", 200_000_000);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "deposit_paid").into_val(&env)]
+    );
+    assert_eq!(client.get_deposit_invoice(&id).status, DepositStatus::BalanceDue);
+
+    pay("invoisio-d1-3", 700_000_000);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "balance_paid").into_val(&env)]
+    );
+    let invoice = client.get_deposit_invoice(&id);
+    assert_eq!(invoice.status, DepositStatus::Paid);
+    assert_eq!(invoice.paid, 1_000_000_000);
+
+    // Each payment is an ordinary record under its own id.
+    let record = client.get_payment(&String::from_str(&env, "invoisio-d1-2"));
+    assert_eq!(record.amount, 200_000_000);
+    assert_eq!(client.payment_count(), 3);
+}
+
+#[test]
+fn test_deposit_invoice_rejects_bad_payments() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-d1");
+    let payment_id = String::from_str(&env, "invoisio-d1-1");
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+    let usdc = String::from_str(&env, "USDC");
+    let issuer = String::from_str(&env, crate::testutils::USDC_TESTNET_ISSUER);
+
+    assert_eq!(
+        client.try_record_invoice_payment(&id, &payment_id, &payer, &xlm, &none, &1),
+        Err(Ok(ContractError::InvoiceScheduleNotFound))
+    );
+    assert_eq!(
+        client.try_issue_deposit_invoice(&id, &xlm, &none, &100, &10_001, &None),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    client.issue_deposit_invoice(&id, &xlm, &none, &100, &5_000, &None);
+    assert_eq!(
+        client.try_issue_deposit_invoice(&id, &xlm, &none, &100, &5_000, &None),
+        Err(Ok(ContractError::InvoiceScheduleExists))
+    );
+
+    assert_eq!(
+        client.try_record_invoice_payment(&id, &payment_id, &payer, &usdc, &issuer, &50),
+        Err(Ok(ContractError::InvalidAsset))
+    );
+    assert_eq!(
+        client.try_record_invoice_payment(&id, &payment_id, &payer, &xlm, &none, &101),
+        Err(Ok(ContractError::InvoiceOverpaid))
+    );
+    assert_eq!(client.get_deposit_invoice(&id).paid, 0);
+    assert!(!client.has_payment(&payment_id));
+}
+
+#[test]
+fn test_anyone_can_expire_a_deposit_invoice_past_its_deadline() {
+    use soroban_sdk::testutils::Ledger as _;
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-d1");
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+    client.issue_deposit_invoice(&id, &xlm, &none, &1_000, &5_000, &Some(1_000));
+    let payment_id = String::from_str(&env, "invoisio-d1-1");
+    client.record_invoice_payment(&id, &payment_id, &payer, &xlm, &none, &100);
+
+    // No signatures needed, but only after the deadline.
+    env.mock_auths(&[]);
+    assert_eq!(
+        client.try_expire_invoice(&id, &None),
+        Err(Ok(ContractError::InvoiceNotExpirable))
+    );
+    env.ledger().with_mut(|li| li.timestamp = 1_001);
+    client.expire_invoice(&id, &None);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "invoice_expired").into_val(&env)]
+    );
+    assert_eq!(client.get_deposit_invoice(&id).status, DepositStatus::Expired);
+    assert_eq!(
+        client.try_expire_invoice(&id, &None),
+        Err(Ok(ContractError::InvoiceNotExpirable))
+    );
+
+    env.mock_all_auths();
+    let late_id = String::from_str(&env, "invoisio-d1-2");
+    assert_eq!(
+        client.try_record_invoice_payment(&id, &late_id, &payer, &xlm, &none, &400),
+        Err(Ok(ContractError::InvoiceExpired))
+    );
+}
+
+#[test]
+fn test_installment_plan_fills_installments_in_order() {
+    use soroban_sdk::testutils::Ledger as _;
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-i1");
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+
+    let schedule = soroban_sdk::vec![&env, (100i128, 2_000u64), (100, 3_000), (100, 4_000)];
+    client.issue_installment_plan(&id, &xlm, &none, &schedule);
+
+    let pay = |payment_id: &str, amount: i128| {
+        let payment_id = String::from_str(&env, payment_id);
+        client.record_invoice_payment(&id, &payment_id, &payer, &xlm, &none, &amount);
+    };
+    // Covers the first installment and half of the second.
+    pay("invoisio-i1-1", 150);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "installment_paid").into_val(&env)]
+    );
+    let plan = client.get_installment_plan(&id);
+    assert_eq!(plan.installments.get(1).unwrap().paid, 50);
+    assert_eq!(plan.balance_due(), 150);
+
+    // The second installment falls overdue.
+    env.ledger().with_mut(|li| li.timestamp = 3_001);
+    assert_eq!(
+        client.installment_statuses(&id),
+        soroban_sdk::vec![
+            &env,
+            InstallmentStatus::Paid,
+            InstallmentStatus::Overdue,
+            InstallmentStatus::Open
+        ]
+    );
+
+    pay("invoisio-i1-2", 150);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "balance_paid").into_val(&env)]
+    );
+    let statuses = client.installment_statuses(&id);
+    assert!(statuses.iter().all(|s| s == InstallmentStatus::Paid));
+}
+
+#[test]
+fn test_installment_plan_rejects_bad_schedules() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let id = String::from_str(&env, "invoisio-i1");
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+
+    let empty: soroban_sdk::Vec<(i128, u64)> = soroban_sdk::vec![&env];
+    assert_eq!(
+        client.try_issue_installment_plan(&id, &xlm, &none, &empty),
+        Err(Ok(ContractError::InvalidSchedule))
+    );
+    let unordered = soroban_sdk::vec![&env, (100i128, 3_000u64), (100, 2_000)];
+    assert_eq!(
+        client.try_issue_installment_plan(&id, &xlm, &none, &unordered),
+        Err(Ok(ContractError::InvalidSchedule))
+    );
+    let zero = soroban_sdk::vec![&env, (100i128, 2_000u64), (0, 3_000)];
+    assert_eq!(
+        client.try_issue_installment_plan(&id, &xlm, &none, &zero),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+
+    // One schedule per invoice, of either kind.
+    client.issue_deposit_invoice(&id, &xlm, &none, &200, &5_000, &None);
+    let schedule = soroban_sdk::vec![&env, (100i128, 2_000u64)];
+    assert_eq!(
+        client.try_issue_installment_plan(&id, &xlm, &none, &schedule),
+        Err(Ok(ContractError::InvoiceScheduleExists))
+    );
+}
+
+#[test]
+fn test_amount_due_accrues_interest_on_overdue_installments() {
+    use crate::schedule::SECS_PER_YEAR;
+    use soroban_sdk::testutils::Ledger as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-i1");
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+
+    let schedule = soroban_sdk::vec![&env, (1_000_000i128, 1_000u64), (1_000_000, 2_000)];
+    client.issue_installment_plan(&id, &xlm, &none, &schedule);
+    let payment_id = String::from_str(&env, "invoisio-i1-1");
+    client.record_invoice_payment(&id, &payment_id, &payer, &xlm, &none, &500_000);
+
+    // 10 % a year; nothing accrues before the due date.
+    client.set_interest_rate(&1_000, &client.admin_nonce());
+    let due = client.get_amount_due(&id);
+    assert_eq!((due.principal, due.interest), (1_500_000, 0));
+
+    // One year past the first due date: 10 % of its unpaid 500 000, plus a
+    // little on the second installment, 1 000 s later.
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + SECS_PER_YEAR);
+    let due = client.get_amount_due(&id);
+    assert_eq!(due.principal, 1_500_000);
+    assert_eq!(due.interest, 50_000 + 99_996);
+
+    client.set_interest_rate(&0, &client.admin_nonce());
+    assert_eq!(client.get_amount_due(&id).interest, 0);
+    assert_eq!(
+        client.try_set_interest_rate(&10_001, &client.admin_nonce()),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+}
+
+// Credit notes
+
+#[test]
+fn test_credit_note_is_spread_across_a_payers_invoices() {
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+    let (d1, d2) = (String::from_str(&env, "invoisio-c1"), String::from_str(&env, "invoisio-c2"));
+    client.issue_deposit_invoice(&d1, &xlm, &none, &300, &5_000, &None);
+    client.issue_deposit_invoice(&d2, &xlm, &none, &1_000, &5_000, &None);
+    let credit_id = String::from_str(&env, "invoisio-cn-1");
+    client.issue_credit_note(&credit_id, &payer, &xlm, &none, &500);
+
+    client.apply_credit_note(&credit_id, &d1, &300);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![&env, Symbol::new(&env, "balance_paid").into_val(&env)]
+    );
+    assert_eq!(client.get_deposit_invoice(&d1).status, DepositStatus::Paid);
+    assert_eq!(client.get_credit_note(&credit_id).remaining, 200);
+
+    client.apply_credit_note(&credit_id, &d2, &200);
+    assert_eq!(client.get_amount_due(&d2).principal, 800);
+    let note = client.get_credit_note(&credit_id);
+    assert_eq!(note.status, CreditNoteStatus::Applied);
+    assert_eq!(note.applied.get(d1.clone()), Some(300));
+    assert_eq!(note.applied.get(d2.clone()), Some(200));
+    assert_eq!(
+        client.try_apply_credit_note(&credit_id, &d2, &1),
+        Err(Ok(ContractError::CreditNoteClosed))
+    );
+
+    // No funds moved, so nothing was recorded as a payment.
+    assert_eq!(client.payment_count(), 0);
+}
+
+#[test]
+fn test_credit_note_rejects_other_payers_and_lapses_when_voided() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let (payer, other) = (Address::generate(&env), Address::generate(&env));
+    let (xlm, none) = (String::from_str(&env, "XLM"), String::from_str(&env, ""));
+    let invoice_id = String::from_str(&env, "invoisio-c3");
+    client.issue_deposit_invoice(&invoice_id, &xlm, &none, &1_000, &5_000, &None);
+    let credit_id = String::from_str(&env, "invoisio-cn-2");
+    client.issue_credit_note(&credit_id, &payer, &xlm, &none, &100);
+    assert_eq!(
+        client.try_issue_credit_note(&credit_id, &payer, &xlm, &none, &100),
+        Err(Ok(ContractError::CreditNoteExists))
+    );
+
+    assert_eq!(
+        client.try_apply_credit_note(&credit_id, &invoice_id, &101),
+        Err(Ok(ContractError::CreditExceedsRemaining))
+    );
+    // An acknowledgement does not name the payer; accepted terms do.
+    client.acknowledge_invoice(&invoice_id, &other);
+    client.apply_credit_note(&credit_id, &invoice_id, &10);
+    client.accept_terms(&invoice_id, &other, &terms_hash(&env));
+    assert_eq!(
+        client.try_apply_credit_note(&credit_id, &invoice_id, &50),
+        Err(Ok(ContractError::PayerMismatch))
+    );
+
+    client.void_credit_note(&credit_id);
+    let note = client.get_credit_note(&credit_id);
+    assert_eq!((note.status, note.remaining), (CreditNoteStatus::Voided, 0));
+    assert_eq!(
+        client.try_void_credit_note(&credit_id),
+        Err(Ok(ContractError::CreditNoteClosed))
+    );
+    assert_eq!(
+        client.try_get_credit_note(&String::from_str(&env, "invoisio-cn-x")),
+        Err(Ok(ContractError::CreditNoteNotFound))
+    );
+}
+
+// Volume limits
+
+#[test]
+fn test_volume_limit_caps_rolling_24h() {
+    use soroban_sdk::testutils::Ledger as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+    let (client, _admin) = setup(&env);
+    let xlm = String::from_str(&env, "XLM");
+    let none = String::from_str(&env, "");
+    let payer = Address::generate(&env);
+
+    client.set_volume_limit(&xlm, &none, &Some(100), &client.admin_nonce());
+    assert_eq!(client.volume_limit(&xlm, &none), Some(100));
+
+    record_xlm(&env, &client, "invoisio-v1", &payer, 60);
+    env.ledger().with_mut(|li| li.timestamp += 12 * 3_600);
+    record_xlm(&env, &client, "invoisio-v2", &payer, 40);
+    assert_eq!(client.payer_volume(&payer, &xlm, &none), 100);
+
+    let over = client.try_record_payment(
+        &String::from_str(&env, "invoisio-v3"),
+        &payer,
+        &xlm,
+        &none,
+        &1,
+    );
+    assert_eq!(over, Err(Ok(ContractError::LimitExceeded)));
+
+    // Other payers have their own window.
+    record_xlm(&env, &client, "invoisio-v4", &Address::generate(&env), 100);
+
+    // Once the first payment leaves the window, its share frees up.
+    env.ledger().with_mut(|li| li.timestamp += 12 * 3_600);
+    assert_eq!(client.payer_volume(&payer, &xlm, &none), 40);
+    record_xlm(&env, &client, "invoisio-v3", &payer, 60);
+}
+
+#[test]
+fn test_volume_limit_is_per_asset_and_removable() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let xlm = String::from_str(&env, "XLM");
+    let none = String::from_str(&env, "");
+    let payer = Address::generate(&env);
+
+    client.set_volume_limit(&xlm, &none, &Some(10), &client.admin_nonce());
+    crate::testutils::record_token(
+        &env,
+        &client,
+        "invoisio-v1",
+        &payer,
+        crate::testutils::USDC,
+        crate::testutils::USDC_TESTNET_ISSUER,
+        1_000,
+    );
+
+    client.set_volume_limit(&xlm, &none, &None, &client.admin_nonce());
+    assert_eq!(client.volume_limit(&xlm, &none), None);
+    record_xlm(&env, &client, "invoisio-v2", &payer, 1_000);
+    assert_eq!(client.payment_count(), 2);
+}
+
+#[test]
+fn test_volume_limit_counts_confirmations() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    client.set_volume_limit(
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &Some(10),
+        &client.admin_nonce(),
+    );
+
+    // Reporting does not count; confirming does.
+    report_xlm(&env, &client, "invoisio-v1", &payer, 8);
+    report_xlm(&env, &client, "invoisio-v2", &payer, 8);
+    client.confirm_pending_payment(&String::from_str(&env, "invoisio-v1"));
+    assert_eq!(
+        client.try_confirm_pending_payment(&String::from_str(&env, "invoisio-v2")),
+        Err(Ok(ContractError::LimitExceeded))
+    );
+}
+
+#[test]
+fn test_set_volume_limit_rejects_non_positive() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    assert_eq!(
+        client.try_set_volume_limit(
+            &String::from_str(&env, "XLM"),
+            &String::from_str(&env, ""),
+            &Some(0),
+            &client.admin_nonce()
+        ),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+}
+
+// Circuit breaker
+
+fn breaker(window_ledgers: u32, max_count: u32, max_volume: i128) -> Option<CircuitBreaker> {
+    Some(CircuitBreaker {
+        window_ledgers,
+        max_count,
+        max_volume,
+    })
+}
+
+#[test]
+fn test_circuit_breaker_trips_on_count() {
+    use soroban_sdk::{Symbol, Val, Vec};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    client.set_circuit_breaker(&breaker(100, 2, 1_000_000), &client.admin_nonce());
+
+    record_xlm(&env, &client, "invoisio-b1", &payer, 1);
+    record_xlm(&env, &client, "invoisio-b2", &payer, 1);
+    assert!(!client.is_paused());
+
+    // The third payment crosses the threshold: it lands, then writes pause.
+    record_xlm(&env, &client, "invoisio-b3", &payer, 1);
+    let (_, topics, _) = last_event(&env);
+    let expected: Vec<Val> =
+        soroban_sdk::vec![&env, Symbol::new(&env, "circuit_tripped").into_val(&env)];
+    assert_eq!(topics, expected);
+    assert!(client.is_paused());
+    assert_eq!(client.payment_count(), 3);
+    assert_eq!(
+        try_record(&env, &client, "invoisio-b4", &payer),
+        Err(ContractError::Paused)
+    );
+
+    client.resume(&client.admin_nonce());
+    assert!(!client.is_paused());
+    assert_eq!(client.breaker_window().count, 0);
+    record_xlm(&env, &client, "invoisio-b4", &payer, 1);
+}
+
+#[test]
+fn test_circuit_breaker_trips_on_volume() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    client.set_circuit_breaker(&breaker(100, 100, 1_000), &client.admin_nonce());
+
+    record_xlm(&env, &client, "invoisio-b1", &payer, 600);
+    assert!(!client.is_paused());
+    record_xlm(&env, &client, "invoisio-b2", &payer, 600);
+    assert!(client.is_paused());
+
+    // Reporting and confirming are blocked too.
+    assert_eq!(
+        client.try_report_pending_payment(
+            &String::from_str(&env, "invoisio-b3"),
+            &payer,
+            &String::from_str(&env, "XLM"),
+            &String::from_str(&env, ""),
+            &1,
+        ),
+        Err(Ok(ContractError::Paused))
+    );
+}
+
+#[test]
+fn test_circuit_breaker_window_rolls_over() {
+    use soroban_sdk::testutils::Ledger as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    client.set_circuit_breaker(&breaker(10, 1, 1_000_000), &client.admin_nonce());
+
+    record_xlm(&env, &client, "invoisio-b1", &payer, 1);
+    env.ledger().with_mut(|li| li.sequence_number += 10);
+    record_xlm(&env, &client, "invoisio-b2", &payer, 1);
+    assert!(!client.is_paused());
+    assert_eq!(client.breaker_window().count, 1);
+
+    client.set_circuit_breaker(&None, &client.admin_nonce());
+    record_xlm(&env, &client, "invoisio-b3", &payer, 1);
+    record_xlm(&env, &client, "invoisio-b4", &payer, 1);
+    assert!(!client.is_paused());
+}
+
+#[test]
+fn test_manual_pause_blocks_writes() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+
+    client.pause(&client.admin_nonce());
+    assert_eq!(
+        try_record(&env, &client, "invoisio-b1", &payer),
+        Err(ContractError::Paused)
+    );
+    client.resume(&client.admin_nonce());
+    record_xlm(&env, &client, "invoisio-b1", &payer, 1);
+}
+
+#[test]
+fn test_set_circuit_breaker_rejects_empty_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+
+    assert_eq!(
+        client.try_set_circuit_breaker(&breaker(0, 1, 1), &client.admin_nonce()),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_set_circuit_breaker(&breaker(10, 1, 0), &client.admin_nonce()),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+}
+
+// FX rates
+
+mod mock_oracle {
+    use crate::oracle::{OracleAsset, PriceData};
+    use soroban_sdk::{contract, contractimpl, symbol_short, Env};
+
+    /// Reflector-style oracle quoting every ticker at the price stored under
+    /// `"price"`, timestamped `"age"` seconds before the current ledger.
+    #[contract]
+    pub struct MockOracle;
+
+    #[contractimpl]
+    impl MockOracle {
+        pub fn set(env: Env, price: i128, age: u64) {
+            env.storage().instance().set(&symbol_short!("price"), &price);
+            env.storage().instance().set(&symbol_short!("age"), &age);
+        }
+
+        pub fn lastprice(env: Env, asset: OracleAsset) -> Option<PriceData> {
+            if asset == OracleAsset::Other(soroban_sdk::Symbol::new(&env, "NOPE")) {
+                return None;
+            }
+            let price: i128 = env.storage().instance().get(&symbol_short!("price"))?;
+            let age: u64 = env
+                .storage()
+                .instance()
+                .get(&symbol_short!("age"))
+                .unwrap_or(0);
+            Some(PriceData {
+                price,
+                timestamp: env.ledger().timestamp() - age,
+            })
+        }
+
+        pub fn decimals(_env: Env) -> u32 {
+            14
+        }
+    }
+}
+
+fn setup_oracle(env: &Env, client: &InvoicePaymentContractClient, price: i128, age: u64) {
+    let contract_id = env.register(mock_oracle::MockOracle, ());
+    mock_oracle::MockOracleClient::new(env, &contract_id).set(&price, &age);
+    client.set_fx_oracle(&Some(contract_id), &client.admin_nonce());
+}
+
+#[test]
+fn test_record_payment_captures_fx_rate() {
+    use soroban_sdk::testutils::Ledger as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+    let (client, _admin) = setup(&env);
+    // 0.12 USD per XLM, quoted 60 s ago.
+    setup_oracle(&env, &client, 12_000_000_000_000, 60);
+
+    record_xlm(&env, &client, "invoisio-fx", &Address::generate(&env), 10_000_000);
+
+    let rate = client.payment_rate(&String::from_str(&env, "invoisio-fx")).unwrap();
+    assert_eq!(
+        rate,
+        FxRate {
+            price: 12_000_000_000_000,
+            decimals: 14,
+            timestamp: 1_700_000_000 - 60,
+        }
+    );
+}
+
+#[test]
+fn test_fx_rate_is_best_effort() {
+    use soroban_sdk::testutils::Ledger as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+
+    // No oracle configured.
+    record_xlm(&env, &client, "invoisio-fx-1", &payer, 1);
+    // Stale price.
+    setup_oracle(&env, &client, 12_000_000_000_000, oracle::MAX_RATE_AGE_SECS + 1);
+    record_xlm(&env, &client, "invoisio-fx-2", &payer, 1);
+    // No price for the asset.
+    crate::testutils::record_token(
+        &env,
+        &client,
+        "invoisio-fx-3",
+        &payer,
+        "NOPE",
+        crate::testutils::USDC_TESTNET_ISSUER,
+        1,
+    );
+    // Oracle address is not a contract at all.
+    client.set_fx_oracle(&Some(Address::generate(&env)), &client.admin_nonce());
+    record_xlm(&env, &client, "invoisio-fx-4", &payer, 1);
+
+    assert_eq!(client.payment_count(), 4);
+    for id in ["invoisio-fx-1", "invoisio-fx-2", "invoisio-fx-3", "invoisio-fx-4"] {
+        assert_eq!(client.payment_rate(&String::from_str(&env, id)), None, "{id}");
+    }
+}
+
+#[test]
+fn test_confirm_pending_payment_captures_rate_at_confirmation() {
+    use soroban_sdk::testutils::Ledger as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    report_xlm(&env, &client, "invoisio-fx-pending", &payer, 1);
+
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    setup_oracle(&env, &client, 11_000_000_000_000, 0);
+    client.confirm_pending_payment(&String::from_str(&env, "invoisio-fx-pending"));
+
+    let rate = client.payment_rate(&String::from_str(&env, "invoisio-fx-pending")).unwrap();
+    assert_eq!(rate.price, 11_000_000_000_000);
+    assert_eq!(rate.timestamp, 1_700_003_600);
+}
+
+// Observers
+
+mod mock_observer {
+    use crate::storage::PaymentRecord;
+    use soroban_sdk::{contract, contractimpl, symbol_short, Env, String};
+
+    /// Observer remembering the last invoice it was told about; panics on
+    /// invoices whose id is `"invoisio-obs-boom"`.
+    #[contract]
+    pub struct MockObserver;
+
+    #[contractimpl]
+    impl MockObserver {
+        pub fn on_payment_recorded(env: Env, record: PaymentRecord) {
+            env.storage().instance().set(&symbol_short!("last"), &record.invoice_id);
+            if record.invoice_id == String::from_str(&env, "invoisio-obs-boom") {
+                panic!("observer failure");
+            }
+        }
+
+        pub fn last(env: Env) -> Option<String> {
+            env.storage().instance().get(&symbol_short!("last"))
+        }
+    }
+}
+
+#[test]
+fn test_observers_are_notified_with_failure_isolation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let observer = env.register(mock_observer::MockObserver, ());
+    let observer_client = mock_observer::MockObserverClient::new(&env, &observer);
+    client.add_observer(&observer, &client.admin_nonce());
+    client.add_observer(&observer, &client.admin_nonce());
+    assert_eq!(client.observers(), soroban_sdk::vec![&env, observer.clone()]);
+
+    record_xlm(&env, &client, "invoisio-obs-1", &payer, 10_000_000);
+    assert_eq!(observer_client.last(), Some(String::from_str(&env, "invoisio-obs-1")));
+
+    // A failing observer is rolled back and reported; the payment stands.
+    record_xlm(&env, &client, "invoisio-obs-boom", &payer, 10_000_000);
+    let (_, topics, _) = last_event(&env);
+    assert_eq!(
+        topics,
+        soroban_sdk::vec![
+            &env,
+            Symbol::new(&env, "observer_failed").into_val(&env),
+            observer.into_val(&env)
+        ]
+    );
+    assert_eq!(observer_client.last(), Some(String::from_str(&env, "invoisio-obs-1")));
+    assert!(client.try_get_payment(&String::from_str(&env, "invoisio-obs-boom")).is_ok());
+
+    client.remove_observer(&observer, &client.admin_nonce());
+    assert_eq!(client.observers().len(), 0);
+    record_xlm(&env, &client, "invoisio-obs-2", &payer, 10_000_000);
+    assert_eq!(observer_client.last(), Some(String::from_str(&env, "invoisio-obs-1")));
+}
+
+#[test]
+fn test_observer_limit() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    for _ in 0..crate::observer::MAX_OBSERVERS {
+        client.add_observer(&Address::generate(&env), &client.admin_nonce());
+    }
+    assert_eq!(
+        client.try_add_observer(&Address::generate(&env), &client.admin_nonce()),
+        Err(Ok(ContractError::TooManyObservers))
+    );
+}
+
+// Validator
+
+mod mock_validator {
+    use crate::storage::PaymentRecord;
+    use soroban_sdk::{contract, contractimpl, Env};
+
+    /// Validator capping every payment at 10 XLM; panics on 1-stroop
+    /// payments to stand in for a broken validator.
+    #[contract]
+    pub struct MockValidator;
+
+    #[contractimpl]
+    impl MockValidator {
+        pub fn validate(_env: Env, record: PaymentRecord) -> bool {
+            if record.amount == 1 {
+                panic!("validator failure");
+            }
+            record.amount <= 100_000_000
+        }
+    }
+}
+
+#[test]
+fn test_validator_accepts_or_rejects_payments() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let validator = env.register(mock_validator::MockValidator, ());
+    client.set_validator(&Some(validator.clone()), &client.admin_nonce());
+    assert_eq!(client.validator(), Some(validator));
+
+    let xlm = String::from_str(&env, "XLM");
+    let none = String::from_str(&env, "");
+
+    record_xlm(&env, &client, "invoisio-val-1", &payer, 100_000_000);
+    let id = String::from_str(&env, "invoisio-val-2");
+    let result = client.try_record_payment(&id, &payer, &xlm, &none, &100_000_001);
+    assert_eq!(result, Err(Ok(ContractError::PaymentRejected)));
+    assert_eq!(client.payment_count(), 1);
+
+    // A failing validator rejects too.
+    let id = String::from_str(&env, "invoisio-val-3");
+    let result = client.try_record_payment(&id, &payer, &xlm, &none, &1);
+    assert_eq!(result, Err(Ok(ContractError::PaymentRejected)));
+
+    client.set_validator(&None, &client.admin_nonce());
+    record_xlm(&env, &client, "invoisio-val-2", &payer, 100_000_001);
+    assert_eq!(client.payment_count(), 2);
+}
+
+#[test]
+fn test_imports_go_through_the_validator_and_observers() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let validator = env.register(mock_validator::MockValidator, ());
+    let observer = env.register(mock_observer::MockObserver, ());
+    let observer_client = mock_observer::MockObserverClient::new(&env, &observer);
+    client.set_validator(&Some(validator), &client.admin_nonce());
+    client.add_observer(&observer, &client.admin_nonce());
+    let imported = |id: &str, amount: i128| PaymentRecord {
+        invoice_id: String::from_str(&env, id),
+        payer: payer.clone(),
+        payer_mux_id: None,
+        asset: Asset::Native,
+        amount,
+        timestamp: 1_600_000_000,
+    };
+
+    // One rejected record fails the whole batch.
+    let batch = soroban_sdk::vec![&env, imported("old-1", 10), imported("old-2", 100_000_001)];
+    assert_eq!(
+        client.try_import_payments(&batch),
+        Err(Ok(ContractError::PaymentRejected))
+    );
+    assert_eq!(client.payment_count(), 0);
+    assert_eq!(observer_client.last(), None);
+
+    let batch = soroban_sdk::vec![&env, imported("old-1", 10), imported("old-2", 20)];
+    client.import_payments(&batch);
+    assert_eq!(observer_client.last(), Some(String::from_str(&env, "old-2")));
+}
+
+#[test]
+fn test_hashed_payments_skip_the_validator_and_observers() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let validator = env.register(mock_validator::MockValidator, ());
+    let observer = env.register(mock_observer::MockObserver, ());
+    let observer_client = mock_observer::MockObserverClient::new(&env, &observer);
+    client.set_validator(&Some(validator), &client.admin_nonce());
+    client.add_observer(&observer, &client.admin_nonce());
+
+    // Over the validator's cap, yet recorded: there is no `PaymentRecord`
+    // to hand it, or the observer.
+    let hash = invoice_hash(&env, "invoice-1");
+    client.record_hashed_payment(
+        &hash,
+        &Address::generate(&env),
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &100_000_001,
+    );
+    assert!(client.has_hashed_payment(&hash));
+    assert_eq!(observer_client.last(), None);
+}
+
+// Golden event encodings
+//
+// Indexers decode `getEvents` topics and values byte-for-byte, so the XDR of
+// every event is pinned by a file under `golden/`. If a change is intended,
+// replace the file's contents with the `actual` text from the failure.
+
+const GOLDEN_PAYER: &str = "GAP5LETOV6YIE62YAM56STDANPRDO7ZFDBGSNHJQIYGGKSMOZAHOOS2S";
+const GOLDEN_TIMESTAMP: u64 = 1_700_000_000;
+
+/// Fresh contract with a fixed ledger timestamp, plus a fixed payer.
+fn golden_setup(env: &Env) -> (InvoicePaymentContractClient<'_>, Address) {
+    use soroban_sdk::testutils::Ledger as _;
+
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = GOLDEN_TIMESTAMP);
+    let (client, _admin) = setup(env);
+    (client, Address::from_str(env, GOLDEN_PAYER))
+}
+
+/// Compare the last emitted event, rendered as `getEvents` returns it (one
+/// base64 `ScVal` per topic, then the value), against `golden`.
+fn assert_event_golden(env: &Env, golden: &str) {
+    use soroban_sdk::xdr::{Limits, ScVal, WriteXdr};
+    use soroban_sdk::{TryFromVal, Val};
+
+    let encode = |v: &Val| {
+        ScVal::try_from_val(env, v)
+            .unwrap()
+            .to_xdr_base64(Limits::none())
+            .unwrap()
+    };
+    let (_contract, topics, value) = last_event(env);
+    let mut actual = std::string::String::new();
+    for topic in topics.iter() {
+        actual += &std::format!("topic: {}\n", encode(&topic));
+    }
+    actual += &std::format!("value: {}\n", encode(&value));
+
+    assert_eq!(actual, golden, "event XDR changed; actual:\n{actual}");
+}
+
+#[test]
+fn test_golden_payment_recorded_xlm() {
+    let env = Env::default();
+    let (client, payer) = golden_setup(&env);
+
+    record_xlm(&env, &client, "invoisio-golden-xlm", &payer, 10_000_000);
+    assert_event_golden(&env, include_str!("../golden/payment_recorded_xlm.txt"));
+}
+
+#[test]
+fn test_golden_payment_recorded_token() {
+    use crate::testutils::{record_token, USDC, USDC_TESTNET_ISSUER};
+
+    let env = Env::default();
+    let (client, payer) = golden_setup(&env);
+
+    record_token(
+        &env,
+        &client,
+        "invoisio-golden-usdc",
+        &payer,
+        USDC,
+        USDC_TESTNET_ISSUER,
+        50_000_000,
+    );
+    assert_event_golden(&env, include_str!("../golden/payment_recorded_token.txt"));
+}
+
+#[test]
+fn test_golden_pending_payment_reported() {
+    let env = Env::default();
+    let (client, payer) = golden_setup(&env);
+
+    report_xlm(&env, &client, "invoisio-golden-pending", &payer, 10_000_000);
+    assert_event_golden(&env, include_str!("../golden/pending_payment_reported.txt"));
+}
+
+#[test]
+fn test_chargeback_flag_and_resolution() {
+    use soroban_sdk::testutils::Ledger as _;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-cb1");
+    let reference = String::from_str(&env, "CB-2026-0042");
+
+    assert_eq!(
+        client.try_flag_chargeback(&id, &reference),
+        Err(Ok(ContractError::PaymentNotFound))
+    );
+    record_xlm(&env, &client, "invoisio-cb1", &payer, 100);
+    assert_eq!(
+        client.try_flag_chargeback(&id, &String::from_str(&env, "")),
+        Err(Ok(ContractError::InvalidInvoiceId))
+    );
+    assert_eq!(
+        client.try_resolve_chargeback(&id, &true),
+        Err(Ok(ContractError::ChargebackNotFound))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.flag_chargeback(&id, &reference);
+    assert_eq!(
+        client.try_flag_chargeback(&id, &reference),
+        Err(Ok(ContractError::ChargebackExists))
+    );
+    let chargeback = client.chargeback(&id).unwrap();
+    assert_eq!(chargeback.reference, reference);
+    assert_eq!(chargeback.status, ChargebackStatus::Open);
+    assert_eq!(chargeback.flagged_at, 1_000);
+    assert_eq!(chargeback.resolved_at, None);
+
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    client.resolve_chargeback(&id, &false);
+    let chargeback = client.chargeback(&id).unwrap();
+    assert_eq!(chargeback.status, ChargebackStatus::Overturned);
+    assert_eq!(chargeback.resolved_at, Some(2_000));
+    assert_eq!(
+        client.try_resolve_chargeback(&id, &true),
+        Err(Ok(ContractError::ChargebackResolved))
+    );
+    // Informational only: the record and its net amount are untouched.
+    assert_eq!(client.net_amount(&id), 100);
+}
+
+#[test]
+fn test_reconciliation_transitions_are_evented() {
+    use soroban_sdk::testutils::Events as _;
+    use soroban_sdk::{Symbol, Val, Vec};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payer = Address::generate(&env);
+    let id = String::from_str(&env, "invoisio-rc1");
+
+    assert_eq!(
+        client.try_set_reconciliation(&id, &Reconciliation::Matched),
+        Err(Ok(ContractError::PaymentNotFound))
+    );
+    record_xlm(&env, &client, "invoisio-rc1", &payer, 100);
+    assert_eq!(client.reconciliation(&id), Reconciliation::Unverified);
+
+    client.set_reconciliation(&id, &Reconciliation::ManualReview);
+    let (_, topics, _) = last_event(&env);
+    let expected: Vec<Val> = soroban_sdk::vec![
+        &env,
+        Symbol::new(&env, "reconciliation_updated").into_val(&env),
+        id.into_val(&env)
+    ];
+    assert_eq!(topics, expected);
+    assert_eq!(client.reconciliation(&id), Reconciliation::ManualReview);
+
+    client.set_reconciliation(&id, &Reconciliation::Matched);
+    assert_eq!(client.reconciliation(&id), Reconciliation::Matched);
+    // Same status again: nothing to record.
+    client.set_reconciliation(&id, &Reconciliation::Matched);
+    assert!(env.events().all().events().is_empty());
+
+    client.set_reconciliation(&id, &Reconciliation::Unverified);
+    assert_eq!(client.reconciliation(&id), Reconciliation::Unverified);
+}
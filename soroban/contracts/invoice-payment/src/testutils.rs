@@ -0,0 +1,183 @@
+//! Reusable fixtures for tests of this contract and of crates built on it.
+//!
+//! Compiled for this crate's own tests, and for dependents with the
+//! `testutils` feature:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! invoice-payment = { path = "../../contracts/invoice-payment", features = ["testutils"] }
+//! ```
+//!
+//! ```ignore
+//! use invoice_payment::testutils::{record_xlm, setup, ONE_XLM};
+//!
+//! let env = Env::default();
+//! env.mock_all_auths();
+//! let (client, _admin) = setup(&env);
+//! record_xlm(&env, &client, "invoisio-001", &Address::generate(&env), ONE_XLM);
+//! ```
+
+extern crate std;
+
+use soroban_sdk::testutils::{Address as _, Events as _, MockAuth, MockAuthInvoke};
+use soroban_sdk::xdr::{ContractEventBody, ScAddress};
+use soroban_sdk::{Address, Env, IntoVal, String, TryFromVal, Val, Vec};
+
+use crate::{InvoicePaymentContract, InvoicePaymentContractClient};
+
+// Assets
+
+/// Native asset code; takes an empty issuer.
+pub const XLM: &str = "XLM";
+pub const USDC: &str = "USDC";
+/// Circle USDC issuer on Stellar testnet.
+pub const USDC_TESTNET_ISSUER: &str = "GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5";
+/// 1 XLM (or 1 unit of any 7-decimal Stellar asset) in stroops.
+pub const ONE_XLM: i128 = 10_000_000;
+
+// Contract
+
+/// Deploy the contract and call `initialize`, returning the client and admin.
+pub fn setup(env: &Env) -> (InvoicePaymentContractClient<'_>, Address) {
+    let admin = Address::generate(env);
+    let contract_id = env.register(InvoicePaymentContract, ());
+    let client = InvoicePaymentContractClient::new(env, &contract_id);
+    client.initialize(&admin);
+    (client, admin)
+}
+
+/// XLM payment helper: 1 XLM = 10_000_000 stroops.
+pub fn record_xlm(
+    env: &Env,
+    client: &InvoicePaymentContractClient,
+    invoice_id: &str,
+    payer: &Address,
+    stroops: i128,
+) {
+    record_token(env, client, invoice_id, payer, XLM, "", stroops);
+}
+
+/// Record a payment in any asset; `issuer` is `""` for XLM.
+pub fn record_token(
+    env: &Env,
+    client: &InvoicePaymentContractClient,
+    invoice_id: &str,
+    payer: &Address,
+    code: &str,
+    issuer: &str,
+    amount: i128,
+) {
+    client.record_payment(
+        &String::from_str(env, invoice_id),
+        payer,
+        &String::from_str(env, code),
+        &String::from_str(env, issuer),
+        &amount,
+    );
+}
+
+// Auth
+
+/// An owned [`MockAuth`]: `address` authorises `fn_name(args)` on
+/// `contract`. Install a set of them with [`mock_auths`].
+///
+/// `MockAuth` borrows everything it points at, which makes it awkward to
+/// build in a helper and return; this type owns its parts instead.
+#[derive(Clone, Debug)]
+pub struct AuthCall {
+    pub address: Address,
+    pub contract: Address,
+    pub fn_name: &'static str,
+    pub args: Vec<Val>,
+}
+
+impl AuthCall {
+    pub fn new(
+        address: &Address,
+        contract: &Address,
+        fn_name: &'static str,
+        args: Vec<Val>,
+    ) -> Self {
+        Self {
+            address: address.clone(),
+            contract: contract.clone(),
+            fn_name,
+            args,
+        }
+    }
+
+    /// `admin` authorising an XLM `record_payment`.
+    pub fn record_xlm(
+        env: &Env,
+        client: &InvoicePaymentContractClient,
+        admin: &Address,
+        invoice_id: &str,
+        payer: &Address,
+        stroops: i128,
+    ) -> Self {
+        let args = (
+            String::from_str(env, invoice_id),
+            payer.clone(),
+            String::from_str(env, XLM),
+            String::from_str(env, ""),
+            stroops,
+        );
+        Self::new(admin, &client.address, "record_payment", args.into_val(env))
+    }
+
+    /// `signer` authorising `set_admin(new_admin, nonce)`. Both the current
+    /// and the new admin must sign, so a successful transfer needs two of
+    /// these.
+    pub fn set_admin(
+        env: &Env,
+        client: &InvoicePaymentContractClient,
+        signer: &Address,
+        new_admin: &Address,
+        nonce: u64,
+    ) -> Self {
+        let args = (new_admin.clone(), nonce).into_val(env);
+        Self::new(signer, &client.address, "set_admin", args)
+    }
+}
+
+/// Replace the env's mocked auths with exactly `calls`.
+pub fn mock_auths(env: &Env, calls: &[AuthCall]) {
+    let invokes: std::vec::Vec<MockAuthInvoke> = calls
+        .iter()
+        .map(|c| MockAuthInvoke {
+            contract: &c.contract,
+            fn_name: c.fn_name,
+            args: c.args.clone(),
+            sub_invokes: &[],
+        })
+        .collect();
+    let auths: std::vec::Vec<MockAuth> = calls
+        .iter()
+        .zip(&invokes)
+        .map(|(c, invoke)| MockAuth {
+            address: &c.address,
+            invoke,
+        })
+        .collect();
+    env.mock_auths(&auths);
+}
+
+// Events
+
+/// The last event of the most recent invocation, as `(contract, topics,
+/// data)`. Panics if it emitted none.
+pub fn last_event(env: &Env) -> (Address, Vec<Val>, Val) {
+    let events = env.events().all();
+    let event = events.events().last().expect("no event emitted");
+    let ContractEventBody::V0(body) = &event.body;
+    let contract = ScAddress::Contract(event.contract_id.clone().expect("not a contract event"));
+    let mut topics = Vec::new(env);
+    for topic in body.topics.iter() {
+        topics.push_back(Val::try_from_val(env, topic).unwrap());
+    }
+    (
+        Address::try_from_val(env, &contract).unwrap(),
+        topics,
+        Val::try_from_val(env, &body.data).unwrap(),
+    )
+}
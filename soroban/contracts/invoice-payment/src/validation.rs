@@ -0,0 +1,322 @@
+use soroban_sdk::{contractclient, Address, Bytes, Env, String};
+
+use crate::errors::ContractError;
+use crate::storage::{
+    get_accepted_assets, get_compliance_policy, get_payer_flags, get_preauth_threshold,
+    get_preauthorization, get_terms_acceptance, get_validator, is_payer_blocked, is_terms_required,
+    Asset, PaymentRecord, MAX_INVOICE_ID_LEN,
+};
+
+/// Length of an account strkey (`G...`): 35 bytes in base32.
+const ACCOUNT_STRKEY_LEN: usize = 56;
+
+/// Longest classic asset code (`AlphaNum12`).
+const MAX_ASSET_CODE_LEN: usize = 12;
+
+/// Strkey version byte of an ed25519 public key (`G...`).
+const ACCOUNT_VERSION_BYTE: u8 = 6 << 3;
+
+/// Validate the raw `record_payment`-style arguments and build the [`Asset`].
+///
+/// Checks run in a fixed order so callers always see the same error for the
+/// same malformed input:
+/// 1. `invoice_id` non-empty and at most [`MAX_INVOICE_ID_LEN`] bytes →
+///    [`ContractError::InvalidInvoiceId`]
+/// 2. `asset_code` / `asset_issuer` consistent, issuer a valid strkey →
+///    [`ContractError::InvalidAsset`]
+/// 3. `amount` strictly positive → [`ContractError::InvalidAmount`]
+///
+/// Idempotency (has this invoice already been recorded?) is **not** checked
+/// here because it depends on which storage tier the caller writes to.
+pub fn validate_payment(
+    env: &Env,
+    invoice_id: &String,
+    asset_code: &String,
+    asset_issuer: &String,
+    amount: i128,
+) -> Result<Asset, ContractError> {
+    validate_id(invoice_id)?;
+    validate_transfer(env, asset_code, asset_issuer, amount)
+}
+
+/// Check an identifier the contract stores or keys storage by (invoice,
+/// payment, quote, merchant or SEP-31 transaction id): non-empty and at most
+/// [`MAX_INVOICE_ID_LEN`] bytes, else [`ContractError::InvalidInvoiceId`].
+pub fn validate_id(id: &String) -> Result<(), ContractError> {
+    if id.is_empty() || id.len() > MAX_INVOICE_ID_LEN {
+        return Err(ContractError::InvalidInvoiceId);
+    }
+    Ok(())
+}
+
+/// Checks 2 and 3 of [`validate_payment`], for ids that cannot be malformed
+/// (`BytesN<32>` invoice hashes).
+pub fn validate_transfer(
+    env: &Env,
+    asset_code: &String,
+    asset_issuer: &String,
+    amount: i128,
+) -> Result<Asset, ContractError> {
+    let asset = build_asset(env, asset_code, asset_issuer)?;
+
+    if amount <= 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+
+    Ok(asset)
+}
+
+/// Build an [`Asset`] from a code / issuer pair.
+///
+/// - `asset_code` must be 1–12 ASCII alphanumeric characters, as for a
+///   classic Stellar asset (`AlphaNum4` or `AlphaNum12`).
+/// - XLM (native) must have an empty issuer.
+/// - Non-XLM assets (tokens) must have an issuer that is a valid account
+///   strkey (`G...`), so a typo'd issuer never reaches storage.
+pub fn build_asset(
+    env: &Env,
+    asset_code: &String,
+    asset_issuer: &String,
+) -> Result<Asset, ContractError> {
+    if !is_classic_asset_code(asset_code) {
+        return Err(ContractError::InvalidAsset);
+    }
+
+    let is_xlm = *asset_code == String::from_str(env, "XLM");
+    let issuer_empty = asset_issuer.is_empty();
+
+    if is_xlm && !issuer_empty {
+        // XLM with issuer is invalid
+        return Err(ContractError::InvalidAsset);
+    }
+    if !is_xlm && issuer_empty {
+        // Token without issuer is invalid
+        return Err(ContractError::InvalidAsset);
+    }
+
+    if !is_xlm && account_key(asset_issuer).is_none() {
+        return Err(ContractError::InvalidAsset);
+    }
+
+    if is_xlm {
+        Ok(Asset::Native)
+    } else {
+        Ok(Asset::Token(asset_code.clone(), asset_issuer.clone()))
+    }
+}
+
+/// Check `payer` against the blocklist and the compliance policy.
+///
+/// The blocklist always applies. Payer flags are only read when the policy
+/// enforces something, so contracts without a policy pay one read for this
+/// check.
+///
+/// - payer blocked → [`ContractError::PayerBlocked`]
+/// - KYC required and payer not `kyc_verified` → [`ContractError::PayerNotVerified`]
+/// - high risk rejected and payer `high_risk` → [`ContractError::PayerHighRisk`]
+pub fn check_payer(env: &Env, payer: &Address) -> Result<(), ContractError> {
+    if is_payer_blocked(env, payer) {
+        return Err(ContractError::PayerBlocked);
+    }
+
+    let policy = get_compliance_policy(env);
+    if !policy.require_kyc && !policy.reject_high_risk {
+        return Ok(());
+    }
+
+    let flags = get_payer_flags(env, payer);
+    if policy.require_kyc && !flags.kyc_verified {
+        return Err(ContractError::PayerNotVerified);
+    }
+    if policy.reject_high_risk && flags.high_risk {
+        return Err(ContractError::PayerHighRisk);
+    }
+    Ok(())
+}
+
+/// Check that `payer` accepted the terms of `invoice_id`, if the contract
+/// requires it. Free (no extra entry read) while terms are not required.
+///
+/// Returns [`ContractError::TermsNotAccepted`] if there is no acceptance or
+/// it was signed by another payer.
+pub fn check_terms(env: &Env, invoice_id: &String, payer: &Address) -> Result<(), ContractError> {
+    if !is_terms_required(env) {
+        return Ok(());
+    }
+    match get_terms_acceptance(env, invoice_id) {
+        Some(acceptance) if acceptance.payer == *payer => Ok(()),
+        _ => Err(ContractError::TermsNotAccepted),
+    }
+}
+
+/// Check a payment of `amount` in `asset` from `payer` against the payer's
+/// preauthorization of `invoice_id`.
+///
+/// A preauthorization, once given, always applies. Without one, payments
+/// are only accepted below the asset's preauthorization threshold, if set.
+///
+/// Returns [`ContractError::NotPreauthorized`] if the payer, asset or
+/// amount (above `max_amount`) does not match, or if a preauthorization is
+/// needed and missing.
+pub fn check_preauthorization(
+    env: &Env,
+    invoice_id: &String,
+    payer: &Address,
+    asset: &Asset,
+    amount: i128,
+) -> Result<(), ContractError> {
+    let matches = match get_preauthorization(env, invoice_id) {
+        Some(preauth) => {
+            preauth.payer == *payer && preauth.asset == *asset && amount <= preauth.max_amount
+        }
+        None => !matches!(get_preauth_threshold(env, asset), Some(min) if amount >= min),
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(ContractError::NotPreauthorized)
+    }
+}
+
+/// Check a payment of `amount` in `asset` against the assets `invoice_id`
+/// accepts, if it lists any.
+///
+/// Returns [`ContractError::InvalidAsset`] for an asset not on the list and
+/// [`ContractError::AmountMismatch`] if `amount` is not the amount expected
+/// in it.
+pub fn check_accepted_asset(
+    env: &Env,
+    invoice_id: &String,
+    asset: &Asset,
+    amount: i128,
+) -> Result<(), ContractError> {
+    let Some(accepted) = get_accepted_assets(env, invoice_id) else {
+        return Ok(());
+    };
+    match accepted.get(asset.clone()) {
+        None => Err(ContractError::InvalidAsset),
+        Some(expected) if expected != amount => Err(ContractError::AmountMismatch),
+        Some(_) => Ok(()),
+    }
+}
+
+/// What a validator contract implements to accept or reject payments.
+#[contractclient(name = "PaymentValidatorClient")]
+pub trait PaymentValidator {
+    fn validate(env: Env, record: PaymentRecord) -> bool;
+}
+
+/// Ask the registered validator contract, if any, whether to accept
+/// `record`. Free (no call) while no validator is set.
+///
+/// Fails closed: a validator that returns `false`, fails, or is not a
+/// contract yields [`ContractError::PaymentRejected`].
+pub fn check_validator(env: &Env, record: &PaymentRecord) -> Result<(), ContractError> {
+    let Some(validator) = get_validator(env) else {
+        return Ok(());
+    };
+    match PaymentValidatorClient::new(env, &validator).try_validate(record) {
+        Ok(Ok(true)) => Ok(()),
+        _ => Err(ContractError::PaymentRejected),
+    }
+}
+
+/// Whether `code` is 1–4 (`AlphaNum4`) or 5–12 (`AlphaNum12`) ASCII
+/// letters and digits.
+fn is_classic_asset_code(code: &String) -> bool {
+    let len = code.len() as usize;
+    if len == 0 || len > MAX_ASSET_CODE_LEN {
+        return false;
+    }
+    let mut buf = [0u8; MAX_ASSET_CODE_LEN];
+    let bytes = &mut buf[..len];
+    code.copy_into_slice(bytes);
+    bytes.iter().all(u8::is_ascii_alphanumeric)
+}
+
+/// Address of the Stellar Asset Contract of `asset`, the token contract
+/// its payments are made through.
+///
+/// Returns [`ContractError::InvalidAsset`] if the asset's code or issuer is
+/// malformed, as it can be for payments imported without validation.
+pub fn asset_contract(env: &Env, asset: &Asset) -> Result<Address, ContractError> {
+    // `Asset` XDR: the asset type, then for a classic asset its code padded
+    // to 4 or 12 bytes and the issuer's `PublicKey` (key type and key).
+    let mut xdr = Bytes::new(env);
+    match asset {
+        Asset::Native => xdr.extend_from_array(&[0, 0, 0, 0]),
+        Asset::Token(code, issuer) => {
+            if !is_classic_asset_code(code) {
+                return Err(ContractError::InvalidAsset);
+            }
+            let key = account_key(issuer).ok_or(ContractError::InvalidAsset)?;
+            let len = code.len() as usize;
+            let mut padded = [0u8; MAX_ASSET_CODE_LEN];
+            code.copy_into_slice(&mut padded[..len]);
+            let (kind, width) = if len <= 4 {
+                (1, 4)
+            } else {
+                (2, MAX_ASSET_CODE_LEN)
+            };
+            xdr.extend_from_array(&[0, 0, 0, kind]);
+            xdr.extend_from_slice(&padded[..width]);
+            xdr.extend_from_array(&[0, 0, 0, 0]);
+            xdr.extend_from_array(&key);
+        }
+    }
+    Ok(env.deployer().with_stellar_asset(xdr).deployed_address())
+}
+
+/// The ed25519 key of `issuer` if it is a well-formed account strkey: 56
+/// base32 characters decoding to the account version byte, a 32-byte key and
+/// a matching CRC16-XModem checksum.
+fn account_key(issuer: &String) -> Option<[u8; 32]> {
+    if issuer.len() as usize != ACCOUNT_STRKEY_LEN {
+        return None;
+    }
+    let mut chars = [0u8; ACCOUNT_STRKEY_LEN];
+    issuer.copy_into_slice(&mut chars);
+
+    // Base32 decode (RFC 4648, no padding): 56 × 5 bits = 35 bytes.
+    let mut raw = [0u8; ACCOUNT_STRKEY_LEN * 5 / 8];
+    let (mut acc, mut bits, mut n) = (0u32, 0u32, 0usize);
+    for c in chars {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'2'..=b'7' => c - b'2' + 26,
+            _ => return None,
+        };
+        acc = ((acc << 5) | v as u32) & 0xFFFF;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            raw[n] = (acc >> bits) as u8;
+            n += 1;
+        }
+    }
+
+    let (payload, checksum) = raw.split_at(raw.len() - 2);
+    if payload[0] != ACCOUNT_VERSION_BYTE
+        || crc16_xmodem(payload) != u16::from_le_bytes([checksum[0], checksum[1]])
+    {
+        return None;
+    }
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&payload[1..]);
+    Some(key)
+}
+
+fn crc16_xmodem(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
@@ -0,0 +1,26 @@
+use soroban_sdk::contracterror;
+
+/// Typed error codes for the merchant registry contract.
+///
+/// **Never reorder or remove codes** once deployed — only add new variants at
+/// the end.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ContractError {
+    /// `initialize()` was called on a contract that is already set up.
+    AlreadyInitialized = 1,
+
+    /// A method that requires admin was called before `initialize()`.
+    NotInitialized = 2,
+
+    /// `onboard()` was called for a `merchant_id` that is already registered.
+    MerchantAlreadyExists = 3,
+
+    /// No merchant is registered under `merchant_id`.
+    MerchantNotFound = 4,
+
+    /// `merchant_id` was empty, longer than 32 bytes, or contained a
+    /// character outside `[A-Za-z0-9_-]`.
+    InvalidMerchantId = 5,
+}
@@ -0,0 +1,22 @@
+use soroban_sdk::{contractevent, Env};
+
+use crate::storage::Merchant;
+
+#[contractevent]
+pub struct MerchantOnboarded {
+    pub merchant: Merchant,
+}
+
+/// Payout, metadata or active flag changed; carries the full new state.
+#[contractevent]
+pub struct MerchantUpdated {
+    pub merchant: Merchant,
+}
+
+pub fn emit_merchant_onboarded(env: &Env, merchant: Merchant) {
+    MerchantOnboarded { merchant }.publish(env);
+}
+
+pub fn emit_merchant_updated(env: &Env, merchant: Merchant) {
+    MerchantUpdated { merchant }.publish(env);
+}
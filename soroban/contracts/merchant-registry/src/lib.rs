@@ -0,0 +1,196 @@
+#![no_std]
+use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, String};
+
+pub mod errors;
+pub mod events;
+pub mod storage;
+
+pub use errors::ContractError;
+pub use storage::{DataKey, Merchant, MerchantStatus};
+
+use events::{emit_merchant_onboarded, emit_merchant_updated};
+use storage::{
+    bump_count, find_merchant, get_admin, get_count, get_merchant, has_admin, has_merchant,
+    set_admin, set_merchant, MAX_MERCHANT_ID_LEN,
+};
+
+// Contract
+
+/// # Invoisio Merchant Registry Contract
+///
+/// The on-chain list of merchants Invoisio bills for. Each merchant has a
+/// payout address, a hash of its off-chain metadata and an active flag.
+/// `invoice-payment` asks [`merchant_status`](MerchantRegistryContract::merchant_status)
+/// before recording a merchant's payment, so onboarding and offboarding are
+/// managed here instead of in every payment contract.
+///
+/// ## Module layout
+/// | Module       | Responsibility                            |
+/// |--------------|-------------------------------------------|
+/// | `errors.rs`  | `#[contracterror]` typed error codes      |
+/// | `storage.rs` | `DataKey`, `Merchant`, TTL helpers        |
+/// | `events.rs`  | Soroban event types and emit helpers      |
+/// | `lib.rs`     | Contract entry-points (this file)         |
+///
+/// ## Design decisions
+/// - **Never deleted:** offboarding clears `active`; the record stays so past
+///   payments keep resolving to a merchant.
+/// - **Metadata by hash:** only a 32-byte hash is stored on-chain; the
+///   document itself lives off-chain.
+/// - **One validation call:** `merchant_status` never fails, so callers can
+///   map each status to their own error.
+#[contract]
+pub struct MerchantRegistryContract;
+
+#[contractimpl]
+impl MerchantRegistryContract {
+    // Lifecycle
+
+    /// Initialise the contract and register the `admin`.
+    ///
+    /// Returns [`ContractError::AlreadyInitialized`] if called a second time.
+    pub fn initialize(env: Env, admin: Address) -> Result<(), ContractError> {
+        if has_admin(&env) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        set_admin(&env, &admin);
+        Ok(())
+    }
+
+    // Write
+
+    /// Register an active merchant. Admin only. Emits `merchant_onboarded`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::NotInitialized`]
+    /// - [`ContractError::InvalidMerchantId`] — empty, too long or not `[A-Za-z0-9_-]`
+    /// - [`ContractError::MerchantAlreadyExists`]
+    pub fn onboard(
+        env: Env,
+        merchant_id: String,
+        payout: Address,
+        metadata_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        validate_merchant_id(&merchant_id)?;
+        if has_merchant(&env, &merchant_id) {
+            return Err(ContractError::MerchantAlreadyExists);
+        }
+
+        let merchant = Merchant {
+            merchant_id,
+            payout,
+            metadata_hash,
+            active: true,
+            onboarded_at: env.ledger().timestamp(),
+        };
+        set_merchant(&env, &merchant);
+        bump_count(&env);
+        emit_merchant_onboarded(&env, merchant);
+        Ok(())
+    }
+
+    /// Change a merchant's payout address. Admin only. Emits `merchant_updated`.
+    ///
+    /// Returns [`ContractError::MerchantNotFound`] for an unknown merchant.
+    pub fn set_payout(env: Env, merchant_id: String, payout: Address) -> Result<(), ContractError> {
+        update(&env, &merchant_id, |m| m.payout = payout)
+    }
+
+    /// Replace a merchant's metadata hash. Admin only. Emits `merchant_updated`.
+    ///
+    /// Returns [`ContractError::MerchantNotFound`] for an unknown merchant.
+    pub fn set_metadata_hash(
+        env: Env,
+        merchant_id: String,
+        metadata_hash: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        update(&env, &merchant_id, |m| m.metadata_hash = metadata_hash)
+    }
+
+    /// Activate or deactivate a merchant. Admin only. Emits `merchant_updated`.
+    ///
+    /// Returns [`ContractError::MerchantNotFound`] for an unknown merchant.
+    pub fn set_active(env: Env, merchant_id: String, active: bool) -> Result<(), ContractError> {
+        update(&env, &merchant_id, |m| m.active = active)
+    }
+
+    // Read
+
+    /// Returns [`ContractError::MerchantNotFound`] for an unknown merchant.
+    pub fn get_merchant(env: Env, merchant_id: String) -> Result<Merchant, ContractError> {
+        get_merchant(&env, &merchant_id)
+    }
+
+    /// Whether `merchant_id` is registered and active. Never fails; this is
+    /// what other contracts call to validate a merchant.
+    pub fn merchant_status(env: Env, merchant_id: String) -> MerchantStatus {
+        match find_merchant(&env, &merchant_id) {
+            None => MerchantStatus::Unknown,
+            Some(m) if m.active => MerchantStatus::Active,
+            Some(_) => MerchantStatus::Inactive,
+        }
+    }
+
+    /// Number of merchants ever onboarded.
+    pub fn merchant_count(env: Env) -> u32 {
+        get_count(&env)
+    }
+
+    // Admin
+
+    /// Return the current admin address.
+    pub fn admin(env: Env) -> Result<Address, ContractError> {
+        get_admin(&env)
+    }
+
+    /// Transfer admin rights. Both the current and the new admin must sign.
+    pub fn set_admin(env: Env, new_admin: Address) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        new_admin.require_auth();
+        set_admin(&env, &new_admin);
+        Ok(())
+    }
+}
+
+// Helpers
+
+/// Admin-gated read-modify-write of one merchant, emitting `merchant_updated`.
+fn update(
+    env: &Env,
+    merchant_id: &String,
+    change: impl FnOnce(&mut Merchant),
+) -> Result<(), ContractError> {
+    let admin = get_admin(env)?;
+    admin.require_auth();
+
+    let mut merchant = get_merchant(env, merchant_id)?;
+    change(&mut merchant);
+    set_merchant(env, &merchant);
+    emit_merchant_updated(env, merchant);
+    Ok(())
+}
+
+/// `[A-Za-z0-9_-]{1,MAX_MERCHANT_ID_LEN}`.
+fn validate_merchant_id(merchant_id: &String) -> Result<(), ContractError> {
+    let len = merchant_id.len();
+    if len == 0 || len > MAX_MERCHANT_ID_LEN {
+        return Err(ContractError::InvalidMerchantId);
+    }
+    let mut buf = [0u8; MAX_MERCHANT_ID_LEN as usize];
+    let bytes = &mut buf[..len as usize];
+    merchant_id.copy_into_slice(bytes);
+    if bytes
+        .iter()
+        .all(|b| b.is_ascii_alphanumeric() || *b == b'-' || *b == b'_')
+    {
+        Ok(())
+    } else {
+        Err(ContractError::InvalidMerchantId)
+    }
+}
+
+mod test;
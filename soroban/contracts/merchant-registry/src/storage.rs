@@ -0,0 +1,121 @@
+use soroban_sdk::{contracttype, Address, BytesN, Env, String};
+
+use crate::errors::ContractError;
+
+// TTL budget — same policy as `invoice-payment`:
+//   MIN_TTL  = 17 280 ledgers ≈ 1 day
+//   BUMP_TTL = 518 400 ledgers ≈ 30 days
+
+pub const MIN_TTL: u32 = 17_280;
+pub const BUMP_TTL: u32 = 518_400;
+
+/// Longest accepted `merchant_id`, in bytes.
+pub const MAX_MERCHANT_ID_LEN: u32 = 32;
+
+// Storage keys
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    /// Admin [`Address`] in **instance** storage.
+    Admin,
+    /// Number of merchants ever onboarded, in **instance** storage.
+    MerchantCount,
+    /// A [`Merchant`] indexed by `merchant_id` in **persistent** storage.
+    Merchant(String),
+}
+
+// Data structures
+
+/// A registered merchant.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Merchant {
+    pub merchant_id: String,
+    /// Account the merchant is paid out to.
+    pub payout: Address,
+    /// SHA-256 of the merchant's off-chain metadata document (legal name,
+    /// contact, branding), so it can be verified without storing it here.
+    pub metadata_hash: BytesN<32>,
+    /// Inactive merchants stay registered but may not take new payments.
+    pub active: bool,
+    /// Unix timestamp of onboarding.
+    pub onboarded_at: u64,
+}
+
+/// Answer to `merchant_status`: the single call other contracts make to
+/// validate a `merchant_id`.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MerchantStatus {
+    Unknown,
+    Active,
+    Inactive,
+}
+
+// Admin helpers (instance storage)
+
+pub fn has_admin(env: &Env) -> bool {
+    env.storage().instance().has(&DataKey::Admin)
+}
+
+/// Returns [`ContractError::NotInitialized`] if `initialize()` was never called.
+pub fn get_admin(env: &Env) -> Result<Address, ContractError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(ContractError::NotInitialized)
+}
+
+pub fn set_admin(env: &Env, admin: &Address) {
+    env.storage().instance().set(&DataKey::Admin, admin);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+pub fn get_count(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::MerchantCount)
+        .unwrap_or(0u32)
+}
+
+pub fn bump_count(env: &Env) {
+    let count = get_count(env);
+    env.storage()
+        .instance()
+        .set(&DataKey::MerchantCount, &(count + 1u32));
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+// Merchant helpers (persistent storage)
+
+pub fn has_merchant(env: &Env, merchant_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Merchant(merchant_id.clone()))
+}
+
+/// Read a merchant without failing when it is absent.
+pub fn find_merchant(env: &Env, merchant_id: &String) -> Option<Merchant> {
+    let key = DataKey::Merchant(merchant_id.clone());
+    let merchant: Option<Merchant> = env.storage().persistent().get(&key);
+    if merchant.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    merchant
+}
+
+/// Returns [`ContractError::MerchantNotFound`] if `merchant_id` is not registered.
+pub fn get_merchant(env: &Env, merchant_id: &String) -> Result<Merchant, ContractError> {
+    find_merchant(env, merchant_id).ok_or(ContractError::MerchantNotFound)
+}
+
+pub fn set_merchant(env: &Env, merchant: &Merchant) {
+    let key = DataKey::Merchant(merchant.merchant_id.clone());
+    env.storage().persistent().set(&key, merchant);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
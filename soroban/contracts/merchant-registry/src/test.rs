@@ -0,0 +1,122 @@
+#![cfg(test)]
+#![allow(clippy::all)]
+
+use super::*;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, BytesN, Env, String};
+
+// Helpers
+
+fn setup(env: &Env) -> (MerchantRegistryContractClient<'_>, Address) {
+    let admin = Address::generate(env);
+    let contract_id = env.register(MerchantRegistryContract, ());
+    let client = MerchantRegistryContractClient::new(env, &contract_id);
+    client.initialize(&admin);
+    (client, admin)
+}
+
+fn onboard(env: &Env, client: &MerchantRegistryContractClient, merchant_id: &str) -> Address {
+    let payout = Address::generate(env);
+    client.onboard(
+        &String::from_str(env, merchant_id),
+        &payout,
+        &BytesN::from_array(env, &[7; 32]),
+    );
+    payout
+}
+
+// Onboarding
+
+#[test]
+fn test_onboard_registers_active_merchant() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let payout = onboard(&env, &client, "acme");
+
+    let merchant = client.get_merchant(&String::from_str(&env, "acme"));
+    assert_eq!(merchant.payout, payout);
+    assert_eq!(merchant.metadata_hash, BytesN::from_array(&env, &[7; 32]));
+    assert!(merchant.active);
+    assert_eq!(client.merchant_count(), 1);
+    assert_eq!(
+        client.merchant_status(&String::from_str(&env, "acme")),
+        MerchantStatus::Active
+    );
+}
+
+#[test]
+fn test_onboard_rejects_duplicates_and_bad_ids() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    onboard(&env, &client, "acme");
+
+    let attempt = |id: &str| {
+        client.try_onboard(
+            &String::from_str(&env, id),
+            &Address::generate(&env),
+            &BytesN::from_array(&env, &[0; 32]),
+        )
+    };
+    assert_eq!(attempt("acme"), Err(Ok(ContractError::MerchantAlreadyExists)));
+    assert_eq!(attempt(""), Err(Ok(ContractError::InvalidMerchantId)));
+    assert_eq!(attempt("acme corp"), Err(Ok(ContractError::InvalidMerchantId)));
+    assert_eq!(
+        attempt("m123456789012345678901234567890123"),
+        Err(Ok(ContractError::InvalidMerchantId))
+    );
+}
+
+#[test]
+fn test_onboard_requires_admin_auth() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+
+    let result = client.try_onboard(
+        &String::from_str(&env, "acme"),
+        &Address::generate(&env),
+        &BytesN::from_array(&env, &[0; 32]),
+    );
+    assert!(result.is_err());
+}
+
+// Updates
+
+#[test]
+fn test_updates_change_merchant() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    onboard(&env, &client, "acme");
+    let id = String::from_str(&env, "acme");
+    let payout = Address::generate(&env);
+
+    client.set_payout(&id, &payout);
+    client.set_metadata_hash(&id, &BytesN::from_array(&env, &[9; 32]));
+    client.set_active(&id, &false);
+
+    let merchant = client.get_merchant(&id);
+    assert_eq!(merchant.payout, payout);
+    assert_eq!(merchant.metadata_hash, BytesN::from_array(&env, &[9; 32]));
+    assert!(!merchant.active);
+    assert_eq!(client.merchant_status(&id), MerchantStatus::Inactive);
+
+    client.set_active(&id, &true);
+    assert_eq!(client.merchant_status(&id), MerchantStatus::Active);
+}
+
+#[test]
+fn test_unknown_merchant() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup(&env);
+    let id = String::from_str(&env, "ghost");
+
+    assert_eq!(client.merchant_status(&id), MerchantStatus::Unknown);
+    assert_eq!(client.try_get_merchant(&id), Err(Ok(ContractError::MerchantNotFound)));
+    assert_eq!(
+        client.try_set_active(&id, &false),
+        Err(Ok(ContractError::MerchantNotFound))
+    );
+}
@@ -0,0 +1,40 @@
+use soroban_sdk::contracterror;
+
+/// Typed error codes for the payment channel contract.
+///
+/// **Never reorder or remove codes** once deployed — error codes are part of
+/// the on-chain ABI. Only add new variants at the end.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ContractError {
+    /// `channel_id` is empty.
+    InvalidChannelId = 1,
+
+    /// The deposit is ≤ 0, or a balance update pays a negative amount.
+    InvalidAmount = 2,
+
+    /// `expires_at` is not in the future.
+    InvalidExpiry = 3,
+
+    /// `open()` was called for a `channel_id` that was already used.
+    ChannelAlreadyExists = 4,
+
+    /// No channel exists for the `channel_id`.
+    ChannelNotFound = 5,
+
+    /// The channel was already settled or expired.
+    ChannelNotOpen = 6,
+
+    /// The balance update was signed for another channel.
+    WrongChannel = 7,
+
+    /// The balance update pays the merchant more than the deposit.
+    ExceedsDeposit = 8,
+
+    /// `claim_expired()` was called before `expires_at`.
+    NotExpired = 9,
+
+    /// The channel has not been settled.
+    SettlementNotFound = 10,
+}
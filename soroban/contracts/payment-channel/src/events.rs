@@ -0,0 +1,40 @@
+use soroban_sdk::{contractevent, Address, Env, String};
+
+use crate::storage::{Channel, Settlement};
+
+#[contractevent]
+pub struct ChannelOpened {
+    pub channel: Channel,
+}
+
+/// The aggregated payment record of a closed channel, for indexers to
+/// reconcile in one go.
+#[contractevent]
+pub struct ChannelSettled {
+    pub settlement: Settlement,
+}
+
+/// The deposit of a channel nobody closed went back to the payer.
+#[contractevent]
+pub struct ChannelExpired {
+    pub channel_id: String,
+    pub payer: Address,
+    pub amount: i128,
+}
+
+pub fn emit_channel_opened(env: &Env, channel: Channel) {
+    ChannelOpened { channel }.publish(env);
+}
+
+pub fn emit_channel_settled(env: &Env, settlement: Settlement) {
+    ChannelSettled { settlement }.publish(env);
+}
+
+pub fn emit_channel_expired(env: &Env, channel: &Channel) {
+    ChannelExpired {
+        channel_id: channel.channel_id.clone(),
+        payer: channel.payer.clone(),
+        amount: channel.deposit,
+    }
+    .publish(env);
+}
@@ -0,0 +1,219 @@
+#![no_std]
+use soroban_sdk::{contract, contractimpl, token, xdr::ToXdr, Address, BytesN, Env, String};
+
+pub mod errors;
+pub mod events;
+pub mod storage;
+
+pub use errors::ContractError;
+pub use storage::{BalanceUpdate, Channel, ChannelStatus, DataKey, Settlement};
+
+use events::{emit_channel_expired, emit_channel_opened, emit_channel_settled};
+use storage::{get_channel, get_settlement, has_channel, set_channel, set_settlement};
+
+// Contract
+
+/// # Invoisio Payment Channel Contract
+///
+/// Unidirectional payment channels for micro-invoices too small to each
+/// afford an on-chain record. The payer locks a deposit, pays each invoice
+/// by signing a cumulative [`BalanceUpdate`] off-chain, and the merchant
+/// closes the channel with the latest one, producing one [`Settlement`].
+///
+/// ## Module layout
+/// | Module       | Responsibility                                    |
+/// |--------------|---------------------------------------------------|
+/// | `errors.rs`  | `#[contracterror]` typed error codes              |
+/// | `storage.rs` | `DataKey`, `Channel`, `Settlement`, TTL helpers   |
+/// | `events.rs`  | Soroban event types and emit helpers              |
+/// | `lib.rs`     | Contract entry-points (this file)                 |
+///
+/// ## Design decisions
+/// - **Only the merchant closes:** every update only ever raises what the
+///   merchant is owed, so the merchant has no reason to submit an old one
+///   and no challenge period is needed.
+/// - **Bound to the deployment:** the payer signs the XDR of
+///   `(contract address, BalanceUpdate)`, and the update names its channel,
+///   so a signature is never valid for another channel or deployment.
+/// - **No stuck funds:** once `expires_at` passes, anyone can return the
+///   deposit of a channel that is still open to the payer.
+/// - **One channel per id:** keyed by `channel_id`, never reused.
+///
+/// ## Typical flow
+/// 1. The payer calls `open(channel_id, payer, merchant, payer_key, token, deposit, expires_at)`.
+/// 2. For each micro-invoice the payer sends the merchant a signed
+///    `BalanceUpdate` with the new running total.
+/// 3. The merchant calls `close(channel_id, update, signature)` with the last
+///    one before `expires_at`; the backend records the `channel_settled`
+///    event as one payment.
+#[contract]
+pub struct PaymentChannelContract;
+
+#[contractimpl]
+impl PaymentChannelContract {
+    // Write
+
+    /// Move `deposit` of `token` from `payer` into a channel to `merchant`.
+    /// Balance updates must be signed by `payer_key`. Signed by the payer.
+    /// Emits `channel_opened`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidChannelId`] — `channel_id` is empty
+    /// - [`ContractError::InvalidAmount`] — `deposit` ≤ 0
+    /// - [`ContractError::InvalidExpiry`] — `expires_at` is not in the future
+    /// - [`ContractError::ChannelAlreadyExists`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn open(
+        env: Env,
+        channel_id: String,
+        payer: Address,
+        merchant: Address,
+        payer_key: BytesN<32>,
+        token: Address,
+        deposit: i128,
+        expires_at: u64,
+    ) -> Result<(), ContractError> {
+        payer.require_auth();
+
+        if channel_id.is_empty() {
+            return Err(ContractError::InvalidChannelId);
+        }
+        if deposit <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if expires_at <= env.ledger().timestamp() {
+            return Err(ContractError::InvalidExpiry);
+        }
+        if has_channel(&env, &channel_id) {
+            return Err(ContractError::ChannelAlreadyExists);
+        }
+
+        token::Client::new(&env, &token).transfer(&payer, env.current_contract_address(), &deposit);
+        let channel = Channel {
+            channel_id,
+            payer,
+            merchant,
+            payer_key,
+            token,
+            deposit,
+            expires_at,
+            status: ChannelStatus::Open,
+        };
+        set_channel(&env, &channel);
+        emit_channel_opened(&env, channel);
+        Ok(())
+    }
+
+    /// Close the channel with the payer's latest `update`: pay its amount to
+    /// the merchant, return the rest of the deposit to the payer and store
+    /// the [`Settlement`]. Signed by the merchant; still possible after
+    /// `expires_at` as long as nobody claimed the deposit back. Emits
+    /// `channel_settled`.
+    ///
+    /// `signature` is the payer key's ed25519 signature over the XDR of
+    /// `(contract address, update)`. An invalid signature aborts the
+    /// invocation rather than returning an error.
+    ///
+    /// ## Errors
+    /// - [`ContractError::ChannelNotFound`]
+    /// - [`ContractError::ChannelNotOpen`] — already settled or expired
+    /// - [`ContractError::WrongChannel`] — `update` names another channel
+    /// - [`ContractError::InvalidAmount`] — `update.amount` < 0
+    /// - [`ContractError::ExceedsDeposit`]
+    pub fn close(
+        env: Env,
+        channel_id: String,
+        update: BalanceUpdate,
+        signature: BytesN<64>,
+    ) -> Result<(), ContractError> {
+        let mut channel = get_channel(&env, &channel_id)?;
+        channel.merchant.require_auth();
+        if channel.status != ChannelStatus::Open {
+            return Err(ContractError::ChannelNotOpen);
+        }
+        if update.channel_id != channel_id {
+            return Err(ContractError::WrongChannel);
+        }
+        if update.amount < 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if update.amount > channel.deposit {
+            return Err(ContractError::ExceedsDeposit);
+        }
+        let message = (env.current_contract_address(), update.clone()).to_xdr(&env);
+        env.crypto()
+            .ed25519_verify(&channel.payer_key, &message, &signature);
+
+        pay(&env, &channel, &channel.merchant, update.amount);
+        pay(&env, &channel, &channel.payer, channel.deposit - update.amount);
+        channel.status = ChannelStatus::Settled;
+        set_channel(&env, &channel);
+
+        let settlement = Settlement {
+            channel_id,
+            payer: channel.payer,
+            merchant: channel.merchant,
+            token: channel.token,
+            amount: update.amount,
+            invoice_count: update.invoice_count,
+            settled_at: env.ledger().timestamp(),
+        };
+        set_settlement(&env, &settlement);
+        emit_channel_settled(&env, settlement);
+        Ok(())
+    }
+
+    /// Return the whole deposit to the payer once `expires_at` has passed
+    /// and the merchant never closed the channel. Permissionless: anyone may
+    /// call it, the funds only ever go to the payer. Emits `channel_expired`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::ChannelNotFound`]
+    /// - [`ContractError::ChannelNotOpen`] — already settled or expired
+    /// - [`ContractError::NotExpired`]
+    pub fn claim_expired(env: Env, channel_id: String) -> Result<(), ContractError> {
+        let mut channel = get_channel(&env, &channel_id)?;
+        if channel.status != ChannelStatus::Open {
+            return Err(ContractError::ChannelNotOpen);
+        }
+        if env.ledger().timestamp() < channel.expires_at {
+            return Err(ContractError::NotExpired);
+        }
+
+        pay(&env, &channel, &channel.payer, channel.deposit);
+        channel.status = ChannelStatus::Expired;
+        set_channel(&env, &channel);
+        emit_channel_expired(&env, &channel);
+        Ok(())
+    }
+
+    // Read
+
+    /// Returns [`ContractError::ChannelNotFound`] if there is no channel for
+    /// `channel_id`.
+    pub fn get_channel(env: Env, channel_id: String) -> Result<Channel, ContractError> {
+        get_channel(&env, &channel_id)
+    }
+
+    /// Returns [`ContractError::SettlementNotFound`] if the channel was never
+    /// closed.
+    pub fn get_settlement(env: Env, channel_id: String) -> Result<Settlement, ContractError> {
+        get_settlement(&env, &channel_id)
+    }
+}
+
+// Helpers
+
+/// Transfer `amount` of the channel's token from the contract to `to`,
+/// skipping zero amounts.
+fn pay(env: &Env, channel: &Channel, to: &Address, amount: i128) {
+    if amount > 0 {
+        token::Client::new(env, &channel.token).transfer(
+            &env.current_contract_address(),
+            to,
+            &amount,
+        );
+    }
+}
+
+mod test;
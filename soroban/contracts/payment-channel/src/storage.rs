@@ -0,0 +1,138 @@
+use soroban_sdk::{contracttype, Address, BytesN, Env, String};
+
+use crate::errors::ContractError;
+
+// TTL budget — same policy as `invoice-payment`:
+//   MIN_TTL  = 17 280 ledgers ≈ 1 day
+//   BUMP_TTL = 518 400 ledgers ≈ 30 days
+
+pub const MIN_TTL: u32 = 17_280;
+pub const BUMP_TTL: u32 = 518_400;
+
+// Storage keys
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    /// A [`Channel`] indexed by `channel_id` in **persistent** storage.
+    Channel(String),
+    /// The [`Settlement`] of a channel indexed by `channel_id` in
+    /// **persistent** storage.
+    Settlement(String),
+}
+
+// Data structures
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChannelStatus {
+    /// The deposit is held; the merchant can close with a balance update.
+    Open,
+    /// Closed by the merchant; see its [`Settlement`].
+    Settled,
+    /// Never closed; the deposit went back to the payer after `expires_at`.
+    Expired,
+}
+
+/// A deposit from which the payer pays one merchant many small invoices.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel {
+    pub channel_id: String,
+    pub payer: Address,
+    /// Receives the amount of the last balance update on close.
+    pub merchant: Address,
+    /// Ed25519 public key whose signature makes a [`BalanceUpdate`] valid.
+    pub payer_key: BytesN<32>,
+    /// Stellar asset (SAC) or other SEP-41 token contract.
+    pub token: Address,
+    /// Amount in the token's smallest unit (> 0).
+    pub deposit: i128,
+    /// Unix timestamp from which the payer can reclaim the deposit of a
+    /// channel that is still open.
+    pub expires_at: u64,
+    pub status: ChannelStatus,
+}
+
+/// What the payer signs off-chain each time it pays an invoice over the
+/// channel. Totals are cumulative, so only the latest update matters.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BalanceUpdate {
+    pub channel_id: String,
+    /// Total owed to the merchant so far.
+    pub amount: i128,
+    /// Micro-invoices covered by `amount`.
+    pub invoice_count: u32,
+}
+
+/// The single aggregated payment record of a closed channel.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settlement {
+    pub channel_id: String,
+    pub payer: Address,
+    pub merchant: Address,
+    pub token: Address,
+    /// Paid to the merchant; the rest of the deposit went back to the payer.
+    pub amount: i128,
+    pub invoice_count: u32,
+    /// Ledger timestamp of the close.
+    pub settled_at: u64,
+}
+
+// Channel helpers (persistent storage)
+
+pub fn has_channel(env: &Env, channel_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Channel(channel_id.clone()))
+}
+
+/// Returns [`ContractError::ChannelNotFound`] if there is no channel for
+/// `channel_id`.
+pub fn get_channel(env: &Env, channel_id: &String) -> Result<Channel, ContractError> {
+    let key = DataKey::Channel(channel_id.clone());
+    let channel: Channel = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::ChannelNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(channel)
+}
+
+pub fn set_channel(env: &Env, channel: &Channel) {
+    let key = DataKey::Channel(channel.channel_id.clone());
+    env.storage().persistent().set(&key, channel);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+// Settlement helpers (persistent storage)
+
+/// Returns [`ContractError::SettlementNotFound`] if the channel was never
+/// settled.
+pub fn get_settlement(env: &Env, channel_id: &String) -> Result<Settlement, ContractError> {
+    let key = DataKey::Settlement(channel_id.clone());
+    let settlement: Settlement = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::SettlementNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(settlement)
+}
+
+pub fn set_settlement(env: &Env, settlement: &Settlement) {
+    let key = DataKey::Settlement(settlement.channel_id.clone());
+    env.storage().persistent().set(&key, settlement);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
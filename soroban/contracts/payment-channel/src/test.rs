@@ -0,0 +1,211 @@
+#![cfg(test)]
+#![allow(clippy::all)]
+
+extern crate std;
+
+use super::*;
+use ed25519_dalek::{Signer as _, SigningKey};
+use soroban_sdk::testutils::{Address as _, Events as _, Ledger as _};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::xdr::ContractEventBody;
+use soroban_sdk::{Address, BytesN, Env, IntoVal, String, Symbol, TryFromVal, Val, Vec};
+
+const START: u64 = 1_700_000_000;
+const EXPIRES_AT: u64 = START + 7 * 86_400;
+const DEPOSIT: i128 = 10_000_000;
+
+// Helpers
+
+struct Setup<'a> {
+    client: PaymentChannelContractClient<'a>,
+    token: TokenClient<'a>,
+    payer: Address,
+    merchant: Address,
+    key: SigningKey,
+}
+
+fn setup(env: &Env) -> Setup<'_> {
+    env.mock_all_auths();
+    set_time(env, START);
+    let contract_id = env.register(PaymentChannelContract, ());
+    let client = PaymentChannelContractClient::new(env, &contract_id);
+
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(env));
+    let payer = Address::generate(env);
+    StellarAssetClient::new(env, &sac.address()).mint(&payer, &DEPOSIT);
+
+    Setup {
+        client,
+        token: TokenClient::new(env, &sac.address()),
+        payer,
+        merchant: Address::generate(env),
+        key: SigningKey::from_bytes(&[5; 32]),
+    }
+}
+
+fn set_time(env: &Env, timestamp: u64) {
+    env.ledger().with_mut(|li| li.timestamp = timestamp);
+}
+
+fn id(env: &Env) -> String {
+    String::from_str(env, "invoisio-ch-1")
+}
+
+fn open(env: &Env, s: &Setup) {
+    s.client.open(
+        &id(env),
+        &s.payer,
+        &s.merchant,
+        &BytesN::from_array(env, &s.key.verifying_key().to_bytes()),
+        &s.token.address,
+        &DEPOSIT,
+        &EXPIRES_AT,
+    );
+}
+
+fn update(env: &Env, amount: i128, invoice_count: u32) -> BalanceUpdate {
+    BalanceUpdate {
+        channel_id: id(env),
+        amount,
+        invoice_count,
+    }
+}
+
+/// Topics of the last event of the most recent invocation.
+fn last_event_topics(env: &Env) -> Vec<Val> {
+    let events = env.events().all();
+    let event = events.events().last().expect("no event emitted");
+    let ContractEventBody::V0(body) = &event.body;
+    let mut topics = Vec::new(env);
+    for topic in body.topics.iter() {
+        topics.push_back(Val::try_from_val(env, topic).unwrap());
+    }
+    topics
+}
+
+/// The payer key's signature over `(contract address, update)`.
+fn sign(env: &Env, s: &Setup, update: &BalanceUpdate) -> BytesN<64> {
+    let message = (s.client.address.clone(), update.clone()).to_xdr(env);
+    let mut bytes = std::vec![0u8; message.len() as usize];
+    message.copy_into_slice(&mut bytes);
+    BytesN::from_array(env, &s.key.sign(&bytes).to_bytes())
+}
+
+// open / close
+
+#[test]
+fn test_close_settles_latest_update_in_one_record() {
+    let env = Env::default();
+    let s = setup(&env);
+    open(&env, &s);
+    assert_eq!(s.token.balance(&s.client.address), DEPOSIT);
+
+    let latest = update(&env, 3_000_000, 300);
+    s.client.close(&id(&env), &latest, &sign(&env, &s, &latest));
+    let expected: Vec<Val> =
+        soroban_sdk::vec![&env, Symbol::new(&env, "channel_settled").into_val(&env)];
+    assert_eq!(last_event_topics(&env), expected);
+
+    assert_eq!(s.token.balance(&s.merchant), 3_000_000);
+    assert_eq!(s.token.balance(&s.payer), DEPOSIT - 3_000_000);
+
+    assert_eq!(s.client.get_channel(&id(&env)).status, ChannelStatus::Settled);
+    let settlement = Settlement {
+        channel_id: id(&env),
+        payer: s.payer.clone(),
+        merchant: s.merchant.clone(),
+        token: s.token.address.clone(),
+        amount: 3_000_000,
+        invoice_count: 300,
+        settled_at: START,
+    };
+    assert_eq!(s.client.get_settlement(&id(&env)), settlement);
+
+    assert_eq!(
+        s.client.try_close(&id(&env), &latest, &sign(&env, &s, &latest)),
+        Err(Ok(ContractError::ChannelNotOpen))
+    );
+}
+
+#[test]
+fn test_close_rejects_invalid_updates() {
+    let env = Env::default();
+    let s = setup(&env);
+    open(&env, &s);
+
+    let too_much = update(&env, DEPOSIT + 1, 1);
+    assert_eq!(
+        s.client.try_close(&id(&env), &too_much, &sign(&env, &s, &too_much)),
+        Err(Ok(ContractError::ExceedsDeposit))
+    );
+    let mut other = update(&env, 1, 1);
+    other.channel_id = String::from_str(&env, "invoisio-ch-2");
+    assert_eq!(
+        s.client.try_close(&id(&env), &other, &sign(&env, &s, &other)),
+        Err(Ok(ContractError::WrongChannel))
+    );
+
+    // A signature over a different amount does not verify.
+    let signed = update(&env, 1, 1);
+    let forged = update(&env, DEPOSIT, 1);
+    assert!(s
+        .client
+        .try_close(&id(&env), &forged, &sign(&env, &s, &signed))
+        .is_err());
+    assert_eq!(s.client.get_channel(&id(&env)).status, ChannelStatus::Open);
+}
+
+#[test]
+fn test_open_validates_arguments() {
+    let env = Env::default();
+    let s = setup(&env);
+    let key = BytesN::from_array(&env, &[0; 32]);
+    let try_open = |channel_id: &str, deposit: i128, expires_at: u64| {
+        s.client.try_open(
+            &String::from_str(&env, channel_id),
+            &s.payer,
+            &s.merchant,
+            &key,
+            &s.token.address,
+            &deposit,
+            &expires_at,
+        )
+    };
+
+    assert_eq!(try_open("", DEPOSIT, EXPIRES_AT), Err(Ok(ContractError::InvalidChannelId)));
+    assert_eq!(try_open("invoisio-ch-1", 0, EXPIRES_AT), Err(Ok(ContractError::InvalidAmount)));
+    assert_eq!(try_open("invoisio-ch-1", DEPOSIT, START), Err(Ok(ContractError::InvalidExpiry)));
+    open(&env, &s);
+    assert_eq!(
+        try_open("invoisio-ch-1", 1, EXPIRES_AT),
+        Err(Ok(ContractError::ChannelAlreadyExists))
+    );
+}
+
+// claim_expired
+
+#[test]
+fn test_claim_expired_returns_deposit_after_expiry() {
+    let env = Env::default();
+    let s = setup(&env);
+    open(&env, &s);
+
+    assert_eq!(
+        s.client.try_claim_expired(&id(&env)),
+        Err(Ok(ContractError::NotExpired))
+    );
+    set_time(&env, EXPIRES_AT);
+    s.client.claim_expired(&id(&env));
+
+    assert_eq!(s.token.balance(&s.payer), DEPOSIT);
+    assert_eq!(s.client.get_channel(&id(&env)).status, ChannelStatus::Expired);
+    assert_eq!(
+        s.client.try_get_settlement(&id(&env)),
+        Err(Ok(ContractError::SettlementNotFound))
+    );
+    let latest = update(&env, 1, 1);
+    assert_eq!(
+        s.client.try_close(&id(&env), &latest, &sign(&env, &s, &latest)),
+        Err(Ok(ContractError::ChannelNotOpen))
+    );
+}
@@ -0,0 +1,27 @@
+use soroban_sdk::contracterror;
+
+/// Typed error codes for the payment factory contract.
+///
+/// **Never reorder or remove codes** once deployed — only add new variants at
+/// the end.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ContractError {
+    /// `initialize()` was called on a contract that is already set up.
+    AlreadyInitialized = 1,
+
+    /// A method that requires admin was called before `initialize()`.
+    NotInitialized = 2,
+
+    /// An instance was already deployed for this `merchant_id`.
+    InstanceAlreadyDeployed = 3,
+
+    /// `merchant_id` was empty, longer than 32 bytes, or contained a
+    /// character outside `[A-Za-z0-9_-]`.
+    InvalidMerchantId = 4,
+
+    /// The `nonce` passed to `set_wasm_hash()` or `set_admin()` is not the
+    /// admin's current [`admin_nonce`](crate::PaymentFactoryContract::admin_nonce).
+    InvalidNonce = 5,
+}
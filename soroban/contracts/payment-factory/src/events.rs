@@ -0,0 +1,24 @@
+use soroban_sdk::{contractevent, Address, BytesN, Env, String};
+
+/// A merchant's `invoice-payment` instance was deployed and initialised.
+/// Indexers start following `instance` from here.
+#[contractevent]
+pub struct InstanceDeployed {
+    pub merchant_id: String,
+    pub instance: Address,
+    pub wasm_hash: BytesN<32>,
+}
+
+pub fn emit_instance_deployed(
+    env: &Env,
+    merchant_id: String,
+    instance: Address,
+    wasm_hash: BytesN<32>,
+) {
+    InstanceDeployed {
+        merchant_id,
+        instance,
+        wasm_hash,
+    }
+    .publish(env);
+}
@@ -0,0 +1,212 @@
+#![no_std]
+use soroban_sdk::{contract, contractclient, contractimpl, Address, Bytes, BytesN, Env, String};
+
+pub mod errors;
+pub mod events;
+pub mod storage;
+
+pub use errors::ContractError;
+pub use storage::DataKey;
+
+use events::emit_instance_deployed;
+use storage::{
+    add_instance, get_admin, get_admin_nonce, get_count, get_instance, get_wasm_hash, has_admin,
+    set_admin, set_admin_nonce, set_wasm_hash, MAX_MERCHANT_ID_LEN,
+};
+
+/// The part of `invoice-payment` the factory calls on a fresh instance.
+#[contractclient(name = "InvoicePaymentClient")]
+pub trait InvoicePaymentInterface {
+    fn initialize(env: Env, admin: Address);
+}
+
+// Contract
+
+/// # Invoisio Payment Factory Contract
+///
+/// Deploys one `invoice-payment` contract per merchant, so each merchant's
+/// records, counters and rent are isolated, while
+/// [`instance_for`](PaymentFactoryContract::instance_for) keeps discovery
+/// on-chain.
+///
+/// ## Module layout
+/// | Module       | Responsibility                            |
+/// |--------------|-------------------------------------------|
+/// | `errors.rs`  | `#[contracterror]` typed error codes      |
+/// | `storage.rs` | `DataKey`, TTL helpers                    |
+/// | `events.rs`  | Soroban event types and emit helpers      |
+/// | `lib.rs`     | Contract entry-points (this file)         |
+///
+/// ## Design decisions
+/// - **Deterministic addresses:** the deploy salt is `sha256(merchant_id)`,
+///   so a merchant's instance address is known before it is deployed (see
+///   `predicted_address`) and can never be taken by another merchant.
+/// - **Deploy and initialise atomically:** `deploy` calls `initialize` in the
+///   same invocation, so nobody can front-run an uninitialised instance.
+/// - **Upgrades are forward-only:** `set_wasm_hash` affects instances
+///   deployed afterwards; existing ones are left untouched.
+/// - **Admin nonces:** `set_wasm_hash` and `set_admin` take the admin's
+///   current `admin_nonce`, so a signed but unsubmitted upgrade or transfer
+///   cannot be replayed once a later one has landed.
+#[contract]
+pub struct PaymentFactoryContract;
+
+#[contractimpl]
+impl PaymentFactoryContract {
+    // Lifecycle
+
+    /// Initialise the factory with its `admin` and the hash of the uploaded
+    /// `invoice-payment` WASM.
+    ///
+    /// Returns [`ContractError::AlreadyInitialized`] if called a second time.
+    pub fn initialize(env: Env, admin: Address, wasm_hash: BytesN<32>) -> Result<(), ContractError> {
+        if has_admin(&env) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        set_admin(&env, &admin);
+        set_wasm_hash(&env, &wasm_hash);
+        Ok(())
+    }
+
+    // Write
+
+    /// Deploy an `invoice-payment` instance for `merchant_id`, initialise it
+    /// with `instance_admin` and return its address. Admin only. Emits
+    /// `instance_deployed`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::NotInitialized`]
+    /// - [`ContractError::InvalidMerchantId`] — empty, too long or not `[A-Za-z0-9_-]`
+    /// - [`ContractError::InstanceAlreadyDeployed`]
+    pub fn deploy(
+        env: Env,
+        merchant_id: String,
+        instance_admin: Address,
+    ) -> Result<Address, ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let salt = merchant_salt(&env, &merchant_id)?;
+        if get_instance(&env, &merchant_id).is_some() {
+            return Err(ContractError::InstanceAlreadyDeployed);
+        }
+
+        let wasm_hash = get_wasm_hash(&env)?;
+        let instance = env
+            .deployer()
+            .with_current_contract(salt)
+            .deploy_v2(wasm_hash.clone(), ());
+        InvoicePaymentClient::new(&env, &instance).initialize(&instance_admin);
+
+        add_instance(&env, &merchant_id, &instance);
+        emit_instance_deployed(&env, merchant_id, instance.clone(), wasm_hash);
+        Ok(instance)
+    }
+
+    /// Use `wasm_hash` for instances deployed from now on. Admin only.
+    ///
+    /// Returns [`ContractError::InvalidNonce`] unless `nonce` is the admin's
+    /// current [`admin_nonce`](Self::admin_nonce).
+    pub fn set_wasm_hash(
+        env: Env,
+        wasm_hash: BytesN<32>,
+        nonce: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_wasm_hash(&env, &wasm_hash);
+        Ok(())
+    }
+
+    // Read
+
+    /// The instance deployed for `merchant_id`, or `None` if there is none.
+    pub fn instance_for(env: Env, merchant_id: String) -> Option<Address> {
+        get_instance(&env, &merchant_id)
+    }
+
+    /// The address `deploy(merchant_id, ..)` deploys to, whether or not it
+    /// has been deployed yet.
+    ///
+    /// Returns [`ContractError::InvalidMerchantId`] for an id `deploy` would
+    /// reject.
+    pub fn predicted_address(env: Env, merchant_id: String) -> Result<Address, ContractError> {
+        let salt = merchant_salt(&env, &merchant_id)?;
+        Ok(env
+            .deployer()
+            .with_current_contract(salt)
+            .deployed_address())
+    }
+
+    /// Number of instances deployed.
+    pub fn instance_count(env: Env) -> u32 {
+        get_count(&env)
+    }
+
+    /// Hash of the WASM new instances run.
+    pub fn wasm_hash(env: Env) -> Result<BytesN<32>, ContractError> {
+        get_wasm_hash(&env)
+    }
+
+    // Admin
+
+    /// Return the current admin address.
+    pub fn admin(env: Env) -> Result<Address, ContractError> {
+        get_admin(&env)
+    }
+
+    /// Return the nonce the current admin must pass to its next
+    /// `set_wasm_hash` or `set_admin`. Starts at 0 and increments with each.
+    pub fn admin_nonce(env: Env) -> Result<u64, ContractError> {
+        Ok(get_admin_nonce(&env, &get_admin(&env)?))
+    }
+
+    /// Transfer admin rights. Both the current and the new admin must sign.
+    ///
+    /// Returns [`ContractError::InvalidNonce`] unless `nonce` is the current
+    /// admin's [`admin_nonce`](Self::admin_nonce).
+    pub fn set_admin(env: Env, new_admin: Address, nonce: u64) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        new_admin.require_auth();
+        use_admin_nonce(&env, &admin, nonce)?;
+        set_admin(&env, &new_admin);
+        Ok(())
+    }
+}
+
+// Helpers
+
+/// Consume `nonce`, which must be `admin`'s current operation nonce.
+fn use_admin_nonce(env: &Env, admin: &Address, nonce: u64) -> Result<(), ContractError> {
+    if nonce != get_admin_nonce(env, admin) {
+        return Err(ContractError::InvalidNonce);
+    }
+    set_admin_nonce(env, admin, nonce + 1);
+    Ok(())
+}
+
+/// `sha256(merchant_id)`, after checking `merchant_id` is
+/// `[A-Za-z0-9_-]{1,MAX_MERCHANT_ID_LEN}`.
+fn merchant_salt(env: &Env, merchant_id: &String) -> Result<BytesN<32>, ContractError> {
+    let len = merchant_id.len();
+    if len == 0 || len > MAX_MERCHANT_ID_LEN {
+        return Err(ContractError::InvalidMerchantId);
+    }
+    let mut buf = [0u8; MAX_MERCHANT_ID_LEN as usize];
+    let bytes = &mut buf[..len as usize];
+    merchant_id.copy_into_slice(bytes);
+    if !bytes
+        .iter()
+        .all(|b| b.is_ascii_alphanumeric() || *b == b'-' || *b == b'_')
+    {
+        return Err(ContractError::InvalidMerchantId);
+    }
+    Ok(env
+        .crypto()
+        .sha256(&Bytes::from_slice(env, bytes))
+        .to_bytes())
+}
+
+mod test;
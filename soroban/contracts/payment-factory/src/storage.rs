@@ -0,0 +1,119 @@
+use soroban_sdk::{contracttype, Address, BytesN, Env, String};
+
+use crate::errors::ContractError;
+
+// TTL budget — same policy as `invoice-payment`:
+//   MIN_TTL  = 17 280 ledgers ≈ 1 day
+//   BUMP_TTL = 518 400 ledgers ≈ 30 days
+
+pub const MIN_TTL: u32 = 17_280;
+pub const BUMP_TTL: u32 = 518_400;
+
+/// Longest accepted `merchant_id`, in bytes. Matches `merchant-registry`.
+pub const MAX_MERCHANT_ID_LEN: u32 = 32;
+
+// Storage keys
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    /// Admin [`Address`] in **instance** storage.
+    Admin,
+    /// Hash of the uploaded `invoice-payment` WASM new instances run, in
+    /// **instance** storage.
+    WasmHash,
+    /// Number of instances deployed, in **instance** storage.
+    InstanceCount,
+    /// Instance [`Address`] indexed by `merchant_id` in **persistent** storage.
+    Instance(String),
+    /// Next operation nonce (`u64`) of an admin address, in **persistent**
+    /// storage. Absent = 0.
+    AdminNonce(Address),
+}
+
+// Admin helpers (instance storage)
+
+pub fn has_admin(env: &Env) -> bool {
+    env.storage().instance().has(&DataKey::Admin)
+}
+
+/// Returns [`ContractError::NotInitialized`] if `initialize()` was never called.
+pub fn get_admin(env: &Env) -> Result<Address, ContractError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(ContractError::NotInitialized)
+}
+
+pub fn set_admin(env: &Env, admin: &Address) {
+    env.storage().instance().set(&DataKey::Admin, admin);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Returns [`ContractError::NotInitialized`] if `initialize()` was never called.
+pub fn get_wasm_hash(env: &Env) -> Result<BytesN<32>, ContractError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::WasmHash)
+        .ok_or(ContractError::NotInitialized)
+}
+
+pub fn set_wasm_hash(env: &Env, wasm_hash: &BytesN<32>) {
+    env.storage().instance().set(&DataKey::WasmHash, wasm_hash);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Return `admin`'s next operation nonce (0 if it never made one).
+pub fn get_admin_nonce(env: &Env, admin: &Address) -> u64 {
+    let key = DataKey::AdminNonce(admin.clone());
+    let nonce: Option<u64> = env.storage().persistent().get(&key);
+    if nonce.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    nonce.unwrap_or(0)
+}
+
+pub fn set_admin_nonce(env: &Env, admin: &Address, nonce: u64) {
+    let key = DataKey::AdminNonce(admin.clone());
+    env.storage().persistent().set(&key, &nonce);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
+
+pub fn get_count(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::InstanceCount)
+        .unwrap_or(0u32)
+}
+
+// Instance helpers (persistent storage)
+
+/// Return the instance deployed for `merchant_id`, if any.
+pub fn get_instance(env: &Env, merchant_id: &String) -> Option<Address> {
+    let key = DataKey::Instance(merchant_id.clone());
+    let instance: Option<Address> = env.storage().persistent().get(&key);
+    if instance.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    }
+    instance
+}
+
+/// Record `instance` for `merchant_id` and bump the instance counter.
+pub fn add_instance(env: &Env, merchant_id: &String, instance: &Address) {
+    let key = DataKey::Instance(merchant_id.clone());
+    env.storage().persistent().set(&key, instance);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+
+    env.storage()
+        .instance()
+        .set(&DataKey::InstanceCount, &(get_count(env) + 1));
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
@@ -0,0 +1,173 @@
+#![cfg(test)]
+#![allow(clippy::all)]
+
+extern crate std;
+
+use super::*;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{contractclient, Address, Bytes, BytesN, Env, String};
+
+/// Built by `make build` in `contracts/invoice-payment`.
+const INVOICE_PAYMENT_WASM: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../target/wasm32v1-none/release/invoice_payment.wasm"
+);
+
+/// Read-only calls made on deployed instances in these tests.
+#[contractclient(name = "InstanceClient")]
+#[allow(dead_code)]
+trait InstanceInterface {
+    fn admin(env: Env) -> Address;
+    fn payment_count(env: Env) -> u32;
+}
+
+// Helpers
+
+fn setup(env: &Env, wasm_hash: &BytesN<32>) -> PaymentFactoryContractClient<'static> {
+    let contract_id = env.register(PaymentFactoryContract, ());
+    let client = PaymentFactoryContractClient::new(env, &contract_id);
+    client.initialize(&Address::generate(env), wasm_hash);
+    client
+}
+
+/// Upload the real `invoice-payment` WASM and return its hash.
+fn upload_invoice_payment(env: &Env) -> BytesN<32> {
+    let wasm = std::fs::read(INVOICE_PAYMENT_WASM)
+        .expect("invoice-payment WASM missing; run `make build` in contracts/invoice-payment");
+    env.deployer()
+        .upload_contract_wasm(Bytes::from_slice(env, &wasm))
+}
+
+fn id(env: &Env, merchant_id: &str) -> String {
+    String::from_str(env, merchant_id)
+}
+
+// Addressing
+
+#[test]
+fn test_predicted_address_is_deterministic_per_merchant() {
+    let env = Env::default();
+    let client = setup(&env, &BytesN::from_array(&env, &[0; 32]));
+
+    let acme = client.predicted_address(&id(&env, "acme"));
+    assert_eq!(client.predicted_address(&id(&env, "acme")), acme);
+    assert_ne!(client.predicted_address(&id(&env, "globex")), acme);
+    assert_eq!(client.instance_for(&id(&env, "acme")), None);
+    assert_eq!(client.instance_count(), 0);
+}
+
+#[test]
+fn test_invalid_merchant_id_is_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = setup(&env, &BytesN::from_array(&env, &[0; 32]));
+
+    for bad in ["", "acme corp", "m123456789012345678901234567890123"] {
+        assert_eq!(
+            client.try_predicted_address(&id(&env, bad)),
+            Err(Ok(ContractError::InvalidMerchantId))
+        );
+        assert_eq!(
+            client.try_deploy(&id(&env, bad), &Address::generate(&env)),
+            Err(Ok(ContractError::InvalidMerchantId))
+        );
+    }
+}
+
+// Admin
+
+#[test]
+fn test_initialize_twice_fails() {
+    let env = Env::default();
+    let hash = BytesN::from_array(&env, &[0; 32]);
+    let client = setup(&env, &hash);
+
+    assert_eq!(
+        client.try_initialize(&Address::generate(&env), &hash),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn test_set_wasm_hash() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = setup(&env, &BytesN::from_array(&env, &[0; 32]));
+    let next = BytesN::from_array(&env, &[1; 32]);
+
+    client.set_wasm_hash(&next, &0);
+    assert_eq!(client.wasm_hash(), next);
+}
+
+#[test]
+fn test_upgrades_and_transfers_take_the_admin_nonce() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = setup(&env, &BytesN::from_array(&env, &[0; 32]));
+    let old_admin = client.admin();
+    let stale = Err(Ok(ContractError::InvalidNonce));
+
+    assert_eq!(client.admin_nonce(), 0);
+    client.set_wasm_hash(&BytesN::from_array(&env, &[1; 32]), &0);
+    // The signed upgrade cannot be submitted a second time.
+    let old = BytesN::from_array(&env, &[0; 32]);
+    assert_eq!(client.try_set_wasm_hash(&old, &0), stale);
+    assert_eq!(client.wasm_hash(), BytesN::from_array(&env, &[1; 32]));
+
+    // Nonces are per admin: rotating away and back does not revive the
+    // transfer signed with nonce 1.
+    let new_admin = Address::generate(&env);
+    client.set_admin(&new_admin, &1);
+    assert_eq!(client.admin_nonce(), 0);
+    client.set_admin(&old_admin, &0);
+    assert_eq!(client.admin_nonce(), 2);
+    assert_eq!(client.try_set_admin(&new_admin, &1), stale);
+}
+
+#[test]
+fn test_deploy_requires_admin_auth() {
+    let env = Env::default();
+    let client = setup(&env, &BytesN::from_array(&env, &[0; 32]));
+
+    let result = client.try_deploy(&id(&env, "acme"), &Address::generate(&env));
+    assert!(result.is_err());
+}
+
+// Deployment (needs the invoice-payment WASM; `make test` builds it)
+
+#[test]
+#[ignore = "needs the invoice-payment WASM; run `make test`"]
+fn test_deploy_initialises_instance_at_predicted_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = setup(&env, &upload_invoice_payment(&env));
+    let merchant_admin = Address::generate(&env);
+    let predicted = client.predicted_address(&id(&env, "acme"));
+
+    let instance = client.deploy(&id(&env, "acme"), &merchant_admin);
+
+    assert_eq!(instance, predicted);
+    assert_eq!(client.instance_for(&id(&env, "acme")), Some(instance.clone()));
+    assert_eq!(client.instance_count(), 1);
+    let instance = InstanceClient::new(&env, &instance);
+    assert_eq!(instance.admin(), merchant_admin);
+    assert_eq!(instance.payment_count(), 0);
+}
+
+#[test]
+#[ignore = "needs the invoice-payment WASM; run `make test`"]
+fn test_deploy_isolates_merchants_and_rejects_redeploy() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = setup(&env, &upload_invoice_payment(&env));
+
+    let acme = client.deploy(&id(&env, "acme"), &Address::generate(&env));
+    let globex = client.deploy(&id(&env, "globex"), &Address::generate(&env));
+    assert_ne!(acme, globex);
+    assert_eq!(client.instance_count(), 2);
+
+    assert_eq!(
+        client.try_deploy(&id(&env, "acme"), &Address::generate(&env)),
+        Err(Ok(ContractError::InstanceAlreadyDeployed))
+    );
+}
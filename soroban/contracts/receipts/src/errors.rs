@@ -0,0 +1,28 @@
+use soroban_sdk::contracterror;
+
+/// Typed error codes for the receipts contract.
+///
+/// **Never reorder or remove codes** once deployed — only add new variants at
+/// the end.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ContractError {
+    /// `initialize()` was called on a contract that is already set up.
+    AlreadyInitialized = 1,
+
+    /// A method that requires admin or minter was called before `initialize()`.
+    NotInitialized = 2,
+
+    /// A receipt was already minted for this `invoice_id`.
+    ReceiptAlreadyMinted = 3,
+
+    /// No receipt exists for the requested token id or `invoice_id`.
+    ReceiptNotFound = 4,
+
+    /// `amount` was zero or negative.
+    InvalidAmount = 5,
+
+    /// Receipts are soulbound: `transfer` always fails with this code.
+    NonTransferable = 6,
+}
@@ -0,0 +1,14 @@
+use soroban_sdk::{contractevent, Env};
+
+use crate::storage::Receipt;
+
+/// A receipt was minted. Wallets and indexers that track this contract pick
+/// it up from here.
+#[contractevent]
+pub struct ReceiptMinted {
+    pub receipt: Receipt,
+}
+
+pub fn emit_receipt_minted(env: &Env, receipt: Receipt) {
+    ReceiptMinted { receipt }.publish(env);
+}
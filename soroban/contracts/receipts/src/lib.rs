@@ -0,0 +1,178 @@
+#![no_std]
+use soroban_sdk::{contract, contractimpl, Address, Env, String};
+
+pub mod errors;
+pub mod events;
+pub mod storage;
+
+pub use errors::ContractError;
+pub use storage::{Asset, DataKey, Receipt};
+
+use events::emit_receipt_minted;
+use storage::{
+    get_admin, get_balance, get_invoice_receipt, get_minter, get_receipt, get_supply, has_admin,
+    has_invoice_receipt, push_receipt, set_admin, set_minter,
+};
+
+// Contract
+
+/// # Invoisio Payment Receipts Contract
+///
+/// Mints one non-transferable receipt token per paid invoice to the payer, so
+/// proof of payment shows up in the payer's wallet. Minting is restricted to
+/// a single minter — the `invoice-payment` contract — which mints as part of
+/// recording a payment.
+///
+/// ## Module layout
+/// | Module       | Responsibility                            |
+/// |--------------|-------------------------------------------|
+/// | `errors.rs`  | `#[contracterror]` typed error codes      |
+/// | `storage.rs` | `DataKey`, `Receipt`, TTL helpers         |
+/// | `events.rs`  | Soroban event types and emit helpers      |
+/// | `lib.rs`     | Contract entry-points (this file)         |
+///
+/// ## Design decisions
+/// - **Soulbound:** there is no way to move a receipt; `transfer` exists only
+///   to fail with [`ContractError::NonTransferable`] for wallets that try.
+/// - **One receipt per invoice:** minting is idempotent by `invoice_id`.
+/// - **Sequential token ids:** ids start at 0 and equal the mint order.
+#[contract]
+pub struct ReceiptContract;
+
+#[contractimpl]
+impl ReceiptContract {
+    // Lifecycle
+
+    /// Initialise the contract with its `admin` and the `minter` allowed to
+    /// mint (the `invoice-payment` contract address).
+    ///
+    /// Returns [`ContractError::AlreadyInitialized`] if called a second time.
+    pub fn initialize(env: Env, admin: Address, minter: Address) -> Result<(), ContractError> {
+        if has_admin(&env) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        set_admin(&env, &admin);
+        set_minter(&env, &minter);
+        Ok(())
+    }
+
+    // Write
+
+    /// Mint a receipt for `invoice_id` to `owner` and return its token id.
+    /// Minter only. Emits `receipt_minted`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::NotInitialized`]
+    /// - [`ContractError::InvalidAmount`] — `amount` ≤ 0
+    /// - [`ContractError::ReceiptAlreadyMinted`] — `invoice_id` already has one
+    pub fn mint(
+        env: Env,
+        invoice_id: String,
+        owner: Address,
+        asset: Asset,
+        amount: i128,
+        paid_at: u64,
+    ) -> Result<u32, ContractError> {
+        let minter = get_minter(&env)?;
+        minter.require_auth();
+
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if has_invoice_receipt(&env, &invoice_id) {
+            return Err(ContractError::ReceiptAlreadyMinted);
+        }
+
+        let receipt = Receipt {
+            token_id: get_supply(&env),
+            invoice_id,
+            owner,
+            asset,
+            amount,
+            paid_at,
+        };
+        push_receipt(&env, &receipt);
+        let token_id = receipt.token_id;
+        emit_receipt_minted(&env, receipt);
+        Ok(token_id)
+    }
+
+    /// Always fails: receipts cannot change owner.
+    pub fn transfer(
+        _env: Env,
+        _from: Address,
+        _to: Address,
+        _token_id: u32,
+    ) -> Result<(), ContractError> {
+        Err(ContractError::NonTransferable)
+    }
+
+    // Read
+
+    /// Returns [`ContractError::ReceiptNotFound`] for an unknown token id.
+    pub fn receipt(env: Env, token_id: u32) -> Result<Receipt, ContractError> {
+        get_receipt(&env, token_id)
+    }
+
+    /// The receipt minted for `invoice_id`.
+    ///
+    /// Returns [`ContractError::ReceiptNotFound`] if none was minted.
+    pub fn receipt_for(env: Env, invoice_id: String) -> Result<Receipt, ContractError> {
+        let token_id = get_invoice_receipt(&env, &invoice_id)?;
+        get_receipt(&env, token_id)
+    }
+
+    /// Returns [`ContractError::ReceiptNotFound`] for an unknown token id.
+    pub fn owner_of(env: Env, token_id: u32) -> Result<Address, ContractError> {
+        Ok(get_receipt(&env, token_id)?.owner)
+    }
+
+    /// Number of receipts held by `owner`.
+    pub fn balance(env: Env, owner: Address) -> u32 {
+        get_balance(&env, &owner)
+    }
+
+    /// Number of receipts minted.
+    pub fn total_supply(env: Env) -> u32 {
+        get_supply(&env)
+    }
+
+    pub fn name(env: Env) -> String {
+        String::from_str(&env, "Invoisio Payment Receipt")
+    }
+
+    pub fn symbol(env: Env) -> String {
+        String::from_str(&env, "INVRCPT")
+    }
+
+    // Admin
+
+    /// Return the current admin address.
+    pub fn admin(env: Env) -> Result<Address, ContractError> {
+        get_admin(&env)
+    }
+
+    /// Return the address allowed to mint.
+    pub fn minter(env: Env) -> Result<Address, ContractError> {
+        get_minter(&env)
+    }
+
+    /// Replace the minter, e.g. after redeploying `invoice-payment`. Admin only.
+    pub fn set_minter(env: Env, minter: Address) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        set_minter(&env, &minter);
+        Ok(())
+    }
+
+    /// Transfer admin rights. Both the current and the new admin must sign.
+    pub fn set_admin(env: Env, new_admin: Address) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        new_admin.require_auth();
+        set_admin(&env, &new_admin);
+        Ok(())
+    }
+}
+
+mod test;
@@ -0,0 +1,167 @@
+use soroban_sdk::{contracttype, Address, Env, String};
+
+use crate::errors::ContractError;
+
+// TTL budget — same policy as `invoice-payment`:
+//   MIN_TTL  = 17 280 ledgers ≈ 1 day
+//   BUMP_TTL = 518 400 ledgers ≈ 30 days
+
+pub const MIN_TTL: u32 = 17_280;
+pub const BUMP_TTL: u32 = 518_400;
+
+// Storage keys
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    /// Admin [`Address`] in **instance** storage.
+    Admin,
+    /// The only address allowed to mint, in **instance** storage. In the
+    /// Invoisio deployment this is the `invoice-payment` contract.
+    Minter,
+    /// Number of receipts minted so far (= next token id), in **instance**
+    /// storage.
+    Supply,
+    /// A [`Receipt`] indexed by token id in **persistent** storage.
+    Receipt(u32),
+    /// Token id of the receipt for an `invoice_id`, in **persistent** storage.
+    InvoiceReceipt(String),
+    /// Number of receipts held by an owner, in **persistent** storage.
+    Balance(Address),
+}
+
+// Data structures
+
+/// Asset the invoice was paid in. Same encoding as `invoice-payment`'s
+/// `Asset`, so values cross the contract boundary unchanged.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Asset {
+    Native,
+    /// `(asset_code, issuer)`.
+    Token(String, String),
+}
+
+/// Wallet-visible proof that `owner` paid `invoice_id`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Receipt {
+    /// Sequential token id, starting at 0.
+    pub token_id: u32,
+    pub invoice_id: String,
+    /// The payer. Receipts never change owner.
+    pub owner: Address,
+    pub asset: Asset,
+    /// Amount paid in the asset's smallest unit.
+    pub amount: i128,
+    /// Unix timestamp of the payment, as recorded by `invoice-payment`.
+    pub paid_at: u64,
+}
+
+// Admin / minter helpers (instance storage)
+
+pub fn has_admin(env: &Env) -> bool {
+    env.storage().instance().has(&DataKey::Admin)
+}
+
+/// Returns [`ContractError::NotInitialized`] if `initialize()` was never called.
+pub fn get_admin(env: &Env) -> Result<Address, ContractError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(ContractError::NotInitialized)
+}
+
+pub fn set_admin(env: &Env, admin: &Address) {
+    env.storage().instance().set(&DataKey::Admin, admin);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+/// Returns [`ContractError::NotInitialized`] if `initialize()` was never called.
+pub fn get_minter(env: &Env) -> Result<Address, ContractError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Minter)
+        .ok_or(ContractError::NotInitialized)
+}
+
+pub fn set_minter(env: &Env, minter: &Address) {
+    env.storage().instance().set(&DataKey::Minter, minter);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+pub fn get_supply(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::Supply)
+        .unwrap_or(0u32)
+}
+
+fn set_supply(env: &Env, supply: u32) {
+    env.storage().instance().set(&DataKey::Supply, &supply);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+// Receipt helpers (persistent storage)
+
+/// Returns [`ContractError::ReceiptNotFound`] if `token_id` was never minted.
+pub fn get_receipt(env: &Env, token_id: u32) -> Result<Receipt, ContractError> {
+    let key = DataKey::Receipt(token_id);
+    let receipt: Receipt = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::ReceiptNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(receipt)
+}
+
+/// Returns [`ContractError::ReceiptNotFound`] if no receipt was minted for
+/// `invoice_id`.
+pub fn get_invoice_receipt(env: &Env, invoice_id: &String) -> Result<u32, ContractError> {
+    let key = DataKey::InvoiceReceipt(invoice_id.clone());
+    let token_id: u32 = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::ReceiptNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(token_id)
+}
+
+pub fn has_invoice_receipt(env: &Env, invoice_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::InvoiceReceipt(invoice_id.clone()))
+}
+
+pub fn get_balance(env: &Env, owner: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Balance(owner.clone()))
+        .unwrap_or(0u32)
+}
+
+/// Store `receipt` under the next token id, index it by invoice and credit
+/// the owner's balance. The caller has checked for duplicates.
+pub fn push_receipt(env: &Env, receipt: &Receipt) {
+    let persistent = env.storage().persistent();
+
+    let key = DataKey::Receipt(receipt.token_id);
+    persistent.set(&key, receipt);
+    persistent.extend_ttl(&key, MIN_TTL, BUMP_TTL);
+
+    let key = DataKey::InvoiceReceipt(receipt.invoice_id.clone());
+    persistent.set(&key, &receipt.token_id);
+    persistent.extend_ttl(&key, MIN_TTL, BUMP_TTL);
+
+    let key = DataKey::Balance(receipt.owner.clone());
+    persistent.set(&key, &(get_balance(env, &receipt.owner) + 1));
+    persistent.extend_ttl(&key, MIN_TTL, BUMP_TTL);
+
+    set_supply(env, receipt.token_id + 1);
+}
@@ -0,0 +1,161 @@
+#![cfg(test)]
+#![allow(clippy::all)]
+
+use super::*;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Env, String};
+
+// Helpers
+
+fn setup(env: &Env) -> (ReceiptContractClient<'_>, Address) {
+    let admin = Address::generate(env);
+    let minter = Address::generate(env);
+    let contract_id = env.register(ReceiptContract, ());
+    let client = ReceiptContractClient::new(env, &contract_id);
+    client.initialize(&admin, &minter);
+    (client, minter)
+}
+
+fn mint(env: &Env, client: &ReceiptContractClient, invoice_id: &str, owner: &Address) -> u32 {
+    client.mint(
+        &String::from_str(env, invoice_id),
+        owner,
+        &Asset::Native,
+        &10_000_000i128,
+        &1_700_000_000u64,
+    )
+}
+
+// Mint
+
+#[test]
+fn test_mint_stores_receipt() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _minter) = setup(&env);
+    let owner = Address::generate(&env);
+
+    assert_eq!(mint(&env, &client, "invoisio-001", &owner), 0);
+    assert_eq!(mint(&env, &client, "invoisio-002", &owner), 1);
+
+    let receipt = client.receipt_for(&String::from_str(&env, "invoisio-002"));
+    assert_eq!(receipt.token_id, 1);
+    assert_eq!(receipt.owner, owner);
+    assert_eq!(receipt.amount, 10_000_000);
+    assert_eq!(receipt.paid_at, 1_700_000_000);
+    assert_eq!(client.receipt(&1), receipt);
+    assert_eq!(client.owner_of(&0), owner);
+    assert_eq!(client.balance(&owner), 2);
+    assert_eq!(client.total_supply(), 2);
+}
+
+#[test]
+fn test_mint_is_idempotent_by_invoice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _minter) = setup(&env);
+    let owner = Address::generate(&env);
+    mint(&env, &client, "invoisio-001", &owner);
+
+    let result = client.try_mint(
+        &String::from_str(&env, "invoisio-001"),
+        &owner,
+        &Asset::Native,
+        &1,
+        &0,
+    );
+    assert_eq!(result, Err(Ok(ContractError::ReceiptAlreadyMinted)));
+    assert_eq!(client.total_supply(), 1);
+}
+
+#[test]
+fn test_mint_rejects_non_positive_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _minter) = setup(&env);
+
+    let result = client.try_mint(
+        &String::from_str(&env, "invoisio-001"),
+        &Address::generate(&env),
+        &Asset::Native,
+        &0,
+        &0,
+    );
+    assert_eq!(result, Err(Ok(ContractError::InvalidAmount)));
+}
+
+#[test]
+fn test_mint_requires_minter_auth() {
+    use soroban_sdk::testutils::AuthorizedFunction;
+    use soroban_sdk::Symbol;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, minter) = setup(&env);
+    mint(&env, &client, "invoisio-001", &Address::generate(&env));
+
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    let (address, invocation) = &auths[0];
+    assert_eq!(*address, minter);
+    match &invocation.function {
+        AuthorizedFunction::Contract((contract, name, _args)) => {
+            assert_eq!(*contract, client.address);
+            assert_eq!(*name, Symbol::new(&env, "mint"));
+        }
+        _ => panic!("unexpected authorized function"),
+    }
+}
+
+// Soulbound
+
+#[test]
+fn test_transfer_always_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _minter) = setup(&env);
+    let owner = Address::generate(&env);
+    let token_id = mint(&env, &client, "invoisio-001", &owner);
+
+    let result = client.try_transfer(&owner, &Address::generate(&env), &token_id);
+    assert_eq!(result, Err(Ok(ContractError::NonTransferable)));
+    assert_eq!(client.owner_of(&token_id), owner);
+}
+
+// Reads
+
+#[test]
+fn test_unknown_receipt() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _minter) = setup(&env);
+
+    assert_eq!(client.try_receipt(&0), Err(Ok(ContractError::ReceiptNotFound)));
+    assert_eq!(
+        client.try_receipt_for(&String::from_str(&env, "invoisio-404")),
+        Err(Ok(ContractError::ReceiptNotFound))
+    );
+    assert_eq!(client.balance(&Address::generate(&env)), 0);
+}
+
+// Admin
+
+#[test]
+fn test_set_minter() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _minter) = setup(&env);
+    let next = Address::generate(&env);
+
+    client.set_minter(&next);
+    assert_eq!(client.minter(), next);
+}
+
+#[test]
+fn test_initialize_twice_fails() {
+    let env = Env::default();
+    let (client, minter) = setup(&env);
+
+    let result = client.try_initialize(&Address::generate(&env), &minter);
+    assert_eq!(result, Err(Ok(ContractError::AlreadyInitialized)));
+}
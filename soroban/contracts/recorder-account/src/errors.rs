@@ -0,0 +1,40 @@
+use soroban_sdk::contracterror;
+
+/// Typed error codes for the recorder account contract.
+///
+/// **Never reorder or remove codes** once deployed — error codes are part of
+/// the on-chain ABI. Only add new variants at the end.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ContractError {
+    /// `initialize()` was called on a contract that is already set up.
+    AlreadyInitialized = 1,
+
+    /// The account was used before `initialize()`.
+    NotInitialized = 2,
+
+    /// The signer set is empty, larger than `MAX_SIGNERS`, or has duplicates.
+    InvalidSigners = 3,
+
+    /// `threshold` is zero or larger than the number of signers.
+    InvalidThreshold = 4,
+
+    /// A signature is from a key that is not a signer.
+    UnknownSigner = 5,
+
+    /// Signatures are not sorted by public key, or a key signed twice.
+    SignaturesOutOfOrder = 6,
+
+    /// Fewer valid signatures than the threshold.
+    NotEnoughSignatures = 7,
+
+    /// Authorizing the call would exceed today's record limit.
+    RecordLimitExceeded = 8,
+
+    /// Authorizing the call would exceed today's spend limit for the token.
+    SpendLimitExceeded = 9,
+
+    /// A limit was negative, or a token amount could not be read.
+    InvalidAmount = 10,
+}
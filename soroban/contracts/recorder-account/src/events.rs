@@ -0,0 +1,30 @@
+use soroban_sdk::{contractevent, Address, Env};
+
+#[contractevent]
+pub struct SignersUpdated {
+    pub signers: u32,
+    pub threshold: u32,
+}
+
+#[contractevent]
+pub struct RecordLimitUpdated {
+    pub limit: Option<u32>,
+}
+
+#[contractevent]
+pub struct SpendLimitUpdated {
+    pub token: Address,
+    pub limit: Option<i128>,
+}
+
+pub fn emit_signers_updated(env: &Env, signers: u32, threshold: u32) {
+    SignersUpdated { signers, threshold }.publish(env);
+}
+
+pub fn emit_record_limit_updated(env: &Env, limit: Option<u32>) {
+    RecordLimitUpdated { limit }.publish(env);
+}
+
+pub fn emit_spend_limit_updated(env: &Env, token: Address, limit: Option<i128>) {
+    SpendLimitUpdated { token, limit }.publish(env);
+}
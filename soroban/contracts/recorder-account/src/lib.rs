@@ -0,0 +1,292 @@
+#![no_std]
+use soroban_sdk::{
+    auth::{Context, ContractContext, CustomAccountInterface},
+    contract, contractimpl,
+    crypto::Hash,
+    Address, BytesN, Env, Symbol, TryIntoVal, Vec,
+};
+
+pub mod errors;
+pub mod events;
+pub mod storage;
+
+pub use errors::ContractError;
+pub use storage::{AccountSignature, DataKey};
+
+use events::{emit_record_limit_updated, emit_signers_updated, emit_spend_limit_updated};
+use storage::{
+    get_record_limit, get_recorded, get_signers, get_spend_limit, get_spent, get_threshold,
+    is_initialized, set_policy, set_record_limit, set_recorded, set_spend_limit, set_spent,
+    SECS_PER_DAY,
+};
+
+/// Most signers the account can have.
+pub const MAX_SIGNERS: u32 = 10;
+
+/// `invoice-payment` entry points that put a payment on record, counted
+/// against the daily record limit.
+const RECORD_FNS: [&str; 7] = [
+    "record_payment",
+    "record_merchant_payment",
+    "record_invoice_payment",
+    "record_backdated_payment",
+    "record_hashed_payment",
+    "record_external_payment",
+    "confirm_pending_payment",
+];
+
+// Contract
+
+/// # Invoisio Recorder Account
+///
+/// A custom account (smart wallet) meant to be the admin of
+/// `invoice-payment`, for operations that need more than one classic
+/// keypair can express.
+///
+/// ## Module layout
+/// | Module       | Responsibility                               |
+/// |--------------|----------------------------------------------|
+/// | `errors.rs`  | `#[contracterror]` typed error codes         |
+/// | `storage.rs` | `DataKey`, `AccountSignature`, TTL helpers   |
+/// | `events.rs`  | Soroban event types and emit helpers         |
+/// | `lib.rs`     | Entry points and `__check_auth` (this file)  |
+///
+/// ## Design decisions
+/// - **M-of-N ed25519 signers:** every authorization needs `threshold`
+///   signatures from distinct signers, e.g. 2 of 3 so one lost or leaked
+///   key neither blocks nor compromises the account.
+/// - **Daily limits:** optional caps on recording calls and on the amount
+///   of each token spent per UTC day, enforced while authorizing so even a
+///   quorum of signers cannot exceed them in a day.
+/// - **Self-governed:** changing signers or limits is a call on the account
+///   itself, so it needs the same quorum; limits do not apply to it.
+#[contract]
+pub struct RecorderAccount;
+
+#[contractimpl]
+impl RecorderAccount {
+    // Lifecycle
+
+    /// Set up the account with its `signers` and the `threshold` of
+    /// signatures required.
+    ///
+    /// ## Errors
+    /// - [`ContractError::AlreadyInitialized`]
+    /// - [`ContractError::InvalidSigners`] / [`ContractError::InvalidThreshold`]
+    pub fn initialize(
+        env: Env,
+        signers: Vec<BytesN<32>>,
+        threshold: u32,
+    ) -> Result<(), ContractError> {
+        if is_initialized(&env) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        update_signers(&env, signers, threshold)
+    }
+
+    // Policy (authorized by the account itself)
+
+    /// Replace the signers and threshold. Needs the current quorum. Emits
+    /// `signers_updated`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::InvalidSigners`] / [`ContractError::InvalidThreshold`]
+    pub fn set_signers(
+        env: Env,
+        signers: Vec<BytesN<32>>,
+        threshold: u32,
+    ) -> Result<(), ContractError> {
+        env.current_contract_address().require_auth();
+        update_signers(&env, signers, threshold)
+    }
+
+    /// Cap the payment-recording calls authorized per UTC day, or lift the
+    /// cap with `None`. Needs the quorum. Emits `record_limit_updated`.
+    pub fn set_record_limit(env: Env, limit: Option<u32>) {
+        env.current_contract_address().require_auth();
+        set_record_limit(&env, &limit);
+        emit_record_limit_updated(&env, limit);
+    }
+
+    /// Cap the amount of `token` transferred, approved or burnt per UTC
+    /// day, or lift the cap with `None`. Needs the quorum. Emits
+    /// `spend_limit_updated`.
+    ///
+    /// Returns [`ContractError::InvalidAmount`] if `limit` is negative.
+    pub fn set_spend_limit(
+        env: Env,
+        token: Address,
+        limit: Option<i128>,
+    ) -> Result<(), ContractError> {
+        env.current_contract_address().require_auth();
+        if matches!(limit, Some(limit) if limit < 0) {
+            return Err(ContractError::InvalidAmount);
+        }
+        set_spend_limit(&env, &token, &limit);
+        emit_spend_limit_updated(&env, token, limit);
+        Ok(())
+    }
+
+    // Read
+
+    pub fn signers(env: Env) -> Result<Vec<BytesN<32>>, ContractError> {
+        get_signers(&env)
+    }
+
+    pub fn threshold(env: Env) -> u32 {
+        get_threshold(&env)
+    }
+
+    pub fn record_limit(env: Env) -> Option<u32> {
+        get_record_limit(&env)
+    }
+
+    pub fn spend_limit(env: Env, token: Address) -> Option<i128> {
+        get_spend_limit(&env, &token)
+    }
+
+    /// Return the recording calls authorized so far today (UTC).
+    pub fn recorded_today(env: Env) -> u32 {
+        get_recorded(&env, today(&env))
+    }
+
+    /// Return the amount of `token` spent so far today (UTC).
+    pub fn spent_today(env: Env, token: Address) -> i128 {
+        get_spent(&env, &token, today(&env))
+    }
+}
+
+#[contractimpl]
+impl CustomAccountInterface for RecorderAccount {
+    type Signature = Vec<AccountSignature>;
+    type Error = ContractError;
+
+    /// Authorize `auth_contexts` if `signatures` holds at least `threshold`
+    /// valid signatures over `signature_payload` from distinct signers,
+    /// sorted by public key, and the calls fit within today's limits.
+    ///
+    /// An invalid signature aborts the invocation rather than returning an
+    /// error.
+    #[allow(non_snake_case)]
+    fn __check_auth(
+        env: Env,
+        signature_payload: Hash<32>,
+        signatures: Vec<AccountSignature>,
+        auth_contexts: Vec<Context>,
+    ) -> Result<(), ContractError> {
+        check_signatures(&env, &signature_payload, &signatures)?;
+        for context in auth_contexts.iter() {
+            if let Context::Contract(call) = context {
+                check_limits(&env, &call)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Helpers
+
+/// Validate and store a new signer set and threshold.
+fn update_signers(
+    env: &Env,
+    signers: Vec<BytesN<32>>,
+    threshold: u32,
+) -> Result<(), ContractError> {
+    if signers.is_empty() || signers.len() > MAX_SIGNERS {
+        return Err(ContractError::InvalidSigners);
+    }
+    for (i, signer) in signers.iter().enumerate() {
+        if signers.first_index_of(&signer) != Some(i as u32) {
+            return Err(ContractError::InvalidSigners);
+        }
+    }
+    if threshold == 0 || threshold > signers.len() {
+        return Err(ContractError::InvalidThreshold);
+    }
+    set_policy(env, &signers, threshold);
+    emit_signers_updated(env, signers.len(), threshold);
+    Ok(())
+}
+
+/// Verify `signatures` over `payload` against the signer set and threshold.
+fn check_signatures(
+    env: &Env,
+    payload: &Hash<32>,
+    signatures: &Vec<AccountSignature>,
+) -> Result<(), ContractError> {
+    let signers = get_signers(env)?;
+    let mut previous: Option<BytesN<32>> = None;
+    for signature in signatures.iter() {
+        if previous.is_some_and(|previous| previous >= signature.public_key) {
+            return Err(ContractError::SignaturesOutOfOrder);
+        }
+        if !signers.contains(&signature.public_key) {
+            return Err(ContractError::UnknownSigner);
+        }
+        env.crypto().ed25519_verify(
+            &signature.public_key,
+            &payload.clone().into(),
+            &signature.signature,
+        );
+        previous = Some(signature.public_key);
+    }
+    if signatures.len() < get_threshold(env) {
+        return Err(ContractError::NotEnoughSignatures);
+    }
+    Ok(())
+}
+
+/// Count `call` against today's record or spend limit. Calls on the
+/// account itself (policy changes) are never limited.
+fn check_limits(env: &Env, call: &ContractContext) -> Result<(), ContractError> {
+    if call.contract == env.current_contract_address() {
+        return Ok(());
+    }
+    let day = today(env);
+
+    if RECORD_FNS
+        .iter()
+        .any(|name| call.fn_name == Symbol::new(env, name))
+    {
+        let recorded = get_recorded(env, day) + 1;
+        if matches!(get_record_limit(env), Some(limit) if recorded > limit) {
+            return Err(ContractError::RecordLimitExceeded);
+        }
+        set_recorded(env, day, recorded);
+        return Ok(());
+    }
+
+    // `transfer(from, to, amount)`, `approve(from, spender, amount, ..)`,
+    // `burn(from, amount)`.
+    let amount_arg = if call.fn_name == Symbol::new(env, "transfer")
+        || call.fn_name == Symbol::new(env, "approve")
+    {
+        2
+    } else if call.fn_name == Symbol::new(env, "burn") {
+        1
+    } else {
+        return Ok(());
+    };
+    let Some(limit) = get_spend_limit(env, &call.contract) else {
+        return Ok(());
+    };
+    let amount: i128 = call
+        .args
+        .get(amount_arg)
+        .ok_or(ContractError::InvalidAmount)?
+        .try_into_val(env)
+        .map_err(|_| ContractError::InvalidAmount)?;
+    let spent = get_spent(env, &call.contract, day)
+        .checked_add(amount.max(0))
+        .filter(|spent| *spent <= limit)
+        .ok_or(ContractError::SpendLimitExceeded)?;
+    set_spent(env, &call.contract, day, spent);
+    Ok(())
+}
+
+/// Current UTC day number.
+fn today(env: &Env) -> u64 {
+    env.ledger().timestamp() / SECS_PER_DAY
+}
+
+mod test;
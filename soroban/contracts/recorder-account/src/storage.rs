@@ -0,0 +1,136 @@
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+use crate::errors::ContractError;
+
+// TTL budget — same policy as `invoice-payment`:
+//   MIN_TTL  = 17 280 ledgers ≈ 1 day
+//   BUMP_TTL = 518 400 ledgers ≈ 30 days
+
+pub const MIN_TTL: u32 = 17_280;
+pub const BUMP_TTL: u32 = 518_400;
+
+/// Lifetime of a daily usage counter: long enough to outlive its day.
+pub const DAY_TTL: u32 = 2 * MIN_TTL;
+
+/// Seconds in a UTC day, the window limits reset over.
+pub const SECS_PER_DAY: u64 = 86_400;
+
+// Storage keys
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    /// Ed25519 public keys (`Vec<BytesN<32>>`) allowed to sign, in
+    /// **instance** storage.
+    Signers,
+    /// Number of signatures (`u32`) needed to authorize anything, in
+    /// **instance** storage.
+    Threshold,
+    /// Max payment-recording calls per day (`u32`), in **instance** storage.
+    /// Absent = unlimited.
+    RecordLimit,
+    /// Max amount of a token spent per day (`i128`), in **instance**
+    /// storage. Absent = unlimited.
+    SpendLimit(Address),
+    /// Recording calls authorized on a day (`u32`), in **temporary** storage.
+    Recorded(u64),
+    /// Amount of a token spent on a day (`i128`), in **temporary** storage.
+    Spent(Address, u64),
+}
+
+// Data structures
+
+/// One signer's signature over the authorization payload.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountSignature {
+    pub public_key: BytesN<32>,
+    pub signature: BytesN<64>,
+}
+
+// Policy helpers (instance storage)
+
+pub fn is_initialized(env: &Env) -> bool {
+    env.storage().instance().has(&DataKey::Signers)
+}
+
+/// Returns [`ContractError::NotInitialized`] if `initialize()` was never called.
+pub fn get_signers(env: &Env) -> Result<Vec<BytesN<32>>, ContractError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Signers)
+        .ok_or(ContractError::NotInitialized)
+}
+
+pub fn get_threshold(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::Threshold)
+        .unwrap_or(0)
+}
+
+/// Store the signer set and threshold and extend instance TTL.
+pub fn set_policy(env: &Env, signers: &Vec<BytesN<32>>, threshold: u32) {
+    env.storage().instance().set(&DataKey::Signers, signers);
+    env.storage().instance().set(&DataKey::Threshold, &threshold);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+pub fn get_record_limit(env: &Env) -> Option<u32> {
+    env.storage().instance().get(&DataKey::RecordLimit)
+}
+
+/// Set or clear the daily record limit and extend instance TTL.
+pub fn set_record_limit(env: &Env, limit: &Option<u32>) {
+    match limit {
+        Some(limit) => env.storage().instance().set(&DataKey::RecordLimit, limit),
+        None => env.storage().instance().remove(&DataKey::RecordLimit),
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+pub fn get_spend_limit(env: &Env, token: &Address) -> Option<i128> {
+    env.storage()
+        .instance()
+        .get(&DataKey::SpendLimit(token.clone()))
+}
+
+/// Set or clear the daily spend limit of `token` and extend instance TTL.
+pub fn set_spend_limit(env: &Env, token: &Address, limit: &Option<i128>) {
+    let key = DataKey::SpendLimit(token.clone());
+    match limit {
+        Some(limit) => env.storage().instance().set(&key, limit),
+        None => env.storage().instance().remove(&key),
+    }
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+// Usage helpers (temporary storage)
+
+/// Return the recording calls authorized on `day` (0 if none).
+pub fn get_recorded(env: &Env, day: u64) -> u32 {
+    env.storage()
+        .temporary()
+        .get(&DataKey::Recorded(day))
+        .unwrap_or(0)
+}
+
+pub fn set_recorded(env: &Env, day: u64, count: u32) {
+    let key = DataKey::Recorded(day);
+    env.storage().temporary().set(&key, &count);
+    env.storage().temporary().extend_ttl(&key, DAY_TTL, DAY_TTL);
+}
+
+/// Return the amount of `token` spent on `day` (0 if none).
+pub fn get_spent(env: &Env, token: &Address, day: u64) -> i128 {
+    env.storage()
+        .temporary()
+        .get(&DataKey::Spent(token.clone(), day))
+        .unwrap_or(0)
+}
+
+pub fn set_spent(env: &Env, token: &Address, day: u64, amount: i128) {
+    let key = DataKey::Spent(token.clone(), day);
+    env.storage().temporary().set(&key, &amount);
+    env.storage().temporary().extend_ttl(&key, DAY_TTL, DAY_TTL);
+}
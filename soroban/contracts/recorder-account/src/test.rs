@@ -0,0 +1,178 @@
+#![cfg(test)]
+#![allow(clippy::all)]
+
+extern crate std;
+
+use super::*;
+use ed25519_dalek::{Signer as _, SigningKey};
+use soroban_sdk::testutils::{Address as _, Ledger as _};
+use soroban_sdk::{vec, Address, BytesN, Env, IntoVal, Symbol, Val, Vec};
+
+const START: u64 = 1_700_000_000;
+
+// Helpers
+
+struct Setup<'a> {
+    client: RecorderAccountClient<'a>,
+    keys: std::vec::Vec<SigningKey>,
+}
+
+/// A 2-of-3 account; `keys` are sorted by public key.
+fn setup(env: &Env) -> Setup<'_> {
+    env.ledger().with_mut(|li| li.timestamp = START);
+    let contract_id = env.register(RecorderAccount, ());
+    let client = RecorderAccountClient::new(env, &contract_id);
+
+    let mut keys: std::vec::Vec<SigningKey> =
+        (1u8..=3).map(|n| SigningKey::from_bytes(&[n; 32])).collect();
+    keys.sort_by_key(|k| k.verifying_key().to_bytes());
+    let signers = Vec::from_iter(env, keys.iter().map(|k| public_key(env, k)));
+    client.initialize(&signers, &2);
+    Setup { client, keys }
+}
+
+fn public_key(env: &Env, key: &SigningKey) -> BytesN<32> {
+    BytesN::from_array(env, &key.verifying_key().to_bytes())
+}
+
+fn payload(env: &Env) -> BytesN<32> {
+    BytesN::from_array(env, &[42; 32])
+}
+
+fn sign(env: &Env, keys: &[&SigningKey]) -> Val {
+    let mut signatures = Vec::<AccountSignature>::new(env);
+    for key in keys {
+        signatures.push_back(AccountSignature {
+            public_key: public_key(env, key),
+            signature: BytesN::from_array(env, &key.sign(&[42; 32]).to_bytes()),
+        });
+    }
+    signatures.into_val(env)
+}
+
+fn call(env: &Env, contract: &Address, fn_name: &str, args: Vec<Val>) -> Vec<Context> {
+    vec![
+        env,
+        Context::Contract(ContractContext {
+            contract: contract.clone(),
+            fn_name: Symbol::new(env, fn_name),
+            args,
+        }),
+    ]
+}
+
+fn check_auth(
+    env: &Env,
+    s: &Setup,
+    signers: &[&SigningKey],
+    contexts: &Vec<Context>,
+) -> Result<(), ContractError> {
+    env.try_invoke_contract_check_auth::<ContractError>(
+        &s.client.address,
+        &payload(env),
+        sign(env, signers),
+        contexts,
+    )
+    .map_err(|e| e.expect("host error"))
+}
+
+// Signers
+
+#[test]
+fn test_initialize_validates_policy() {
+    let env = Env::default();
+    let contract_id = env.register(RecorderAccount, ());
+    let client = RecorderAccountClient::new(&env, &contract_id);
+    let key = BytesN::from_array(&env, &[1; 32]);
+
+    assert_eq!(
+        client.try_initialize(&vec![&env, key.clone(), key.clone()], &1),
+        Err(Ok(ContractError::InvalidSigners))
+    );
+    assert_eq!(
+        client.try_initialize(&vec![&env, key.clone()], &2),
+        Err(Ok(ContractError::InvalidThreshold))
+    );
+    client.initialize(&vec![&env, key.clone()], &1);
+    assert_eq!(
+        client.try_initialize(&vec![&env, key], &1),
+        Err(Ok(ContractError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn test_two_of_three_signatures_are_required() {
+    let env = Env::default();
+    let s = setup(&env);
+    let payments = Address::generate(&env);
+    let record = call(&env, &payments, "record_payment", Vec::new(&env));
+    let [a, b, c] = [&s.keys[0], &s.keys[1], &s.keys[2]];
+
+    assert_eq!(check_auth(&env, &s, &[a], &record), Err(ContractError::NotEnoughSignatures));
+    assert_eq!(check_auth(&env, &s, &[b, a], &record), Err(ContractError::SignaturesOutOfOrder));
+    assert_eq!(check_auth(&env, &s, &[a, a], &record), Err(ContractError::SignaturesOutOfOrder));
+    let stranger = SigningKey::from_bytes(&[9; 32]);
+    assert_eq!(check_auth(&env, &s, &[&stranger], &record), Err(ContractError::UnknownSigner));
+
+    assert_eq!(check_auth(&env, &s, &[a, c], &record), Ok(()));
+    assert_eq!(check_auth(&env, &s, &[a, b, c], &record), Ok(()));
+}
+
+// Limits
+
+#[test]
+fn test_record_limit_resets_daily() {
+    let env = Env::default();
+    let s = setup(&env);
+    env.mock_all_auths();
+    s.client.set_record_limit(&Some(2));
+    let payments = Address::generate(&env);
+    let record = call(&env, &payments, "record_payment", Vec::new(&env));
+    let other = call(&env, &payments, "set_metadata", Vec::new(&env));
+    let quorum = [&s.keys[0], &s.keys[1]];
+
+    assert_eq!(check_auth(&env, &s, &quorum, &record), Ok(()));
+    assert_eq!(check_auth(&env, &s, &quorum, &other), Ok(()));
+    assert_eq!(check_auth(&env, &s, &quorum, &record), Ok(()));
+    assert_eq!(s.client.recorded_today(), 2);
+    assert_eq!(
+        check_auth(&env, &s, &quorum, &record),
+        Err(ContractError::RecordLimitExceeded)
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = START + 86_400);
+    assert_eq!(check_auth(&env, &s, &quorum, &record), Ok(()));
+}
+
+#[test]
+fn test_spend_limit_caps_token_transfers() {
+    let env = Env::default();
+    let s = setup(&env);
+    env.mock_all_auths();
+    let token = Address::generate(&env);
+    s.client.set_spend_limit(&token, &Some(100));
+    let to = Address::generate(&env);
+    let transfer = |amount: i128| {
+        let args = vec![
+            &env,
+            s.client.address.into_val(&env),
+            to.into_val(&env),
+            amount.into_val(&env),
+        ];
+        call(&env, &token, "transfer", args)
+    };
+    let quorum = [&s.keys[1], &s.keys[2]];
+
+    assert_eq!(check_auth(&env, &s, &quorum, &transfer(60)), Ok(()));
+    assert_eq!(
+        check_auth(&env, &s, &quorum, &transfer(41)),
+        Err(ContractError::SpendLimitExceeded)
+    );
+    assert_eq!(check_auth(&env, &s, &quorum, &transfer(40)), Ok(()));
+    assert_eq!(s.client.spent_today(&token), 100);
+
+    assert_eq!(
+        s.client.try_set_spend_limit(&token, &Some(-1)),
+        Err(Ok(ContractError::InvalidAmount))
+    );
+}
@@ -0,0 +1,41 @@
+use soroban_sdk::contracterror;
+
+/// Typed error codes for the subscriptions contract.
+///
+/// **Never reorder or remove codes** once deployed — error codes are part of
+/// the on-chain ABI. Only add new variants at the end.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ContractError {
+    /// `initialize()` was called on a contract that is already set up.
+    AlreadyInitialized = 1,
+
+    /// A method that requires admin was called before `initialize()`.
+    NotInitialized = 2,
+
+    /// `create_plan()` was called with a `plan_id` that already exists.
+    PlanAlreadyExists = 3,
+
+    /// No plan exists for the `plan_id`.
+    PlanNotFound = 4,
+
+    /// `plan_id` was empty, longer than [`MAX_PLAN_ID_LEN`](crate::storage::MAX_PLAN_ID_LEN)
+    /// bytes, or contained a character other than `[A-Za-z0-9_-]`.
+    InvalidPlanId = 5,
+
+    /// `amount` was zero or negative.
+    InvalidAmount = 6,
+
+    /// `asset_code` was empty, XLM was given an issuer, or a token had none.
+    InvalidAsset = 7,
+
+    /// `interval_secs` was zero.
+    InvalidInterval = 8,
+
+    /// The plan was cancelled; no further cycles are issued.
+    PlanCancelled = 9,
+
+    /// `issue_invoice()` was called before the next cycle's start time.
+    CycleNotDue = 10,
+}
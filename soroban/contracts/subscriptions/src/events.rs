@@ -0,0 +1,48 @@
+use soroban_sdk::{contractevent, Address, Env, String};
+
+use crate::storage::{Asset, Plan};
+
+#[contractevent]
+pub struct PlanCreated {
+    pub plan: Plan,
+}
+
+#[contractevent]
+pub struct PlanCancelled {
+    pub plan_id: String,
+}
+
+/// One billing cycle's invoice. The backend creates the invoice under
+/// `invoice_id` and, once paid, records it in `invoice-payment` as usual.
+#[contractevent]
+pub struct InvoiceIssued {
+    pub plan_id: String,
+    pub cycle: u32,
+    pub invoice_id: String,
+    pub payer: Address,
+    pub asset: Asset,
+    pub amount: i128,
+    /// Unix timestamp the cycle became due.
+    pub due_at: u64,
+}
+
+pub fn emit_plan_created(env: &Env, plan: Plan) {
+    PlanCreated { plan }.publish(env);
+}
+
+pub fn emit_plan_cancelled(env: &Env, plan_id: String) {
+    PlanCancelled { plan_id }.publish(env);
+}
+
+pub fn emit_invoice_issued(env: &Env, plan: &Plan, cycle: u32, invoice_id: String) {
+    InvoiceIssued {
+        plan_id: plan.plan_id.clone(),
+        cycle,
+        invoice_id,
+        payer: plan.payer.clone(),
+        asset: plan.asset.clone(),
+        amount: plan.amount,
+        due_at: plan.due_at(cycle),
+    }
+    .publish(env);
+}
@@ -0,0 +1,268 @@
+#![no_std]
+use soroban_sdk::{contract, contractimpl, Address, Env, String};
+
+pub mod errors;
+pub mod events;
+pub mod storage;
+
+pub use errors::ContractError;
+pub use storage::{Asset, DataKey, Plan};
+
+use events::{emit_invoice_issued, emit_plan_cancelled, emit_plan_created};
+use storage::{get_admin, get_plan, has_admin, has_plan, set_admin, set_plan, MAX_PLAN_ID_LEN};
+
+// Contract
+
+/// # Invoisio Subscriptions Contract
+///
+/// Owns the schedule of recurring billing plans and hands out one invoice id
+/// per cycle, so recurrence is defined on-chain instead of being simulated by
+/// the backend. It never moves funds: each issued invoice is paid and then
+/// recorded in `invoice-payment` exactly like a one-off invoice.
+///
+/// ## Module layout
+/// | Module       | Responsibility                            |
+/// |--------------|-------------------------------------------|
+/// | `errors.rs`  | `#[contracterror]` typed error codes      |
+/// | `storage.rs` | `DataKey`, `Plan`, TTL helpers            |
+/// | `events.rs`  | Soroban event types and emit helpers      |
+/// | `lib.rs`     | Contract entry-points (this file)         |
+///
+/// ## Design decisions
+/// - **Deterministic invoice ids:** cycle `n` of plan `p` is always
+///   `invoisio-<p>-<n>`, so the backend, the reconciler and auditors derive
+///   the same id without coordination.
+/// - **Cycles are anchored to `start`:** cycle `n` is due at
+///   `start + n * interval_secs`, however late it is issued, so schedules
+///   never drift.
+/// - **One cycle per call:** `issue_invoice` issues the oldest due cycle; a
+///   backend that fell behind calls it until it returns `CycleNotDue`.
+///
+/// ## Typical backend flow
+/// 1. `create_plan(plan_id, payer, asset_code, asset_issuer, amount, interval_secs, start)`.
+/// 2. On a timer, call `issue_invoice(plan_id)` while `due_cycles(plan_id) > 0`.
+/// 3. For each `invoice_issued` event, create the invoice under `invoice_id`.
+/// 4. When it is paid, `record_payment(invoice_id, ...)` on `invoice-payment`.
+#[contract]
+pub struct SubscriptionsContract;
+
+#[contractimpl]
+impl SubscriptionsContract {
+    // Lifecycle
+
+    /// Initialise the contract and register the `admin`.
+    ///
+    /// Returns [`ContractError::AlreadyInitialized`] if called a second time.
+    pub fn initialize(env: Env, admin: Address) -> Result<(), ContractError> {
+        if has_admin(&env) {
+            return Err(ContractError::AlreadyInitialized);
+        }
+        set_admin(&env, &admin);
+        Ok(())
+    }
+
+    // Write
+
+    /// Create a plan billing `payer` `amount` of the asset every
+    /// `interval_secs`, with the first cycle due at `start` (Unix seconds).
+    /// Admin only. Emits `plan_created`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::NotInitialized`]
+    /// - [`ContractError::InvalidPlanId`] — empty, too long or not `[A-Za-z0-9_-]`
+    /// - [`ContractError::InvalidAsset`] — same rules as `invoice-payment`
+    /// - [`ContractError::InvalidAmount`] — `amount` ≤ 0
+    /// - [`ContractError::InvalidInterval`] — `interval_secs` is 0
+    /// - [`ContractError::PlanAlreadyExists`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_plan(
+        env: Env,
+        plan_id: String,
+        payer: Address,
+        asset_code: String,
+        asset_issuer: String,
+        amount: i128,
+        interval_secs: u64,
+        start: u64,
+    ) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        validate_plan_id(&plan_id)?;
+        let asset = build_asset(&env, &asset_code, &asset_issuer)?;
+        if amount <= 0 {
+            return Err(ContractError::InvalidAmount);
+        }
+        if interval_secs == 0 {
+            return Err(ContractError::InvalidInterval);
+        }
+        if has_plan(&env, &plan_id) {
+            return Err(ContractError::PlanAlreadyExists);
+        }
+
+        let plan = Plan {
+            plan_id,
+            payer,
+            asset,
+            amount,
+            interval_secs,
+            start,
+            next_cycle: 0,
+            active: true,
+        };
+        set_plan(&env, &plan);
+        emit_plan_created(&env, plan);
+        Ok(())
+    }
+
+    /// Stop issuing cycles for `plan_id`. Already issued invoices are
+    /// unaffected. Admin only. Emits `plan_cancelled`.
+    ///
+    /// Returns [`ContractError::PlanCancelled`] if it already was.
+    pub fn cancel_plan(env: Env, plan_id: String) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let mut plan = get_plan(&env, &plan_id)?;
+        if !plan.active {
+            return Err(ContractError::PlanCancelled);
+        }
+        plan.active = false;
+        set_plan(&env, &plan);
+        emit_plan_cancelled(&env, plan_id);
+        Ok(())
+    }
+
+    /// Issue the oldest due, not yet issued cycle of `plan_id` and return its
+    /// invoice id. Admin only. Emits `invoice_issued`.
+    ///
+    /// ## Errors
+    /// - [`ContractError::PlanNotFound`]
+    /// - [`ContractError::PlanCancelled`]
+    /// - [`ContractError::CycleNotDue`] — the next cycle starts in the future
+    pub fn issue_invoice(env: Env, plan_id: String) -> Result<String, ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+
+        let mut plan = get_plan(&env, &plan_id)?;
+        if !plan.active {
+            return Err(ContractError::PlanCancelled);
+        }
+        if plan.due_cycles(env.ledger().timestamp()) == 0 {
+            return Err(ContractError::CycleNotDue);
+        }
+
+        let cycle = plan.next_cycle;
+        let invoice_id = cycle_invoice_id(&env, &plan.plan_id, cycle);
+        plan.next_cycle += 1;
+        set_plan(&env, &plan);
+        emit_invoice_issued(&env, &plan, cycle, invoice_id.clone());
+        Ok(invoice_id)
+    }
+
+    // Read
+
+    /// Returns [`ContractError::PlanNotFound`] if `plan_id` does not exist.
+    pub fn get_plan(env: Env, plan_id: String) -> Result<Plan, ContractError> {
+        get_plan(&env, &plan_id)
+    }
+
+    /// Number of cycles of `plan_id` that are due now but not yet issued
+    /// (0 for a cancelled plan).
+    pub fn due_cycles(env: Env, plan_id: String) -> Result<u32, ContractError> {
+        Ok(get_plan(&env, &plan_id)?.due_cycles(env.ledger().timestamp()))
+    }
+
+    /// The invoice id of `cycle` of `plan_id`, whether or not it exists yet.
+    ///
+    /// Returns [`ContractError::InvalidPlanId`] for an id `create_plan` would
+    /// reject.
+    pub fn invoice_id(env: Env, plan_id: String, cycle: u32) -> Result<String, ContractError> {
+        validate_plan_id(&plan_id)?;
+        Ok(cycle_invoice_id(&env, &plan_id, cycle))
+    }
+
+    /// Return the current admin address.
+    pub fn admin(env: Env) -> Result<Address, ContractError> {
+        get_admin(&env)
+    }
+
+    /// Transfer admin rights. Both the current and the new admin must sign.
+    pub fn set_admin(env: Env, new_admin: Address) -> Result<(), ContractError> {
+        let admin = get_admin(&env)?;
+        admin.require_auth();
+        new_admin.require_auth();
+        set_admin(&env, &new_admin);
+        Ok(())
+    }
+}
+
+// Helpers
+
+const INVOICE_PREFIX: &[u8] = b"invoisio-";
+
+/// `[A-Za-z0-9_-]{1,MAX_PLAN_ID_LEN}`.
+fn validate_plan_id(plan_id: &String) -> Result<(), ContractError> {
+    let len = plan_id.len();
+    if len == 0 || len > MAX_PLAN_ID_LEN {
+        return Err(ContractError::InvalidPlanId);
+    }
+    let mut buf = [0u8; MAX_PLAN_ID_LEN as usize];
+    let bytes = &mut buf[..len as usize];
+    plan_id.copy_into_slice(bytes);
+    if bytes
+        .iter()
+        .all(|b| b.is_ascii_alphanumeric() || *b == b'-' || *b == b'_')
+    {
+        Ok(())
+    } else {
+        Err(ContractError::InvalidPlanId)
+    }
+}
+
+/// `invoisio-<plan_id>-<cycle>`. `plan_id` must already be validated.
+fn cycle_invoice_id(env: &Env, plan_id: &String, cycle: u32) -> String {
+    // prefix + plan id + '-' + up to 10 decimal digits
+    let mut buf = [0u8; INVOICE_PREFIX.len() + MAX_PLAN_ID_LEN as usize + 1 + 10];
+    let mut at = INVOICE_PREFIX.len();
+    buf[..at].copy_from_slice(INVOICE_PREFIX);
+
+    let id_len = plan_id.len() as usize;
+    plan_id.copy_into_slice(&mut buf[at..at + id_len]);
+    at += id_len;
+    buf[at] = b'-';
+    at += 1;
+
+    let mut digits = [0u8; 10];
+    let mut start = digits.len();
+    let mut n = cycle;
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    let digits = &digits[start..];
+    buf[at..at + digits.len()].copy_from_slice(digits);
+    at += digits.len();
+
+    String::from_bytes(env, &buf[..at])
+}
+
+/// Same rules as `invoice-payment`: XLM takes no issuer, tokens need one.
+fn build_asset(env: &Env, code: &String, issuer: &String) -> Result<Asset, ContractError> {
+    if code.is_empty() {
+        return Err(ContractError::InvalidAsset);
+    }
+    let is_xlm = *code == String::from_str(env, "XLM");
+    let issuer_empty = issuer.is_empty();
+    match (is_xlm, issuer_empty) {
+        (true, true) => Ok(Asset::Native),
+        (false, false) => Ok(Asset::Token(code.clone(), issuer.clone())),
+        _ => Err(ContractError::InvalidAsset),
+    }
+}
+
+mod test;
@@ -0,0 +1,125 @@
+use soroban_sdk::{contracttype, Address, Env, String};
+
+use crate::errors::ContractError;
+
+// TTL budget — same policy as `invoice-payment`:
+//   MIN_TTL  = 17 280 ledgers ≈ 1 day
+//   BUMP_TTL = 518 400 ledgers ≈ 30 days
+
+pub const MIN_TTL: u32 = 17_280;
+pub const BUMP_TTL: u32 = 518_400;
+
+/// Longest accepted `plan_id`, in bytes. Keeps generated invoice ids
+/// (`invoisio-<plan_id>-<cycle>`) within a fixed-size buffer.
+pub const MAX_PLAN_ID_LEN: u32 = 32;
+
+// Storage keys
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    /// Admin [`Address`] in **instance** storage.
+    Admin,
+    /// A [`Plan`] indexed by `plan_id` in **persistent** storage.
+    Plan(String),
+}
+
+// Data structures
+
+/// Asset a plan is billed in. Same encoding as `invoice-payment`'s `Asset`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Asset {
+    Native,
+    /// `(asset_code, issuer)`.
+    Token(String, String),
+}
+
+/// A recurring billing plan.
+///
+/// Cycle `n` (0-based) starts at `start + n * interval_secs`; its invoice id
+/// is `invoisio-<plan_id>-<n>`. `next_cycle` is the first cycle whose invoice
+/// has not been issued yet.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plan {
+    pub plan_id: String,
+    /// Account expected to pay each cycle.
+    pub payer: Address,
+    pub asset: Asset,
+    /// Amount per cycle in the asset's smallest unit (> 0).
+    pub amount: i128,
+    /// Cycle length in seconds (> 0).
+    pub interval_secs: u64,
+    /// Unix timestamp of cycle 0.
+    pub start: u64,
+    pub next_cycle: u32,
+    /// `false` once cancelled.
+    pub active: bool,
+}
+
+impl Plan {
+    /// Unix timestamp at which `cycle` becomes due.
+    pub fn due_at(&self, cycle: u32) -> u64 {
+        self.start
+            .saturating_add(self.interval_secs.saturating_mul(cycle as u64))
+    }
+
+    /// Cycles that are due at `now` but not yet issued.
+    pub fn due_cycles(&self, now: u64) -> u32 {
+        if !self.active || now < self.start {
+            return 0;
+        }
+        let elapsed = (now - self.start) / self.interval_secs + 1;
+        (elapsed.min(u32::MAX as u64) as u32).saturating_sub(self.next_cycle)
+    }
+}
+
+// Admin helpers (instance storage)
+
+pub fn has_admin(env: &Env) -> bool {
+    env.storage().instance().has(&DataKey::Admin)
+}
+
+/// Returns [`ContractError::NotInitialized`] if `initialize()` was never called.
+pub fn get_admin(env: &Env) -> Result<Address, ContractError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(ContractError::NotInitialized)
+}
+
+pub fn set_admin(env: &Env, admin: &Address) {
+    env.storage().instance().set(&DataKey::Admin, admin);
+    env.storage().instance().extend_ttl(MIN_TTL, BUMP_TTL);
+}
+
+// Plan helpers (persistent storage)
+
+pub fn has_plan(env: &Env, plan_id: &String) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Plan(plan_id.clone()))
+}
+
+/// Returns [`ContractError::PlanNotFound`] if no plan exists for `plan_id`.
+pub fn get_plan(env: &Env, plan_id: &String) -> Result<Plan, ContractError> {
+    let key = DataKey::Plan(plan_id.clone());
+    let plan: Plan = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .ok_or(ContractError::PlanNotFound)?;
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+    Ok(plan)
+}
+
+pub fn set_plan(env: &Env, plan: &Plan) {
+    let key = DataKey::Plan(plan.plan_id.clone());
+    env.storage().persistent().set(&key, plan);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, MIN_TTL, BUMP_TTL);
+}
@@ -0,0 +1,232 @@
+#![cfg(test)]
+#![allow(clippy::all)]
+
+use super::*;
+use soroban_sdk::testutils::{Address as _, Ledger as _};
+use soroban_sdk::{Address, Env, String};
+
+const DAY: u64 = 86_400;
+const START: u64 = 1_700_000_000;
+
+// Helpers
+
+fn setup(env: &Env) -> (SubscriptionsContractClient<'_>, Address) {
+    env.mock_all_auths();
+    let admin = Address::generate(env);
+    let contract_id = env.register(SubscriptionsContract, ());
+    let client = SubscriptionsContractClient::new(env, &contract_id);
+    client.initialize(&admin);
+    (client, admin)
+}
+
+fn set_time(env: &Env, timestamp: u64) {
+    env.ledger().with_mut(|li| li.timestamp = timestamp);
+}
+
+/// Monthly-ish (30 day) XLM plan starting at [`START`].
+fn create_plan(env: &Env, client: &SubscriptionsContractClient, plan_id: &str) -> Address {
+    let payer = Address::generate(env);
+    client.create_plan(
+        &String::from_str(env, plan_id),
+        &payer,
+        &String::from_str(env, "XLM"),
+        &String::from_str(env, ""),
+        &10_000_000i128,
+        &(30 * DAY),
+        &START,
+    );
+    payer
+}
+
+// create_plan
+
+#[test]
+fn test_create_plan_stores_plan() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let payer = create_plan(&env, &client, "acme-pro");
+
+    let plan = client.get_plan(&String::from_str(&env, "acme-pro"));
+    assert_eq!(plan.payer, payer);
+    assert_eq!(plan.asset, Asset::Native);
+    assert_eq!(plan.amount, 10_000_000);
+    assert_eq!(plan.interval_secs, 30 * DAY);
+    assert_eq!(plan.next_cycle, 0);
+    assert!(plan.active);
+}
+
+#[test]
+fn test_create_plan_rejects_duplicates_and_bad_input() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    create_plan(&env, &client, "acme");
+    let payer = Address::generate(&env);
+
+    let attempt = |id: &str, code: &str, issuer: &str, amount: i128, interval: u64| {
+        client.try_create_plan(
+            &String::from_str(&env, id),
+            &payer,
+            &String::from_str(&env, code),
+            &String::from_str(&env, issuer),
+            &amount,
+            &interval,
+            &START,
+        )
+    };
+
+    assert_eq!(attempt("acme", "XLM", "", 1, DAY), Err(Ok(ContractError::PlanAlreadyExists)));
+    assert_eq!(attempt("", "XLM", "", 1, DAY), Err(Ok(ContractError::InvalidPlanId)));
+    assert_eq!(attempt("a b", "XLM", "", 1, DAY), Err(Ok(ContractError::InvalidPlanId)));
+    assert_eq!(
+        attempt("x123456789012345678901234567890123", "XLM", "", 1, DAY),
+        Err(Ok(ContractError::InvalidPlanId))
+    );
+    assert_eq!(attempt("p", "USDC", "", 1, DAY), Err(Ok(ContractError::InvalidAsset)));
+    assert_eq!(attempt("p", "XLM", "", 0, DAY), Err(Ok(ContractError::InvalidAmount)));
+    assert_eq!(attempt("p", "XLM", "", 1, 0), Err(Ok(ContractError::InvalidInterval)));
+}
+
+#[test]
+fn test_create_plan_requires_admin_auth() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(SubscriptionsContract, ());
+    let client = SubscriptionsContractClient::new(&env, &contract_id);
+    client.initialize(&admin);
+
+    // No auths mocked.
+    let result = client.try_create_plan(
+        &String::from_str(&env, "acme"),
+        &Address::generate(&env),
+        &String::from_str(&env, "XLM"),
+        &String::from_str(&env, ""),
+        &1,
+        &DAY,
+        &START,
+    );
+    assert!(result.is_err());
+}
+
+// Cycles
+
+#[test]
+fn test_invoice_id_format() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let id = |plan: &str, cycle: u32| client.invoice_id(&String::from_str(&env, plan), &cycle);
+
+    assert_eq!(id("acme-pro", 0), String::from_str(&env, "invoisio-acme-pro-0"));
+    assert_eq!(id("acme_pro", 42), String::from_str(&env, "invoisio-acme_pro-42"));
+    assert_eq!(id("a", u32::MAX), String::from_str(&env, "invoisio-a-4294967295"));
+}
+
+#[test]
+fn test_issue_invoice_follows_schedule() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    create_plan(&env, &client, "acme");
+    let plan_id = String::from_str(&env, "acme");
+
+    set_time(&env, START - 1);
+    assert_eq!(client.due_cycles(&plan_id), 0);
+    assert_eq!(client.try_issue_invoice(&plan_id), Err(Ok(ContractError::CycleNotDue)));
+
+    set_time(&env, START);
+    assert_eq!(client.due_cycles(&plan_id), 1);
+    assert_eq!(client.issue_invoice(&plan_id), String::from_str(&env, "invoisio-acme-0"));
+    assert_eq!(client.try_issue_invoice(&plan_id), Err(Ok(ContractError::CycleNotDue)));
+
+    set_time(&env, START + 30 * DAY);
+    assert_eq!(client.issue_invoice(&plan_id), String::from_str(&env, "invoisio-acme-1"));
+}
+
+#[test]
+fn test_late_issuing_catches_up_without_drift() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    create_plan(&env, &client, "acme");
+    let plan_id = String::from_str(&env, "acme");
+
+    // Backend was down for ~3 cycles.
+    set_time(&env, START + 95 * DAY);
+    assert_eq!(client.due_cycles(&plan_id), 4);
+    for cycle in 0..4u32 {
+        let id = client.issue_invoice(&plan_id);
+        assert_eq!(id, client.invoice_id(&plan_id, &cycle));
+    }
+    assert_eq!(client.due_cycles(&plan_id), 0);
+
+    // Cycle 4 is still due at START + 120 days, not 30 days after catch-up.
+    set_time(&env, START + 120 * DAY - 1);
+    assert_eq!(client.due_cycles(&plan_id), 0);
+    set_time(&env, START + 120 * DAY);
+    assert_eq!(client.due_cycles(&plan_id), 1);
+}
+
+#[test]
+fn test_cancelled_plan_issues_nothing() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    create_plan(&env, &client, "acme");
+    let plan_id = String::from_str(&env, "acme");
+
+    client.cancel_plan(&plan_id);
+    set_time(&env, START + 365 * DAY);
+    assert_eq!(client.due_cycles(&plan_id), 0);
+    assert_eq!(client.try_issue_invoice(&plan_id), Err(Ok(ContractError::PlanCancelled)));
+    assert_eq!(client.try_cancel_plan(&plan_id), Err(Ok(ContractError::PlanCancelled)));
+}
+
+#[test]
+fn test_unknown_plan() {
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let plan_id = String::from_str(&env, "ghost");
+
+    assert_eq!(client.try_get_plan(&plan_id), Err(Ok(ContractError::PlanNotFound)));
+    assert_eq!(client.try_issue_invoice(&plan_id), Err(Ok(ContractError::PlanNotFound)));
+}
+
+// Events
+
+#[test]
+fn test_issue_invoice_emits_invoice_issued_event() {
+    use soroban_sdk::testutils::Events as _;
+    use soroban_sdk::{IntoVal, Map, Symbol, Val};
+
+    let env = Env::default();
+    let (client, _admin) = setup(&env);
+    let payer = create_plan(&env, &client, "acme");
+    let plan_id = String::from_str(&env, "acme");
+    set_time(&env, START + 31 * DAY);
+    client.issue_invoice(&plan_id);
+    client.issue_invoice(&plan_id);
+
+    // Events of the last invocation only: cycle 1.
+    let mut data: Map<Symbol, Val> = Map::new(&env);
+    data.set(Symbol::new(&env, "amount"), 10_000_000i128.into_val(&env));
+    data.set(Symbol::new(&env, "asset"), Asset::Native.into_val(&env));
+    data.set(Symbol::new(&env, "cycle"), 1u32.into_val(&env));
+    data.set(Symbol::new(&env, "due_at"), (START + 30 * DAY).into_val(&env));
+    data.set(
+        Symbol::new(&env, "invoice_id"),
+        String::from_str(&env, "invoisio-acme-1").into_val(&env),
+    );
+    data.set(Symbol::new(&env, "payer"), payer.into_val(&env));
+    data.set(Symbol::new(&env, "plan_id"), plan_id.into_val(&env));
+
+    assert_eq!(
+        env.events().all(),
+        soroban_sdk::vec![
+            &env,
+            (
+                client.address.clone(),
+                soroban_sdk::vec![
+                    &env,
+                    Symbol::new(&env, "invoice_issued").into_val(&env)
+                ],
+                data.into_val(&env),
+            ),
+        ]
+    );
+}
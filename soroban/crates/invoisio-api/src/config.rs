@@ -0,0 +1,52 @@
+use std::net::SocketAddr;
+
+use crate::error::{Error, Result};
+
+/// API configuration, read from environment variables.
+///
+/// | Variable                      | Required | Default        |
+/// |-------------------------------|----------|----------------|
+/// | `INVOISIO_RPC_URL`            | yes      |                |
+/// | `INVOISIO_NETWORK_PASSPHRASE` | yes      |                |
+/// | `INVOISIO_CONTRACT_ID`        | yes      |                |
+/// | `DATABASE_URL`                | yes      |                |
+/// | `INVOISIO_API_ADDR`           | no       | `0.0.0.0:8080` |
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub rpc_url: String,
+    pub network_passphrase: String,
+    pub contract_id: String,
+    /// The indexer's Postgres database.
+    pub database_url: String,
+    pub addr: SocketAddr,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            rpc_url: required("INVOISIO_RPC_URL")?,
+            network_passphrase: required("INVOISIO_NETWORK_PASSPHRASE")?,
+            contract_id: required("INVOISIO_CONTRACT_ID")?,
+            database_url: required("DATABASE_URL")?,
+            addr: parsed("INVOISIO_API_ADDR", SocketAddr::from(([0, 0, 0, 0], 8080)))?,
+        })
+    }
+}
+
+fn required(name: &str) -> Result<String> {
+    optional(name).ok_or_else(|| Error::Config(format!("{name} is not set")))
+}
+
+fn optional(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn parsed<T: std::str::FromStr>(name: &str, default: T) -> Result<T> {
+    optional(name)
+        .map(|v| {
+            v.parse()
+                .map_err(|_| Error::Config(format!("{name}=`{v}` is not valid")))
+        })
+        .transpose()
+        .map(|v| v.unwrap_or(default))
+}
@@ -0,0 +1,150 @@
+//! Read-only queries over the `invoisio-indexer` schema.
+//!
+//! The indexer owns the tables; this module never writes or migrates.
+//! `NUMERIC` amounts are read as text so no `i128` precision is lost.
+
+use std::time::Duration;
+
+use invoisio_client::{Asset, PaymentRecord};
+use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
+use sqlx::{Postgres, QueryBuilder, Row};
+
+use crate::error::Result;
+use crate::filter::PaymentFilter;
+use crate::service::{AssetStats, Payment, Source, Stats};
+
+const SELECT_PAYMENTS: &str = "SELECT invoice_id, payer, asset_code, asset_issuer,
+        amount::text AS amount, EXTRACT(EPOCH FROM recorded_at)::bigint AS timestamp,
+        ledger, tx_hash
+    FROM payments";
+
+#[derive(Clone, Debug)]
+pub struct PaymentsDb {
+    pool: PgPool,
+    contract_id: String,
+}
+
+impl PaymentsDb {
+    /// Connect lazily, so the API starts (and falls back to the contract)
+    /// while the database is down.
+    pub fn connect(database_url: &str, contract_id: &str) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(8)
+            .acquire_timeout(Duration::from_secs(5))
+            .connect_lazy(database_url)?;
+        Ok(Self::with_pool(pool, contract_id))
+    }
+
+    pub fn with_pool(pool: PgPool, contract_id: &str) -> Self {
+        Self {
+            pool,
+            contract_id: contract_id.to_string(),
+        }
+    }
+
+    pub async fn payment(&self, invoice_id: &str) -> Result<Option<Payment>> {
+        let row = sqlx::query(&format!(
+            "{SELECT_PAYMENTS} WHERE contract_id = $1 AND invoice_id = $2"
+        ))
+        .bind(&self.contract_id)
+        .bind(invoice_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        row.map(|r| payment(&r)).transpose()
+    }
+
+    /// Payments matching `filter`, newest first.
+    pub async fn payments(&self, filter: &PaymentFilter) -> Result<Vec<Payment>> {
+        let rows = list_query(&self.contract_id, filter)
+            .build()
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(payment).collect()
+    }
+
+    /// Totals per asset and the indexer's checkpoint ledger.
+    pub async fn stats(&self) -> Result<Stats> {
+        let rows = sqlx::query(
+            "SELECT asset_code, asset_issuer, COUNT(*) AS payments, SUM(amount)::text AS volume
+             FROM payments WHERE contract_id = $1
+             GROUP BY asset_code, asset_issuer
+             ORDER BY payments DESC, asset_code",
+        )
+        .bind(&self.contract_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let assets: Vec<AssetStats> = rows
+            .iter()
+            .map(|r| AssetStats {
+                asset: Asset::from_code_issuer(r.get("asset_code"), r.get("asset_issuer")),
+                payments: r.get::<i64, _>("payments") as u64,
+                volume: r.get("volume"),
+            })
+            .collect();
+
+        let indexed_ledger = sqlx::query("SELECT ledger FROM checkpoints WHERE contract_id = $1")
+            .bind(&self.contract_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(|r| r.get::<i32, _>("ledger") as u32);
+
+        Ok(Stats {
+            payments: assets.iter().map(|a| a.payments).sum(),
+            assets,
+            indexed_ledger,
+            source: Source::Indexer,
+        })
+    }
+}
+
+/// `SELECT` for `filter`, newest first, then by invoice id so pages are
+/// stable.
+pub fn list_query<'a>(
+    contract_id: &'a str,
+    filter: &'a PaymentFilter,
+) -> QueryBuilder<'a, Postgres> {
+    let mut q = QueryBuilder::new(SELECT_PAYMENTS);
+    q.push(" WHERE contract_id = ").push_bind(contract_id);
+    if let Some(payer) = &filter.payer {
+        q.push(" AND payer = ").push_bind(payer);
+    }
+    if let Some(asset) = &filter.asset {
+        q.push(" AND asset_code = ").push_bind(asset.code());
+        q.push(" AND asset_issuer = ").push_bind(asset.issuer());
+    }
+    if let Some(from) = filter.from {
+        q.push(" AND recorded_at >= to_timestamp(")
+            .push_bind(from as f64)
+            .push(")");
+    }
+    if let Some(to) = filter.to {
+        q.push(" AND recorded_at < to_timestamp(")
+            .push_bind(to as f64)
+            .push(")");
+    }
+    q.push(" ORDER BY recorded_at DESC, invoice_id LIMIT ")
+        .push_bind(filter.limit as i64)
+        .push(" OFFSET ")
+        .push_bind(filter.offset as i64);
+    q
+}
+
+fn payment(r: &PgRow) -> Result<Payment> {
+    let amount = r
+        .get::<&str, _>("amount")
+        .parse()
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+    Ok(Payment {
+        record: PaymentRecord {
+            invoice_id: r.get("invoice_id"),
+            payer: r.get("payer"),
+            payer_mux_id: None,
+            asset: Asset::from_code_issuer(r.get("asset_code"), r.get("asset_issuer")),
+            amount,
+            timestamp: r.get::<i64, _>("timestamp") as u64,
+        },
+        ledger: Some(r.get::<i32, _>("ledger") as u32),
+        tx_hash: r.get("tx_hash"),
+        source: Source::Indexer,
+    })
+}
@@ -0,0 +1,25 @@
+/// Errors surfaced by the API.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+
+    /// Malformed query parameter.
+    #[error("invalid query: {0}")]
+    Query(String),
+
+    #[error("no payment recorded for {0}")]
+    NotFound(String),
+
+    /// Soroban RPC / decoding failure.
+    #[error(transparent)]
+    Client(#[from] invoisio_client::Error),
+
+    #[error("database: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
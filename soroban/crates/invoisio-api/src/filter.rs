@@ -0,0 +1,96 @@
+//! Query-string parameters of `GET /payments`.
+
+use std::time::UNIX_EPOCH;
+
+use invoisio_client::Asset;
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+
+/// Page size when `limit` is not given.
+pub const DEFAULT_LIMIT: u32 = 100;
+
+/// Largest accepted `limit`.
+pub const MAX_LIMIT: u32 = 500;
+
+/// Raw `GET /payments` query, as sent.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PaymentQuery {
+    pub payer: Option<String>,
+    /// `XLM` or `CODE:ISSUER`.
+    pub asset: Option<String>,
+    /// `YYYY-MM-DD` or RFC 3339, inclusive.
+    pub from: Option<String>,
+    /// `YYYY-MM-DD` or RFC 3339, exclusive.
+    pub to: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// Validated `GET /payments` filter. Times are Unix seconds of the
+/// contract's recording timestamp.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentFilter {
+    pub payer: Option<String>,
+    pub asset: Option<Asset>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+impl PaymentFilter {
+    pub fn parse(q: &PaymentQuery) -> Result<Self> {
+        let limit = q.limit.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(Error::Query(format!(
+                "limit must be between 1 and {MAX_LIMIT}"
+            )));
+        }
+        let from = q.from.as_deref().map(parse_date).transpose()?;
+        let to = q.to.as_deref().map(parse_date).transpose()?;
+        if let (Some(from), Some(to)) = (from, to) {
+            if from >= to {
+                return Err(Error::Query("`from` must be before `to`".into()));
+            }
+        }
+        Ok(Self {
+            payer: q.payer.clone().filter(|p| !p.is_empty()),
+            asset: q.asset.as_deref().map(parse_asset).transpose()?,
+            from,
+            to,
+            limit,
+            offset: q.offset.unwrap_or(0),
+        })
+    }
+}
+
+/// `XLM` / `native` or `CODE:ISSUER`.
+pub fn parse_asset(s: &str) -> Result<Asset> {
+    if s.eq_ignore_ascii_case("xlm") || s.eq_ignore_ascii_case("native") {
+        return Ok(Asset::Native);
+    }
+    match s.split_once(':') {
+        Some((code, issuer)) if !code.is_empty() && !issuer.is_empty() => Ok(Asset::Token {
+            code: code.to_string(),
+            issuer: issuer.to_string(),
+        }),
+        _ => Err(Error::Query(format!(
+            "asset: expected `XLM` or `CODE:ISSUER`, got `{s}`"
+        ))),
+    }
+}
+
+/// `YYYY-MM-DD` (midnight UTC) or RFC 3339, as Unix seconds.
+pub fn parse_date(s: &str) -> Result<u64> {
+    let full = if s.len() == 10 {
+        format!("{s}T00:00:00Z")
+    } else {
+        s.to_string()
+    };
+    humantime::parse_rfc3339(&full)
+        .map_err(|e| Error::Query(format!("invalid date `{s}`: {e}")))?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .map_err(|_| Error::Query(format!("date `{s}` is before 1970")))
+}
@@ -0,0 +1,30 @@
+//! # invoisio-api
+//!
+//! Read-only HTTP API over recorded payments, so frontends stop calling
+//! Soroban RPC directly. Reads come from the `invoisio-indexer` database;
+//! single-invoice lookups and the payment count fall back to contract state
+//! when the indexer has nothing or is unreachable.
+//!
+//! ## Module layout
+//! | Module       | Responsibility                                        |
+//! |--------------|-------------------------------------------------------|
+//! | `config.rs`  | Environment configuration                             |
+//! | `filter.rs`  | [`PaymentFilter`]: query-string parsing               |
+//! | `db.rs`      | [`PaymentsDb`]: queries over the indexer's tables     |
+//! | `service.rs` | [`Payments`]: indexer reads with contract fallback    |
+//! | `server.rs`  | HTTP routes                                           |
+
+pub mod config;
+pub mod db;
+pub mod error;
+pub mod filter;
+pub mod server;
+pub mod service;
+
+pub use config::Config;
+pub use db::PaymentsDb;
+pub use error::{Error, Result};
+pub use filter::PaymentFilter;
+pub use service::{AssetStats, Page, Payment, Payments, Source, Stats};
+
+mod test;
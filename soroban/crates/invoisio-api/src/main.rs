@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use invoisio_api::{server, Config, Payments, PaymentsDb};
+use invoisio_client::InvoicePaymentClient;
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() -> invoisio_api::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let config = Config::from_env()?;
+    let client = InvoicePaymentClient::new(
+        &config.rpc_url,
+        &config.network_passphrase,
+        &config.contract_id,
+    )?;
+    let db = PaymentsDb::connect(&config.database_url, &config.contract_id)?;
+
+    tracing::info!(contract = %config.contract_id, "starting payments API");
+
+    server::serve(
+        config.addr,
+        server::router(Arc::new(Payments::new(db, client))),
+        async {
+            let _ = tokio::signal::ctrl_c().await;
+        },
+    )
+    .await?;
+    Ok(())
+}
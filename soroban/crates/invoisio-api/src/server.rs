@@ -0,0 +1,91 @@
+//! HTTP routes.
+//!
+//! | Route                       | Response    |
+//! |-----------------------------|-------------|
+//! | `GET /payments/:invoice_id` | [`Payment`] |
+//! | `GET /payments`             | [`Page`]    |
+//! | `GET /stats`                | [`Stats`]   |
+//!
+//! `GET /payments` takes `payer`, `asset`, `from`, `to`, `limit` and
+//! `offset` (see [`PaymentQuery`]). Errors are `{"error": "..."}` with
+//! `400`, `404` or `500`.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+
+use crate::error::{Error, Result};
+use crate::filter::{PaymentFilter, PaymentQuery};
+use crate::service::{Page, Payment, Payments, Stats};
+
+/// The API's routes.
+pub fn router(payments: Arc<Payments>) -> Router {
+    Router::new()
+        .route("/payments", get(list))
+        .route("/payments/:invoice_id", get(payment))
+        .route("/stats", get(stats))
+        .with_state(payments)
+}
+
+/// Serve `router` on `addr` until `shutdown` resolves.
+pub async fn serve(
+    addr: SocketAddr,
+    router: Router,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!(%addr, "serving payments API");
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown)
+        .await
+}
+
+async fn payment(
+    State(payments): State<Arc<Payments>>,
+    Path(invoice_id): Path<String>,
+) -> Result<Json<Payment>> {
+    Ok(Json(payments.payment(&invoice_id).await?))
+}
+
+async fn list(
+    State(payments): State<Arc<Payments>>,
+    Query(query): Query<PaymentQuery>,
+) -> Result<Json<Page>> {
+    let filter = PaymentFilter::parse(&query)?;
+    Ok(Json(payments.payments(&filter).await?))
+}
+
+async fn stats(State(payments): State<Arc<Payments>>) -> Result<Json<Stats>> {
+    Ok(Json(payments.stats().await?))
+}
+
+impl Error {
+    /// HTTP status this error is reported with.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Error::Query(_) => StatusCode::BAD_REQUEST,
+            Error::NotFound(_) => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        // Internal failures are logged, not shown to callers.
+        let message = if status.is_server_error() {
+            tracing::error!(error = %self, "request failed");
+            "internal error".to_string()
+        } else {
+            self.to_string()
+        };
+        let body = serde_json::json!({ "error": message });
+        (status, Json(body)).into_response()
+    }
+}
@@ -0,0 +1,127 @@
+//! Indexer reads with contract-state fallback.
+
+use invoisio_client::{Asset, InvoicePaymentClient, PaymentRecord};
+use serde::Serialize;
+
+use crate::db::PaymentsDb;
+use crate::error::{Error, Result};
+use crate::filter::PaymentFilter;
+
+/// Where a response was read from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Indexer,
+    /// Contract state via Soroban RPC.
+    Contract,
+}
+
+/// A recorded payment.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Payment {
+    #[serde(flatten)]
+    pub record: PaymentRecord,
+    /// Ledger of the `payment_recorded` event; indexer only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledger: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    pub source: Source,
+}
+
+impl Payment {
+    fn from_contract(record: PaymentRecord) -> Self {
+        Self {
+            record,
+            ledger: None,
+            tx_hash: None,
+            source: Source::Contract,
+        }
+    }
+}
+
+/// One page of `GET /payments`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Page {
+    pub payments: Vec<Payment>,
+    /// `offset` of the next page, if this one was full.
+    pub next_offset: Option<u32>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct AssetStats {
+    pub asset: Asset,
+    pub payments: u64,
+    /// Sum of amounts in the asset's smallest unit, as a decimal string.
+    pub volume: String,
+}
+
+/// `GET /stats`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Stats {
+    pub payments: u64,
+    /// Per-asset totals; empty when read from the contract.
+    pub assets: Vec<AssetStats>,
+    /// Ledger the indexer has caught up to.
+    pub indexed_ledger: Option<u32>,
+    pub source: Source,
+}
+
+/// The API's reads.
+pub struct Payments {
+    db: PaymentsDb,
+    client: InvoicePaymentClient,
+}
+
+impl Payments {
+    pub fn new(db: PaymentsDb, client: InvoicePaymentClient) -> Self {
+        Self { db, client }
+    }
+
+    /// The payment for `invoice_id`. Falls back to the contract when the
+    /// indexer has not seen it yet or cannot be read; [`Error::NotFound`]
+    /// if neither has it.
+    pub async fn payment(&self, invoice_id: &str) -> Result<Payment> {
+        match self.db.payment(invoice_id).await {
+            Ok(Some(payment)) => return Ok(payment),
+            Ok(None) => {}
+            Err(err) => {
+                tracing::warn!(invoice = invoice_id, error = %err, "indexer read failed, using contract");
+            }
+        }
+        self.client
+            .get_payment_opt(invoice_id)
+            .await?
+            .map(Payment::from_contract)
+            .ok_or_else(|| Error::NotFound(invoice_id.to_string()))
+    }
+
+    /// Payments matching `filter`, newest first. Indexer only: the contract
+    /// cannot be searched.
+    pub async fn payments(&self, filter: &PaymentFilter) -> Result<Page> {
+        let payments = self.db.payments(filter).await?;
+        let next_offset = (payments.len() == filter.limit as usize)
+            .then(|| filter.offset.saturating_add(filter.limit));
+        Ok(Page {
+            payments,
+            next_offset,
+        })
+    }
+
+    /// Totals from the indexer, or just the contract's payment count if it
+    /// cannot be read.
+    pub async fn stats(&self) -> Result<Stats> {
+        match self.db.stats().await {
+            Ok(stats) => Ok(stats),
+            Err(err) => {
+                tracing::warn!(error = %err, "indexer read failed, using contract");
+                Ok(Stats {
+                    payments: self.client.payment_count().await?.into(),
+                    assets: Vec::new(),
+                    indexed_ledger: None,
+                    source: Source::Contract,
+                })
+            }
+        }
+    }
+}
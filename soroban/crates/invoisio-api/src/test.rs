@@ -0,0 +1,139 @@
+#![cfg(test)]
+
+use axum::http::StatusCode;
+use invoisio_client::{Asset, InvoicePaymentClient, PaymentRecord};
+
+use crate::db::list_query;
+use crate::filter::{parse_date, PaymentQuery, DEFAULT_LIMIT};
+use crate::{Error, Payment, PaymentFilter, Payments, PaymentsDb, Source};
+
+const ISSUER: &str = "GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5";
+const CONTRACT_ID: &str = "CDOTTHKN4Z5RFSEDOILRIHNQRWGRCZWOMTDBCFK7W3OWRS2V3H7CEZVM";
+
+fn query(pairs: &[(&str, &str)]) -> crate::Result<PaymentFilter> {
+    let mut q = PaymentQuery::default();
+    for (key, value) in pairs {
+        let value = Some(value.to_string());
+        match *key {
+            "payer" => q.payer = value,
+            "asset" => q.asset = value,
+            "from" => q.from = value,
+            "to" => q.to = value,
+            "limit" => q.limit = value.map(|v| v.parse().unwrap()),
+            "offset" => q.offset = value.map(|v| v.parse().unwrap()),
+            _ => unreachable!("{key}"),
+        }
+    }
+    PaymentFilter::parse(&q)
+}
+
+// Filters
+
+#[test]
+fn test_filter_defaults() {
+    let f = query(&[]).unwrap();
+    assert_eq!((f.payer, f.asset, f.from, f.to), (None, None, None, None));
+    assert_eq!((f.limit, f.offset), (DEFAULT_LIMIT, 0));
+}
+
+#[test]
+fn test_filter_parses_asset_and_dates() {
+    let usdc = format!("USDC:{ISSUER}");
+    let f = query(&[
+        ("payer", ISSUER),
+        ("asset", &usdc),
+        ("from", "2024-01-01"),
+        ("to", "2024-02-01T12:00:00Z"),
+        ("limit", "20"),
+        ("offset", "40"),
+    ])
+    .unwrap();
+    assert_eq!(
+        f.asset,
+        Some(Asset::Token {
+            code: "USDC".into(),
+            issuer: ISSUER.into(),
+        })
+    );
+    assert_eq!(f.from, Some(1_704_067_200));
+    assert_eq!(f.to, Some(1_706_788_800));
+    assert_eq!((f.limit, f.offset), (20, 40));
+    assert_eq!(
+        query(&[("asset", "xlm")]).unwrap().asset,
+        Some(Asset::Native)
+    );
+}
+
+#[test]
+fn test_filter_rejects_bad_parameters() {
+    for bad in [
+        query(&[("asset", "USDC")]),
+        query(&[("from", "yesterday")]),
+        query(&[("from", "2024-02-01"), ("to", "2024-01-01")]),
+        query(&[("limit", "0")]),
+        query(&[("limit", "501")]),
+    ] {
+        let err = bad.unwrap_err();
+        assert!(matches!(err, Error::Query(_)), "{err}");
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+    }
+    assert!(parse_date("1969-12-31").is_err());
+}
+
+#[test]
+fn test_list_query_binds_only_given_filters() {
+    let all = query(&[]).unwrap();
+    assert!(list_query(CONTRACT_ID, &all).sql().ends_with(
+        " WHERE contract_id = $1 ORDER BY recorded_at DESC, invoice_id LIMIT $2 OFFSET $3"
+    ));
+
+    let narrow = query(&[("asset", "XLM"), ("to", "2024-01-01")]).unwrap();
+    assert!(list_query(CONTRACT_ID, &narrow).sql().ends_with(
+        " WHERE contract_id = $1 AND asset_code = $2 AND asset_issuer = $3 \
+         AND recorded_at < to_timestamp($4) \
+         ORDER BY recorded_at DESC, invoice_id LIMIT $5 OFFSET $6"
+    ));
+}
+
+// Responses
+
+#[test]
+fn test_payment_json_flattens_record() {
+    let payment = Payment {
+        record: PaymentRecord {
+            invoice_id: "invoisio-1".into(),
+            payer: ISSUER.into(),
+            payer_mux_id: None,
+            asset: Asset::Native,
+            amount: 12_500_000,
+            timestamp: 1_700_000_000,
+        },
+        ledger: Some(7),
+        tx_hash: None,
+        source: Source::Indexer,
+    };
+    assert_eq!(
+        serde_json::to_value(&payment).unwrap(),
+        serde_json::json!({
+            "invoice_id": "invoisio-1",
+            "payer": ISSUER,
+            "asset": { "type": "native" },
+            "amount": "12500000",
+            "timestamp": 1_700_000_000,
+            "ledger": 7,
+            "source": "indexer",
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_payment_falls_back_to_contract_when_indexer_is_down() {
+    let db = PaymentsDb::connect("postgres://invoisio@127.0.0.1:1/invoisio", CONTRACT_ID).unwrap();
+    let client = InvoicePaymentClient::new("http://127.0.0.1:1", "Test", CONTRACT_ID).unwrap();
+    let payments = Payments::new(db, client);
+
+    // Neither is reachable; the RPC error shows the contract was tried.
+    let err = payments.payment("invoisio-1").await.unwrap_err();
+    assert!(matches!(err, Error::Client(_)), "{err}");
+    assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
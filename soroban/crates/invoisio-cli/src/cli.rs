@@ -0,0 +1,338 @@
+//! Command-line surface. Every global flag can also come from the
+//! environment so CI jobs don't need to repeat them.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::{Args, Parser, Subcommand};
+use invoisio_client::{scval, Asset, Network};
+use stellar_xdr::curr::ScVal;
+
+use crate::export::{parse_date, Format};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "invoisio",
+    version,
+    about = "Operate the Invoisio invoice-payment contract"
+)]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Args)]
+pub struct GlobalArgs {
+    /// Network profile: local, testnet, futurenet or mainnet.
+    #[arg(
+        long,
+        global = true,
+        env = "INVOISIO_NETWORK",
+        value_parser = Network::from_str,
+        default_value_t = Network::Testnet
+    )]
+    pub network: Network,
+
+    /// Profiles file holding per-network contract ids and overrides.
+    #[arg(long, global = true, env = "INVOISIO_PROFILES", default_value = "invoisio.toml")]
+    pub profiles: PathBuf,
+
+    /// Override the profile's Soroban RPC URL.
+    #[arg(long, global = true, env = "INVOISIO_RPC_URL")]
+    pub rpc_url: Option<String>,
+
+    /// Override the profile's network passphrase.
+    #[arg(long, global = true, env = "INVOISIO_NETWORK_PASSPHRASE")]
+    pub network_passphrase: Option<String>,
+
+    /// Override the profile's contract (`C...`). Rejected if the profiles
+    /// file assigns it to another network.
+    #[arg(long, global = true, env = "INVOISIO_CONTRACT_ID")]
+    pub contract_id: Option<String>,
+
+    /// Secret seed (`S...`) used as transaction source and signer.
+    #[arg(long, global = true, env = "INVOISIO_SECRET", hide_env_values = true)]
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Build the contract, upload it, create and initialise an instance,
+    /// read it back, and save its id in the network's profile.
+    Deploy {
+        /// Prebuilt `invoice_payment.wasm`. Built with cargo if omitted.
+        #[arg(long)]
+        wasm: Option<PathBuf>,
+        /// Package to build when `--wasm` is omitted.
+        #[arg(long, default_value = "invoice-payment", conflicts_with = "wasm")]
+        package: String,
+        /// Workspace to build in. Defaults to cargo's own lookup.
+        #[arg(long, conflicts_with = "wasm")]
+        manifest_path: Option<PathBuf>,
+        /// 32-byte hex salt; fixes the contract address. Random if omitted.
+        #[arg(long, value_parser = parse_hex32)]
+        salt: Option<[u8; 32]>,
+        /// Admin to initialise with. Defaults to the signer.
+        #[arg(long)]
+        admin: Option<String>,
+        /// Skip the `initialize` call and the read-back.
+        #[arg(long)]
+        no_init: bool,
+        /// Overwrite a contract id the profile already has.
+        #[arg(long)]
+        replace: bool,
+        /// Do not write the contract id to the profiles file.
+        #[arg(long)]
+        no_save: bool,
+        /// Only simulate the upload (later steps need it on the ledger).
+        /// Exits nonzero if the simulation fails.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Call `initialize` on an existing instance.
+    Initialize {
+        /// Defaults to the signer.
+        #[arg(long)]
+        admin: Option<String>,
+        /// Only simulate: print the result, resources, events and host
+        /// diagnostics. Exits nonzero if the simulation fails.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Call `record_payment` (admin only).
+    Record {
+        #[arg(long)]
+        invoice_id: String,
+        /// Payer account (`G...`).
+        #[arg(long)]
+        payer: String,
+        /// `XLM` or `CODE:ISSUER`.
+        #[arg(long, value_parser = parse_asset)]
+        asset: Asset,
+        /// Amount in stroops (7 decimal places).
+        #[arg(long, allow_negative_numbers = true)]
+        amount: i128,
+        /// Only simulate: print the result, resources, events and host
+        /// diagnostics. Exits nonzero if the simulation fails.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Record the payments in a CSV: validate every row, show how they
+    /// differ from chain state, confirm, then record the new ones.
+    Import {
+        /// CSV with `invoice_id`, `payer`, `asset` (or `asset_code` and
+        /// `asset_issuer`) and `amount_stroops` (or decimal `amount`).
+        file: PathBuf,
+        /// Invoice ids looked up per simulation when diffing.
+        #[arg(long, default_value_t = 50)]
+        lookup_batch: usize,
+        /// Record without asking for confirmation.
+        #[arg(long, short)]
+        yes: bool,
+        /// Record the valid rows even if others are invalid or duplicated.
+        #[arg(long)]
+        skip_invalid: bool,
+        /// Only diff and simulate each new row. Exits nonzero if any row
+        /// would not be imported.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Print the payment record for an invoice as JSON.
+    Get { invoice_id: String },
+
+    /// Print the number of recorded payments.
+    Count,
+
+    /// Transfer admin rights; both the current and the new admin sign.
+    SetAdmin {
+        /// Secret seed of the incoming admin.
+        #[arg(long, env = "INVOISIO_NEW_ADMIN_SECRET", hide_env_values = true)]
+        new_admin_secret: String,
+        /// Only simulate: print the result, resources, events and host
+        /// diagnostics. Exits nonzero if the simulation fails.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Rotate the admin to a freshly generated key: fund it, transfer
+    /// admin rights, verify, then replace the secret file. Safe to rerun
+    /// after a failure.
+    RotateAdmin {
+        /// File holding the current admin secret. Receives the new secret;
+        /// the old one is kept in `<file>.previous`.
+        #[arg(long, env = "INVOISIO_SECRET_FILE")]
+        secret_file: PathBuf,
+        /// Stroops of XLM the current admin funds the new account with.
+        #[arg(long, default_value_t = 50_000_000)]
+        starting_balance: i64,
+        /// Only check the secret file and simulate `set_admin`; nothing is
+        /// written or submitted. Exits nonzero if the simulation fails.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Build, sign and submit transactions as base64 XDR, so several
+    /// parties can sign on offline machines.
+    Tx {
+        #[command(subcommand)]
+        command: TxCommand,
+    },
+
+    /// Print contract events as JSON lines.
+    Events {
+        /// First ledger to scan. Ignored when `--cursor` is given.
+        #[arg(long)]
+        start_ledger: Option<u32>,
+        /// Resume from a cursor printed by a previous run.
+        #[arg(long)]
+        cursor: Option<String>,
+        #[arg(long, default_value_t = 100)]
+        limit: u32,
+        /// Keep polling for new events.
+        #[arg(long)]
+        follow: bool,
+    },
+
+    /// Print a SEP-7 `web+stellar:pay` URI for an invoice, with the
+    /// `invoisio-<id>` memo wallets must pay with.
+    PayUri {
+        #[arg(long)]
+        invoice_id: String,
+        /// Account to pay (`G...` or `M...`).
+        #[arg(long)]
+        destination: String,
+        /// `XLM` or `CODE:ISSUER`.
+        #[arg(long, value_parser = parse_asset)]
+        asset: Asset,
+        /// Amount in stroops (7 decimal places). Left to the payer if omitted.
+        #[arg(long)]
+        amount: Option<i128>,
+        /// Message the wallet shows the payer.
+        #[arg(long)]
+        msg: Option<String>,
+        /// Also write the URI as an SVG QR code here.
+        #[arg(long)]
+        qr: Option<PathBuf>,
+    },
+
+    /// Export recorded payments in a date range as CSV or JSON lines.
+    Export {
+        /// Inclusive start: `YYYY-MM-DD` or RFC 3339.
+        #[arg(long, value_parser = parse_date)]
+        from: u64,
+        /// Exclusive end: `YYYY-MM-DD` or RFC 3339.
+        #[arg(long, value_parser = parse_date)]
+        to: u64,
+        #[arg(long, value_enum, default_value_t = Format::Csv)]
+        format: Format,
+        /// Write here instead of stdout. Appended to when resuming.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// First ledger to scan. Must be within RPC retention.
+        #[arg(long)]
+        start_ledger: Option<u32>,
+        /// Save the cursor here after every page and resume from it.
+        #[arg(long)]
+        cursor_file: Option<PathBuf>,
+        #[arg(long, default_value_t = 200)]
+        limit: u32,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TxCommand {
+    /// Simulate a contract call and print the unsigned envelope. With
+    /// `--from`, re-simulate an envelope whose auth entries are now signed.
+    Build {
+        /// Transaction source (`G...`); its next sequence number is used.
+        #[arg(long, required_unless_present = "from")]
+        source: Option<String>,
+        /// Contract function to call.
+        #[arg(long, required_unless_present = "from", conflicts_with = "from")]
+        function: Option<String>,
+        /// Argument as `TYPE:VALUE` (`address`, `string`, `symbol`, `bool`,
+        /// `u32`, `u64`, `i128`, `asset`). Repeat in call order.
+        #[arg(long = "arg", value_parser = parse_scval)]
+        args: Vec<ScVal>,
+        /// Ledgers auth signatures stay valid for (about 5s each).
+        #[arg(long, default_value_t = 17_280)]
+        auth_ledgers: u32,
+        /// Envelope to re-simulate instead of building a new call.
+        #[arg(long, conflicts_with = "source")]
+        from: Option<PathBuf>,
+    },
+
+    /// Add the `--secret` key's signature. Works offline. The key signs the
+    /// auth entries naming it, or else the envelope.
+    Sign {
+        /// Envelope file; stdin if omitted.
+        input: Option<PathBuf>,
+    },
+
+    /// Submit a fully signed envelope and wait for it to land.
+    Submit {
+        /// Envelope file; stdin if omitted.
+        input: Option<PathBuf>,
+        /// Only simulate the envelope, signed auth included. Exits nonzero
+        /// if the simulation fails.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Parse a contract argument written as `TYPE:VALUE`.
+pub fn parse_scval(s: &str) -> Result<ScVal, String> {
+    let (kind, value) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected `TYPE:VALUE`, got `{s}`"))?;
+    let bad = |e: &dyn std::fmt::Display| format!("invalid {kind} `{value}`: {e}");
+    match kind {
+        "address" => scval::address(value).map_err(|e| bad(&e)),
+        "string" => scval::string(value).map_err(|e| bad(&e)),
+        "symbol" => scval::symbol(value).map_err(|e| bad(&e)),
+        "bool" => value.parse().map(ScVal::Bool).map_err(|e| bad(&e)),
+        "u32" => value.parse().map(ScVal::U32).map_err(|e| bad(&e)),
+        "u64" => value.parse().map(ScVal::U64).map_err(|e| bad(&e)),
+        "i128" => value.parse().map(scval::i128).map_err(|e| bad(&e)),
+        "asset" => ScVal::try_from(&parse_asset(value)?).map_err(|e| bad(&e)),
+        _ => Err(format!("unknown argument type `{kind}`")),
+    }
+}
+
+/// Parse `XLM` / `native` or `CODE:ISSUER`.
+pub fn parse_asset(s: &str) -> Result<Asset, String> {
+    if s.eq_ignore_ascii_case("xlm") || s.eq_ignore_ascii_case("native") {
+        return Ok(Asset::Native);
+    }
+    match s.split_once(':') {
+        Some((code, issuer)) if !code.is_empty() && !issuer.is_empty() => Ok(Asset::Token {
+            code: code.to_string(),
+            issuer: issuer.to_string(),
+        }),
+        _ => Err(format!("expected `XLM` or `CODE:ISSUER`, got `{s}`")),
+    }
+}
+
+pub fn parse_hex32(s: &str) -> Result<[u8; 32], String> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.len() != 64 {
+        return Err(format!("expected 64 hex characters, got {}", s.len()));
+    }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16)
+            .map_err(|_| format!("invalid hex `{s}`"))?;
+    }
+    Ok(out)
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,395 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use invoisio_client::client::record_payment_args;
+use invoisio_client::{
+    scval, DryRun, EventStart, InvoicePaymentClient, Keypair, Network, PayRequest, Profiles,
+    RecordPayment, TxOutcome,
+};
+use serde_json::json;
+use stellar_xdr::curr::ScVal;
+
+use crate::cli::{Cli, Command, GlobalArgs, TxCommand};
+use crate::deploy::{Deploy, Target};
+use crate::error::{Error, Result};
+use crate::export::Export;
+use crate::import::{self, Import};
+use crate::rotate::Rotation;
+use crate::tx::{self, Signed};
+
+/// How long write commands wait for confirmation.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+/// `events --follow` poll interval once caught up.
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Resolved global flags: the selected profile with explicit flags applied.
+struct Context {
+    network: Network,
+    profiles: PathBuf,
+    rpc_url: String,
+    passphrase: String,
+    contract_id: Option<String>,
+    secret: Option<String>,
+}
+
+impl Context {
+    fn new(g: GlobalArgs) -> Result<Self> {
+        let profiles = Profiles::load(&g.profiles)?;
+        let profile = profiles.resolve(g.network);
+        if let Some(id) = &g.contract_id {
+            profiles.check_contract(g.network, id)?;
+        }
+        Ok(Self {
+            network: g.network,
+            profiles: g.profiles,
+            rpc_url: g.rpc_url.unwrap_or(profile.rpc_url),
+            passphrase: g.network_passphrase.unwrap_or(profile.passphrase),
+            contract_id: g.contract_id.or(profile.contract_id),
+            secret: g.secret,
+        })
+    }
+
+    fn client(&self) -> Result<InvoicePaymentClient> {
+        let contract_id = self.contract_id.as_deref().ok_or_else(|| {
+            Error::Usage(
+                "no contract id: set one in the profiles file or pass --contract-id".into(),
+            )
+        })?;
+        Ok(
+            InvoicePaymentClient::new(&self.rpc_url, &self.passphrase, contract_id)?
+                .with_confirm_timeout(CONFIRM_TIMEOUT),
+        )
+    }
+
+    fn signer(&self) -> Result<Keypair> {
+        let secret = self
+            .secret
+            .as_deref()
+            .ok_or_else(|| Error::Usage("--secret (or INVOISIO_SECRET) is required".into()))?;
+        Ok(Keypair::from_secret(secret)?)
+    }
+}
+
+pub async fn run(cli: Cli) -> Result<()> {
+    let ctx = Context::new(cli.global)?;
+    match cli.command {
+        Command::Deploy {
+            wasm,
+            package,
+            manifest_path,
+            salt,
+            admin,
+            no_init,
+            replace,
+            no_save,
+            dry_run,
+        } => {
+            let deploy = Deploy {
+                wasm,
+                package,
+                manifest_path,
+                salt,
+                admin,
+                no_init,
+                replace,
+                no_save,
+            };
+            let target = Target {
+                network: ctx.network,
+                profiles: &ctx.profiles,
+                rpc_url: &ctx.rpc_url,
+                passphrase: &ctx.passphrase,
+            };
+            let signer = ctx.signer()?;
+            if dry_run {
+                return print_dry_run(deploy.dry_run(&target, &signer).await?);
+            }
+            print_json(&deploy.run(&target, &signer, CONFIRM_TIMEOUT).await?)
+        }
+        Command::Initialize { admin, dry_run } => {
+            let signer = ctx.signer()?;
+            let admin = admin.unwrap_or_else(|| signer.public_key());
+            if dry_run {
+                let args = vec![scval::address(&admin)?];
+                let run = ctx
+                    .client()?
+                    .dry_run(&signer.public_key(), "initialize", args)
+                    .await?;
+                return print_dry_run(run);
+            }
+            let outcome = ctx.client()?.initialize(&signer, &admin).await?;
+            print_outcome(&outcome)
+        }
+        Command::Record {
+            invoice_id,
+            payer,
+            asset,
+            amount,
+            dry_run,
+        } => {
+            let payment = RecordPayment {
+                invoice_id,
+                payer,
+                asset,
+                amount,
+            };
+            if dry_run {
+                let source = ctx.signer()?.public_key();
+                let args = record_payment_args(&payment)?;
+                let run = ctx
+                    .client()?
+                    .dry_run(&source, "record_payment", args)
+                    .await?;
+                return print_dry_run(run);
+            }
+            let outcome = ctx
+                .client()?
+                .record_payment(&ctx.signer()?, &payment)
+                .await?;
+            print_outcome(&outcome)
+        }
+        Command::Import {
+            file,
+            lookup_batch,
+            yes,
+            skip_invalid,
+            dry_run,
+        } => {
+            let counts = Import {
+                file,
+                lookup_batch,
+                yes,
+                skip_invalid,
+                dry_run,
+            }
+            .run(&ctx.client()?, &ctx.signer()?)
+            .await?;
+            eprintln!("{}", import::summary(&counts));
+            let problems: usize = counts
+                .iter()
+                .filter(|(status, _)| status.is_problem())
+                .map(|(_, n)| n)
+                .sum();
+            if problems > 0 {
+                return Err(Error::Import(format!("{problems} rows not imported")));
+            }
+            Ok(())
+        }
+        Command::Get { invoice_id } => print_json(&ctx.client()?.get_payment(&invoice_id).await?),
+        Command::Count => print_json(&ctx.client()?.payment_count().await?),
+        Command::SetAdmin {
+            new_admin_secret,
+            dry_run,
+        } => {
+            let new_admin = Keypair::from_secret(&new_admin_secret)?;
+            if dry_run {
+                let client = ctx.client()?;
+                let args = vec![
+                    scval::address(&new_admin.public_key())?,
+                    ScVal::U64(client.admin_nonce().await?),
+                ];
+                let source = ctx.signer()?.public_key();
+                return print_dry_run(client.dry_run(&source, "set_admin", args).await?);
+            }
+            let outcome = ctx
+                .client()?
+                .set_admin(&ctx.signer()?, &new_admin)
+                .await?;
+            print_outcome(&outcome)
+        }
+        Command::RotateAdmin {
+            secret_file,
+            starting_balance,
+            dry_run,
+        } => {
+            let rotation = Rotation {
+                secret_file,
+                starting_balance,
+            };
+            let client = ctx.client()?;
+            if dry_run {
+                return print_dry_run(rotation.dry_run(&client).await?);
+            }
+            print_json(&rotation.run(&client).await?)
+        }
+        Command::Tx { command } => run_tx(&ctx, command).await,
+        Command::Events {
+            start_ledger,
+            cursor,
+            limit,
+            follow,
+        } => {
+            let client = ctx.client()?;
+            let mut start = match (cursor, start_ledger) {
+                (Some(c), _) => EventStart::Cursor(c),
+                (None, Some(l)) => EventStart::Ledger(l),
+                (None, None) => {
+                    return Err(Error::Usage(
+                        "one of --start-ledger or --cursor is required".into(),
+                    ))
+                }
+            };
+            loop {
+                let page = client.events(start.clone(), limit).await?;
+                for event in &page.events {
+                    println!("{}", serde_json::to_string(event)?);
+                }
+                if let Some(cursor) = &page.cursor {
+                    eprintln!("cursor: {cursor}");
+                    start = EventStart::Cursor(cursor.clone());
+                }
+                if !follow {
+                    return Ok(());
+                }
+                if page.events.len() < limit as usize {
+                    tokio::time::sleep(FOLLOW_INTERVAL).await;
+                }
+            }
+        }
+        Command::PayUri {
+            invoice_id,
+            destination,
+            asset,
+            amount,
+            msg,
+            qr,
+        } => {
+            let mut request = PayRequest::for_invoice(&destination, &invoice_id, asset, amount)?
+                .on_network(&ctx.passphrase);
+            if let Some(msg) = msg {
+                request = request.with_msg(msg)?;
+            }
+            println!("{}", request.to_uri());
+            if let Some(path) = qr {
+                std::fs::write(&path, request.qr_svg()?)?;
+                eprintln!("qr code: {}", path.display());
+            }
+            Ok(())
+        }
+        Command::Export {
+            from,
+            to,
+            format,
+            output,
+            start_ledger,
+            cursor_file,
+            limit,
+        } => {
+            let summary = Export {
+                from,
+                to,
+                format,
+                output,
+                start_ledger,
+                cursor_file,
+                limit,
+            }
+            .run(&ctx.client()?)
+            .await?;
+            eprintln!(
+                "exported {} payments ({} events scanned)",
+                summary.rows, summary.events_scanned
+            );
+            if let Some(cursor) = summary.cursor {
+                eprintln!("cursor: {cursor}");
+            }
+            Ok(())
+        }
+    }
+}
+
+async fn run_tx(ctx: &Context, command: TxCommand) -> Result<()> {
+    match command {
+        TxCommand::Build {
+            source,
+            function,
+            args,
+            auth_ledgers,
+            from,
+        } => {
+            let client = ctx.client()?;
+            let envelope = match (from, source, function) {
+                (Some(path), _, _) => tx::rebuild(&client, tx::read(Some(&path))?).await?,
+                (None, Some(source), Some(function)) => {
+                    tx::build(&client, &source, &function, args, auth_ledgers).await?
+                }
+                _ => unreachable!("clap requires --from or --source and --function"),
+            };
+            let unsigned = tx::unsigned_auth(&envelope)?;
+            if !unsigned.is_empty() {
+                eprintln!("auth signatures needed from: {}", unsigned.join(", "));
+            }
+            tx::print(&envelope)
+        }
+        TxCommand::Sign { input } => {
+            let envelope = tx::read(input.as_deref())?;
+            let signer = ctx.signer()?;
+            let (envelope, signed) = tx::sign(envelope, &signer, &ctx.passphrase).await?;
+            match signed {
+                Signed::AuthEntries(n) => {
+                    eprintln!("signed {n} auth entries; next: tx build --from")
+                }
+                Signed::Envelope => eprintln!("signed the envelope"),
+                Signed::Unchanged => eprintln!("envelope already signed by this key"),
+            }
+            let unsigned = tx::unsigned_auth(&envelope)?;
+            if signed == Signed::Envelope && !unsigned.is_empty() {
+                let unsigned = unsigned.join(", ");
+                eprintln!("warning: signing the auth of {unsigned} later voids this signature");
+            }
+            tx::print(&envelope)
+        }
+        TxCommand::Submit { input, dry_run } => {
+            let envelope = tx::read(input.as_deref())?;
+            if dry_run {
+                return print_dry_run(tx::dry_run(&ctx.client()?, envelope).await?);
+            }
+            let unsigned = tx::unsigned_auth(&envelope)?;
+            if !unsigned.is_empty() {
+                return Err(Error::Usage(format!(
+                    "auth entries still unsigned: {}",
+                    unsigned.join(", ")
+                )));
+            }
+            print_outcome(&ctx.client()?.submit(&envelope).await?)
+        }
+    }
+}
+
+fn print_outcome(outcome: &TxOutcome) -> Result<()> {
+    print_json(&json!({ "hash": outcome.hash, "ledger": outcome.ledger }))
+}
+
+/// Print what a dry run found, then fail if the simulation did.
+fn print_dry_run(run: DryRun) -> Result<()> {
+    let resources = run.estimate.as_ref().map(|e| {
+        json!({
+            "instructions": e.instructions,
+            "disk_read_bytes": e.disk_read_bytes,
+            "write_bytes": e.write_bytes,
+            "read_only_entries": e.read_only.len(),
+            "read_write_entries": e.read_write.len(),
+            "resource_fee": e.resource_fee,
+            "inclusion_fee": e.inclusion_fee,
+            "total_fee": e.total_fee(),
+        })
+    });
+    print_json(&json!({
+        "ok": run.error.is_none(),
+        "error": run.error.as_ref().map(ToString::to_string),
+        "result": run.result.as_ref().map(scval::display),
+        "resources": resources,
+        "auth": run.auth,
+        "events": run.events,
+        "diagnostics": run.diagnostics,
+    }))?;
+    match run.error {
+        Some(err) => Err(err.into()),
+        None => Ok(()),
+    }
+}
+
+fn print_json(value: &impl serde::Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
@@ -0,0 +1,231 @@
+//! `deploy`: take the contract from source to a verified instance that the
+//! network profile points at.
+//!
+//! 1. Build the WASM with cargo, unless `--wasm` names one.
+//! 2. Check the RPC endpoint serves the selected network, and that the
+//!    profile has no contract id yet (unless `--replace`).
+//! 3. Upload the code and create the instance.
+//! 4. Call `initialize`, then read `admin` and `payment_count` back.
+//! 5. Write the contract id into the network's section of the profiles
+//!    file.
+//!
+//! The profile is only written once the reads agree, so a half-finished
+//! deployment never becomes the network's configured contract.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use invoisio_client::{
+    dry_run, tx as xdr, DryRun, InvoicePaymentClient, Keypair, Network, Profiles, RpcClient,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::cli::to_hex;
+use crate::error::{Error, Result};
+
+/// Target `stellar contract build` compiles for.
+pub const WASM_TARGET: &str = "wasm32v1-none";
+
+/// What the deployment did.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub contract_id: String,
+    pub wasm_hash: String,
+    pub upload_tx: String,
+    pub create_tx: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initialize_tx: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin: Option<String>,
+    /// Profiles file the contract id was written to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<PathBuf>,
+}
+
+pub struct Deploy {
+    /// Prebuilt WASM; built from `package` if `None`.
+    pub wasm: Option<PathBuf>,
+    pub package: String,
+    pub manifest_path: Option<PathBuf>,
+    pub salt: Option<[u8; 32]>,
+    /// Defaults to the signer.
+    pub admin: Option<String>,
+    pub no_init: bool,
+    /// Overwrite a contract id already in the profile.
+    pub replace: bool,
+    /// Leave the profiles file alone.
+    pub no_save: bool,
+}
+
+/// Where the deployment goes.
+pub struct Target<'a> {
+    pub network: Network,
+    pub profiles: &'a Path,
+    pub rpc_url: &'a str,
+    pub passphrase: &'a str,
+}
+
+impl Deploy {
+    pub async fn run(
+        &self,
+        target: &Target<'_>,
+        signer: &Keypair,
+        timeout: Duration,
+    ) -> Result<Report> {
+        let wasm = self.wasm()?;
+        let rpc = RpcClient::new(target.rpc_url);
+        self.preflight(target, &rpc).await?;
+
+        let salt = self.salt.unwrap_or_else(|| random_salt(signer));
+        let deployment =
+            invoisio_client::deploy(&rpc, target.passphrase, signer, &wasm, salt, timeout).await?;
+        let mut report = Report {
+            contract_id: deployment.contract_id.clone(),
+            wasm_hash: to_hex(&deployment.wasm_hash),
+            upload_tx: deployment.upload.hash,
+            create_tx: deployment.create.hash,
+            initialize_tx: None,
+            admin: None,
+            profiles: None,
+        };
+        eprintln!("created {}", report.contract_id);
+
+        if !self.no_init {
+            let admin = self.admin.clone().unwrap_or_else(|| signer.public_key());
+            let client =
+                InvoicePaymentClient::with_rpc(rpc, target.passphrase, &report.contract_id)?
+                    .with_confirm_timeout(timeout);
+            report.initialize_tx = Some(client.initialize(signer, &admin).await?.hash);
+            verify(&client, &admin).await?;
+            report.admin = Some(admin);
+        }
+
+        if !self.no_save {
+            Profiles::save_contract_id(target.profiles, target.network, &report.contract_id)?;
+            report.profiles = Some(target.profiles.to_path_buf());
+        }
+        Ok(report)
+    }
+
+    /// Run the checks and simulate the upload. Creating the instance needs
+    /// the code on the ledger, so later steps are not simulated.
+    pub async fn dry_run(&self, target: &Target<'_>, signer: &Keypair) -> Result<DryRun> {
+        let wasm = self.wasm()?;
+        let rpc = RpcClient::new(target.rpc_url);
+        self.preflight(target, &rpc).await?;
+
+        let source = signer.public_key_bytes();
+        let sequence = rpc.get_account_sequence(source).await? + 1;
+        let upload = xdr::build_upload(source, sequence, &wasm)?;
+        let salt = self.salt.unwrap_or_else(|| random_salt(signer));
+        let contract_id = xdr::deployed_contract_id(source, salt, target.passphrase)?;
+        eprintln!("upload only; the instance would be {contract_id}");
+        Ok(dry_run::dry_run(&rpc, upload, None).await?)
+    }
+
+    fn wasm(&self) -> Result<Vec<u8>> {
+        let path = match &self.wasm {
+            Some(path) => path.clone(),
+            None => build_wasm(&self.package, self.manifest_path.as_deref())?,
+        };
+        eprintln!("wasm: {}", path.display());
+        Ok(std::fs::read(path)?)
+    }
+
+    /// Fail before spending fees on a deployment that could not be saved
+    /// or that would land on the wrong network.
+    async fn preflight(&self, target: &Target<'_>, rpc: &RpcClient) -> Result<()> {
+        let remote = rpc.get_network().await?.passphrase;
+        if remote != target.passphrase {
+            return Err(invoisio_client::Error::NetworkMismatch {
+                expected: target.passphrase.to_string(),
+                actual: remote,
+            }
+            .into());
+        }
+        if self.no_save || self.replace {
+            return Ok(());
+        }
+        let profile = Profiles::load(target.profiles)?.resolve(target.network);
+        match profile.contract_id {
+            Some(existing) => Err(Error::Deploy(format!(
+                "{} already sets {} to {existing}; pass --replace to overwrite it",
+                target.profiles.display(),
+                target.network
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Read the new instance back: the admin it was initialised with and no
+/// payments yet.
+async fn verify(client: &InvoicePaymentClient, admin: &str) -> Result<()> {
+    let actual = client.admin().await?;
+    if actual != admin {
+        return Err(Error::Deploy(format!(
+            "admin reads back as {actual}, expected {admin}"
+        )));
+    }
+    let count = client.payment_count().await?;
+    if count != 0 {
+        return Err(Error::Deploy(format!(
+            "new instance reports {count} payments"
+        )));
+    }
+    Ok(())
+}
+
+/// Build `package` for [`WASM_TARGET`] and return the path of its WASM.
+pub fn build_wasm(package: &str, manifest_path: Option<&Path>) -> Result<PathBuf> {
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut command = Command::new(cargo);
+    command.args([
+        "build",
+        "--release",
+        "--target",
+        WASM_TARGET,
+        "--package",
+        package,
+    ]);
+    command.args(["--message-format", "json-render-diagnostics"]);
+    if let Some(path) = manifest_path {
+        command.arg("--manifest-path").arg(path);
+    }
+    let output = command.stderr(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        return Err(Error::Deploy(format!(
+            "cargo build failed ({})",
+            output.status
+        )));
+    }
+    wasm_artifact(&output.stdout, package)
+        .ok_or_else(|| Error::Deploy(format!("cargo build produced no WASM for {package}")))
+}
+
+/// The `.wasm` file cargo's JSON messages report for `package`.
+pub fn wasm_artifact(messages: &[u8], package: &str) -> Option<PathBuf> {
+    let target = package.replace('-', "_");
+    messages
+        .split(|b| *b == b'\n')
+        .filter_map(|line| serde_json::from_slice::<serde_json::Value>(line).ok())
+        .filter(|m| m["reason"] == "compiler-artifact" && m["target"]["name"] == target)
+        .flat_map(|m| m["filenames"].as_array().cloned().unwrap_or_default())
+        .filter_map(|f| f.as_str().map(PathBuf::from))
+        .rfind(|f| f.extension().is_some_and(|e| e == "wasm"))
+}
+
+/// Salt unique per signer and invocation. Not secret; only needs to avoid
+/// colliding with an earlier deployment.
+fn random_salt(signer: &Keypair) -> [u8; 32] {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let mut hasher = Sha256::new();
+    hasher.update(signer.public_key_bytes());
+    hasher.update(nanos.to_be_bytes());
+    hasher.finalize().into()
+}
@@ -0,0 +1,37 @@
+/// Errors surfaced by the CLI. Printed to stderr with a nonzero exit.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Missing or inconsistent flags.
+    #[error("{0}")]
+    Usage(String),
+
+    /// `deploy` stopped before saving the contract id.
+    #[error("deploy: {0}")]
+    Deploy(String),
+
+    /// `rotate-admin` found the chain or key files in an unexpected state.
+    #[error("rotation: {0}")]
+    Rotation(String),
+
+    /// `import` stopped before recording, or some rows were not imported.
+    #[error("import: {0}")]
+    Import(String),
+
+    #[error(transparent)]
+    Client(#[from] invoisio_client::Error),
+
+    #[error("csv: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Malformed transaction envelope.
+    #[error("xdr: {0}")]
+    Xdr(#[from] stellar_xdr::curr::Error),
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
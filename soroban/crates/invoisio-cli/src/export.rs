@@ -0,0 +1,242 @@
+//! `invoisio export`: payment history in a date range, for accountants.
+//!
+//! Walks `payment_recorded` events through `getEvents` and writes one row per
+//! payment whose contract timestamp falls in `[from, to)`. With
+//! `--cursor-file`, the `getEvents` cursor is saved after every page and the
+//! output file is appended to, so an interrupted export of a long history
+//! resumes where it stopped instead of starting over.
+//!
+//! RPC only retains a few days of events; older history must come from the
+//! indexer's database.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use invoisio_client::{ContractEvent, EventEnvelope, EventStart, InvoicePaymentClient, PaymentRecord};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+
+/// Stellar amounts carry 7 decimal places.
+const DECIMALS: usize = 7;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Csv,
+    /// One JSON object per line.
+    Jsonl,
+}
+
+/// One exported payment. Field order is the CSV column order.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ExportRow {
+    pub invoice_id: String,
+    pub payer: String,
+    pub asset_code: String,
+    pub asset_issuer: String,
+    /// Exact integer amount in stroops.
+    pub amount_stroops: String,
+    /// `amount_stroops` with the decimal point applied.
+    pub amount: String,
+    pub timestamp: u64,
+    /// `timestamp` as RFC 3339 UTC.
+    pub recorded_at: String,
+    pub ledger: u32,
+    pub tx_hash: String,
+    pub event_id: String,
+}
+
+impl ExportRow {
+    pub fn new(envelope: &EventEnvelope, record: &PaymentRecord) -> Self {
+        Self {
+            invoice_id: record.invoice_id.clone(),
+            payer: record.payer.clone(),
+            asset_code: record.asset.code().to_string(),
+            asset_issuer: record.asset.issuer().to_string(),
+            amount_stroops: record.amount.to_string(),
+            amount: format_amount(record.amount),
+            timestamp: record.timestamp,
+            recorded_at: humantime::format_rfc3339_seconds(
+                UNIX_EPOCH + Duration::from_secs(record.timestamp),
+            )
+            .to_string(),
+            ledger: envelope.ledger,
+            tx_hash: envelope.tx_hash.clone().unwrap_or_default(),
+            event_id: envelope.id.clone(),
+        }
+    }
+}
+
+/// Export options.
+#[derive(Clone, Debug)]
+pub struct Export {
+    /// Inclusive lower bound, unix seconds.
+    pub from: u64,
+    /// Exclusive upper bound, unix seconds.
+    pub to: u64,
+    pub format: Format,
+    pub output: Option<PathBuf>,
+    pub start_ledger: Option<u32>,
+    pub cursor_file: Option<PathBuf>,
+    pub limit: u32,
+}
+
+/// Totals reported once the export finishes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub rows: u64,
+    pub events_scanned: u64,
+    pub cursor: Option<String>,
+}
+
+impl Export {
+    pub async fn run(&self, client: &InvoicePaymentClient) -> Result<Summary> {
+        if self.from >= self.to {
+            return Err(Error::Usage("--from must be before --to".into()));
+        }
+
+        let saved = match &self.cursor_file {
+            Some(path) => load_cursor(path)?,
+            None => None,
+        };
+        let resuming = saved.is_some();
+        let mut start = match (saved, self.start_ledger) {
+            (Some(c), _) => EventStart::Cursor(c),
+            (None, Some(l)) => EventStart::Ledger(l),
+            (None, None) => {
+                return Err(Error::Usage(
+                    "--start-ledger is required unless resuming from --cursor-file".into(),
+                ))
+            }
+        };
+
+        let out: Box<dyn Write> = match &self.output {
+            Some(path) => Box::new(
+                OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .append(resuming)
+                    .truncate(!resuming)
+                    .open(path)?,
+            ),
+            None => Box::new(std::io::stdout().lock()),
+        };
+        let mut sink = Sink::new(self.format, out, !resuming);
+        let mut summary = Summary::default();
+
+        loop {
+            let page = client.events(start.clone(), self.limit).await?;
+            summary.events_scanned += page.events.len() as u64;
+
+            let mut past_range = false;
+            for envelope in &page.events {
+                let ContractEvent::PaymentRecorded { record } = &envelope.event else {
+                    continue;
+                };
+                if record.timestamp >= self.to {
+                    past_range = true;
+                    break;
+                }
+                if record.timestamp >= self.from {
+                    sink.write(&ExportRow::new(envelope, record))?;
+                    summary.rows += 1;
+                }
+            }
+            sink.flush()?;
+
+            if let Some(cursor) = page.cursor {
+                if let Some(path) = &self.cursor_file {
+                    save_cursor(path, &cursor)?;
+                }
+                summary.cursor = Some(cursor.clone());
+                start = EventStart::Cursor(cursor);
+            }
+            if past_range || page.events.len() < self.limit as usize {
+                return Ok(summary);
+            }
+        }
+    }
+}
+
+enum Sink {
+    Csv(Box<csv::Writer<Box<dyn Write>>>),
+    Jsonl(Box<dyn Write>),
+}
+
+impl Sink {
+    fn new(format: Format, out: Box<dyn Write>, headers: bool) -> Self {
+        match format {
+            Format::Csv => Sink::Csv(Box::new(
+                csv::WriterBuilder::new()
+                    .has_headers(headers)
+                    .from_writer(out),
+            )),
+            Format::Jsonl => Sink::Jsonl(out),
+        }
+    }
+
+    fn write(&mut self, row: &ExportRow) -> Result<()> {
+        match self {
+            Sink::Csv(w) => w.serialize(row)?,
+            Sink::Jsonl(w) => {
+                serde_json::to_writer(&mut *w, row)?;
+                w.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        match self {
+            Sink::Csv(w) => w.flush()?,
+            Sink::Jsonl(w) => w.flush()?,
+        }
+        Ok(())
+    }
+}
+
+/// Format a stroop amount with 7 decimal places: `12_500_000` → `1.2500000`.
+pub fn format_amount(stroops: i128) -> String {
+    let sign = if stroops < 0 { "-" } else { "" };
+    let abs = stroops.unsigned_abs();
+    let scale = 10u128.pow(DECIMALS as u32);
+    format!(
+        "{sign}{}.{:0width$}",
+        abs / scale,
+        abs % scale,
+        width = DECIMALS
+    )
+}
+
+/// Parse `YYYY-MM-DD` (midnight UTC) or an RFC 3339 timestamp into unix
+/// seconds.
+pub fn parse_date(s: &str) -> core::result::Result<u64, String> {
+    let full = if s.len() == 10 {
+        format!("{s}T00:00:00Z")
+    } else {
+        s.to_string()
+    };
+    humantime::parse_rfc3339(&full)
+        .map_err(|e| format!("invalid date `{s}`: {e}"))?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .map_err(|_| format!("date `{s}` is before 1970"))
+}
+
+fn load_cursor(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(s) => Ok(Some(s.trim().to_string()).filter(|s| !s.is_empty())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn save_cursor(path: &Path, cursor: &str) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, cursor)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
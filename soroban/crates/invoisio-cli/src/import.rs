@@ -0,0 +1,380 @@
+//! `invoisio import`: record a CSV of payments, e.g. from a legacy ledger.
+//!
+//! Every row is validated before anything touches the chain. Valid rows are
+//! looked up with `find_payments`, a batch per simulation, and compared
+//! with chain state. The resulting diff is printed and confirmed, then each
+//! new row is recorded with its own `record_payment` call. One JSON line
+//! per row goes to stdout with the row's final status.
+//!
+//! Recorded rows come back as `unchanged` on a rerun, so an interrupted
+//! import is finished by running it again. Conflicting records on chain are
+//! reported and never overwritten.
+//!
+//! Columns are matched by header and unknown columns are ignored, so an
+//! `invoisio export` CSV imports as is:
+//!
+//! - `invoice_id` and `payer` (`G...`, `M...` or `C...`);
+//! - `asset` (`XLM` or `CODE:ISSUER`), or `asset_code` and `asset_issuer`;
+//! - `amount_stroops`, or `amount` with up to 7 decimal places.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, Read};
+use std::path::PathBuf;
+
+use invoisio_client::client::record_payment_args;
+use invoisio_client::{
+    scval, tx, Asset, InvoicePaymentClient, Keypair, PaymentRecord, RecordPayment,
+};
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::{MuxedEd25519Account, ScAddress, Uint256};
+
+use crate::cli::parse_asset;
+use crate::error::{Error, Result};
+
+/// Stellar amounts carry 7 decimal places.
+const DECIMALS: usize = 7;
+
+/// Where a row stands. Ordered as the summary lists them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// Not on chain yet; recorded once confirmed.
+    New,
+    Recorded,
+    /// `--dry-run`: the `record_payment` simulation succeeded.
+    WouldRecord,
+    /// On chain already, with the same payer, asset and amount.
+    Unchanged,
+    /// On chain with a different payer, asset or amount.
+    Conflict,
+    /// Repeats the invoice id of an earlier row.
+    Duplicate,
+    Invalid,
+    Failed,
+}
+
+impl Status {
+    /// Name as serialized.
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::New => "new",
+            Status::Recorded => "recorded",
+            Status::WouldRecord => "would_record",
+            Status::Unchanged => "unchanged",
+            Status::Conflict => "conflict",
+            Status::Duplicate => "duplicate",
+            Status::Invalid => "invalid",
+            Status::Failed => "failed",
+        }
+    }
+
+    /// Whether the row ends the import with a nonzero exit.
+    pub fn is_problem(self) -> bool {
+        matches!(
+            self,
+            Status::Conflict | Status::Duplicate | Status::Invalid | Status::Failed
+        )
+    }
+}
+
+/// One CSV row and what became of it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Row {
+    /// 1-based line in the file, header included.
+    pub line: u64,
+    pub invoice_id: String,
+    pub status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    /// The validated payment; `None` for invalid rows.
+    #[serde(skip)]
+    pub payment: Option<RecordPayment>,
+}
+
+/// Raw columns. Empty cells deserialize as `None`.
+#[derive(Debug, Default, Deserialize)]
+pub struct CsvRow {
+    pub invoice_id: Option<String>,
+    pub payer: Option<String>,
+    pub asset: Option<String>,
+    pub asset_code: Option<String>,
+    pub asset_issuer: Option<String>,
+    pub amount_stroops: Option<String>,
+    pub amount: Option<String>,
+}
+
+/// Import options.
+#[derive(Clone, Debug)]
+pub struct Import {
+    pub file: PathBuf,
+    /// Invoice ids looked up per `find_payments` simulation.
+    pub lookup_batch: usize,
+    /// Record without asking for confirmation.
+    pub yes: bool,
+    /// Record the valid rows even if others are invalid or duplicated.
+    pub skip_invalid: bool,
+    /// Simulate each new row instead of recording it.
+    pub dry_run: bool,
+}
+
+impl Import {
+    /// Run the import and return how many rows ended in each status.
+    pub async fn run(
+        &self,
+        client: &InvoicePaymentClient,
+        signer: &Keypair,
+    ) -> Result<BTreeMap<Status, usize>> {
+        let mut rows = read(std::fs::File::open(&self.file)?)?;
+        diff(client, &mut rows, self.lookup_batch).await?;
+
+        eprintln!("{}", summary(&tally(&rows)));
+        for row in rows.iter().filter(|r| r.status != Status::New) {
+            print_row(row)?;
+        }
+        let new = rows.iter().filter(|r| r.status == Status::New).count();
+        let rejected = rows
+            .iter()
+            .filter(|r| matches!(r.status, Status::Invalid | Status::Duplicate))
+            .count();
+        if rejected > 0 && !self.skip_invalid {
+            return Err(Error::Import(format!(
+                "{rejected} invalid or duplicate rows; fix them or pass --skip-invalid"
+            )));
+        }
+        if new > 0 && !self.dry_run && !self.yes && !confirm(new)? {
+            return Err(Error::Import("nothing recorded".into()));
+        }
+
+        let source = signer.public_key();
+        for row in rows.iter_mut().filter(|r| r.status == Status::New) {
+            let Some(payment) = &row.payment else {
+                continue;
+            };
+            if self.dry_run {
+                let args = record_payment_args(payment)?;
+                let run = client.dry_run(&source, "record_payment", args).await?;
+                match run.error {
+                    None => row.status = Status::WouldRecord,
+                    Some(err) => row.fail(Status::Failed, err.to_string()),
+                }
+            } else {
+                match client.record_payment(signer, payment).await {
+                    Ok(outcome) => {
+                        row.status = Status::Recorded;
+                        row.tx_hash = Some(outcome.hash);
+                    }
+                    // The transaction may still land; a rerun finds out.
+                    Err(err) => row.fail(Status::Failed, err.to_string()),
+                }
+            }
+            print_row(row)?;
+        }
+        Ok(tally(&rows))
+    }
+}
+
+impl Row {
+    fn fail(&mut self, status: Status, detail: String) {
+        self.status = status;
+        self.detail = Some(detail);
+    }
+}
+
+/// Parse and validate a CSV. Rows come back `New`, `Invalid` or
+/// `Duplicate`; only a malformed file is an error.
+pub fn read(input: impl Read) -> Result<Vec<Row>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input);
+    let headers = reader.headers()?.clone();
+    for column in ["invoice_id", "payer"] {
+        if !headers.iter().any(|h| h == column) {
+            return Err(Error::Usage(format!("missing CSV column `{column}`")));
+        }
+    }
+
+    let mut first_line = HashMap::new();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map_or(0, |p| p.line());
+        let raw: CsvRow = record.deserialize(Some(&headers))?;
+        let mut row = Row {
+            line,
+            invoice_id: raw.invoice_id.clone().unwrap_or_default(),
+            status: Status::New,
+            detail: None,
+            tx_hash: None,
+            payment: None,
+        };
+        match validate(raw) {
+            Err(e) => row.fail(Status::Invalid, e),
+            Ok(payment) => match first_line.entry(payment.invoice_id.clone()) {
+                Entry::Occupied(first) => row.fail(
+                    Status::Duplicate,
+                    format!("same invoice id as line {}", first.get()),
+                ),
+                Entry::Vacant(slot) => {
+                    slot.insert(line);
+                    row.payment = Some(payment);
+                }
+            },
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Check one row the way the contract will, so bad rows fail here rather
+/// than one transaction at a time.
+pub fn validate(row: CsvRow) -> core::result::Result<RecordPayment, String> {
+    let invoice_id = row.invoice_id.ok_or("empty invoice_id")?;
+    let payer = row.payer.ok_or("empty payer")?;
+    scval::sc_address(&payer).map_err(|_| format!("invalid payer `{payer}`"))?;
+
+    let asset = match (row.asset, row.asset_code) {
+        (Some(asset), _) => parse_asset(&asset)?,
+        (None, Some(code)) => {
+            let issuer = row.asset_issuer.unwrap_or_default();
+            if code == "XLM" && !issuer.is_empty() {
+                return Err("XLM takes no asset_issuer".into());
+            }
+            Asset::from_code_issuer(&code, &issuer)
+        }
+        (None, None) => return Err("missing asset".into()),
+    };
+    if let Asset::Token { code, issuer } = &asset {
+        if code.len() > 12 || !code.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return Err(format!("invalid asset code `{code}`"));
+        }
+        tx::account_id(issuer).map_err(|_| format!("invalid asset issuer `{issuer}`"))?;
+    }
+
+    let amount = match (row.amount_stroops, row.amount) {
+        (Some(stroops), _) => stroops
+            .parse()
+            .map_err(|_| format!("invalid amount_stroops `{stroops}`"))?,
+        (None, Some(amount)) => parse_amount(&amount)?,
+        (None, None) => return Err("missing amount".into()),
+    };
+    if amount <= 0 {
+        return Err(format!("amount must be positive, got {amount}"));
+    }
+
+    Ok(RecordPayment {
+        invoice_id,
+        payer,
+        asset,
+        amount,
+    })
+}
+
+/// Look up every `New` row on chain and mark those already recorded as
+/// `Unchanged` or `Conflict`.
+pub async fn diff(client: &InvoicePaymentClient, rows: &mut [Row], batch: usize) -> Result<()> {
+    let mut pending: Vec<&mut Row> = rows
+        .iter_mut()
+        .filter(|r| r.status == Status::New)
+        .collect();
+    for chunk in pending.chunks_mut(batch.max(1)) {
+        let ids: Vec<&str> = chunk.iter().map(|r| r.invoice_id.as_str()).collect();
+        let found = client.find_payments(&ids).await?;
+        for row in chunk.iter_mut() {
+            let (Some(payment), Some(Some(record))) = (&row.payment, found.get(&row.invoice_id))
+            else {
+                continue;
+            };
+            match compare(payment, record)? {
+                None => row.status = Status::Unchanged,
+                Some(detail) => row.fail(Status::Conflict, detail),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// How `record` differs from `payment`, or `None` if it matches.
+pub fn compare(payment: &RecordPayment, record: &PaymentRecord) -> Result<Option<String>> {
+    let on_chain_payer = match record.payer_mux_id {
+        Some(id) => scval::address_to_strkey(&ScAddress::MuxedAccount(MuxedEd25519Account {
+            id,
+            ed25519: Uint256(tx::account_id(&record.payer)?),
+        }))?,
+        None => record.payer.clone(),
+    };
+    let mut differences = Vec::new();
+    if payment.payer != on_chain_payer {
+        differences.push(format!("payer {on_chain_payer}"));
+    }
+    if payment.asset != record.asset {
+        let asset = &record.asset;
+        differences.push(format!("asset {}:{}", asset.code(), asset.issuer()));
+    }
+    if payment.amount != record.amount {
+        differences.push(format!("amount {}", record.amount));
+    }
+    if differences.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(format!("on chain with {}", differences.join(", "))))
+}
+
+/// Parse a decimal amount into stroops: `1.25` → `12_500_000`.
+pub fn parse_amount(s: &str) -> core::result::Result<i128, String> {
+    let invalid = || format!("invalid amount `{s}`");
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if whole.is_empty()
+        || fraction.len() > DECIMALS
+        || !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let stroops: i128 = format!("{whole}{fraction:0<DECIMALS$}")
+        .parse()
+        .map_err(|_| invalid())?;
+    Ok(if negative { -stroops } else { stroops })
+}
+
+/// How many rows are in each status.
+pub fn tally(rows: &[Row]) -> BTreeMap<Status, usize> {
+    let mut counts = BTreeMap::new();
+    for row in rows {
+        *counts.entry(row.status).or_default() += 1;
+    }
+    counts
+}
+
+/// One-line summary, e.g. `new 12, unchanged 3, conflict 1`.
+pub fn summary(counts: &BTreeMap<Status, usize>) -> String {
+    if counts.is_empty() {
+        return "no rows".into();
+    }
+    counts
+        .iter()
+        .map(|(status, n)| format!("{} {n}", status.name()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_row(row: &Row) -> Result<()> {
+    println!("{}", serde_json::to_string(row)?);
+    Ok(())
+}
+
+/// Ask on stderr / stdin before recording `new` rows.
+fn confirm(new: usize) -> Result<bool> {
+    eprint!("record {new} payments? [y/N] ");
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
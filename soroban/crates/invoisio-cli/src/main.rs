@@ -0,0 +1,38 @@
+//! # invoisio
+//!
+//! Operator CLI for the `invoice-payment` contract, built on
+//! `invoisio-client`. Replaces ad-hoc `stellar contract invoke` scripts.
+//!
+//! ## Module layout
+//! | Module        | Responsibility                            |
+//! |---------------|-------------------------------------------|
+//! | `cli.rs`      | clap definitions and argument parsers     |
+//! | `commands.rs` | Subcommand execution                      |
+//! | `deploy.rs`   | `deploy`: build, deploy, verify, save     |
+//! | `export.rs`   | `export`: CSV / JSON-lines payment export |
+//! | `import.rs`   | `import`: bulk CSV payment import         |
+//! | `rotate.rs`   | `rotate-admin`: admin key rotation        |
+//! | `tx.rs`       | `tx build` / `sign` / `submit`: multisig  |
+//! | `error.rs`    | [`Error`](error::Error) / exit reporting  |
+
+mod cli;
+mod commands;
+mod deploy;
+mod error;
+mod export;
+mod import;
+mod rotate;
+mod tx;
+
+use clap::Parser;
+
+#[tokio::main]
+async fn main() {
+    let cli = cli::Cli::parse();
+    if let Err(err) = commands::run(cli).await {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+mod test;
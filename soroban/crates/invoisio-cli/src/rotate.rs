@@ -0,0 +1,198 @@
+//! `rotate-admin`: replace the contract admin key end to end.
+//!
+//! The steps run in an order that is safe to repeat after a crash:
+//!
+//! 1. Generate the new key and write it to `<secret-file>.pending` before
+//!    anything touches the chain. A rerun picks the same key up again.
+//! 2. Create the new account, funded by the current admin, unless it
+//!    already exists.
+//! 3. Call `set_admin`, signed by both keys, unless the contract already
+//!    names the new key.
+//! 4. Read `admin` back and check it is the new key.
+//! 5. Copy the old secret to `<secret-file>.previous`, then rename the
+//!    pending file over the secret file.
+//!
+//! The secret file is only replaced once the contract is verified, so it
+//! always holds a key the contract accepts (or, at worst, the outgoing one
+//! with the new key still in `.pending`).
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use invoisio_client::{scval, DryRun, InvoicePaymentClient, Keypair};
+use rand::RngCore;
+use serde::Serialize;
+use stellar_xdr::curr::ScVal;
+
+use crate::error::{Error, Result};
+
+/// What the rotation did. Steps already done by an earlier run are `None`.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub old_admin: String,
+    pub new_admin: String,
+    pub create_account_tx: Option<String>,
+    pub set_admin_tx: Option<String>,
+    pub previous_secret_file: PathBuf,
+}
+
+pub struct Rotation {
+    /// Holds the current admin secret; replaced with the new one.
+    pub secret_file: PathBuf,
+    /// Stroops of XLM the current admin sends to the new account.
+    pub starting_balance: i64,
+}
+
+impl Rotation {
+    pub async fn run(&self, client: &InvoicePaymentClient) -> Result<Report> {
+        let current = Keypair::from_secret(&read_secret(&self.secret_file)?)?;
+        let pending_file = with_suffix(&self.secret_file, "pending");
+        let new = match read_pending(&pending_file)? {
+            Some(key) => key,
+            None => {
+                let key = generate();
+                write_secret(&pending_file, &key.secret())?;
+                key
+            }
+        };
+
+        let admin = client.admin().await?;
+        let switched = admin == new.public_key();
+        if !switched && admin != current.public_key() {
+            return Err(Error::Rotation(format!(
+                "{} does not hold the contract admin ({admin})",
+                self.secret_file.display()
+            )));
+        }
+
+        let mut report = Report {
+            old_admin: current.public_key(),
+            new_admin: new.public_key(),
+            create_account_tx: None,
+            set_admin_tx: None,
+            previous_secret_file: with_suffix(&self.secret_file, "previous"),
+        };
+        if !switched {
+            let exists = client
+                .rpc()
+                .find_account_sequence(new.public_key_bytes())
+                .await?
+                .is_some();
+            if !exists {
+                let outcome = client
+                    .create_account(&current, &new.public_key(), self.starting_balance)
+                    .await?;
+                report.create_account_tx = Some(outcome.hash);
+            }
+            report.set_admin_tx = Some(client.set_admin(&current, &new).await?.hash);
+        }
+
+        let admin = client.admin().await?;
+        if admin != new.public_key() {
+            return Err(Error::Rotation(format!(
+                "contract admin is {admin} after set_admin, expected {}",
+                new.public_key()
+            )));
+        }
+
+        let previous = &report.previous_secret_file;
+        install(&self.secret_file, &pending_file, previous)?;
+        Ok(report)
+    }
+
+    /// Check the secret file holds the admin and simulate the `set_admin`
+    /// call. Nothing is written or submitted; the account creation is a
+    /// classic operation and is not simulated.
+    pub async fn dry_run(&self, client: &InvoicePaymentClient) -> Result<DryRun> {
+        let current = Keypair::from_secret(&read_secret(&self.secret_file)?)?;
+        // An interrupted rotation's key if there is one, else a throwaway.
+        let pending = read_pending(&with_suffix(&self.secret_file, "pending"))?;
+        let new = pending.unwrap_or_else(generate);
+        let admin = client.admin().await?;
+        if admin == new.public_key() {
+            return Err(Error::Rotation(
+                "set_admin already landed; run without --dry-run to finish".into(),
+            ));
+        }
+        if admin != current.public_key() {
+            return Err(Error::Rotation(format!(
+                "{} does not hold the contract admin ({admin})",
+                self.secret_file.display()
+            )));
+        }
+        let args = vec![
+            scval::address(&new.public_key())?,
+            ScVal::U64(client.admin_nonce().await?),
+        ];
+        Ok(client
+            .dry_run(&current.public_key(), "set_admin", args)
+            .await?)
+    }
+}
+
+/// A key from the OS random number generator.
+fn generate() -> Keypair {
+    let mut seed = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut seed);
+    Keypair::from_seed(seed)
+}
+
+/// The key an interrupted rotation left in `path`, if any.
+fn read_pending(path: &Path) -> Result<Option<Keypair>> {
+    match std::fs::read_to_string(path) {
+        Ok(secret) => Ok(Some(Keypair::from_secret(secret.trim())?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Read a secret file, ignoring surrounding whitespace.
+pub fn read_secret(path: &Path) -> Result<String> {
+    Ok(std::fs::read_to_string(path)?.trim().to_string())
+}
+
+/// `path` with `.suffix` appended to its file name.
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Durably write `secret` to `path`, readable by the owner only. Goes
+/// through a temporary file so `path` is never left half-written.
+pub fn write_secret(path: &Path, secret: &str) -> Result<()> {
+    let tmp = with_suffix(path, "tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&tmp)?;
+    writeln!(file, "{secret}")?;
+    file.sync_all()?;
+    std::fs::rename(&tmp, path)?;
+    sync_dir(path)
+}
+
+/// Keep the outgoing secret as `previous`, then atomically move `pending`
+/// over `secret_file`.
+pub fn install(secret_file: &Path, pending: &Path, previous: &Path) -> Result<()> {
+    write_secret(previous, &read_secret(secret_file)?)?;
+    std::fs::rename(pending, secret_file)?;
+    sync_dir(secret_file)
+}
+
+/// Flush the directory entry of `path` so a rename survives a crash.
+fn sync_dir(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let dir = match path.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p,
+            _ => Path::new("."),
+        };
+        std::fs::File::open(dir)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
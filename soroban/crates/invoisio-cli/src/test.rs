@@ -0,0 +1,539 @@
+#![cfg(test)]
+
+use clap::{CommandFactory, Parser};
+use invoisio_client::{Asset, Network};
+
+use crate::cli::{parse_asset, parse_hex32, to_hex, Cli, Command, TxCommand};
+
+const ISSUER: &str = "GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5";
+
+#[test]
+fn test_cli_definition_is_valid() {
+    Cli::command().debug_assert();
+}
+
+#[test]
+fn test_parse_asset() {
+    assert_eq!(parse_asset("XLM").unwrap(), Asset::Native);
+    assert_eq!(parse_asset("native").unwrap(), Asset::Native);
+    assert_eq!(
+        parse_asset(&format!("USDC:{ISSUER}")).unwrap(),
+        Asset::Token {
+            code: "USDC".into(),
+            issuer: ISSUER.into()
+        }
+    );
+    assert!(parse_asset("USDC").is_err());
+    assert!(parse_asset("USDC:").is_err());
+}
+
+#[test]
+fn test_hex32_round_trips() {
+    let bytes = [0xabu8; 32];
+    assert_eq!(parse_hex32(&to_hex(&bytes)).unwrap(), bytes);
+    assert_eq!(parse_hex32(&format!("0x{}", to_hex(&bytes))).unwrap(), bytes);
+    assert!(parse_hex32("abcd").is_err());
+    assert!(parse_hex32(&"zz".repeat(32)).is_err());
+}
+
+#[test]
+fn test_record_parses_flags() {
+    let cli = Cli::try_parse_from([
+        "invoisio",
+        "--network",
+        "mainnet",
+        "record",
+        "--invoice-id",
+        "invoisio-1",
+        "--payer",
+        ISSUER,
+        "--asset",
+        "XLM",
+        "--amount",
+        "10000000",
+    ])
+    .unwrap();
+    assert_eq!(cli.global.network, Network::Mainnet);
+    assert!(matches!(
+        cli.command,
+        Command::Record { amount: 10_000_000, asset: Asset::Native, .. }
+    ));
+}
+
+// Export
+
+#[test]
+fn test_format_amount_applies_seven_decimals() {
+    use crate::export::format_amount;
+    assert_eq!(format_amount(12_500_000), "1.2500000");
+    assert_eq!(format_amount(1), "0.0000001");
+    assert_eq!(format_amount(0), "0.0000000");
+    assert_eq!(format_amount(-10_000_000), "-1.0000000");
+    assert_eq!(
+        format_amount(i128::MIN),
+        "-17014118346046923173168730371588.4105728"
+    );
+}
+
+#[test]
+fn test_parse_date_accepts_day_and_rfc3339() {
+    use crate::export::parse_date;
+    assert_eq!(parse_date("1970-01-02").unwrap(), 86_400);
+    assert_eq!(parse_date("2023-11-14T22:13:20Z").unwrap(), 1_700_000_000);
+    assert!(parse_date("14/11/2023").is_err());
+}
+
+#[test]
+fn test_export_row_csv_columns() {
+    use crate::export::ExportRow;
+    use invoisio_client::{ContractEvent, EventEnvelope, PaymentRecord};
+
+    let record = PaymentRecord {
+        invoice_id: "invoisio-1".into(),
+        payer: ISSUER.into(),
+        payer_mux_id: None,
+        asset: Asset::Native,
+        amount: 12_500_000,
+        timestamp: 1_700_000_000,
+    };
+    let envelope = EventEnvelope {
+        id: "0001-1".into(),
+        ledger: 7,
+        ledger_closed_at: "2023-11-14T22:13:20Z".into(),
+        contract_id: "C".into(),
+        tx_hash: None,
+        event: ContractEvent::PaymentRecorded {
+            record: record.clone(),
+        },
+    };
+
+    let mut w = csv::Writer::from_writer(Vec::new());
+    w.serialize(ExportRow::new(&envelope, &record)).unwrap();
+    let text = String::from_utf8(w.into_inner().unwrap()).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "invoice_id,payer,asset_code,asset_issuer,amount_stroops,amount,timestamp,recorded_at,ledger,tx_hash,event_id"
+    );
+    assert_eq!(
+        lines.next().unwrap(),
+        format!("invoisio-1,{ISSUER},XLM,,12500000,1.2500000,1700000000,2023-11-14T22:13:20Z,7,,0001-1")
+    );
+}
+
+// Deploy
+
+#[test]
+fn test_deploy_builds_unless_given_a_wasm() {
+    let cli =
+        Cli::try_parse_from(["invoisio", "--network", "testnet", "deploy", "--replace"]).unwrap();
+    match cli.command {
+        Command::Deploy {
+            wasm,
+            package,
+            replace,
+            no_save,
+            ..
+        } => {
+            assert_eq!(wasm, None);
+            assert_eq!(package, "invoice-payment");
+            assert!(replace && !no_save);
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+    assert!(
+        Cli::try_parse_from(["invoisio", "deploy", "--wasm", "c.wasm", "--package", "x"]).is_err()
+    );
+}
+
+#[test]
+fn test_deploy_finds_wasm_in_cargo_messages() {
+    use crate::deploy::wasm_artifact;
+
+    let messages = br#"{"reason":"compiler-artifact","target":{"name":"soroban_sdk"},"filenames":["/t/libsoroban_sdk.rlib"]}
+{"reason":"compiler-artifact","target":{"name":"invoice_payment"},"filenames":["/t/wasm32v1-none/release/invoice_payment.wasm","/t/libinvoice_payment.rlib"]}
+{"reason":"build-finished","success":true}
+"#;
+    assert_eq!(
+        wasm_artifact(messages, "invoice-payment").unwrap().to_str(),
+        Some("/t/wasm32v1-none/release/invoice_payment.wasm")
+    );
+    assert_eq!(wasm_artifact(messages, "escrow"), None);
+}
+
+// Pay URI
+
+#[test]
+fn test_pay_uri_parses_flags() {
+    let cli = Cli::try_parse_from([
+        "invoisio",
+        "pay-uri",
+        "--invoice-id",
+        "abc123",
+        "--destination",
+        ISSUER,
+        "--asset",
+        "XLM",
+        "--qr",
+        "invoice.svg",
+    ])
+    .unwrap();
+    let Command::PayUri { amount, qr, .. } = cli.command else {
+        panic!("expected pay-uri");
+    };
+    assert_eq!(amount, None);
+    assert_eq!(qr.unwrap(), std::path::Path::new("invoice.svg"));
+}
+
+// Admin rotation
+
+#[test]
+fn test_rotate_admin_parses_flags() {
+    let cli = Cli::try_parse_from([
+        "invoisio",
+        "rotate-admin",
+        "--secret-file",
+        "keys/admin.secret",
+    ])
+    .unwrap();
+    let Command::RotateAdmin {
+        secret_file,
+        starting_balance,
+        ..
+    } = cli.command
+    else {
+        panic!("expected rotate-admin");
+    };
+    assert_eq!(secret_file, std::path::Path::new("keys/admin.secret"));
+    assert_eq!(starting_balance, 50_000_000);
+}
+
+#[test]
+fn test_rotation_file_names() {
+    use crate::rotate::with_suffix;
+    let path = std::path::Path::new("keys/admin.secret");
+    assert_eq!(
+        with_suffix(path, "pending"),
+        std::path::Path::new("keys/admin.secret.pending")
+    );
+}
+
+#[test]
+fn test_install_swaps_secret_and_keeps_previous() {
+    use crate::rotate::{install, read_secret, with_suffix, write_secret};
+    let dir = std::env::temp_dir().join(format!("invoisio-rotate-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let secret_file = dir.join("admin.secret");
+    let pending = with_suffix(&secret_file, "pending");
+    let previous = with_suffix(&secret_file, "previous");
+    write_secret(&secret_file, "SOLD").unwrap();
+    write_secret(&pending, "SNEW").unwrap();
+
+    install(&secret_file, &pending, &previous).unwrap();
+    assert_eq!(read_secret(&secret_file).unwrap(), "SNEW");
+    assert_eq!(read_secret(&previous).unwrap(), "SOLD");
+    assert!(!pending.exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&previous).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// Offline signing
+
+#[test]
+fn test_parse_scval_argument_types() {
+    use crate::cli::parse_scval;
+    use stellar_xdr::curr::ScVal;
+
+    assert_eq!(parse_scval("u64:7").unwrap(), ScVal::U64(7));
+    assert_eq!(parse_scval("bool:true").unwrap(), ScVal::Bool(true));
+    assert_eq!(
+        parse_scval("i128:-5").unwrap(),
+        invoisio_client::scval::i128(-5)
+    );
+    assert!(matches!(
+        parse_scval(&format!("address:{ISSUER}")).unwrap(),
+        ScVal::Address(_)
+    ));
+    assert!(parse_scval("asset:XLM").is_ok());
+    assert!(parse_scval("u64:-1").is_err());
+    assert!(parse_scval("float:1.5").is_err());
+    assert!(parse_scval("7").is_err());
+}
+
+#[test]
+fn test_tx_build_needs_a_call_or_an_envelope() {
+    let parse = |args: &[&str]| Cli::try_parse_from([&["invoisio", "tx", "build"], args].concat());
+    assert!(parse(&[]).is_err());
+    assert!(parse(&["--from", "tx.xdr"]).is_ok());
+    assert!(parse(&["--from", "tx.xdr", "--source", ISSUER]).is_err());
+    let cli = parse(&[
+        "--source",
+        ISSUER,
+        "--function",
+        "set_admin",
+        "--arg",
+        &format!("address:{ISSUER}"),
+        "--arg",
+        "u64:3",
+    ])
+    .unwrap();
+    let Command::Tx {
+        command: TxCommand::Build { args, .. },
+    } = cli.command
+    else {
+        panic!("expected tx build");
+    };
+    assert_eq!(args.len(), 2);
+}
+
+#[tokio::test]
+async fn test_tx_sign_orders_auth_before_envelope() {
+    use crate::tx::{sign, unsigned_auth, Signed};
+    use invoisio_client::{scval, tx, Keypair};
+    use stellar_xdr::curr::{
+        ContractId, Hash, InvokeContractArgs, ScAddress, ScSymbol, ScVal,
+        SorobanAddressCredentials, SorobanAuthorizationEntry, SorobanAuthorizedFunction,
+        SorobanAuthorizedInvocation, SorobanCredentials, TransactionEnvelope,
+    };
+
+    const PASSPHRASE: &str = "Test SDF Network ; September 2015";
+    let source = Keypair::from_seed([1u8; 32]);
+    let cosigner = Keypair::from_seed([2u8; 32]);
+
+    let call =
+        tx::build_invoke(source.public_key_bytes(), 1, [9u8; 32], "set_admin", vec![]).unwrap();
+    let entry = SorobanAuthorizationEntry {
+        credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+            address: scval::sc_address(&cosigner.public_key()).unwrap(),
+            nonce: 1,
+            signature_expiration_ledger: 100,
+            signature: ScVal::Void,
+        }),
+        root_invocation: SorobanAuthorizedInvocation {
+            function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+                contract_address: ScAddress::Contract(ContractId(Hash([9u8; 32]))),
+                function_name: ScSymbol("set_admin".try_into().unwrap()),
+                args: Default::default(),
+            }),
+            sub_invocations: Default::default(),
+        },
+    };
+    let envelope = tx::unsigned(tx::set_auth(call, vec![entry]).unwrap());
+    assert_eq!(unsigned_auth(&envelope).unwrap(), [cosigner.public_key()]);
+
+    // Signing the envelope first leaves the auth entry for later...
+    let (early, signed) = sign(envelope.clone(), &source, PASSPHRASE).await.unwrap();
+    assert_eq!(signed, Signed::Envelope);
+    // ...which is then refused.
+    assert!(sign(early, &cosigner, PASSPHRASE).await.is_err());
+
+    let (envelope, signed) = sign(envelope, &cosigner, PASSPHRASE).await.unwrap();
+    assert_eq!(signed, Signed::AuthEntries(1));
+    assert!(unsigned_auth(&envelope).unwrap().is_empty());
+
+    let (envelope, signed) = sign(envelope, &source, PASSPHRASE).await.unwrap();
+    assert_eq!(signed, Signed::Envelope);
+    let (envelope, signed) = sign(envelope, &source, PASSPHRASE).await.unwrap();
+    assert_eq!(signed, Signed::Unchanged);
+    let TransactionEnvelope::Tx(v1) = envelope else {
+        panic!("expected v1 envelope");
+    };
+    assert_eq!(v1.signatures.len(), 1);
+}
+
+// Import
+
+#[test]
+fn test_import_parses_flags() {
+    let cli =
+        Cli::try_parse_from(["invoisio", "import", "legacy.csv", "-y", "--skip-invalid"]).unwrap();
+    match cli.command {
+        Command::Import {
+            file,
+            lookup_batch,
+            yes,
+            skip_invalid,
+            dry_run,
+        } => {
+            assert_eq!(file.to_str(), Some("legacy.csv"));
+            assert_eq!(lookup_batch, 50);
+            assert!(yes && skip_invalid && !dry_run);
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[test]
+fn test_import_parse_amount_is_exact() {
+    use crate::import::parse_amount;
+
+    assert_eq!(parse_amount("1.25").unwrap(), 12_500_000);
+    assert_eq!(parse_amount("1.2500000").unwrap(), 12_500_000);
+    assert_eq!(parse_amount("0.0000001").unwrap(), 1);
+    assert_eq!(parse_amount("42").unwrap(), 420_000_000);
+    assert_eq!(parse_amount("-1").unwrap(), -10_000_000);
+    assert!(parse_amount("0.00000001").is_err());
+    assert!(parse_amount(".5").is_err());
+    assert!(parse_amount("1,5").is_err());
+    assert!(parse_amount("1.-5").is_err());
+}
+
+#[test]
+fn test_import_read_validates_rows() {
+    use crate::import::{read, Status};
+
+    let csv = format!(
+        "invoice_id,payer,asset,amount_stroops
+inv-1,{ISSUER},XLM,100
+inv-2,{ISSUER},USDC:{ISSUER},5
+inv-1,{ISSUER},XLM,100
+,{ISSUER},XLM,1
+inv-3,GNOTAKEY,XLM,1
+inv-4,{ISSUER},USDC,1
+inv-5,{ISSUER},XLM,0
+inv-6,{ISSUER},XLM,
+"
+    );
+    let rows = read(csv.as_bytes()).unwrap();
+    let statuses: Vec<_> = rows.iter().map(|r| (r.line, r.status)).collect();
+    assert_eq!(
+        statuses,
+        [
+            (2, Status::New),
+            (3, Status::New),
+            (4, Status::Duplicate),
+            (5, Status::Invalid),
+            (6, Status::Invalid),
+            (7, Status::Invalid),
+            (8, Status::Invalid),
+            (9, Status::Invalid),
+        ]
+    );
+    assert_eq!(rows[2].detail.as_deref(), Some("same invoice id as line 2"));
+    assert_eq!(rows[7].detail.as_deref(), Some("missing amount"));
+    let payment = rows[1].payment.as_ref().unwrap();
+    assert_eq!(payment.amount, 5);
+    assert_eq!(
+        payment.asset,
+        parse_asset(&format!("USDC:{ISSUER}")).unwrap()
+    );
+}
+
+#[test]
+fn test_import_reads_export_csv() {
+    use crate::import::{read, Status};
+
+    let csv = format!(
+        "invoice_id,payer,asset_code,asset_issuer,amount_stroops,amount,timestamp
+invoisio-1,{ISSUER},XLM,,12500000,1.2500000,1700000000
+invoisio-2,{ISSUER},USDC,{ISSUER},,0.5,1700000001
+"
+    );
+    let rows = read(csv.as_bytes()).unwrap();
+    assert!(rows.iter().all(|r| r.status == Status::New));
+    let first = rows[0].payment.as_ref().unwrap();
+    assert_eq!(
+        (first.asset.clone(), first.amount),
+        (Asset::Native, 12_500_000)
+    );
+    assert_eq!(rows[1].payment.as_ref().unwrap().amount, 5_000_000);
+
+    let missing = read("invoice_id,asset,amount\ninv-1,XLM,1\n".as_bytes());
+    assert!(missing.is_err());
+}
+
+#[test]
+fn test_import_compare_against_chain() {
+    use crate::import::compare;
+    use invoisio_client::{scval, tx, PaymentRecord, RecordPayment};
+    use stellar_xdr::curr::{MuxedEd25519Account, ScAddress, Uint256};
+
+    let muxed = scval::address_to_strkey(&ScAddress::MuxedAccount(MuxedEd25519Account {
+        id: 7,
+        ed25519: Uint256(tx::account_id(ISSUER).unwrap()),
+    }))
+    .unwrap();
+    let payment = RecordPayment {
+        invoice_id: "inv-1".into(),
+        payer: muxed,
+        asset: Asset::Native,
+        amount: 100,
+    };
+    let mut record = PaymentRecord {
+        invoice_id: "inv-1".into(),
+        payer: ISSUER.into(),
+        payer_mux_id: Some(7),
+        asset: Asset::Native,
+        amount: 100,
+        timestamp: 1_700_000_000,
+    };
+    assert_eq!(compare(&payment, &record).unwrap(), None);
+
+    record.amount = 99;
+    record.payer_mux_id = None;
+    assert_eq!(
+        compare(&payment, &record).unwrap().unwrap(),
+        format!("on chain with payer {ISSUER}, amount 99")
+    );
+}
+
+#[test]
+fn test_import_summary_lists_statuses_in_order() {
+    use crate::import::{summary, Status};
+    use std::collections::BTreeMap;
+
+    let counts = BTreeMap::from([
+        (Status::Conflict, 1),
+        (Status::New, 12),
+        (Status::Unchanged, 3),
+    ]);
+    assert_eq!(summary(&counts), "new 12, unchanged 3, conflict 1");
+    assert_eq!(summary(&BTreeMap::new()), "no rows");
+    assert!(Status::Conflict.is_problem() && !Status::Unchanged.is_problem());
+}
+
+// Dry runs
+
+#[test]
+fn test_every_write_command_accepts_dry_run() {
+    let writes: [&[&str]; 7] = [
+        &["deploy", "--wasm", "c.wasm"],
+        &["initialize"],
+        &[
+            "record",
+            "--invoice-id",
+            "i",
+            "--payer",
+            ISSUER,
+            "--asset",
+            "XLM",
+            "--amount",
+            "1",
+        ],
+        &["import", "payments.csv"],
+        &["set-admin", "--new-admin-secret", "S"],
+        &["rotate-admin", "--secret-file", "admin.secret"],
+        &["tx", "submit", "signed.xdr"],
+    ];
+    for args in writes {
+        let cli = Cli::try_parse_from([&["invoisio"], args, &["--dry-run"]].concat()).unwrap();
+        let dry_run = match cli.command {
+            Command::Deploy { dry_run, .. }
+            | Command::Initialize { dry_run, .. }
+            | Command::Record { dry_run, .. }
+            | Command::Import { dry_run, .. }
+            | Command::SetAdmin { dry_run, .. }
+            | Command::RotateAdmin { dry_run, .. }
+            | Command::Tx {
+                command: TxCommand::Submit { dry_run, .. },
+            } => dry_run,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert!(dry_run, "{args:?}");
+    }
+}
@@ -0,0 +1,186 @@
+//! `tx build` / `tx sign` / `tx submit`: transactions that several parties
+//! sign away from the network, passed around as base64
+//! `TransactionEnvelope` XDR.
+//!
+//! 1. `tx build` (online) simulates the call and prints the unsigned
+//!    envelope, with every auth entry's signature expiry already set.
+//! 2. Each key named in an auth entry runs `tx sign` (offline).
+//! 3. If step 2 happened, `tx build --from` (online) re-simulates so the
+//!    resources cover signature verification. The signed auth is kept.
+//! 4. Each signer of the source account runs `tx sign` (offline).
+//! 5. `tx submit` (online) sends it.
+//!
+//! Envelope signatures cover the auth entries, so step 4 must come last:
+//! [`sign`] refuses to touch auth entries once the envelope is signed.
+
+use std::io::Read;
+use std::path::Path;
+
+use invoisio_client::{dry_run, scval, tx, DryRun, InvoicePaymentClient, Keypair};
+use stellar_xdr::curr::{
+    DecoratedSignature, Limits, ReadXdr, ScVal, SorobanCredentials, Transaction,
+    TransactionEnvelope, TransactionV1Envelope, WriteXdr,
+};
+
+use crate::error::{Error, Result};
+
+/// What [`sign`] added.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Signed {
+    /// A signature on this many auth entries.
+    AuthEntries(usize),
+    Envelope,
+    /// Nothing: the envelope already carried this key's signature.
+    Unchanged,
+}
+
+/// Simulate `function` with `source` as transaction source and return the
+/// unsigned envelope. Address auth signatures expire `auth_ledgers` after
+/// the simulated ledger.
+pub async fn build(
+    client: &InvoicePaymentClient,
+    source: &str,
+    function: &str,
+    args: Vec<ScVal>,
+    auth_ledgers: u32,
+) -> Result<TransactionEnvelope> {
+    let source = tx::account_id(source)?;
+    let sequence = client.rpc().get_account_sequence(source).await? + 1;
+    let sim = client
+        .simulate(client.build(source, sequence, function, args)?)
+        .await?;
+    let expiration = sim.latest_ledger + auth_ledgers;
+    let auth = sim
+        .auth
+        .into_iter()
+        .map(|mut entry| {
+            if let SorobanCredentials::Address(creds) = &mut entry.credentials {
+                creds.signature_expiration_ledger = expiration;
+            }
+            entry
+        })
+        .collect();
+    Ok(tx::unsigned(tx::set_auth(sim.transaction, auth)?))
+}
+
+/// Re-simulate `envelope` with its (signed) auth entries attached.
+pub async fn rebuild(
+    client: &InvoicePaymentClient,
+    envelope: TransactionEnvelope,
+) -> Result<TransactionEnvelope> {
+    let (tx, signatures) = v1(envelope)?;
+    if !signatures.is_empty() {
+        return Err(Error::Usage(
+            "the envelope is already signed; rebuild before signing the envelope".into(),
+        ));
+    }
+    let sim = client.simulate(tx::reset_resources(tx)).await?;
+    Ok(tx::unsigned(sim.transaction))
+}
+
+/// Simulate `envelope` as submitted, with its signed auth enforced.
+pub async fn dry_run(
+    client: &InvoicePaymentClient,
+    envelope: TransactionEnvelope,
+) -> Result<DryRun> {
+    let (tx, _) = v1(envelope)?;
+    let tx = tx::reset_resources(tx);
+    Ok(dry_run::dry_run(client.rpc(), tx, Some(client.contract_id())).await?)
+}
+
+/// Add `signer`'s signature to `envelope`: to every auth entry naming it,
+/// or else to the envelope itself.
+pub async fn sign(
+    envelope: TransactionEnvelope,
+    signer: &Keypair,
+    network_passphrase: &str,
+) -> Result<(TransactionEnvelope, Signed)> {
+    let (tx, mut signatures) = v1(envelope)?;
+    let public_key = signer.public_key_bytes();
+    let auth = tx::auth_entries(&tx).to_vec();
+    let named = auth
+        .iter()
+        .filter(|e| tx::auth_entry_needs(e, public_key))
+        .count();
+
+    if named > 0 {
+        if !signatures.is_empty() {
+            return Err(Error::Usage(
+                "auth entries must be signed before the envelope; signing them now would \
+                 invalidate the envelope signatures"
+                    .into(),
+            ));
+        }
+        let mut signed = Vec::with_capacity(auth.len());
+        for entry in auth {
+            signed.push(if tx::auth_entry_needs(&entry, public_key) {
+                tx::cosign_auth_entry(entry, signer, network_passphrase).await?
+            } else {
+                entry
+            });
+        }
+        let tx = tx::set_auth(tx, signed)?;
+        return Ok((envelope_of(tx, signatures)?, Signed::AuthEntries(named)));
+    }
+
+    let hash = tx::transaction_hash(&tx, network_passphrase)?;
+    let signature = signer.sign_decorated(&hash)?;
+    if signatures.contains(&signature) {
+        return Ok((envelope_of(tx, signatures)?, Signed::Unchanged));
+    }
+    signatures.push(signature);
+    Ok((envelope_of(tx, signatures)?, Signed::Envelope))
+}
+
+/// Addresses of auth entries that carry no signature yet.
+pub fn unsigned_auth(envelope: &TransactionEnvelope) -> Result<Vec<String>> {
+    let TransactionEnvelope::Tx(v1) = envelope else {
+        return Ok(Vec::new());
+    };
+    tx::auth_entries(&v1.tx)
+        .iter()
+        .filter_map(|entry| match &entry.credentials {
+            SorobanCredentials::Address(creds) if creds.signature == ScVal::Void => {
+                Some(scval::address_to_strkey(&creds.address).map_err(Error::from))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Read a base64 envelope from `input`, or stdin.
+pub fn read(input: Option<&Path>) -> Result<TransactionEnvelope> {
+    let text = match input {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            text
+        }
+    };
+    let envelope = TransactionEnvelope::from_xdr_base64(text.trim(), Limits::none())?;
+    Ok(envelope)
+}
+
+/// Print `envelope` as base64 on stdout.
+pub fn print(envelope: &TransactionEnvelope) -> Result<()> {
+    println!("{}", envelope.to_xdr_base64(Limits::none())?);
+    Ok(())
+}
+
+fn v1(envelope: TransactionEnvelope) -> Result<(Transaction, Vec<DecoratedSignature>)> {
+    match envelope {
+        TransactionEnvelope::Tx(v1) => Ok((v1.tx, v1.signatures.to_vec())),
+        _ => Err(Error::Usage("expected a v1 transaction envelope".into())),
+    }
+}
+
+fn envelope_of(
+    tx: Transaction,
+    signatures: Vec<DecoratedSignature>,
+) -> Result<TransactionEnvelope> {
+    Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: signatures.try_into()?,
+    }))
+}
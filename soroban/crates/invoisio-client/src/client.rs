@@ -0,0 +1,796 @@
+//! Typed client for the `invoice-payment` contract.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use stellar_xdr::curr::{
+    Limits, ReadXdr, ScBytes, ScVal, SorobanAuthorizationEntry, Transaction, TransactionEnvelope,
+    TransactionMeta, WriteXdr,
+};
+
+use crate::dry_run::{self, DryRun};
+use crate::error::{Error, Result};
+use crate::estimate::ResourceEstimate;
+use crate::events::{contract_filter, EventEnvelope};
+use crate::horizon::HorizonClient;
+use crate::receipt::PaymentReceipt;
+use crate::rpc::{GetEventsRequest, Pagination, RpcClient, SimulateTransactionResponse};
+use crate::scval;
+use crate::sep31::Remittance;
+use crate::signer::Signer;
+use crate::tx;
+use crate::types::{PaymentRecord, RecordPayment};
+use crate::verify::VerificationReport;
+
+/// Account used as the (never-submitted) source of read-only simulations.
+/// Simulation does not check that the source exists or signs.
+const READ_SOURCE: [u8; 32] = [0u8; 32];
+
+/// Result of `simulateTransaction` for a single contract call.
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    /// Transaction with resources, fee and auth applied — ready to sign.
+    pub transaction: Transaction,
+    /// Value the call returned during simulation.
+    pub result: ScVal,
+    /// Authorization entries recorded during simulation.
+    pub auth: Vec<SorobanAuthorizationEntry>,
+    pub min_resource_fee: i64,
+    pub latest_ledger: u32,
+}
+
+/// Outcome of a transaction that made it into a ledger successfully.
+#[derive(Clone, Debug)]
+pub struct TxOutcome {
+    pub hash: String,
+    pub ledger: u32,
+    pub return_value: Option<ScVal>,
+}
+
+/// Where to start reading events from.
+#[derive(Clone, Debug)]
+pub enum EventStart {
+    Ledger(u32),
+    Cursor(String),
+}
+
+/// One page of decoded contract events.
+#[derive(Clone, Debug)]
+pub struct EventPage {
+    pub events: Vec<EventEnvelope>,
+    /// Pass back as [`EventStart::Cursor`] to continue.
+    pub cursor: Option<String>,
+    pub latest_ledger: u32,
+    pub oldest_ledger: Option<u32>,
+}
+
+/// Async client bound to one deployed `invoice-payment` contract.
+#[derive(Debug)]
+pub struct InvoicePaymentClient {
+    rpc: RpcClient,
+    network_passphrase: String,
+    contract_id: String,
+    contract: [u8; 32],
+    poll_interval: Duration,
+    confirm_timeout: Duration,
+}
+
+impl InvoicePaymentClient {
+    pub fn new(rpc_url: &str, network_passphrase: &str, contract_id: &str) -> Result<Self> {
+        Self::with_rpc(RpcClient::new(rpc_url), network_passphrase, contract_id)
+    }
+
+    pub fn with_rpc(rpc: RpcClient, network_passphrase: &str, contract_id: &str) -> Result<Self> {
+        Ok(Self {
+            contract: tx::contract_id(contract_id)?,
+            rpc,
+            network_passphrase: network_passphrase.to_string(),
+            contract_id: contract_id.to_string(),
+            poll_interval: Duration::from_secs(1),
+            confirm_timeout: Duration::from_secs(30),
+        })
+    }
+
+    /// How long [`submit`](Self::submit) waits for a transaction to land.
+    pub fn with_confirm_timeout(mut self, timeout: Duration) -> Self {
+        self.confirm_timeout = timeout;
+        self
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    pub fn network_passphrase(&self) -> &str {
+        &self.network_passphrase
+    }
+
+    // Generic plumbing
+
+    /// Build an unsigned transaction invoking `function` from `source`.
+    pub fn build(
+        &self,
+        source: [u8; 32],
+        sequence: i64,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<Transaction> {
+        tx::build_invoke(source, sequence, self.contract, function, args)
+    }
+
+    /// Simulate `tx` and assemble it with the returned resources and auth.
+    pub async fn simulate(&self, tx: Transaction) -> Result<Simulation> {
+        simulate(&self.rpc, tx).await
+    }
+
+    /// Simulate `function` with `source` (`G...`) as transaction source and
+    /// report what it will consume. Nothing is submitted.
+    pub async fn estimate(
+        &self,
+        source: &str,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<ResourceEstimate> {
+        let source = tx::account_id(source)?;
+        let sequence = self.rpc.get_account_sequence(source).await? + 1;
+        let sim = self
+            .simulate(self.build(source, sequence, function, args)?)
+            .await?;
+        ResourceEstimate::from_simulation(sim)
+    }
+
+    /// Simulate `function` with `source` (`G...`) as transaction source and
+    /// report everything the simulation returned, including why the call
+    /// would fail. Nothing is submitted.
+    pub async fn dry_run(&self, source: &str, function: &str, args: Vec<ScVal>) -> Result<DryRun> {
+        let source = tx::account_id(source)?;
+        let sequence = self.rpc.get_account_sequence(source).await? + 1;
+        let tx = self.build(source, sequence, function, args)?;
+        dry_run::dry_run(&self.rpc, tx, Some(&self.contract_id)).await
+    }
+
+    /// Simulate a read-only call and return its value. Nothing is submitted.
+    pub async fn read(&self, function: &str, args: Vec<ScVal>) -> Result<ScVal> {
+        let tx = self.build(READ_SOURCE, 0, function, args)?;
+        Ok(self.simulate(tx).await?.result)
+    }
+
+    /// Simulate, sign and submit a call with `signer` as source, then wait
+    /// for it to land.
+    pub async fn invoke<S: Signer>(
+        &self,
+        signer: &S,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<TxOutcome> {
+        self.invoke_with_fee(signer, function, args, tx::DEFAULT_BASE_FEE)
+            .await
+    }
+
+    /// [`invoke`](Self::invoke), bidding `inclusion_fee` stroops on top of
+    /// the simulated resource fee.
+    pub async fn invoke_with_fee<S: Signer>(
+        &self,
+        signer: &S,
+        function: &str,
+        args: Vec<ScVal>,
+        inclusion_fee: u32,
+    ) -> Result<TxOutcome> {
+        let source = signer.public_key_bytes();
+        let sequence = self.rpc.get_account_sequence(source).await? + 1;
+        let mut tx = self.build(source, sequence, function, args)?;
+        tx.fee = inclusion_fee;
+        let sim = self.simulate(tx).await?;
+        let envelope = tx::sign(sim.transaction, &self.network_passphrase, signer).await?;
+        self.submit(&envelope).await
+    }
+
+    /// Simulate and sign a call at an explicit `sequence` without sending
+    /// it. The transaction is only valid until `valid_until` (Unix
+    /// seconds), so once that passes it can never land. Callers managing
+    /// their own sequence numbers use this with [`send`](Self::send) and
+    /// [`wait_for`](Self::wait_for).
+    pub async fn prepare<S: Signer>(
+        &self,
+        signer: &S,
+        function: &str,
+        args: Vec<ScVal>,
+        sequence: i64,
+        inclusion_fee: u32,
+        valid_until: u64,
+    ) -> Result<TransactionEnvelope> {
+        let mut tx = self.build(signer.public_key_bytes(), sequence, function, args)?;
+        tx.fee = inclusion_fee;
+        let tx = tx::set_deadline(tx, valid_until);
+        let sim = self.simulate(tx).await?;
+        tx::sign(sim.transaction, &self.network_passphrase, signer).await
+    }
+
+    /// Simulate, sign and submit a call that `authorizer` must authorise
+    /// while `sponsor` is the transaction source and pays every fee.
+    ///
+    /// `authorizer` only signs its address-credential auth entry, so it
+    /// needs no XLM beyond existing on the ledger. This is how end customers
+    /// make payer-signed calls without holding fee balance.
+    pub async fn invoke_sponsored(
+        &self,
+        sponsor: &impl Signer,
+        authorizer: &impl Signer,
+        function: &str,
+        args: Vec<ScVal>,
+    ) -> Result<TxOutcome> {
+        let source = sponsor.public_key_bytes();
+        let sequence = self.rpc.get_account_sequence(source).await? + 1;
+        let sim = self
+            .simulate(self.build(source, sequence, function, args.clone())?)
+            .await?;
+
+        // Auth entries expire a few minutes (≈ 60 ledgers) after simulation.
+        let expiration = sim.latest_ledger + 60;
+        let mut auth = Vec::with_capacity(sim.auth.len());
+        for entry in sim.auth {
+            auth.push(if tx::auth_entry_needs(&entry, authorizer.public_key_bytes()) {
+                tx::sign_auth_entry(entry, authorizer, &self.network_passphrase, expiration).await?
+            } else {
+                entry
+            });
+        }
+
+        // Re-simulate with the signed auth attached so the resource estimate
+        // covers signature verification.
+        let tx = tx::set_auth(self.build(source, sequence, function, args)?, auth)?;
+        let sim = self.simulate(tx).await?;
+        let envelope = tx::sign(sim.transaction, &self.network_passphrase, sponsor).await?;
+        self.submit(&envelope).await
+    }
+
+    /// Wrap an already signed `inner` envelope in a fee bump paid by
+    /// `sponsor` and submit it. For transactions whose source (e.g. a
+    /// customer account) cannot cover the fee it was signed with.
+    pub async fn submit_fee_bumped(
+        &self,
+        sponsor: &impl Signer,
+        inner: TransactionEnvelope,
+    ) -> Result<TxOutcome> {
+        let TransactionEnvelope::Tx(ref v1) = inner else {
+            return Err(Error::Decode("fee bump needs a signed v1 envelope".into()));
+        };
+        let fee = i64::from(v1.tx.fee) + i64::from(tx::DEFAULT_BASE_FEE);
+        let bump = tx::fee_bump(inner, sponsor.public_key_bytes(), fee)?;
+        let envelope = tx::sign_fee_bump(bump, &self.network_passphrase, sponsor).await?;
+        self.submit(&envelope).await
+    }
+
+    /// Create `destination` (`G...`) with `starting_balance` stroops of XLM
+    /// paid by `funder`. A classic operation, so it skips simulation.
+    pub async fn create_account(
+        &self,
+        funder: &impl Signer,
+        destination: &str,
+        starting_balance: i64,
+    ) -> Result<TxOutcome> {
+        let source = funder.public_key_bytes();
+        let sequence = self.rpc.get_account_sequence(source).await? + 1;
+        let tx = tx::build_create_account(
+            source,
+            sequence,
+            tx::account_id(destination)?,
+            starting_balance,
+        )?;
+        let envelope = tx::sign(tx, &self.network_passphrase, funder).await?;
+        self.submit(&envelope).await
+    }
+
+    /// Submit a signed envelope and poll `getTransaction` until it succeeds,
+    /// fails, or [`with_confirm_timeout`](Self::with_confirm_timeout) elapses.
+    pub async fn submit(&self, envelope: &TransactionEnvelope) -> Result<TxOutcome> {
+        submit(&self.rpc, envelope, self.poll_interval, self.confirm_timeout).await
+    }
+
+    /// Send a signed envelope without waiting; returns its hash. Sending
+    /// the same envelope again is harmless.
+    pub async fn send(&self, envelope: &TransactionEnvelope) -> Result<String> {
+        send(&self.rpc, envelope).await
+    }
+
+    /// Poll `getTransaction` for `hash` until it is final.
+    pub async fn wait_for(&self, hash: &str) -> Result<TxOutcome> {
+        wait_for(&self.rpc, hash, self.poll_interval, self.confirm_timeout).await
+    }
+
+    // Write
+
+    /// Call `initialize` with `signer` as source. `admin` must authorise, so
+    /// in practice `signer` is the admin itself.
+    pub async fn initialize(&self, signer: &impl Signer, admin: &str) -> Result<TxOutcome> {
+        self.invoke(signer, "initialize", vec![scval::address(admin)?])
+            .await
+    }
+
+    /// Call `record_payment` with `signer` (the contract admin) as source.
+    pub async fn record_payment(&self, signer: &impl Signer, p: &RecordPayment) -> Result<TxOutcome> {
+        self.invoke(signer, "record_payment", record_payment_args(p)?)
+            .await
+    }
+
+    /// [`record_payment`](Self::record_payment) with an explicit inclusion
+    /// fee bid, e.g. from a fee strategy under surge pricing.
+    pub async fn record_payment_with_fee(
+        &self,
+        signer: &impl Signer,
+        p: &RecordPayment,
+        inclusion_fee: u32,
+    ) -> Result<TxOutcome> {
+        let args = record_payment_args(p)?;
+        self.invoke_with_fee(signer, "record_payment", args, inclusion_fee)
+            .await
+    }
+
+    /// Call `record_payment_signed` with `submitter` as source. Any funded
+    /// account can submit, e.g. a relayer wrapping the transaction in a fee
+    /// bump; `signature` is the contract signing key's signature over
+    /// [`signed_payment_message`]. `p.payer` cannot be a muxed `M...`
+    /// address.
+    pub async fn record_payment_signed(
+        &self,
+        submitter: &impl Signer,
+        p: &RecordPayment,
+        signature: &[u8; 64],
+    ) -> Result<TxOutcome> {
+        let args = vec![
+            signed_payment(p)?,
+            ScVal::Bytes(ScBytes(signature.to_vec().try_into()?)),
+        ];
+        self.invoke(submitter, "record_payment_signed", args).await
+    }
+
+    /// Call `record_remittance_payment` with `signer` (the contract admin) as
+    /// source, recording `p` with the SEP-31 identifiers of the transaction
+    /// that delivered it (see [`AnchorClient::remittance`]).
+    ///
+    /// [`AnchorClient::remittance`]: crate::AnchorClient::remittance
+    pub async fn record_remittance_payment(
+        &self,
+        signer: &impl Signer,
+        p: &RecordPayment,
+        remittance: &Remittance,
+    ) -> Result<TxOutcome> {
+        let mut args = record_payment_args(p)?;
+        args.push(ScVal::try_from(remittance)?);
+        self.invoke(signer, "record_remittance_payment", args).await
+    }
+
+    /// Estimate a `record_payment` submitted by `source` (the contract admin,
+    /// `G...`). The returned transaction is ready to sign and submit.
+    pub async fn simulate_record_payment(
+        &self,
+        source: &str,
+        p: &RecordPayment,
+    ) -> Result<ResourceEstimate> {
+        self.estimate(source, "record_payment", record_payment_args(p)?)
+            .await
+    }
+
+    /// Call `report_pending_payment` with `signer` (the contract admin) as source.
+    pub async fn report_pending_payment(
+        &self,
+        signer: &impl Signer,
+        p: &RecordPayment,
+    ) -> Result<TxOutcome> {
+        self.invoke(signer, "report_pending_payment", record_payment_args(p)?)
+            .await
+    }
+
+    pub async fn confirm_pending_payment(
+        &self,
+        signer: &impl Signer,
+        invoice_id: &str,
+    ) -> Result<TxOutcome> {
+        self.invoke(signer, "confirm_pending_payment", vec![scval::string(invoice_id)?])
+            .await
+    }
+
+    /// Transfer admin rights. Both the current and the new admin sign: the
+    /// current admin as transaction source, the new admin via its auth entry.
+    /// Uses the current [`admin_nonce`](Self::admin_nonce).
+    pub async fn set_admin(
+        &self,
+        current: &impl Signer,
+        new_admin: &impl Signer,
+    ) -> Result<TxOutcome> {
+        let args = vec![
+            scval::address(&new_admin.public_key())?,
+            ScVal::U64(self.admin_nonce().await?),
+        ];
+        self.invoke_sponsored(current, new_admin, "set_admin", args)
+            .await
+    }
+
+    /// Set the ed25519 key whose signatures `record_payment_signed`
+    /// accepts, or disable signed payments with `None`. Admin only; uses
+    /// the current [`admin_nonce`](Self::admin_nonce).
+    pub async fn set_signing_key(
+        &self,
+        admin: &impl Signer,
+        key: Option<[u8; 32]>,
+    ) -> Result<TxOutcome> {
+        let key = match key {
+            Some(key) => ScVal::Bytes(ScBytes(key.to_vec().try_into()?)),
+            None => ScVal::Void,
+        };
+        let args = vec![key, ScVal::U64(self.admin_nonce().await?)];
+        self.invoke(admin, "set_signing_key", args).await
+    }
+
+    // Payer calls (sponsored)
+
+    /// Call `acknowledge_invoice` for `payer`, with `sponsor` paying fees.
+    pub async fn acknowledge_invoice(
+        &self,
+        sponsor: &impl Signer,
+        payer: &impl Signer,
+        invoice_id: &str,
+    ) -> Result<TxOutcome> {
+        let args = vec![scval::string(invoice_id)?, scval::address(&payer.public_key())?];
+        self.invoke_sponsored(sponsor, payer, "acknowledge_invoice", args)
+            .await
+    }
+
+    /// Call `accept_terms` for `payer`, with `sponsor` paying fees.
+    pub async fn accept_terms(
+        &self,
+        sponsor: &impl Signer,
+        payer: &impl Signer,
+        invoice_id: &str,
+        terms_hash: &[u8; 32],
+    ) -> Result<TxOutcome> {
+        let args = vec![
+            scval::string(invoice_id)?,
+            scval::address(&payer.public_key())?,
+            ScVal::Bytes(ScBytes(terms_hash.to_vec().try_into()?)),
+        ];
+        self.invoke_sponsored(sponsor, payer, "accept_terms", args)
+            .await
+    }
+
+    /// Call `preauthorize` for `payer`, with `sponsor` paying fees.
+    pub async fn preauthorize(
+        &self,
+        sponsor: &impl Signer,
+        payer: &impl Signer,
+        invoice_id: &str,
+        asset_code: &str,
+        asset_issuer: &str,
+        max_amount: i128,
+    ) -> Result<TxOutcome> {
+        let args = vec![
+            scval::string(invoice_id)?,
+            scval::address(&payer.public_key())?,
+            scval::string(asset_code)?,
+            scval::string(asset_issuer)?,
+            scval::i128(max_amount),
+        ];
+        self.invoke_sponsored(sponsor, payer, "preauthorize", args)
+            .await
+    }
+
+    // Read
+
+    /// Fetch the record for `invoice_id`.
+    ///
+    /// Returns `Error::Contract(ContractError::PaymentNotFound)` if it was
+    /// never recorded, and [`Error::EntryArchived`] if it exists but must be
+    /// restored first.
+    pub async fn get_payment(&self, invoice_id: &str) -> Result<PaymentRecord> {
+        let v = self
+            .read("get_payment", vec![scval::string(invoice_id)?])
+            .await?;
+        PaymentRecord::try_from(&v)
+    }
+
+    /// Fetch the record for `invoice_id`, or `None` if it was never recorded
+    /// (or has been compacted).
+    pub async fn get_payment_opt(&self, invoice_id: &str) -> Result<Option<PaymentRecord>> {
+        let v = self
+            .read("get_payment_opt", vec![scval::string(invoice_id)?])
+            .await?;
+        scval::to_option(&v, PaymentRecord::try_from)
+    }
+
+    /// Look up a batch of invoices in one simulation. Every id maps to its
+    /// record, or to `None` if it was never recorded (or has been compacted).
+    pub async fn find_payments(
+        &self,
+        invoice_ids: &[&str],
+    ) -> Result<BTreeMap<String, Option<PaymentRecord>>> {
+        let ids = invoice_ids
+            .iter()
+            .map(|id| scval::string(id))
+            .collect::<Result<Vec<_>>>()?;
+        let v = self.read("find_payments", vec![scval::vec(ids)?]).await?;
+        scval::to_payment_lookups(&v)
+    }
+
+    /// One page of `export_page`: the full records of the payments with
+    /// sequence numbers `cursor..cursor + limit`, and the next cursor
+    /// (`None` after the last page). Start at 0.
+    pub async fn export_page(
+        &self,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<(Vec<PaymentRecord>, Option<u32>)> {
+        let v = self
+            .read("export_page", vec![ScVal::U32(cursor), ScVal::U32(limit)])
+            .await?;
+        scval::to_export_page(&v)
+    }
+
+    /// Fetch the canonical receipt of `invoice_id`, ready to
+    /// [`sign`](PaymentReceipt::sign). Fails as [`Self::get_payment`] does.
+    pub async fn get_receipt(&self, invoice_id: &str) -> Result<PaymentReceipt> {
+        let v = self
+            .read("get_receipt", vec![scval::string(invoice_id)?])
+            .await?;
+        PaymentReceipt::try_from(&v)
+    }
+
+    /// Fetch the SEP-31 identifiers recorded with the payment of
+    /// `invoice_id`, or `None` if it was not a SEP-31 payment.
+    pub async fn remittance(&self, invoice_id: &str) -> Result<Option<Remittance>> {
+        let v = self
+            .read("remittance", vec![scval::string(invoice_id)?])
+            .await?;
+        scval::to_option(&v, Remittance::try_from)
+    }
+
+    /// Find the invoice settled by SEP-31 transaction `transaction_id`.
+    pub async fn remittance_invoice(&self, transaction_id: &str) -> Result<Option<String>> {
+        let v = self
+            .read("remittance_invoice", vec![scval::string(transaction_id)?])
+            .await?;
+        scval::to_option(&v, scval::to_string)
+    }
+
+    pub async fn get_pending_payment(&self, invoice_id: &str) -> Result<PaymentRecord> {
+        let v = self
+            .read("get_pending_payment", vec![scval::string(invoice_id)?])
+            .await?;
+        PaymentRecord::try_from(&v)
+    }
+
+    pub async fn has_payment(&self, invoice_id: &str) -> Result<bool> {
+        let v = self
+            .read("has_payment", vec![scval::string(invoice_id)?])
+            .await?;
+        scval::to_bool(&v)
+    }
+
+    pub async fn payment_count(&self) -> Result<u32> {
+        scval::to_u32(&self.read("payment_count", vec![]).await?)
+    }
+
+    /// Current admin as a `G...` strkey.
+    pub async fn admin(&self) -> Result<String> {
+        scval::to_address(&self.read("admin", vec![]).await?)
+    }
+
+    /// Fetch the nonce the admin must pass to its next config change or
+    /// admin transfer.
+    pub async fn admin_nonce(&self) -> Result<u64> {
+        scval::to_u64(&self.read("admin_nonce", vec![]).await?)
+    }
+
+    // Verification
+
+    /// Cross-check the record of `invoice_id` against the payment operation
+    /// on `horizon` whose text memo is the invoice id, sent by the recorded
+    /// payer. The report lists every field (memo, asset, amount, source)
+    /// that disagrees, or `OperationNotFound` if there is no such operation.
+    ///
+    /// Fails as [`Self::get_payment`] does if there is no record.
+    pub async fn verify_payment(
+        &self,
+        horizon: &HorizonClient,
+        invoice_id: &str,
+    ) -> Result<VerificationReport> {
+        let record = self.get_payment(invoice_id).await?;
+        let operation = horizon.find_payment(&record.payer, invoice_id).await?;
+        Ok(VerificationReport::new(record, operation.as_ref()))
+    }
+
+    // Events
+
+    /// Fetch and decode one page of this contract's events.
+    pub async fn events(&self, start: EventStart, limit: u32) -> Result<EventPage> {
+        let (start_ledger, cursor) = match start {
+            EventStart::Ledger(l) => (Some(l), None),
+            EventStart::Cursor(c) => (None, Some(c)),
+        };
+        let response = self
+            .rpc
+            .get_events(&GetEventsRequest {
+                start_ledger,
+                end_ledger: None,
+                filters: vec![contract_filter(&self.contract_id)],
+                pagination: Some(Pagination {
+                    cursor,
+                    limit: Some(limit),
+                }),
+            })
+            .await?;
+
+        let events = response
+            .events
+            .iter()
+            .map(EventEnvelope::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        let cursor = response
+            .cursor
+            .or_else(|| events.last().map(|e| e.id.clone()));
+        Ok(EventPage {
+            events,
+            cursor,
+            latest_ledger: response.latest_ledger,
+            oldest_ledger: response.oldest_ledger,
+        })
+    }
+}
+
+// Transport-level helpers, shared with [`crate::deploy`] which has no
+// contract to bind to yet.
+
+/// Simulate `tx` and assemble it with the returned resources and auth.
+pub async fn simulate(rpc: &RpcClient, tx: Transaction) -> Result<Simulation> {
+    let response = rpc.simulate_transaction(&tx::unsigned(tx.clone())).await?;
+    simulation(tx, response)
+}
+
+/// Interpret a `simulateTransaction` `response` for `tx`.
+pub(crate) fn simulation(
+    tx: Transaction,
+    response: SimulateTransactionResponse,
+) -> Result<Simulation> {
+    if let Some(message) = response.error {
+        return Err(Error::from_simulation_message(message));
+    }
+    if let Some(preamble) = response.restore_preamble {
+        return Err(Error::EntryArchived {
+            transaction_data: preamble.transaction_data,
+            min_resource_fee: parse_fee(&preamble.min_resource_fee)?,
+        });
+    }
+
+    let result = response
+        .results
+        .and_then(|r| r.into_iter().next())
+        .ok_or_else(|| Error::Simulation("no result returned".into()))?;
+    let auth = result
+        .auth
+        .iter()
+        .map(|a| SorobanAuthorizationEntry::from_xdr_base64(a, Limits::none()))
+        .collect::<core::result::Result<Vec<_>, _>>()?;
+    let min_resource_fee = parse_fee(response.min_resource_fee.as_deref().unwrap_or("0"))?;
+    let transaction_data = response
+        .transaction_data
+        .ok_or_else(|| Error::Simulation("no transactionData returned".into()))?;
+
+    Ok(Simulation {
+        transaction: tx::assemble(tx, &transaction_data, min_resource_fee, auth.clone())?,
+        result: ScVal::from_xdr_base64(&result.xdr, Limits::none())?,
+        auth,
+        min_resource_fee,
+        latest_ledger: response.latest_ledger,
+    })
+}
+
+/// Submit a signed envelope and wait for it to become final.
+pub async fn submit(
+    rpc: &RpcClient,
+    envelope: &TransactionEnvelope,
+    poll_interval: Duration,
+    timeout: Duration,
+) -> Result<TxOutcome> {
+    let hash = send(rpc, envelope).await?;
+    wait_for(rpc, &hash, poll_interval, timeout).await
+}
+
+/// Send a signed envelope; `PENDING` and `DUPLICATE` count as accepted.
+pub async fn send(rpc: &RpcClient, envelope: &TransactionEnvelope) -> Result<String> {
+    let sent = rpc.send_transaction(envelope).await?;
+    match sent.status.as_str() {
+        "PENDING" | "DUPLICATE" => Ok(sent.hash),
+        _ => Err(Error::SendRejected {
+            hash: sent.hash,
+            status: sent.status,
+            error_result_xdr: sent.error_result_xdr,
+        }),
+    }
+}
+
+/// Poll `getTransaction` for `hash` until it succeeds, fails, or `timeout`
+/// elapses.
+pub async fn wait_for(
+    rpc: &RpcClient,
+    hash: &str,
+    poll_interval: Duration,
+    timeout: Duration,
+) -> Result<TxOutcome> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let status = rpc.get_transaction(hash).await?;
+        match status.status.as_str() {
+            "SUCCESS" => {
+                let return_value = match status.result_meta_xdr.as_deref() {
+                    Some(meta) => return_value(meta)?,
+                    None => None,
+                };
+                return Ok(TxOutcome {
+                    hash: hash.to_string(),
+                    ledger: status.ledger.unwrap_or(status.latest_ledger),
+                    return_value,
+                });
+            }
+            "FAILED" => {
+                return Err(Error::TransactionFailed {
+                    hash: hash.to_string(),
+                    result_xdr: status.result_xdr,
+                })
+            }
+            _ if Instant::now() >= deadline => {
+                return Err(Error::Timeout {
+                    hash: hash.to_string(),
+                })
+            }
+            _ => tokio::time::sleep(poll_interval).await,
+        }
+    }
+}
+
+/// Encode the `record_payment` / `report_pending_payment` argument list.
+pub fn record_payment_args(p: &RecordPayment) -> Result<Vec<ScVal>> {
+    Ok(vec![
+        scval::string(&p.invoice_id)?,
+        scval::address(&p.payer)?,
+        scval::string(p.asset.code())?,
+        scval::string(p.asset.issuer())?,
+        scval::i128(p.amount),
+    ])
+}
+
+/// Bytes the signing key signs to authorize `p` for `record_payment_signed`
+/// on `contract_id`: the XDR of `(contract address, SignedPayment)`.
+///
+/// Needs no network access, so it can run next to an offline key.
+pub fn signed_payment_message(contract_id: &str, p: &RecordPayment) -> Result<Vec<u8>> {
+    let message = scval::vec(vec![scval::address(contract_id)?, signed_payment(p)?])?;
+    Ok(message.to_xdr(Limits::none())?)
+}
+
+/// Encode `p` as the contract's `SignedPayment` struct.
+fn signed_payment(p: &RecordPayment) -> Result<ScVal> {
+    scval::map(vec![
+        ("invoice_id", scval::string(&p.invoice_id)?),
+        ("payer", scval::address(&p.payer)?),
+        ("asset_code", scval::string(p.asset.code())?),
+        ("asset_issuer", scval::string(p.asset.issuer())?),
+        ("amount", scval::i128(p.amount)),
+    ])
+}
+
+fn parse_fee(s: &str) -> Result<i64> {
+    s.parse()
+        .map_err(|_| Error::Decode(format!("invalid fee `{s}`")))
+}
+
+/// Extract the contract return value from base64 `TransactionMeta`.
+fn return_value(meta: &str) -> Result<Option<ScVal>> {
+    Ok(match TransactionMeta::from_xdr_base64(meta, Limits::none())? {
+        TransactionMeta::V3(m) => m.soroban_meta.map(|s| s.return_value),
+        TransactionMeta::V4(m) => m.soroban_meta.and_then(|s| s.return_value),
+        _ => None,
+    })
+}
@@ -0,0 +1,57 @@
+//! Uploading contract code and creating instances.
+//!
+//! Deployment is two transactions: upload the WASM (idempotent — the ledger
+//! keys code by hash) and create an instance whose address is derived from
+//! the deployer and a salt, so it is known before submission.
+
+use std::time::Duration;
+
+use crate::client::{simulate, submit, TxOutcome};
+use crate::error::Result;
+use crate::rpc::RpcClient;
+use crate::signer::Signer;
+use crate::tx;
+
+/// Result of [`deploy`].
+#[derive(Clone, Debug)]
+pub struct Deployment {
+    /// `C...` strkey of the new instance.
+    pub contract_id: String,
+    pub wasm_hash: [u8; 32],
+    pub upload: TxOutcome,
+    pub create: TxOutcome,
+}
+
+/// Upload `wasm` and create an instance of it with `signer` as deployer.
+///
+/// The instance is **not** initialised; call
+/// [`InvoicePaymentClient::initialize`](crate::InvoicePaymentClient::initialize)
+/// next.
+pub async fn deploy<S: Signer>(
+    rpc: &RpcClient,
+    network_passphrase: &str,
+    signer: &S,
+    wasm: &[u8],
+    salt: [u8; 32],
+    timeout: Duration,
+) -> Result<Deployment> {
+    let source = signer.public_key_bytes();
+    let poll = Duration::from_secs(1);
+
+    let sequence = rpc.get_account_sequence(source).await? + 1;
+    let sim = simulate(rpc, tx::build_upload(source, sequence, wasm)?).await?;
+    let envelope = tx::sign(sim.transaction, network_passphrase, signer).await?;
+    let upload = submit(rpc, &envelope, poll, timeout).await?;
+
+    let wasm_hash = tx::wasm_hash(wasm);
+    let sim = simulate(rpc, tx::build_create(source, sequence + 1, wasm_hash, salt)?).await?;
+    let envelope = tx::sign(sim.transaction, network_passphrase, signer).await?;
+    let create = submit(rpc, &envelope, poll, timeout).await?;
+
+    Ok(Deployment {
+        contract_id: tx::deployed_contract_id(source, salt, network_passphrase)?,
+        wasm_hash,
+        upload,
+        create,
+    })
+}
@@ -0,0 +1,141 @@
+//! Dry runs: everything a simulation says about a call, without
+//! submitting it.
+//!
+//! [`simulate`](crate::client::simulate) stops at the first problem. A dry
+//! run keeps the diagnostic events whether or not the call would succeed,
+//! so an operator can see why it would fail, what it would cost and which
+//! events it would emit before anything touches the network.
+
+use serde::Serialize;
+use stellar_xdr::curr::{
+    ContractEvent as XdrEvent, ContractEventBody, ContractEventType, DiagnosticEvent, Limits,
+    ReadXdr, ScAddress, ScVal, SorobanCredentials, Transaction,
+};
+
+use crate::client::simulation;
+use crate::error::{Error, Result};
+use crate::estimate::ResourceEstimate;
+use crate::events::ContractEvent;
+use crate::rpc::{RpcClient, SimulateTransactionResponse};
+use crate::scval;
+use crate::tx;
+
+/// Outcome of [`dry_run`].
+#[derive(Debug)]
+pub struct DryRun {
+    /// Why the call would fail; `None` if it would succeed.
+    pub error: Option<Error>,
+    /// Value the call would return.
+    pub result: Option<ScVal>,
+    pub estimate: Option<ResourceEstimate>,
+    /// Addresses that must sign an auth entry besides the source.
+    pub auth: Vec<String>,
+    /// Events the call would emit.
+    pub events: Vec<SimulatedEvent>,
+    /// Host diagnostics (calls, returns, logs and errors), rendered.
+    pub diagnostics: Vec<String>,
+}
+
+/// A contract event from a simulation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SimulatedEvent {
+    pub contract_id: Option<String>,
+    /// Decoded when the dry-run contract emitted it.
+    pub event: Option<ContractEvent>,
+    pub topics: Vec<String>,
+    pub data: String,
+}
+
+/// Simulate `tx` and collect the result, resources, auth and events.
+/// Events of `contract_id` are decoded. A failing simulation is reported
+/// in [`DryRun::error`]; `Err` means the simulation could not be run.
+pub async fn dry_run(
+    rpc: &RpcClient,
+    tx: Transaction,
+    contract_id: Option<&str>,
+) -> Result<DryRun> {
+    let response = rpc.simulate_transaction(&tx::unsigned(tx.clone())).await?;
+    DryRun::from_response(tx, response, contract_id)
+}
+
+impl DryRun {
+    /// Interpret a `simulateTransaction` `response` for `tx`.
+    pub fn from_response(
+        tx: Transaction,
+        response: SimulateTransactionResponse,
+        contract_id: Option<&str>,
+    ) -> Result<Self> {
+        let mut events = Vec::new();
+        let mut diagnostics = Vec::new();
+        for raw in response.events.iter().flatten() {
+            let diagnostic = DiagnosticEvent::from_xdr_base64(raw, Limits::none())?;
+            match diagnostic.event.type_ {
+                ContractEventType::Contract if diagnostic.in_successful_contract_call => {
+                    events.push(SimulatedEvent::new(&diagnostic.event, contract_id));
+                }
+                ContractEventType::Contract => {}
+                _ => diagnostics.push(render(&diagnostic.event)),
+            }
+        }
+
+        let mut run = DryRun {
+            error: None,
+            result: None,
+            estimate: None,
+            auth: Vec::new(),
+            events,
+            diagnostics,
+        };
+        match simulation(tx, response) {
+            Ok(sim) => {
+                run.result = Some(sim.result.clone());
+                run.auth = sim
+                    .auth
+                    .iter()
+                    .filter_map(|entry| match &entry.credentials {
+                        SorobanCredentials::Address(creds) => {
+                            scval::address_to_strkey(&creds.address).ok()
+                        }
+                        SorobanCredentials::SourceAccount => None,
+                    })
+                    .collect();
+                run.estimate = Some(ResourceEstimate::from_simulation(sim)?);
+            }
+            Err(err) => run.error = Some(err),
+        }
+        Ok(run)
+    }
+}
+
+impl SimulatedEvent {
+    fn new(event: &XdrEvent, dry_run_contract: Option<&str>) -> Self {
+        let ContractEventBody::V0(body) = &event.body;
+        let contract_id = emitter(event);
+        let ours = contract_id.is_some() && contract_id.as_deref() == dry_run_contract;
+        Self {
+            event: ours
+                .then(|| ContractEvent::decode(&body.topics, &body.data).ok())
+                .flatten(),
+            topics: body.topics.iter().map(scval::display).collect(),
+            data: scval::display(&body.data),
+            contract_id,
+        }
+    }
+}
+
+/// `topic topic ...: data`, prefixed with the emitting contract if any.
+fn render(event: &XdrEvent) -> String {
+    let ContractEventBody::V0(body) = &event.body;
+    let topics: Vec<_> = body.topics.iter().map(scval::display).collect();
+    let line = format!("{}: {}", topics.join(" "), scval::display(&body.data));
+    match emitter(event) {
+        Some(id) => format!("[{id}] {line}"),
+        None => line,
+    }
+}
+
+/// `C...` strkey of the contract that emitted `event`.
+fn emitter(event: &XdrEvent) -> Option<String> {
+    let id = event.contract_id.clone()?;
+    scval::address_to_strkey(&ScAddress::Contract(id)).ok()
+}
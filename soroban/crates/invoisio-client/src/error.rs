@@ -0,0 +1,144 @@
+use stellar_xdr::curr::{Limits, ReadXdr, TransactionResult, TransactionResultResult};
+
+use crate::types::ContractError;
+
+/// Everything that can go wrong talking to the contract through Soroban RPC.
+///
+/// Contract-level failures are decoded into [`ContractError`] so callers can
+/// match on them exactly like the on-chain tests do; everything else is
+/// transport, encoding or network state.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// HTTP transport failure (connection refused, TLS, timeout, ...).
+    #[error("http: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The RPC server answered with a JSON-RPC error object.
+    #[error("rpc error {code}: {message}")]
+    Rpc { code: i64, message: String },
+
+    /// XDR encoding / decoding failure.
+    #[error("xdr: {0}")]
+    Xdr(#[from] stellar_xdr::curr::Error),
+
+    /// A `G...` / `C...` / `S...` strkey could not be parsed.
+    #[error("invalid strkey: {0}")]
+    Strkey(String),
+
+    /// The contract returned one of its typed errors.
+    #[error("contract error: {0:?}")]
+    Contract(ContractError),
+
+    /// The contract returned an error code this client does not know about.
+    #[error("unknown contract error code {0}")]
+    UnknownContractError(u32),
+
+    /// Simulation failed for a reason other than a contract error.
+    #[error("simulation failed: {0}")]
+    Simulation(String),
+
+    /// Simulation succeeded but the footprint touches archived entries; the
+    /// caller must submit a restore operation before retrying.
+    ///
+    /// This is how an archived `PaymentRecord` surfaces off-chain — it is
+    /// never reported as [`ContractError::PaymentNotFound`].
+    #[error("entry archived: restore required (min resource fee {min_resource_fee})")]
+    EntryArchived {
+        /// Base64 `SorobanTransactionData` for the restore transaction.
+        transaction_data: String,
+        min_resource_fee: i64,
+    },
+
+    /// A value returned by the contract did not have the expected shape.
+    #[error("decode: {0}")]
+    Decode(String),
+
+    /// `sendTransaction` rejected the transaction outright.
+    #[error("transaction {hash} rejected with status {status}")]
+    SendRejected {
+        hash: String,
+        status: String,
+        error_result_xdr: Option<String>,
+    },
+
+    /// The transaction was included in a ledger but failed.
+    #[error("transaction {hash} failed")]
+    TransactionFailed {
+        hash: String,
+        result_xdr: Option<String>,
+    },
+
+    /// The transaction was not seen in a ledger before the polling deadline.
+    #[error("transaction {hash} not confirmed before timeout")]
+    Timeout { hash: String },
+
+    /// An external signer (Ledger, KMS) refused to sign or returned an
+    /// unusable signature.
+    #[error("signer: {0}")]
+    Signer(String),
+
+    /// The profiles file is unreadable, malformed, or contradicts the
+    /// selected network.
+    #[error("profile: {0}")]
+    Profile(String),
+
+    /// The RPC endpoint serves a different network than the profile says.
+    #[error("network mismatch: expected `{expected}`, RPC serves `{actual}`")]
+    NetworkMismatch { expected: String, actual: String },
+
+    /// An anchor's `stellar.toml` or API response is missing something the
+    /// SEP requires, or the transaction is not in the expected state.
+    #[error("anchor: {0}")]
+    Anchor(String),
+
+    /// A SEP-7 payment request cannot be built as asked (memo too long,
+    /// non-positive amount, URI too large for a QR code).
+    #[error("payment request: {0}")]
+    PayRequest(String),
+}
+
+impl Error {
+    /// `true` if the network rejected the transaction for a stale or
+    /// skipped sequence number (`txBadSeq`), at send time or in a ledger.
+    /// Rebuilding with a fresh sequence number fixes it.
+    pub fn is_bad_seq(&self) -> bool {
+        let xdr = match self {
+            Error::SendRejected {
+                error_result_xdr: Some(xdr),
+                ..
+            } => xdr,
+            Error::TransactionFailed {
+                result_xdr: Some(xdr),
+                ..
+            } => xdr,
+            _ => return false,
+        };
+        TransactionResult::from_xdr_base64(xdr, Limits::none())
+            .is_ok_and(|r| matches!(r.result, TransactionResultResult::TxBadSeq))
+    }
+
+    /// Parse the `Error(Contract, #N)` fragment the host embeds in simulation
+    /// error strings into a typed error, if present.
+    pub(crate) fn from_simulation_message(message: String) -> Self {
+        match parse_contract_error_code(&message) {
+            Some(code) => match ContractError::try_from(code) {
+                Ok(err) => Error::Contract(err),
+                Err(()) => Error::UnknownContractError(code),
+            },
+            None => Error::Simulation(message),
+        }
+    }
+}
+
+/// Extract `N` from the first `Error(Contract, #N)` in a host error string.
+pub(crate) fn parse_contract_error_code(message: &str) -> Option<u32> {
+    const MARKER: &str = "Error(Contract, #";
+    let start = message.find(MARKER)? + MARKER.len();
+    let digits: String = message[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
@@ -0,0 +1,76 @@
+//! Resource and fee estimates from `simulateTransaction`.
+//!
+//! A Soroban fee is `inclusion fee + resource fee`. Simulation fixes the
+//! resource part (CPU, footprint, I/O); the inclusion part is what competes
+//! for ledger space under load. [`ResourceEstimate`] exposes both so a
+//! submitter can bid the inclusion fee separately from the resources.
+
+use stellar_xdr::curr::{LedgerKey, SorobanTransactionData, Transaction, TransactionExt};
+
+use crate::client::Simulation;
+use crate::error::{Error, Result};
+
+/// What a simulated call will consume, plus the transaction assembled from
+/// it.
+#[derive(Clone, Debug)]
+pub struct ResourceEstimate {
+    /// Assembled with resources, auth and `inclusion_fee + resource_fee`;
+    /// ready to sign.
+    pub transaction: Transaction,
+    /// CPU instructions budgeted.
+    pub instructions: u32,
+    pub disk_read_bytes: u32,
+    pub write_bytes: u32,
+    pub read_only: Vec<LedgerKey>,
+    pub read_write: Vec<LedgerKey>,
+    /// Resource fee (stroops) declared in the transaction data.
+    pub resource_fee: i64,
+    /// Inclusion fee (stroops) currently set on `transaction`.
+    pub inclusion_fee: u32,
+    pub latest_ledger: u32,
+}
+
+impl ResourceEstimate {
+    pub fn from_simulation(sim: Simulation) -> Result<Self> {
+        let TransactionExt::V1(data) = &sim.transaction.ext else {
+            return Err(Error::Simulation("assembled transaction has no Soroban data".into()));
+        };
+        let SorobanTransactionData {
+            resources,
+            resource_fee,
+            ..
+        } = data.clone();
+        let resource_fee_u32 = u32::try_from(resource_fee)
+            .map_err(|_| Error::Simulation(format!("resource fee {resource_fee} out of range")))?;
+
+        Ok(Self {
+            inclusion_fee: sim.transaction.fee.saturating_sub(resource_fee_u32),
+            instructions: resources.instructions,
+            disk_read_bytes: resources.disk_read_bytes,
+            write_bytes: resources.write_bytes,
+            read_only: resources.footprint.read_only.to_vec(),
+            read_write: resources.footprint.read_write.to_vec(),
+            resource_fee,
+            latest_ledger: sim.latest_ledger,
+            transaction: sim.transaction,
+        })
+    }
+
+    /// Total fee (stroops) the transaction currently bids.
+    pub fn total_fee(&self) -> u32 {
+        self.transaction.fee
+    }
+
+    /// The assembled transaction with its inclusion fee replaced by
+    /// `inclusion_fee`. The resource part is left as simulated.
+    pub fn with_inclusion_fee(&self, inclusion_fee: u32) -> Result<Transaction> {
+        let resource_fee = u32::try_from(self.resource_fee).map_err(|_| {
+            Error::Simulation(format!("resource fee {} out of range", self.resource_fee))
+        })?;
+        let mut tx = self.transaction.clone();
+        tx.fee = resource_fee
+            .checked_add(inclusion_fee)
+            .ok_or_else(|| Error::Simulation("fee overflows u32".into()))?;
+        Ok(tx)
+    }
+}
@@ -0,0 +1,145 @@
+//! Typed decoding of the contract's Soroban events.
+//!
+//! `#[contractevent]` publishes each event with a single topic — the struct
+//! name in `lower_snake_case` — and a data map keyed by field name:
+//!
+//! | Topic                      | Data                                     |
+//! |----------------------------|------------------------------------------|
+//! | `payment_recorded`         | `{ record: PaymentRecord }`              |
+//! | `pending_payment_reported` | `{ record: PaymentRecord }`              |
+//! | `payment_imported`         | `{ record: PaymentRecord }`              |
+//! | `payment_refunded`         | `{ invoice_id, amount, refunded_total }` |
+
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::{Limits, ReadXdr, ScVal, WriteXdr};
+
+use crate::error::Result;
+use crate::rpc::{EventFilter, RpcEvent};
+use crate::scval;
+use crate::types::{i128_string, PaymentRecord};
+
+pub const PAYMENT_RECORDED: &str = "payment_recorded";
+pub const PENDING_PAYMENT_REPORTED: &str = "pending_payment_reported";
+pub const PAYMENT_IMPORTED: &str = "payment_imported";
+pub const PAYMENT_REFUNDED: &str = "payment_refunded";
+
+/// A decoded contract event.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ContractEvent {
+    PaymentRecorded {
+        record: PaymentRecord,
+    },
+    PendingPaymentReported {
+        record: PaymentRecord,
+    },
+    /// A historical payment migrated from a previous deployment; `record`
+    /// keeps its original timestamp.
+    PaymentImported {
+        record: PaymentRecord,
+    },
+    /// A refund of `amount` against `invoice_id`; `refunded_total` includes
+    /// it.
+    PaymentRefunded {
+        invoice_id: String,
+        #[serde(with = "i128_string")]
+        amount: i128,
+        #[serde(with = "i128_string")]
+        refunded_total: i128,
+    },
+    /// An event this client version does not know how to decode. Kept so
+    /// consumers can checkpoint past it instead of stalling.
+    Unknown {
+        topic: String,
+    },
+}
+
+impl ContractEvent {
+    /// The event's topic symbol.
+    pub fn topic(&self) -> &str {
+        match self {
+            ContractEvent::PaymentRecorded { .. } => PAYMENT_RECORDED,
+            ContractEvent::PendingPaymentReported { .. } => PENDING_PAYMENT_REPORTED,
+            ContractEvent::PaymentImported { .. } => PAYMENT_IMPORTED,
+            ContractEvent::PaymentRefunded { .. } => PAYMENT_REFUNDED,
+            ContractEvent::Unknown { topic } => topic,
+        }
+    }
+
+    /// Decode from the raw topic list and data value.
+    pub fn decode(topics: &[ScVal], data: &ScVal) -> Result<Self> {
+        let topic = match topics.first() {
+            Some(t) => scval::to_symbol(t)?,
+            None => String::new(),
+        };
+        Ok(match topic.as_str() {
+            PAYMENT_RECORDED => ContractEvent::PaymentRecorded {
+                record: PaymentRecord::try_from(scval::map_get(data, "record")?)?,
+            },
+            PENDING_PAYMENT_REPORTED => ContractEvent::PendingPaymentReported {
+                record: PaymentRecord::try_from(scval::map_get(data, "record")?)?,
+            },
+            PAYMENT_IMPORTED => ContractEvent::PaymentImported {
+                record: PaymentRecord::try_from(scval::map_get(data, "record")?)?,
+            },
+            PAYMENT_REFUNDED => ContractEvent::PaymentRefunded {
+                invoice_id: scval::to_string(scval::map_get(data, "invoice_id")?)?,
+                amount: scval::to_i128(scval::map_get(data, "amount")?)?,
+                refunded_total: scval::to_i128(scval::map_get(data, "refunded_total")?)?,
+            },
+            _ => ContractEvent::Unknown { topic },
+        })
+    }
+}
+
+/// A decoded event plus the position metadata RPC attaches to it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    /// RPC event id; doubles as a resumable cursor.
+    pub id: String,
+    pub ledger: u32,
+    pub ledger_closed_at: String,
+    pub contract_id: String,
+    pub tx_hash: Option<String>,
+    pub event: ContractEvent,
+}
+
+impl TryFrom<&RpcEvent> for EventEnvelope {
+    type Error = crate::error::Error;
+
+    fn try_from(raw: &RpcEvent) -> Result<Self> {
+        let topics = raw
+            .topic
+            .iter()
+            .map(|t| ScVal::from_xdr_base64(t, Limits::none()))
+            .collect::<core::result::Result<Vec<_>, _>>()?;
+        let data = ScVal::from_xdr_base64(&raw.value, Limits::none())?;
+        Ok(EventEnvelope {
+            id: raw.id.clone(),
+            ledger: raw.ledger,
+            ledger_closed_at: raw.ledger_closed_at.clone(),
+            contract_id: raw.contract_id.clone(),
+            tx_hash: raw.tx_hash.clone(),
+            event: ContractEvent::decode(&topics, &data)?,
+        })
+    }
+}
+
+/// Filter matching every event emitted by `contract_id`.
+pub fn contract_filter(contract_id: &str) -> EventFilter {
+    EventFilter {
+        kind: "contract".into(),
+        contract_ids: vec![contract_id.to_string()],
+        topics: Vec::new(),
+    }
+}
+
+/// Filter matching only events with the given topic symbol.
+pub fn topic_filter(contract_id: &str, topic: &str) -> Result<EventFilter> {
+    let topic = scval::symbol(topic)?.to_xdr_base64(Limits::none())?;
+    Ok(EventFilter {
+        kind: "contract".into(),
+        contract_ids: vec![contract_id.to_string()],
+        topics: vec![vec![topic]],
+    })
+}
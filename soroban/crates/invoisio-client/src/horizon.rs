@@ -0,0 +1,194 @@
+//! Minimal Horizon reader for the payment operations behind recorded
+//! payments.
+//!
+//! Only what [`crate::verify`] needs: a payer's outgoing payment-like
+//! operations with their transaction memo.
+
+use serde::Deserialize;
+
+use crate::error::Result;
+use crate::types::Asset;
+
+/// Page size requested from Horizon (its maximum).
+pub const PAGE_LIMIT: u32 = 200;
+
+/// Pages of a payer's history searched, newest first, before giving up on
+/// finding the operation of an invoice.
+pub const MAX_PAGES: usize = 5;
+
+/// Operation types whose `asset_*` / `amount` fields describe what the
+/// destination received.
+pub const PAYMENT_KINDS: [&str; 3] = [
+    "payment",
+    "path_payment_strict_receive",
+    "path_payment_strict_send",
+];
+
+#[derive(Clone, Debug)]
+pub struct HorizonClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl HorizonClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http(reqwest::Client::new(), base_url)
+    }
+
+    /// Use a preconfigured `reqwest::Client` (timeouts, proxies, ...).
+    pub fn with_http(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// One page of the payment-like operations of `account`, newest first,
+    /// strictly before `cursor` (or from the latest when `None`), with their
+    /// transactions joined in.
+    pub async fn payments(
+        &self,
+        account: &str,
+        cursor: Option<&str>,
+    ) -> Result<Vec<PaymentOperation>> {
+        let mut query = vec![
+            ("order", "desc".to_string()),
+            ("limit", PAGE_LIMIT.to_string()),
+            ("join", "transactions".to_string()),
+        ];
+        if let Some(c) = cursor {
+            query.push(("cursor", c.to_string()));
+        }
+        let page: Page<PaymentOperation> = self
+            .http
+            .get(format!("{}/accounts/{account}/payments", self.base_url))
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(page.embedded.records)
+    }
+
+    /// The newest successful payment sent by `payer` whose text memo is
+    /// `memo`, searching at most [`MAX_PAGES`] pages back.
+    pub async fn find_payment(&self, payer: &str, memo: &str) -> Result<Option<PaymentOperation>> {
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_PAGES {
+            let page = self.payments(payer, cursor.as_deref()).await?;
+            let found = page.iter().find(|op| {
+                op.transaction_successful
+                    && PAYMENT_KINDS.contains(&op.kind.as_str())
+                    && op.from.as_deref() == Some(payer)
+                    && op.memo() == Some(memo)
+            });
+            if let Some(op) = found {
+                return Ok(Some(op.clone()));
+            }
+            match page.last() {
+                Some(last) if page.len() == PAGE_LIMIT as usize => {
+                    cursor = Some(last.paging_token.clone())
+                }
+                _ => break,
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    #[serde(rename = "_embedded")]
+    embedded: Embedded<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Embedded<T> {
+    records: Vec<T>,
+}
+
+/// A Horizon payment-like operation. Only the fields verification needs
+/// are kept.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct PaymentOperation {
+    pub id: String,
+    pub paging_token: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub transaction_hash: String,
+    #[serde(default)]
+    pub transaction_successful: bool,
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Mux id (decimal string) when sent from an `M...` address.
+    #[serde(default)]
+    pub from_muxed_id: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub asset_type: Option<String>,
+    #[serde(default)]
+    pub asset_code: Option<String>,
+    #[serde(default)]
+    pub asset_issuer: Option<String>,
+    /// Decimal string with 7 fractional digits, e.g. `"10.0000000"`.
+    #[serde(default)]
+    pub amount: Option<String>,
+    pub created_at: String,
+    /// Present because we request `join=transactions`.
+    #[serde(default)]
+    pub transaction: Option<HorizonTransaction>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct HorizonTransaction {
+    pub memo_type: String,
+    #[serde(default)]
+    pub memo: Option<String>,
+}
+
+impl PaymentOperation {
+    /// The transaction's memo, if it is a text memo.
+    pub fn memo(&self) -> Option<&str> {
+        let tx = self.transaction.as_ref()?;
+        match tx.memo_type.as_str() {
+            "text" => tx.memo.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The asset the operation delivered to its destination.
+    pub fn asset(&self) -> Option<Asset> {
+        match self.asset_type.as_deref()? {
+            "native" => Some(Asset::Native),
+            "credit_alphanum4" | "credit_alphanum12" => Some(Asset::Token {
+                code: self.asset_code.clone()?,
+                issuer: self.asset_issuer.clone()?,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The delivered amount in base units (7 decimals).
+    pub fn amount(&self) -> Option<i128> {
+        parse_amount(self.amount.as_deref()?)
+    }
+}
+
+/// Parse a Horizon decimal amount (`"12.3400000"`) into base units (7 dp).
+pub fn parse_amount(s: &str) -> Option<i128> {
+    const DECIMALS: usize = 7;
+    let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+    if whole.is_empty() || frac.len() > DECIMALS || s.starts_with('-') {
+        return None;
+    }
+    let whole: i128 = whole.parse().ok()?;
+    let frac_digits = format!("{:0<width$}", frac, width = DECIMALS);
+    let frac: i128 = frac_digits.parse().ok()?;
+    whole.checked_mul(10_000_000)?.checked_add(frac)
+}
@@ -0,0 +1,99 @@
+//! # invoisio-client
+//!
+//! Async Soroban-RPC client for the Invoisio `invoice-payment` contract.
+//!
+//! Wraps the plumbing every consumer of the contract otherwise re-implements:
+//! argument encoding, transaction building and signing, simulation, submission
+//! with confirmation polling, and typed event decoding.
+//!
+//! ## Module layout
+//! | Module        | Responsibility                                           |
+//! |---------------|----------------------------------------------------------|
+//! | `client.rs`   | [`InvoicePaymentClient`] — typed contract methods        |
+//! | `backoff.rs`  | [`Backoff`] — exponential retry delays                   |
+//! | `deploy.rs`   | WASM upload and instance creation                        |
+//! | `dry_run.rs`  | [`DryRun`] — simulated result, events and diagnostics    |
+//! | `estimate.rs` | [`ResourceEstimate`] — CPU, footprint and fees           |
+//! | `rpc.rs`      | JSON-RPC transport and Soroban RPC wire types            |
+//! | `tx.rs`       | Transaction build / assemble / sign, [`Keypair`]         |
+//! | `signer.rs`   | [`Signer`] trait: local, Ledger and HTTP KMS signers     |
+//! | `network.rs`  | [`Network`] profiles: passphrase, endpoints, contract id |
+//! | `events.rs`   | [`ContractEvent`] decoding and `getEvents` filters       |
+//! | `horizon.rs`  | [`HorizonClient`] — a payer's payment operations         |
+//! | `receipt.rs`  | [`PaymentReceipt`] — render, sign and verify receipts    |
+//! | `verify.rs`   | [`VerificationReport`] — record vs. Horizon payment      |
+//! | `sep7.rs`     | [`PayRequest`] — SEP-7 pay URIs and QR codes             |
+//! | `sep10.rs`    | [`WebAuth`] — SEP-10 challenges and [`Jwt`] tokens       |
+//! | `sep31.rs`    | [`AnchorClient`] — SEP-31 transactions and remittances   |
+//! | `scval.rs`    | `ScVal` ⇄ Rust conversions                               |
+//! | `types.rs`    | Off-chain mirrors of contract types and errors           |
+//! | `error.rs`    | [`Error`] / [`Result`]                                   |
+//!
+//! ## Example
+//! ```no_run
+//! # async fn run() -> invoisio_client::Result<()> {
+//! use invoisio_client::{Asset, InvoicePaymentClient, Keypair, RecordPayment};
+//!
+//! let client = InvoicePaymentClient::new(
+//!     "https://soroban-testnet.stellar.org",
+//!     "Test SDF Network ; September 2015",
+//!     "CDOTTHKN4Z5RFSEDOILRIHNQRWGRCZWOMTDBCFK7W3OWRS2V3H7CEZVM",
+//! )?;
+//! let admin = Keypair::from_secret("S...")?;
+//!
+//! client
+//!     .record_payment(
+//!         &admin,
+//!         &RecordPayment {
+//!             invoice_id: "invoisio-abc123".into(),
+//!             payer: "GBXX...".into(),
+//!             asset: Asset::Native,
+//!             amount: 10_000_000,
+//!         },
+//!     )
+//!     .await?;
+//!
+//! let record = client.get_payment("invoisio-abc123").await?;
+//! # Ok(()) }
+//! ```
+
+pub mod backoff;
+pub mod client;
+pub mod deploy;
+pub mod dry_run;
+pub mod error;
+pub mod estimate;
+pub mod events;
+pub mod horizon;
+pub mod network;
+pub mod receipt;
+pub mod rpc;
+pub mod scval;
+pub mod sep10;
+pub mod sep31;
+pub mod sep7;
+pub mod signer;
+pub mod tx;
+pub mod types;
+pub mod verify;
+
+pub use backoff::Backoff;
+pub use client::{EventPage, EventStart, InvoicePaymentClient, Simulation, TxOutcome};
+pub use deploy::{deploy, Deployment};
+pub use dry_run::{DryRun, SimulatedEvent};
+pub use error::{Error, Result};
+pub use estimate::ResourceEstimate;
+pub use events::{ContractEvent, EventEnvelope};
+pub use horizon::{HorizonClient, PaymentOperation};
+pub use network::{Network, NetworkProfile, Profiles};
+pub use receipt::{PaymentReceipt, SignedReceipt};
+pub use rpc::RpcClient;
+pub use sep10::{Jwt, JwtClaims, WebAuth};
+pub use sep31::{AnchorClient, Remittance, Sep31Transaction};
+pub use sep7::PayRequest;
+pub use signer::{AnySigner, HttpKmsSigner, LedgerSigner, Signer};
+pub use tx::Keypair;
+pub use types::{Asset, ContractError, PaymentRecord, RecordPayment};
+pub use verify::{Discrepancy, VerificationReport};
+
+mod test;
@@ -0,0 +1,260 @@
+//! Network profiles: passphrase, endpoints and the contract deployed there.
+//!
+//! Built-in defaults cover endpoints and passphrases. Contract ids are
+//! deployment-specific, so they come from a profiles file (`invoisio.toml`):
+//!
+//! ```toml
+//! [networks.testnet]
+//! contract_id = "C..."
+//!
+//! [networks.mainnet]
+//! contract_id = "C..."
+//! rpc_url = "https://rpc.example.com"   # optional override
+//! ```
+//!
+//! Binding each contract id to its network is what keeps a testnet id out
+//! of mainnet tooling: select the network, never paste the id.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::rpc::RpcClient;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    /// `stellar/quickstart` on localhost.
+    Local,
+    Testnet,
+    Futurenet,
+    Mainnet,
+}
+
+impl Network {
+    pub const ALL: [Network; 4] = [
+        Network::Local,
+        Network::Testnet,
+        Network::Futurenet,
+        Network::Mainnet,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Network::Local => "local",
+            Network::Testnet => "testnet",
+            Network::Futurenet => "futurenet",
+            Network::Mainnet => "mainnet",
+        }
+    }
+
+    /// Built-in profile with no contract id.
+    pub fn defaults(self) -> NetworkProfile {
+        let (passphrase, rpc_url, horizon_url) = match self {
+            Network::Local => (
+                "Standalone Network ; February 2017",
+                "http://localhost:8000/soroban/rpc",
+                "http://localhost:8000",
+            ),
+            Network::Testnet => (
+                "Test SDF Network ; September 2015",
+                "https://soroban-testnet.stellar.org",
+                "https://horizon-testnet.stellar.org",
+            ),
+            Network::Futurenet => (
+                "Test SDF Future Network ; October 2022",
+                "https://rpc-futurenet.stellar.org",
+                "https://horizon-futurenet.stellar.org",
+            ),
+            Network::Mainnet => (
+                "Public Global Stellar Network ; September 2015",
+                "https://mainnet.sorobanrpc.com",
+                "https://horizon.stellar.org",
+            ),
+        };
+        NetworkProfile {
+            network: self,
+            passphrase: passphrase.into(),
+            rpc_url: rpc_url.into(),
+            horizon_url: horizon_url.into(),
+            contract_id: None,
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        Network::ALL
+            .into_iter()
+            .find(|n| n.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown network `{s}` (local, testnet, futurenet, mainnet)"))
+    }
+}
+
+impl core::fmt::Display for Network {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Everything needed to talk to the contract on one network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkProfile {
+    pub network: Network,
+    pub passphrase: String,
+    pub rpc_url: String,
+    pub horizon_url: String,
+    pub contract_id: Option<String>,
+}
+
+impl NetworkProfile {
+    /// Check the RPC endpoint actually serves this profile's network.
+    /// Catches a mainnet profile pointed at a testnet URL and vice versa.
+    pub async fn verify(&self, rpc: &RpcClient) -> Result<()> {
+        let remote = rpc.get_network().await?.passphrase;
+        if remote != self.passphrase {
+            return Err(Error::NetworkMismatch {
+                expected: self.passphrase.clone(),
+                actual: remote,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Per-network overrides read from the profiles file. Unset fields fall
+/// back to [`Network::defaults`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub horizon_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_id: Option<String>,
+}
+
+/// Contents of a profiles file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profiles {
+    #[serde(default)]
+    pub networks: BTreeMap<Network, ProfileOverrides>,
+}
+
+impl Profiles {
+    pub fn parse(toml_str: &str) -> Result<Self> {
+        toml::from_str(toml_str).map_err(|e| Error::Profile(e.to_string()))
+    }
+
+    /// Load `path`; a missing file yields no overrides.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(s) => Self::parse(&s),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::Profile(format!("{}: {e}", path.display()))),
+        }
+    }
+
+    /// Write `contract_id` into `network`'s section of the profiles file at
+    /// `path`, creating the file or section if needed. The file is replaced
+    /// atomically.
+    pub fn save_contract_id(path: &Path, network: Network, contract_id: &str) -> Result<()> {
+        let io = |e: std::io::Error| Error::Profile(format!("{}: {e}", path.display()));
+        let text = match std::fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(io(e)),
+        };
+        let text = Self::with_contract_id(&text, network, contract_id)?;
+        let mut tmp = path.as_os_str().to_os_string();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, text).map_err(io)?;
+        std::fs::rename(&tmp, path).map_err(io)
+    }
+
+    /// `toml_str` with `network`'s `contract_id` set to `contract_id`.
+    /// Edits the text in place, so comments and other settings survive.
+    pub fn with_contract_id(toml_str: &str, network: Network, contract_id: &str) -> Result<String> {
+        let header = format!("[networks.{network}]");
+        let setting = format!("contract_id = \"{contract_id}\"");
+        let mut lines = Vec::new();
+        let mut in_section = false;
+        let mut written = false;
+        for line in toml_str.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                in_section = trimmed == header;
+            } else if in_section && trimmed.split('=').next().map(str::trim) == Some("contract_id")
+            {
+                continue;
+            }
+            lines.push(line.to_string());
+            if in_section && !written && trimmed == header {
+                lines.push(setting.clone());
+                written = true;
+            }
+        }
+        if !written {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(header);
+            lines.push(setting);
+        }
+        let text = lines.join("\n") + "\n";
+
+        // Layouts the line edit does not understand (inline tables, dotted
+        // keys) fail here rather than being written out.
+        let saved = Self::parse(&text)?.resolve(network).contract_id;
+        if saved.as_deref() != Some(contract_id) {
+            return Err(Error::Profile(format!(
+                "could not set {network} contract_id in the profiles file; edit it by hand"
+            )));
+        }
+        Ok(text)
+    }
+
+    /// Built-in defaults for `network` with this file's overrides applied.
+    pub fn resolve(&self, network: Network) -> NetworkProfile {
+        let mut profile = network.defaults();
+        if let Some(o) = self.networks.get(&network) {
+            if let Some(v) = &o.passphrase {
+                profile.passphrase = v.clone();
+            }
+            if let Some(v) = &o.rpc_url {
+                profile.rpc_url = v.clone();
+            }
+            if let Some(v) = &o.horizon_url {
+                profile.horizon_url = v.clone();
+            }
+            profile.contract_id = o.contract_id.clone();
+        }
+        profile
+    }
+
+    /// The network whose profile lists `contract_id`, if any.
+    pub fn network_of(&self, contract_id: &str) -> Option<Network> {
+        self.networks
+            .iter()
+            .find(|(_, o)| o.contract_id.as_deref() == Some(contract_id))
+            .map(|(n, _)| *n)
+    }
+
+    /// Reject `contract_id` if the profiles file assigns it to a network
+    /// other than `network`.
+    pub fn check_contract(&self, network: Network, contract_id: &str) -> Result<()> {
+        match self.network_of(contract_id) {
+            Some(owner) if owner != network => Err(Error::Profile(format!(
+                "contract {contract_id} belongs to {owner}, not {network}"
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
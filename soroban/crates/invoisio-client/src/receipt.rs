@@ -0,0 +1,129 @@
+//! Payment receipts a payer can hand to a third party.
+//!
+//! [`PaymentReceipt`] mirrors what the contract's `get_receipt` returns. Its
+//! canonical bytes are the XDR of that same `ScVal`, so the receipt signed
+//! off-chain is byte-for-byte the one anyone can re-fetch from the contract
+//! it names. Signing goes through [`Signer`] over the SHA-256 of those
+//! bytes; [`SignedReceipt::verify`] needs nothing but the signed receipt.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{Limits, ScVal, WriteXdr};
+
+use crate::error::{Error, Result};
+use crate::signer::{self, from_hex, to_hex, Signer};
+use crate::types::{i128_string, Asset};
+
+/// Decimal places of amounts rendered by [`PaymentReceipt::render`].
+const DECIMALS: u32 = 7;
+
+/// Off-chain mirror of the contract's `PaymentReceipt`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PaymentReceipt {
+    /// `C...` id of the contract that recorded the payment.
+    pub contract: String,
+    pub invoice_id: String,
+    pub payer: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payer_mux_id: Option<u64>,
+    pub asset: Asset,
+    #[serde(with = "i128_string")]
+    pub amount: i128,
+    #[serde(with = "i128_string")]
+    pub refunded: i128,
+    /// Unix seconds the customer paid at.
+    pub paid_at: u64,
+    /// Unix seconds the payment was recorded at.
+    pub recorded_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merchant_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt_number: Option<u64>,
+}
+
+impl PaymentReceipt {
+    /// XDR of the receipt as the contract encodes it.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>> {
+        Ok(ScVal::try_from(self)?.to_xdr(Limits::none())?)
+    }
+
+    /// SHA-256 of [`Self::canonical_bytes`], the value that gets signed.
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        Ok(Sha256::digest(self.canonical_bytes()?).into())
+    }
+
+    /// Plain-text rendering for people, one field per line.
+    pub fn render(&self) -> String {
+        let code = self.asset.code();
+        let mut lines = vec![
+            "Invoisio payment receipt".to_string(),
+            format!("Invoice:     {}", self.invoice_id),
+        ];
+        if let Some(number) = self.receipt_number {
+            lines.push(format!("Receipt no.: {number}"));
+        }
+        if let Some(merchant_id) = &self.merchant_id {
+            lines.push(format!("Merchant:    {merchant_id}"));
+        }
+        lines.push(match self.payer_mux_id {
+            Some(id) => format!("Payer:       {} (mux id {id})", self.payer),
+            None => format!("Payer:       {}", self.payer),
+        });
+        lines.push(match &self.asset {
+            Asset::Native => format!("Amount:      {} {code}", decimal(self.amount)),
+            Asset::Token { issuer, .. } => {
+                format!("Amount:      {} {code} ({issuer})", decimal(self.amount))
+            }
+        });
+        if self.refunded != 0 {
+            lines.push(format!("Refunded:    {} {code}", decimal(self.refunded)));
+        }
+        lines.push(format!("Paid at:     {} (Unix)", self.paid_at));
+        lines.push(format!("Recorded at: {} (Unix)", self.recorded_at));
+        lines.push(format!("Contract:    {}", self.contract));
+        lines.join("\n")
+    }
+
+    /// Sign the receipt's [`hash`](Self::hash) with `signer`.
+    pub async fn sign(self, signer: &impl Signer) -> Result<SignedReceipt> {
+        let signature = signer.sign_hash(self.hash()?).await?;
+        Ok(SignedReceipt {
+            receipt: self,
+            signer: signer.public_key(),
+            signature: to_hex(&signature),
+        })
+    }
+}
+
+/// A [`PaymentReceipt`] with an ed25519 signature over its hash.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SignedReceipt {
+    pub receipt: PaymentReceipt,
+    /// `G...` key that signed.
+    pub signer: String,
+    /// Hex-encoded 64-byte signature.
+    pub signature: String,
+}
+
+impl SignedReceipt {
+    /// Check the signature against `signer`.
+    ///
+    /// This proves who vouched for the receipt, not that it is on chain;
+    /// compare it with `get_receipt` on `receipt.contract` for that.
+    pub fn verify(&self) -> Result<()> {
+        let public_key = stellar_strkey::ed25519::PublicKey::from_string(&self.signer)
+            .map_err(|_| Error::Strkey(self.signer.clone()))?;
+        let signature: [u8; 64] = from_hex(&self.signature)
+            .and_then(|s| s.try_into().ok())
+            .ok_or_else(|| Error::Signer("signature is not 64 hex-encoded bytes".into()))?;
+        signer::verify(public_key.0, &self.receipt.hash()?, &signature)
+    }
+}
+
+/// Format base units with [`DECIMALS`] decimal places, e.g. `12.3400000`.
+fn decimal(amount: i128) -> String {
+    let unit = 10u128.pow(DECIMALS);
+    let sign = if amount < 0 { "-" } else { "" };
+    let (whole, frac) = (amount.unsigned_abs() / unit, amount.unsigned_abs() % unit);
+    format!("{sign}{whole}.{frac:0width$}", width = DECIMALS as usize)
+}
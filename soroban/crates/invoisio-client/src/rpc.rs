@@ -0,0 +1,395 @@
+//! Minimal async JSON-RPC transport for Soroban RPC.
+//!
+//! Only the methods this workspace needs are wrapped. Request and response
+//! shapes follow the
+//! [Soroban RPC reference](https://developers.stellar.org/docs/data/rpc/api-reference/methods).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use stellar_xdr::curr::{
+    AccountId, LedgerEntryData, LedgerKey, LedgerKeyAccount, Limits, PublicKey, ReadXdr,
+    TransactionEnvelope, Uint256, WriteXdr,
+};
+
+use crate::error::{Error, Result};
+
+/// Async Soroban RPC client.
+#[derive(Debug)]
+pub struct RpcClient {
+    http: reqwest::Client,
+    url: String,
+    next_id: AtomicU64,
+}
+
+impl RpcClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_http(reqwest::Client::new(), url)
+    }
+
+    /// Use a preconfigured `reqwest::Client` (timeouts, proxies, ...).
+    pub fn with_http(http: reqwest::Client, url: impl Into<String>) -> Self {
+        Self {
+            http,
+            url: url.into(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Issue a raw JSON-RPC call.
+    pub async fn call<P: Serialize, R: DeserializeOwned>(&self, method: &str, params: P) -> Result<R> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        let response: JsonRpcResponse<R> = self
+            .http
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        match (response.result, response.error) {
+            (_, Some(err)) => Err(Error::Rpc {
+                code: err.code,
+                message: err.message,
+            }),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(Error::Decode(format!("`{method}` returned no result"))),
+        }
+    }
+
+    // Network
+
+    pub async fn get_health(&self) -> Result<GetHealthResponse> {
+        self.call("getHealth", json!({})).await
+    }
+
+    pub async fn get_network(&self) -> Result<GetNetworkResponse> {
+        self.call("getNetwork", json!({})).await
+    }
+
+    pub async fn get_latest_ledger(&self) -> Result<GetLatestLedgerResponse> {
+        self.call("getLatestLedger", json!({})).await
+    }
+
+    /// Current sequence number of a `G...` account (raw 32-byte key).
+    pub async fn get_account_sequence(&self, account: [u8; 32]) -> Result<i64> {
+        self.find_account_sequence(account)
+            .await?
+            .ok_or_else(|| Error::Decode("source account not found".into()))
+    }
+
+    /// Like [`get_account_sequence`](Self::get_account_sequence), but `None`
+    /// if the account does not exist yet.
+    pub async fn find_account_sequence(&self, account: [u8; 32]) -> Result<Option<i64>> {
+        let key = LedgerKey::Account(LedgerKeyAccount {
+            account_id: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(account))),
+        });
+        let response: GetLedgerEntriesResponse = self
+            .call(
+                "getLedgerEntries",
+                json!({ "keys": [key.to_xdr_base64(Limits::none())?] }),
+            )
+            .await?;
+
+        let Some(entry) = response.entries.unwrap_or_default().into_iter().next() else {
+            return Ok(None);
+        };
+        match LedgerEntryData::from_xdr_base64(&entry.xdr, Limits::none())? {
+            LedgerEntryData::Account(acc) => Ok(Some(acc.seq_num.0)),
+            other => Err(Error::Decode(format!(
+                "expected account entry, got {:?}",
+                other.discriminant()
+            ))),
+        }
+    }
+
+    /// Inclusion fees bid in recent ledgers, for pricing under surge.
+    pub async fn get_fee_stats(&self) -> Result<GetFeeStatsResponse> {
+        self.call("getFeeStats", json!({})).await
+    }
+
+    // Transactions
+
+    pub async fn simulate_transaction(
+        &self,
+        envelope: &TransactionEnvelope,
+    ) -> Result<SimulateTransactionResponse> {
+        self.call(
+            "simulateTransaction",
+            json!({ "transaction": envelope.to_xdr_base64(Limits::none())? }),
+        )
+        .await
+    }
+
+    pub async fn send_transaction(
+        &self,
+        envelope: &TransactionEnvelope,
+    ) -> Result<SendTransactionResponse> {
+        self.call(
+            "sendTransaction",
+            json!({ "transaction": envelope.to_xdr_base64(Limits::none())? }),
+        )
+        .await
+    }
+
+    pub async fn get_transaction(&self, hash: &str) -> Result<GetTransactionResponse> {
+        self.call("getTransaction", json!({ "hash": hash })).await
+    }
+
+    // Events
+
+    pub async fn get_events(&self, request: &GetEventsRequest) -> Result<GetEventsResponse> {
+        self.call("getEvents", request).await
+    }
+}
+
+// Wire types
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse<R> {
+    result: Option<R>,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetHealthResponse {
+    pub status: String,
+    #[serde(default)]
+    pub latest_ledger: Option<u32>,
+    #[serde(default)]
+    pub oldest_ledger: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetNetworkResponse {
+    pub passphrase: String,
+    pub protocol_version: u32,
+    #[serde(default)]
+    pub friendbot_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetLatestLedgerResponse {
+    pub id: String,
+    pub sequence: u32,
+    pub protocol_version: u32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetFeeStatsResponse {
+    /// Inclusion fees of Soroban (contract call) transactions.
+    pub soroban_inclusion_fee: FeeDistribution,
+    /// Inclusion fees of classic transactions.
+    pub inclusion_fee: FeeDistribution,
+    pub latest_ledger: u32,
+}
+
+/// Inclusion fees (stroops) bid over the last `ledger_count` ledgers. The
+/// server encodes the fees as decimal strings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeDistribution {
+    #[serde(deserialize_with = "u64_string")]
+    pub max: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub min: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub mode: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub p10: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub p20: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub p30: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub p40: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub p50: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub p60: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub p70: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub p80: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub p90: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub p95: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub p99: u64,
+    #[serde(deserialize_with = "u64_string")]
+    pub transaction_count: u64,
+    pub ledger_count: u32,
+}
+
+fn u64_string<'de, D: serde::Deserializer<'de>>(d: D) -> core::result::Result<u64, D::Error> {
+    let s = String::deserialize(d)?;
+    s.parse().map_err(serde::de::Error::custom)
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetLedgerEntriesResponse {
+    entries: Option<Vec<LedgerEntryResult>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LedgerEntryResult {
+    xdr: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateTransactionResponse {
+    pub latest_ledger: u32,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub transaction_data: Option<String>,
+    /// Stroops, encoded by the server as a decimal string.
+    #[serde(default)]
+    pub min_resource_fee: Option<String>,
+    #[serde(default)]
+    pub results: Option<Vec<SimulateHostFunctionResult>>,
+    #[serde(default)]
+    pub events: Option<Vec<String>>,
+    #[serde(default)]
+    pub restore_preamble: Option<RestorePreamble>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateHostFunctionResult {
+    /// Base64 `SorobanAuthorizationEntry` values recorded during simulation.
+    #[serde(default)]
+    pub auth: Vec<String>,
+    /// Base64 `ScVal` return value.
+    pub xdr: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestorePreamble {
+    pub transaction_data: String,
+    pub min_resource_fee: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendTransactionResponse {
+    /// `PENDING`, `DUPLICATE`, `TRY_AGAIN_LATER` or `ERROR`.
+    pub status: String,
+    pub hash: String,
+    pub latest_ledger: u32,
+    #[serde(default)]
+    pub error_result_xdr: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTransactionResponse {
+    /// `SUCCESS`, `FAILED` or `NOT_FOUND`.
+    pub status: String,
+    pub latest_ledger: u32,
+    #[serde(default)]
+    pub ledger: Option<u32>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub envelope_xdr: Option<String>,
+    #[serde(default)]
+    pub result_xdr: Option<String>,
+    #[serde(default)]
+    pub result_meta_xdr: Option<String>,
+}
+
+/// `getEvents` request. Set either `start_ledger` or `pagination.cursor`.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEventsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_ledger: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_ledger: Option<u32>,
+    pub filters: Vec<EventFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventFilter {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub contract_ids: Vec<String>,
+    /// Each inner vec is one topic pattern of base64 `ScVal`s or `"*"`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pagination {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEventsResponse {
+    #[serde(default)]
+    pub events: Vec<RpcEvent>,
+    pub latest_ledger: u32,
+    /// Oldest ledger still retained by the server (newer RPC versions only).
+    #[serde(default)]
+    pub oldest_ledger: Option<u32>,
+    /// Cursor to resume from after the last returned event.
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub ledger: u32,
+    pub ledger_closed_at: String,
+    pub contract_id: String,
+    pub id: String,
+    /// Base64 `ScVal` topics.
+    pub topic: Vec<String>,
+    /// Base64 `ScVal` event data.
+    pub value: String,
+    #[serde(default)]
+    pub in_successful_contract_call: Option<bool>,
+    #[serde(default)]
+    pub tx_hash: Option<String>,
+}
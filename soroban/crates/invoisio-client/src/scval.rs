@@ -0,0 +1,363 @@
+//! Conversions between Rust values and `ScVal`, the contract's wire format.
+//!
+//! `#[contracttype]` encodes:
+//! - structs as `ScVal::Map` keyed by `Symbol(field_name)`, sorted by key;
+//! - unit enum variants as `ScVal::Vec([Symbol(variant)])`;
+//! - tuple enum variants as `ScVal::Vec([Symbol(variant), field0, field1, ...])`.
+
+use std::collections::BTreeMap;
+
+use stellar_xdr::curr::{
+    AccountId, ContractId, Hash, Int128Parts, MuxedEd25519Account, PublicKey, ScAddress, ScMap,
+    ScMapEntry, ScString, ScSymbol, ScVal, ScVec, Uint256,
+};
+
+use crate::error::{Error, Result};
+use crate::receipt::PaymentReceipt;
+use crate::sep31::Remittance;
+use crate::signer::to_hex;
+use crate::types::{Asset, PaymentRecord};
+
+// Encoding
+
+pub fn string(s: &str) -> Result<ScVal> {
+    Ok(ScVal::String(ScString(s.try_into()?)))
+}
+
+pub fn symbol(s: &str) -> Result<ScVal> {
+    Ok(ScVal::Symbol(ScSymbol(s.try_into()?)))
+}
+
+pub fn i128(v: i128) -> ScVal {
+    ScVal::I128(Int128Parts {
+        hi: (v >> 64) as i64,
+        lo: v as u64,
+    })
+}
+
+/// Encode a `G...` account, `M...` muxed account or `C...` contract strkey as
+/// an `ScVal::Address`.
+pub fn address(strkey: &str) -> Result<ScVal> {
+    Ok(ScVal::Address(sc_address(strkey)?))
+}
+
+pub fn sc_address(strkey: &str) -> Result<ScAddress> {
+    match stellar_strkey::Strkey::from_string(strkey) {
+        Ok(stellar_strkey::Strkey::PublicKeyEd25519(pk)) => Ok(ScAddress::Account(AccountId(
+            PublicKey::PublicKeyTypeEd25519(Uint256(pk.0)),
+        ))),
+        Ok(stellar_strkey::Strkey::MuxedAccountEd25519(m)) => {
+            Ok(ScAddress::MuxedAccount(MuxedEd25519Account {
+                id: m.id,
+                ed25519: Uint256(m.ed25519),
+            }))
+        }
+        Ok(stellar_strkey::Strkey::Contract(c)) => Ok(ScAddress::Contract(ContractId(Hash(c.0)))),
+        _ => Err(Error::Strkey(strkey.to_string())),
+    }
+}
+
+pub fn vec(items: Vec<ScVal>) -> Result<ScVal> {
+    Ok(ScVal::Vec(Some(ScVec(items.try_into()?))))
+}
+
+/// Build an `ScVal::Map` from `(key, value)` pairs, sorting by key the way
+/// the host requires.
+pub fn map(mut entries: Vec<(&str, ScVal)>) -> Result<ScVal> {
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    let entries = entries
+        .into_iter()
+        .map(|(k, v)| Ok(ScMapEntry { key: symbol(k)?, val: v }))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ScVal::Map(Some(ScMap(entries.try_into()?))))
+}
+
+// Decoding
+
+pub fn to_string(v: &ScVal) -> Result<String> {
+    match v {
+        ScVal::String(ScString(s)) => Ok(s.to_utf8_string()?),
+        other => Err(unexpected("String", other)),
+    }
+}
+
+pub fn to_symbol(v: &ScVal) -> Result<String> {
+    match v {
+        ScVal::Symbol(ScSymbol(s)) => Ok(s.to_utf8_string()?),
+        other => Err(unexpected("Symbol", other)),
+    }
+}
+
+pub fn to_i128(v: &ScVal) -> Result<i128> {
+    match v {
+        ScVal::I128(Int128Parts { hi, lo }) => Ok(((*hi as i128) << 64) | *lo as i128),
+        other => Err(unexpected("I128", other)),
+    }
+}
+
+pub fn to_u64(v: &ScVal) -> Result<u64> {
+    match v {
+        ScVal::U64(n) => Ok(*n),
+        other => Err(unexpected("U64", other)),
+    }
+}
+
+pub fn to_u32(v: &ScVal) -> Result<u32> {
+    match v {
+        ScVal::U32(n) => Ok(*n),
+        other => Err(unexpected("U32", other)),
+    }
+}
+
+pub fn to_bool(v: &ScVal) -> Result<bool> {
+    match v {
+        ScVal::Bool(b) => Ok(*b),
+        other => Err(unexpected("Bool", other)),
+    }
+}
+
+/// Decode an `ScVal::Address` into its strkey form.
+pub fn to_address(v: &ScVal) -> Result<String> {
+    match v {
+        ScVal::Address(addr) => address_to_strkey(addr),
+        other => Err(unexpected("Address", other)),
+    }
+}
+
+pub fn address_to_strkey(addr: &ScAddress) -> Result<String> {
+    match addr {
+        ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(pk)))) => {
+            Ok(stellar_strkey::ed25519::PublicKey(*pk).to_string())
+        }
+        ScAddress::MuxedAccount(MuxedEd25519Account { id, ed25519: Uint256(pk) }) => {
+            Ok(stellar_strkey::ed25519::MuxedAccount { ed25519: *pk, id: *id }.to_string())
+        }
+        ScAddress::Contract(ContractId(Hash(id))) => Ok(stellar_strkey::Contract(*id).to_string()),
+        other => Err(Error::Decode(format!("unsupported address kind: {other:?}"))),
+    }
+}
+
+/// Decode an optional value, where `None` is encoded as `ScVal::Void`.
+pub fn to_option<'a, T>(
+    v: &'a ScVal,
+    decode: impl FnOnce(&'a ScVal) -> Result<T>,
+) -> Result<Option<T>> {
+    match v {
+        ScVal::Void => Ok(None),
+        v => decode(v).map(Some),
+    }
+}
+
+pub fn to_vec(v: &ScVal) -> Result<&[ScVal]> {
+    match v {
+        ScVal::Vec(Some(ScVec(items))) => Ok(items.as_slice()),
+        other => Err(unexpected("Vec", other)),
+    }
+}
+
+/// Decode the `Map<String, Option<PaymentRecord>>` returned by
+/// `find_payments`.
+pub fn to_payment_lookups(v: &ScVal) -> Result<BTreeMap<String, Option<PaymentRecord>>> {
+    let ScVal::Map(Some(ScMap(entries))) = v else {
+        return Err(unexpected("Map", v));
+    };
+    entries
+        .iter()
+        .map(|e| Ok((to_string(&e.key)?, to_option(&e.val, PaymentRecord::try_from)?)))
+        .collect()
+}
+
+/// Decode the `(Vec<PaymentRecord>, Option<u32>)` page returned by
+/// `export_page`. Tuples are encoded as an `ScVal::Vec` of their fields.
+pub fn to_export_page(v: &ScVal) -> Result<(Vec<PaymentRecord>, Option<u32>)> {
+    let [records, next] = to_vec(v)? else {
+        return Err(unexpected("Vec of 2", v));
+    };
+    let records = to_vec(records)?
+        .iter()
+        .map(PaymentRecord::try_from)
+        .collect::<Result<Vec<_>>>()?;
+    Ok((records, to_option(next, to_u32)?))
+}
+
+/// Look up `key` in an `ScVal::Map` keyed by symbols.
+pub fn map_get<'a>(v: &'a ScVal, key: &str) -> Result<&'a ScVal> {
+    let ScVal::Map(Some(ScMap(entries))) = v else {
+        return Err(unexpected("Map", v));
+    };
+    entries
+        .as_slice()
+        .iter()
+        .find(|e| matches!(&e.key, ScVal::Symbol(ScSymbol(s)) if s.as_slice() == key.as_bytes()))
+        .map(|e| &e.val)
+        .ok_or_else(|| Error::Decode(format!("missing map key `{key}`")))
+}
+
+fn unexpected(expected: &str, got: &ScVal) -> Error {
+    Error::Decode(format!("expected {expected}, got {got:?}"))
+}
+
+// Display
+
+/// Compact human-readable rendering, for diagnostics and logs. Not meant to
+/// be parsed back.
+pub fn display(v: &ScVal) -> String {
+    let join = |items: Vec<String>| items.join(", ");
+    match v {
+        ScVal::Void => "()".into(),
+        ScVal::Bool(b) => b.to_string(),
+        ScVal::U32(n) => n.to_string(),
+        ScVal::I32(n) => n.to_string(),
+        ScVal::U64(n) => n.to_string(),
+        ScVal::I64(n) => n.to_string(),
+        ScVal::I128(_) => to_i128(v).map_or_else(|_| format!("{v:?}"), |n| n.to_string()),
+        ScVal::Symbol(ScSymbol(s)) => String::from_utf8_lossy(s.as_slice()).into_owned(),
+        ScVal::String(ScString(s)) => format!("{:?}", String::from_utf8_lossy(s.as_slice())),
+        ScVal::Bytes(b) => format!("0x{}", to_hex(b.0.as_slice())),
+        ScVal::Address(a) => address_to_strkey(a).unwrap_or_else(|_| format!("{a:?}")),
+        ScVal::Vec(Some(ScVec(items))) => {
+            format!("[{}]", join(items.iter().map(display).collect()))
+        }
+        ScVal::Map(Some(ScMap(entries))) => {
+            let entries = entries
+                .iter()
+                .map(|e| format!("{}: {}", display(&e.key), display(&e.val)))
+                .collect();
+            format!("{{{}}}", join(entries))
+        }
+        other => format!("{other:?}"),
+    }
+}
+
+// Contract types
+
+impl TryFrom<&ScVal> for Asset {
+    type Error = Error;
+
+    fn try_from(v: &ScVal) -> Result<Self> {
+        let items = to_vec(v)?;
+        let tag = items
+            .first()
+            .ok_or_else(|| Error::Decode("empty Asset vec".into()))
+            .and_then(to_symbol)?;
+        match (tag.as_str(), items.len()) {
+            ("Native", 1) => Ok(Asset::Native),
+            ("Token", 3) => Ok(Asset::Token {
+                code: to_string(&items[1])?,
+                issuer: to_string(&items[2])?,
+            }),
+            _ => Err(Error::Decode(format!("unknown Asset variant `{tag}`"))),
+        }
+    }
+}
+
+impl TryFrom<&Asset> for ScVal {
+    type Error = Error;
+
+    fn try_from(asset: &Asset) -> Result<Self> {
+        match asset {
+            Asset::Native => vec(vec![symbol("Native")?]),
+            Asset::Token { code, issuer } => {
+                vec(vec![symbol("Token")?, string(code)?, string(issuer)?])
+            }
+        }
+    }
+}
+
+impl TryFrom<&ScVal> for PaymentRecord {
+    type Error = Error;
+
+    fn try_from(v: &ScVal) -> Result<Self> {
+        Ok(PaymentRecord {
+            invoice_id: to_string(map_get(v, "invoice_id")?)?,
+            payer: to_address(map_get(v, "payer")?)?,
+            // Absent in records from deployments predating muxed payers.
+            payer_mux_id: match map_get(v, "payer_mux_id") {
+                Ok(ScVal::Void) | Err(_) => None,
+                Ok(id) => Some(to_u64(id)?),
+            },
+            asset: Asset::try_from(map_get(v, "asset")?)?,
+            amount: to_i128(map_get(v, "amount")?)?,
+            timestamp: to_u64(map_get(v, "timestamp")?)?,
+        })
+    }
+}
+
+impl TryFrom<&PaymentRecord> for ScVal {
+    type Error = Error;
+
+    fn try_from(r: &PaymentRecord) -> Result<Self> {
+        map(vec![
+            ("invoice_id", string(&r.invoice_id)?),
+            ("payer", address(&r.payer)?),
+            ("payer_mux_id", r.payer_mux_id.map_or(ScVal::Void, ScVal::U64)),
+            ("asset", ScVal::try_from(&r.asset)?),
+            ("amount", i128(r.amount)),
+            ("timestamp", ScVal::U64(r.timestamp)),
+        ])
+    }
+}
+
+impl TryFrom<&ScVal> for PaymentReceipt {
+    type Error = Error;
+
+    fn try_from(v: &ScVal) -> Result<Self> {
+        Ok(PaymentReceipt {
+            contract: to_address(map_get(v, "contract")?)?,
+            invoice_id: to_string(map_get(v, "invoice_id")?)?,
+            payer: to_address(map_get(v, "payer")?)?,
+            payer_mux_id: to_option(map_get(v, "payer_mux_id")?, to_u64)?,
+            asset: Asset::try_from(map_get(v, "asset")?)?,
+            amount: to_i128(map_get(v, "amount")?)?,
+            refunded: to_i128(map_get(v, "refunded")?)?,
+            paid_at: to_u64(map_get(v, "paid_at")?)?,
+            recorded_at: to_u64(map_get(v, "recorded_at")?)?,
+            merchant_id: to_option(map_get(v, "merchant_id")?, to_string)?,
+            receipt_number: to_option(map_get(v, "receipt_number")?, to_u64)?,
+        })
+    }
+}
+
+impl TryFrom<&PaymentReceipt> for ScVal {
+    type Error = Error;
+
+    fn try_from(r: &PaymentReceipt) -> Result<Self> {
+        map(vec![
+            ("contract", address(&r.contract)?),
+            ("invoice_id", string(&r.invoice_id)?),
+            ("payer", address(&r.payer)?),
+            ("payer_mux_id", r.payer_mux_id.map_or(ScVal::Void, ScVal::U64)),
+            ("asset", ScVal::try_from(&r.asset)?),
+            ("amount", i128(r.amount)),
+            ("refunded", i128(r.refunded)),
+            ("paid_at", ScVal::U64(r.paid_at)),
+            ("recorded_at", ScVal::U64(r.recorded_at)),
+            ("merchant_id", r.merchant_id.as_deref().map_or(Ok(ScVal::Void), string)?),
+            ("receipt_number", r.receipt_number.map_or(ScVal::Void, ScVal::U64)),
+        ])
+    }
+}
+
+impl TryFrom<&ScVal> for Remittance {
+    type Error = Error;
+
+    fn try_from(v: &ScVal) -> Result<Self> {
+        Ok(Remittance {
+            transaction_id: to_string(map_get(v, "transaction_id")?)?,
+            sending_anchor: to_string(map_get(v, "sending_anchor")?)?,
+            receiving_anchor: to_string(map_get(v, "receiving_anchor")?)?,
+        })
+    }
+}
+
+impl TryFrom<&Remittance> for ScVal {
+    type Error = Error;
+
+    fn try_from(r: &Remittance) -> Result<Self> {
+        map(vec![
+            ("transaction_id", string(&r.transaction_id)?),
+            ("sending_anchor", string(&r.sending_anchor)?),
+            ("receiving_anchor", string(&r.receiving_anchor)?),
+        ])
+    }
+}
@@ -0,0 +1,348 @@
+//! SEP-10 web authentication: prove control of a Stellar account to an
+//! anchor and get a JWT for its other SEP APIs (e.g.
+//! [`AnchorClient`](crate::AnchorClient)).
+//!
+//! The anchor sends a challenge transaction signed by its `SIGNING_KEY`;
+//! [`WebAuth::authenticate`] checks it was built as SEP-10 requires before
+//! signing it through [`Signer`], so a hostile server cannot get a real
+//! transaction signed. The returned [`Jwt`] is decoded but not verified:
+//! only the anchor holds the key it is signed with.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use stellar_xdr::curr::{
+    Limits, MuxedAccount, OperationBody, Preconditions, ReadXdr, TransactionEnvelope, Uint256,
+    WriteXdr,
+};
+
+use crate::error::{Error, Result};
+use crate::sep31::{fetch_stellar_toml, toml_value};
+use crate::signer::{self, Signer};
+use crate::tx;
+
+/// Seconds of clock skew tolerated on the challenge's time bounds.
+pub const CLOCK_SKEW: u64 = 300;
+
+/// An anchor's SEP-10 endpoint.
+#[derive(Clone, Debug)]
+pub struct WebAuth {
+    http: reqwest::Client,
+    endpoint: String,
+    home_domain: String,
+    /// The anchor's `SIGNING_KEY`, which must sign every challenge.
+    server_key: [u8; 32],
+    network_passphrase: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChallengeResponse {
+    transaction: String,
+    #[serde(default)]
+    network_passphrase: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    token: String,
+}
+
+impl WebAuth {
+    /// Read `WEB_AUTH_ENDPOINT` and `SIGNING_KEY` from the `stellar.toml` of
+    /// `home_domain`.
+    pub async fn discover(home_domain: &str, network_passphrase: &str) -> Result<Self> {
+        let http = reqwest::Client::new();
+        let stellar_toml = fetch_stellar_toml(&http, home_domain).await?;
+        let endpoint = toml_value(&stellar_toml, "WEB_AUTH_ENDPOINT")?;
+        let server_key = toml_value(&stellar_toml, "SIGNING_KEY")?;
+        Self::with_endpoint(
+            http,
+            home_domain,
+            &endpoint,
+            &server_key,
+            network_passphrase,
+        )
+    }
+
+    /// Use a known endpoint and `G...` signing key with a preconfigured
+    /// `reqwest::Client`.
+    pub fn with_endpoint(
+        http: reqwest::Client,
+        home_domain: &str,
+        endpoint: &str,
+        server_key: &str,
+        network_passphrase: &str,
+    ) -> Result<Self> {
+        Ok(Self {
+            http,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            home_domain: home_domain.to_string(),
+            server_key: tx::account_id(server_key)?,
+            network_passphrase: network_passphrase.to_string(),
+        })
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Run the whole flow for `signer`'s account: fetch and check the
+    /// challenge, sign it, and exchange it for a token.
+    pub async fn authenticate(&self, signer: &impl Signer) -> Result<Jwt> {
+        let challenge = self.challenge(&signer.public_key()).await?;
+        let signed = self.sign_challenge(challenge, signer).await?;
+        self.token(&signed).await
+    }
+
+    /// Fetch a challenge for `account` (`G...`) and check it with
+    /// [`Self::validate_challenge`].
+    pub async fn challenge(&self, account: &str) -> Result<TransactionEnvelope> {
+        let response: ChallengeResponse = self
+            .http
+            .get(&self.endpoint)
+            .query(&[("account", account), ("home_domain", &self.home_domain)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(passphrase) = response.network_passphrase {
+            if passphrase != self.network_passphrase {
+                return Err(Error::NetworkMismatch {
+                    expected: self.network_passphrase.clone(),
+                    actual: passphrase,
+                });
+            }
+        }
+        let envelope = TransactionEnvelope::from_xdr_base64(&response.transaction, Limits::none())?;
+        self.validate_challenge(&envelope, tx::account_id(account)?, unix_now())?;
+        Ok(envelope)
+    }
+
+    /// Check that `envelope` is a SEP-10 challenge from this anchor for
+    /// `account` at Unix time `now`:
+    ///
+    /// - source is the anchor's signing key and the sequence number is 0,
+    ///   so it can never be submitted;
+    /// - `now` is within its time bounds, give or take [`CLOCK_SKEW`];
+    /// - every operation is `manage_data`; the first is sourced by
+    ///   `account`, named `<home_domain> auth` and carries a 64-byte nonce;
+    ///   the others are sourced by the anchor, and `web_auth_domain` names
+    ///   the endpoint's host;
+    /// - the anchor has signed it.
+    pub fn validate_challenge(
+        &self,
+        envelope: &TransactionEnvelope,
+        account: [u8; 32],
+        now: u64,
+    ) -> Result<()> {
+        let TransactionEnvelope::Tx(v1) = envelope else {
+            return Err(invalid("not a v1 transaction envelope"));
+        };
+        let tx = &v1.tx;
+        if ed25519_of(&tx.source_account) != Some(self.server_key) {
+            return Err(invalid("source is not the anchor's signing key"));
+        }
+        if tx.seq_num.0 != 0 {
+            return Err(invalid("sequence number is not 0"));
+        }
+        let bounds = match &tx.cond {
+            Preconditions::Time(bounds) => Some(bounds),
+            Preconditions::V2(v2) => v2.time_bounds.as_ref(),
+            Preconditions::None => None,
+        }
+        .ok_or_else(|| invalid("no time bounds"))?;
+        if now + CLOCK_SKEW < bounds.min_time.0
+            || (bounds.max_time.0 != 0 && now > bounds.max_time.0 + CLOCK_SKEW)
+        {
+            return Err(invalid("outside its time bounds"));
+        }
+
+        if tx.operations.is_empty() {
+            return Err(invalid("no operations"));
+        }
+        for (i, op) in tx.operations.iter().enumerate() {
+            let OperationBody::ManageData(data) = &op.body else {
+                return Err(invalid("operation other than manage_data"));
+            };
+            let name = String::from_utf8_lossy(data.data_name.as_slice());
+            let value = data.data_value.as_ref().map(|v| v.0.as_slice());
+            let source = op.source_account.as_ref().and_then(ed25519_of);
+            if i == 0 {
+                if source != Some(account) {
+                    return Err(invalid("first operation is not sourced by the account"));
+                }
+                if name != format!("{} auth", self.home_domain) {
+                    return Err(invalid("first operation is not `<home_domain> auth`"));
+                }
+                if value.map_or(0, <[u8]>::len) != 64 {
+                    return Err(invalid("nonce is not 64 bytes"));
+                }
+            } else if name == "client_domain" {
+                // Sourced by the client domain's account, checked by the
+                // anchor.
+            } else if source != Some(self.server_key) {
+                return Err(invalid("operation not sourced by the anchor"));
+            } else if name == "web_auth_domain" && value != Some(self.endpoint_host().as_bytes()) {
+                return Err(invalid("web_auth_domain is not the endpoint's host"));
+            }
+        }
+
+        let hash = tx::transaction_hash(tx, &self.network_passphrase)?;
+        let signed = v1.signatures.iter().any(|sig| {
+            <[u8; 64]>::try_from(sig.signature.0.as_slice())
+                .is_ok_and(|s| signer::verify(self.server_key, &hash, &s).is_ok())
+        });
+        if !signed {
+            return Err(invalid("not signed by the anchor's signing key"));
+        }
+        Ok(())
+    }
+
+    /// Add `signer`'s signature to a challenge checked by
+    /// [`Self::validate_challenge`].
+    pub async fn sign_challenge(
+        &self,
+        envelope: TransactionEnvelope,
+        signer: &impl Signer,
+    ) -> Result<TransactionEnvelope> {
+        let TransactionEnvelope::Tx(mut v1) = envelope else {
+            return Err(invalid("not a v1 transaction envelope"));
+        };
+        let hash = tx::transaction_hash(&v1.tx, &self.network_passphrase)?;
+        let signature = tx::decorated(signer.public_key_bytes(), signer.sign_hash(hash).await?)?;
+        let mut signatures = v1.signatures.to_vec();
+        signatures.push(signature);
+        v1.signatures = signatures.try_into()?;
+        Ok(TransactionEnvelope::Tx(v1))
+    }
+
+    /// Exchange a signed challenge for a token.
+    pub async fn token(&self, signed: &TransactionEnvelope) -> Result<Jwt> {
+        let response: TokenResponse = self
+            .http
+            .post(&self.endpoint)
+            .json(&serde_json::json!({ "transaction": signed.to_xdr_base64(Limits::none())? }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Jwt::parse(&response.token)
+    }
+
+    /// Host of the endpoint, e.g. `auth.anchor.example` for
+    /// `https://auth.anchor.example/auth`.
+    fn endpoint_host(&self) -> &str {
+        let rest = self
+            .endpoint
+            .split_once("://")
+            .map_or(self.endpoint.as_str(), |(_, rest)| rest);
+        rest.split(['/', '?']).next().unwrap_or(rest)
+    }
+}
+
+/// A SEP-10 token and the claims it carries.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Jwt {
+    /// The encoded token, sent as `Authorization: Bearer <token>`.
+    pub token: String,
+    pub claims: JwtClaims,
+}
+
+/// Claims SEP-10 puts in the token.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JwtClaims {
+    /// The endpoint that issued the token.
+    pub iss: String,
+    /// The authenticated account, `G...` or `G...:<memo>`.
+    pub sub: String,
+    /// Issued at, Unix seconds.
+    pub iat: u64,
+    /// Expires at, Unix seconds.
+    pub exp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_domain: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home_domain: Option<String>,
+}
+
+impl Jwt {
+    /// Decode the claims of `token` without verifying its signature.
+    pub fn parse(token: &str) -> Result<Self> {
+        let payload = token
+            .split('.')
+            .nth(1)
+            .and_then(base64url_decode)
+            .ok_or_else(|| Error::Anchor("malformed JWT".into()))?;
+        let claims = serde_json::from_slice(&payload)
+            .map_err(|e| Error::Anchor(format!("JWT claims: {e}")))?;
+        Ok(Self {
+            token: token.to_string(),
+            claims,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.token
+    }
+
+    /// Whether the token expires within `margin` seconds of Unix time `now`.
+    pub fn expires_within(&self, now: u64, margin: u64) -> bool {
+        now + margin >= self.claims.exp
+    }
+
+    /// Whether the token has expired at Unix time `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_within(now, 0)
+    }
+}
+
+impl core::fmt::Debug for Jwt {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Jwt")
+            .field("token", &"<redacted>")
+            .field("claims", &self.claims)
+            .finish()
+    }
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::Anchor(format!("invalid SEP-10 challenge: {reason}"))
+}
+
+fn ed25519_of(account: &MuxedAccount) -> Option<[u8; 32]> {
+    match account {
+        MuxedAccount::Ed25519(Uint256(key)) => Some(*key),
+        MuxedAccount::MuxedEd25519(_) => None,
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Decode unpadded URL-safe base64, as used by JWTs.
+pub(crate) fn base64url_decode(s: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let (mut buf, mut bits) = (0u32, 0);
+    for c in s.trim_end_matches('=').bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'-' => 62,
+            b'_' => 63,
+            _ => return None,
+        };
+        buf = buf << 6 | u32::from(v);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+        }
+    }
+    Some(out)
+}
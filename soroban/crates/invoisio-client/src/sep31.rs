@@ -0,0 +1,174 @@
+//! SEP-31 cross-border payments.
+//!
+//! [`Remittance`] mirrors the identifiers the contract's
+//! `record_remittance_payment` stores beside a payment. [`AnchorClient`]
+//! reads them from the receiving anchor: its SEP-31 API is the
+//! `DIRECT_PAYMENT_SERVER` of its `stellar.toml`, and transactions are read
+//! with a SEP-10 token for the sending anchor's account.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Off-chain mirror of the contract's `Remittance`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Remittance {
+    /// Transaction id assigned by the receiving anchor.
+    pub transaction_id: String,
+    /// Home domain of the anchor the sender paid in.
+    pub sending_anchor: String,
+    /// Home domain of the anchor that delivered the payment on Stellar.
+    pub receiving_anchor: String,
+}
+
+/// A SEP-31 transaction as the receiving anchor reports it. Only the fields
+/// reconciliation needs are kept.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct Sep31Transaction {
+    pub id: String,
+    /// e.g. `pending_sender`, `pending_receiver`, `completed`, `error`.
+    pub status: String,
+    /// Decimal string amount the sending anchor paid, in `amount_in_asset`.
+    #[serde(default)]
+    pub amount_in: Option<String>,
+    /// SEP-38 asset id, e.g. `stellar:USDC:GA5Z...`.
+    #[serde(default)]
+    pub amount_in_asset: Option<String>,
+    /// Decimal string amount delivered to the receiver.
+    #[serde(default)]
+    pub amount_out: Option<String>,
+    #[serde(default)]
+    pub amount_out_asset: Option<String>,
+    /// Account the sending anchor pays on Stellar, with `stellar_memo`.
+    #[serde(default)]
+    pub stellar_account_id: Option<String>,
+    #[serde(default)]
+    pub stellar_memo_type: Option<String>,
+    #[serde(default)]
+    pub stellar_memo: Option<String>,
+    /// Hash of the Stellar transaction that paid the anchor, once received.
+    #[serde(default)]
+    pub stellar_transaction_id: Option<String>,
+    #[serde(default)]
+    pub completed_at: Option<String>,
+}
+
+impl Sep31Transaction {
+    /// Whether the anchor has delivered the payment.
+    pub fn is_completed(&self) -> bool {
+        self.status == "completed"
+    }
+
+    /// The identifiers to record with the payment.
+    pub fn remittance(&self, sending_anchor: &str, receiving_anchor: &str) -> Remittance {
+        Remittance {
+            transaction_id: self.id.clone(),
+            sending_anchor: sending_anchor.to_string(),
+            receiving_anchor: receiving_anchor.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TransactionResponse {
+    transaction: Sep31Transaction,
+}
+
+/// Reads SEP-31 transactions from one receiving anchor.
+#[derive(Clone, Debug)]
+pub struct AnchorClient {
+    http: reqwest::Client,
+    home_domain: String,
+    server: String,
+}
+
+impl AnchorClient {
+    /// Find the SEP-31 server of the anchor at `home_domain` (e.g.
+    /// `anchor.example.com`) from its `stellar.toml`.
+    pub async fn discover(home_domain: &str) -> Result<Self> {
+        let http = reqwest::Client::new();
+        let stellar_toml = fetch_stellar_toml(&http, home_domain).await?;
+        let server = toml_value(&stellar_toml, "DIRECT_PAYMENT_SERVER")?;
+        Ok(Self::with_server(http, home_domain, server))
+    }
+
+    /// Use a known SEP-31 server and a preconfigured `reqwest::Client`.
+    pub fn with_server(
+        http: reqwest::Client,
+        home_domain: impl Into<String>,
+        server: impl Into<String>,
+    ) -> Self {
+        Self {
+            http,
+            home_domain: home_domain.into(),
+            server: server.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn home_domain(&self) -> &str {
+        &self.home_domain
+    }
+
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    /// Fetch transaction `id`, authenticated with the SEP-10 `token` of the
+    /// sending anchor.
+    pub async fn transaction(&self, token: &str, id: &str) -> Result<Sep31Transaction> {
+        let response: TransactionResponse = self
+            .http
+            .get(format!("{}/transactions/{id}", self.server))
+            .bearer_auth(token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(response.transaction)
+    }
+
+    /// Fetch transaction `id` and build the [`Remittance`] to record with
+    /// it, this anchor being the receiving one. Fails with
+    /// [`Error::Anchor`] until the anchor reports it `completed`.
+    pub async fn remittance(
+        &self,
+        token: &str,
+        id: &str,
+        sending_anchor: &str,
+    ) -> Result<Remittance> {
+        let transaction = self.transaction(token, id).await?;
+        if !transaction.is_completed() {
+            return Err(Error::Anchor(format!(
+                "transaction {id} is {}, not completed",
+                transaction.status
+            )));
+        }
+        Ok(transaction.remittance(sending_anchor, &self.home_domain))
+    }
+}
+
+/// Fetch `https://<home_domain>/.well-known/stellar.toml`.
+pub(crate) async fn fetch_stellar_toml(
+    http: &reqwest::Client,
+    home_domain: &str,
+) -> Result<String> {
+    Ok(http
+        .get(format!("https://{home_domain}/.well-known/stellar.toml"))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?)
+}
+
+/// Read the string under `key` in a `stellar.toml`.
+pub(crate) fn toml_value(stellar_toml: &str, key: &str) -> Result<String> {
+    let table: toml::Table =
+        toml::from_str(stellar_toml).map_err(|e| Error::Anchor(format!("stellar.toml: {e}")))?;
+    table
+        .get(key)
+        .and_then(toml::Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| Error::Anchor(format!("stellar.toml has no {key}")))
+}
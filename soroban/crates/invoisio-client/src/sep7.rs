@@ -0,0 +1,172 @@
+//! SEP-7 `web+stellar:pay` URIs for invoices.
+//!
+//! A [`PayRequest`] built with [`PayRequest::for_invoice`] always carries the
+//! invoice's `invoisio-<id>` text memo, so a wallet that opens the URI (or
+//! scans its QR code) pays with the memo the backend matches on. With the
+//! `qr` feature, [`PayRequest::qr_svg`] renders the URI as an SVG QR code.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::network::Network;
+use crate::types::Asset;
+
+/// Prefix of the text memo of every Invoisio payment.
+pub const MEMO_PREFIX: &str = "invoisio-";
+
+/// Longest text memo Stellar accepts, in bytes.
+pub const MAX_MEMO_LEN: usize = 28;
+
+/// Longest `msg` SEP-7 allows, in characters.
+pub const MAX_MSG_LEN: usize = 300;
+
+/// Decimal places of Stellar amounts.
+const DECIMALS: u32 = 7;
+
+/// A SEP-7 `pay` request.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PayRequest {
+    /// `G...` or `M...` account to pay.
+    pub destination: String,
+    pub asset: Asset,
+    /// In the asset's smallest unit; `None` lets the payer choose.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<i128>,
+    /// Text memo, `invoisio-<id>`.
+    pub memo: String,
+    /// Shown to the payer by the wallet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub msg: Option<String>,
+    /// Only set off the public network, as SEP-7 asks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_passphrase: Option<String>,
+}
+
+impl PayRequest {
+    /// Request payment of `invoice_id` to `destination`. The memo is
+    /// `invoice_id` itself if it already starts with [`MEMO_PREFIX`], else
+    /// the prefixed id.
+    ///
+    /// Fails with [`Error::PayRequest`] if the memo exceeds
+    /// [`MAX_MEMO_LEN`] bytes or `amount` is not positive, and with
+    /// [`Error::Strkey`] if `destination` is not an account.
+    pub fn for_invoice(
+        destination: &str,
+        invoice_id: &str,
+        asset: Asset,
+        amount: Option<i128>,
+    ) -> Result<Self> {
+        if stellar_strkey::ed25519::PublicKey::from_string(destination).is_err()
+            && stellar_strkey::ed25519::MuxedAccount::from_string(destination).is_err()
+        {
+            return Err(Error::Strkey(destination.to_string()));
+        }
+        let memo = invoice_memo(invoice_id);
+        if memo.len() > MAX_MEMO_LEN {
+            return Err(Error::PayRequest(format!(
+                "memo `{memo}` is longer than {MAX_MEMO_LEN} bytes"
+            )));
+        }
+        if amount.is_some_and(|a| a <= 0) {
+            return Err(Error::PayRequest("amount must be positive".into()));
+        }
+        Ok(Self {
+            destination: destination.to_string(),
+            asset,
+            amount,
+            memo,
+            msg: None,
+            network_passphrase: None,
+        })
+    }
+
+    /// Add a message for the payer, at most [`MAX_MSG_LEN`] characters.
+    pub fn with_msg(mut self, msg: impl Into<String>) -> Result<Self> {
+        let msg = msg.into();
+        if msg.chars().count() > MAX_MSG_LEN {
+            return Err(Error::PayRequest(format!(
+                "msg is longer than {MAX_MSG_LEN} characters"
+            )));
+        }
+        self.msg = Some(msg);
+        Ok(self)
+    }
+
+    /// Target the network with `passphrase`. Left out of the URI for the
+    /// public network.
+    pub fn on_network(mut self, passphrase: &str) -> Self {
+        self.network_passphrase =
+            (passphrase != Network::Mainnet.defaults().passphrase).then(|| passphrase.to_string());
+        self
+    }
+
+    /// The `web+stellar:pay?...` URI.
+    pub fn to_uri(&self) -> String {
+        let mut params = vec![("destination", self.destination.clone())];
+        if let Some(amount) = self.amount {
+            params.push(("amount", decimal(amount)));
+        }
+        if let Asset::Token { code, issuer } = &self.asset {
+            params.push(("asset_code", code.clone()));
+            params.push(("asset_issuer", issuer.clone()));
+        }
+        params.push(("memo", self.memo.clone()));
+        params.push(("memo_type", "MEMO_TEXT".into()));
+        if let Some(msg) = &self.msg {
+            params.push(("msg", msg.clone()));
+        }
+        if let Some(passphrase) = &self.network_passphrase {
+            params.push(("network_passphrase", passphrase.clone()));
+        }
+        let query: Vec<String> = params
+            .iter()
+            .map(|(key, value)| format!("{key}={}", percent_encode(value)))
+            .collect();
+        format!("web+stellar:pay?{}", query.join("&"))
+    }
+
+    /// The URI as an SVG QR code.
+    #[cfg(feature = "qr")]
+    pub fn qr_svg(&self) -> Result<String> {
+        let code = qrcode::QrCode::new(self.to_uri().as_bytes())
+            .map_err(|e| Error::PayRequest(format!("qr: {e}")))?;
+        Ok(code
+            .render::<qrcode::render::svg::Color>()
+            .min_dimensions(256, 256)
+            .build())
+    }
+}
+
+/// Text memo of `invoice_id`: `invoisio-<id>`.
+pub fn invoice_memo(invoice_id: &str) -> String {
+    if invoice_id.starts_with(MEMO_PREFIX) {
+        invoice_id.to_string()
+    } else {
+        format!("{MEMO_PREFIX}{invoice_id}")
+    }
+}
+
+/// Base units as a decimal without trailing zeros, e.g. `12.5`.
+fn decimal(amount: i128) -> String {
+    let unit = 10i128.pow(DECIMALS);
+    let (whole, frac) = (amount / unit, amount % unit);
+    if frac == 0 {
+        return whole.to_string();
+    }
+    let frac = format!("{frac:0width$}", width = DECIMALS as usize);
+    format!("{whole}.{}", frac.trim_end_matches('0'))
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
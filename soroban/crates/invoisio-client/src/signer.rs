@@ -0,0 +1,324 @@
+//! Pluggable transaction signing.
+//!
+//! Everything that signs — transaction envelopes and Soroban auth entries —
+//! goes through [`Signer`], which only ever sees a 32-byte hash. That keeps
+//! the secret wherever the implementation wants it:
+//!
+//! | Signer            | Key lives in                                        |
+//! |-------------------|-----------------------------------------------------|
+//! | [`Keypair`]       | This process                                        |
+//! | [`LedgerSigner`]  | A Ledger running the Stellar app (hash signing on)  |
+//! | [`HttpKmsSigner`] | A remote KMS behind an HTTP signing API             |
+//!
+//! External signers verify every signature they get back against the public
+//! key before it is attached, so a misconfigured device or KMS fails here
+//! rather than as an opaque `txBadAuth` on submission.
+
+use std::future::Future;
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::tx::Keypair;
+
+/// Something that can produce ed25519 signatures for one Stellar account.
+pub trait Signer: Send + Sync {
+    /// Raw ed25519 public key of the signing account.
+    fn public_key_bytes(&self) -> [u8; 32];
+
+    /// `G...` strkey of the signing account.
+    fn public_key(&self) -> String {
+        stellar_strkey::ed25519::PublicKey(self.public_key_bytes()).to_string()
+    }
+
+    /// Sign `hash` (a transaction hash or an auth-entry preimage hash).
+    fn sign_hash(&self, hash: [u8; 32]) -> impl Future<Output = Result<[u8; 64]>> + Send;
+}
+
+impl Signer for Keypair {
+    fn public_key_bytes(&self) -> [u8; 32] {
+        Keypair::public_key_bytes(self)
+    }
+
+    async fn sign_hash(&self, hash: [u8; 32]) -> Result<[u8; 64]> {
+        Ok(self.sign(&hash))
+    }
+}
+
+/// Check `signature` over `hash` against `public_key`.
+pub fn verify(public_key: [u8; 32], hash: &[u8; 32], signature: &[u8; 64]) -> Result<()> {
+    let key = VerifyingKey::from_bytes(&public_key)
+        .map_err(|e| Error::Signer(format!("invalid public key: {e}")))?;
+    key.verify(hash, &Signature::from_bytes(signature))
+        .map_err(|_| Error::Signer("signature does not verify against the public key".into()))
+}
+
+// Ledger
+
+/// APDU class byte of the Stellar Ledger app.
+const CLA: u8 = 0xe0;
+const INS_GET_PUBLIC_KEY: u8 = 0x02;
+const INS_SIGN_HASH: u8 = 0x08;
+
+const SW_OK: u16 = 0x9000;
+const SW_DENIED: u16 = 0x6985;
+const SW_HASH_SIGNING_DISABLED: u16 = 0x6c66;
+
+/// Signer backed by a Ledger device running the Stellar app.
+///
+/// APDUs are exchanged over HTTP with a Speculos-compatible bridge
+/// (`POST {url}/apdu` with `{"data": "<hex>"}`), which fronts either a real
+/// device or the emulator. The app must have hash signing enabled, since
+/// Soroban transactions are signed blind.
+#[derive(Clone, Debug)]
+pub struct LedgerSigner {
+    http: reqwest::Client,
+    url: String,
+    path: Vec<u32>,
+    public_key: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize)]
+struct ApduBody {
+    data: String,
+}
+
+impl LedgerSigner {
+    /// Connect to the bridge at `url` and load the public key of Stellar
+    /// account `account` (`m/44'/148'/{account}'`).
+    pub async fn connect(url: impl Into<String>, account: u32) -> Result<Self> {
+        let mut signer = Self {
+            http: reqwest::Client::new(),
+            url: url.into().trim_end_matches('/').to_string(),
+            path: bip32_path(account).to_vec(),
+            public_key: [0; 32],
+        };
+        let response = signer
+            .exchange(apdu(INS_GET_PUBLIC_KEY, &encode_path(&signer.path)))
+            .await?;
+        signer.public_key = response
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::Signer(format!("ledger returned a {}-byte public key", response.len())))?;
+        Ok(signer)
+    }
+
+    async fn exchange(&self, apdu: Vec<u8>) -> Result<Vec<u8>> {
+        let body: ApduBody = self
+            .http
+            .post(format!("{}/apdu", self.url))
+            .json(&ApduBody { data: to_hex(&apdu) })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let response = from_hex(&body.data)
+            .ok_or_else(|| Error::Signer(format!("ledger bridge returned bad hex `{}`", body.data)))?;
+        split_status(response)
+    }
+}
+
+impl Signer for LedgerSigner {
+    fn public_key_bytes(&self) -> [u8; 32] {
+        self.public_key
+    }
+
+    async fn sign_hash(&self, hash: [u8; 32]) -> Result<[u8; 64]> {
+        let mut data = encode_path(&self.path);
+        data.extend_from_slice(&hash);
+        let response = self.exchange(apdu(INS_SIGN_HASH, &data)).await?;
+        let signature: [u8; 64] = response
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::Signer(format!("ledger returned a {}-byte signature", response.len())))?;
+        verify(self.public_key, &hash, &signature)?;
+        Ok(signature)
+    }
+}
+
+/// SLIP-44 path of Stellar account `account`: `m/44'/148'/{account}'`.
+pub fn bip32_path(account: u32) -> [u32; 3] {
+    const HARDENED: u32 = 0x8000_0000;
+    [44 | HARDENED, 148 | HARDENED, account | HARDENED]
+}
+
+/// Path as the Stellar app expects it: a length byte, then big-endian u32s.
+pub(crate) fn encode_path(path: &[u32]) -> Vec<u8> {
+    let mut out = vec![path.len() as u8];
+    for index in path {
+        out.extend_from_slice(&index.to_be_bytes());
+    }
+    out
+}
+
+/// A single-frame APDU with `P1 = P2 = 0` (no on-device confirmation of the
+/// key; hash signing always prompts).
+pub(crate) fn apdu(ins: u8, data: &[u8]) -> Vec<u8> {
+    let mut out = vec![CLA, ins, 0x00, 0x00, data.len() as u8];
+    out.extend_from_slice(data);
+    out
+}
+
+/// Strip and check the trailing status word of an APDU response.
+pub(crate) fn split_status(mut response: Vec<u8>) -> Result<Vec<u8>> {
+    if response.len() < 2 {
+        return Err(Error::Signer("ledger response is missing its status word".into()));
+    }
+    let sw = response.split_off(response.len() - 2);
+    match u16::from_be_bytes([sw[0], sw[1]]) {
+        SW_OK => Ok(response),
+        SW_DENIED => Err(Error::Signer("rejected on the ledger device".into())),
+        SW_HASH_SIGNING_DISABLED => Err(Error::Signer(
+            "hash signing is disabled in the ledger Stellar app settings".into(),
+        )),
+        sw => Err(Error::Signer(format!("ledger status {sw:#06x}"))),
+    }
+}
+
+// HTTP KMS
+
+/// Signer backed by a remote KMS exposing:
+///
+/// - `GET  {url}/keys/{key_id}` → `{"public_key": "G..."}`
+/// - `POST {url}/keys/{key_id}/sign` with `{"payload": "<hex>"}` →
+///   `{"signature": "<hex>"}`
+///
+/// Requests carry `Authorization: Bearer {token}` when a token is set.
+#[derive(Clone)]
+pub struct HttpKmsSigner {
+    http: reqwest::Client,
+    url: String,
+    token: Option<String>,
+    public_key: [u8; 32],
+}
+
+#[derive(Deserialize)]
+struct KmsKey {
+    public_key: String,
+}
+
+#[derive(Serialize)]
+struct KmsSignRequest {
+    payload: String,
+}
+
+#[derive(Deserialize)]
+struct KmsSignResponse {
+    signature: String,
+}
+
+impl HttpKmsSigner {
+    /// Connect to the KMS at `url` and load the public key of `key_id`.
+    pub async fn connect(
+        url: impl Into<String>,
+        key_id: &str,
+        token: Option<String>,
+    ) -> Result<Self> {
+        let mut signer = Self {
+            http: reqwest::Client::new(),
+            url: format!("{}/keys/{key_id}", url.into().trim_end_matches('/')),
+            token,
+            public_key: [0; 32],
+        };
+        let key: KmsKey = signer
+            .authorized(signer.http.get(&signer.url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        signer.public_key = crate::tx::account_id(&key.public_key)?;
+        Ok(signer)
+    }
+
+    fn authorized(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+impl Signer for HttpKmsSigner {
+    fn public_key_bytes(&self) -> [u8; 32] {
+        self.public_key
+    }
+
+    async fn sign_hash(&self, hash: [u8; 32]) -> Result<[u8; 64]> {
+        let response: KmsSignResponse = self
+            .authorized(self.http.post(format!("{}/sign", self.url)))
+            .json(&KmsSignRequest {
+                payload: to_hex(&hash),
+            })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let signature: [u8; 64] = from_hex(&response.signature)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| Error::Signer("KMS returned a malformed signature".into()))?;
+        verify(self.public_key, &hash, &signature)?;
+        Ok(signature)
+    }
+}
+
+impl core::fmt::Debug for HttpKmsSigner {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Never print the token.
+        f.debug_struct("HttpKmsSigner")
+            .field("url", &self.url)
+            .field("public_key", &Signer::public_key(self))
+            .finish()
+    }
+}
+
+// Runtime choice
+
+/// One of the built-in signers, for services that pick one from config.
+#[derive(Clone, Debug)]
+pub enum AnySigner {
+    Local(Keypair),
+    Ledger(LedgerSigner),
+    Kms(HttpKmsSigner),
+}
+
+impl From<Keypair> for AnySigner {
+    fn from(keypair: Keypair) -> Self {
+        AnySigner::Local(keypair)
+    }
+}
+
+impl Signer for AnySigner {
+    fn public_key_bytes(&self) -> [u8; 32] {
+        match self {
+            AnySigner::Local(s) => Signer::public_key_bytes(s),
+            AnySigner::Ledger(s) => s.public_key_bytes(),
+            AnySigner::Kms(s) => s.public_key_bytes(),
+        }
+    }
+
+    async fn sign_hash(&self, hash: [u8; 32]) -> Result<[u8; 64]> {
+        match self {
+            AnySigner::Local(s) => s.sign_hash(hash).await,
+            AnySigner::Ledger(s) => s.sign_hash(hash).await,
+            AnySigner::Kms(s) => s.sign_hash(hash).await,
+        }
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub(crate) fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
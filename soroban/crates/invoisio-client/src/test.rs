@@ -0,0 +1,1142 @@
+#![cfg(test)]
+
+use super::*;
+use stellar_xdr::curr::{FeeBumpTransactionInnerTx, ScVal, TransactionEnvelope};
+
+const PASSPHRASE: &str = "Test SDF Network ; September 2015";
+const USDC_ISSUER: &str = "GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5";
+
+fn payer() -> Keypair {
+    Keypair::from_seed([7u8; 32])
+}
+
+fn sample_record() -> PaymentRecord {
+    PaymentRecord {
+        invoice_id: "invoisio-abc123".into(),
+        payer: payer().public_key(),
+        payer_mux_id: None,
+        asset: Asset::Token {
+            code: "USDC".into(),
+            issuer: USDC_ISSUER.into(),
+        },
+        amount: 50_000_000,
+        timestamp: 1_700_000_000,
+    }
+}
+
+// ScVal conversions
+
+#[test]
+fn test_i128_round_trips_extremes() {
+    for v in [0i128, 1, -1, i64::MAX as i128 + 1, i128::MAX, i128::MIN] {
+        assert_eq!(scval::to_i128(&scval::i128(v)).unwrap(), v);
+    }
+}
+
+#[test]
+fn test_payment_record_round_trips_through_scval() {
+    let record = sample_record();
+    let v = ScVal::try_from(&record).unwrap();
+    assert_eq!(PaymentRecord::try_from(&v).unwrap(), record);
+}
+
+#[test]
+fn test_native_asset_encodes_as_single_symbol_vec() {
+    let v = ScVal::try_from(&Asset::Native).unwrap();
+    assert_eq!(v, scval::vec(vec![scval::symbol("Native").unwrap()]).unwrap());
+    assert_eq!(Asset::try_from(&v).unwrap(), Asset::Native);
+}
+
+#[test]
+fn test_map_entries_are_sorted_by_key() {
+    let v = scval::map(vec![
+        ("timestamp", ScVal::U64(1)),
+        ("amount", scval::i128(1)),
+    ])
+    .unwrap();
+    let ScVal::Map(Some(m)) = v else { panic!("expected map") };
+    let keys: Vec<_> = m
+        .0
+        .as_slice()
+        .iter()
+        .map(|e| scval::to_symbol(&e.key).unwrap())
+        .collect();
+    assert_eq!(keys, ["amount", "timestamp"]);
+}
+
+#[test]
+fn test_payment_lookups_decode_hits_and_misses() {
+    use stellar_xdr::curr::{ScMap, ScMapEntry};
+
+    let record = sample_record();
+    let entries = vec![
+        ScMapEntry {
+            key: scval::string("invoisio-abc123").unwrap(),
+            val: ScVal::try_from(&record).unwrap(),
+        },
+        ScMapEntry {
+            key: scval::string("invoisio-missing").unwrap(),
+            val: ScVal::Void,
+        },
+    ];
+    let v = ScVal::Map(Some(ScMap(entries.try_into().unwrap())));
+
+    let found = scval::to_payment_lookups(&v).unwrap();
+    assert_eq!(found["invoisio-abc123"], Some(record));
+    assert_eq!(found["invoisio-missing"], None);
+}
+
+#[test]
+fn test_export_page_decodes_records_and_cursor() {
+    let record = sample_record();
+    let records = scval::vec(vec![ScVal::try_from(&record).unwrap()]).unwrap();
+
+    let v = scval::vec(vec![records.clone(), ScVal::U32(50)]).unwrap();
+    assert_eq!(scval::to_export_page(&v).unwrap(), (vec![record], Some(50)));
+
+    let last = scval::vec(vec![records, ScVal::Void]).unwrap();
+    assert_eq!(scval::to_export_page(&last).unwrap().1, None);
+    assert!(scval::to_export_page(&ScVal::U32(1)).is_err());
+}
+
+#[test]
+fn test_muxed_address_round_trips() {
+    // USDC_ISSUER's key with mux id 42.
+    let muxed = "MBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLEAAAAAAAAAAAFJN3W";
+    let v = scval::address(muxed).unwrap();
+    assert_eq!(scval::to_address(&v).unwrap(), muxed);
+}
+
+#[test]
+fn test_payment_record_mux_id_round_trips_and_defaults() {
+    let record = PaymentRecord {
+        payer_mux_id: Some(42),
+        ..sample_record()
+    };
+    let v = ScVal::try_from(&record).unwrap();
+    assert_eq!(PaymentRecord::try_from(&v).unwrap(), record);
+
+    // Older records have no `payer_mux_id` key at all.
+    let ScVal::Map(Some(map)) = ScVal::try_from(&sample_record()).unwrap() else {
+        unreachable!()
+    };
+    let mut entries = map.0.to_vec();
+    entries.retain(|e| scval::to_symbol(&e.key).unwrap() != "payer_mux_id");
+    let legacy = ScVal::Map(Some(stellar_xdr::curr::ScMap(entries.try_into().unwrap())));
+    assert_eq!(PaymentRecord::try_from(&legacy).unwrap(), sample_record());
+}
+
+#[test]
+fn test_address_rejects_garbage() {
+    assert!(matches!(scval::address("GABC123"), Err(Error::Strkey(_))));
+}
+
+#[test]
+fn test_record_payment_args_order_matches_contract() {
+    let args = client::record_payment_args(&RecordPayment {
+        invoice_id: "invoisio-001".into(),
+        payer: payer().public_key(),
+        asset: Asset::Native,
+        amount: 10_000_000,
+    })
+    .unwrap();
+
+    assert_eq!(args.len(), 5);
+    assert_eq!(scval::to_string(&args[0]).unwrap(), "invoisio-001");
+    assert_eq!(scval::to_address(&args[1]).unwrap(), payer().public_key());
+    assert_eq!(scval::to_string(&args[2]).unwrap(), "XLM");
+    assert_eq!(scval::to_string(&args[3]).unwrap(), "");
+    assert_eq!(scval::to_i128(&args[4]).unwrap(), 10_000_000);
+}
+
+#[test]
+fn test_signed_payment_message_binds_contract_and_fields() {
+    use stellar_xdr::curr::{Limits, ReadXdr};
+
+    const CONTRACT: &str = "CDOTTHKN4Z5RFSEDOILRIHNQRWGRCZWOMTDBCFK7W3OWRS2V3H7CEZVM";
+    let payment = RecordPayment {
+        invoice_id: "invoisio-001".into(),
+        payer: payer().public_key(),
+        asset: Asset::Native,
+        amount: 10_000_000,
+    };
+    let bytes = client::signed_payment_message(CONTRACT, &payment).unwrap();
+
+    let message = ScVal::from_xdr(&bytes, Limits::none()).unwrap();
+    let items = scval::to_vec(&message).unwrap();
+    assert_eq!(scval::to_address(&items[0]).unwrap(), CONTRACT);
+    assert_eq!(scval::to_string(scval::map_get(&items[1], "asset_code").unwrap()).unwrap(), "XLM");
+    assert_eq!(scval::to_i128(scval::map_get(&items[1], "amount").unwrap()).unwrap(), 10_000_000);
+
+    let other = RecordPayment {
+        amount: 10_000_001,
+        ..payment
+    };
+    assert_ne!(client::signed_payment_message(CONTRACT, &other).unwrap(), bytes);
+}
+
+// Backoff
+
+#[test]
+fn test_backoff_doubles_and_caps() {
+    use std::time::Duration;
+
+    let b = Backoff {
+        base: Duration::from_secs(1),
+        max: Duration::from_secs(10),
+        max_attempts: 10,
+    };
+    assert_eq!(b.delay(0), Duration::from_secs(1));
+    assert_eq!(b.delay(1), Duration::from_secs(2));
+    assert_eq!(b.delay(3), Duration::from_secs(8));
+    assert_eq!(b.delay(4), Duration::from_secs(10));
+    assert_eq!(b.delay(40), Duration::from_secs(10));
+}
+
+// Errors
+
+#[test]
+fn test_contract_error_codes_match_contract() {
+    for code in 1..=54u32 {
+        let err = ContractError::try_from(code).unwrap();
+        assert_eq!(err as u32, code);
+    }
+    assert!(ContractError::try_from(0).is_err());
+}
+
+#[test]
+fn test_simulation_message_maps_to_contract_error() {
+    let msg = "HostError: Error(Contract, #4)\n\nEvent log (newest first): ...".to_string();
+    assert!(matches!(
+        Error::from_simulation_message(msg),
+        Error::Contract(ContractError::PaymentNotFound)
+    ));
+
+    let msg = "HostError: Error(Budget, ExceededLimit)".to_string();
+    assert!(matches!(
+        Error::from_simulation_message(msg),
+        Error::Simulation(_)
+    ));
+}
+
+#[test]
+fn test_bad_seq_is_detected_at_send_and_in_ledger() {
+    use stellar_xdr::curr::{
+        Limits, TransactionResult, TransactionResultExt, TransactionResultResult, WriteXdr,
+    };
+
+    let result = |result| {
+        let r = TransactionResult {
+            fee_charged: 100,
+            result,
+            ext: TransactionResultExt::V0,
+        };
+        Some(r.to_xdr_base64(Limits::none()).unwrap())
+    };
+    let rejected = |xdr| Error::SendRejected {
+        hash: "ab".into(),
+        status: "ERROR".into(),
+        error_result_xdr: xdr,
+    };
+    assert!(rejected(result(TransactionResultResult::TxBadSeq)).is_bad_seq());
+    assert!(!rejected(result(TransactionResultResult::TxInsufficientFee)).is_bad_seq());
+    assert!(!rejected(None).is_bad_seq());
+
+    let failed = Error::TransactionFailed {
+        hash: "ab".into(),
+        result_xdr: result(TransactionResultResult::TxBadSeq),
+    };
+    assert!(failed.is_bad_seq());
+    assert!(!Error::Timeout { hash: "ab".into() }.is_bad_seq());
+}
+
+// Events
+
+#[test]
+fn test_decode_payment_recorded_event() {
+    let record = sample_record();
+    let topics = vec![scval::symbol("payment_recorded").unwrap()];
+    let data = scval::map(vec![("record", ScVal::try_from(&record).unwrap())]).unwrap();
+
+    assert_eq!(
+        ContractEvent::decode(&topics, &data).unwrap(),
+        ContractEvent::PaymentRecorded { record }
+    );
+}
+
+#[test]
+fn test_decode_payment_imported_event() {
+    let record = sample_record();
+    let topics = vec![scval::symbol("payment_imported").unwrap()];
+    let data = scval::map(vec![("record", ScVal::try_from(&record).unwrap())]).unwrap();
+
+    let event = ContractEvent::decode(&topics, &data).unwrap();
+    assert_eq!(event.topic(), "payment_imported");
+    assert_eq!(event, ContractEvent::PaymentImported { record });
+}
+
+#[test]
+fn test_decode_payment_refunded_event() {
+    let topics = vec![scval::symbol("payment_refunded").unwrap()];
+    let data = scval::map(vec![
+        ("invoice_id", scval::string("invoisio-abc123").unwrap()),
+        ("amount", scval::i128(20_000_000)),
+        ("refunded_total", scval::i128(30_000_000)),
+    ])
+    .unwrap();
+
+    let event = ContractEvent::decode(&topics, &data).unwrap();
+    assert_eq!(event.topic(), "payment_refunded");
+    assert_eq!(
+        event,
+        ContractEvent::PaymentRefunded {
+            invoice_id: "invoisio-abc123".into(),
+            amount: 20_000_000,
+            refunded_total: 30_000_000,
+        }
+    );
+}
+
+#[test]
+fn test_decode_unknown_event_is_not_an_error() {
+    let topics = vec![scval::symbol("something_new").unwrap()];
+    assert_eq!(
+        ContractEvent::decode(&topics, &ScVal::Void).unwrap(),
+        ContractEvent::Unknown {
+            topic: "something_new".into()
+        }
+    );
+}
+
+// JSON
+
+#[test]
+fn test_payment_record_json_keeps_amount_exact() {
+    let mut record = sample_record();
+    record.amount = i128::MAX;
+    let json = serde_json::to_string(&record).unwrap();
+    assert!(json.contains(&format!("\"amount\":\"{}\"", i128::MAX)));
+    assert_eq!(serde_json::from_str::<PaymentRecord>(&json).unwrap(), record);
+}
+
+// Transactions
+
+#[tokio::test]
+async fn test_signed_envelope_verifies_against_transaction_hash() {
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    let signer = payer();
+    let contract = [1u8; 32];
+    let t = tx::build_invoke(signer.public_key_bytes(), 42, contract, "payment_count", vec![])
+        .unwrap();
+    let hash = tx::transaction_hash(&t, PASSPHRASE).unwrap();
+
+    let envelope = tx::sign(t, PASSPHRASE, &signer).await.unwrap();
+    assert_eq!(
+        tx::envelope_hash(&envelope, PASSPHRASE).unwrap(),
+        signer::to_hex(&hash)
+    );
+    let TransactionEnvelope::Tx(env) = envelope else {
+        panic!("expected v1 envelope");
+    };
+    let sig = &env.signatures.as_slice()[0];
+    let pk = signer.public_key_bytes();
+    assert_eq!(sig.hint.0, [pk[28], pk[29], pk[30], pk[31]]);
+
+    let key = VerifyingKey::from_bytes(&pk).unwrap();
+    let bytes: [u8; 64] = sig.signature.0.as_slice().try_into().unwrap();
+    key.verify(&hash, &Signature::from_bytes(&bytes)).unwrap();
+}
+
+#[test]
+fn test_transaction_hash_depends_on_network() {
+    let t = tx::build_invoke([2u8; 32], 1, [1u8; 32], "admin", vec![]).unwrap();
+    assert_ne!(
+        tx::transaction_hash(&t, PASSPHRASE).unwrap(),
+        tx::transaction_hash(&t, "Public Global Stellar Network ; September 2015").unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_fee_bump_keeps_inner_signature_and_is_signed_by_sponsor() {
+    let customer = payer();
+    let sponsor = Keypair::from_seed([8u8; 32]);
+    let t = tx::build_invoke(customer.public_key_bytes(), 7, [1u8; 32], "payment_count", vec![])
+        .unwrap();
+    let inner = tx::sign(t, PASSPHRASE, &customer).await.unwrap();
+
+    let bump = tx::fee_bump(inner.clone(), sponsor.public_key_bytes(), 300).unwrap();
+    let hash = tx::fee_bump_hash(&bump, PASSPHRASE).unwrap();
+    let TransactionEnvelope::TxFeeBump(env) =
+        tx::sign_fee_bump(bump, PASSPHRASE, &sponsor).await.unwrap()
+    else {
+        panic!("expected fee bump envelope");
+    };
+    assert_eq!(env.tx.fee, 300);
+    let FeeBumpTransactionInnerTx::Tx(wrapped) = &env.tx.inner_tx;
+    assert_eq!(TransactionEnvelope::Tx(wrapped.clone()), inner);
+
+    let sig = &env.signatures.as_slice()[0];
+    let bytes: [u8; 64] = sig.signature.0.as_slice().try_into().unwrap();
+    signer::verify(sponsor.public_key_bytes(), &hash, &bytes).unwrap();
+
+    let bumped = TransactionEnvelope::TxFeeBump(env);
+    assert!(tx::fee_bump(bumped, [0u8; 32], 1).is_err());
+}
+
+#[test]
+fn test_keypair_debug_hides_secret() {
+    let debug = format!("{:?}", payer());
+    assert!(debug.contains(&payer().public_key()));
+    assert!(!debug.contains("signing"));
+}
+
+#[tokio::test]
+async fn test_cosigned_auth_entry_keeps_signatures_sorted_and_unique() {
+    use stellar_xdr::curr::{
+        ContractId, Hash, InvokeContractArgs, ScAddress, ScSymbol, SorobanAddressCredentials,
+        SorobanAuthorizationEntry, SorobanAuthorizedFunction, SorobanAuthorizedInvocation,
+        SorobanCredentials,
+    };
+
+    let mut entry = SorobanAuthorizationEntry {
+        credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+            address: scval::sc_address(&payer().public_key()).unwrap(),
+            nonce: 42,
+            signature_expiration_ledger: 1_000,
+            signature: ScVal::Void,
+        }),
+        root_invocation: SorobanAuthorizedInvocation {
+            function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+                contract_address: ScAddress::Contract(ContractId(Hash([1u8; 32]))),
+                function_name: ScSymbol("set_admin".try_into().unwrap()),
+                args: Default::default(),
+            }),
+            sub_invocations: Default::default(),
+        },
+    };
+    let keys = [Keypair::from_seed([9u8; 32]), payer()];
+
+    for key in keys.iter().chain(&keys) {
+        entry = tx::cosign_auth_entry(entry, key, PASSPHRASE).await.unwrap();
+    }
+    let SorobanCredentials::Address(creds) = &entry.credentials else {
+        panic!("expected address credentials");
+    };
+    assert_eq!(creds.signature_expiration_ledger, 1_000);
+    let public_keys: Vec<_> = scval::to_vec(&creds.signature)
+        .unwrap()
+        .iter()
+        .map(|s| match scval::map_get(s, "public_key").unwrap() {
+            ScVal::Bytes(b) => b.0.to_vec(),
+            other => panic!("unexpected {other:?}"),
+        })
+        .collect();
+    let mut expected: Vec<_> = keys.iter().map(|k| k.public_key_bytes().to_vec()).collect();
+    expected.sort();
+    assert_eq!(public_keys, expected);
+}
+
+#[test]
+fn test_reset_resources_restores_inclusion_fee() {
+    use stellar_xdr::curr::{SorobanTransactionData, TransactionExt};
+
+    let t = tx::build_invoke([2u8; 32], 1, [1u8; 32], "admin", vec![]).unwrap();
+    let mut assembled = t.clone();
+    assembled.fee += 5_000;
+    assembled.ext = TransactionExt::V1(SorobanTransactionData {
+        resource_fee: 5_000,
+        ..Default::default()
+    });
+    assert_eq!(tx::reset_resources(assembled), t);
+}
+
+#[test]
+fn test_deadline_sets_max_time_only() {
+    use stellar_xdr::curr::{Preconditions, TimeBounds, TimePoint};
+
+    let t = tx::build_invoke([2u8; 32], 1, [1u8; 32], "admin", vec![]).unwrap();
+    assert_eq!(t.cond, Preconditions::None);
+    let t = tx::set_deadline(t, 1_700_000_000);
+    assert_eq!(
+        t.cond,
+        Preconditions::Time(TimeBounds {
+            min_time: TimePoint(0),
+            max_time: TimePoint(1_700_000_000),
+        })
+    );
+}
+
+#[test]
+fn test_keypair_secret_round_trips() {
+    let secret = payer().secret();
+    assert!(secret.starts_with('S'));
+    let parsed = Keypair::from_secret(&secret).unwrap();
+    assert_eq!(parsed.public_key(), payer().public_key());
+}
+
+#[test]
+fn test_create_account_is_a_single_classic_operation() {
+    use stellar_xdr::curr::OperationBody;
+
+    let t = tx::build_create_account([2u8; 32], 5, payer().public_key_bytes(), 50_000_000).unwrap();
+    assert_eq!(t.fee, tx::DEFAULT_BASE_FEE);
+    let [op] = t.operations.as_slice() else {
+        panic!("expected one operation");
+    };
+    let OperationBody::CreateAccount(create) = &op.body else {
+        panic!("expected create_account");
+    };
+    assert_eq!(create.starting_balance, 50_000_000);
+}
+
+// Signers
+
+#[tokio::test]
+async fn test_any_signer_matches_local_keypair() {
+    let keypair = payer();
+    let any = AnySigner::from(keypair.clone());
+    assert_eq!(Signer::public_key(&any), keypair.public_key());
+
+    let hash = [9u8; 32];
+    let signature = any.sign_hash(hash).await.unwrap();
+    assert_eq!(signature, keypair.sign(&hash));
+    signer::verify(keypair.public_key_bytes(), &hash, &signature).unwrap();
+    assert!(matches!(
+        signer::verify(keypair.public_key_bytes(), &[8u8; 32], &signature),
+        Err(Error::Signer(_))
+    ));
+}
+
+#[test]
+fn test_ledger_apdu_encoding() {
+    assert_eq!(
+        signer::bip32_path(0),
+        [0x8000_002c, 0x8000_0094, 0x8000_0000]
+    );
+    let path = signer::encode_path(&signer::bip32_path(1));
+    assert_eq!(
+        path,
+        [3, 0x80, 0, 0, 0x2c, 0x80, 0, 0, 0x94, 0x80, 0, 0, 1]
+    );
+    let apdu = signer::apdu(0x02, &path);
+    assert_eq!(&apdu[..5], &[0xe0, 0x02, 0x00, 0x00, 13]);
+    assert_eq!(&apdu[5..], path.as_slice());
+}
+
+#[test]
+fn test_ledger_status_words() {
+    assert_eq!(signer::split_status(vec![1, 2, 0x90, 0x00]).unwrap(), vec![1, 2]);
+    for sw in [[0x69, 0x85], [0x6c, 0x66], [0x6a, 0x80]] {
+        assert!(matches!(signer::split_status(sw.to_vec()), Err(Error::Signer(_))));
+    }
+    assert!(signer::split_status(vec![0x90]).is_err());
+}
+
+#[test]
+fn test_deployed_contract_id_is_deterministic_per_salt_and_network() {
+    let deployer = payer().public_key_bytes();
+    let a = tx::deployed_contract_id(deployer, [1u8; 32], PASSPHRASE).unwrap();
+    assert!(a.starts_with('C'));
+    assert_eq!(a, tx::deployed_contract_id(deployer, [1u8; 32], PASSPHRASE).unwrap());
+    assert_ne!(a, tx::deployed_contract_id(deployer, [2u8; 32], PASSPHRASE).unwrap());
+    assert_ne!(
+        a,
+        tx::deployed_contract_id(
+            deployer,
+            [1u8; 32],
+            "Public Global Stellar Network ; September 2015"
+        )
+        .unwrap()
+    );
+}
+
+// Estimates
+
+#[test]
+fn test_resource_estimate_reads_assembled_transaction() {
+    use stellar_xdr::curr::{
+        LedgerFootprint, Limits, SorobanResources, SorobanTransactionData,
+        SorobanTransactionDataExt, TransactionExt, WriteXdr,
+    };
+
+    let data = SorobanTransactionData {
+        ext: SorobanTransactionDataExt::V0,
+        resources: SorobanResources {
+            footprint: LedgerFootprint {
+                read_only: Default::default(),
+                read_write: Default::default(),
+            },
+            instructions: 1_234_567,
+            disk_read_bytes: 300,
+            write_bytes: 200,
+        },
+        resource_fee: 50_000,
+    };
+    let t = tx::build_invoke([2u8; 32], 1, [1u8; 32], "record_payment", vec![]).unwrap();
+    let t = tx::assemble(
+        t,
+        &data.to_xdr_base64(Limits::none()).unwrap(),
+        50_000,
+        vec![],
+    )
+    .unwrap();
+    assert!(matches!(t.ext, TransactionExt::V1(_)));
+
+    let estimate = ResourceEstimate::from_simulation(Simulation {
+        transaction: t,
+        result: ScVal::Void,
+        auth: vec![],
+        min_resource_fee: 50_000,
+        latest_ledger: 99,
+    })
+    .unwrap();
+
+    assert_eq!(estimate.instructions, 1_234_567);
+    assert_eq!(estimate.disk_read_bytes, 300);
+    assert_eq!(estimate.write_bytes, 200);
+    assert_eq!(estimate.resource_fee, 50_000);
+    assert_eq!(estimate.inclusion_fee, tx::DEFAULT_BASE_FEE);
+    assert_eq!(estimate.total_fee(), 50_000 + tx::DEFAULT_BASE_FEE);
+    assert_eq!(estimate.with_inclusion_fee(1_000).unwrap().fee, 51_000);
+}
+
+#[test]
+fn test_resource_estimate_requires_soroban_data() {
+    let t = tx::build_invoke([2u8; 32], 1, [1u8; 32], "admin", vec![]).unwrap();
+    let sim = Simulation {
+        transaction: t,
+        result: ScVal::Void,
+        auth: vec![],
+        min_resource_fee: 0,
+        latest_ledger: 1,
+    };
+    assert!(matches!(
+        ResourceEstimate::from_simulation(sim),
+        Err(Error::Simulation(_))
+    ));
+}
+
+#[test]
+fn test_fee_stats_decode_string_fees() {
+    let distribution = |p50: &str| {
+        serde_json::json!({
+            "max": "5000", "min": "100", "mode": "100",
+            "p10": "100", "p20": "100", "p30": "100", "p40": "100", "p50": p50,
+            "p60": "300", "p70": "400", "p80": "500", "p90": "1000", "p95": "2000",
+            "p99": "4000", "transactionCount": "42", "ledgerCount": 50
+        })
+    };
+    let stats: rpc::GetFeeStatsResponse = serde_json::from_value(serde_json::json!({
+        "sorobanInclusionFee": distribution("250"),
+        "inclusionFee": distribution("100"),
+        "latestLedger": 1234
+    }))
+    .unwrap();
+    assert_eq!(stats.soroban_inclusion_fee.p50, 250);
+    assert_eq!(stats.soroban_inclusion_fee.p99, 4000);
+    assert_eq!(stats.soroban_inclusion_fee.transaction_count, 42);
+    assert_eq!(stats.inclusion_fee.p50, 100);
+    assert_eq!(stats.latest_ledger, 1234);
+
+    let bad = serde_json::json!({
+        "sorobanInclusionFee": distribution("lots"),
+        "inclusionFee": distribution("100"),
+        "latestLedger": 1
+    });
+    assert!(serde_json::from_value::<rpc::GetFeeStatsResponse>(bad).is_err());
+}
+
+// Network profiles
+
+#[test]
+fn test_network_parses_case_insensitively_and_displays_name() {
+    assert_eq!("Mainnet".parse::<Network>().unwrap(), Network::Mainnet);
+    assert_eq!(Network::Futurenet.to_string(), "futurenet");
+    assert!("pubnet".parse::<Network>().is_err());
+}
+
+#[test]
+fn test_builtin_profiles_have_distinct_passphrases() {
+    for (i, a) in Network::ALL.iter().enumerate() {
+        for b in &Network::ALL[i + 1..] {
+            assert_ne!(a.defaults().passphrase, b.defaults().passphrase);
+        }
+    }
+}
+
+#[test]
+fn test_profiles_file_overrides_defaults() {
+    let profiles = Profiles::parse(
+        r#"
+        [networks.testnet]
+        contract_id = "CTEST"
+
+        [networks.mainnet]
+        contract_id = "CMAIN"
+        rpc_url = "https://rpc.example.com"
+        "#,
+    )
+    .unwrap();
+
+    let main = profiles.resolve(Network::Mainnet);
+    assert_eq!(main.contract_id.as_deref(), Some("CMAIN"));
+    assert_eq!(main.rpc_url, "https://rpc.example.com");
+    assert_eq!(main.passphrase, Network::Mainnet.defaults().passphrase);
+    assert_eq!(profiles.resolve(Network::Local), Network::Local.defaults());
+}
+
+#[test]
+fn test_contract_from_another_network_is_rejected() {
+    let profiles = Profiles::parse("[networks.testnet]\ncontract_id = \"CTEST\"\n").unwrap();
+    assert_eq!(profiles.network_of("CTEST"), Some(Network::Testnet));
+    assert!(profiles.check_contract(Network::Testnet, "CTEST").is_ok());
+    assert!(matches!(
+        profiles.check_contract(Network::Mainnet, "CTEST"),
+        Err(Error::Profile(_))
+    ));
+    assert!(profiles.check_contract(Network::Mainnet, "CUNKNOWN").is_ok());
+}
+
+#[test]
+fn test_profiles_contract_id_is_written_in_place() {
+    let before = "# profiles\n[networks.testnet]\n# contract_id = \"C...\"\nrpc_url = \"https://rpc\"\n\n[networks.mainnet]\ncontract_id = \"CMAIN\"\n";
+    let after = Profiles::with_contract_id(before, Network::Testnet, "CNEW").unwrap();
+    assert_eq!(
+        after,
+        "# profiles\n[networks.testnet]\ncontract_id = \"CNEW\"\n# contract_id = \"C...\"\nrpc_url = \"https://rpc\"\n\n[networks.mainnet]\ncontract_id = \"CMAIN\"\n"
+    );
+
+    let replaced = Profiles::with_contract_id(&after, Network::Mainnet, "CMAIN2").unwrap();
+    let profiles = Profiles::parse(&replaced).unwrap();
+    assert_eq!(profiles.resolve(Network::Mainnet).contract_id.as_deref(), Some("CMAIN2"));
+    assert_eq!(profiles.resolve(Network::Testnet).contract_id.as_deref(), Some("CNEW"));
+    assert_eq!(profiles.resolve(Network::Testnet).rpc_url, "https://rpc");
+
+    let added = Profiles::with_contract_id("", Network::Local, "CLOCAL").unwrap();
+    assert_eq!(added, "[networks.local]\ncontract_id = \"CLOCAL\"\n");
+}
+
+#[test]
+fn test_profiles_contract_id_refuses_layouts_it_cannot_edit() {
+    let inline = "networks.testnet = { contract_id = \"COLD\" }\n";
+    assert!(matches!(
+        Profiles::with_contract_id(inline, Network::Testnet, "CNEW"),
+        Err(Error::Profile(_))
+    ));
+}
+
+#[test]
+fn test_unknown_network_in_profiles_file_is_an_error() {
+    assert!(matches!(
+        Profiles::parse("[networks.pubnet]\ncontract_id = \"C\"\n"),
+        Err(Error::Profile(_))
+    ));
+}
+
+// Receipts
+
+fn sample_receipt() -> PaymentReceipt {
+    let record = sample_record();
+    PaymentReceipt {
+        contract: "CDOTTHKN4Z5RFSEDOILRIHNQRWGRCZWOMTDBCFK7W3OWRS2V3H7CEZVM".into(),
+        invoice_id: record.invoice_id,
+        payer: record.payer,
+        payer_mux_id: None,
+        asset: record.asset,
+        amount: record.amount,
+        refunded: 0,
+        paid_at: record.timestamp,
+        recorded_at: record.timestamp,
+        merchant_id: Some("acme".into()),
+        receipt_number: Some(42),
+    }
+}
+
+#[test]
+fn test_receipt_round_trips_through_scval() {
+    let receipt = sample_receipt();
+    let v = ScVal::try_from(&receipt).unwrap();
+    assert_eq!(PaymentReceipt::try_from(&v).unwrap(), receipt);
+}
+
+#[test]
+fn test_receipt_renders_amount_with_seven_decimals() {
+    let text = sample_receipt().render();
+    assert!(text.contains("Receipt no.: 42"));
+    assert!(text.contains(&format!("Amount:      5.0000000 USDC ({USDC_ISSUER})")));
+    assert!(!text.contains("Refunded"));
+}
+
+#[tokio::test]
+async fn test_signed_receipt_verifies_and_detects_tampering() {
+    let signed = sample_receipt().sign(&payer()).await.unwrap();
+    assert_eq!(signed.signer, payer().public_key());
+    signed.verify().unwrap();
+
+    let mut tampered = signed.clone();
+    tampered.receipt.amount += 1;
+    assert!(matches!(tampered.verify(), Err(Error::Signer(_))));
+
+    let mut other_key = signed;
+    other_key.signer = Keypair::from_seed([8u8; 32]).public_key();
+    assert!(matches!(other_key.verify(), Err(Error::Signer(_))));
+}
+
+// Verification
+
+fn sample_operation() -> PaymentOperation {
+    serde_json::from_value(serde_json::json!({
+        "id": "12884905985",
+        "paging_token": "12884905985",
+        "type": "payment",
+        "transaction_hash": "ab12",
+        "transaction_successful": true,
+        "from": payer().public_key(),
+        "to": "GDESTINATION",
+        "asset_type": "credit_alphanum4",
+        "asset_code": "USDC",
+        "asset_issuer": USDC_ISSUER,
+        "amount": "5.0000000",
+        "created_at": "2023-11-14T22:13:20Z",
+        "transaction": { "memo_type": "text", "memo": "invoisio-abc123" }
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_horizon_amount_parses_to_base_units() {
+    assert_eq!(horizon::parse_amount("5.0000000"), Some(50_000_000));
+    assert_eq!(horizon::parse_amount("12.34"), Some(123_400_000));
+    assert_eq!(horizon::parse_amount("7"), Some(70_000_000));
+    assert_eq!(horizon::parse_amount("0.00000001"), None);
+    assert_eq!(horizon::parse_amount("-1.0"), None);
+}
+
+#[test]
+fn test_verification_matches_identical_operation() {
+    let report = VerificationReport::new(sample_record(), Some(&sample_operation()));
+    assert!(report.is_match());
+    assert_eq!(report.transaction_hash.as_deref(), Some("ab12"));
+}
+
+#[test]
+fn test_verification_reports_every_mismatched_field() {
+    let mut op = sample_operation();
+    op.amount = Some("4.9000000".into());
+    op.asset_type = Some("native".into());
+    op.from = Some("GSOMEONEELSE".into());
+    op.transaction = Some(horizon::HorizonTransaction {
+        memo_type: "hash".into(),
+        memo: Some("AAAA".into()),
+    });
+
+    let report = VerificationReport::new(sample_record(), Some(&op));
+    assert_eq!(
+        report.discrepancies,
+        vec![
+            Discrepancy::Memo {
+                recorded: "invoisio-abc123".into(),
+                paid: None,
+            },
+            Discrepancy::Asset {
+                recorded: sample_record().asset,
+                paid: Some(Asset::Native),
+            },
+            Discrepancy::Amount {
+                recorded: 50_000_000,
+                paid: Some("4.9000000".into()),
+            },
+            Discrepancy::Source {
+                recorded: payer().public_key(),
+                recorded_mux_id: None,
+                paid: Some("GSOMEONEELSE".into()),
+                paid_mux_id: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_verification_without_operation_reports_it_missing() {
+    let report = VerificationReport::new(sample_record(), None);
+    assert_eq!(report.discrepancies, vec![Discrepancy::OperationNotFound]);
+    assert_eq!(report.operation_id, None);
+}
+
+// SEP-31
+
+#[test]
+fn test_sep31_transaction_yields_remittance_that_round_trips() {
+    let tx: Sep31Transaction = serde_json::from_value(serde_json::json!({
+        "id": "82fhs729f63dh0v4",
+        "status": "completed",
+        "amount_in": "18.34",
+        "amount_in_asset": format!("stellar:USDC:{USDC_ISSUER}"),
+        "stellar_account_id": "GDESTINATION",
+        "stellar_memo_type": "hash",
+        "stellar_memo": "YzI5NjcwNWIxMTIxNmQ5NmE5YzE3OGY0YjBhYzJkZmE=",
+        "stellar_transaction_id": "b9d0b229",
+        "completed_at": "2024-02-01T10:00:00Z"
+    }))
+    .unwrap();
+    assert!(tx.is_completed());
+
+    let remittance = tx.remittance("sender.example", "receiver.example");
+    assert_eq!(remittance.transaction_id, "82fhs729f63dh0v4");
+    let v = ScVal::try_from(&remittance).unwrap();
+    assert_eq!(Remittance::try_from(&v).unwrap(), remittance);
+}
+
+#[test]
+fn test_stellar_toml_lookup() {
+    let toml = r#"
+NETWORK_PASSPHRASE = "Test SDF Network ; September 2015"
+DIRECT_PAYMENT_SERVER = "https://receiver.example/sep31/"
+
+[[CURRENCIES]]
+code = "USDC"
+"#;
+    assert_eq!(
+        sep31::toml_value(toml, "DIRECT_PAYMENT_SERVER").unwrap(),
+        "https://receiver.example/sep31/"
+    );
+    assert!(matches!(
+        sep31::toml_value(toml, "WEB_AUTH_ENDPOINT"),
+        Err(Error::Anchor(_))
+    ));
+    assert!(matches!(
+        sep31::toml_value("not toml = ", "DIRECT_PAYMENT_SERVER"),
+        Err(Error::Anchor(_))
+    ));
+}
+
+// SEP-10
+
+const ANCHOR: &str = "anchor.example";
+
+fn anchor_key() -> Keypair {
+    Keypair::from_seed([11u8; 32])
+}
+
+fn web_auth() -> WebAuth {
+    WebAuth::with_endpoint(
+        reqwest::Client::new(),
+        ANCHOR,
+        "https://auth.anchor.example/auth",
+        &anchor_key().public_key(),
+        PASSPHRASE,
+    )
+    .unwrap()
+}
+
+/// A challenge for `payer()` valid from `now` for 15 minutes, built by
+/// `anchor_key()` with the first operation named `name`.
+async fn challenge(now: u64, name: &str) -> TransactionEnvelope {
+    use stellar_xdr::curr::{
+        DataValue, ManageDataOp, Memo, MuxedAccount, Operation, OperationBody, Preconditions,
+        SequenceNumber, String64, StringM, TimeBounds, TimePoint, Transaction, TransactionExt,
+        Uint256,
+    };
+
+    let manage_data = |source: [u8; 32], name: &str, value: &[u8]| Operation {
+        source_account: Some(MuxedAccount::Ed25519(Uint256(source))),
+        body: OperationBody::ManageData(ManageDataOp {
+            data_name: String64(StringM::try_from(name.as_bytes().to_vec()).unwrap()),
+            data_value: Some(DataValue(value.to_vec().try_into().unwrap())),
+        }),
+    };
+    let server = anchor_key().public_key_bytes();
+    let tx = Transaction {
+        source_account: MuxedAccount::Ed25519(Uint256(server)),
+        fee: 200,
+        seq_num: SequenceNumber(0),
+        cond: Preconditions::Time(TimeBounds {
+            min_time: TimePoint(now),
+            max_time: TimePoint(now + 900),
+        }),
+        memo: Memo::None,
+        operations: vec![
+            manage_data(payer().public_key_bytes(), name, &[7u8; 64]),
+            manage_data(server, "web_auth_domain", b"auth.anchor.example"),
+        ]
+        .try_into()
+        .unwrap(),
+        ext: TransactionExt::V0,
+    };
+    tx::sign(tx, PASSPHRASE, &anchor_key()).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_sep10_accepts_well_formed_challenge_and_cosigns_it() {
+    let auth = web_auth();
+    let now = 1_700_000_000;
+    let envelope = challenge(now, "anchor.example auth").await;
+    let account = payer().public_key_bytes();
+    auth.validate_challenge(&envelope, account, now).unwrap();
+
+    let signed = auth.sign_challenge(envelope, &payer()).await.unwrap();
+    let TransactionEnvelope::Tx(v1) = signed else {
+        panic!("expected a v1 envelope");
+    };
+    assert_eq!(v1.signatures.len(), 2);
+    let hash = tx::transaction_hash(&v1.tx, PASSPHRASE).unwrap();
+    let signature: [u8; 64] = v1.signatures[1].signature.0.as_slice().try_into().unwrap();
+    signer::verify(account, &hash, &signature).unwrap();
+}
+
+#[tokio::test]
+async fn test_sep10_rejects_foreign_expired_or_unsigned_challenges() {
+    let auth = web_auth();
+    let now = 1_700_000_000;
+    let account = payer().public_key_bytes();
+    let rejected = |envelope: &TransactionEnvelope, account: [u8; 32], now: u64| {
+        matches!(
+            auth.validate_challenge(envelope, account, now),
+            Err(Error::Anchor(_))
+        )
+    };
+
+    let other_domain = challenge(now, "evil.example auth").await;
+    assert!(rejected(&other_domain, account, now));
+
+    let envelope = challenge(now, "anchor.example auth").await;
+    assert!(rejected(&envelope, account, now + 3_600));
+    let other_account = Keypair::from_seed([8u8; 32]).public_key_bytes();
+    assert!(rejected(&envelope, other_account, now));
+
+    let TransactionEnvelope::Tx(mut v1) = envelope else {
+        panic!("expected a v1 envelope");
+    };
+    v1.signatures = Default::default();
+    let unsigned = TransactionEnvelope::Tx(v1);
+    assert!(rejected(&unsigned, account, now));
+}
+
+#[test]
+fn test_jwt_claims_decode_and_expiry() {
+    // {"iss":"https://auth.anchor.example/auth","sub":"GABC","iat":1700000000,"exp":1700086400}
+    let token = "eyJhbGciOiJIUzI1NiJ9.\
+        eyJpc3MiOiJodHRwczovL2F1dGguYW5jaG9yLmV4YW1wbGUvYXV0aCIsInN1YiI6IkdBQkMi\
+        LCJpYXQiOjE3MDAwMDAwMDAsImV4cCI6MTcwMDA4NjQwMH0.\
+        c2ln";
+    let jwt = Jwt::parse(token).unwrap();
+    assert_eq!(jwt.claims.sub, "GABC");
+    assert_eq!(jwt.claims.exp, 1_700_086_400);
+    assert!(!jwt.is_expired(1_700_000_000));
+    assert!(jwt.expires_within(1_700_086_000, 600));
+    assert!(jwt.is_expired(1_700_086_400));
+    assert!(!format!("{jwt:?}").contains(token));
+    assert!(matches!(Jwt::parse("not-a-jwt"), Err(Error::Anchor(_))));
+}
+
+// SEP-7
+
+#[test]
+fn test_pay_request_uri_carries_invoice_memo() {
+    let to = payer().public_key();
+    let usdc = Asset::Token {
+        code: "USDC".into(),
+        issuer: USDC_ISSUER.into(),
+    };
+    let request = PayRequest::for_invoice(&to, "abc123", usdc, Some(125_000_000))
+        .unwrap()
+        .with_msg("Invoice #123")
+        .unwrap()
+        .on_network(PASSPHRASE);
+    assert_eq!(request.memo, "invoisio-abc123");
+    assert_eq!(
+        request.to_uri(),
+        format!(
+            "web+stellar:pay?destination={to}&amount=12.5&asset_code=USDC&asset_issuer={USDC_ISSUER}\
+             &memo=invoisio-abc123&memo_type=MEMO_TEXT&msg=Invoice%20%23123\
+             &network_passphrase=Test%20SDF%20Network%20%3B%20September%202015"
+        )
+    );
+
+    // Public network: no passphrase, and an already prefixed id is kept.
+    let mainnet = Network::Mainnet.defaults().passphrase;
+    let native = PayRequest::for_invoice(&to, "invoisio-abc123", Asset::Native, None)
+        .unwrap()
+        .on_network(&mainnet);
+    assert_eq!(
+        native.to_uri(),
+        format!("web+stellar:pay?destination={to}&memo=invoisio-abc123&memo_type=MEMO_TEXT")
+    );
+}
+
+#[test]
+fn test_pay_request_rejects_what_wallets_cannot_pay() {
+    let to = payer().public_key();
+    let too_long = "x".repeat(sep7::MAX_MEMO_LEN);
+    assert!(matches!(
+        PayRequest::for_invoice(&to, &too_long, Asset::Native, None),
+        Err(Error::PayRequest(_))
+    ));
+    assert!(matches!(
+        PayRequest::for_invoice(&to, "abc123", Asset::Native, Some(0)),
+        Err(Error::PayRequest(_))
+    ));
+    assert!(matches!(
+        PayRequest::for_invoice("GNOTANACCOUNT", "abc123", Asset::Native, None),
+        Err(Error::Strkey(_))
+    ));
+}
+
+// Dry runs
+
+#[test]
+fn test_scval_display_is_compact() {
+    let v = scval::map(vec![
+        ("amount", scval::i128(-5)),
+        ("memo", scval::string("hi").unwrap()),
+        ("tags", scval::vec(vec![scval::symbol("a").unwrap(), ScVal::U32(1)]).unwrap()),
+    ])
+    .unwrap();
+    assert_eq!(scval::display(&v), r#"{amount: -5, memo: "hi", tags: [a, 1]}"#);
+    let payer = payer().public_key();
+    assert_eq!(scval::display(&scval::address(&payer).unwrap()), payer);
+}
+
+#[test]
+fn test_failed_dry_run_keeps_diagnostics_and_drops_rolled_back_events() {
+    use stellar_xdr::curr::{
+        ContractEvent as XdrEvent, ContractEventBody, ContractEventType, ContractEventV0,
+        ContractId, DiagnosticEvent, ExtensionPoint, Hash, Limits, WriteXdr,
+    };
+
+    let contract = [3u8; 32];
+    let event = |type_, topic: &str, in_successful_contract_call| {
+        DiagnosticEvent {
+            in_successful_contract_call,
+            event: XdrEvent {
+                ext: ExtensionPoint::V0,
+                contract_id: Some(ContractId(Hash(contract))),
+                type_,
+                body: ContractEventBody::V0(ContractEventV0 {
+                    topics: vec![scval::symbol(topic).unwrap()].try_into().unwrap(),
+                    data: ScVal::U32(7),
+                }),
+            },
+        }
+        .to_xdr_base64(Limits::none())
+        .unwrap()
+    };
+    let response: rpc::SimulateTransactionResponse = serde_json::from_value(serde_json::json!({
+        "latestLedger": 10,
+        "error": "HostError: Error(Contract, #3)",
+        "events": [
+            event(ContractEventType::Diagnostic, "log", false),
+            event(ContractEventType::Contract, "payment_recorded", false),
+        ],
+    }))
+    .unwrap();
+
+    let t = tx::build_invoke([2u8; 32], 1, contract, "record_payment", vec![]).unwrap();
+    let contract_id = stellar_strkey::Contract(contract).to_string();
+    let run = DryRun::from_response(t, response, Some(&contract_id)).unwrap();
+    assert!(matches!(run.error, Some(Error::Contract(_))));
+    assert!(run.estimate.is_none());
+    assert!(run.events.is_empty());
+    assert_eq!(run.diagnostics, [format!("[{contract_id}] log: 7")]);
+}
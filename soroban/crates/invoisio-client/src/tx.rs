@@ -0,0 +1,485 @@
+//! Transaction building, assembly and signing.
+//!
+//! Every contract call is a single `InvokeHostFunction` operation. The flow is
+//! always: [`build_invoke`] → simulate → [`assemble`] → [`sign`] → submit.
+//! Deployment uses the same flow with [`build_upload`] / [`build_create`].
+//! Signing goes through [`Signer`], so the key need not live in-process.
+
+use ed25519_dalek::{Signer as _, SigningKey};
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{
+    AccountId, ContractExecutable, ContractId, ContractIdPreimage, ContractIdPreimageFromAddress,
+    CreateAccountOp, CreateContractArgs, DecoratedSignature, FeeBumpTransaction, FeeBumpTransactionEnvelope,
+    FeeBumpTransactionExt, FeeBumpTransactionInnerTx, Hash, HashIdPreimage,
+    HashIdPreimageContractId, HashIdPreimageSorobanAuthorization, HostFunction, InvokeContractArgs, PublicKey, InvokeHostFunctionOp, Limits, Memo, MuxedAccount, Operation,
+    OperationBody, Preconditions, ReadXdr, ScAddress, ScBytes, ScSymbol, ScVal, SequenceNumber,
+    Signature, SignatureHint, SorobanAuthorizationEntry, SorobanCredentials,
+    SorobanTransactionData, TimeBounds, TimePoint, Transaction, TransactionEnvelope, TransactionExt,
+    TransactionSignaturePayload, TransactionSignaturePayloadTaggedTransaction,
+    TransactionV1Envelope, Uint256, WriteXdr,
+};
+
+use crate::error::{Error, Result};
+use crate::scval;
+use crate::signer::Signer;
+
+/// Default inclusion fee (stroops) added on top of the simulated resource fee.
+pub const DEFAULT_BASE_FEE: u32 = 100;
+
+/// Network ID: SHA-256 of the network passphrase.
+pub fn network_id(passphrase: &str) -> Hash {
+    Hash(Sha256::digest(passphrase.as_bytes()).into())
+}
+
+/// An ed25519 keypair used as transaction source and signer.
+#[derive(Clone)]
+pub struct Keypair {
+    signing: SigningKey,
+}
+
+impl Keypair {
+    /// Parse an `S...` secret seed.
+    pub fn from_secret(secret: &str) -> Result<Self> {
+        let seed = stellar_strkey::ed25519::PrivateKey::from_string(secret)
+            .map_err(|_| Error::Strkey("<secret seed>".into()))?;
+        Ok(Self::from_seed(seed.0))
+    }
+
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self {
+            signing: SigningKey::from_bytes(&seed),
+        }
+    }
+
+    pub fn public_key_bytes(&self) -> [u8; 32] {
+        self.signing.verifying_key().to_bytes()
+    }
+
+    /// `G...` strkey of the public key.
+    pub fn public_key(&self) -> String {
+        stellar_strkey::ed25519::PublicKey(self.public_key_bytes()).to_string()
+    }
+
+    /// `S...` strkey of the secret seed, for writing a key file.
+    pub fn secret(&self) -> String {
+        stellar_strkey::ed25519::PrivateKey(self.signing.to_bytes()).to_string()
+    }
+
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        self.signing.sign(message).to_bytes()
+    }
+
+    /// Sign `payload` and wrap it with this key's hint.
+    pub fn sign_decorated(&self, payload: &[u8]) -> Result<DecoratedSignature> {
+        decorated(self.public_key_bytes(), self.sign(payload))
+    }
+}
+
+/// Wrap `signature` by `public_key` with the key's hint.
+pub fn decorated(public_key: [u8; 32], signature: [u8; 64]) -> Result<DecoratedSignature> {
+    let pk = public_key;
+    Ok(DecoratedSignature {
+        hint: SignatureHint([pk[28], pk[29], pk[30], pk[31]]),
+        signature: Signature(signature.to_vec().try_into()?),
+    })
+}
+
+impl core::fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Never print the secret.
+        f.debug_struct("Keypair")
+            .field("public_key", &self.public_key())
+            .finish()
+    }
+}
+
+/// Decode a `C...` contract strkey into the 32-byte contract id.
+pub fn contract_id(strkey: &str) -> Result<[u8; 32]> {
+    stellar_strkey::Contract::from_string(strkey)
+        .map(|c| c.0)
+        .map_err(|_| Error::Strkey(strkey.to_string()))
+}
+
+/// Decode a `G...` account strkey into its raw public key.
+pub fn account_id(strkey: &str) -> Result<[u8; 32]> {
+    stellar_strkey::ed25519::PublicKey::from_string(strkey)
+        .map(|pk| pk.0)
+        .map_err(|_| Error::Strkey(strkey.to_string()))
+}
+
+/// Build an unsigned, un-simulated transaction invoking `function` on
+/// `contract` with `args`. `sequence` is the **next** sequence number
+/// (account sequence + 1).
+pub fn build_invoke(
+    source: [u8; 32],
+    sequence: i64,
+    contract: [u8; 32],
+    function: &str,
+    args: Vec<ScVal>,
+) -> Result<Transaction> {
+    build_host_function(
+        source,
+        sequence,
+        HostFunction::InvokeContract(InvokeContractArgs {
+            contract_address: ScAddress::Contract(ContractId(Hash(contract))),
+            function_name: ScSymbol(function.try_into()?),
+            args: args.try_into()?,
+        }),
+    )
+}
+
+/// Build a transaction uploading `wasm` to the ledger.
+pub fn build_upload(source: [u8; 32], sequence: i64, wasm: &[u8]) -> Result<Transaction> {
+    build_host_function(
+        source,
+        sequence,
+        HostFunction::UploadContractWasm(wasm.to_vec().try_into()?),
+    )
+}
+
+/// Build a transaction creating a contract instance of `wasm_hash`, with
+/// its address derived from `source` and `salt`.
+pub fn build_create(
+    source: [u8; 32],
+    sequence: i64,
+    wasm_hash: [u8; 32],
+    salt: [u8; 32],
+) -> Result<Transaction> {
+    build_host_function(
+        source,
+        sequence,
+        HostFunction::CreateContract(CreateContractArgs {
+            contract_id_preimage: contract_id_preimage(source, salt),
+            executable: ContractExecutable::Wasm(Hash(wasm_hash)),
+        }),
+    )
+}
+
+/// SHA-256 of `wasm`, the key the ledger stores uploaded code under.
+pub fn wasm_hash(wasm: &[u8]) -> [u8; 32] {
+    Sha256::digest(wasm).into()
+}
+
+/// Address [`build_create`] will deploy to, as a `C...` strkey.
+pub fn deployed_contract_id(
+    deployer: [u8; 32],
+    salt: [u8; 32],
+    network_passphrase: &str,
+) -> Result<String> {
+    let preimage = HashIdPreimage::ContractId(HashIdPreimageContractId {
+        network_id: network_id(network_passphrase),
+        contract_id_preimage: contract_id_preimage(deployer, salt),
+    });
+    let id: [u8; 32] = Sha256::digest(preimage.to_xdr(Limits::none())?).into();
+    Ok(stellar_strkey::Contract(id).to_string())
+}
+
+fn contract_id_preimage(deployer: [u8; 32], salt: [u8; 32]) -> ContractIdPreimage {
+    ContractIdPreimage::Address(ContractIdPreimageFromAddress {
+        address: ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(
+            deployer,
+        )))),
+        salt: Uint256(salt),
+    })
+}
+
+fn build_host_function(
+    source: [u8; 32],
+    sequence: i64,
+    host_function: HostFunction,
+) -> Result<Transaction> {
+    build_single(
+        source,
+        sequence,
+        OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function,
+            auth: Default::default(),
+        }),
+    )
+}
+
+/// Build a classic transaction creating `destination` with
+/// `starting_balance` stroops of XLM paid by `source`. Needs no simulation.
+pub fn build_create_account(
+    source: [u8; 32],
+    sequence: i64,
+    destination: [u8; 32],
+    starting_balance: i64,
+) -> Result<Transaction> {
+    build_single(
+        source,
+        sequence,
+        OperationBody::CreateAccount(CreateAccountOp {
+            destination: AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(destination))),
+            starting_balance,
+        }),
+    )
+}
+
+fn build_single(source: [u8; 32], sequence: i64, body: OperationBody) -> Result<Transaction> {
+    let op = Operation {
+        source_account: None,
+        body,
+    };
+    Ok(Transaction {
+        source_account: MuxedAccount::Ed25519(Uint256(source)),
+        fee: DEFAULT_BASE_FEE,
+        seq_num: SequenceNumber(sequence),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: vec![op].try_into()?,
+        ext: TransactionExt::V0,
+    })
+}
+
+/// Apply simulation output to `tx`: attach the Soroban resources, add the
+/// resource fee to the inclusion fee, and attach the recorded auth entries.
+pub fn assemble(
+    mut tx: Transaction,
+    transaction_data: &str,
+    min_resource_fee: i64,
+    auth: Vec<SorobanAuthorizationEntry>,
+) -> Result<Transaction> {
+    let data = SorobanTransactionData::from_xdr_base64(transaction_data, Limits::none())?;
+    let resource_fee = u32::try_from(min_resource_fee)
+        .map_err(|_| Error::Simulation(format!("resource fee {min_resource_fee} out of range")))?;
+
+    tx.fee = tx.fee.saturating_add(resource_fee);
+    tx.ext = TransactionExt::V1(data);
+
+    // Simulating a transaction that already carries signed auth returns no
+    // new entries; keep the signed ones in that case.
+    if auth.is_empty() {
+        Ok(tx)
+    } else {
+        set_auth(tx, auth)
+    }
+}
+
+/// Undo [`assemble`]: drop the Soroban resources and their fee but keep
+/// the auth entries, so `tx` can be simulated again (e.g. once its auth
+/// entries are signed).
+pub fn reset_resources(mut tx: Transaction) -> Transaction {
+    if let TransactionExt::V1(data) = &tx.ext {
+        let resource_fee = u32::try_from(data.resource_fee).unwrap_or(0);
+        tx.fee = tx.fee.saturating_sub(resource_fee);
+    }
+    tx.ext = TransactionExt::V0;
+    tx
+}
+
+/// Auth entries of the invoke operation in `tx`; empty for classic
+/// transactions.
+pub fn auth_entries(tx: &Transaction) -> &[SorobanAuthorizationEntry] {
+    match tx.operations.first() {
+        Some(Operation {
+            body: OperationBody::InvokeHostFunction(op),
+            ..
+        }) => op.auth.as_slice(),
+        _ => &[],
+    }
+}
+
+/// Replace the auth entries of the invoke operation in `tx`.
+pub fn set_auth(mut tx: Transaction, auth: Vec<SorobanAuthorizationEntry>) -> Result<Transaction> {
+    let mut ops = tx.operations.to_vec();
+    if let Some(Operation {
+        body: OperationBody::InvokeHostFunction(op),
+        ..
+    }) = ops.first_mut()
+    {
+        op.auth = auth.try_into()?;
+    }
+    tx.operations = ops.try_into()?;
+    Ok(tx)
+}
+
+/// Make `tx` invalid after `max_time` (Unix seconds). A transaction with
+/// no deadline can sit in a mempool and land arbitrarily late; one with a
+/// deadline is definitely dead once it passes.
+pub fn set_deadline(mut tx: Transaction, max_time: u64) -> Transaction {
+    tx.cond = Preconditions::Time(TimeBounds {
+        min_time: TimePoint(0),
+        max_time: TimePoint(max_time),
+    });
+    tx
+}
+
+/// Hash that every signer of `tx` on `network_passphrase` signs.
+pub fn transaction_hash(tx: &Transaction, network_passphrase: &str) -> Result<[u8; 32]> {
+    let payload = TransactionSignaturePayload {
+        network_id: network_id(network_passphrase),
+        tagged_transaction: TransactionSignaturePayloadTaggedTransaction::Tx(tx.clone()),
+    };
+    let bytes = payload.to_xdr(Limits::none())?;
+    Ok(Sha256::digest(bytes).into())
+}
+
+/// Hex hash of a signed envelope, as `sendTransaction` and
+/// `getTransaction` report it.
+pub fn envelope_hash(envelope: &TransactionEnvelope, network_passphrase: &str) -> Result<String> {
+    let hash = match envelope {
+        TransactionEnvelope::Tx(v1) => transaction_hash(&v1.tx, network_passphrase)?,
+        TransactionEnvelope::TxFeeBump(bump) => fee_bump_hash(&bump.tx, network_passphrase)?,
+        TransactionEnvelope::TxV0(_) => {
+            return Err(Error::Decode("v0 envelopes are not supported".into()))
+        }
+    };
+    Ok(crate::signer::to_hex(&hash))
+}
+
+/// Sign `tx` with `signer` and wrap it in a V1 envelope.
+pub async fn sign<S: Signer>(
+    tx: Transaction,
+    network_passphrase: &str,
+    signer: &S,
+) -> Result<TransactionEnvelope> {
+    let hash = transaction_hash(&tx, network_passphrase)?;
+    let signature = decorated(signer.public_key_bytes(), signer.sign_hash(hash).await?)?;
+    Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: vec![signature].try_into()?,
+    }))
+}
+
+/// Wrap `tx` in an envelope without signatures (for simulation).
+pub fn unsigned(tx: Transaction) -> TransactionEnvelope {
+    TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: Default::default(),
+    })
+}
+
+/// Wrap a signed V1 `inner` envelope in a fee-bump transaction paid by
+/// `fee_source`. `fee` is the total the sponsor pays and must cover the
+/// inner fee plus one more inclusion fee.
+///
+/// The inner transaction's signatures stay valid, so its source only needs
+/// to exist; the fee source pays everything.
+pub fn fee_bump(
+    inner: TransactionEnvelope,
+    fee_source: [u8; 32],
+    fee: i64,
+) -> Result<FeeBumpTransaction> {
+    let TransactionEnvelope::Tx(inner) = inner else {
+        return Err(Error::Decode("fee bump needs a signed v1 envelope".into()));
+    };
+    Ok(FeeBumpTransaction {
+        fee_source: MuxedAccount::Ed25519(Uint256(fee_source)),
+        fee,
+        inner_tx: FeeBumpTransactionInnerTx::Tx(inner),
+        ext: FeeBumpTransactionExt::V0,
+    })
+}
+
+/// Hash the fee source of `tx` on `network_passphrase` signs.
+pub fn fee_bump_hash(tx: &FeeBumpTransaction, network_passphrase: &str) -> Result<[u8; 32]> {
+    let payload = TransactionSignaturePayload {
+        network_id: network_id(network_passphrase),
+        tagged_transaction: TransactionSignaturePayloadTaggedTransaction::TxFeeBump(tx.clone()),
+    };
+    let bytes = payload.to_xdr(Limits::none())?;
+    Ok(Sha256::digest(bytes).into())
+}
+
+/// Sign the fee bump `tx` with `signer` (its fee source) and wrap it in an
+/// envelope.
+pub async fn sign_fee_bump<S: Signer>(
+    tx: FeeBumpTransaction,
+    network_passphrase: &str,
+    signer: &S,
+) -> Result<TransactionEnvelope> {
+    let hash = fee_bump_hash(&tx, network_passphrase)?;
+    let signature = decorated(signer.public_key_bytes(), signer.sign_hash(hash).await?)?;
+    Ok(TransactionEnvelope::TxFeeBump(FeeBumpTransactionEnvelope {
+        tx,
+        signatures: vec![signature].try_into()?,
+    }))
+}
+
+/// Sign a simulated `SorobanAuthorizationEntry` with `signer`.
+///
+/// Needed whenever an address other than the transaction source must
+/// authorise the call — e.g. the incoming admin co-signing `set_admin`.
+/// Entries using source-account credentials are returned unchanged.
+pub async fn sign_auth_entry<S: Signer>(
+    mut entry: SorobanAuthorizationEntry,
+    signer: &S,
+    network_passphrase: &str,
+    signature_expiration_ledger: u32,
+) -> Result<SorobanAuthorizationEntry> {
+    if let SorobanCredentials::Address(creds) = &mut entry.credentials {
+        creds.signature_expiration_ledger = signature_expiration_ledger;
+        creds.signature = ScVal::Void;
+    }
+    cosign_auth_entry(entry, signer, network_passphrase).await
+}
+
+/// Add `signer`'s signature to `entry`, keeping signatures other keys
+/// already made. For accounts whose auth needs several signers; the entry
+/// must already carry its signature expiration ledger.
+pub async fn cosign_auth_entry<S: Signer>(
+    entry: SorobanAuthorizationEntry,
+    signer: &S,
+    network_passphrase: &str,
+) -> Result<SorobanAuthorizationEntry> {
+    let SorobanCredentials::Address(mut creds) = entry.credentials.clone() else {
+        return Ok(entry);
+    };
+
+    let preimage = HashIdPreimage::SorobanAuthorization(HashIdPreimageSorobanAuthorization {
+        network_id: network_id(network_passphrase),
+        nonce: creds.nonce,
+        signature_expiration_ledger: creds.signature_expiration_ledger,
+        invocation: entry.root_invocation.clone(),
+    });
+    let payload: [u8; 32] = Sha256::digest(preimage.to_xdr(Limits::none())?).into();
+
+    // Account signature format expected by the host:
+    // Vec<Map{public_key, signature}>, sorted by public key.
+    let public_key = signer.public_key_bytes();
+    let signed = signer.sign_hash(payload).await?;
+    let signature = scval::map(vec![
+        (
+            "public_key",
+            ScVal::Bytes(ScBytes(public_key.to_vec().try_into()?)),
+        ),
+        (
+            "signature",
+            ScVal::Bytes(ScBytes(signed.to_vec().try_into()?)),
+        ),
+    ])?;
+    let mut signatures = match &creds.signature {
+        ScVal::Vec(Some(existing)) => existing.to_vec(),
+        _ => Vec::new(),
+    };
+    signatures.retain(|s| signature_key(s) != Some(public_key));
+    signatures.push(signature);
+    signatures.sort_by_key(signature_key);
+    creds.signature = scval::vec(signatures)?;
+
+    Ok(SorobanAuthorizationEntry {
+        credentials: SorobanCredentials::Address(creds),
+        root_invocation: entry.root_invocation,
+    })
+}
+
+/// Public key of one account signature map.
+fn signature_key(signature: &ScVal) -> Option<[u8; 32]> {
+    match scval::map_get(signature, "public_key").ok()? {
+        ScVal::Bytes(bytes) => bytes.0.as_slice().try_into().ok(),
+        _ => None,
+    }
+}
+
+/// Return `true` if `entry` must be signed by `public_key` (i.e. it carries
+/// address credentials for that account).
+pub fn auth_entry_needs(entry: &SorobanAuthorizationEntry, public_key: [u8; 32]) -> bool {
+    matches!(
+        &entry.credentials,
+        SorobanCredentials::Address(creds)
+            if creds.address == ScAddress::Account(AccountId(
+                PublicKey::PublicKeyTypeEd25519(Uint256(public_key)),
+            ))
+    )
+}
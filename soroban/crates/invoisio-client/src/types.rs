@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+
+/// Off-chain mirror of the contract's `ContractError`.
+///
+/// Codes **must** match `contracts/invoice-payment/src/errors.rs` exactly —
+/// they are part of the on-chain ABI. Only add new variants at the end.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum ContractError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    PaymentAlreadyRecorded = 3,
+    PaymentNotFound = 4,
+    InvalidAmount = 5,
+    InvalidInvoiceId = 6,
+    InvalidAsset = 7,
+    PaymentAlreadyPending = 8,
+    PendingPaymentNotFound = 9,
+    MerchantRegistryNotSet = 10,
+    UnknownMerchant = 11,
+    MerchantInactive = 12,
+    PayerNotVerified = 13,
+    PayerHighRisk = 14,
+    PayerBlocked = 15,
+    LimitExceeded = 16,
+    Paused = 17,
+    UsdcIssuerAlreadySet = 18,
+    MetadataTooLarge = 19,
+    PaymentCompacted = 20,
+    TermsNotAccepted = 21,
+    QuoteNotFound = 22,
+    QuoteExpired = 23,
+    QuoteAlreadyAccepted = 24,
+    RefundExceedsPayment = 25,
+    InvoiceScheduleExists = 26,
+    InvoiceScheduleNotFound = 27,
+    InvoiceOverpaid = 28,
+    InvalidSchedule = 29,
+    InvoiceExpired = 30,
+    InvoiceNotExpirable = 31,
+    InvalidTimestamp = 32,
+    TooManyObservers = 33,
+    PaymentRejected = 34,
+    NotPreauthorized = 35,
+    SigningKeyNotSet = 36,
+    InvalidNonce = 37,
+    AlreadyCollected = 38,
+    BatchFull = 39,
+    NothingToSettle = 40,
+    SettlementNotFound = 41,
+    OutsidePayoutWindow = 42,
+    CreditNoteNotFound = 43,
+    CreditNoteExists = 44,
+    CreditNoteClosed = 45,
+    CreditExceedsRemaining = 46,
+    PayerMismatch = 47,
+    InvalidTax = 48,
+    AmountMismatch = 49,
+    ChargebackExists = 50,
+    ChargebackNotFound = 51,
+    ChargebackResolved = 52,
+    TokenMismatch = 53,
+    InvalidRemittance = 54,
+}
+
+impl TryFrom<u32> for ContractError {
+    type Error = ();
+
+    fn try_from(code: u32) -> Result<Self, ()> {
+        Ok(match code {
+            1 => Self::AlreadyInitialized,
+            2 => Self::NotInitialized,
+            3 => Self::PaymentAlreadyRecorded,
+            4 => Self::PaymentNotFound,
+            5 => Self::InvalidAmount,
+            6 => Self::InvalidInvoiceId,
+            7 => Self::InvalidAsset,
+            8 => Self::PaymentAlreadyPending,
+            9 => Self::PendingPaymentNotFound,
+            10 => Self::MerchantRegistryNotSet,
+            11 => Self::UnknownMerchant,
+            12 => Self::MerchantInactive,
+            13 => Self::PayerNotVerified,
+            14 => Self::PayerHighRisk,
+            15 => Self::PayerBlocked,
+            16 => Self::LimitExceeded,
+            17 => Self::Paused,
+            18 => Self::UsdcIssuerAlreadySet,
+            19 => Self::MetadataTooLarge,
+            20 => Self::PaymentCompacted,
+            21 => Self::TermsNotAccepted,
+            22 => Self::QuoteNotFound,
+            23 => Self::QuoteExpired,
+            24 => Self::QuoteAlreadyAccepted,
+            25 => Self::RefundExceedsPayment,
+            26 => Self::InvoiceScheduleExists,
+            27 => Self::InvoiceScheduleNotFound,
+            28 => Self::InvoiceOverpaid,
+            29 => Self::InvalidSchedule,
+            30 => Self::InvoiceExpired,
+            31 => Self::InvoiceNotExpirable,
+            32 => Self::InvalidTimestamp,
+            33 => Self::TooManyObservers,
+            34 => Self::PaymentRejected,
+            35 => Self::NotPreauthorized,
+            36 => Self::SigningKeyNotSet,
+            37 => Self::InvalidNonce,
+            38 => Self::AlreadyCollected,
+            39 => Self::BatchFull,
+            40 => Self::NothingToSettle,
+            41 => Self::SettlementNotFound,
+            42 => Self::OutsidePayoutWindow,
+            43 => Self::CreditNoteNotFound,
+            44 => Self::CreditNoteExists,
+            45 => Self::CreditNoteClosed,
+            46 => Self::CreditExceedsRemaining,
+            47 => Self::PayerMismatch,
+            48 => Self::InvalidTax,
+            49 => Self::AmountMismatch,
+            50 => Self::ChargebackExists,
+            51 => Self::ChargebackNotFound,
+            52 => Self::ChargebackResolved,
+            53 => Self::TokenMismatch,
+            54 => Self::InvalidRemittance,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// Off-chain mirror of the contract's `Asset` enum.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Asset {
+    /// Native XLM.
+    Native,
+    /// Stellar-issued token, e.g. `USDC:GBBD47...`.
+    Token { code: String, issuer: String },
+}
+
+impl Asset {
+    /// Asset code as passed to `record_payment` (`"XLM"` for native).
+    pub fn code(&self) -> &str {
+        match self {
+            Asset::Native => "XLM",
+            Asset::Token { code, .. } => code,
+        }
+    }
+
+    /// Issuer as passed to `record_payment` (`""` for native).
+    pub fn issuer(&self) -> &str {
+        match self {
+            Asset::Native => "",
+            Asset::Token { issuer, .. } => issuer,
+        }
+    }
+
+    /// Build an [`Asset`] from the `(asset_code, asset_issuer)` pair the
+    /// contract accepts. Mirrors the contract's own rule: `"XLM"` is native,
+    /// anything else is a token.
+    pub fn from_code_issuer(code: &str, issuer: &str) -> Self {
+        if code == "XLM" {
+            Asset::Native
+        } else {
+            Asset::Token {
+                code: code.to_string(),
+                issuer: issuer.to_string(),
+            }
+        }
+    }
+}
+
+/// Off-chain mirror of the contract's `PaymentRecord`.
+///
+/// Addresses are kept as strkeys (`G...` / `C...`) so the type serializes to
+/// human-readable JSON for indexers and webhooks.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PaymentRecord {
+    pub invoice_id: String,
+    pub payer: String,
+    /// Mux id when the payer paid from a muxed (`M...`) address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payer_mux_id: Option<u64>,
+    pub asset: Asset,
+    /// Amount in the asset's smallest unit. Serialized as a string because
+    /// JSON numbers cannot carry the full `i128` range.
+    #[serde(with = "i128_string")]
+    pub amount: i128,
+    /// Ledger Unix timestamp (seconds) at recording time.
+    pub timestamp: u64,
+}
+
+/// Arguments of a `record_payment` call.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RecordPayment {
+    pub invoice_id: String,
+    /// `G...` account, `M...` muxed account or `C...` contract strkey.
+    pub payer: String,
+    pub asset: Asset,
+    #[serde(with = "i128_string")]
+    pub amount: i128,
+}
+
+pub(crate) mod i128_string {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &i128, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<i128, D::Error> {
+        let s = String::deserialize(d)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
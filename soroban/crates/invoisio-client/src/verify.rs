@@ -0,0 +1,119 @@
+//! Cross-checking a recorded payment against the Horizon payment operation
+//! it claims to record.
+//!
+//! The contract only knows what the recorder told it. [`compare`] checks
+//! each field of the [`PaymentRecord`] against the operation the payer
+//! actually submitted — memo, asset, amount and source — and lists every
+//! field that disagrees, so a consumer can flag the record for review
+//! instead of trusting it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::horizon::PaymentOperation;
+use crate::types::{i128_string, Asset, PaymentRecord};
+
+/// One field on which the record and the operation disagree.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "field", rename_all = "snake_case")]
+pub enum Discrepancy {
+    /// No successful payment from the payer carries the invoice id as its
+    /// text memo.
+    OperationNotFound,
+    /// The operation's text memo is not the invoice id (`None` if it has no
+    /// text memo).
+    Memo {
+        recorded: String,
+        paid: Option<String>,
+    },
+    /// `paid` is `None` for an asset type the record cannot hold.
+    Asset {
+        recorded: Asset,
+        paid: Option<Asset>,
+    },
+    /// `paid` is `None` if Horizon's amount could not be parsed.
+    Amount {
+        #[serde(with = "i128_string")]
+        recorded: i128,
+        paid: Option<String>,
+    },
+    /// Source account or mux id differ.
+    Source {
+        recorded: String,
+        recorded_mux_id: Option<u64>,
+        paid: Option<String>,
+        paid_mux_id: Option<u64>,
+    },
+}
+
+/// Result of [`crate::InvoicePaymentClient::verify_payment`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub record: PaymentRecord,
+    /// Id of the Horizon operation checked against, if one was found.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<String>,
+    /// Empty when the record matches the operation.
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl VerificationReport {
+    /// Check `record` against `operation`, or report it missing.
+    pub fn new(record: PaymentRecord, operation: Option<&PaymentOperation>) -> Self {
+        let discrepancies = match operation {
+            Some(op) => compare(&record, op),
+            None => vec![Discrepancy::OperationNotFound],
+        };
+        Self {
+            record,
+            operation_id: operation.map(|op| op.id.clone()),
+            transaction_hash: operation.map(|op| op.transaction_hash.clone()),
+            discrepancies,
+        }
+    }
+
+    /// Whether the record matches the operation on every field.
+    pub fn is_match(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+/// Every field on which `record` disagrees with `op`.
+pub fn compare(record: &PaymentRecord, op: &PaymentOperation) -> Vec<Discrepancy> {
+    let mut found = Vec::new();
+
+    if op.memo() != Some(record.invoice_id.as_str()) {
+        found.push(Discrepancy::Memo {
+            recorded: record.invoice_id.clone(),
+            paid: op.memo().map(str::to_string),
+        });
+    }
+
+    let asset = op.asset();
+    if asset.as_ref() != Some(&record.asset) {
+        found.push(Discrepancy::Asset {
+            recorded: record.asset.clone(),
+            paid: asset,
+        });
+    }
+
+    if op.amount() != Some(record.amount) {
+        found.push(Discrepancy::Amount {
+            recorded: record.amount,
+            paid: op.amount.clone(),
+        });
+    }
+
+    let mux_id = op.from_muxed_id.as_deref().and_then(|id| id.parse().ok());
+    if op.from.as_deref() != Some(record.payer.as_str()) || mux_id != record.payer_mux_id {
+        found.push(Discrepancy::Source {
+            recorded: record.payer.clone(),
+            recorded_mux_id: record.payer_mux_id,
+            paid: op.from.clone(),
+            paid_mux_id: mux_id,
+        });
+    }
+
+    found
+}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use invoisio_client::Network;
+
+/// Harness configuration, read from `INVOISIO_E2E_*` environment variables.
+/// Defaults match `docker-compose.quickstart.yml`.
+///
+/// | Variable                  | Default                                             |
+/// |---------------------------|-----------------------------------------------------|
+/// | `INVOISIO_E2E_RPC_URL`    | `http://localhost:8000/soroban/rpc`                 |
+/// | `INVOISIO_E2E_FRIENDBOT`  | `http://localhost:8000/friendbot`                   |
+/// | `INVOISIO_E2E_PASSPHRASE` | `Standalone Network ; February 2017`                |
+/// | `INVOISIO_E2E_WASM`       | `target/wasm32v1-none/release/invoice_payment.wasm` |
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub rpc_url: String,
+    pub friendbot_url: String,
+    pub network_passphrase: String,
+    pub wasm: PathBuf,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Self::from_env_for(Network::Local)
+    }
+
+    /// Like [`from_env`](Self::from_env), but defaulting to `network`'s RPC,
+    /// passphrase and the Friendbot behind its Horizon.
+    pub fn from_env_for(network: Network) -> Self {
+        let defaults = network.defaults();
+        Self {
+            rpc_url: optional("INVOISIO_E2E_RPC_URL").unwrap_or(defaults.rpc_url),
+            friendbot_url: optional("INVOISIO_E2E_FRIENDBOT")
+                .unwrap_or_else(|| format!("{}/friendbot", defaults.horizon_url)),
+            network_passphrase: optional("INVOISIO_E2E_PASSPHRASE").unwrap_or(defaults.passphrase),
+            wasm: optional("INVOISIO_E2E_WASM").map(PathBuf::from).unwrap_or_else(|| {
+                // Workspace target dir, relative to this crate.
+                PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                    .join("../../target/wasm32v1-none/release/invoice_payment.wasm")
+            }),
+        }
+    }
+}
+
+fn optional(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
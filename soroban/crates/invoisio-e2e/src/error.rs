@@ -0,0 +1,20 @@
+/// Errors surfaced by the end-to-end harness.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Friendbot transport failure.
+    #[error("http: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// Contract / Soroban RPC failure.
+    #[error(transparent)]
+    Client(#[from] invoisio_client::Error),
+
+    /// The contract WASM has not been built.
+    #[error("reading {path}: {source} (run `make -C contracts/invoice-payment build`)")]
+    Wasm {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
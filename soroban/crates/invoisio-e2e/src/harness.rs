@@ -0,0 +1,94 @@
+//! Network setup shared by the end-to-end tests.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use invoisio_client::{Deployment, InvoicePaymentClient, Keypair, RpcClient};
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub struct Harness {
+    config: Config,
+    rpc: RpcClient,
+    http: reqwest::Client,
+}
+
+impl Harness {
+    pub fn new(config: Config) -> Self {
+        Self {
+            rpc: RpcClient::new(&config.rpc_url),
+            http: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(Config::from_env())
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    /// A fresh account, funded by Friendbot.
+    pub async fn funded_account(&self) -> Result<Keypair> {
+        let keypair = Keypair::from_seed(fresh_seed());
+        self.http
+            .get(&self.config.friendbot_url)
+            .query(&[("addr", keypair.public_key())])
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(keypair)
+    }
+
+    /// Deploy a new contract instance with `admin` as deployer and admin,
+    /// and initialise it.
+    pub async fn deploy(&self, admin: &Keypair) -> Result<(InvoicePaymentClient, Deployment)> {
+        let wasm = std::fs::read(&self.config.wasm).map_err(|source| Error::Wasm {
+            path: self.config.wasm.display().to_string(),
+            source,
+        })?;
+        let deployment = invoisio_client::deploy(
+            &self.rpc,
+            &self.config.network_passphrase,
+            admin,
+            &wasm,
+            fresh_seed(),
+            CONFIRM_TIMEOUT,
+        )
+        .await?;
+
+        let client = InvoicePaymentClient::new(
+            &self.config.rpc_url,
+            &self.config.network_passphrase,
+            &deployment.contract_id,
+        )?
+        .with_confirm_timeout(CONFIRM_TIMEOUT);
+        client.initialize(admin, &admin.public_key()).await?;
+        Ok((client, deployment))
+    }
+}
+
+/// 32 bytes that differ per call and per run. Not secret-grade; these keys
+/// only ever hold Friendbot XLM on a throwaway network.
+pub fn fresh_seed() -> [u8; 32] {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+            .to_le_bytes(),
+    );
+    hasher.update(std::process::id().to_le_bytes());
+    hasher.update(COUNTER.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+    hasher.finalize().into()
+}
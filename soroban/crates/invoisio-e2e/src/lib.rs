@@ -0,0 +1,34 @@
+//! # invoisio-e2e
+//!
+//! End-to-end tests that deploy the real WASM to a local
+//! `stellar/quickstart` network and drive it over Soroban RPC. They catch
+//! what mocked-auth unit tests cannot: footprint, auth-tree and fee issues.
+//!
+//! The tests are `#[ignore]`d so `cargo test` stays offline; run them with
+//! a quickstart up (see `docker-compose.quickstart.yml`):
+//!
+//! ```sh
+//! make -C contracts/invoice-payment build
+//! docker compose -f docker-compose.quickstart.yml up -d --wait
+//! cargo test -p invoisio-e2e -- --ignored --test-threads=1
+//! ```
+//!
+//! ## Module layout
+//! | Module       | Responsibility                                      |
+//! |--------------|-----------------------------------------------------|
+//! | `config.rs`  | `INVOISIO_E2E_*` environment configuration          |
+//! | `harness.rs` | [`Harness`]: Friendbot funding, deploy + initialise |
+//! | `load.rs`    | [`LoadTest`]: synthetic traffic for `loadtest`      |
+//! | `error.rs`   | [`Error`] / [`Result`]                              |
+
+pub mod config;
+pub mod error;
+pub mod harness;
+pub mod load;
+
+pub use config::Config;
+pub use error::{Error, Result};
+pub use harness::Harness;
+pub use load::LoadTest;
+
+mod test;
@@ -0,0 +1,318 @@
+//! Synthetic load for capacity planning (`loadtest` binary).
+//!
+//! [`LoadTest::run`] deploys a fresh instance, so the run never touches a
+//! live deployment's data or signing key. It then:
+//!
+//! 1. Funds `submitters` accounts and registers a throwaway signing key.
+//!    Each submitter can then call `record_payment_signed`. A Soroban
+//!    transaction per source account fits in a ledger, so record
+//!    throughput scales with submitters rather than with `rate`.
+//! 2. Starts one operation every `1 / rate` seconds until all `invoices`
+//!    are recorded. A `read_ratio` share are `get_payment` reads of
+//!    already-recorded invoices; the rest record the next invoice.
+//!
+//! Operations run concurrently, up to `max_in_flight`. Latency is measured
+//! from when an operation is started to when it finishes. It includes time
+//! spent waiting for its submitter, so a saturated setup shows up as
+//! latency, not as a silently lower rate.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use invoisio_client::client::signed_payment_message;
+use invoisio_client::{Asset, Error as ClientError, InvoicePaymentClient, Keypair, RecordPayment};
+use serde::Serialize;
+use tokio::sync::{Mutex as AsyncMutex, Semaphore};
+use tokio::task::JoinSet;
+
+use crate::error::Result;
+use crate::harness::{fresh_seed, Harness};
+
+/// Percentiles in every [`Summary`].
+pub const PERCENTILES: [f64; 4] = [50.0, 90.0, 95.0, 99.0];
+
+#[derive(Clone, Debug)]
+pub struct LoadTest {
+    pub invoices: u32,
+    /// Operations started per second.
+    pub rate: f64,
+    /// Share of operations that are reads, in `[0, 1)`.
+    pub read_ratio: f64,
+    pub submitters: usize,
+    pub max_in_flight: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+    Record,
+    Get,
+}
+
+/// Raw results for one kind of operation.
+#[derive(Debug, Default)]
+pub struct Stats {
+    latencies: Vec<Duration>,
+    failures: BTreeMap<String, u64>,
+}
+
+impl Stats {
+    pub fn success(&mut self, latency: Duration) {
+        self.latencies.push(latency);
+    }
+
+    pub fn failure(&mut self, kind: String) {
+        *self.failures.entry(kind).or_default() += 1;
+    }
+
+    pub fn summary(&self) -> Summary {
+        let mut sorted = self.latencies.clone();
+        sorted.sort();
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        Summary {
+            ok: sorted.len() as u64,
+            failed: self.failures.values().sum(),
+            latency_ms: PERCENTILES
+                .iter()
+                .filter_map(|p| Some((format!("p{p}"), ms(percentile(&sorted, *p)?))))
+                .chain(sorted.last().map(|max| ("max".to_string(), ms(*max))))
+                .collect(),
+            failures: self.failures.clone(),
+        }
+    }
+}
+
+/// Outcome counts, latency percentiles of successful operations, and
+/// failures by [`failure_kind`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Summary {
+    pub ok: u64,
+    pub failed: u64,
+    /// `p50`, `p90`, `p95`, `p99` and `max`; empty without successes.
+    pub latency_ms: BTreeMap<String, f64>,
+    pub failures: BTreeMap<String, u64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Report {
+    pub contract_id: String,
+    pub invoices: u32,
+    pub submitters: usize,
+    pub elapsed_secs: f64,
+    pub offered_rate: f64,
+    /// Operations finished per second, successful or not.
+    pub achieved_rate: f64,
+    pub ops: BTreeMap<Op, Summary>,
+}
+
+impl Report {
+    /// Plain-text table for the terminal.
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "contract {}\n{} invoices, {} submitters, {:.1}s, {:.2} op/s offered, {:.2} achieved\n\n",
+            self.contract_id,
+            self.invoices,
+            self.submitters,
+            self.elapsed_secs,
+            self.offered_rate,
+            self.achieved_rate
+        );
+        out.push_str(&format!(
+            "{:<8} {:>7} {:>7} {:>9} {:>9} {:>9} {:>9} {:>9}\n",
+            "op", "ok", "failed", "p50 ms", "p90 ms", "p95 ms", "p99 ms", "max ms"
+        ));
+        for (op, summary) in &self.ops {
+            let cell = |key: &str| match summary.latency_ms.get(key) {
+                Some(ms) => format!("{ms:.0}"),
+                None => "-".into(),
+            };
+            out.push_str(&format!(
+                "{:<8} {:>7} {:>7} {:>9} {:>9} {:>9} {:>9} {:>9}\n",
+                format!("{op:?}").to_lowercase(),
+                summary.ok,
+                summary.failed,
+                cell("p50"),
+                cell("p90"),
+                cell("p95"),
+                cell("p99"),
+                cell("max")
+            ));
+        }
+        for (op, summary) in &self.ops {
+            for (kind, n) in &summary.failures {
+                out.push_str(&format!(
+                    "\n{} failure {kind}: {n}",
+                    format!("{op:?}").to_lowercase()
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Nearest-rank percentile `p` (0-100] of ascending `sorted`.
+pub fn percentile(sorted: &[Duration], p: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// The `i`th operation of a run: reads are spread evenly at `read_ratio`,
+/// and there is nothing to read until something is recorded.
+pub fn schedule(i: u64, read_ratio: f64, recorded: usize) -> Op {
+    let reads = |n: u64| (n as f64 * read_ratio).floor() as u64;
+    if recorded > 0 && reads(i + 1) > reads(i) {
+        Op::Get
+    } else {
+        Op::Record
+    }
+}
+
+/// Low-cardinality failure label, specific enough to act on.
+pub fn failure_kind(err: &ClientError) -> String {
+    match err {
+        ClientError::Contract(e) => format!("contract:{e:?}"),
+        ClientError::UnknownContractError(code) => format!("contract:#{code}"),
+        ClientError::SendRejected { .. } if err.is_bad_seq() => "send:bad_seq".into(),
+        ClientError::SendRejected { status, .. } => format!("send:{status}"),
+        ClientError::TransactionFailed { .. } if err.is_bad_seq() => "tx_failed:bad_seq".into(),
+        ClientError::TransactionFailed { .. } => "tx_failed".into(),
+        ClientError::Timeout { .. } => "timeout".into(),
+        ClientError::Rpc { code, .. } => format!("rpc:{code}"),
+        ClientError::Http(e) if e.is_timeout() => "http:timeout".into(),
+        ClientError::Http(_) => "http".into(),
+        ClientError::Simulation(_) => "simulation".into(),
+        _ => "other".into(),
+    }
+}
+
+struct Shared {
+    client: InvoicePaymentClient,
+    signing_key: Keypair,
+    payer: String,
+    submitters: Vec<AsyncMutex<Keypair>>,
+    recorded: Mutex<Vec<String>>,
+    stats: Mutex<BTreeMap<Op, Stats>>,
+}
+
+impl Shared {
+    fn finish(&self, op: Op, started: Instant, result: Result<(), ClientError>) {
+        let mut stats = self.stats.lock().unwrap();
+        let stats = stats.entry(op).or_default();
+        match result {
+            Ok(()) => stats.success(started.elapsed()),
+            Err(err) => stats.failure(failure_kind(&err)),
+        }
+    }
+
+    async fn record(&self, n: u32, started: Instant) {
+        let payment = RecordPayment {
+            invoice_id: format!("load-{n}"),
+            payer: self.payer.clone(),
+            asset: Asset::Native,
+            amount: 10_000_000,
+        };
+        let result = async {
+            let message = signed_payment_message(self.client.contract_id(), &payment)?;
+            let signature = self.signing_key.sign(&message);
+            let submitter = self.submitters[n as usize % self.submitters.len()]
+                .lock()
+                .await;
+            self.client
+                .record_payment_signed(&*submitter, &payment, &signature)
+                .await
+        }
+        .await;
+        if result.is_ok() {
+            self.recorded.lock().unwrap().push(payment.invoice_id);
+        }
+        self.finish(Op::Record, started, result.map(drop));
+    }
+
+    async fn get(&self, i: u64, started: Instant) {
+        let invoice_id = {
+            let recorded = self.recorded.lock().unwrap();
+            // A fixed stride spreads reads over everything recorded so far.
+            recorded[(i as usize).wrapping_mul(7919) % recorded.len()].clone()
+        };
+        let result = self.client.get_payment(&invoice_id).await;
+        self.finish(Op::Get, started, result.map(drop));
+    }
+}
+
+impl LoadTest {
+    pub async fn run(&self, harness: &Harness) -> Result<Report> {
+        let admin = harness.funded_account().await?;
+        let (client, deployment) = harness.deploy(&admin).await?;
+        eprintln!("deployed {}", deployment.contract_id);
+
+        let signing_key = Keypair::from_seed(fresh_seed());
+        client
+            .set_signing_key(&admin, Some(signing_key.public_key_bytes()))
+            .await?;
+        let mut submitters = Vec::with_capacity(self.submitters);
+        for _ in 0..self.submitters.max(1) {
+            submitters.push(AsyncMutex::new(harness.funded_account().await?));
+        }
+        eprintln!("funded {} submitters", submitters.len());
+
+        let shared = Arc::new(Shared {
+            client,
+            signing_key,
+            payer: admin.public_key(),
+            submitters,
+            recorded: Mutex::default(),
+            stats: Mutex::default(),
+        });
+        let permits = Arc::new(Semaphore::new(self.max_in_flight.max(1)));
+        let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / self.rate));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut tasks = JoinSet::new();
+
+        let begun = Instant::now();
+        let mut next_invoice = 0;
+        let mut i = 0;
+        while next_invoice < self.invoices {
+            ticker.tick().await;
+            let started = Instant::now();
+            let permit = permits.clone().acquire_owned().await.unwrap();
+            let recorded = shared.recorded.lock().unwrap().len();
+            let shared = shared.clone();
+            match schedule(i, self.read_ratio, recorded) {
+                Op::Record => {
+                    let n = next_invoice;
+                    next_invoice += 1;
+                    tasks.spawn(async move {
+                        shared.record(n, started).await;
+                        drop(permit);
+                    });
+                }
+                Op::Get => {
+                    tasks.spawn(async move {
+                        shared.get(i, started).await;
+                        drop(permit);
+                    });
+                }
+            }
+            i += 1;
+        }
+        while tasks.join_next().await.is_some() {}
+
+        let elapsed = begun.elapsed().as_secs_f64();
+        let stats = shared.stats.lock().unwrap();
+        let ops: BTreeMap<_, _> = stats.iter().map(|(op, s)| (*op, s.summary())).collect();
+        let finished: u64 = ops.values().map(|s| s.ok + s.failed).sum();
+        Ok(Report {
+            contract_id: deployment.contract_id,
+            invoices: self.invoices,
+            submitters: self.submitters.max(1),
+            elapsed_secs: elapsed,
+            offered_rate: self.rate,
+            achieved_rate: finished as f64 / elapsed,
+            ops,
+        })
+    }
+}
@@ -0,0 +1,93 @@
+//! # loadtest
+//!
+//! Drives record / get traffic at a fresh deployment and reports latency
+//! percentiles and failures; see [`invoisio_e2e::load`]. Targets testnet by
+//! default, funding its accounts from Friendbot:
+//!
+//! ```sh
+//! make -C contracts/invoice-payment build
+//! cargo run -p invoisio-e2e --bin loadtest -- --invoices 500 --rate 5 --submitters 16
+//! ```
+
+use std::str::FromStr;
+
+use clap::{CommandFactory, Parser};
+use invoisio_client::Network;
+use invoisio_e2e::load::LoadTest;
+use invoisio_e2e::{Config, Harness};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "loadtest",
+    version,
+    about = "Synthetic load against a fresh Invoisio deployment"
+)]
+struct Args {
+    /// Network whose RPC, passphrase and Friendbot are used. The
+    /// `INVOISIO_E2E_*` variables override each.
+    #[arg(long, value_parser = Network::from_str, default_value_t = Network::Testnet)]
+    network: Network,
+
+    /// Invoices to record.
+    #[arg(long, default_value_t = 100)]
+    invoices: u32,
+
+    /// Operations started per second.
+    #[arg(long, default_value_t = 2.0)]
+    rate: f64,
+
+    /// Share of operations that read a recorded invoice back.
+    #[arg(long, default_value_t = 0.5)]
+    read_ratio: f64,
+
+    /// Funded accounts submitting records in parallel.
+    #[arg(long, default_value_t = 4)]
+    submitters: usize,
+
+    /// Operations allowed in flight at once.
+    #[arg(long, default_value_t = 64)]
+    max_in_flight: usize,
+
+    /// Print the report as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    if !args.rate.is_finite() || args.rate <= 0.0 {
+        usage("--rate must be positive");
+    }
+    if !(0.0..1.0).contains(&args.read_ratio) {
+        usage("--read-ratio must be at least 0 and below 1");
+    }
+    if args.submitters == 0 || args.max_in_flight == 0 {
+        usage("--submitters and --max-in-flight must be at least 1");
+    }
+
+    let harness = Harness::new(Config::from_env_for(args.network));
+    let load = LoadTest {
+        invoices: args.invoices,
+        rate: args.rate,
+        read_ratio: args.read_ratio,
+        submitters: args.submitters,
+        max_in_flight: args.max_in_flight,
+    };
+    match load.run(&harness).await {
+        Ok(report) if args.json => {
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        }
+        Ok(report) => println!("{}", report.render()),
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn usage(message: &str) -> ! {
+    Args::command()
+        .error(clap::error::ErrorKind::InvalidValue, message)
+        .exit()
+}
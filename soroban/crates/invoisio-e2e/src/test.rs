@@ -0,0 +1,176 @@
+#![cfg(test)]
+
+use invoisio_client::{Asset, ContractError, ContractEvent, Error, EventStart, RecordPayment};
+
+use std::time::Duration;
+
+use crate::load::{self, Op, Stats};
+use crate::Harness;
+
+fn payment(invoice_id: &str, payer: &str, amount: i128) -> RecordPayment {
+    RecordPayment {
+        invoice_id: invoice_id.into(),
+        payer: payer.into(),
+        asset: Asset::Native,
+        amount,
+    }
+}
+
+// initialize / record / get / events
+
+#[tokio::test]
+#[ignore = "needs a local quickstart; see crate docs"]
+async fn test_record_and_read_back_over_rpc() {
+    let h = Harness::from_env();
+    let admin = h.funded_account().await.unwrap();
+    let payer = h.funded_account().await.unwrap();
+    let (client, deployment) = h.deploy(&admin).await.unwrap();
+
+    assert_eq!(client.admin().await.unwrap(), admin.public_key());
+    assert_eq!(client.payment_count().await.unwrap(), 0);
+
+    let p = payment("invoisio-e2e-1", &payer.public_key(), 10_000_000);
+    let outcome = client.record_payment(&admin, &p).await.unwrap();
+    assert!(outcome.ledger >= deployment.create.ledger);
+
+    let record = client.get_payment("invoisio-e2e-1").await.unwrap();
+    assert_eq!(record.payer, payer.public_key());
+    assert_eq!(record.asset, Asset::Native);
+    assert_eq!(record.amount, 10_000_000);
+    assert!(client.has_payment("invoisio-e2e-1").await.unwrap());
+    assert_eq!(client.payment_count().await.unwrap(), 1);
+
+    let page = client
+        .events(EventStart::Ledger(deployment.create.ledger), 100)
+        .await
+        .unwrap();
+    let recorded: Vec<_> = page
+        .events
+        .iter()
+        .filter_map(|e| match &e.event {
+            ContractEvent::PaymentRecorded { record } => Some((e, record)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(recorded.len(), 1);
+    let (envelope, event_record) = recorded[0];
+    assert_eq!(envelope.contract_id, deployment.contract_id);
+    assert_eq!(envelope.tx_hash.as_deref(), Some(outcome.hash.as_str()));
+    assert_eq!(event_record, &record);
+}
+
+#[tokio::test]
+#[ignore = "needs a local quickstart; see crate docs"]
+async fn test_duplicate_record_surfaces_contract_error() {
+    let h = Harness::from_env();
+    let admin = h.funded_account().await.unwrap();
+    let (client, _) = h.deploy(&admin).await.unwrap();
+
+    let p = payment("invoisio-e2e-dup", &admin.public_key(), 1);
+    client.record_payment(&admin, &p).await.unwrap();
+    let err = client.record_payment(&admin, &p).await.unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Contract(ContractError::PaymentAlreadyRecorded)
+    ));
+}
+
+#[tokio::test]
+#[ignore = "needs a local quickstart; see crate docs"]
+async fn test_non_admin_cannot_record() {
+    let h = Harness::from_env();
+    let admin = h.funded_account().await.unwrap();
+    let stranger = h.funded_account().await.unwrap();
+    let (client, _) = h.deploy(&admin).await.unwrap();
+
+    let p = payment("invoisio-e2e-stranger", &stranger.public_key(), 1);
+    assert!(client.record_payment(&stranger, &p).await.is_err());
+    assert!(!client.has_payment("invoisio-e2e-stranger").await.unwrap());
+}
+
+// Auth tree
+
+#[tokio::test]
+#[ignore = "needs a local quickstart; see crate docs"]
+async fn test_set_admin_with_co_signed_auth_entry() {
+    let h = Harness::from_env();
+    let admin = h.funded_account().await.unwrap();
+    let next = h.funded_account().await.unwrap();
+    let (client, _) = h.deploy(&admin).await.unwrap();
+
+    client.set_admin(&admin, &next).await.unwrap();
+    assert_eq!(client.admin().await.unwrap(), next.public_key());
+
+    let p = payment("invoisio-e2e-next-admin", &admin.public_key(), 1);
+    client.record_payment(&next, &p).await.unwrap();
+
+    let p = payment("invoisio-e2e-old-admin", &admin.public_key(), 1);
+    assert!(client.record_payment(&admin, &p).await.is_err());
+}
+
+// Load test
+
+#[test]
+fn test_percentile_uses_nearest_rank() {
+    let ms: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+    assert_eq!(load::percentile(&ms, 50.0), Some(Duration::from_millis(50)));
+    assert_eq!(load::percentile(&ms, 99.0), Some(Duration::from_millis(99)));
+    assert_eq!(
+        load::percentile(&ms, 100.0),
+        Some(Duration::from_millis(100))
+    );
+    assert_eq!(
+        load::percentile(&ms[..1], 90.0),
+        Some(Duration::from_millis(1))
+    );
+    assert_eq!(load::percentile(&[], 50.0), None);
+}
+
+#[test]
+fn test_schedule_spreads_reads_at_ratio() {
+    let ops: Vec<Op> = (0..10).map(|i| load::schedule(i, 0.3, 1)).collect();
+    let reads = ops.iter().filter(|op| **op == Op::Get).count();
+    assert_eq!(reads, 3);
+    assert_eq!(ops[..4], [Op::Record, Op::Record, Op::Record, Op::Get]);
+
+    // Nothing to read yet, or no reads asked for.
+    assert!((0..10).all(|i| load::schedule(i, 0.5, 0) == Op::Record));
+    assert!((0..10).all(|i| load::schedule(i, 0.0, 5) == Op::Record));
+}
+
+#[test]
+fn test_stats_summary_counts_and_groups_failures() {
+    let mut stats = Stats::default();
+    for ms in [30, 10, 20] {
+        stats.success(Duration::from_millis(ms));
+    }
+    stats.failure(load::failure_kind(&Error::Timeout { hash: "ab".into() }));
+    stats.failure("timeout".into());
+    stats.failure(load::failure_kind(&Error::Contract(
+        ContractError::PaymentAlreadyRecorded,
+    )));
+
+    let summary = stats.summary();
+    assert_eq!((summary.ok, summary.failed), (3, 3));
+    assert_eq!(summary.latency_ms["p50"], 20.0);
+    assert_eq!(summary.latency_ms["max"], 30.0);
+    assert_eq!(summary.failures["timeout"], 2);
+    assert_eq!(summary.failures["contract:PaymentAlreadyRecorded"], 1);
+
+    assert!(Stats::default().summary().latency_ms.is_empty());
+}
+
+#[test]
+fn test_failure_kind_separates_send_statuses() {
+    let rejected = Error::SendRejected {
+        hash: "ab".into(),
+        status: "TRY_AGAIN_LATER".into(),
+        error_result_xdr: None,
+    };
+    assert_eq!(load::failure_kind(&rejected), "send:TRY_AGAIN_LATER");
+    let rpc = Error::Rpc {
+        code: -32600,
+        message: "bad".into(),
+    };
+    assert_eq!(load::failure_kind(&rpc), "rpc:-32600");
+}
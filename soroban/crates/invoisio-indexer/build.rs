@@ -0,0 +1,10 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // The generated code is checked in under `src/generated`, so a normal
+    // build needs no `protoc`. Build with `--features protoc` after editing
+    // the proto to regenerate it.
+    #[cfg(feature = "protoc")]
+    tonic_build::configure()
+        .out_dir("src/generated")
+        .compile_protos(&["proto/payments.proto"], &["proto"])?;
+    Ok(())
+}
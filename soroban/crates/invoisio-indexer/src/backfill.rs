@@ -0,0 +1,130 @@
+//! Rebuilding the database from history.
+//!
+//! Two sources:
+//! - **RPC** — replay `getEvents` from a start ledger. Limited to the node's
+//!   retention window (a few days on public RPC).
+//! - **Archive** — a JSON-lines file of [`EventEnvelope`]s, as printed by
+//!   `invoisio events` or exported from a full-history event store. This is
+//!   the disaster-recovery path back to the contract's first ledger.
+//!
+//! Both feed the same [`Batch`] / [`Store::apply`] path as the live
+//! indexer. Writes are upserts and the checkpoint only moves forward, so a
+//! backfill is safe to re-run and to run alongside the live indexer.
+
+use std::io::BufRead;
+use std::path::Path;
+
+use invoisio_client::{EventEnvelope, EventPage, EventStart, InvoicePaymentClient};
+
+use crate::error::{Error, Result};
+use crate::rows::Batch;
+use crate::store::Store;
+
+/// Running totals, logged as the backfill proceeds.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub events: u64,
+    pub payments: u64,
+    pub last_ledger: Option<u32>,
+}
+
+impl Progress {
+    fn add(&mut self, batch: &Batch) {
+        self.events += batch.events.len() as u64;
+        self.payments += batch.payments.len() as u64;
+        if let Some(checkpoint) = &batch.checkpoint {
+            self.last_ledger = Some(checkpoint.ledger);
+        }
+    }
+}
+
+/// Replay `getEvents` from `from_ledger` up to and including `to_ledger`
+/// (or the latest ledger when `None`).
+pub async fn from_rpc(
+    client: &InvoicePaymentClient,
+    store: &Store,
+    from_ledger: u32,
+    to_ledger: Option<u32>,
+    page_limit: u32,
+) -> Result<Progress> {
+    let contract_id = client.contract_id();
+    let mut progress = Progress::default();
+    let mut start = EventStart::Ledger(from_ledger);
+
+    loop {
+        let mut page = client.events(start, page_limit).await?;
+        let full = page.events.len() >= page_limit as usize;
+
+        let mut done = !full;
+        if let Some(to) = to_ledger {
+            if page.events.iter().any(|e| e.ledger > to) {
+                page.events.retain(|e| e.ledger <= to);
+                // The server cursor points past events we dropped.
+                page.cursor = None;
+                done = true;
+            }
+        }
+
+        let batch = Batch::from_page(&page)?;
+        store.apply(contract_id, &batch).await?;
+        progress.add(&batch);
+        tracing::info!(events = progress.events, ledger = ?progress.last_ledger, "backfill progress");
+
+        match page.cursor {
+            Some(cursor) if !done => start = EventStart::Cursor(cursor),
+            _ => return Ok(progress),
+        }
+    }
+}
+
+/// Load every event for `contract_id` from a JSON-lines archive, in file
+/// order. Blank lines are skipped; events of other contracts are ignored.
+pub fn read_archive(path: &Path, contract_id: &str) -> Result<Vec<EventEnvelope>> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut events = Vec::new();
+    for (n, line) in file.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let envelope: EventEnvelope = serde_json::from_str(&line)
+            .map_err(|e| Error::Archive(format!("{}:{}: {e}", path.display(), n + 1)))?;
+        if envelope.contract_id == contract_id {
+            events.push(envelope);
+        }
+    }
+    Ok(events)
+}
+
+/// Replay an archive written by `invoisio events` into the store.
+pub async fn from_archive(
+    store: &Store,
+    contract_id: &str,
+    path: &Path,
+    batch_size: usize,
+) -> Result<Progress> {
+    let events = read_archive(path, contract_id)?;
+    let mut progress = Progress::default();
+    for batch in archive_batches(&events, batch_size)? {
+        store.apply(contract_id, &batch).await?;
+        progress.add(&batch);
+        tracing::info!(events = progress.events, ledger = ?progress.last_ledger, "backfill progress");
+    }
+    Ok(progress)
+}
+
+/// Split archived `events` into batches of up to `batch_size`.
+pub fn archive_batches(events: &[EventEnvelope], batch_size: usize) -> Result<Vec<Batch>> {
+    events
+        .chunks(batch_size.max(1))
+        .map(|chunk| {
+            // Event ids double as cursors, so the last one checkpoints the chunk.
+            Batch::from_page(&EventPage {
+                events: chunk.to_vec(),
+                cursor: None,
+                latest_ledger: chunk.last().map_or(0, |e| e.ledger),
+                oldest_ledger: None,
+            })
+        })
+        .collect()
+}
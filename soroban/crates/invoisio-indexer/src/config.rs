@@ -0,0 +1,85 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::publish::Sink;
+
+/// Indexer configuration, read from environment variables.
+///
+/// | Variable                      | Required | Default  |
+/// |-------------------------------|----------|----------|
+/// | `INVOISIO_RPC_URL`            | yes      |          |
+/// | `INVOISIO_NETWORK_PASSPHRASE` | yes      |          |
+/// | `INVOISIO_CONTRACT_ID`        | yes      |          |
+/// | `DATABASE_URL`                | yes      |          |
+/// | `INVOISIO_START_LEDGER`       | no       | `1`      |
+/// | `INVOISIO_POLL_SECS`          | no       | `5`      |
+/// | `INVOISIO_PAGE_LIMIT`         | no       | `100`    |
+/// | `INVOISIO_METRICS_ADDR`       | no       | disabled |
+/// | `INVOISIO_GRAPHQL_ADDR`       | no       | disabled |
+/// | `INVOISIO_GRPC_ADDR`          | no       | disabled |
+/// | `INVOISIO_PUBLISH_URL`        | no       | disabled |
+/// | `INVOISIO_ARCHIVE_PATH`       | no       | disabled |
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub rpc_url: String,
+    pub network_passphrase: String,
+    pub contract_id: String,
+    /// Postgres url, or `sqlite://path` with the `sqlite` feature.
+    pub database_url: String,
+    /// Ledger to start from when no checkpoint exists yet.
+    pub start_ledger: u32,
+    pub poll_interval: Duration,
+    pub page_limit: u32,
+    /// `host:port` for the Prometheus `/metrics` endpoint.
+    pub metrics_addr: Option<SocketAddr>,
+    /// `host:port` for the `/graphql` endpoint.
+    pub graphql_addr: Option<SocketAddr>,
+    /// `host:port` for the gRPC `Payments` service.
+    pub grpc_addr: Option<SocketAddr>,
+    /// Kafka topic or NATS subject to publish events to.
+    pub publish: Option<Sink>,
+    /// JSON-lines event archive to fill retention gaps from.
+    pub archive_path: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            rpc_url: required("INVOISIO_RPC_URL")?,
+            network_passphrase: required("INVOISIO_NETWORK_PASSPHRASE")?,
+            contract_id: required("INVOISIO_CONTRACT_ID")?,
+            database_url: required("DATABASE_URL")?,
+            start_ledger: parsed("INVOISIO_START_LEDGER", 1)?,
+            poll_interval: Duration::from_secs(parsed("INVOISIO_POLL_SECS", 5)?),
+            page_limit: parsed("INVOISIO_PAGE_LIMIT", 100)?,
+            metrics_addr: parsed_optional("INVOISIO_METRICS_ADDR")?,
+            graphql_addr: parsed_optional("INVOISIO_GRAPHQL_ADDR")?,
+            grpc_addr: parsed_optional("INVOISIO_GRPC_ADDR")?,
+            publish: parsed_optional("INVOISIO_PUBLISH_URL")?,
+            archive_path: optional("INVOISIO_ARCHIVE_PATH").map(PathBuf::from),
+        })
+    }
+}
+
+fn required(name: &str) -> Result<String> {
+    optional(name).ok_or_else(|| Error::Config(format!("{name} is not set")))
+}
+
+fn optional(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn parsed<T: std::str::FromStr>(name: &str, default: T) -> Result<T> {
+    Ok(parsed_optional(name)?.unwrap_or(default))
+}
+
+fn parsed_optional<T: std::str::FromStr>(name: &str) -> Result<Option<T>> {
+    optional(name)
+        .map(|v| {
+            v.parse()
+                .map_err(|_| Error::Config(format!("{name}=`{v}` is not valid")))
+        })
+        .transpose()
+}
@@ -0,0 +1,29 @@
+/// Errors surfaced by the indexer.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+
+    /// Soroban RPC / decoding failure.
+    #[error(transparent)]
+    Client(#[from] invoisio_client::Error),
+
+    #[error("database: {0}")]
+    Database(#[from] sqlx::Error),
+
+    /// Malformed backfill archive line.
+    #[error("archive: {0}")]
+    Archive(String),
+
+    /// Kafka / NATS failure.
+    #[error("publish: {0}")]
+    Publish(String),
+
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
@@ -0,0 +1,40 @@
+//! Retention gaps.
+//!
+//! Soroban RPC only keeps a few days of events. If the indexer falls behind
+//! further than that (an outage over a weekend, say), its checkpoint points
+//! at ledgers the node has already dropped, and polling from the next
+//! retained ledger would silently skip everything in between. The
+//! [`Indexer`](crate::Indexer) checks for this on start-up and after every
+//! failed poll, records a [`GapIncident`], and replays the missing range
+//! from the configured archive before moving on.
+
+/// Ledgers, inclusive, that RPC no longer serves but were never indexed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gap {
+    pub from_ledger: u32,
+    pub to_ledger: u32,
+}
+
+/// The gap between `next_ledger`, the first ledger still to index, and
+/// `oldest_ledger`, the first one RPC retains. `None` when RPC still covers
+/// `next_ledger`.
+pub fn detect(next_ledger: u32, oldest_ledger: u32) -> Option<Gap> {
+    (next_ledger < oldest_ledger).then(|| Gap {
+        from_ledger: next_ledger,
+        to_ledger: oldest_ledger - 1,
+    })
+}
+
+/// A recorded gap, as stored in `gap_incidents`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GapIncident {
+    pub id: i64,
+    pub from_ledger: u32,
+    pub to_ledger: u32,
+    /// Unix seconds.
+    pub detected_at: i64,
+    /// Unix seconds; `None` while the range is still missing.
+    pub resolved_at: Option<i64>,
+    /// Archive events replayed into the range, when resolved automatically.
+    pub events_recovered: Option<i64>,
+}
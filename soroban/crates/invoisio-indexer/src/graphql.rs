@@ -0,0 +1,379 @@
+//! GraphQL queries over the indexed tables.
+//!
+//! Served at `POST /graphql`, with GraphiQL at `GET /graphql`, when
+//! `INVOISIO_GRAPHQL_ADDR` is set. Read-only: the indexer remains the only
+//! writer. Amounts are decimal strings, times are Unix seconds of the
+//! contract's timestamp, and lists are Relay connections paged with
+//! `first` / `after`.
+//!
+//! ```graphql
+//! {
+//!   payments(filter: { assetCode: "USDC", from: 1704067200 }, first: 20) {
+//!     edges { cursor node { invoiceId payer amount timestamp } }
+//!     pageInfo { hasNextPage endCursor }
+//!   }
+//!   invoice(invoiceId: "invoisio-abc123") { payment { amount } refundedTotal netAmount }
+//!   aggregates { payments assets { assetCode volume refunded } }
+//! }
+//! ```
+
+use std::future::Future;
+use std::net::SocketAddr;
+
+use async_graphql::connection::{self, Connection, Edge};
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Object, OutputType, Schema, SimpleObject,
+};
+use axum::extract::State;
+use axum::response::Html;
+use axum::routing::get;
+use axum::{Json, Router};
+
+pub use crate::query::PaymentFilter;
+use crate::query::Queries;
+use crate::rows::{PaymentRow, RefundRow};
+use crate::store::Store;
+
+/// Page size when `first` is not given.
+pub const DEFAULT_PAGE: usize = 50;
+
+/// Largest accepted `first`.
+pub const MAX_PAGE: usize = 500;
+
+/// Deepest query accepted.
+const MAX_DEPTH: usize = 8;
+
+pub type IndexerSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+/// The schema, reading `contract_id`'s rows from `store`.
+pub fn schema(store: Store, contract_id: &str) -> IndexerSchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .data(Reader(Queries::new(store, contract_id)))
+        .limit_depth(MAX_DEPTH)
+        .finish()
+}
+
+/// Routes for `schema`.
+pub fn router(schema: IndexerSchema) -> Router {
+    Router::new()
+        .route("/graphql", get(graphiql).post(execute))
+        .with_state(schema)
+}
+
+/// Serve [`router`] on `addr` until `shutdown` resolves.
+pub async fn serve(
+    addr: SocketAddr,
+    schema: IndexerSchema,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!(%addr, "serving /graphql");
+    axum::serve(listener, router(schema))
+        .with_graceful_shutdown(shutdown)
+        .await
+}
+
+/// Spawn [`serve`] in the background if `addr` is set, logging failures.
+pub fn spawn(addr: Option<SocketAddr>, schema: IndexerSchema) {
+    if let Some(addr) = addr {
+        tokio::spawn(async move {
+            if let Err(err) = serve(addr, schema, std::future::pending()).await {
+                tracing::error!(%addr, error = %err, "graphql endpoint failed");
+            }
+        });
+    }
+}
+
+async fn execute(
+    State(schema): State<IndexerSchema>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema.execute(request).await)
+}
+
+async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+/// A recorded or reported payment.
+#[derive(Clone, Debug, PartialEq, Eq, SimpleObject)]
+pub struct Payment {
+    pub invoice_id: String,
+    pub payer: String,
+    /// `XLM` for native.
+    pub asset_code: String,
+    /// Empty for native.
+    pub asset_issuer: String,
+    pub amount: String,
+    pub timestamp: i64,
+    pub ledger: i32,
+    pub event_id: String,
+    pub tx_hash: Option<String>,
+}
+
+/// One `payment_refunded` event.
+#[derive(Clone, Debug, PartialEq, Eq, SimpleObject)]
+pub struct Refund {
+    pub invoice_id: String,
+    pub amount: String,
+    /// All refunds against the invoice up to and including this one.
+    pub refunded_total: String,
+    pub ledger: i32,
+    pub event_id: String,
+    pub tx_hash: Option<String>,
+}
+
+/// Everything indexed about one invoice.
+#[derive(Clone, Debug, PartialEq, Eq, SimpleObject)]
+pub struct Invoice {
+    pub invoice_id: String,
+    pub payment: Option<Payment>,
+    /// Reported but not yet recorded.
+    pub pending: Option<Payment>,
+    /// Oldest first.
+    pub refunds: Vec<Refund>,
+    pub refunded_total: String,
+    /// Payment amount less refunds; null until paid.
+    pub net_amount: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, SimpleObject)]
+pub struct AssetAggregate {
+    pub asset_code: String,
+    pub asset_issuer: String,
+    pub payments: i64,
+    /// Sum of payment amounts.
+    pub volume: String,
+    /// Sum of refunds against those payments.
+    pub refunded: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, SimpleObject)]
+pub struct Aggregates {
+    pub payments: i64,
+    /// Largest payment count first.
+    pub assets: Vec<AssetAggregate>,
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// The recorded payment for `invoice_id`.
+    async fn payment(
+        &self,
+        ctx: &Context<'_>,
+        invoice_id: String,
+    ) -> async_graphql::Result<Option<Payment>> {
+        reader(ctx).payment(&invoice_id).await.map_err(internal)
+    }
+
+    /// Recorded payments matching `filter`, newest first.
+    async fn payments(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<PaymentFilter>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> async_graphql::Result<Connection<usize, Payment>> {
+        let filter = filter.unwrap_or_default();
+        page(after, first, |limit, offset| {
+            reader(ctx).payments(&filter, limit, offset)
+        })
+        .await
+    }
+
+    /// Payment, pending report and refunds of `invoice_id`.
+    async fn invoice(
+        &self,
+        ctx: &Context<'_>,
+        invoice_id: String,
+    ) -> async_graphql::Result<Invoice> {
+        reader(ctx).invoice(&invoice_id).await.map_err(internal)
+    }
+
+    /// Refunds, newest first, optionally of one invoice.
+    async fn refunds(
+        &self,
+        ctx: &Context<'_>,
+        invoice_id: Option<String>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> async_graphql::Result<Connection<usize, Refund>> {
+        page(after, first, |limit, offset| {
+            reader(ctx).refunds(invoice_id.as_deref(), limit, offset)
+        })
+        .await
+    }
+
+    /// Count, volume and refunds per asset of the payments matching
+    /// `filter`.
+    async fn aggregates(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<PaymentFilter>,
+    ) -> async_graphql::Result<Aggregates> {
+        let filter = filter.unwrap_or_default();
+        reader(ctx).aggregates(&filter).await.map_err(internal)
+    }
+}
+
+/// Run one page of an offset-backed connection. Cursors are row offsets.
+async fn page<T, F, Fut>(
+    after: Option<String>,
+    first: Option<i32>,
+    fetch: F,
+) -> async_graphql::Result<Connection<usize, T>>
+where
+    T: OutputType,
+    F: FnOnce(i64, i64) -> Fut,
+    Fut: Future<Output = sqlx::Result<Vec<T>>>,
+{
+    connection::query(
+        after,
+        None,
+        first,
+        None,
+        |after: Option<usize>, _, first, _| async move {
+            let offset = after.map_or(0, |a| a + 1);
+            let limit = first.unwrap_or(DEFAULT_PAGE).min(MAX_PAGE);
+            // One extra row tells whether another page follows.
+            let mut rows = fetch(limit as i64 + 1, offset as i64)
+                .await
+                .map_err(internal)?;
+            let has_next = rows.len() > limit;
+            rows.truncate(limit);
+            let mut connection = Connection::new(offset > 0, has_next);
+            connection.edges.extend(
+                rows.into_iter()
+                    .enumerate()
+                    .map(|(i, row)| Edge::new(offset + i, row)),
+            );
+            Ok::<_, async_graphql::Error>(connection)
+        },
+    )
+    .await
+}
+
+/// Log a database failure and hide it from the caller.
+fn internal(err: sqlx::Error) -> async_graphql::Error {
+    tracing::error!(error = %err, "graphql query failed");
+    async_graphql::Error::new("internal error")
+}
+
+fn reader<'a>(ctx: &Context<'a>) -> &'a Reader {
+    ctx.data_unchecked::<Reader>()
+}
+
+/// [`Queries`] mapped onto the GraphQL types.
+struct Reader(Queries);
+
+impl Reader {
+    async fn payment(&self, invoice_id: &str) -> sqlx::Result<Option<Payment>> {
+        Ok(self.0.payment(invoice_id).await?.map(payment))
+    }
+
+    async fn payments(
+        &self,
+        filter: &PaymentFilter,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<Payment>> {
+        let rows = self.0.payments(filter, limit, offset).await?;
+        Ok(rows.into_iter().map(payment).collect())
+    }
+
+    async fn invoice(&self, invoice_id: &str) -> sqlx::Result<Invoice> {
+        let payment = self.payment(invoice_id).await?;
+        let pending = self.0.pending(invoice_id).await?.map(self::payment);
+        let refunds: Vec<Refund> = self
+            .0
+            .invoice_refunds(invoice_id)
+            .await?
+            .into_iter()
+            .map(refund)
+            .collect();
+
+        // `refunded_total` is cumulative, so the latest refund carries it.
+        let refunded_total = refunds
+            .last()
+            .map_or_else(|| "0".to_string(), |r| r.refunded_total.clone());
+        let net_amount = payment
+            .as_ref()
+            .map(|p| net_amount(&p.amount, &refunded_total))
+            .transpose()?;
+        Ok(Invoice {
+            invoice_id: invoice_id.to_string(),
+            payment,
+            pending,
+            refunds,
+            refunded_total,
+            net_amount,
+        })
+    }
+
+    async fn refunds(
+        &self,
+        invoice_id: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<Refund>> {
+        let rows = self.0.latest_refunds(invoice_id, limit, offset).await?;
+        Ok(rows.into_iter().map(refund).collect())
+    }
+
+    async fn aggregates(&self, filter: &PaymentFilter) -> sqlx::Result<Aggregates> {
+        let assets: Vec<AssetAggregate> = self
+            .0
+            .aggregates(filter)
+            .await?
+            .into_iter()
+            .map(|t| AssetAggregate {
+                asset_code: t.asset_code,
+                asset_issuer: t.asset_issuer,
+                payments: t.payments,
+                volume: t.volume,
+                refunded: t.refunded,
+            })
+            .collect();
+        Ok(Aggregates {
+            payments: assets.iter().map(|a| a.payments).sum(),
+            assets,
+        })
+    }
+}
+
+/// `amount - refunded_total`, both decimal strings.
+pub fn net_amount(amount: &str, refunded_total: &str) -> sqlx::Result<String> {
+    let parse = |s: &str| {
+        s.parse::<i128>()
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+    };
+    Ok((parse(amount)? - parse(refunded_total)?).to_string())
+}
+
+fn payment(r: PaymentRow) -> Payment {
+    Payment {
+        invoice_id: r.invoice_id,
+        payer: r.payer,
+        asset_code: r.asset_code,
+        asset_issuer: r.asset_issuer,
+        amount: r.amount,
+        timestamp: r.timestamp,
+        ledger: r.ledger,
+        event_id: r.event_id,
+        tx_hash: r.tx_hash,
+    }
+}
+
+fn refund(r: RefundRow) -> Refund {
+    Refund {
+        invoice_id: r.invoice_id,
+        amount: r.amount,
+        refunded_total: r.refunded_total,
+        ledger: r.ledger,
+        event_id: r.event_id,
+        tx_hash: r.tx_hash,
+    }
+}
@@ -0,0 +1,258 @@
+//! gRPC service over the indexed tables (`proto/payments.proto`).
+//!
+//! Served when `INVOISIO_GRPC_ADDR` is set. Besides unary lookups it offers
+//! `WatchPayments`, a server stream of payment events in event-id order:
+//! it replays what is already indexed after the caller's cursor, then pushes
+//! each page as soon as the [`Indexer`](crate::Indexer) commits it (woken
+//! through a [`Feed`]). Every message carries its event id as `cursor`, so a
+//! consumer that stores it can reconnect without gaps or duplicates.
+//!
+//! Rows written by another process (e.g. a separate `backfill` run) are
+//! picked up on the next idle re-check instead. Events backfilled below a
+//! consumer's cursor are not re-sent.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use invoisio_client::events::{
+    PAYMENT_IMPORTED, PAYMENT_RECORDED, PAYMENT_REFUNDED, PENDING_PAYMENT_REPORTED,
+};
+use invoisio_client::{ContractEvent, EventEnvelope, PaymentRecord};
+use tokio::sync::{mpsc, watch};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+use crate::indexer::Feed;
+use crate::query::Queries;
+use crate::rows::{PaymentRow, RefundRow};
+use crate::store::Store;
+
+/// Generated from `proto/payments.proto`; see the `protoc` feature.
+pub mod proto {
+    include!("generated/invoisio.indexer.v1.rs");
+}
+
+use proto::payment_event::Event;
+use proto::payments_server::{Payments, PaymentsServer};
+
+/// Events read per query while catching a stream up.
+pub const WATCH_PAGE: i64 = 100;
+
+/// Messages buffered per stream before the reader waits on the consumer.
+const STREAM_BUFFER: usize = 64;
+
+/// Topics `WatchPayments` delivers.
+const TOPICS: [&str; 4] = [
+    PAYMENT_RECORDED,
+    PAYMENT_IMPORTED,
+    PENDING_PAYMENT_REPORTED,
+    PAYMENT_REFUNDED,
+];
+
+/// The `Payments` service, reading `contract_id`'s rows.
+#[derive(Clone)]
+pub struct PaymentsService {
+    queries: Arc<Queries>,
+    feed: Feed,
+    idle: Duration,
+}
+
+impl PaymentsService {
+    /// `idle` bounds how long a caught-up stream waits for `feed` before
+    /// checking the database again.
+    pub fn new(store: Store, contract_id: &str, feed: Feed, idle: Duration) -> Self {
+        Self {
+            queries: Arc::new(Queries::new(store, contract_id)),
+            feed,
+            idle,
+        }
+    }
+}
+
+/// Serve `service` on `addr` until `shutdown` resolves.
+pub async fn serve(
+    addr: SocketAddr,
+    service: PaymentsService,
+    shutdown: impl std::future::Future<Output = ()> + Send,
+) -> Result<(), tonic::transport::Error> {
+    tracing::info!(%addr, "serving gRPC");
+    tonic::transport::Server::builder()
+        .add_service(PaymentsServer::new(service))
+        .serve_with_shutdown(addr, shutdown)
+        .await
+}
+
+/// Spawn [`serve`] in the background if `addr` is set, logging failures.
+pub fn spawn(addr: Option<SocketAddr>, service: PaymentsService) {
+    if let Some(addr) = addr {
+        tokio::spawn(async move {
+            if let Err(err) = serve(addr, service, std::future::pending()).await {
+                tracing::error!(%addr, error = %err, "grpc endpoint failed");
+            }
+        });
+    }
+}
+
+#[tonic::async_trait]
+impl Payments for PaymentsService {
+    async fn get_payment(
+        &self,
+        request: Request<proto::GetPaymentRequest>,
+    ) -> Result<Response<proto::Payment>, Status> {
+        let invoice_id = request.into_inner().invoice_id;
+        match self.queries.payment(&invoice_id).await.map_err(internal)? {
+            Some(row) => Ok(Response::new(payment_row(row))),
+            None => Err(Status::not_found(format!(
+                "no payment recorded for `{invoice_id}`"
+            ))),
+        }
+    }
+
+    async fn list_refunds(
+        &self,
+        request: Request<proto::ListRefundsRequest>,
+    ) -> Result<Response<proto::ListRefundsResponse>, Status> {
+        let invoice_id = request.into_inner().invoice_id;
+        let rows = self
+            .queries
+            .invoice_refunds(&invoice_id)
+            .await
+            .map_err(internal)?;
+        Ok(Response::new(proto::ListRefundsResponse {
+            refunds: rows.into_iter().map(refund_row).collect(),
+        }))
+    }
+
+    type WatchPaymentsStream = ReceiverStream<Result<proto::PaymentEvent, Status>>;
+
+    async fn watch_payments(
+        &self,
+        request: Request<proto::WatchPaymentsRequest>,
+    ) -> Result<Response<Self::WatchPaymentsStream>, Status> {
+        let cursor = match request.into_inner().cursor {
+            c if c.is_empty() => self.queries.tip().await.map_err(internal)?,
+            c => c,
+        };
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        // Subscribe before the first read so no commit slips in between.
+        let feed = self.feed.subscribe();
+        tokio::spawn(watch(self.queries.clone(), cursor, feed, self.idle, tx));
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Stream events after `cursor` into `tx` until the consumer goes away.
+async fn watch(
+    queries: Arc<Queries>,
+    mut cursor: String,
+    mut feed: watch::Receiver<u32>,
+    idle: Duration,
+    tx: mpsc::Sender<Result<proto::PaymentEvent, Status>>,
+) {
+    loop {
+        let events = match queries.events_after(&cursor, &TOPICS, WATCH_PAGE).await {
+            Ok(events) => events,
+            Err(err) => {
+                let _ = tx.send(Err(internal(err))).await;
+                return;
+            }
+        };
+        let caught_up = (events.len() as i64) < WATCH_PAGE;
+        for envelope in events {
+            cursor.clone_from(&envelope.id);
+            if let Some(event) = payment_event(&envelope) {
+                if tx.send(Ok(event)).await.is_err() {
+                    return;
+                }
+            }
+        }
+        if caught_up {
+            tokio::select! {
+                _ = tx.closed() => return,
+                _ = changed(&mut feed) => {}
+                _ = tokio::time::sleep(idle) => {}
+            }
+        }
+    }
+}
+
+/// Resolve on the next commit; never, once the indexer has stopped.
+async fn changed(feed: &mut watch::Receiver<u32>) {
+    if feed.changed().await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// `envelope` as a stream message, or `None` for topics the stream skips.
+pub fn payment_event(envelope: &EventEnvelope) -> Option<proto::PaymentEvent> {
+    let event = match &envelope.event {
+        ContractEvent::PaymentRecorded { record } => Event::Recorded(payment(envelope, record)),
+        ContractEvent::PaymentImported { record } => Event::Imported(payment(envelope, record)),
+        ContractEvent::PendingPaymentReported { record } => {
+            Event::Pending(payment(envelope, record))
+        }
+        ContractEvent::PaymentRefunded {
+            invoice_id,
+            amount,
+            refunded_total,
+        } => Event::Refunded(proto::Refund {
+            invoice_id: invoice_id.clone(),
+            amount: amount.to_string(),
+            refunded_total: refunded_total.to_string(),
+            ledger: envelope.ledger,
+            event_id: envelope.id.clone(),
+            tx_hash: envelope.tx_hash.clone().unwrap_or_default(),
+        }),
+        ContractEvent::Unknown { .. } => return None,
+    };
+    Some(proto::PaymentEvent {
+        cursor: envelope.id.clone(),
+        event: Some(event),
+    })
+}
+
+fn payment(envelope: &EventEnvelope, record: &PaymentRecord) -> proto::Payment {
+    proto::Payment {
+        invoice_id: record.invoice_id.clone(),
+        payer: record.payer.clone(),
+        asset_code: record.asset.code().to_string(),
+        asset_issuer: record.asset.issuer().to_string(),
+        amount: record.amount.to_string(),
+        timestamp: record.timestamp as i64,
+        ledger: envelope.ledger,
+        event_id: envelope.id.clone(),
+        tx_hash: envelope.tx_hash.clone().unwrap_or_default(),
+    }
+}
+
+/// Log a database failure and hide it from the caller.
+fn internal(err: sqlx::Error) -> Status {
+    tracing::error!(error = %err, "grpc request failed");
+    Status::internal("internal error")
+}
+
+fn payment_row(r: PaymentRow) -> proto::Payment {
+    proto::Payment {
+        invoice_id: r.invoice_id,
+        payer: r.payer,
+        asset_code: r.asset_code,
+        asset_issuer: r.asset_issuer,
+        amount: r.amount,
+        timestamp: r.timestamp,
+        ledger: r.ledger as u32,
+        event_id: r.event_id,
+        tx_hash: r.tx_hash.unwrap_or_default(),
+    }
+}
+
+fn refund_row(r: RefundRow) -> proto::Refund {
+    proto::Refund {
+        invoice_id: r.invoice_id,
+        amount: r.amount,
+        refunded_total: r.refunded_total,
+        ledger: r.ledger as u32,
+        event_id: r.event_id,
+        tx_hash: r.tx_hash.unwrap_or_default(),
+    }
+}
@@ -0,0 +1,234 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use invoisio_client::{EventStart, InvoicePaymentClient};
+use invoisio_metrics::metrics;
+use tokio::sync::watch;
+
+use crate::backfill;
+use crate::error::Result;
+use crate::gap::{self, Gap};
+use crate::publish::Publisher;
+use crate::rows::Batch;
+use crate::store::Store;
+
+/// Commit notifications for in-process readers such as the gRPC stream.
+/// Holds the ledger of the last committed non-empty page.
+#[derive(Clone, Debug)]
+pub struct Feed(watch::Sender<u32>);
+
+impl Feed {
+    pub fn new() -> Self {
+        Self(watch::Sender::new(0))
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<u32> {
+        self.0.subscribe()
+    }
+
+    fn notify(&self, ledger: u32) {
+        self.0.send_replace(ledger);
+    }
+}
+
+impl Default for Feed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Polls `getEvents` for one contract and feeds pages into the store.
+pub struct Indexer {
+    client: InvoicePaymentClient,
+    store: Store,
+    start_ledger: u32,
+    page_limit: u32,
+    feed: Option<Feed>,
+    publisher: Option<Publisher>,
+    archive: Option<PathBuf>,
+    /// Compare the resume point with RPC retention before the next poll.
+    /// Set on start-up and after every failed poll.
+    check_retention: AtomicBool,
+}
+
+impl Indexer {
+    pub fn new(
+        client: InvoicePaymentClient,
+        store: Store,
+        start_ledger: u32,
+        page_limit: u32,
+    ) -> Self {
+        Self {
+            client,
+            store,
+            start_ledger,
+            page_limit,
+            feed: None,
+            publisher: None,
+            archive: None,
+            check_retention: AtomicBool::new(true),
+        }
+    }
+
+    /// Notify `feed` after every committed page that carried events.
+    pub fn with_feed(mut self, feed: Feed) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+
+    /// Publish every page's events to `publisher` before committing it.
+    pub fn with_publisher(mut self, publisher: Publisher) -> Self {
+        self.publisher = Some(publisher);
+        self
+    }
+
+    /// Fill retention gaps from this JSON-lines archive. Without one, gaps
+    /// are recorded and left open.
+    pub fn with_archive(mut self, path: PathBuf) -> Self {
+        self.archive = Some(path);
+        self
+    }
+
+    /// Fetch and commit one page. Returns the number of events indexed.
+    pub async fn poll_once(&self) -> Result<usize> {
+        let contract_id = self.client.contract_id();
+        let checkpoint = self.store.checkpoint(contract_id).await?;
+        let next_ledger = checkpoint.as_ref().map_or(self.start_ledger, |c| c.ledger);
+        let start = match self.retention_gap(next_ledger).await? {
+            Some(gap) => {
+                self.recover(&gap).await?;
+                EventStart::Ledger(gap.to_ledger + 1)
+            }
+            None => match checkpoint {
+                Some(checkpoint) => EventStart::Cursor(checkpoint.cursor),
+                None => EventStart::Ledger(self.start_ledger),
+            },
+        };
+        let page = self
+            .client
+            .events(start, self.page_limit)
+            .await
+            .inspect_err(|err| metrics().rpc_error(err))?;
+        let batch = Batch::from_page(&page)?;
+        self.commit(&batch).await?;
+        Ok(batch.events.len())
+    }
+
+    /// Publish, store and announce one batch.
+    async fn commit(&self, batch: &Batch) -> Result<()> {
+        let contract_id = self.client.contract_id();
+        if let Some(publisher) = &self.publisher {
+            publisher.publish(&batch.events).await?;
+        }
+        self.store.apply(contract_id, batch).await?;
+
+        for event in &batch.events {
+            metrics()
+                .events_indexed
+                .with_label_values(&[&event.topic])
+                .inc();
+        }
+        if let Some(checkpoint) = &batch.checkpoint {
+            metrics().indexer_ledger.set(checkpoint.ledger.into());
+        }
+
+        if let (Some(feed), Some(checkpoint)) = (&self.feed, &batch.checkpoint) {
+            if !batch.is_empty() {
+                feed.notify(checkpoint.ledger);
+            }
+        }
+
+        if !batch.is_empty() {
+            tracing::info!(
+                events = batch.events.len(),
+                payments = batch.payments.len(),
+                pending = batch.pending.len(),
+                ledger = batch.checkpoint.as_ref().map(|c| c.ledger),
+                "indexed page"
+            );
+        }
+        Ok(())
+    }
+
+    /// The ledgers between `next_ledger` and RPC's oldest retained one, if
+    /// a check is due and RPC has already dropped `next_ledger`.
+    async fn retention_gap(&self, next_ledger: u32) -> Result<Option<Gap>> {
+        if !self.check_retention.swap(false, Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let health = self
+            .client
+            .rpc()
+            .get_health()
+            .await
+            .inspect_err(|err| metrics().rpc_error(err))?;
+        Ok(health
+            .oldest_ledger
+            .and_then(|oldest| gap::detect(next_ledger, oldest)))
+    }
+
+    /// Record `gap` and replay it from the archive, if there is one.
+    async fn recover(&self, gap: &Gap) -> Result<()> {
+        let contract_id = self.client.contract_id();
+        let id = self.store.open_gap(contract_id, gap).await?;
+        tracing::warn!(
+            from_ledger = gap.from_ledger,
+            to_ledger = gap.to_ledger,
+            "ledgers left RPC retention before they were indexed"
+        );
+        let Some(path) = &self.archive else {
+            metrics().indexer_gaps.with_label_values(&["open"]).inc();
+            tracing::error!(
+                incident = id,
+                "no INVOISIO_ARCHIVE_PATH; gap left open, run `backfill --archive` to fill it"
+            );
+            return Ok(());
+        };
+
+        let mut events = backfill::read_archive(path, contract_id)?;
+        events.retain(|e| (gap.from_ledger..=gap.to_ledger).contains(&e.ledger));
+        for batch in backfill::archive_batches(&events, self.page_limit as usize)? {
+            self.commit(&batch).await?;
+        }
+        self.store
+            .resolve_gap(id, Some(events.len() as u64))
+            .await?;
+        metrics()
+            .indexer_gaps
+            .with_label_values(&["backfilled"])
+            .inc();
+        tracing::info!(
+            incident = id,
+            events = events.len(),
+            archive = %path.display(),
+            "gap backfilled from archive"
+        );
+        Ok(())
+    }
+
+    /// Poll until `shutdown` resolves. Full pages are followed immediately;
+    /// empty pages and errors wait `idle` before retrying.
+    pub async fn run(&self, idle: Duration, shutdown: impl std::future::Future<Output = ()>) {
+        tokio::pin!(shutdown);
+        loop {
+            let wait = match self.poll_once().await {
+                Ok(n) if n >= self.page_limit as usize => Duration::ZERO,
+                Ok(_) => idle,
+                Err(err) => {
+                    tracing::error!(error = %err, "poll failed");
+                    // An outage may have outlasted RPC retention.
+                    self.check_retention.store(true, Ordering::Relaxed);
+                    idle
+                }
+            };
+            tokio::select! {
+                _ = &mut shutdown => {
+                    tracing::info!("shutting down");
+                    return;
+                }
+                _ = tokio::time::sleep(wait) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! # invoisio-indexer
+//!
+//! Follows the `invoice-payment` contract's events via Soroban RPC
+//! `getEvents`, decodes them with `invoisio-client`, and upserts typed rows
+//! into Postgres (or, with the `sqlite` feature, a local SQLite file)
+//! alongside a per-contract resume checkpoint. Each backend is an optional
+//! feature; `postgres` is on by default.
+//!
+//! ## Module layout
+//! | Module        | Responsibility                                         |
+//! |---------------|--------------------------------------------------------|
+//! | `config.rs`   | Environment configuration                              |
+//! | `rows.rs`     | Event → row mapping, [`Batch`], [`Checkpoint`]         |
+//! | `store.rs`    | [`Store`], dispatching to the enabled backends         |
+//! | `postgres.rs` | `PgStore` transactional writes (`postgres` feature)    |
+//! | `sqlite.rs`   | `SqliteStore`, the embedded backend (`sqlite` feature) |
+//! | `query.rs`    | Read queries for the APIs, per backend                 |
+//! | `indexer.rs`  | [`Indexer`] poll loop, [`Feed`] commit notifications   |
+//! | `gap.rs`      | RPC retention [`Gap`] detection, [`GapIncident`]       |
+//! | `backfill.rs` | Replay from a start ledger or a JSON-lines archive     |
+//! | `graphql.rs`  | GraphQL queries over the indexed tables                |
+//! | `grpc.rs`     | gRPC lookups and the `WatchPayments` stream            |
+//! | `publish.rs`  | Kafka / NATS publishing of every indexed event         |
+//! | `schema.sql`  | `contract_events`, `payments`, `pending_payments`,     |
+//! |               | `refunds`, `checkpoints`, `gap_incidents`              |
+//! |               | (`schema_sqlite.sql` mirrors it)                       |
+
+pub mod backfill;
+pub mod config;
+pub mod error;
+pub mod gap;
+pub mod graphql;
+pub mod grpc;
+pub mod indexer;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod publish;
+pub mod query;
+pub mod rows;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod store;
+
+pub use config::Config;
+pub use error::{Error, Result};
+pub use gap::{Gap, GapIncident};
+pub use indexer::{Feed, Indexer};
+#[cfg(feature = "postgres")]
+pub use postgres::PgStore;
+pub use publish::{Publisher, Sink};
+pub use rows::{Batch, Checkpoint};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+pub use store::Store;
+
+#[cfg(not(any(feature = "postgres", feature = "sqlite")))]
+compile_error!("enable the `postgres` or `sqlite` feature, or both");
+
+mod test;
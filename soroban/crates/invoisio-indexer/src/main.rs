@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use invoisio_client::InvoicePaymentClient;
+use invoisio_indexer::{backfill, graphql, grpc, Config, Feed, Indexer, Publisher, Store};
+use tracing_subscriber::EnvFilter;
+
+/// Connection settings come from the environment; see [`Config`].
+#[derive(Debug, Parser)]
+#[command(name = "invoisio-indexer", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Follow new events (default).
+    Run,
+    /// Replay history into the database, then exit.
+    Backfill {
+        /// Replay `getEvents` from this ledger (within RPC retention).
+        #[arg(long, conflicts_with = "archive", required_unless_present = "archive")]
+        from_ledger: Option<u32>,
+        /// Stop after this ledger. Defaults to the latest.
+        #[arg(long, requires = "from_ledger")]
+        to_ledger: Option<u32>,
+        /// Replay a JSON-lines event archive (e.g. `invoisio events` output).
+        #[arg(long)]
+        archive: Option<PathBuf>,
+    },
+    /// List recorded retention gaps.
+    Gaps {
+        /// Mark this incident resolved (e.g. after a manual backfill).
+        #[arg(long)]
+        resolve: Option<i64>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> invoisio_indexer::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let cli = Cli::parse();
+    let config = Config::from_env()?;
+    invoisio_metrics::spawn(config.metrics_addr);
+    let client =
+        InvoicePaymentClient::new(&config.rpc_url, &config.network_passphrase, &config.contract_id)?;
+    let store = Store::connect(&config.database_url).await?;
+    store.migrate().await?;
+
+    match cli.command.unwrap_or(Command::Run) {
+        Command::Run => {
+            graphql::spawn(
+                config.graphql_addr,
+                graphql::schema(store.clone(), &config.contract_id),
+            );
+            let feed = Feed::new();
+            grpc::spawn(
+                config.grpc_addr,
+                grpc::PaymentsService::new(
+                    store.clone(),
+                    &config.contract_id,
+                    feed.clone(),
+                    config.poll_interval,
+                ),
+            );
+            let mut indexer =
+                Indexer::new(client, store, config.start_ledger, config.page_limit).with_feed(feed);
+            if let Some(sink) = &config.publish {
+                indexer = indexer.with_publisher(Publisher::connect(sink).await?);
+            }
+            if let Some(path) = config.archive_path {
+                indexer = indexer.with_archive(path);
+            }
+            tracing::info!(contract = %config.contract_id, "starting indexer");
+            indexer
+                .run(config.poll_interval, async {
+                    let _ = tokio::signal::ctrl_c().await;
+                })
+                .await;
+        }
+        Command::Backfill {
+            from_ledger,
+            to_ledger,
+            archive,
+        } => {
+            let progress = match (archive, from_ledger) {
+                (Some(path), _) => {
+                    let batch_size = config.page_limit as usize;
+                    backfill::from_archive(&store, &config.contract_id, &path, batch_size).await?
+                }
+                (None, Some(from)) => {
+                    backfill::from_rpc(&client, &store, from, to_ledger, config.page_limit).await?
+                }
+                (None, None) => unreachable!("clap requires one of --archive / --from-ledger"),
+            };
+            tracing::info!(
+                events = progress.events,
+                payments = progress.payments,
+                last_ledger = ?progress.last_ledger,
+                "backfill complete"
+            );
+        }
+        Command::Gaps { resolve: Some(id) } => {
+            if !store.resolve_gap(id, None).await? {
+                return Err(invoisio_indexer::Error::Config(format!(
+                    "no gap incident {id}"
+                )));
+            }
+            tracing::info!(incident = id, "gap marked resolved");
+        }
+        Command::Gaps { resolve: None } => {
+            for gap in store.gaps(&config.contract_id).await? {
+                let status = match (gap.resolved_at, gap.events_recovered) {
+                    (None, _) => "open".to_string(),
+                    (Some(_), Some(n)) => format!("backfilled ({n} events)"),
+                    (Some(_), None) => "resolved manually".to_string(),
+                };
+                println!(
+                    "{}\tledgers {}..={}\t{}",
+                    gap.id, gap.from_ledger, gap.to_ledger, status
+                );
+            }
+        }
+    }
+    Ok(())
+}
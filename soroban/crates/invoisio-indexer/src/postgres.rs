@@ -0,0 +1,262 @@
+//! Postgres sink (`postgres` feature, on by default).
+//!
+//! Each [`Batch`] is applied in a single transaction together with its
+//! checkpoint, so a crash can only replay a page, never skip one. All writes
+//! are upserts keyed by event id / invoice id, which makes replays harmless.
+
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::{Postgres, Row, Transaction};
+
+use crate::error::Result;
+use crate::gap::{Gap, GapIncident};
+use crate::rows::{Batch, Checkpoint, EventRow, PaymentRow, RefundRow};
+
+/// Idempotent DDL, run by [`PgStore::migrate`].
+pub const SCHEMA: &str = include_str!("schema.sql");
+
+#[derive(Clone, Debug)]
+pub struct PgStore {
+    pool: PgPool,
+}
+
+impl PgStore {
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(4)
+            .connect(database_url)
+            .await?;
+        Ok(Self::with_pool(pool))
+    }
+
+    pub fn with_pool(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    pub async fn migrate(&self) -> Result<()> {
+        sqlx::raw_sql(SCHEMA).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    pub async fn checkpoint(&self, contract_id: &str) -> Result<Option<Checkpoint>> {
+        let row = sqlx::query("SELECT cursor, ledger FROM checkpoints WHERE contract_id = $1")
+            .bind(contract_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| Checkpoint {
+            cursor: r.get("cursor"),
+            ledger: r.get::<i32, _>("ledger") as u32,
+        }))
+    }
+
+    /// Write a batch and advance the checkpoint atomically. The checkpoint
+    /// never moves backwards, so a backfill of old ledgers can run next to
+    /// (or after) the live indexer without rewinding it.
+    pub async fn apply(&self, contract_id: &str, batch: &Batch) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for event in &batch.events {
+            insert_event(&mut tx, event).await?;
+        }
+        for row in &batch.pending {
+            upsert_pending(&mut tx, row).await?;
+        }
+        for row in &batch.payments {
+            upsert_payment(&mut tx, row).await?;
+        }
+        for row in &batch.refunds {
+            insert_refund(&mut tx, row).await?;
+        }
+        if let Some(checkpoint) = &batch.checkpoint {
+            save_checkpoint(&mut tx, contract_id, checkpoint).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Record `gap`, or widen (and reopen) the incident starting at the
+    /// same ledger. Returns the incident id.
+    pub async fn open_gap(&self, contract_id: &str, gap: &Gap) -> Result<i64> {
+        let id = sqlx::query_scalar(
+            "INSERT INTO gap_incidents (contract_id, from_ledger, to_ledger)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (contract_id, from_ledger) DO UPDATE SET
+                 to_ledger = GREATEST(gap_incidents.to_ledger, EXCLUDED.to_ledger),
+                 resolved_at = NULL,
+                 events_recovered = NULL
+             RETURNING id",
+        )
+        .bind(contract_id)
+        .bind(gap.from_ledger as i32)
+        .bind(gap.to_ledger as i32)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// Mark incident `id` resolved. Returns `false` if it does not exist.
+    pub async fn resolve_gap(&self, id: i64, events_recovered: Option<u64>) -> Result<bool> {
+        let done = sqlx::query(
+            "UPDATE gap_incidents SET resolved_at = now(), events_recovered = $2 WHERE id = $1",
+        )
+        .bind(id)
+        .bind(events_recovered.map(|n| n as i32))
+        .execute(&self.pool)
+        .await?;
+        Ok(done.rows_affected() == 1)
+    }
+
+    /// Every incident for `contract_id`, oldest first.
+    pub async fn gaps(&self, contract_id: &str) -> Result<Vec<GapIncident>> {
+        let rows = sqlx::query(
+            "SELECT id, from_ledger, to_ledger,
+                 EXTRACT(EPOCH FROM detected_at)::bigint AS detected_at,
+                 EXTRACT(EPOCH FROM resolved_at)::bigint AS resolved_at,
+                 events_recovered
+             FROM gap_incidents WHERE contract_id = $1 ORDER BY from_ledger",
+        )
+        .bind(contract_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(|r| GapIncident {
+                id: r.get("id"),
+                from_ledger: r.get::<i32, _>("from_ledger") as u32,
+                to_ledger: r.get::<i32, _>("to_ledger") as u32,
+                detected_at: r.get("detected_at"),
+                resolved_at: r.get("resolved_at"),
+                events_recovered: r.get::<Option<i32>, _>("events_recovered").map(Into::into),
+            })
+            .collect())
+    }
+}
+
+async fn insert_event(tx: &mut Transaction<'_, Postgres>, e: &EventRow) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO contract_events
+             (event_id, contract_id, ledger, ledger_closed_at, tx_hash, topic, payload)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         ON CONFLICT (event_id) DO NOTHING",
+    )
+    .bind(&e.event_id)
+    .bind(&e.contract_id)
+    .bind(e.ledger)
+    .bind(&e.ledger_closed_at)
+    .bind(&e.tx_hash)
+    .bind(&e.topic)
+    .bind(&e.payload)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn upsert_payment(tx: &mut Transaction<'_, Postgres>, p: &PaymentRow) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO payments
+             (contract_id, invoice_id, payer, asset_code, asset_issuer, amount,
+              recorded_at, ledger, event_id, tx_hash)
+         VALUES ($1, $2, $3, $4, $5, $6::numeric, to_timestamp($7), $8, $9, $10)
+         ON CONFLICT (contract_id, invoice_id) DO UPDATE SET
+             payer = EXCLUDED.payer,
+             asset_code = EXCLUDED.asset_code,
+             asset_issuer = EXCLUDED.asset_issuer,
+             amount = EXCLUDED.amount,
+             recorded_at = EXCLUDED.recorded_at,
+             ledger = EXCLUDED.ledger,
+             event_id = EXCLUDED.event_id,
+             tx_hash = EXCLUDED.tx_hash",
+    )
+    .bind(&p.contract_id)
+    .bind(&p.invoice_id)
+    .bind(&p.payer)
+    .bind(&p.asset_code)
+    .bind(&p.asset_issuer)
+    .bind(&p.amount)
+    .bind(p.timestamp as f64)
+    .bind(p.ledger)
+    .bind(&p.event_id)
+    .bind(&p.tx_hash)
+    .execute(&mut **tx)
+    .await?;
+
+    // A recorded payment supersedes its pending report.
+    sqlx::query("DELETE FROM pending_payments WHERE contract_id = $1 AND invoice_id = $2")
+        .bind(&p.contract_id)
+        .bind(&p.invoice_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+async fn upsert_pending(tx: &mut Transaction<'_, Postgres>, p: &PaymentRow) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO pending_payments
+             (contract_id, invoice_id, payer, asset_code, asset_issuer, amount,
+              reported_at, ledger, event_id)
+         VALUES ($1, $2, $3, $4, $5, $6::numeric, to_timestamp($7), $8, $9)
+         ON CONFLICT (contract_id, invoice_id) DO UPDATE SET
+             payer = EXCLUDED.payer,
+             asset_code = EXCLUDED.asset_code,
+             asset_issuer = EXCLUDED.asset_issuer,
+             amount = EXCLUDED.amount,
+             reported_at = EXCLUDED.reported_at,
+             ledger = EXCLUDED.ledger,
+             event_id = EXCLUDED.event_id",
+    )
+    .bind(&p.contract_id)
+    .bind(&p.invoice_id)
+    .bind(&p.payer)
+    .bind(&p.asset_code)
+    .bind(&p.asset_issuer)
+    .bind(&p.amount)
+    .bind(p.timestamp as f64)
+    .bind(p.ledger)
+    .bind(&p.event_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn insert_refund(tx: &mut Transaction<'_, Postgres>, r: &RefundRow) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO refunds
+             (event_id, contract_id, invoice_id, amount, refunded_total, ledger, tx_hash)
+         VALUES ($1, $2, $3, $4::numeric, $5::numeric, $6, $7)
+         ON CONFLICT (event_id) DO NOTHING",
+    )
+    .bind(&r.event_id)
+    .bind(&r.contract_id)
+    .bind(&r.invoice_id)
+    .bind(&r.amount)
+    .bind(&r.refunded_total)
+    .bind(r.ledger)
+    .bind(&r.tx_hash)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn save_checkpoint(
+    tx: &mut Transaction<'_, Postgres>,
+    contract_id: &str,
+    c: &Checkpoint,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO checkpoints (contract_id, cursor, ledger, updated_at)
+         VALUES ($1, $2, $3, now())
+         ON CONFLICT (contract_id) DO UPDATE SET
+             cursor = EXCLUDED.cursor,
+             ledger = EXCLUDED.ledger,
+             updated_at = now()
+         WHERE checkpoints.ledger <= EXCLUDED.ledger",
+    )
+    .bind(contract_id)
+    .bind(&c.cursor)
+    .bind(c.ledger as i32)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,226 @@
+//! Publishing indexed events to Kafka or NATS.
+//!
+//! With `INVOISIO_PUBLISH_URL` set, the [`Indexer`](crate::Indexer) sends
+//! every event of a page to the configured sink *before* committing the
+//! page, so delivery is at-least-once: a failed publish or commit retries the
+//! whole page. Each message is keyed by its event id to let consumers (or the
+//! broker) drop the duplicates a retry can produce:
+//!
+//! | Sink  | URL                                   | Key                     |
+//! |-------|---------------------------------------|-------------------------|
+//! | Kafka | `kafka://host:9092[,host:9092]/topic` | record key              |
+//! | NATS  | `nats://host:4222/subject`            | `Nats-Msg-Id` header    |
+//!
+//! Kafka needs the `kafka` feature. NATS publishes through JetStream, so a
+//! stream must cover the subject; its duplicate window then deduplicates by
+//! `Nats-Msg-Id`. Backfills do not publish; retention gaps the indexer
+//! replays from its archive do.
+//!
+//! The payload is an [`EventMessage`] as JSON. Its fields only ever gain
+//! new siblings within one [`MESSAGE_SCHEMA`] version.
+
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::rows::EventRow;
+
+/// `schema` field of every message.
+pub const MESSAGE_SCHEMA: &str = "invoisio.contract_event.v1";
+
+/// The published JSON document.
+///
+/// ```json
+/// {
+///   "schema": "invoisio.contract_event.v1",
+///   "event_id": "0000123456789-0000000001",
+///   "contract_id": "C...",
+///   "ledger": 28744,
+///   "ledger_closed_at": "2024-01-01T00:00:00Z",
+///   "tx_hash": "ab12...",
+///   "topic": "payment_recorded",
+///   "data": { "event": "payment_recorded", "record": { ... } }
+/// }
+/// ```
+///
+/// `data` is the decoded [`ContractEvent`](invoisio_client::ContractEvent),
+/// with amounts as decimal strings; undecodable events carry only their
+/// topic.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EventMessage<'a> {
+    pub schema: &'static str,
+    pub event_id: &'a str,
+    pub contract_id: &'a str,
+    pub ledger: u32,
+    pub ledger_closed_at: &'a str,
+    pub tx_hash: Option<&'a str>,
+    pub topic: &'a str,
+    pub data: &'a serde_json::Value,
+}
+
+impl<'a> EventMessage<'a> {
+    pub fn new(row: &'a EventRow) -> Self {
+        Self {
+            schema: MESSAGE_SCHEMA,
+            event_id: &row.event_id,
+            contract_id: &row.contract_id,
+            ledger: row.ledger as u32,
+            ledger_closed_at: &row.ledger_closed_at,
+            tx_hash: row.tx_hash.as_deref(),
+            topic: &row.topic,
+            data: &row.payload,
+        }
+    }
+
+    /// Idempotency key: the event id, unique per network.
+    pub fn key(&self) -> &'a str {
+        self.event_id
+    }
+
+    pub fn to_json(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+}
+
+/// Where to publish, parsed from `INVOISIO_PUBLISH_URL`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Sink {
+    Kafka {
+        /// Comma-separated `host:port` list.
+        brokers: String,
+        topic: String,
+    },
+    Nats {
+        /// `nats://host:port`.
+        url: String,
+        subject: String,
+    },
+}
+
+impl FromStr for Sink {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::Config(format!("publish url `{s}` is not valid"));
+        let (scheme, rest) = s.split_once("://").ok_or_else(invalid)?;
+        let (hosts, name) = rest.split_once('/').ok_or_else(invalid)?;
+        if hosts.is_empty() || name.is_empty() || name.contains('/') {
+            return Err(invalid());
+        }
+        match scheme {
+            "kafka" => Ok(Sink::Kafka {
+                brokers: hosts.into(),
+                topic: name.into(),
+            }),
+            "nats" => Ok(Sink::Nats {
+                url: format!("nats://{hosts}"),
+                subject: name.into(),
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// A connected sink.
+pub enum Publisher {
+    #[cfg(feature = "kafka")]
+    Kafka {
+        producer: rdkafka::producer::FutureProducer,
+        topic: String,
+    },
+    #[cfg(feature = "nats")]
+    Nats {
+        jetstream: async_nats::jetstream::Context,
+        subject: String,
+    },
+}
+
+impl Publisher {
+    pub async fn connect(sink: &Sink) -> Result<Self> {
+        match sink {
+            #[cfg(feature = "kafka")]
+            Sink::Kafka { brokers, topic } => {
+                let producer = rdkafka::ClientConfig::new()
+                    .set("bootstrap.servers", brokers)
+                    // No broker-side duplicates from producer retries.
+                    .set("enable.idempotence", "true")
+                    .create()
+                    .map_err(publish_error)?;
+                Ok(Publisher::Kafka {
+                    producer,
+                    topic: topic.clone(),
+                })
+            }
+            #[cfg(feature = "nats")]
+            Sink::Nats { url, subject } => {
+                let client = async_nats::connect(url).await.map_err(publish_error)?;
+                Ok(Publisher::Nats {
+                    jetstream: async_nats::jetstream::new(client),
+                    subject: subject.clone(),
+                })
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::Config(format!(
+                "{sink:?} needs a build with the matching `kafka` / `nats` feature"
+            ))),
+        }
+    }
+
+    /// Publish `events` in order and wait until the sink has acknowledged
+    /// every one of them.
+    pub async fn publish(&self, events: &[EventRow]) -> Result<()> {
+        let messages: Vec<_> = events.iter().map(EventMessage::new).collect();
+        match self {
+            #[cfg(feature = "kafka")]
+            Publisher::Kafka { producer, topic } => {
+                use rdkafka::producer::FutureRecord;
+
+                let mut deliveries = Vec::with_capacity(messages.len());
+                for message in &messages {
+                    let payload = message.to_json()?;
+                    let record = FutureRecord::to(topic).key(message.key()).payload(&payload);
+                    deliveries.push(
+                        producer
+                            .send_result(record)
+                            .map_err(|(err, _)| publish_error(err))?,
+                    );
+                }
+                for delivery in deliveries {
+                    delivery
+                        .await
+                        .map_err(|_| Error::Publish("kafka producer dropped".into()))?
+                        .map_err(|(err, _)| publish_error(err))?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "nats")]
+            Publisher::Nats { jetstream, subject } => {
+                let mut acks = Vec::with_capacity(messages.len());
+                for message in &messages {
+                    let mut headers = async_nats::HeaderMap::new();
+                    headers.insert("Nats-Msg-Id", message.key());
+                    let ack = jetstream
+                        .publish_with_headers(subject.clone(), headers, message.to_json()?.into())
+                        .await
+                        .map_err(publish_error)?;
+                    acks.push(ack);
+                }
+                for ack in acks {
+                    ack.await.map_err(publish_error)?;
+                }
+                Ok(())
+            }
+            #[allow(unreachable_patterns)]
+            _ => {
+                let _ = messages;
+                unreachable!("no publisher can be connected without a sink feature")
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "kafka", feature = "nats"))]
+fn publish_error(err: impl std::fmt::Display) -> Error {
+    Error::Publish(err.to_string())
+}
@@ -0,0 +1,585 @@
+//! Read-side queries behind the GraphQL and gRPC layers.
+//!
+//! [`Queries`] answers the same questions from either [`Store`] backend and
+//! returns the rows the writers store ([`PaymentRow`], [`RefundRow`]), so
+//! the API layers never see SQL. Postgres sums amounts as `NUMERIC`; SQLite
+//! keeps them as text, so its totals are summed in Rust.
+
+use async_graphql::InputObject;
+use invoisio_client::EventEnvelope;
+
+use crate::rows::{PaymentRow, RefundRow};
+use crate::store::Store;
+
+/// Filter over recorded payments. All given fields must match.
+#[derive(Clone, Debug, Default, PartialEq, Eq, InputObject)]
+pub struct PaymentFilter {
+    pub payer: Option<String>,
+    pub asset_code: Option<String>,
+    pub asset_issuer: Option<String>,
+    pub invoice_ids: Option<Vec<String>>,
+    /// Unix seconds, inclusive.
+    pub from: Option<i64>,
+    /// Unix seconds, exclusive.
+    pub to: Option<i64>,
+}
+
+/// Totals for one asset over the payments matching a filter.
+#[derive(Clone, Debug, PartialEq, Eq, sqlx::FromRow)]
+pub struct AssetTotals {
+    pub asset_code: String,
+    pub asset_issuer: String,
+    pub payments: i64,
+    /// Sum of payment amounts, decimal.
+    pub volume: String,
+    /// Sum of refunds against those payments, decimal.
+    pub refunded: String,
+}
+
+/// Queries over one contract's rows.
+#[derive(Clone, Debug)]
+pub struct Queries {
+    store: Store,
+    contract_id: String,
+}
+
+impl Queries {
+    pub fn new(store: Store, contract_id: &str) -> Self {
+        Self {
+            store,
+            contract_id: contract_id.to_string(),
+        }
+    }
+
+    /// The recorded payment for `invoice_id`.
+    pub async fn payment(&self, invoice_id: &str) -> sqlx::Result<Option<PaymentRow>> {
+        match &self.store {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(s) => pg::payment(s.pool(), &self.contract_id, invoice_id).await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(s) => lite::payment(s.pool(), &self.contract_id, invoice_id).await,
+        }
+    }
+
+    /// The pending report for `invoice_id` (`tx_hash` is always `None`).
+    pub async fn pending(&self, invoice_id: &str) -> sqlx::Result<Option<PaymentRow>> {
+        match &self.store {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(s) => pg::pending(s.pool(), &self.contract_id, invoice_id).await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(s) => lite::pending(s.pool(), &self.contract_id, invoice_id).await,
+        }
+    }
+
+    /// Recorded payments matching `filter`, newest first.
+    pub async fn payments(
+        &self,
+        filter: &PaymentFilter,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<PaymentRow>> {
+        let id = &self.contract_id;
+        match &self.store {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(s) => pg::payments(s.pool(), id, filter, limit, offset).await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(s) => lite::payments(s.pool(), id, filter, limit, offset).await,
+        }
+    }
+
+    /// Every refund of `invoice_id`, oldest first.
+    pub async fn invoice_refunds(&self, invoice_id: &str) -> sqlx::Result<Vec<RefundRow>> {
+        self.refunds(Some(invoice_id), Order::Oldest, i64::MAX, 0)
+            .await
+    }
+
+    /// Refunds, newest first, optionally of one invoice.
+    pub async fn latest_refunds(
+        &self,
+        invoice_id: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<RefundRow>> {
+        self.refunds(invoice_id, Order::Newest, limit, offset).await
+    }
+
+    async fn refunds(
+        &self,
+        invoice_id: Option<&str>,
+        order: Order,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<RefundRow>> {
+        let id = &self.contract_id;
+        match &self.store {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(s) => pg::refunds(s.pool(), id, invoice_id, order, limit, offset).await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(s) => lite::refunds(s.pool(), id, invoice_id, order, limit, offset).await,
+        }
+    }
+
+    /// Per-asset totals, largest payment count first.
+    pub async fn aggregates(&self, filter: &PaymentFilter) -> sqlx::Result<Vec<AssetTotals>> {
+        match &self.store {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(s) => pg::aggregates(s.pool(), &self.contract_id, filter).await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(s) => lite::aggregates(s.pool(), &self.contract_id, filter).await,
+        }
+    }
+
+    /// Latest indexed event id, or `""` before the first event.
+    pub async fn tip(&self) -> sqlx::Result<String> {
+        match &self.store {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(s) => pg::tip(s.pool(), &self.contract_id).await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(s) => lite::tip(s.pool(), &self.contract_id).await,
+        }
+    }
+
+    /// Up to `limit` events with one of `topics` after `cursor`, in
+    /// event-id order.
+    pub async fn events_after(
+        &self,
+        cursor: &str,
+        topics: &[&str],
+        limit: i64,
+    ) -> sqlx::Result<Vec<EventEnvelope>> {
+        let id = &self.contract_id;
+        match &self.store {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(s) => pg::events_after(s.pool(), id, cursor, topics, limit).await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(s) => lite::events_after(s.pool(), id, cursor, topics, limit).await,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Order {
+    Oldest,
+    Newest,
+}
+
+impl Order {
+    fn sql(self) -> &'static str {
+        match self {
+            Order::Oldest => " ORDER BY ledger, event_id",
+            Order::Newest => " ORDER BY ledger DESC, event_id DESC",
+        }
+    }
+}
+
+fn decode_error(err: impl std::error::Error + Send + Sync + 'static) -> sqlx::Error {
+    sqlx::Error::Decode(Box::new(err))
+}
+
+/// Postgres dialect.
+#[cfg(feature = "postgres")]
+pub mod pg {
+    use invoisio_client::EventEnvelope;
+    use sqlx::postgres::PgPool;
+    use sqlx::{Postgres, QueryBuilder, Row};
+
+    use super::{decode_error, AssetTotals, Order, PaymentFilter};
+    use crate::rows::{PaymentRow, RefundRow};
+
+    const PAYMENT_COLUMNS: &str = "contract_id, invoice_id, payer, asset_code, asset_issuer,
+        amount::text AS amount, EXTRACT(EPOCH FROM recorded_at)::bigint AS timestamp,
+        ledger, event_id, tx_hash";
+
+    const PENDING_COLUMNS: &str = "contract_id, invoice_id, payer, asset_code, asset_issuer,
+        amount::text AS amount, EXTRACT(EPOCH FROM reported_at)::bigint AS timestamp,
+        ledger, event_id, NULL::text AS tx_hash";
+
+    const REFUND_COLUMNS: &str = "contract_id, event_id, invoice_id, amount::text AS amount,
+        refunded_total::text AS refunded_total, ledger, tx_hash";
+
+    pub(super) async fn payment(
+        pool: &PgPool,
+        contract_id: &str,
+        invoice_id: &str,
+    ) -> sqlx::Result<Option<PaymentRow>> {
+        sqlx::query_as(&format!(
+            "SELECT {PAYMENT_COLUMNS} FROM payments WHERE contract_id = $1 AND invoice_id = $2"
+        ))
+        .bind(contract_id)
+        .bind(invoice_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub(super) async fn pending(
+        pool: &PgPool,
+        contract_id: &str,
+        invoice_id: &str,
+    ) -> sqlx::Result<Option<PaymentRow>> {
+        sqlx::query_as(&format!(
+            "SELECT {PENDING_COLUMNS} FROM pending_payments
+             WHERE contract_id = $1 AND invoice_id = $2"
+        ))
+        .bind(contract_id)
+        .bind(invoice_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub(super) async fn payments(
+        pool: &PgPool,
+        contract_id: &str,
+        filter: &PaymentFilter,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<PaymentRow>> {
+        let mut q = QueryBuilder::new(format!("SELECT {PAYMENT_COLUMNS} FROM payments"));
+        push_filter(&mut q, contract_id, filter);
+        q.push(" ORDER BY recorded_at DESC, invoice_id LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+        q.build_query_as().fetch_all(pool).await
+    }
+
+    pub(super) async fn refunds(
+        pool: &PgPool,
+        contract_id: &str,
+        invoice_id: Option<&str>,
+        order: Order,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<RefundRow>> {
+        let mut q = QueryBuilder::new(format!("SELECT {REFUND_COLUMNS} FROM refunds"));
+        q.push(" WHERE contract_id = ").push_bind(contract_id);
+        if let Some(invoice_id) = invoice_id {
+            q.push(" AND invoice_id = ").push_bind(invoice_id);
+        }
+        q.push(order.sql())
+            .push(" LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+        q.build_query_as().fetch_all(pool).await
+    }
+
+    pub(super) async fn aggregates(
+        pool: &PgPool,
+        contract_id: &str,
+        filter: &PaymentFilter,
+    ) -> sqlx::Result<Vec<AssetTotals>> {
+        aggregates_query(contract_id, filter)
+            .build_query_as()
+            .fetch_all(pool)
+            .await
+    }
+
+    /// Per-asset totals of the payments matching `filter`, refunds included.
+    pub fn aggregates_query<'a>(
+        contract_id: &'a str,
+        filter: &'a PaymentFilter,
+    ) -> QueryBuilder<'a, Postgres> {
+        let mut q = QueryBuilder::new(
+            "SELECT asset_code, asset_issuer, COUNT(*) AS payments,
+                 SUM(amount)::text AS volume, COALESCE(SUM(refunded), 0)::text AS refunded
+             FROM payments
+             LEFT JOIN (
+                 SELECT contract_id, invoice_id, SUM(amount) AS refunded
+                 FROM refunds GROUP BY contract_id, invoice_id
+             ) r USING (contract_id, invoice_id)",
+        );
+        push_filter(&mut q, contract_id, filter);
+        q.push(" GROUP BY asset_code, asset_issuer ORDER BY payments DESC, asset_code");
+        q
+    }
+
+    /// `WHERE` clause for `filter` over `payments` columns.
+    pub fn push_filter<'a>(
+        q: &mut QueryBuilder<'a, Postgres>,
+        contract_id: &'a str,
+        filter: &'a PaymentFilter,
+    ) {
+        q.push(" WHERE contract_id = ").push_bind(contract_id);
+        if let Some(payer) = &filter.payer {
+            q.push(" AND payer = ").push_bind(payer);
+        }
+        if let Some(code) = &filter.asset_code {
+            q.push(" AND asset_code = ").push_bind(code);
+        }
+        if let Some(issuer) = &filter.asset_issuer {
+            q.push(" AND asset_issuer = ").push_bind(issuer);
+        }
+        if let Some(ids) = &filter.invoice_ids {
+            q.push(" AND invoice_id = ANY(").push_bind(ids).push(")");
+        }
+        if let Some(from) = filter.from {
+            q.push(" AND recorded_at >= to_timestamp(")
+                .push_bind(from as f64)
+                .push(")");
+        }
+        if let Some(to) = filter.to {
+            q.push(" AND recorded_at < to_timestamp(")
+                .push_bind(to as f64)
+                .push(")");
+        }
+    }
+
+    pub(super) async fn tip(pool: &PgPool, contract_id: &str) -> sqlx::Result<String> {
+        sqlx::query_scalar(
+            "SELECT COALESCE(MAX(event_id), '') FROM contract_events WHERE contract_id = $1",
+        )
+        .bind(contract_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub(super) async fn events_after(
+        pool: &PgPool,
+        contract_id: &str,
+        cursor: &str,
+        topics: &[&str],
+        limit: i64,
+    ) -> sqlx::Result<Vec<EventEnvelope>> {
+        let rows = sqlx::query(
+            "SELECT event_id, contract_id, ledger, ledger_closed_at, tx_hash, payload
+             FROM contract_events
+             WHERE contract_id = $1 AND topic = ANY($2) AND event_id > $3
+             ORDER BY event_id LIMIT $4",
+        )
+        .bind(contract_id)
+        .bind(topics)
+        .bind(cursor)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+        rows.iter()
+            .map(|r| {
+                let payload: serde_json::Value = r.get("payload");
+                Ok(EventEnvelope {
+                    id: r.get("event_id"),
+                    ledger: r.get::<i32, _>("ledger") as u32,
+                    ledger_closed_at: r.get("ledger_closed_at"),
+                    contract_id: r.get("contract_id"),
+                    tx_hash: r.get("tx_hash"),
+                    event: serde_json::from_value(payload).map_err(decode_error)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// SQLite dialect.
+#[cfg(feature = "sqlite")]
+pub mod lite {
+    use std::collections::BTreeMap;
+
+    use invoisio_client::EventEnvelope;
+    use sqlx::sqlite::SqlitePool;
+    use sqlx::{QueryBuilder, Row, Sqlite};
+
+    use super::{decode_error, AssetTotals, Order, PaymentFilter};
+    use crate::rows::{PaymentRow, RefundRow};
+
+    const PAYMENT_COLUMNS: &str = "contract_id, invoice_id, payer, asset_code, asset_issuer,
+        amount, recorded_at AS timestamp, ledger, event_id, tx_hash";
+
+    const PENDING_COLUMNS: &str = "contract_id, invoice_id, payer, asset_code, asset_issuer,
+        amount, reported_at AS timestamp, ledger, event_id, NULL AS tx_hash";
+
+    const REFUND_COLUMNS: &str =
+        "contract_id, event_id, invoice_id, amount, refunded_total, ledger, tx_hash";
+
+    pub(super) async fn payment(
+        pool: &SqlitePool,
+        contract_id: &str,
+        invoice_id: &str,
+    ) -> sqlx::Result<Option<PaymentRow>> {
+        sqlx::query_as(&format!(
+            "SELECT {PAYMENT_COLUMNS} FROM payments WHERE contract_id = ?1 AND invoice_id = ?2"
+        ))
+        .bind(contract_id)
+        .bind(invoice_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub(super) async fn pending(
+        pool: &SqlitePool,
+        contract_id: &str,
+        invoice_id: &str,
+    ) -> sqlx::Result<Option<PaymentRow>> {
+        sqlx::query_as(&format!(
+            "SELECT {PENDING_COLUMNS} FROM pending_payments
+             WHERE contract_id = ?1 AND invoice_id = ?2"
+        ))
+        .bind(contract_id)
+        .bind(invoice_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub(super) async fn payments(
+        pool: &SqlitePool,
+        contract_id: &str,
+        filter: &PaymentFilter,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<PaymentRow>> {
+        let mut q = QueryBuilder::new(format!("SELECT {PAYMENT_COLUMNS} FROM payments"));
+        push_filter(&mut q, contract_id, filter);
+        q.push(" ORDER BY recorded_at DESC, invoice_id LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+        q.build_query_as().fetch_all(pool).await
+    }
+
+    pub(super) async fn refunds(
+        pool: &SqlitePool,
+        contract_id: &str,
+        invoice_id: Option<&str>,
+        order: Order,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<RefundRow>> {
+        let mut q = QueryBuilder::new(format!("SELECT {REFUND_COLUMNS} FROM refunds"));
+        q.push(" WHERE contract_id = ").push_bind(contract_id);
+        if let Some(invoice_id) = invoice_id {
+            q.push(" AND invoice_id = ").push_bind(invoice_id);
+        }
+        q.push(order.sql())
+            .push(" LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+        q.build_query_as().fetch_all(pool).await
+    }
+
+    pub(super) async fn aggregates(
+        pool: &SqlitePool,
+        contract_id: &str,
+        filter: &PaymentFilter,
+    ) -> sqlx::Result<Vec<AssetTotals>> {
+        // `refunded_total` is cumulative, so the latest refund carries it.
+        let mut q = QueryBuilder::new(
+            "SELECT asset_code, asset_issuer, amount,
+                 COALESCE((
+                     SELECT refunded_total FROM refunds r
+                     WHERE r.contract_id = payments.contract_id
+                       AND r.invoice_id = payments.invoice_id
+                     ORDER BY r.ledger DESC, r.event_id DESC LIMIT 1
+                 ), '0') AS refunded
+             FROM payments",
+        );
+        push_filter(&mut q, contract_id, filter);
+        let rows = q.build().fetch_all(pool).await?;
+
+        let mut totals: BTreeMap<(String, String), (i64, i128, i128)> = BTreeMap::new();
+        for r in &rows {
+            let amount = parse_amount(r.get("amount"))?;
+            let refunded = parse_amount(r.get("refunded"))?;
+            let t = totals
+                .entry((r.get("asset_code"), r.get("asset_issuer")))
+                .or_default();
+            t.0 += 1;
+            t.1 += amount;
+            t.2 += refunded;
+        }
+        let mut assets: Vec<AssetTotals> = totals
+            .into_iter()
+            .map(
+                |((asset_code, asset_issuer), (payments, volume, refunded))| AssetTotals {
+                    asset_code,
+                    asset_issuer,
+                    payments,
+                    volume: volume.to_string(),
+                    refunded: refunded.to_string(),
+                },
+            )
+            .collect();
+        // Stable, so ties keep the map's asset order.
+        assets.sort_by_key(|a| std::cmp::Reverse(a.payments));
+        Ok(assets)
+    }
+
+    fn parse_amount(s: &str) -> sqlx::Result<i128> {
+        s.parse().map_err(decode_error)
+    }
+
+    /// `WHERE` clause for `filter` over `payments` columns.
+    pub fn push_filter<'a>(
+        q: &mut QueryBuilder<'a, Sqlite>,
+        contract_id: &'a str,
+        filter: &'a PaymentFilter,
+    ) {
+        q.push(" WHERE contract_id = ").push_bind(contract_id);
+        if let Some(payer) = &filter.payer {
+            q.push(" AND payer = ").push_bind(payer);
+        }
+        if let Some(code) = &filter.asset_code {
+            q.push(" AND asset_code = ").push_bind(code);
+        }
+        if let Some(issuer) = &filter.asset_issuer {
+            q.push(" AND asset_issuer = ").push_bind(issuer);
+        }
+        if let Some(ids) = &filter.invoice_ids {
+            q.push(" AND invoice_id IN (");
+            let mut list = q.separated(", ");
+            for id in ids {
+                list.push_bind(id);
+            }
+            list.push_unseparated(")");
+        }
+        if let Some(from) = filter.from {
+            q.push(" AND recorded_at >= ").push_bind(from);
+        }
+        if let Some(to) = filter.to {
+            q.push(" AND recorded_at < ").push_bind(to);
+        }
+    }
+
+    pub(super) async fn tip(pool: &SqlitePool, contract_id: &str) -> sqlx::Result<String> {
+        sqlx::query_scalar(
+            "SELECT COALESCE(MAX(event_id), '') FROM contract_events WHERE contract_id = ?1",
+        )
+        .bind(contract_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub(super) async fn events_after(
+        pool: &SqlitePool,
+        contract_id: &str,
+        cursor: &str,
+        topics: &[&str],
+        limit: i64,
+    ) -> sqlx::Result<Vec<EventEnvelope>> {
+        let mut q = QueryBuilder::new(
+            "SELECT event_id, contract_id, ledger, ledger_closed_at, tx_hash, payload
+             FROM contract_events WHERE contract_id = ",
+        );
+        q.push_bind(contract_id).push(" AND topic IN (");
+        let mut list = q.separated(", ");
+        for topic in topics {
+            list.push_bind(*topic);
+        }
+        list.push_unseparated(")");
+        q.push(" AND event_id > ")
+            .push_bind(cursor)
+            .push(" ORDER BY event_id LIMIT ")
+            .push_bind(limit);
+        let rows = q.build().fetch_all(pool).await?;
+        rows.iter()
+            .map(|r| {
+                Ok(EventEnvelope {
+                    id: r.get("event_id"),
+                    ledger: r.get::<i64, _>("ledger") as u32,
+                    ledger_closed_at: r.get("ledger_closed_at"),
+                    contract_id: r.get("contract_id"),
+                    tx_hash: r.get("tx_hash"),
+                    event: serde_json::from_str(r.get("payload")).map_err(decode_error)?,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,139 @@
+//! Mapping from decoded contract events to database rows.
+//!
+//! Everything here is pure so the mapping can be tested without a database;
+//! [`crate::store`] only binds these values.
+
+use invoisio_client::{ContractEvent, EventEnvelope, EventPage, PaymentRecord};
+
+use crate::error::Result;
+
+/// Raw event log row. Written for every event, decoded or not.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventRow {
+    pub event_id: String,
+    pub contract_id: String,
+    pub ledger: i32,
+    pub ledger_closed_at: String,
+    pub tx_hash: Option<String>,
+    pub topic: String,
+    pub payload: serde_json::Value,
+}
+
+/// Row for `payments` (recorded) or `pending_payments` (reported).
+#[derive(Clone, Debug, PartialEq, Eq, sqlx::FromRow)]
+pub struct PaymentRow {
+    pub contract_id: String,
+    pub invoice_id: String,
+    pub payer: String,
+    pub asset_code: String,
+    pub asset_issuer: String,
+    /// Decimal string; bound as `NUMERIC(39, 0)` so `i128` stays exact.
+    pub amount: String,
+    /// Contract timestamp (unix seconds).
+    pub timestamp: i64,
+    pub ledger: i32,
+    pub event_id: String,
+    pub tx_hash: Option<String>,
+}
+
+impl PaymentRow {
+    fn new(envelope: &EventEnvelope, record: &PaymentRecord) -> Self {
+        Self {
+            contract_id: envelope.contract_id.clone(),
+            invoice_id: record.invoice_id.clone(),
+            payer: record.payer.clone(),
+            asset_code: record.asset.code().to_string(),
+            asset_issuer: record.asset.issuer().to_string(),
+            amount: record.amount.to_string(),
+            timestamp: record.timestamp as i64,
+            ledger: envelope.ledger as i32,
+            event_id: envelope.id.clone(),
+            tx_hash: envelope.tx_hash.clone(),
+        }
+    }
+}
+
+/// Row for `refunds`, one per `payment_refunded` event.
+#[derive(Clone, Debug, PartialEq, Eq, sqlx::FromRow)]
+pub struct RefundRow {
+    pub contract_id: String,
+    pub event_id: String,
+    pub invoice_id: String,
+    /// Decimal strings, like [`PaymentRow::amount`].
+    pub amount: String,
+    pub refunded_total: String,
+    pub ledger: i32,
+    pub tx_hash: Option<String>,
+}
+
+/// Where to resume after a batch has been committed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub cursor: String,
+    pub ledger: u32,
+}
+
+/// Everything one `getEvents` page turns into, applied in one transaction.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Batch {
+    pub events: Vec<EventRow>,
+    pub payments: Vec<PaymentRow>,
+    pub pending: Vec<PaymentRow>,
+    pub refunds: Vec<RefundRow>,
+    /// `None` when the page carried neither events nor a cursor.
+    pub checkpoint: Option<Checkpoint>,
+}
+
+impl Batch {
+    pub fn from_page(page: &EventPage) -> Result<Self> {
+        let mut batch = Batch::default();
+        for envelope in &page.events {
+            batch.events.push(EventRow {
+                event_id: envelope.id.clone(),
+                contract_id: envelope.contract_id.clone(),
+                ledger: envelope.ledger as i32,
+                ledger_closed_at: envelope.ledger_closed_at.clone(),
+                tx_hash: envelope.tx_hash.clone(),
+                topic: envelope.event.topic().to_string(),
+                payload: serde_json::to_value(&envelope.event)?,
+            });
+            match &envelope.event {
+                ContractEvent::PaymentRecorded { record }
+                | ContractEvent::PaymentImported { record } => {
+                    batch.payments.push(PaymentRow::new(envelope, record))
+                }
+                ContractEvent::PendingPaymentReported { record } => {
+                    batch.pending.push(PaymentRow::new(envelope, record))
+                }
+                ContractEvent::PaymentRefunded {
+                    invoice_id,
+                    amount,
+                    refunded_total,
+                } => batch.refunds.push(RefundRow {
+                    contract_id: envelope.contract_id.clone(),
+                    event_id: envelope.id.clone(),
+                    invoice_id: invoice_id.clone(),
+                    amount: amount.to_string(),
+                    refunded_total: refunded_total.to_string(),
+                    ledger: envelope.ledger as i32,
+                    tx_hash: envelope.tx_hash.clone(),
+                }),
+                ContractEvent::Unknown { .. } => {}
+            }
+        }
+
+        // Prefer the server cursor: on an empty page it still moves past
+        // the scanned range. Fall back to the last event id otherwise.
+        let last = page.events.last();
+        let cursor = page.cursor.clone().or_else(|| last.map(|e| e.id.clone()));
+        batch.checkpoint = cursor.map(|cursor| Checkpoint {
+            cursor,
+            ledger: last.map_or(page.latest_ledger, |e| e.ledger),
+        });
+        Ok(batch)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
@@ -0,0 +1,76 @@
+-- Invoisio indexer schema. Idempotent: executed on every start-up.
+
+-- Raw log of every contract event, including ones this version can't decode.
+CREATE TABLE IF NOT EXISTS contract_events (
+    event_id         TEXT        PRIMARY KEY,
+    contract_id      TEXT        NOT NULL,
+    ledger           INTEGER     NOT NULL,
+    ledger_closed_at TEXT        NOT NULL,
+    tx_hash          TEXT,
+    topic            TEXT        NOT NULL,
+    payload          JSONB       NOT NULL
+);
+CREATE INDEX IF NOT EXISTS contract_events_ledger_idx ON contract_events (contract_id, ledger);
+
+-- One row per recorded payment (`payment_recorded`).
+CREATE TABLE IF NOT EXISTS payments (
+    contract_id  TEXT           NOT NULL,
+    invoice_id   TEXT           NOT NULL,
+    payer        TEXT           NOT NULL,
+    asset_code   TEXT           NOT NULL,
+    asset_issuer TEXT           NOT NULL,
+    amount       NUMERIC(39, 0) NOT NULL,
+    recorded_at  TIMESTAMPTZ    NOT NULL,
+    ledger       INTEGER        NOT NULL,
+    event_id     TEXT           NOT NULL,
+    tx_hash      TEXT,
+    PRIMARY KEY (contract_id, invoice_id)
+);
+CREATE INDEX IF NOT EXISTS payments_payer_idx ON payments (payer);
+CREATE INDEX IF NOT EXISTS payments_recorded_at_idx ON payments (recorded_at);
+
+-- Candidates seen via `pending_payment_reported`.
+CREATE TABLE IF NOT EXISTS pending_payments (
+    contract_id  TEXT           NOT NULL,
+    invoice_id   TEXT           NOT NULL,
+    payer        TEXT           NOT NULL,
+    asset_code   TEXT           NOT NULL,
+    asset_issuer TEXT           NOT NULL,
+    amount       NUMERIC(39, 0) NOT NULL,
+    reported_at  TIMESTAMPTZ    NOT NULL,
+    ledger       INTEGER        NOT NULL,
+    event_id     TEXT           NOT NULL,
+    PRIMARY KEY (contract_id, invoice_id)
+);
+
+-- One row per `payment_refunded` event.
+CREATE TABLE IF NOT EXISTS refunds (
+    event_id       TEXT           PRIMARY KEY,
+    contract_id    TEXT           NOT NULL,
+    invoice_id     TEXT           NOT NULL,
+    amount         NUMERIC(39, 0) NOT NULL,
+    refunded_total NUMERIC(39, 0) NOT NULL,
+    ledger         INTEGER        NOT NULL,
+    tx_hash        TEXT
+);
+CREATE INDEX IF NOT EXISTS refunds_invoice_idx ON refunds (contract_id, invoice_id);
+
+-- Ledger ranges that left RPC retention before they were indexed.
+CREATE TABLE IF NOT EXISTS gap_incidents (
+    id               BIGSERIAL   PRIMARY KEY,
+    contract_id      TEXT        NOT NULL,
+    from_ledger      INTEGER     NOT NULL,
+    to_ledger        INTEGER     NOT NULL,
+    detected_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+    resolved_at      TIMESTAMPTZ,
+    events_recovered INTEGER,
+    UNIQUE (contract_id, from_ledger)
+);
+
+-- Resume point per contract.
+CREATE TABLE IF NOT EXISTS checkpoints (
+    contract_id TEXT        PRIMARY KEY,
+    cursor      TEXT        NOT NULL,
+    ledger      INTEGER     NOT NULL,
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
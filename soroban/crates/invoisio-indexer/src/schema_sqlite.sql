@@ -0,0 +1,72 @@
+-- Invoisio indexer schema for the embedded SQLite backend (`sqlite`
+-- feature). Mirrors `schema.sql`; amounts are decimal TEXT so i128 stays
+-- exact, times are Unix seconds. Idempotent: executed on every start-up.
+
+CREATE TABLE IF NOT EXISTS contract_events (
+    event_id         TEXT    PRIMARY KEY,
+    contract_id      TEXT    NOT NULL,
+    ledger           INTEGER NOT NULL,
+    ledger_closed_at TEXT    NOT NULL,
+    tx_hash          TEXT,
+    topic            TEXT    NOT NULL,
+    payload          TEXT    NOT NULL
+);
+CREATE INDEX IF NOT EXISTS contract_events_ledger_idx ON contract_events (contract_id, ledger);
+
+CREATE TABLE IF NOT EXISTS payments (
+    contract_id  TEXT    NOT NULL,
+    invoice_id   TEXT    NOT NULL,
+    payer        TEXT    NOT NULL,
+    asset_code   TEXT    NOT NULL,
+    asset_issuer TEXT    NOT NULL,
+    amount       TEXT    NOT NULL,
+    recorded_at  INTEGER NOT NULL,
+    ledger       INTEGER NOT NULL,
+    event_id     TEXT    NOT NULL,
+    tx_hash      TEXT,
+    PRIMARY KEY (contract_id, invoice_id)
+);
+CREATE INDEX IF NOT EXISTS payments_payer_idx ON payments (payer);
+CREATE INDEX IF NOT EXISTS payments_recorded_at_idx ON payments (recorded_at);
+
+CREATE TABLE IF NOT EXISTS pending_payments (
+    contract_id  TEXT    NOT NULL,
+    invoice_id   TEXT    NOT NULL,
+    payer        TEXT    NOT NULL,
+    asset_code   TEXT    NOT NULL,
+    asset_issuer TEXT    NOT NULL,
+    amount       TEXT    NOT NULL,
+    reported_at  INTEGER NOT NULL,
+    ledger       INTEGER NOT NULL,
+    event_id     TEXT    NOT NULL,
+    PRIMARY KEY (contract_id, invoice_id)
+);
+
+CREATE TABLE IF NOT EXISTS refunds (
+    event_id       TEXT    PRIMARY KEY,
+    contract_id    TEXT    NOT NULL,
+    invoice_id     TEXT    NOT NULL,
+    amount         TEXT    NOT NULL,
+    refunded_total TEXT    NOT NULL,
+    ledger         INTEGER NOT NULL,
+    tx_hash        TEXT
+);
+CREATE INDEX IF NOT EXISTS refunds_invoice_idx ON refunds (contract_id, invoice_id);
+
+CREATE TABLE IF NOT EXISTS gap_incidents (
+    id               INTEGER PRIMARY KEY,
+    contract_id      TEXT    NOT NULL,
+    from_ledger      INTEGER NOT NULL,
+    to_ledger        INTEGER NOT NULL,
+    detected_at      INTEGER NOT NULL DEFAULT (unixepoch()),
+    resolved_at      INTEGER,
+    events_recovered INTEGER,
+    UNIQUE (contract_id, from_ledger)
+);
+
+CREATE TABLE IF NOT EXISTS checkpoints (
+    contract_id TEXT    PRIMARY KEY,
+    cursor      TEXT    NOT NULL,
+    ledger      INTEGER NOT NULL,
+    updated_at  INTEGER NOT NULL DEFAULT (unixepoch())
+);
@@ -0,0 +1,269 @@
+//! Embedded SQLite sink (`sqlite` feature).
+//!
+//! Same contract as [`PgStore`](crate::PgStore): each [`Batch`] is applied in
+//! one transaction with its checkpoint, writes are upserts, and the
+//! checkpoint never moves backwards. Amounts are stored as decimal text, so
+//! totals over them are summed in Rust (see [`crate::query`]).
+
+use std::str::FromStr;
+
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
+use sqlx::{Row, Sqlite, Transaction};
+
+use crate::error::Result;
+use crate::gap::{Gap, GapIncident};
+use crate::rows::{Batch, Checkpoint, EventRow, PaymentRow, RefundRow};
+
+/// Idempotent DDL, run by [`SqliteStore::migrate`].
+pub const SCHEMA: &str = include_str!("schema_sqlite.sql");
+
+#[derive(Clone, Debug)]
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    /// Open (creating if needed) the database at a `sqlite://path` url.
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(database_url)?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        Self::connect_with(options).await
+    }
+
+    /// A throwaway in-memory store, for tests.
+    pub async fn in_memory() -> Result<Self> {
+        Self::connect_with(SqliteConnectOptions::from_str("sqlite::memory:")?).await
+    }
+
+    async fn connect_with(options: SqliteConnectOptions) -> Result<Self> {
+        // One writer at a time anyway, and `:memory:` databases are
+        // per-connection.
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+        Ok(Self { pool })
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    pub async fn migrate(&self) -> Result<()> {
+        sqlx::raw_sql(SCHEMA).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    pub async fn checkpoint(&self, contract_id: &str) -> Result<Option<Checkpoint>> {
+        let row = sqlx::query("SELECT cursor, ledger FROM checkpoints WHERE contract_id = ?1")
+            .bind(contract_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| Checkpoint {
+            cursor: r.get("cursor"),
+            ledger: r.get::<i64, _>("ledger") as u32,
+        }))
+    }
+
+    /// Write a batch and advance the checkpoint atomically.
+    pub async fn apply(&self, contract_id: &str, batch: &Batch) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for event in &batch.events {
+            insert_event(&mut tx, event).await?;
+        }
+        for row in &batch.pending {
+            upsert_pending(&mut tx, row).await?;
+        }
+        for row in &batch.payments {
+            upsert_payment(&mut tx, row).await?;
+        }
+        for row in &batch.refunds {
+            insert_refund(&mut tx, row).await?;
+        }
+        if let Some(checkpoint) = &batch.checkpoint {
+            save_checkpoint(&mut tx, contract_id, checkpoint).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// See [`PgStore::open_gap`](crate::PgStore::open_gap).
+    pub async fn open_gap(&self, contract_id: &str, gap: &Gap) -> Result<i64> {
+        let id = sqlx::query_scalar(
+            "INSERT INTO gap_incidents (contract_id, from_ledger, to_ledger)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT (contract_id, from_ledger) DO UPDATE SET
+                 to_ledger = MAX(gap_incidents.to_ledger, excluded.to_ledger),
+                 resolved_at = NULL,
+                 events_recovered = NULL
+             RETURNING id",
+        )
+        .bind(contract_id)
+        .bind(gap.from_ledger as i64)
+        .bind(gap.to_ledger as i64)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    pub async fn resolve_gap(&self, id: i64, events_recovered: Option<u64>) -> Result<bool> {
+        let done = sqlx::query(
+            "UPDATE gap_incidents SET resolved_at = unixepoch(), events_recovered = ?2
+             WHERE id = ?1",
+        )
+        .bind(id)
+        .bind(events_recovered.map(|n| n as i64))
+        .execute(&self.pool)
+        .await?;
+        Ok(done.rows_affected() == 1)
+    }
+
+    pub async fn gaps(&self, contract_id: &str) -> Result<Vec<GapIncident>> {
+        let rows = sqlx::query(
+            "SELECT id, from_ledger, to_ledger, detected_at, resolved_at, events_recovered
+             FROM gap_incidents WHERE contract_id = ?1 ORDER BY from_ledger",
+        )
+        .bind(contract_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(|r| GapIncident {
+                id: r.get("id"),
+                from_ledger: r.get::<i64, _>("from_ledger") as u32,
+                to_ledger: r.get::<i64, _>("to_ledger") as u32,
+                detected_at: r.get("detected_at"),
+                resolved_at: r.get("resolved_at"),
+                events_recovered: r.get("events_recovered"),
+            })
+            .collect())
+    }
+}
+
+async fn insert_event(tx: &mut Transaction<'_, Sqlite>, e: &EventRow) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO contract_events
+             (event_id, contract_id, ledger, ledger_closed_at, tx_hash, topic, payload)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT (event_id) DO NOTHING",
+    )
+    .bind(&e.event_id)
+    .bind(&e.contract_id)
+    .bind(e.ledger)
+    .bind(&e.ledger_closed_at)
+    .bind(&e.tx_hash)
+    .bind(&e.topic)
+    .bind(e.payload.to_string())
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn upsert_payment(tx: &mut Transaction<'_, Sqlite>, p: &PaymentRow) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO payments
+             (contract_id, invoice_id, payer, asset_code, asset_issuer, amount,
+              recorded_at, ledger, event_id, tx_hash)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT (contract_id, invoice_id) DO UPDATE SET
+             payer = excluded.payer,
+             asset_code = excluded.asset_code,
+             asset_issuer = excluded.asset_issuer,
+             amount = excluded.amount,
+             recorded_at = excluded.recorded_at,
+             ledger = excluded.ledger,
+             event_id = excluded.event_id,
+             tx_hash = excluded.tx_hash",
+    )
+    .bind(&p.contract_id)
+    .bind(&p.invoice_id)
+    .bind(&p.payer)
+    .bind(&p.asset_code)
+    .bind(&p.asset_issuer)
+    .bind(&p.amount)
+    .bind(p.timestamp)
+    .bind(p.ledger)
+    .bind(&p.event_id)
+    .bind(&p.tx_hash)
+    .execute(&mut **tx)
+    .await?;
+
+    // A recorded payment supersedes its pending report.
+    sqlx::query("DELETE FROM pending_payments WHERE contract_id = ?1 AND invoice_id = ?2")
+        .bind(&p.contract_id)
+        .bind(&p.invoice_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+async fn upsert_pending(tx: &mut Transaction<'_, Sqlite>, p: &PaymentRow) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO pending_payments
+             (contract_id, invoice_id, payer, asset_code, asset_issuer, amount,
+              reported_at, ledger, event_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT (contract_id, invoice_id) DO UPDATE SET
+             payer = excluded.payer,
+             asset_code = excluded.asset_code,
+             asset_issuer = excluded.asset_issuer,
+             amount = excluded.amount,
+             reported_at = excluded.reported_at,
+             ledger = excluded.ledger,
+             event_id = excluded.event_id",
+    )
+    .bind(&p.contract_id)
+    .bind(&p.invoice_id)
+    .bind(&p.payer)
+    .bind(&p.asset_code)
+    .bind(&p.asset_issuer)
+    .bind(&p.amount)
+    .bind(p.timestamp)
+    .bind(p.ledger)
+    .bind(&p.event_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn insert_refund(tx: &mut Transaction<'_, Sqlite>, r: &RefundRow) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO refunds
+             (event_id, contract_id, invoice_id, amount, refunded_total, ledger, tx_hash)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT (event_id) DO NOTHING",
+    )
+    .bind(&r.event_id)
+    .bind(&r.contract_id)
+    .bind(&r.invoice_id)
+    .bind(&r.amount)
+    .bind(&r.refunded_total)
+    .bind(r.ledger)
+    .bind(&r.tx_hash)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn save_checkpoint(
+    tx: &mut Transaction<'_, Sqlite>,
+    contract_id: &str,
+    c: &Checkpoint,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO checkpoints (contract_id, cursor, ledger, updated_at)
+         VALUES (?1, ?2, ?3, unixepoch())
+         ON CONFLICT (contract_id) DO UPDATE SET
+             cursor = excluded.cursor,
+             ledger = excluded.ledger,
+             updated_at = unixepoch()
+         WHERE checkpoints.ledger <= excluded.ledger",
+    )
+    .bind(contract_id)
+    .bind(&c.cursor)
+    .bind(c.ledger as i64)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
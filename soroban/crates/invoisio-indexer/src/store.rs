@@ -0,0 +1,95 @@
+//! [`Store`], the indexer's database: Postgres (`postgres` feature) or the
+//! embedded SQLite file (`sqlite` feature), picked by url scheme.
+
+use crate::error::Result;
+use crate::gap::{Gap, GapIncident};
+#[cfg(feature = "postgres")]
+use crate::postgres::PgStore;
+use crate::rows::{Batch, Checkpoint};
+#[cfg(feature = "sqlite")]
+use crate::sqlite::SqliteStore;
+
+/// The indexer's database.
+#[derive(Clone, Debug)]
+pub enum Store {
+    #[cfg(feature = "postgres")]
+    Postgres(PgStore),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteStore),
+}
+
+impl Store {
+    /// Connect by url scheme: `sqlite:` opens (creating if needed) a local
+    /// file, anything else is handed to Postgres. Fails if the backend's
+    /// feature is off.
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        if database_url.starts_with("sqlite:") {
+            #[cfg(feature = "sqlite")]
+            return Ok(Store::Sqlite(SqliteStore::connect(database_url).await?));
+            #[cfg(not(feature = "sqlite"))]
+            return Err(crate::Error::Config(
+                "`sqlite:` database urls need the `sqlite` feature".into(),
+            ));
+        }
+        #[cfg(feature = "postgres")]
+        return Ok(Store::Postgres(PgStore::connect(database_url).await?));
+        #[cfg(not(feature = "postgres"))]
+        return Err(crate::Error::Config(
+            "Postgres database urls need the `postgres` feature".into(),
+        ));
+    }
+
+    pub async fn migrate(&self) -> Result<()> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(store) => store.migrate().await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.migrate().await,
+        }
+    }
+
+    pub async fn checkpoint(&self, contract_id: &str) -> Result<Option<Checkpoint>> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(store) => store.checkpoint(contract_id).await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.checkpoint(contract_id).await,
+        }
+    }
+
+    pub async fn apply(&self, contract_id: &str, batch: &Batch) -> Result<()> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(store) => store.apply(contract_id, batch).await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.apply(contract_id, batch).await,
+        }
+    }
+
+    pub async fn open_gap(&self, contract_id: &str, gap: &Gap) -> Result<i64> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(store) => store.open_gap(contract_id, gap).await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.open_gap(contract_id, gap).await,
+        }
+    }
+
+    pub async fn resolve_gap(&self, id: i64, events_recovered: Option<u64>) -> Result<bool> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(store) => store.resolve_gap(id, events_recovered).await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.resolve_gap(id, events_recovered).await,
+        }
+    }
+
+    pub async fn gaps(&self, contract_id: &str) -> Result<Vec<GapIncident>> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Store::Postgres(store) => store.gaps(contract_id).await,
+            #[cfg(feature = "sqlite")]
+            Store::Sqlite(store) => store.gaps(contract_id).await,
+        }
+    }
+}
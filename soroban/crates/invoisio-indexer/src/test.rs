@@ -0,0 +1,606 @@
+#![cfg(test)]
+
+use super::*;
+use invoisio_client::{Asset, ContractEvent, EventEnvelope, EventPage, PaymentRecord};
+
+const CONTRACT: &str = "CCJZ5DGASBWQXR5MPFCJXMBI333XE5U3FSJTNQU7RIKE3P5GN2K2WYD5";
+const PAYER: &str = "GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5";
+
+fn record(invoice_id: &str, amount: i128) -> PaymentRecord {
+    PaymentRecord {
+        invoice_id: invoice_id.into(),
+        payer: PAYER.into(),
+        payer_mux_id: None,
+        asset: Asset::Token {
+            code: "USDC".into(),
+            issuer: PAYER.into(),
+        },
+        amount,
+        timestamp: 1_700_000_000,
+    }
+}
+
+fn envelope(id: &str, ledger: u32, event: ContractEvent) -> EventEnvelope {
+    EventEnvelope {
+        id: id.into(),
+        ledger,
+        ledger_closed_at: "2024-01-01T00:00:00Z".into(),
+        contract_id: CONTRACT.into(),
+        tx_hash: Some("ab".repeat(32)),
+        event,
+    }
+}
+
+fn page(events: Vec<EventEnvelope>, cursor: Option<&str>) -> EventPage {
+    EventPage {
+        events,
+        cursor: cursor.map(Into::into),
+        latest_ledger: 500,
+        oldest_ledger: Some(1),
+    }
+}
+
+// Row mapping
+
+#[test]
+fn test_events_are_split_by_kind() {
+    let batch = Batch::from_page(&page(
+        vec![
+            envelope(
+                "0001-1",
+                10,
+                ContractEvent::PendingPaymentReported {
+                    record: record("invoisio-a", 5),
+                },
+            ),
+            envelope(
+                "0002-1",
+                11,
+                ContractEvent::PaymentRecorded {
+                    record: record("invoisio-b", 7),
+                },
+            ),
+            envelope(
+                "0003-1",
+                12,
+                ContractEvent::Unknown {
+                    topic: "something_new".into(),
+                },
+            ),
+        ],
+        Some("0003-1"),
+    ))
+    .unwrap();
+
+    assert_eq!(batch.events.len(), 3);
+    assert_eq!(batch.events[2].topic, "something_new");
+    assert_eq!(batch.pending.len(), 1);
+    assert_eq!(batch.pending[0].invoice_id, "invoisio-a");
+    assert_eq!(batch.payments.len(), 1);
+    assert_eq!(batch.payments[0].invoice_id, "invoisio-b");
+    assert_eq!(batch.payments[0].ledger, 11);
+    assert_eq!(batch.payments[0].asset_code, "USDC");
+}
+
+#[test]
+fn test_amount_is_kept_exact() {
+    let batch = Batch::from_page(&page(
+        vec![envelope(
+            "0001-1",
+            10,
+            ContractEvent::PaymentRecorded {
+                record: record("invoisio-max", i128::MAX),
+            },
+        )],
+        None,
+    ))
+    .unwrap();
+    assert_eq!(batch.payments[0].amount, i128::MAX.to_string());
+}
+
+#[test]
+fn test_native_asset_maps_to_xlm_with_empty_issuer() {
+    let mut r = record("invoisio-xlm", 1);
+    r.asset = Asset::Native;
+    let batch = Batch::from_page(&page(
+        vec![envelope("0001-1", 10, ContractEvent::PaymentRecorded { record: r })],
+        None,
+    ))
+    .unwrap();
+    assert_eq!(batch.payments[0].asset_code, "XLM");
+    assert_eq!(batch.payments[0].asset_issuer, "");
+}
+
+#[test]
+fn test_refund_event_becomes_refund_row() {
+    let batch = Batch::from_page(&page(
+        vec![envelope(
+            "0004-1",
+            13,
+            ContractEvent::PaymentRefunded {
+                invoice_id: "invoisio-b".into(),
+                amount: 2,
+                refunded_total: 3,
+            },
+        )],
+        None,
+    ))
+    .unwrap();
+    assert_eq!(batch.events[0].topic, "payment_refunded");
+    assert!(batch.payments.is_empty());
+    assert_eq!(batch.refunds.len(), 1);
+    let refund = &batch.refunds[0];
+    assert_eq!(refund.invoice_id, "invoisio-b");
+    assert_eq!(refund.event_id, "0004-1");
+    assert_eq!(refund.amount, "2");
+    assert_eq!(refund.refunded_total, "3");
+    assert_eq!(refund.ledger, 13);
+}
+
+// Checkpointing
+
+#[test]
+fn test_checkpoint_prefers_server_cursor() {
+    let batch = Batch::from_page(&page(
+        vec![envelope(
+            "0001-1",
+            10,
+            ContractEvent::Unknown { topic: "x".into() },
+        )],
+        Some("0009-0"),
+    ))
+    .unwrap();
+    assert_eq!(
+        batch.checkpoint,
+        Some(Checkpoint {
+            cursor: "0009-0".into(),
+            ledger: 10
+        })
+    );
+}
+
+#[test]
+fn test_checkpoint_falls_back_to_last_event_id() {
+    let batch = Batch::from_page(&page(
+        vec![
+            envelope("0001-1", 10, ContractEvent::Unknown { topic: "x".into() }),
+            envelope("0002-1", 11, ContractEvent::Unknown { topic: "x".into() }),
+        ],
+        None,
+    ))
+    .unwrap();
+    assert_eq!(batch.checkpoint.unwrap().cursor, "0002-1");
+}
+
+#[test]
+fn test_empty_page_advances_to_server_cursor_at_latest_ledger() {
+    let batch = Batch::from_page(&page(vec![], Some("0500-0"))).unwrap();
+    assert!(batch.is_empty());
+    assert_eq!(
+        batch.checkpoint,
+        Some(Checkpoint {
+            cursor: "0500-0".into(),
+            ledger: 500
+        })
+    );
+
+    let batch = Batch::from_page(&page(vec![], None)).unwrap();
+    assert_eq!(batch.checkpoint, None);
+}
+
+// Backfill
+
+#[test]
+fn test_read_archive_filters_contract_and_skips_blank_lines() {
+    let other = EventEnvelope {
+        contract_id: "COTHER".into(),
+        ..envelope("0002-1", 11, ContractEvent::Unknown { topic: "x".into() })
+    };
+    let ours = envelope(
+        "0001-1",
+        10,
+        ContractEvent::PaymentRecorded {
+            record: record("invoisio-a", 5),
+        },
+    );
+    let path = std::env::temp_dir().join(format!("invoisio-archive-{}.jsonl", std::process::id()));
+    let body = format!(
+        "{}\n\n{}\n",
+        serde_json::to_string(&ours).unwrap(),
+        serde_json::to_string(&other).unwrap()
+    );
+    std::fs::write(&path, body).unwrap();
+
+    let events = backfill::read_archive(&path, CONTRACT).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(events, vec![ours]);
+}
+
+#[test]
+fn test_read_archive_reports_bad_line() {
+    let path = std::env::temp_dir().join(format!("invoisio-bad-{}.jsonl", std::process::id()));
+    std::fs::write(&path, "not json\n").unwrap();
+    let err = backfill::read_archive(&path, CONTRACT).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(err, Error::Archive(msg) if msg.contains(":1:")));
+}
+
+#[test]
+fn test_archive_batches_checkpoint_each_chunk() {
+    let events: Vec<_> = (0..5)
+        .map(|n| {
+            let id = format!("000{n}-1");
+            envelope(&id, 10 + n, ContractEvent::Unknown { topic: "x".into() })
+        })
+        .collect();
+    let batches = backfill::archive_batches(&events, 2).unwrap();
+    let ledgers: Vec<_> = batches
+        .iter()
+        .map(|b| b.checkpoint.as_ref().unwrap().ledger)
+        .collect();
+    assert_eq!(ledgers, vec![11, 13, 14]);
+    assert_eq!(batches[2].checkpoint.as_ref().unwrap().cursor, "0004-1");
+}
+
+// Gaps
+
+#[test]
+fn test_gap_detect_only_below_retention() {
+    assert_eq!(
+        gap::detect(100, 250),
+        Some(Gap {
+            from_ledger: 100,
+            to_ledger: 249,
+        })
+    );
+    assert_eq!(gap::detect(250, 250), None);
+    assert_eq!(gap::detect(300, 250), None);
+}
+
+// GraphQL
+
+#[cfg(feature = "postgres")]
+fn unreachable_store() -> Store {
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .acquire_timeout(std::time::Duration::from_secs(1))
+        .connect_lazy("postgres://invoisio@127.0.0.1:1/invoisio")
+        .unwrap();
+    Store::Postgres(PgStore::with_pool(pool))
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn test_graphql_schema_exposes_queries() {
+    let sdl = graphql::schema(unreachable_store(), CONTRACT).sdl();
+    for field in [
+        "payment(invoiceId: String!): Payment",
+        "payments(filter: PaymentFilter, first: Int, after: String): PaymentConnection!",
+        "invoice(invoiceId: String!): Invoice!",
+        "refunds(invoiceId: String, first: Int, after: String): RefundConnection!",
+        "aggregates(filter: PaymentFilter): Aggregates!",
+    ] {
+        assert!(sdl.contains(field), "missing `{field}`");
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn test_graphql_hides_database_errors() {
+    let schema = graphql::schema(unreachable_store(), CONTRACT);
+    let response = schema
+        .execute("{ payments(first: 5) { edges { cursor } } }")
+        .await;
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(response.errors[0].message, "internal error");
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+fn test_graphql_filter_binds_only_given_fields() {
+    let filter = graphql::PaymentFilter {
+        asset_code: Some("USDC".into()),
+        invoice_ids: Some(vec!["invoisio-a".into()]),
+        from: Some(1_704_067_200),
+        ..Default::default()
+    };
+    let mut q = sqlx::QueryBuilder::new("SELECT 1 FROM payments");
+    query::pg::push_filter(&mut q, CONTRACT, &filter);
+    assert_eq!(
+        q.sql(),
+        "SELECT 1 FROM payments WHERE contract_id = $1 AND asset_code = $2 \
+         AND invoice_id = ANY($3) AND recorded_at >= to_timestamp($4)"
+    );
+    let filter = graphql::PaymentFilter::default();
+    let q = query::pg::aggregates_query(CONTRACT, &filter);
+    assert!(q
+        .sql()
+        .ends_with(" WHERE contract_id = $1 GROUP BY asset_code, asset_issuer ORDER BY payments DESC, asset_code"));
+}
+
+#[test]
+fn test_graphql_net_amount_is_exact() {
+    let max = i128::MAX.to_string();
+    assert_eq!(graphql::net_amount(&max, "0").unwrap(), max);
+    assert_eq!(graphql::net_amount("50", "20").unwrap(), "30");
+    assert!(graphql::net_amount("1.5", "0").is_err());
+}
+
+// gRPC
+
+#[test]
+fn test_grpc_event_carries_cursor_and_exact_amounts() {
+    use grpc::proto::payment_event::Event;
+
+    let recorded = envelope(
+        "0002-1",
+        11,
+        ContractEvent::PaymentRecorded {
+            record: record("invoisio-max", i128::MAX),
+        },
+    );
+    let event = grpc::payment_event(&recorded).unwrap();
+    assert_eq!(event.cursor, "0002-1");
+    let Some(Event::Recorded(payment)) = event.event else {
+        panic!("expected a recorded payment");
+    };
+    assert_eq!(payment.amount, i128::MAX.to_string());
+    assert_eq!((payment.ledger, payment.timestamp), (11, 1_700_000_000));
+    assert_eq!(payment.tx_hash, "ab".repeat(32));
+
+    let refunded = envelope(
+        "0003-1",
+        12,
+        ContractEvent::PaymentRefunded {
+            invoice_id: "invoisio-max".into(),
+            amount: 5,
+            refunded_total: 5,
+        },
+    );
+    let event = grpc::payment_event(&refunded).unwrap();
+    assert!(matches!(event.event, Some(Event::Refunded(r)) if r.event_id == "0003-1"));
+
+    let unknown = envelope("0004-1", 13, ContractEvent::Unknown { topic: "x".into() });
+    assert_eq!(grpc::payment_event(&unknown), None);
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn test_grpc_hides_database_errors() {
+    use grpc::proto::payments_server::Payments;
+
+    let service = grpc::PaymentsService::new(
+        unreachable_store(),
+        CONTRACT,
+        Feed::new(),
+        std::time::Duration::from_secs(1),
+    );
+    let request = tonic::Request::new(grpc::proto::WatchPaymentsRequest::default());
+    let status = service.watch_payments(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Internal);
+    assert_eq!(status.message(), "internal error");
+}
+
+// Publishing
+
+#[test]
+fn test_publish_message_schema_is_stable() {
+    let batch = Batch::from_page(&page(
+        vec![envelope(
+            "0002-1",
+            11,
+            ContractEvent::PaymentRefunded {
+                invoice_id: "invoisio-b".into(),
+                amount: 2,
+                refunded_total: 3,
+            },
+        )],
+        None,
+    ))
+    .unwrap();
+    let message = publish::EventMessage::new(&batch.events[0]);
+    assert_eq!(message.key(), "0002-1");
+    let json: serde_json::Value = serde_json::from_slice(&message.to_json().unwrap()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "schema": "invoisio.contract_event.v1",
+            "event_id": "0002-1",
+            "contract_id": CONTRACT,
+            "ledger": 11,
+            "ledger_closed_at": "2024-01-01T00:00:00Z",
+            "tx_hash": "ab".repeat(32),
+            "topic": "payment_refunded",
+            "data": {
+                "event": "payment_refunded",
+                "invoice_id": "invoisio-b",
+                "amount": "2",
+                "refunded_total": "3"
+            }
+        })
+    );
+}
+
+#[test]
+fn test_publish_url_parses_kafka_and_nats() {
+    assert_eq!(
+        "kafka://k1:9092,k2:9092/invoisio.events"
+            .parse::<Sink>()
+            .unwrap(),
+        Sink::Kafka {
+            brokers: "k1:9092,k2:9092".into(),
+            topic: "invoisio.events".into()
+        }
+    );
+    assert_eq!(
+        "nats://localhost:4222/invoisio.events"
+            .parse::<Sink>()
+            .unwrap(),
+        Sink::Nats {
+            url: "nats://localhost:4222".into(),
+            subject: "invoisio.events".into()
+        }
+    );
+    for bad in [
+        "kafka://k1:9092",
+        "kafka:///topic",
+        "amqp://h/q",
+        "nats://h/a/b",
+    ] {
+        assert!(bad.parse::<Sink>().is_err(), "{bad}");
+    }
+}
+
+// SQLite
+
+#[cfg(feature = "sqlite")]
+async fn sqlite_store() -> Store {
+    let store = Store::Sqlite(SqliteStore::in_memory().await.unwrap());
+    store.migrate().await.unwrap();
+    let mut xlm = record("invoisio-xlm", 30);
+    xlm.asset = Asset::Native;
+    let events = vec![
+        envelope(
+            "0001-1",
+            10,
+            ContractEvent::PaymentRecorded {
+                record: record("invoisio-a", i128::MAX / 2),
+            },
+        ),
+        envelope(
+            "0002-1",
+            11,
+            ContractEvent::PaymentRecorded {
+                record: record("invoisio-b", i128::MAX / 2),
+            },
+        ),
+        envelope("0003-1", 12, ContractEvent::PaymentRecorded { record: xlm }),
+        envelope(
+            "0004-1",
+            13,
+            ContractEvent::PaymentRefunded {
+                invoice_id: "invoisio-a".into(),
+                amount: 5,
+                refunded_total: 5,
+            },
+        ),
+        envelope(
+            "0005-1",
+            14,
+            ContractEvent::PendingPaymentReported {
+                record: record("invoisio-c", 1),
+            },
+        ),
+    ];
+    let batch = Batch::from_page(&page(events, Some("0005-1"))).unwrap();
+    store.apply(CONTRACT, &batch).await.unwrap();
+    store
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_sqlite_apply_is_idempotent_and_checkpoint_only_advances() {
+    let store = sqlite_store().await;
+    let replay = Batch::from_page(&page(
+        vec![envelope(
+            "0001-1",
+            10,
+            ContractEvent::PaymentRecorded {
+                record: record("invoisio-a", i128::MAX / 2),
+            },
+        )],
+        None,
+    ))
+    .unwrap();
+    store.apply(CONTRACT, &replay).await.unwrap();
+
+    let checkpoint = store.checkpoint(CONTRACT).await.unwrap().unwrap();
+    assert_eq!(checkpoint.cursor, "0005-1");
+    assert_eq!(checkpoint.ledger, 14);
+
+    let queries = query::Queries::new(store, CONTRACT);
+    let page = queries.payments(&Default::default(), 10, 0).await.unwrap();
+    assert_eq!(page.len(), 3);
+    assert_eq!(queries.tip().await.unwrap(), "0005-1");
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_sqlite_queries_match_postgres_semantics() {
+    let queries = query::Queries::new(sqlite_store().await, CONTRACT);
+
+    let usdc = query::PaymentFilter {
+        asset_code: Some("USDC".into()),
+        invoice_ids: Some(vec!["invoisio-a".into(), "invoisio-b".into()]),
+        from: Some(1_700_000_000),
+        ..Default::default()
+    };
+    assert_eq!(queries.payments(&usdc, 10, 0).await.unwrap().len(), 2);
+
+    // Totals past i64 stay exact.
+    let totals = queries.aggregates(&Default::default()).await.unwrap();
+    assert_eq!(totals[0].asset_code, "USDC");
+    assert_eq!(totals[0].payments, 2);
+    assert_eq!(totals[0].volume, (i128::MAX / 2 * 2).to_string());
+    assert_eq!(totals[0].refunded, "5");
+    assert_eq!(totals[1].asset_code, "XLM");
+    assert_eq!(totals[1].asset_issuer, "");
+
+    let pending = queries.pending("invoisio-c").await.unwrap().unwrap();
+    assert_eq!((pending.amount.as_str(), pending.tx_hash), ("1", None));
+    let refunds = queries.invoice_refunds("invoisio-a").await.unwrap();
+    assert_eq!(refunds.len(), 1);
+
+    let topics = ["payment_refunded"];
+    let events = queries.events_after("0001-1", &topics, 10).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert!(matches!(
+        events[0].event,
+        ContractEvent::PaymentRefunded { amount: 5, .. }
+    ));
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_sqlite_backs_graphql() {
+    let schema = graphql::schema(sqlite_store().await, CONTRACT);
+    let response = schema
+        .execute(r#"{ invoice(invoiceId: "invoisio-a") { refundedTotal netAmount } }"#)
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["invoice"]["refundedTotal"], "5");
+    assert_eq!(
+        data["invoice"]["netAmount"],
+        (i128::MAX / 2 - 5).to_string()
+    );
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_sqlite_gap_incidents_widen_and_reopen() {
+    let store = sqlite_store().await;
+    let first = Gap {
+        from_ledger: 15,
+        to_ledger: 40,
+    };
+    let id = store.open_gap(CONTRACT, &first).await.unwrap();
+    assert!(store.resolve_gap(id, Some(3)).await.unwrap());
+    assert!(!store.resolve_gap(id + 1, None).await.unwrap());
+
+    // Detected again from the same resume point, further behind.
+    let wider = Gap {
+        from_ledger: 15,
+        to_ledger: 60,
+    };
+    assert_eq!(store.open_gap(CONTRACT, &wider).await.unwrap(), id);
+    let gaps = store.gaps(CONTRACT).await.unwrap();
+    assert_eq!(gaps.len(), 1);
+    assert_eq!((gaps[0].from_ledger, gaps[0].to_ledger), (15, 60));
+    assert_eq!(gaps[0].resolved_at, None);
+    assert_eq!(gaps[0].events_recovered, None);
+
+    // A narrower report never shrinks the recorded range.
+    store.open_gap(CONTRACT, &first).await.unwrap();
+    assert_eq!(store.gaps(CONTRACT).await.unwrap()[0].to_ledger, 60);
+    assert!(store.gaps("COTHER").await.unwrap().is_empty());
+}
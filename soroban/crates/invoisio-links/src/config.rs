@@ -0,0 +1,81 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+/// Link service configuration, read from environment variables.
+///
+/// | Variable                      | Required | Default        |
+/// |-------------------------------|----------|----------------|
+/// | `INVOISIO_RPC_URL`            | yes      |                |
+/// | `INVOISIO_NETWORK_PASSPHRASE` | yes      |                |
+/// | `INVOISIO_CONTRACT_ID`        | yes      |                |
+/// | `INVOISIO_DESTINATION`        | yes      |                |
+/// | `INVOISIO_LINKS_BASE_URL`     | yes      |                |
+/// | `INVOISIO_LINKS_API_KEY`      | yes      |                |
+/// | `INVOISIO_LINKS_ADDR`         | no       | `0.0.0.0:8080` |
+/// | `INVOISIO_LINKS_DB`           | no       | `links.db`     |
+/// | `INVOISIO_LINK_TTL_SECS`      | no       | `86400`        |
+/// | `INVOISIO_LINK_MAX_TTL_SECS`  | no       | `2592000`      |
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub rpc_url: String,
+    pub network_passphrase: String,
+    pub contract_id: String,
+    /// `G...` account that receives invoice payments.
+    pub destination: String,
+    /// Public URL the service is reached at; links are `{base_url}/l/{code}`.
+    pub base_url: String,
+    /// Bearer token required to mint links and read their stats.
+    pub api_key: String,
+    pub addr: SocketAddr,
+    /// SQLite database holding the links.
+    pub db_path: PathBuf,
+    /// Lifetime of a link minted without `ttl_secs`.
+    pub default_ttl: Duration,
+    /// Longest `ttl_secs` a mint request may ask for.
+    pub max_ttl: Duration,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            rpc_url: required("INVOISIO_RPC_URL")?,
+            network_passphrase: required("INVOISIO_NETWORK_PASSPHRASE")?,
+            contract_id: required("INVOISIO_CONTRACT_ID")?,
+            destination: required("INVOISIO_DESTINATION")?,
+            base_url: required("INVOISIO_LINKS_BASE_URL")?
+                .trim_end_matches('/')
+                .to_string(),
+            api_key: required("INVOISIO_LINKS_API_KEY")?,
+            addr: parsed(
+                "INVOISIO_LINKS_ADDR",
+                SocketAddr::from(([0, 0, 0, 0], 8080)),
+            )?,
+            db_path: optional("INVOISIO_LINKS_DB")
+                .unwrap_or_else(|| "links.db".into())
+                .into(),
+            default_ttl: Duration::from_secs(parsed("INVOISIO_LINK_TTL_SECS", 86_400)?),
+            max_ttl: Duration::from_secs(parsed("INVOISIO_LINK_MAX_TTL_SECS", 2_592_000)?),
+        })
+    }
+}
+
+fn required(name: &str) -> Result<String> {
+    optional(name).ok_or_else(|| Error::Config(format!("{name} is not set")))
+}
+
+fn optional(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn parsed<T: std::str::FromStr>(name: &str, default: T) -> Result<T> {
+    optional(name)
+        .map(|v| {
+            v.parse()
+                .map_err(|_| Error::Config(format!("{name}=`{v}` is not valid")))
+        })
+        .transpose()
+        .map(|v| v.unwrap_or(default))
+}
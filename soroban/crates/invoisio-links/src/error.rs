@@ -0,0 +1,34 @@
+/// Errors surfaced by the link service.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+
+    /// Rejected mint request.
+    #[error("invalid link: {0}")]
+    Invalid(String),
+
+    #[error("link {0} not found")]
+    NotFound(String),
+
+    #[error("link {0} has expired")]
+    Expired(String),
+
+    #[error("missing or wrong API key")]
+    Unauthorized,
+
+    /// Soroban RPC / decoding failure.
+    #[error(transparent)]
+    Client(#[from] invoisio_client::Error),
+
+    #[error("database: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
@@ -0,0 +1,30 @@
+//! # invoisio-links
+//!
+//! Mints short-lived, shareable payment links for invoices. Opening a link
+//! counts the open and resolves it to the invoice's SEP-7 `web+stellar:pay`
+//! URI and the payer's `preauthorize` call, with whether the contract
+//! already holds a payment for the invoice. Links live in SQLite.
+//!
+//! ## Module layout
+//! | Module      | Responsibility                                          |
+//! |-------------|---------------------------------------------------------|
+//! | `config.rs` | Environment configuration                               |
+//! | `link.rs`   | [`Link`], [`NewLink`], [`Resolution`], code generation  |
+//! | `store.rs`  | [`LinkStore`]: SQLite persistence and open counts       |
+//! | `links.rs`  | [`Links`]: minting and resolving against the contract   |
+//! | `server.rs` | HTTP routes                                             |
+
+pub mod config;
+pub mod error;
+pub mod link;
+pub mod links;
+pub mod server;
+pub mod store;
+
+pub use config::Config;
+pub use error::{Error, Result};
+pub use link::{ContractCall, Link, Minted, NewLink, Resolution};
+pub use links::Links;
+pub use store::LinkStore;
+
+mod test;
@@ -0,0 +1,130 @@
+//! Payment links and what they resolve to.
+
+use invoisio_client::Asset;
+use rand::distributions::{Alphanumeric, DistString};
+use serde::{Deserialize, Serialize};
+
+/// Length of a link code. 62^10 codes keep links unguessable while they
+/// are alive.
+pub const CODE_LEN: usize = 10;
+
+/// Contract function a payer signs to commit to paying an invoice.
+pub const PREAUTHORIZE: &str = "preauthorize";
+
+/// A minted link.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Link {
+    pub code: String,
+    pub invoice_id: String,
+    pub asset: Asset,
+    /// In the asset's smallest unit; `None` lets the payer choose.
+    #[serde(default, with = "opt_i128_string")]
+    pub amount: Option<i128>,
+    /// Shown to the payer by the wallet.
+    #[serde(default)]
+    pub msg: Option<String>,
+    /// Unix seconds.
+    pub created_at: u64,
+    /// Unix seconds; the link stops resolving from then on.
+    pub expires_at: u64,
+    /// Times the link was resolved.
+    pub opens: u64,
+    #[serde(default)]
+    pub last_opened_at: Option<u64>,
+}
+
+impl Link {
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// Body of `POST /links`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NewLink {
+    pub invoice_id: String,
+    pub asset: Asset,
+    #[serde(default, with = "opt_i128_string")]
+    pub amount: Option<i128>,
+    #[serde(default)]
+    pub msg: Option<String>,
+    /// Lifetime in seconds; the service default if omitted.
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+/// Response of `POST /links`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Minted {
+    pub code: String,
+    /// Shareable `{base_url}/l/{code}`.
+    pub url: String,
+    pub expires_at: u64,
+}
+
+/// What `GET /l/{code}` resolves a link to.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Resolution {
+    pub code: String,
+    pub invoice_id: String,
+    /// SEP-7 `web+stellar:pay` URI for wallets.
+    pub uri: String,
+    /// `preauthorize` call for wallets that sign Soroban invocations. Only
+    /// set when the link has an amount.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_call: Option<ContractCall>,
+    /// Whether the contract holds a payment for the invoice; `None` if it
+    /// could not be read.
+    pub paid: Option<bool>,
+    pub expires_at: u64,
+}
+
+/// A contract invocation for the payer to sign. `args` leaves out `payer`,
+/// which is the signing wallet's own address.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContractCall {
+    pub contract_id: String,
+    pub function: String,
+    pub args: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ContractCall {
+    /// `preauthorize(invoice_id, payer, asset_code, asset_issuer,
+    /// max_amount)` for `link`, or `None` without an amount.
+    pub fn preauthorize(contract_id: &str, link: &Link) -> Option<Self> {
+        let amount = link.amount?;
+        let mut args = serde_json::Map::new();
+        args.insert("invoice_id".into(), link.invoice_id.clone().into());
+        args.insert("asset_code".into(), link.asset.code().into());
+        args.insert("asset_issuer".into(), link.asset.issuer().into());
+        args.insert("max_amount".into(), amount.to_string().into());
+        Some(Self {
+            contract_id: contract_id.to_string(),
+            function: PREAUTHORIZE.to_string(),
+            args,
+        })
+    }
+}
+
+/// A fresh random link code.
+pub fn new_code() -> String {
+    Alphanumeric.sample_string(&mut rand::thread_rng(), CODE_LEN)
+}
+
+/// Amounts as decimal strings: JSON numbers cannot carry every `i128`.
+mod opt_i128_string {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<i128>, s: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(v) => s.serialize_some(&v.to_string()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<i128>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|s| s.parse().map_err(D::Error::custom))
+            .transpose()
+    }
+}
@@ -0,0 +1,158 @@
+//! Minting and resolving links.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use invoisio_client::{InvoicePaymentClient, PayRequest};
+
+use crate::error::{Error, Result};
+use crate::link::{new_code, ContractCall, Link, Minted, NewLink, Resolution};
+use crate::store::LinkStore;
+
+/// The link service: [`LinkStore`] plus the contract the links pay into.
+pub struct Links {
+    store: LinkStore,
+    client: InvoicePaymentClient,
+    /// `G...` account that receives invoice payments.
+    destination: String,
+    base_url: String,
+    default_ttl: Duration,
+    max_ttl: Duration,
+}
+
+impl Links {
+    pub fn new(
+        store: LinkStore,
+        client: InvoicePaymentClient,
+        destination: String,
+        base_url: String,
+        default_ttl: Duration,
+        max_ttl: Duration,
+    ) -> Self {
+        Self {
+            store,
+            client,
+            destination,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            default_ttl,
+            max_ttl,
+        }
+    }
+
+    /// Shareable URL of `code`.
+    pub fn url(&self, code: &str) -> String {
+        format!("{}/l/{code}", self.base_url)
+    }
+
+    /// Mint a link at `now` (Unix seconds).
+    ///
+    /// Fails with [`Error::Invalid`] if wallets could not pay the request
+    /// (see [`PayRequest::for_invoice`]), `invoice_id` is empty, or
+    /// `ttl_secs` is zero or above the configured maximum.
+    pub async fn mint(&self, new: &NewLink, now: u64) -> Result<Minted> {
+        if new.invoice_id.is_empty() {
+            return Err(Error::Invalid("invoice_id is empty".into()));
+        }
+        let ttl = new.ttl_secs.unwrap_or(self.default_ttl.as_secs());
+        if ttl == 0 || ttl > self.max_ttl.as_secs() {
+            return Err(Error::Invalid(format!(
+                "ttl_secs must be between 1 and {}",
+                self.max_ttl.as_secs()
+            )));
+        }
+        let mut link = Link {
+            code: String::new(),
+            invoice_id: new.invoice_id.clone(),
+            asset: new.asset.clone(),
+            amount: new.amount,
+            msg: new.msg.clone(),
+            created_at: now,
+            expires_at: now.saturating_add(ttl),
+            opens: 0,
+            last_opened_at: None,
+        };
+        self.pay_request(&link)
+            .map_err(|e| Error::Invalid(e.to_string()))?;
+
+        // A taken code only means drawing another one.
+        loop {
+            link.code = new_code();
+            if self.store.insert(&link).await? {
+                break;
+            }
+        }
+        tracing::info!(code = %link.code, invoice = %link.invoice_id, "minted link");
+        Ok(Minted {
+            url: self.url(&link.code),
+            code: link.code,
+            expires_at: link.expires_at,
+        })
+    }
+
+    /// Link `code` as stored, with its open count.
+    pub async fn link(&self, code: &str) -> Result<Link> {
+        self.store
+            .get(code)
+            .await?
+            .ok_or_else(|| Error::NotFound(code.to_string()))
+    }
+
+    /// Count an open of `code` at `now` and resolve it. Fails with
+    /// [`Error::NotFound`] or [`Error::Expired`].
+    pub async fn resolve(&self, code: &str, now: u64) -> Result<Resolution> {
+        let link = self.live(code, now).await?;
+        self.store.record_open(code, now).await?;
+
+        let paid = match self.client.has_payment(&link.invoice_id).await {
+            Ok(paid) => Some(paid),
+            Err(err) => {
+                tracing::warn!(invoice = %link.invoice_id, error = %err, "has_payment failed");
+                None
+            }
+        };
+        Ok(Resolution {
+            uri: self.pay_request(&link)?.to_uri(),
+            contract_call: ContractCall::preauthorize(self.client.contract_id(), &link),
+            code: link.code,
+            invoice_id: link.invoice_id,
+            paid,
+            expires_at: link.expires_at,
+        })
+    }
+
+    /// The SEP-7 URI of live link `code` as an SVG QR code. Not counted as
+    /// an open.
+    pub async fn qr_svg(&self, code: &str, now: u64) -> Result<String> {
+        let link = self.live(code, now).await?;
+        Ok(self.pay_request(&link)?.qr_svg()?)
+    }
+
+    async fn live(&self, code: &str, now: u64) -> Result<Link> {
+        let link = self.link(code).await?;
+        if link.is_expired(now) {
+            return Err(Error::Expired(code.to_string()));
+        }
+        Ok(link)
+    }
+
+    fn pay_request(&self, link: &Link) -> invoisio_client::Result<PayRequest> {
+        let request = PayRequest::for_invoice(
+            &self.destination,
+            &link.invoice_id,
+            link.asset.clone(),
+            link.amount,
+        )?
+        .on_network(self.client.network_passphrase());
+        match &link.msg {
+            Some(msg) => request.with_msg(msg.as_str()),
+            None => Ok(request),
+        }
+    }
+}
+
+/// Current Unix time in seconds.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use invoisio_client::InvoicePaymentClient;
+use invoisio_links::{server, Config, LinkStore, Links};
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() -> invoisio_links::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let config = Config::from_env()?;
+    let client = InvoicePaymentClient::new(
+        &config.rpc_url,
+        &config.network_passphrase,
+        &config.contract_id,
+    )?;
+    let store = LinkStore::open(&config.db_path).await?;
+    let links = Links::new(
+        store,
+        client,
+        config.destination.clone(),
+        config.base_url.clone(),
+        config.default_ttl,
+        config.max_ttl,
+    );
+
+    tracing::info!(contract = %config.contract_id, base_url = %config.base_url, "starting link service");
+
+    server::serve(
+        config.addr,
+        server::router(Arc::new(links), &config.api_key),
+        async {
+            let _ = tokio::signal::ctrl_c().await;
+        },
+    )
+    .await?;
+    Ok(())
+}
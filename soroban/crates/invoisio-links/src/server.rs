@@ -0,0 +1,132 @@
+//! HTTP routes.
+//!
+//! | Route                | Auth    | Response                          |
+//! |----------------------|---------|-----------------------------------|
+//! | `POST /links`        | API key | `201` [`Minted`]                  |
+//! | `GET /links/:code`   | API key | [`Link`], with its open count     |
+//! | `GET /l/:code`       | none    | [`Resolution`]; counts an open    |
+//! | `GET /l/:code/qr`    | none    | SVG QR code of the SEP-7 URI      |
+//!
+//! Errors are `{"error": "..."}` with `400`, `401`, `404`, `410` (expired)
+//! or `500`.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+
+use crate::error::{Error, Result};
+use crate::link::{Link, Minted, NewLink, Resolution};
+use crate::links::{now, Links};
+
+#[derive(Clone)]
+struct AppState {
+    links: Arc<Links>,
+    api_key: Arc<str>,
+}
+
+/// The service's routes; minting and stats require `Bearer <api_key>`.
+pub fn router(links: Arc<Links>, api_key: &str) -> Router {
+    Router::new()
+        .route("/links", post(mint))
+        .route("/links/:code", get(link))
+        .route("/l/:code", get(resolve))
+        .route("/l/:code/qr", get(qr))
+        .with_state(AppState {
+            links,
+            api_key: api_key.into(),
+        })
+}
+
+/// Serve `router` on `addr` until `shutdown` resolves.
+pub async fn serve(
+    addr: SocketAddr,
+    router: Router,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!(%addr, "serving payment links");
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown)
+        .await
+}
+
+/// Whether `headers` carry `Authorization: Bearer <api_key>`.
+pub fn authorized(headers: &HeaderMap, api_key: &str) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.as_bytes(), api_key.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn mint(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(new): Json<NewLink>,
+) -> Result<(StatusCode, Json<Minted>)> {
+    if !authorized(&headers, &state.api_key) {
+        return Err(Error::Unauthorized);
+    }
+    let minted = state.links.mint(&new, now()).await?;
+    Ok((StatusCode::CREATED, Json(minted)))
+}
+
+async fn link(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(code): Path<String>,
+) -> Result<Json<Link>> {
+    if !authorized(&headers, &state.api_key) {
+        return Err(Error::Unauthorized);
+    }
+    Ok(Json(state.links.link(&code).await?))
+}
+
+async fn resolve(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+) -> Result<Json<Resolution>> {
+    Ok(Json(state.links.resolve(&code, now()).await?))
+}
+
+async fn qr(State(state): State<AppState>, Path(code): Path<String>) -> Result<Response> {
+    let svg = state.links.qr_svg(&code, now()).await?;
+    Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response())
+}
+
+impl Error {
+    /// HTTP status this error is reported with.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Error::Invalid(_) => StatusCode::BAD_REQUEST,
+            Error::Unauthorized => StatusCode::UNAUTHORIZED,
+            Error::NotFound(_) => StatusCode::NOT_FOUND,
+            Error::Expired(_) => StatusCode::GONE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        // Internal failures are logged, not shown to callers.
+        let message = if status.is_server_error() {
+            tracing::error!(error = %self, "request failed");
+            "internal error".to_string()
+        } else {
+            self.to_string()
+        };
+        let body = serde_json::json!({ "error": message });
+        (status, Json(body)).into_response()
+    }
+}
@@ -0,0 +1,118 @@
+//! SQLite persistence of links.
+//!
+//! Codes are the primary key, so a colliding code is refused rather than
+//! overwriting another invoice's link. Opens are counted in place.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use invoisio_client::Asset;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
+
+use crate::error::Result;
+use crate::link::Link;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS links (
+    code           TEXT    PRIMARY KEY,
+    invoice_id     TEXT    NOT NULL,
+    asset_code     TEXT    NOT NULL,
+    asset_issuer   TEXT    NOT NULL,
+    amount         TEXT,
+    msg            TEXT,
+    created_at     INTEGER NOT NULL,
+    expires_at     INTEGER NOT NULL,
+    opens          INTEGER NOT NULL DEFAULT 0,
+    last_opened_at INTEGER
+);
+CREATE INDEX IF NOT EXISTS links_invoice_idx ON links (invoice_id);
+";
+
+#[derive(Clone, Debug)]
+pub struct LinkStore {
+    pool: SqlitePool,
+}
+
+impl LinkStore {
+    /// Open (creating if needed) the database at `path`.
+    pub async fn open(path: &Path) -> Result<Self> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+        Self::connect(options).await
+    }
+
+    /// A throwaway in-memory store, for tests.
+    pub async fn in_memory() -> Result<Self> {
+        Self::connect(SqliteConnectOptions::from_str("sqlite::memory:")?).await
+    }
+
+    async fn connect(options: SqliteConnectOptions) -> Result<Self> {
+        // `:memory:` databases are per-connection.
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+        sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+        Ok(Self { pool })
+    }
+
+    /// Store `link`. Returns `false`, storing nothing, if its code is taken.
+    pub async fn insert(&self, link: &Link) -> Result<bool> {
+        let done = sqlx::query(
+            "INSERT INTO links
+                 (code, invoice_id, asset_code, asset_issuer, amount, msg, created_at, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT (code) DO NOTHING",
+        )
+        .bind(&link.code)
+        .bind(&link.invoice_id)
+        .bind(link.asset.code())
+        .bind(link.asset.issuer())
+        .bind(link.amount.map(|a| a.to_string()))
+        .bind(&link.msg)
+        .bind(link.created_at as i64)
+        .bind(link.expires_at as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(done.rows_affected() == 1)
+    }
+
+    pub async fn get(&self, code: &str) -> Result<Option<Link>> {
+        let row = sqlx::query("SELECT * FROM links WHERE code = ?1")
+            .bind(code)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(|r| link(&r)).transpose()
+    }
+
+    /// Count an open of `code` at `at` (Unix seconds).
+    pub async fn record_open(&self, code: &str, at: u64) -> Result<()> {
+        sqlx::query("UPDATE links SET opens = opens + 1, last_opened_at = ?2 WHERE code = ?1")
+            .bind(code)
+            .bind(at as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+fn link(r: &SqliteRow) -> Result<Link> {
+    let amount = r
+        .get::<Option<String>, _>("amount")
+        .map(|a| a.parse::<i128>())
+        .transpose()
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+    Ok(Link {
+        code: r.get("code"),
+        invoice_id: r.get("invoice_id"),
+        asset: Asset::from_code_issuer(r.get("asset_code"), r.get("asset_issuer")),
+        amount,
+        msg: r.get("msg"),
+        created_at: r.get::<i64, _>("created_at") as u64,
+        expires_at: r.get::<i64, _>("expires_at") as u64,
+        opens: r.get::<i64, _>("opens") as u64,
+        last_opened_at: r.get::<Option<i64>, _>("last_opened_at").map(|t| t as u64),
+    })
+}
@@ -0,0 +1,194 @@
+#![cfg(test)]
+
+use std::time::Duration;
+
+use axum::http::{header, HeaderMap, StatusCode};
+use invoisio_client::{Asset, InvoicePaymentClient};
+
+use crate::link::{ContractCall, Link, NewLink, CODE_LEN, PREAUTHORIZE};
+use crate::server::authorized;
+use crate::{Error, LinkStore, Links};
+
+const DESTINATION: &str = "GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5";
+const CONTRACT_ID: &str = "CDOTTHKN4Z5RFSEDOILRIHNQRWGRCZWOMTDBCFK7W3OWRS2V3H7CEZVM";
+const PASSPHRASE: &str = "Test SDF Network ; September 2015";
+const NOW: u64 = 1_700_000_000;
+const DAY: u64 = 86_400;
+
+fn usdc() -> Asset {
+    Asset::Token {
+        code: "USDC".into(),
+        issuer: DESTINATION.into(),
+    }
+}
+
+fn new_link(amount: Option<i128>) -> NewLink {
+    NewLink {
+        invoice_id: "abc123".into(),
+        asset: usdc(),
+        amount,
+        msg: None,
+        ttl_secs: None,
+    }
+}
+
+fn link(amount: Option<i128>) -> Link {
+    Link {
+        code: "abcDEF1234".into(),
+        invoice_id: "invoisio-1".into(),
+        asset: Asset::Native,
+        amount,
+        msg: Some("Invoice #1".into()),
+        created_at: NOW,
+        expires_at: NOW + DAY,
+        opens: 0,
+        last_opened_at: None,
+    }
+}
+
+/// A service whose RPC endpoint is unreachable.
+async fn links() -> Links {
+    Links::new(
+        LinkStore::in_memory().await.unwrap(),
+        InvoicePaymentClient::new("http://127.0.0.1:1", PASSPHRASE, CONTRACT_ID).unwrap(),
+        DESTINATION.into(),
+        "https://pay.example/".into(),
+        Duration::from_secs(DAY),
+        Duration::from_secs(7 * DAY),
+    )
+}
+
+// Store
+
+#[tokio::test]
+async fn test_store_round_trips_and_counts_opens() {
+    let store = LinkStore::in_memory().await.unwrap();
+    let link = link(Some(i128::MAX));
+    assert!(store.insert(&link).await.unwrap());
+    assert_eq!(store.get(&link.code).await.unwrap().unwrap(), link);
+
+    // A taken code is refused without touching the existing link.
+    let other = Link {
+        invoice_id: "invoisio-2".into(),
+        ..link.clone()
+    };
+    assert!(!store.insert(&other).await.unwrap());
+
+    store.record_open(&link.code, NOW + 5).await.unwrap();
+    store.record_open(&link.code, NOW + 9).await.unwrap();
+    let opened = store.get(&link.code).await.unwrap().unwrap();
+    assert_eq!(opened.invoice_id, "invoisio-1");
+    assert_eq!((opened.opens, opened.last_opened_at), (2, Some(NOW + 9)));
+    assert_eq!(store.get("missing").await.unwrap(), None);
+}
+
+// Minting
+
+#[tokio::test]
+async fn test_mint_returns_shareable_url() {
+    let links = links().await;
+    let minted = links.mint(&new_link(Some(125_000_000)), NOW).await.unwrap();
+    assert_eq!(minted.code.len(), CODE_LEN);
+    assert_eq!(minted.url, format!("https://pay.example/l/{}", minted.code));
+    assert_eq!(minted.expires_at, NOW + DAY);
+
+    let link = links.link(&minted.code).await.unwrap();
+    assert_eq!((link.invoice_id.as_str(), link.opens), ("abc123", 0));
+}
+
+#[tokio::test]
+async fn test_mint_rejects_unpayable_requests() {
+    let links = links().await;
+    let too_long = NewLink {
+        invoice_id: "x".repeat(40),
+        ..new_link(None)
+    };
+    let forever = NewLink {
+        ttl_secs: Some(8 * DAY),
+        ..new_link(None)
+    };
+    for bad in [new_link(Some(0)), too_long, forever] {
+        assert!(matches!(
+            links.mint(&bad, NOW).await,
+            Err(Error::Invalid(_))
+        ));
+    }
+}
+
+// Resolving
+
+#[tokio::test]
+async fn test_resolve_counts_opens_and_builds_payloads() {
+    let links = links().await;
+    let code = links
+        .mint(&new_link(Some(125_000_000)), NOW)
+        .await
+        .unwrap()
+        .code;
+
+    let resolution = links.resolve(&code, NOW + 60).await.unwrap();
+    assert_eq!(resolution.invoice_id, "abc123");
+    assert!(resolution.uri.starts_with(&format!(
+        "web+stellar:pay?destination={DESTINATION}&amount=12.5&asset_code=USDC"
+    )));
+    assert!(resolution.uri.contains("&memo=invoisio-abc123&"));
+    // The contract could not be reached.
+    assert_eq!(resolution.paid, None);
+
+    let call = resolution.contract_call.unwrap();
+    assert_eq!(call.contract_id, CONTRACT_ID);
+    assert_eq!(call.function, PREAUTHORIZE);
+    assert_eq!(call.args["max_amount"], "125000000");
+    assert_eq!(call.args["asset_issuer"], DESTINATION);
+    assert!(!call.args.contains_key("payer"));
+
+    links.resolve(&code, NOW + 90).await.unwrap();
+    let link = links.link(&code).await.unwrap();
+    assert_eq!((link.opens, link.last_opened_at), (2, Some(NOW + 90)));
+}
+
+#[tokio::test]
+async fn test_resolve_refuses_expired_and_unknown_links() {
+    let links = links().await;
+    let code = links.mint(&new_link(None), NOW).await.unwrap().code;
+
+    let err = links.resolve(&code, NOW + DAY).await.unwrap_err();
+    assert!(matches!(err, Error::Expired(_)));
+    assert_eq!(err.status(), StatusCode::GONE);
+    assert_eq!(links.link(&code).await.unwrap().opens, 0);
+
+    let err = links.resolve("nope", NOW).await.unwrap_err();
+    assert_eq!(err.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_no_contract_call_without_amount() {
+    assert_eq!(ContractCall::preauthorize(CONTRACT_ID, &link(None)), None);
+}
+
+// HTTP
+
+#[test]
+fn test_api_key_must_match_exactly() {
+    let with = |value: &str| {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, value.parse().unwrap());
+        headers
+    };
+    assert!(authorized(&with("Bearer s3cret"), "s3cret"));
+    assert!(!authorized(&with("Bearer s3cre"), "s3cret"));
+    assert!(!authorized(&with("s3cret"), "s3cret"));
+    assert!(!authorized(&HeaderMap::new(), "s3cret"));
+}
+
+#[test]
+fn test_new_link_amount_is_a_string() {
+    let new: NewLink = serde_json::from_value(serde_json::json!({
+        "invoice_id": "abc123",
+        "asset": { "type": "native" },
+        "amount": "170141183460469231731687303715884105727",
+    }))
+    .unwrap();
+    assert_eq!(new.amount, Some(i128::MAX));
+    assert_eq!(new.ttl_secs, None);
+}
@@ -0,0 +1,291 @@
+//! # invoisio-metrics
+//!
+//! Prometheus metrics for the off-chain services (reconciler, indexer,
+//! webhook notifier) and the `/metrics` endpoint that exposes them.
+//!
+//! Each service is its own process, so metrics live in a process-wide
+//! [`metrics()`] instance instead of being threaded through constructors.
+//! Recording is cheap and works whether or not the endpoint is served.
+//!
+//! | Metric                                   | Type      | Labels    |
+//! |------------------------------------------|-----------|-----------|
+//! | `invoisio_payments_recorded_total`       | counter   | `outcome` |
+//! | `invoisio_submission_duration_seconds`   | histogram |           |
+//! | `invoisio_inclusion_fee_stroops`         | gauge     |           |
+//! | `invoisio_inclusion_rate`                | gauge     |           |
+//! | `invoisio_horizon_lag_seconds`           | gauge     |           |
+//! | `invoisio_recorder_balance_xlm`          | gauge     |           |
+//! | `invoisio_recorder_spendable_xlm`        | gauge     |           |
+//! | `invoisio_recorder_sequence`             | gauge     |           |
+//! | `invoisio_recorder_alerts`               | gauge     | `alert`   |
+//! | `invoisio_queue_jobs`                    | gauge     | `status`  |
+//! | `invoisio_queue_oldest_pending_seconds`  | gauge     |           |
+//! | `invoisio_rpc_errors_total`              | counter   | `kind`    |
+//! | `invoisio_events_indexed_total`          | counter   | `topic`   |
+//! | `invoisio_indexer_ledger`                | gauge     |           |
+//! | `invoisio_indexer_gaps_total`            | counter   | `outcome` |
+//! | `invoisio_webhooks_total`                | counter   | `result`  |
+
+use std::net::SocketAddr;
+use std::sync::OnceLock;
+
+use prometheus::{
+    Encoder, Gauge, Histogram, HistogramOpts, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+
+pub struct Metrics {
+    registry: Registry,
+    /// `record_payment` outcomes: `recorded`, `already_recorded`,
+    /// `rejected`, `unknown_invoice`, `failed`.
+    pub payments_recorded: IntCounterVec,
+    /// Wall time of a single `record_payment` submission attempt.
+    pub submission_duration: Histogram,
+    /// Inclusion fee bid on the latest submission.
+    pub inclusion_fee: IntGauge,
+    /// Share of recent submissions that made it into a ledger.
+    pub inclusion_rate: Gauge,
+    /// Age of the newest Horizon operation the reconciler has handled.
+    pub horizon_lag: Gauge,
+    /// Recorder account's native balance.
+    pub recorder_balance: Gauge,
+    /// Recorder XLM left for fees above the minimum balance.
+    pub recorder_spendable: Gauge,
+    pub recorder_sequence: IntGauge,
+    /// 1 while a recorder health alert fires, by alert name.
+    pub recorder_alerts: IntGaugeVec,
+    /// Submission queue entries: `pending`, `failed`.
+    pub queue_jobs: IntGaugeVec,
+    /// Age of the oldest pending submission; 0 when none.
+    pub queue_oldest_pending: Gauge,
+    /// Soroban RPC / client failures by [`client_error_kind`].
+    pub rpc_errors: IntCounterVec,
+    pub events_indexed: IntCounterVec,
+    /// Ledger of the indexer's latest checkpoint.
+    pub indexer_ledger: IntGauge,
+    /// Ledger ranges lost from RPC retention: `backfilled`, `open`.
+    pub indexer_gaps: IntCounterVec,
+    /// Webhook deliveries: `delivered`, `rejected`, `gave_up`, and `skipped`
+    /// for ones settled before a restart.
+    pub webhooks: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+        let m = Self {
+            payments_recorded: IntCounterVec::new(
+                Opts::new(
+                    "invoisio_payments_recorded_total",
+                    "Payments handled by the reconciler, by outcome",
+                ),
+                &["outcome"],
+            )
+            .unwrap(),
+            submission_duration: Histogram::with_opts(
+                HistogramOpts::new(
+                    "invoisio_submission_duration_seconds",
+                    "record_payment submission latency including retries",
+                )
+                .buckets(vec![0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0]),
+            )
+            .unwrap(),
+            inclusion_fee: IntGauge::new(
+                "invoisio_inclusion_fee_stroops",
+                "Inclusion fee bid on the latest record_payment",
+            )
+            .unwrap(),
+            inclusion_rate: Gauge::new(
+                "invoisio_inclusion_rate",
+                "Share of recent submissions included in a ledger",
+            )
+            .unwrap(),
+            horizon_lag: Gauge::new(
+                "invoisio_horizon_lag_seconds",
+                "Age of the newest Horizon operation handled",
+            )
+            .unwrap(),
+            recorder_balance: Gauge::new(
+                "invoisio_recorder_balance_xlm",
+                "Native balance of the recorder account",
+            )
+            .unwrap(),
+            recorder_spendable: Gauge::new(
+                "invoisio_recorder_spendable_xlm",
+                "Recorder XLM above the minimum balance",
+            )
+            .unwrap(),
+            recorder_sequence: IntGauge::new(
+                "invoisio_recorder_sequence",
+                "Recorder account sequence number",
+            )
+            .unwrap(),
+            recorder_alerts: IntGaugeVec::new(
+                Opts::new(
+                    "invoisio_recorder_alerts",
+                    "Recorder health alerts currently firing",
+                ),
+                &["alert"],
+            )
+            .unwrap(),
+            queue_jobs: IntGaugeVec::new(
+                Opts::new("invoisio_queue_jobs", "Submission queue entries, by status"),
+                &["status"],
+            )
+            .unwrap(),
+            queue_oldest_pending: Gauge::new(
+                "invoisio_queue_oldest_pending_seconds",
+                "Age of the oldest pending submission",
+            )
+            .unwrap(),
+            rpc_errors: IntCounterVec::new(
+                Opts::new("invoisio_rpc_errors_total", "Soroban RPC / client errors"),
+                &["kind"],
+            )
+            .unwrap(),
+            events_indexed: IntCounterVec::new(
+                Opts::new("invoisio_events_indexed_total", "Contract events indexed"),
+                &["topic"],
+            )
+            .unwrap(),
+            indexer_ledger: IntGauge::new(
+                "invoisio_indexer_ledger",
+                "Ledger of the indexer's latest checkpoint",
+            )
+            .unwrap(),
+            indexer_gaps: IntCounterVec::new(
+                Opts::new(
+                    "invoisio_indexer_gaps_total",
+                    "Ledger ranges that left RPC retention before being indexed",
+                ),
+                &["outcome"],
+            )
+            .unwrap(),
+            webhooks: IntCounterVec::new(
+                Opts::new("invoisio_webhooks_total", "Webhook deliveries, by result"),
+                &["result"],
+            )
+            .unwrap(),
+            registry,
+        };
+        m.registry
+            .register(Box::new(m.payments_recorded.clone()))
+            .unwrap();
+        m.registry
+            .register(Box::new(m.submission_duration.clone()))
+            .unwrap();
+        m.registry
+            .register(Box::new(m.inclusion_fee.clone()))
+            .unwrap();
+        m.registry
+            .register(Box::new(m.inclusion_rate.clone()))
+            .unwrap();
+        m.registry.register(Box::new(m.horizon_lag.clone())).unwrap();
+        m.registry
+            .register(Box::new(m.recorder_balance.clone()))
+            .unwrap();
+        m.registry
+            .register(Box::new(m.recorder_spendable.clone()))
+            .unwrap();
+        m.registry
+            .register(Box::new(m.recorder_sequence.clone()))
+            .unwrap();
+        m.registry
+            .register(Box::new(m.recorder_alerts.clone()))
+            .unwrap();
+        m.registry.register(Box::new(m.queue_jobs.clone())).unwrap();
+        m.registry
+            .register(Box::new(m.queue_oldest_pending.clone()))
+            .unwrap();
+        m.registry.register(Box::new(m.rpc_errors.clone())).unwrap();
+        m.registry
+            .register(Box::new(m.events_indexed.clone()))
+            .unwrap();
+        m.registry
+            .register(Box::new(m.indexer_ledger.clone()))
+            .unwrap();
+        m.registry
+            .register(Box::new(m.indexer_gaps.clone()))
+            .unwrap();
+        m.registry.register(Box::new(m.webhooks.clone())).unwrap();
+        m
+    }
+
+    /// Count a client error under its [`client_error_kind`].
+    pub fn rpc_error(&self, err: &invoisio_client::Error) {
+        self.rpc_errors
+            .with_label_values(&[client_error_kind(err)])
+            .inc();
+    }
+
+    /// Prometheus text exposition of every metric.
+    pub fn render(&self) -> String {
+        let mut buf = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buf)
+            .expect("text encoding into a Vec cannot fail");
+        String::from_utf8(buf).expect("text exposition is UTF-8")
+    }
+}
+
+/// The process-wide metrics instance.
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+/// Low-cardinality label for a client error.
+pub fn client_error_kind(err: &invoisio_client::Error) -> &'static str {
+    use invoisio_client::Error::*;
+    match err {
+        Http(_) => "http",
+        Rpc { .. } => "rpc",
+        Xdr(_) | Decode(_) | Strkey(_) => "decode",
+        Contract(_) | UnknownContractError(_) => "contract",
+        Simulation(_) => "simulation",
+        EntryArchived { .. } => "archived",
+        SendRejected { .. } => "send_rejected",
+        TransactionFailed { .. } => "tx_failed",
+        Timeout { .. } => "timeout",
+        Signer(_) => "signer",
+        Profile(_) | NetworkMismatch { .. } | PayRequest(_) => "config",
+        Anchor(_) => "anchor",
+    }
+}
+
+/// Serve `GET /metrics` on `addr` until `shutdown` resolves.
+pub async fn serve(
+    addr: SocketAddr,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    let app = axum::Router::new().route(
+        "/metrics",
+        axum::routing::get(|| async {
+            (
+                [(
+                    axum::http::header::CONTENT_TYPE,
+                    "text/plain; version=0.0.4",
+                )],
+                metrics().render(),
+            )
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!(%addr, "serving /metrics");
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown)
+        .await
+}
+
+/// Spawn [`serve`] in the background if `addr` is set, logging failures.
+pub fn spawn(addr: Option<SocketAddr>) {
+    if let Some(addr) = addr {
+        tokio::spawn(async move {
+            if let Err(err) = serve(addr, std::future::pending()).await {
+                tracing::error!(%addr, error = %err, "metrics endpoint failed");
+            }
+        });
+    }
+}
+
+mod test;
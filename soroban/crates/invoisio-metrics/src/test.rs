@@ -0,0 +1,63 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn test_render_includes_every_metric() {
+    let m = metrics();
+    m.payments_recorded.with_label_values(&["recorded"]).inc();
+    m.rpc_errors.with_label_values(&["http"]).inc();
+    m.events_indexed
+        .with_label_values(&["payment_recorded"])
+        .inc();
+    m.webhooks.with_label_values(&["delivered"]).inc();
+    m.submission_duration.observe(1.5);
+    m.inclusion_fee.set(200);
+    m.inclusion_rate.set(0.9);
+    m.horizon_lag.set(3.0);
+    m.recorder_balance.set(12.5);
+    m.recorder_spendable.set(11.0);
+    m.recorder_sequence.set(7);
+    m.recorder_alerts.with_label_values(&["low_balance"]).set(1);
+    m.queue_jobs.with_label_values(&["pending"]).set(2);
+    m.queue_oldest_pending.set(30.0);
+    m.indexer_ledger.set(42);
+    m.indexer_gaps.with_label_values(&["open"]).inc();
+
+    let text = m.render();
+    for name in [
+        "invoisio_payments_recorded_total",
+        "invoisio_submission_duration_seconds",
+        "invoisio_inclusion_fee_stroops",
+        "invoisio_inclusion_rate",
+        "invoisio_horizon_lag_seconds",
+        "invoisio_recorder_balance_xlm",
+        "invoisio_recorder_spendable_xlm",
+        "invoisio_recorder_sequence",
+        "invoisio_recorder_alerts",
+        "invoisio_queue_jobs",
+        "invoisio_queue_oldest_pending_seconds",
+        "invoisio_rpc_errors_total",
+        "invoisio_events_indexed_total",
+        "invoisio_indexer_ledger",
+        "invoisio_indexer_gaps_total",
+        "invoisio_webhooks_total",
+    ] {
+        assert!(text.contains(name), "missing {name}");
+    }
+    assert!(text.contains("invoisio_payments_recorded_total{outcome=\"recorded\"}"));
+}
+
+#[test]
+fn test_client_error_kind_is_low_cardinality() {
+    use invoisio_client::{ContractError, Error};
+    assert_eq!(
+        client_error_kind(&Error::Contract(ContractError::PaymentNotFound)),
+        "contract"
+    );
+    assert_eq!(
+        client_error_kind(&Error::Timeout { hash: "ab".into() }),
+        "timeout"
+    );
+    assert_eq!(client_error_kind(&Error::Decode("x".into())), "decode");
+}
@@ -0,0 +1,122 @@
+//! # invoisio-proof
+//!
+//! Verify that a payment record is included in a published Merkle root,
+//! with no RPC access and no allocator.
+//!
+//! `no_std`, so the same check runs in a partner's service, in a browser
+//! through WASM, or inside another Soroban contract.
+//!
+//! ## Tree
+//! The tree follows RFC 6962 (Certificate Transparency) so any CT-style
+//! library can build roots and proofs for it:
+//!
+//! - leaf: `SHA-256(0x00 || record)`
+//! - node: `SHA-256(0x01 || left || right)`
+//! - a tree of `n` leaves splits at the largest power of two below `n`, so
+//!   the tree needs no padding.
+//!
+//! `record` is the XDR of the `PaymentRecord` `ScVal`, byte for byte what
+//! `get_payment` returns. A record's bytes can therefore be re-fetched and
+//! re-hashed by anyone.
+//!
+//! ## Example
+//! ```
+//! use invoisio_proof::{leaf_hash, node_hash, verify_inclusion, Proof};
+//!
+//! let (a, b) = (b"record a".as_slice(), b"record b".as_slice());
+//! let root = node_hash(&leaf_hash(a), &leaf_hash(b));
+//! let proof = Proof { leaf_index: 1, tree_size: 2, path: &[leaf_hash(a)] };
+//! assert!(verify_inclusion(b, &proof, &root).is_ok());
+//! ```
+
+#![no_std]
+
+use sha2::{Digest, Sha256};
+
+/// A SHA-256 digest.
+pub type Hash = [u8; 32];
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Why a proof was rejected.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// `leaf_index` is not below `tree_size`.
+    IndexOutOfRange,
+    /// The path has more or fewer hashes than a leaf at `leaf_index` in a
+    /// tree of `tree_size` needs.
+    PathLength,
+    /// The proof is well-formed but leads to another root.
+    RootMismatch,
+}
+
+/// Audit path of one leaf: the sibling hashes from the leaf up to the root.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Proof<'a> {
+    /// Position of the record among the tree's leaves, from 0.
+    pub leaf_index: u64,
+    /// Number of leaves under the published root.
+    pub tree_size: u64,
+    pub path: &'a [Hash],
+}
+
+/// Hash of the leaf for a record's XDR.
+pub fn leaf_hash(record: &[u8]) -> Hash {
+    Sha256::new()
+        .chain_update([LEAF_PREFIX])
+        .chain_update(record)
+        .finalize()
+        .into()
+}
+
+/// Hash of the inner node over `left` and `right`.
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    Sha256::new()
+        .chain_update([NODE_PREFIX])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// Fold `proof` over `leaf` into the root it implies (RFC 9162, 2.1.3.2).
+pub fn root_from_proof(leaf: Hash, proof: &Proof) -> Result<Hash, Error> {
+    if proof.leaf_index >= proof.tree_size {
+        return Err(Error::IndexOutOfRange);
+    }
+    let (mut index, mut last) = (proof.leaf_index, proof.tree_size - 1);
+    let mut hash = leaf;
+    for sibling in proof.path {
+        if last == 0 {
+            return Err(Error::PathLength);
+        }
+        if index & 1 == 1 || index == last {
+            hash = node_hash(sibling, &hash);
+            // A last node without a right sibling is promoted unchanged.
+            while index & 1 == 0 && index != 0 {
+                index >>= 1;
+                last >>= 1;
+            }
+        } else {
+            hash = node_hash(&hash, sibling);
+        }
+        index >>= 1;
+        last >>= 1;
+    }
+    if last != 0 {
+        return Err(Error::PathLength);
+    }
+    Ok(hash)
+}
+
+/// Check that `record` (a `PaymentRecord`'s XDR) is the leaf at
+/// `proof.leaf_index` of the tree whose root is `root`.
+pub fn verify_inclusion(record: &[u8], proof: &Proof, root: &Hash) -> Result<(), Error> {
+    if root_from_proof(leaf_hash(record), proof)? != *root {
+        return Err(Error::RootMismatch);
+    }
+    Ok(())
+}
+
+mod test;
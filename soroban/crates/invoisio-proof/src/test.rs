@@ -0,0 +1,132 @@
+#![cfg(test)]
+
+extern crate std;
+
+use std::vec::Vec;
+
+use super::*;
+
+/// Root of `leaves` per RFC 6962, 2.1.
+fn root(leaves: &[Hash]) -> Hash {
+    match leaves {
+        [leaf] => *leaf,
+        _ => {
+            let k = split(leaves.len());
+            node_hash(&root(&leaves[..k]), &root(&leaves[k..]))
+        }
+    }
+}
+
+/// Audit path of leaf `m` per RFC 6962, 2.1.1.
+fn path(m: usize, leaves: &[Hash]) -> Vec<Hash> {
+    if leaves.len() == 1 {
+        return Vec::new();
+    }
+    let k = split(leaves.len());
+    let (mut p, sibling) = if m < k {
+        (path(m, &leaves[..k]), root(&leaves[k..]))
+    } else {
+        (path(m - k, &leaves[k..]), root(&leaves[..k]))
+    };
+    p.push(sibling);
+    p
+}
+
+/// Largest power of two strictly below `n`.
+fn split(n: usize) -> usize {
+    let mut k = 1;
+    while k * 2 < n {
+        k *= 2;
+    }
+    k
+}
+
+fn records(n: usize) -> Vec<Vec<u8>> {
+    (0..n)
+        .map(|i| std::format!("record {i}").into_bytes())
+        .collect()
+}
+
+#[test]
+fn test_every_leaf_verifies_in_trees_of_every_size() {
+    for n in 1..=17 {
+        let records = records(n);
+        let leaves: Vec<Hash> = records.iter().map(|r| leaf_hash(r)).collect();
+        let root = root(&leaves);
+        for (m, record) in records.iter().enumerate() {
+            let path = path(m, &leaves);
+            let proof = Proof {
+                leaf_index: m as u64,
+                tree_size: n as u64,
+                path: &path,
+            };
+            assert_eq!(
+                verify_inclusion(record, &proof, &root),
+                Ok(()),
+                "leaf {m} of {n}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_wrong_record_or_root_is_rejected() {
+    let records = records(5);
+    let leaves: Vec<Hash> = records.iter().map(|r| leaf_hash(r)).collect();
+    let root = root(&leaves);
+    let path = path(2, &leaves);
+    let proof = Proof {
+        leaf_index: 2,
+        tree_size: 5,
+        path: &path,
+    };
+
+    assert_eq!(
+        verify_inclusion(&records[3], &proof, &root),
+        Err(Error::RootMismatch)
+    );
+    assert_eq!(
+        verify_inclusion(&records[2], &proof, &[0; 32]),
+        Err(Error::RootMismatch)
+    );
+    // A leaf hash cannot pose as an inner node.
+    assert_ne!(leaf_hash(&[0; 64]), node_hash(&[0; 32], &[0; 32]));
+}
+
+#[test]
+fn test_malformed_proofs_are_rejected() {
+    let records = records(4);
+    let leaves: Vec<Hash> = records.iter().map(|r| leaf_hash(r)).collect();
+    let root = root(&leaves);
+    let path = path(1, &leaves);
+
+    let out_of_range = Proof {
+        leaf_index: 4,
+        tree_size: 4,
+        path: &path,
+    };
+    assert_eq!(
+        verify_inclusion(&records[1], &out_of_range, &root),
+        Err(Error::IndexOutOfRange)
+    );
+    let short = Proof {
+        leaf_index: 1,
+        tree_size: 4,
+        path: &path[..1],
+    };
+    assert_eq!(
+        verify_inclusion(&records[1], &short, &root),
+        Err(Error::PathLength)
+    );
+    let mut long_path = path.clone();
+    long_path.push([0; 32]);
+    let long = Proof {
+        leaf_index: 1,
+        tree_size: 4,
+        path: &long_path,
+    };
+    assert_eq!(
+        verify_inclusion(&records[1], &long, &root),
+        Err(Error::PathLength)
+    );
+}
@@ -0,0 +1,204 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use invoisio_client::{AnySigner, HttpKmsSigner, Keypair, LedgerSigner};
+
+use crate::error::{Error, Result};
+use crate::fees::FeePolicy;
+use crate::health::Thresholds;
+use crate::matcher::parse_amount;
+
+/// Daemon configuration, read from `INVOISIO_*` environment variables.
+///
+/// | Variable                        | Required | Default             |
+/// |---------------------------------|----------|---------------------|
+/// | `INVOISIO_HORIZON_URL`          | yes      |                     |
+/// | `INVOISIO_RPC_URL`              | yes      |                     |
+/// | `INVOISIO_NETWORK_PASSPHRASE`   | yes      |                     |
+/// | `INVOISIO_CONTRACT_ID`          | yes      |                     |
+/// | `INVOISIO_SIGNER`               | no       | `local`             |
+/// | `INVOISIO_RECORDER_SECRET`      | `local`  |                     |
+/// | `INVOISIO_LEDGER_URL`           | `ledger` |                     |
+/// | `INVOISIO_LEDGER_ACCOUNT`       | no       | `0`                 |
+/// | `INVOISIO_KMS_URL`              | `kms`    |                     |
+/// | `INVOISIO_KMS_KEY_ID`           | `kms`    |                     |
+/// | `INVOISIO_KMS_TOKEN`            | no       |                     |
+/// | `INVOISIO_DESTINATION`          | yes      |                     |
+/// | `INVOISIO_INVOICE_API_URL`      | yes      |                     |
+/// | `INVOISIO_CURSOR_FILE`          | no       | `reconciler.cursor` |
+/// | `INVOISIO_QUEUE_PATH`           | no       | `reconciler.db`     |
+/// | `INVOISIO_POLL_SECS`            | no       | `5`                 |
+/// | `INVOISIO_MAX_ATTEMPTS`         | no       | `5`                 |
+/// | `INVOISIO_FEE_FLOOR`            | no       | `100`               |
+/// | `INVOISIO_FEE_CAP`              | no       | `10000`             |
+/// | `INVOISIO_URGENT_FEE_CAP`       | no       | `100000`            |
+/// | `INVOISIO_URGENT_AFTER_SECS`    | no       | `300`               |
+/// | `INVOISIO_METRICS_ADDR`         | no       | disabled            |
+/// | `INVOISIO_HEALTH_SECS`          | no       | `60`                |
+/// | `INVOISIO_MIN_BALANCE_XLM`      | no       | `10`                |
+/// | `INVOISIO_MAX_BACKLOG`          | no       | `100`               |
+/// | `INVOISIO_MAX_BACKLOG_AGE_SECS` | no       | `600`               |
+/// | `INVOISIO_SEQUENCE_STALL_SECS`  | no       | `300`               |
+/// | `INVOISIO_ALERT_WEBHOOK_URL`    | no       | disabled            |
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub horizon_url: String,
+    pub rpc_url: String,
+    pub network_passphrase: String,
+    pub contract_id: String,
+    /// Where the contract admin key that signs `record_payment` lives.
+    pub signer: SignerConfig,
+    /// `G...` account that receives invoice payments.
+    pub destination: String,
+    /// Base URL of the Invoisio backend exposing `GET /invoices/:id`.
+    pub invoice_api_url: String,
+    pub cursor_file: PathBuf,
+    /// SQLite database holding the submission queue.
+    pub queue_path: PathBuf,
+    pub poll_interval: Duration,
+    pub max_attempts: u32,
+    /// Inclusion fee bounds, in stroops; see [`crate::fees`].
+    pub fees: FeePolicy,
+    /// `host:port` for the Prometheus `/metrics` endpoint.
+    pub metrics_addr: Option<SocketAddr>,
+    /// How often the recorder [health monitor](crate::health) checks.
+    pub health_interval: Duration,
+    pub thresholds: Thresholds,
+    /// Where health alerts are POSTed.
+    pub alert_webhook_url: Option<String>,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            horizon_url: required("INVOISIO_HORIZON_URL")?,
+            rpc_url: required("INVOISIO_RPC_URL")?,
+            network_passphrase: required("INVOISIO_NETWORK_PASSPHRASE")?,
+            contract_id: required("INVOISIO_CONTRACT_ID")?,
+            signer: SignerConfig::from_env()?,
+            destination: required("INVOISIO_DESTINATION")?,
+            invoice_api_url: required("INVOISIO_INVOICE_API_URL")?,
+            cursor_file: optional("INVOISIO_CURSOR_FILE")
+                .unwrap_or_else(|| "reconciler.cursor".into())
+                .into(),
+            queue_path: optional("INVOISIO_QUEUE_PATH")
+                .unwrap_or_else(|| "reconciler.db".into())
+                .into(),
+            poll_interval: Duration::from_secs(parsed("INVOISIO_POLL_SECS", 5)?),
+            max_attempts: parsed("INVOISIO_MAX_ATTEMPTS", 5)?,
+            fees: FeePolicy {
+                floor: parsed("INVOISIO_FEE_FLOOR", 100)?,
+                cap: parsed("INVOISIO_FEE_CAP", 10_000)?,
+                urgent_cap: parsed("INVOISIO_URGENT_FEE_CAP", 100_000)?,
+                urgent_after: Duration::from_secs(parsed("INVOISIO_URGENT_AFTER_SECS", 300)?),
+                ..FeePolicy::default()
+            },
+            metrics_addr: parsed_optional("INVOISIO_METRICS_ADDR")?,
+            health_interval: Duration::from_secs(parsed("INVOISIO_HEALTH_SECS", 60)?),
+            thresholds: Thresholds {
+                min_balance: xlm("INVOISIO_MIN_BALANCE_XLM", "10")?,
+                max_backlog: parsed("INVOISIO_MAX_BACKLOG", 100)?,
+                max_backlog_age: Duration::from_secs(parsed("INVOISIO_MAX_BACKLOG_AGE_SECS", 600)?),
+                sequence_stall: Duration::from_secs(parsed("INVOISIO_SEQUENCE_STALL_SECS", 300)?),
+            },
+            alert_webhook_url: optional("INVOISIO_ALERT_WEBHOOK_URL"),
+        })
+    }
+}
+
+/// Recorder key source, selected by `INVOISIO_SIGNER`.
+#[derive(Clone)]
+pub enum SignerConfig {
+    /// `S...` secret held in this process.
+    Local { secret: String },
+    /// Ledger Stellar app behind an APDU bridge, account `m/44'/148'/{account}'`.
+    Ledger { url: String, account: u32 },
+    /// Remote KMS; the secret never leaves it.
+    Kms {
+        url: String,
+        key_id: String,
+        token: Option<String>,
+    },
+}
+
+impl SignerConfig {
+    fn from_env() -> Result<Self> {
+        match optional("INVOISIO_SIGNER").as_deref().unwrap_or("local") {
+            "local" => Ok(Self::Local {
+                secret: required("INVOISIO_RECORDER_SECRET")?,
+            }),
+            "ledger" => Ok(Self::Ledger {
+                url: required("INVOISIO_LEDGER_URL")?,
+                account: parsed("INVOISIO_LEDGER_ACCOUNT", 0)?,
+            }),
+            "kms" => Ok(Self::Kms {
+                url: required("INVOISIO_KMS_URL")?,
+                key_id: required("INVOISIO_KMS_KEY_ID")?,
+                token: optional("INVOISIO_KMS_TOKEN"),
+            }),
+            other => Err(Error::Config(format!(
+                "INVOISIO_SIGNER=`{other}` is not one of local, ledger, kms"
+            ))),
+        }
+    }
+
+    /// Build the signer, contacting the device or KMS for its public key.
+    pub async fn connect(&self) -> Result<AnySigner> {
+        Ok(match self {
+            Self::Local { secret } => Keypair::from_secret(secret)?.into(),
+            Self::Ledger { url, account } => {
+                AnySigner::Ledger(LedgerSigner::connect(url, *account).await?)
+            }
+            Self::Kms { url, key_id, token } => {
+                AnySigner::Kms(HttpKmsSigner::connect(url, key_id, token.clone()).await?)
+            }
+        })
+    }
+}
+
+impl std::fmt::Debug for SignerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the secret or token.
+        match self {
+            Self::Local { .. } => f.write_str("Local"),
+            Self::Ledger { url, account } => f
+                .debug_struct("Ledger")
+                .field("url", url)
+                .field("account", account)
+                .finish(),
+            Self::Kms { url, key_id, .. } => f
+                .debug_struct("Kms")
+                .field("url", url)
+                .field("key_id", key_id)
+                .finish(),
+        }
+    }
+}
+
+fn required(name: &str) -> Result<String> {
+    optional(name).ok_or_else(|| Error::Config(format!("{name} is not set")))
+}
+
+fn optional(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+/// An XLM amount such as `12.5`, in stroops.
+fn xlm(name: &str, default: &str) -> Result<i128> {
+    let v = optional(name).unwrap_or_else(|| default.into());
+    parse_amount(&v).ok_or_else(|| Error::Config(format!("{name}=`{v}` is not valid")))
+}
+
+fn parsed<T: std::str::FromStr>(name: &str, default: T) -> Result<T> {
+    Ok(parsed_optional(name)?.unwrap_or(default))
+}
+
+fn parsed_optional<T: std::str::FromStr>(name: &str) -> Result<Option<T>> {
+    optional(name)
+        .map(|v| {
+            v.parse()
+                .map_err(|_| Error::Config(format!("{name}=`{v}` is not valid")))
+        })
+        .transpose()
+}
@@ -0,0 +1,38 @@
+//! Horizon paging-token checkpoint persisted to a local file.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+
+/// File-backed cursor. Writes go to `<path>.tmp` and are renamed into place
+/// so a crash mid-write never leaves a truncated cursor behind.
+#[derive(Clone, Debug)]
+pub struct FileCursor {
+    path: PathBuf,
+}
+
+impl FileCursor {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Last saved cursor, or `None` on first run.
+    pub async fn load(&self) -> Result<Option<String>> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(s) => Ok(Some(s.trim().to_string()).filter(|s| !s.is_empty())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn save(&self, cursor: &str) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        tokio::fs::write(&tmp, cursor).await?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+}
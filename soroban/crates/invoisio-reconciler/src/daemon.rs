@@ -0,0 +1,319 @@
+//! The reconciliation loop.
+//!
+//! For every payment operation received by the destination account:
+//! 1. Skip it unless it carries an `invoisio-<id>` text memo.
+//! 2. Look up the invoice and [`verify`] asset and amount.
+//! 3. Enqueue the `record_payment` in the [`SubmissionQueue`], keyed by
+//!    invoice id.
+//! 4. Advance the persisted cursor.
+//!
+//! A separate drain step submits due queue entries one attempt at a time,
+//! rescheduling transient failures with [`Backoff`]; an already-recorded
+//! invoice counts as success. Each attempt bids the inclusion fee chosen by
+//! the [`FeeStrategy`], which rises with retries, job age and congestion.
+//! The [`Submitter`] tracks each sent transaction in the queue, so a
+//! timed-out one is followed up rather than sent twice, and a job is not
+//! failed while its transaction could still land.
+//!
+//! The cursor only moves past an operation once it is queued, and a queue
+//! entry is only closed once the contract has the record, so a crash or
+//! deploy at any point leads to at most a repeated submission — which the
+//! contract's idempotency guard makes harmless.
+
+use std::time::{Instant, SystemTime};
+
+use invoisio_client::{AnySigner, InvoicePaymentClient};
+use invoisio_metrics::metrics;
+
+use crate::cursor::FileCursor;
+use crate::error::Result;
+use crate::fees::{inclusion, FeeStrategy};
+use crate::horizon::{HorizonClient, PaymentOperation};
+use crate::invoices::InvoiceSource;
+use crate::matcher::{candidate, verify, Verdict};
+use crate::queue::{Enqueued, Job, SubmissionQueue};
+use crate::submitter::{is_retryable, until_expired, Backoff, Submission, Submitter};
+
+/// Queue entries submitted per [`Reconciler::drain_once`].
+const DRAIN_BATCH: u32 = 16;
+
+/// What happened to a single Horizon operation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// Not an Invoisio payment to our destination.
+    Ignored,
+    /// Memo matched but the backend doesn't know the invoice.
+    UnknownInvoice,
+    /// Asset or amount didn't match the invoice; left for manual review.
+    Rejected(Verdict),
+    /// Verified and queued for submission.
+    Queued,
+    /// The invoice already has a queue entry (e.g. a replayed operation or
+    /// a second payment against the same invoice).
+    AlreadyQueued,
+}
+
+/// What happened to a single queue entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Attempt {
+    Recorded(Submission),
+    /// Transient failure; retried after `delay`.
+    Retrying { delay: std::time::Duration, error: String },
+    /// Submission failed permanently (the contract rejected the input, or
+    /// attempts ran out).
+    Failed(String),
+}
+
+pub struct Reconciler<S> {
+    horizon: HorizonClient,
+    client: InvoicePaymentClient,
+    signer: AnySigner,
+    invoices: S,
+    cursor: FileCursor,
+    queue: SubmissionQueue,
+    destination: String,
+    backoff: Backoff,
+    fees: FeeStrategy,
+    submitter: Submitter,
+}
+
+impl<S: InvoiceSource> Reconciler<S> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        horizon: HorizonClient,
+        client: InvoicePaymentClient,
+        signer: AnySigner,
+        invoices: S,
+        cursor: FileCursor,
+        queue: SubmissionQueue,
+        destination: String,
+        backoff: Backoff,
+    ) -> Self {
+        Self {
+            horizon,
+            client,
+            signer,
+            invoices,
+            cursor,
+            queue,
+            destination,
+            backoff,
+            fees: FeeStrategy::default(),
+            submitter: Submitter::new(),
+        }
+    }
+
+    /// Bid inclusion fees with `fees` instead of the default policy.
+    pub fn with_fees(mut self, fees: FeeStrategy) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    pub fn fees(&self) -> &FeeStrategy {
+        &self.fees
+    }
+
+    /// Process one page of operations after the saved cursor. Returns the
+    /// number of operations consumed (0 means caught up).
+    pub async fn poll_once(&self) -> Result<usize> {
+        let cursor = self.cursor.load().await?;
+        let ops = self
+            .horizon
+            .payments(&self.destination, cursor.as_deref())
+            .await?;
+
+        for op in &ops {
+            let outcome = self.handle(op).await?;
+            log_outcome(op, &outcome);
+            record_metrics(op, &outcome);
+            self.cursor.save(&op.paging_token).await?;
+        }
+        Ok(ops.len())
+    }
+
+    /// Handle a single operation. Errors here are fatal for the current
+    /// poll: the cursor is not advanced and the operation is retried on the
+    /// next poll. Nothing is submitted here; see [`Self::drain_once`].
+    pub async fn handle(&self, op: &PaymentOperation) -> Result<Outcome> {
+        let Some(candidate) = candidate(op, &self.destination) else {
+            return Ok(Outcome::Ignored);
+        };
+        let Some(expected) = self.invoices.expected(&candidate.invoice_id).await? else {
+            return Ok(Outcome::UnknownInvoice);
+        };
+        match verify(&candidate, &expected) {
+            Verdict::Match => {}
+            verdict => return Ok(Outcome::Rejected(verdict)),
+        }
+
+        Ok(match self.queue.enqueue(&candidate.to_record()).await? {
+            Enqueued::New => Outcome::Queued,
+            Enqueued::Duplicate => Outcome::AlreadyQueued,
+        })
+    }
+
+    /// Submit up to one batch of due queue entries, one attempt each.
+    /// Returns the number of entries attempted (0 means nothing is due).
+    pub async fn drain_once(&self) -> Result<usize> {
+        let jobs = self.queue.due(SystemTime::now(), DRAIN_BATCH).await?;
+        for job in &jobs {
+            let attempt = self.attempt(job).await?;
+            log_attempt(job, &attempt);
+            record_attempt(&attempt);
+        }
+        Ok(jobs.len())
+    }
+
+    async fn attempt(&self, job: &Job) -> Result<Attempt> {
+        let invoice_id = &job.payment.invoice_id;
+        self.fees.refresh(self.client.rpc()).await;
+        let age = job.created_at.elapsed().unwrap_or_default();
+        let bid = self.fees.bid(job.attempts, age);
+        if bid.capped {
+            tracing::warn!(%invoice_id, fee = bid.fee, urgent = bid.urgent, "inclusion fee bid capped");
+        }
+        metrics().inclusion_fee.set(i64::from(bid.fee));
+
+        let started = Instant::now();
+        let result = self
+            .submitter
+            .submit(&self.client, &self.signer, &self.queue, job, bid.fee)
+            .await?;
+        metrics()
+            .submission_duration
+            .observe(started.elapsed().as_secs_f64());
+        if let Some(included) = inclusion(&result) {
+            self.fees.observe(included);
+        }
+        if let Some(rate) = self.fees.inclusion_rate() {
+            metrics().inclusion_rate.set(rate);
+        }
+
+        let err = match result {
+            Ok(submission) => {
+                let hash = match &submission {
+                    Submission::Recorded { hash } => Some(hash.as_str()),
+                    Submission::AlreadyRecorded => None,
+                };
+                self.queue.complete(invoice_id, hash).await?;
+                return Ok(Attempt::Recorded(submission));
+            }
+            Err(err) => err,
+        };
+        metrics().rpc_error(&err);
+
+        let error = err.to_string();
+        if is_retryable(&err) && job.attempts + 1 < self.backoff.max_attempts {
+            let delay = self.backoff.delay(job.attempts);
+            self.queue.retry_later(invoice_id, delay, &error).await?;
+            Ok(Attempt::Retrying { delay, error })
+        } else if let Some(sent) = self.queue.in_flight(invoice_id).await? {
+            // Out of attempts, but a sent transaction may still land.
+            // Wait out its deadline before judging the job.
+            let delay = until_expired(&sent, SystemTime::now());
+            self.queue.retry_later(invoice_id, delay, &error).await?;
+            Ok(Attempt::Retrying { delay, error })
+        } else {
+            self.queue.fail(invoice_id, &error).await?;
+            Ok(Attempt::Failed(error))
+        }
+    }
+
+    /// Poll until `shutdown` resolves, sleeping `idle` whenever caught up.
+    pub async fn run(
+        &self,
+        idle: std::time::Duration,
+        shutdown: impl std::future::Future<Output = ()>,
+    ) {
+        tokio::pin!(shutdown);
+        loop {
+            let polled = self.poll_once().await.unwrap_or_else(|err| {
+                tracing::error!(error = %err, "poll failed");
+                0
+            });
+            let drained = self.drain_once().await.unwrap_or_else(|err| {
+                tracing::error!(error = %err, "drain failed");
+                0
+            });
+            let wait = if polled + drained == 0 {
+                idle
+            } else {
+                std::time::Duration::ZERO
+            };
+            tokio::select! {
+                _ = &mut shutdown => {
+                    tracing::info!("shutting down");
+                    return;
+                }
+                _ = tokio::time::sleep(wait) => {}
+            }
+        }
+    }
+}
+
+fn record_metrics(op: &PaymentOperation, outcome: &Outcome) {
+    let label = match outcome {
+        Outcome::Ignored | Outcome::Queued => None,
+        Outcome::UnknownInvoice => Some("unknown_invoice"),
+        Outcome::Rejected(_) => Some("rejected"),
+        Outcome::AlreadyQueued => Some("already_queued"),
+    };
+    if let Some(label) = label {
+        count(label);
+    }
+    if let Ok(created) = humantime::parse_rfc3339(&op.created_at) {
+        let lag = SystemTime::now()
+            .duration_since(created)
+            .unwrap_or_default();
+        metrics().horizon_lag.set(lag.as_secs_f64());
+    }
+}
+
+fn record_attempt(attempt: &Attempt) {
+    match attempt {
+        Attempt::Recorded(Submission::Recorded { .. }) => count("recorded"),
+        Attempt::Recorded(Submission::AlreadyRecorded) => count("already_recorded"),
+        Attempt::Retrying { .. } => {}
+        Attempt::Failed(_) => count("failed"),
+    }
+}
+
+fn count(outcome: &str) {
+    metrics()
+        .payments_recorded
+        .with_label_values(&[outcome])
+        .inc();
+}
+
+fn log_outcome(op: &PaymentOperation, outcome: &Outcome) {
+    match outcome {
+        Outcome::Ignored => tracing::trace!(op = %op.id, "ignored"),
+        Outcome::UnknownInvoice => {
+            tracing::warn!(op = %op.id, tx = %op.transaction_hash, "memo references unknown invoice")
+        }
+        Outcome::Rejected(verdict) => {
+            tracing::warn!(op = %op.id, tx = %op.transaction_hash, ?verdict, "payment does not match invoice")
+        }
+        Outcome::Queued => {
+            tracing::info!(op = %op.id, tx = %op.transaction_hash, "payment queued for recording")
+        }
+        Outcome::AlreadyQueued => {
+            tracing::info!(op = %op.id, tx = %op.transaction_hash, "invoice already queued")
+        }
+    }
+}
+
+fn log_attempt(job: &Job, attempt: &Attempt) {
+    let invoice_id = &job.payment.invoice_id;
+    match attempt {
+        Attempt::Recorded(submission) => {
+            tracing::info!(%invoice_id, ?submission, "payment recorded")
+        }
+        Attempt::Retrying { delay, error } => {
+            tracing::warn!(%invoice_id, attempt = job.attempts, ?delay, %error, "record_payment failed; retrying")
+        }
+        Attempt::Failed(error) => {
+            tracing::error!(%invoice_id, %error, "record_payment failed permanently")
+        }
+    }
+}
@@ -0,0 +1,29 @@
+/// Errors surfaced by the reconciliation daemon.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Missing or malformed configuration.
+    #[error("config: {0}")]
+    Config(String),
+
+    /// Horizon or invoice API transport failure.
+    #[error("http: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// Contract / Soroban RPC failure.
+    #[error(transparent)]
+    Client(#[from] invoisio_client::Error),
+
+    /// Local file I/O (cursor).
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Submission queue database failure.
+    #[error("queue: {0}")]
+    Queue(#[from] sqlx::Error),
+
+    /// Malformed queued payload.
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
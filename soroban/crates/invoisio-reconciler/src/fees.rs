@@ -0,0 +1,193 @@
+//! Inclusion fee bidding for `record_payment`.
+//!
+//! A Soroban fee is the resource fee fixed by simulation plus an inclusion
+//! fee that competes for ledger space. The 100-stroop minimum is enough
+//! until the network surges. After that, a record bidding the minimum waits
+//! until it times out, then retries at the same losing bid. [`FeeStrategy`]
+//! bids from three signals:
+//!
+//! - **Surge pricing:** recent Soroban inclusion fees from `getFeeStats`.
+//!   Normal jobs bid the median; time-sensitive jobs bid the 90th
+//!   percentile.
+//! - **Our own inclusion:** a window of recent submissions. Misses (timeouts,
+//!   `TRY_AGAIN_LATER`) double the bid, and a mostly-missed window
+//!   quadruples it.
+//! - **The job itself:** every retry doubles the bid, and a job pending
+//!   longer than `urgent_after` counts as time-sensitive.
+//!
+//! The bid never goes below `floor` or above `cap`. Time-sensitive jobs
+//! use `urgent_cap` instead of `cap`, so congestion never spends more than
+//! the budget.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use invoisio_client::rpc::GetFeeStatsResponse;
+use invoisio_client::{Error as ClientError, RpcClient};
+
+use crate::submitter::Submission;
+
+/// Bounds and tuning, in stroops.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeePolicy {
+    /// Lowest bid; the network minimum.
+    pub floor: u32,
+    /// Highest bid for a normal job.
+    pub cap: u32,
+    /// Highest bid for a time-sensitive job.
+    pub urgent_cap: u32,
+    /// Pending this long, a job is time-sensitive.
+    pub urgent_after: Duration,
+    /// Submissions remembered for the inclusion rate.
+    pub window: usize,
+    /// How long network fee stats are reused before refreshing.
+    pub stats_ttl: Duration,
+}
+
+impl Default for FeePolicy {
+    fn default() -> Self {
+        Self {
+            floor: 100,
+            cap: 10_000,
+            urgent_cap: 100_000,
+            urgent_after: Duration::from_secs(300),
+            window: 20,
+            stats_ttl: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Recent Soroban inclusion fees on the network.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NetworkFees {
+    pub p50: u32,
+    pub p90: u32,
+}
+
+impl From<&GetFeeStatsResponse> for NetworkFees {
+    fn from(stats: &GetFeeStatsResponse) -> Self {
+        let fee = |v: u64| u32::try_from(v).unwrap_or(u32::MAX);
+        Self {
+            p50: fee(stats.soroban_inclusion_fee.p50),
+            p90: fee(stats.soroban_inclusion_fee.p90),
+        }
+    }
+}
+
+/// An inclusion fee to submit with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bid {
+    pub fee: u32,
+    pub urgent: bool,
+    /// The cap cut the bid down.
+    pub capped: bool,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// `true` for each recent submission that was included.
+    recent: VecDeque<bool>,
+    network: Option<(Instant, NetworkFees)>,
+}
+
+#[derive(Debug, Default)]
+pub struct FeeStrategy {
+    policy: FeePolicy,
+    state: Mutex<State>,
+}
+
+impl FeeStrategy {
+    pub fn new(policy: FeePolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn policy(&self) -> &FeePolicy {
+        &self.policy
+    }
+
+    /// The bid for a job that has failed `attempts` times and has been
+    /// pending for `age`.
+    pub fn bid(&self, attempts: u32, age: Duration) -> Bid {
+        let state = self.state.lock().unwrap();
+        let urgent = age >= self.policy.urgent_after;
+        let network = state.network.map(|(_, fees)| fees).unwrap_or_default();
+        let market = if urgent { network.p90 } else { network.p50 };
+
+        let misses = state.recent.iter().filter(|included| !**included).count();
+        let congestion: u64 = match misses {
+            0 => 1,
+            n if n * 2 <= state.recent.len() => 2,
+            _ => 4,
+        };
+        let retries = 1u64 << attempts.min(16);
+
+        let cap = if urgent {
+            self.policy.urgent_cap
+        } else {
+            self.policy.cap
+        };
+        let wanted = u64::from(market.max(self.policy.floor)) * congestion * retries;
+        let fee = wanted.min(u64::from(cap)).max(u64::from(self.policy.floor)) as u32;
+        Bid {
+            fee,
+            urgent,
+            capped: wanted > u64::from(cap),
+        }
+    }
+
+    /// Remember whether a submission made it into a ledger.
+    pub fn observe(&self, included: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.recent.push_back(included);
+        while state.recent.len() > self.policy.window.max(1) {
+            state.recent.pop_front();
+        }
+    }
+
+    /// Share of remembered submissions that were included, if any.
+    pub fn inclusion_rate(&self) -> Option<f64> {
+        let state = self.state.lock().unwrap();
+        if state.recent.is_empty() {
+            return None;
+        }
+        let included = state.recent.iter().filter(|i| **i).count();
+        Some(included as f64 / state.recent.len() as f64)
+    }
+
+    pub fn set_network(&self, fees: NetworkFees) {
+        self.state.lock().unwrap().network = Some((Instant::now(), fees));
+    }
+
+    /// Refresh network fees from `getFeeStats` once they are older than
+    /// `stats_ttl`. On failure the previous figures are kept.
+    pub async fn refresh(&self, rpc: &RpcClient) {
+        let stale = match self.state.lock().unwrap().network {
+            Some((at, _)) => at.elapsed() >= self.policy.stats_ttl,
+            None => true,
+        };
+        if !stale {
+            return;
+        }
+        match rpc.get_fee_stats().await {
+            Ok(stats) => self.set_network(NetworkFees::from(&stats)),
+            Err(err) => tracing::warn!(error = %err, "getFeeStats failed; keeping last fees"),
+        }
+    }
+}
+
+/// What a submission result says about inclusion: `Some(false)` if the
+/// transaction was outbid or crowded out, `None` if it says nothing (e.g.
+/// nothing was sent because the invoice was already recorded).
+pub fn inclusion(result: &Result<Submission, ClientError>) -> Option<bool> {
+    match result {
+        Ok(Submission::Recorded { .. }) => Some(true),
+        Ok(Submission::AlreadyRecorded) => None,
+        Err(ClientError::Timeout { .. }) => Some(false),
+        Err(ClientError::SendRejected { status, .. }) if status == "TRY_AGAIN_LATER" => Some(false),
+        Err(_) => None,
+    }
+}
@@ -0,0 +1,418 @@
+//! Recorder account health monitor.
+//!
+//! `record_payment` stops landing long before anything in the poll loop
+//! notices: the recorder runs out of XLM for fees, its sequence number gets
+//! stuck, or the outbox grows faster than it drains. [`Monitor`] checks all
+//! three every `INVOISIO_HEALTH_SECS`, exports them as metrics, and POSTs an
+//! alert to `INVOISIO_ALERT_WEBHOOK_URL` whenever one starts or stops firing.
+//!
+//! | Alert              | Fires when                                           |
+//! |--------------------|------------------------------------------------------|
+//! | `account_missing`  | Horizon does not know the recorder account           |
+//! | `low_balance`      | XLM above the reserve < `INVOISIO_MIN_BALANCE_XLM`   |
+//! | `sequence_stalled` | Jobs pending, sequence unchanged for                 |
+//! |                    | `INVOISIO_SEQUENCE_STALL_SECS`                       |
+//! | `backlog`          | Pending jobs > `INVOISIO_MAX_BACKLOG`, or the oldest |
+//! |                    | older than `INVOISIO_MAX_BACKLOG_AGE_SECS`           |
+//!
+//! The alert body is JSON with a Slack-compatible `text` field:
+//!
+//! ```json
+//! { "alert": "low_balance", "status": "firing", "account": "G...",
+//!   "text": "recorder G... has 3.2000000 XLM above reserve (minimum 10.0000000)" }
+//! ```
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant, SystemTime};
+
+use invoisio_client::{RpcClient, Signer};
+use invoisio_metrics::metrics;
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::horizon::{Account, HorizonClient};
+use crate::matcher::parse_amount;
+use crate::queue::SubmissionQueue;
+
+/// Base reserve per ledger entry, in stroops (0.5 XLM).
+pub const BASE_RESERVE: i128 = 5_000_000;
+
+const STROOPS_PER_XLM: f64 = 10_000_000.0;
+
+/// Every [`Alert::name`], for the `invoisio_recorder_alerts` gauge.
+pub const ALERTS: [&str; 4] = [
+    "account_missing",
+    "low_balance",
+    "sequence_stalled",
+    "backlog",
+];
+
+/// Alert thresholds.
+#[derive(Clone, Debug)]
+pub struct Thresholds {
+    /// Minimum spendable XLM above the reserve, in stroops.
+    pub min_balance: i128,
+    pub max_backlog: u64,
+    pub max_backlog_age: Duration,
+    pub sequence_stall: Duration,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            min_balance: 100_000_000,
+            max_backlog: 100,
+            max_backlog_age: Duration::from_secs(600),
+            sequence_stall: Duration::from_secs(300),
+        }
+    }
+}
+
+/// Native balance of an account, in stroops.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Balance {
+    pub total: i128,
+    /// What is left for fees after the minimum balance and selling
+    /// liabilities.
+    pub spendable: i128,
+}
+
+impl Balance {
+    /// `None` if the account holds no parseable native balance.
+    pub fn of(account: &Account) -> Option<Self> {
+        let native = account.balances.iter().find(|b| b.asset_type == "native")?;
+        let total = parse_amount(&native.balance)?;
+        let liabilities = match &native.selling_liabilities {
+            Some(l) => parse_amount(l)?,
+            None => 0,
+        };
+        let entries = 2 + i128::from(account.subentry_count) + i128::from(account.num_sponsoring)
+            - i128::from(account.num_sponsored);
+        Some(Self {
+            total,
+            spendable: total - entries * BASE_RESERVE - liabilities,
+        })
+    }
+}
+
+/// Outbox state relevant to alerting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Backlog {
+    pub pending: u64,
+    pub failed: u64,
+    pub oldest_age: Option<Duration>,
+}
+
+/// One health reading.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    /// `None` if the account does not exist.
+    pub balance: Option<Balance>,
+    pub sequence: Option<i64>,
+    pub backlog: Backlog,
+}
+
+/// A firing condition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Alert {
+    AccountMissing,
+    LowBalance { spendable: i128, minimum: i128 },
+    SequenceStalled { sequence: i64, stalled: Duration },
+    Backlog { pending: u64, oldest_age: Duration },
+}
+
+impl Alert {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Alert::AccountMissing => "account_missing",
+            Alert::LowBalance { .. } => "low_balance",
+            Alert::SequenceStalled { .. } => "sequence_stalled",
+            Alert::Backlog { .. } => "backlog",
+        }
+    }
+
+    pub fn message(&self, account: &str) -> String {
+        match self {
+            Alert::AccountMissing => format!("recorder {account} does not exist on the network"),
+            Alert::LowBalance { spendable, minimum } => format!(
+                "recorder {account} has {} XLM above reserve (minimum {})",
+                xlm(*spendable),
+                xlm(*minimum)
+            ),
+            Alert::SequenceStalled { sequence, stalled } => format!(
+                "recorder {account} sequence stuck at {sequence} for {}s with submissions pending",
+                stalled.as_secs()
+            ),
+            Alert::Backlog {
+                pending,
+                oldest_age,
+            } => format!(
+                "{pending} record_payment submissions pending, oldest {}s old",
+                oldest_age.as_secs()
+            ),
+        }
+    }
+}
+
+/// Conditions firing for `snapshot`. `stalled` is how long the sequence has
+/// not moved while jobs were pending (see [`SequenceWatch`]).
+pub fn evaluate(
+    snapshot: &Snapshot,
+    stalled: Option<Duration>,
+    thresholds: &Thresholds,
+) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+    match snapshot.balance {
+        None => alerts.push(Alert::AccountMissing),
+        Some(balance) if balance.spendable < thresholds.min_balance => {
+            alerts.push(Alert::LowBalance {
+                spendable: balance.spendable,
+                minimum: thresholds.min_balance,
+            })
+        }
+        Some(_) => {}
+    }
+    if let (Some(sequence), Some(stalled)) = (snapshot.sequence, stalled) {
+        if stalled >= thresholds.sequence_stall {
+            alerts.push(Alert::SequenceStalled { sequence, stalled });
+        }
+    }
+    let backlog = snapshot.backlog;
+    let oldest_age = backlog.oldest_age.unwrap_or_default();
+    if backlog.pending > thresholds.max_backlog || oldest_age > thresholds.max_backlog_age {
+        alerts.push(Alert::Backlog {
+            pending: backlog.pending,
+            oldest_age,
+        });
+    }
+    alerts
+}
+
+/// Tracks how long the sequence number has stood still while work is
+/// pending. Any change, or an empty backlog, resets it.
+#[derive(Debug, Default)]
+pub struct SequenceWatch {
+    since: Option<(i64, Instant)>,
+}
+
+impl SequenceWatch {
+    pub fn observe(&mut self, sequence: i64, pending: bool, now: Instant) -> Option<Duration> {
+        match self.since {
+            Some((last, since)) if pending && last == sequence => Some(now - since),
+            _ => {
+                self.since = pending.then_some((sequence, now));
+                None
+            }
+        }
+    }
+}
+
+/// Whether an alert started or stopped firing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Firing,
+    Resolved,
+}
+
+/// Alerts currently firing, so notifications go out only on changes.
+#[derive(Debug, Default)]
+pub struct Active {
+    firing: BTreeMap<&'static str, Alert>,
+}
+
+impl Active {
+    /// Replace the firing set with `alerts`, returning what changed.
+    pub fn update(&mut self, alerts: Vec<Alert>) -> Vec<(Status, Alert)> {
+        let mut next = BTreeMap::new();
+        let mut changes = Vec::new();
+        for alert in alerts {
+            if !self.firing.contains_key(alert.name()) {
+                changes.push((Status::Firing, alert.clone()));
+            }
+            next.insert(alert.name(), alert);
+        }
+        for (name, alert) in std::mem::take(&mut self.firing) {
+            if !next.contains_key(name) {
+                changes.push((Status::Resolved, alert));
+            }
+        }
+        self.firing = next;
+        changes
+    }
+}
+
+/// JSON POSTed to the alert webhook.
+#[derive(Debug, Serialize)]
+pub struct Notification<'a> {
+    pub alert: &'static str,
+    pub status: Status,
+    pub account: &'a str,
+    pub text: String,
+}
+
+pub struct Monitor {
+    horizon: HorizonClient,
+    rpc: RpcClient,
+    queue: SubmissionQueue,
+    /// `G...` recorder address.
+    account: String,
+    account_key: [u8; 32],
+    thresholds: Thresholds,
+    alert_url: Option<String>,
+    http: reqwest::Client,
+    sequence: SequenceWatch,
+    active: Active,
+}
+
+impl Monitor {
+    pub fn new(
+        horizon: HorizonClient,
+        rpc: RpcClient,
+        queue: SubmissionQueue,
+        recorder: &impl Signer,
+        thresholds: Thresholds,
+        alert_url: Option<String>,
+    ) -> Self {
+        Self {
+            horizon,
+            rpc,
+            queue,
+            account: recorder.public_key(),
+            account_key: recorder.public_key_bytes(),
+            thresholds,
+            alert_url,
+            http: reqwest::Client::new(),
+            sequence: SequenceWatch::default(),
+            active: Active::default(),
+        }
+    }
+
+    /// Read balance, sequence and backlog, and update the gauges.
+    pub async fn snapshot(&self) -> Result<Snapshot> {
+        let account = self.horizon.account(&self.account).await?;
+        let balance = account.as_ref().and_then(Balance::of);
+        // Submissions build on RPC's view of the sequence, so watch that one.
+        let sequence = match account {
+            Some(_) => Some(
+                self.rpc
+                    .get_account_sequence(self.account_key)
+                    .await
+                    .inspect_err(|err| metrics().rpc_error(err))?,
+            ),
+            None => None,
+        };
+        let depth = self.queue.depth().await?;
+        let oldest_age = self
+            .queue
+            .oldest_pending()
+            .await?
+            .map(|t| SystemTime::now().duration_since(t).unwrap_or_default());
+        let backlog = Backlog {
+            pending: depth.pending,
+            failed: depth.failed,
+            oldest_age,
+        };
+
+        let m = metrics();
+        if let Some(balance) = balance {
+            m.recorder_balance
+                .set(balance.total as f64 / STROOPS_PER_XLM);
+            m.recorder_spendable
+                .set(balance.spendable as f64 / STROOPS_PER_XLM);
+        }
+        if let Some(sequence) = sequence {
+            m.recorder_sequence.set(sequence);
+        }
+        m.queue_jobs
+            .with_label_values(&["pending"])
+            .set(backlog.pending as i64);
+        m.queue_jobs
+            .with_label_values(&["failed"])
+            .set(backlog.failed as i64);
+        m.queue_oldest_pending
+            .set(oldest_age.unwrap_or_default().as_secs_f64());
+
+        Ok(Snapshot {
+            balance,
+            sequence,
+            backlog,
+        })
+    }
+
+    /// Take one reading and notify on alert changes.
+    pub async fn check_once(&mut self) -> Result<()> {
+        let snapshot = self.snapshot().await?;
+        let stalled = snapshot.sequence.and_then(|sequence| {
+            self.sequence
+                .observe(sequence, snapshot.backlog.pending > 0, Instant::now())
+        });
+        let alerts = evaluate(&snapshot, stalled, &self.thresholds);
+        for name in ALERTS {
+            let firing = alerts.iter().any(|a| a.name() == name);
+            metrics()
+                .recorder_alerts
+                .with_label_values(&[name])
+                .set(firing.into());
+        }
+        for (status, alert) in self.active.update(alerts) {
+            self.notify(status, &alert).await;
+        }
+        Ok(())
+    }
+
+    async fn notify(&self, status: Status, alert: &Alert) {
+        let text = alert.message(&self.account);
+        match status {
+            Status::Firing => tracing::warn!(alert = alert.name(), %text, "recorder alert firing"),
+            Status::Resolved => {
+                tracing::info!(alert = alert.name(), %text, "recorder alert resolved")
+            }
+        }
+        let Some(url) = &self.alert_url else {
+            return;
+        };
+        let body = Notification {
+            alert: alert.name(),
+            status,
+            account: &self.account,
+            text,
+        };
+        let sent = self
+            .http
+            .post(url)
+            .timeout(Duration::from_secs(10))
+            .json(&body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status);
+        if let Err(err) = sent {
+            tracing::error!(alert = alert.name(), error = %err, "alert webhook failed");
+        }
+    }
+
+    /// Check every `interval` until `shutdown` resolves.
+    pub async fn run(
+        mut self,
+        interval: Duration,
+        shutdown: impl std::future::Future<Output = ()>,
+    ) {
+        tokio::pin!(shutdown);
+        loop {
+            if let Err(err) = self.check_once().await {
+                tracing::error!(error = %err, "health check failed");
+            }
+            tokio::select! {
+                _ = &mut shutdown => return,
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+    }
+}
+
+/// `stroops` as a 7-decimal XLM string.
+pub fn xlm(stroops: i128) -> String {
+    let sign = if stroops < 0 { "-" } else { "" };
+    let abs = stroops.unsigned_abs();
+    format!("{sign}{}.{:07}", abs / 10_000_000, abs % 10_000_000)
+}
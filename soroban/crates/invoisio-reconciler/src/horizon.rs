@@ -0,0 +1,140 @@
+//! Horizon `/accounts/:id/payments` reader, plus the account lookup the
+//! [health monitor](crate::health) uses.
+//!
+//! Horizon's payments endpoint is polled with a paging-token cursor rather
+//! than consumed over SSE: a poll loop survives proxies and restarts without
+//! special handling, and the cursor doubles as the daemon's checkpoint.
+
+use serde::Deserialize;
+
+use crate::error::Result;
+
+/// Page size requested from Horizon (its maximum).
+pub const PAGE_LIMIT: u32 = 200;
+
+#[derive(Clone, Debug)]
+pub struct HorizonClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl HorizonClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Payment-like operations received by `account`, oldest first, strictly
+    /// after `cursor` (or from the beginning of history when `None`).
+    pub async fn payments(
+        &self,
+        account: &str,
+        cursor: Option<&str>,
+    ) -> Result<Vec<PaymentOperation>> {
+        let mut query = vec![
+            ("order", "asc".to_string()),
+            ("limit", PAGE_LIMIT.to_string()),
+            ("join", "transactions".to_string()),
+        ];
+        if let Some(c) = cursor {
+            query.push(("cursor", c.to_string()));
+        }
+        let page: Page<PaymentOperation> = self
+            .http
+            .get(format!("{}/accounts/{account}/payments", self.base_url))
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(page.embedded.records)
+    }
+
+    /// `account`'s balances and reserve inputs, or `None` if it does not
+    /// exist (never funded, or merged).
+    pub async fn account(&self, account: &str) -> Result<Option<Account>> {
+        let response = self
+            .http
+            .get(format!("{}/accounts/{account}", self.base_url))
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.json().await?))
+    }
+}
+
+/// The parts of a Horizon account record the health monitor reads.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Account {
+    /// Decimal string; `i64` on the wire.
+    pub sequence: String,
+    #[serde(default)]
+    pub subentry_count: u32,
+    #[serde(default)]
+    pub num_sponsoring: u32,
+    #[serde(default)]
+    pub num_sponsored: u32,
+    pub balances: Vec<AccountBalance>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AccountBalance {
+    pub asset_type: String,
+    /// Decimal string with 7 fractional digits.
+    pub balance: String,
+    #[serde(default)]
+    pub selling_liabilities: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    #[serde(rename = "_embedded")]
+    embedded: Embedded<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Embedded<T> {
+    records: Vec<T>,
+}
+
+/// A Horizon payment-like operation (`payment`, `path_payment_*`,
+/// `create_account`, ...). Only the fields the matcher needs are kept.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PaymentOperation {
+    pub id: String,
+    pub paging_token: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub transaction_hash: String,
+    #[serde(default)]
+    pub transaction_successful: bool,
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub asset_type: Option<String>,
+    #[serde(default)]
+    pub asset_code: Option<String>,
+    #[serde(default)]
+    pub asset_issuer: Option<String>,
+    /// Decimal string with 7 fractional digits, e.g. `"10.0000000"`.
+    #[serde(default)]
+    pub amount: Option<String>,
+    pub created_at: String,
+    /// Present because we request `join=transactions`.
+    #[serde(default)]
+    pub transaction: Option<HorizonTransaction>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct HorizonTransaction {
+    pub memo_type: String,
+    #[serde(default)]
+    pub memo: Option<String>,
+}
@@ -0,0 +1,67 @@
+//! Where the daemon learns what an invoice should have been paid with.
+
+use std::future::Future;
+
+use invoisio_client::Asset;
+use serde::Deserialize;
+
+use crate::error::Result;
+use crate::matcher::ExpectedInvoice;
+
+/// Lookup of invoice expectations by on-chain invoice id.
+pub trait InvoiceSource: Send + Sync {
+    /// `Ok(None)` means the backend does not know the invoice.
+    fn expected(
+        &self,
+        invoice_id: &str,
+    ) -> impl Future<Output = Result<Option<ExpectedInvoice>>> + Send;
+}
+
+/// [`InvoiceSource`] backed by the Invoisio API: `GET {base}/invoices/{id}`.
+#[derive(Clone, Debug)]
+pub struct HttpInvoiceSource {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl HttpInvoiceSource {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct InvoiceDto {
+    invoice_id: String,
+    asset_code: String,
+    #[serde(default)]
+    asset_issuer: String,
+    /// Base units as a decimal string.
+    amount: String,
+}
+
+impl InvoiceSource for HttpInvoiceSource {
+    async fn expected(&self, invoice_id: &str) -> Result<Option<ExpectedInvoice>> {
+        let response = self
+            .http
+            .get(format!("{}/invoices/{invoice_id}", self.base_url))
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let dto: InvoiceDto = response.error_for_status()?.json().await?;
+        let Ok(amount) = dto.amount.parse() else {
+            tracing::warn!(invoice_id, amount = %dto.amount, "invoice API returned a bad amount");
+            return Ok(None);
+        };
+        Ok(Some(ExpectedInvoice {
+            invoice_id: dto.invoice_id,
+            asset: Asset::from_code_issuer(&dto.asset_code, &dto.asset_issuer),
+            amount,
+        }))
+    }
+}
@@ -0,0 +1,44 @@
+//! # invoisio-reconciler
+//!
+//! Long-running service implementing the contract's "typical backend flow":
+//! stream Horizon payments to the merchant account, match `invoisio-<id>`
+//! memos, verify asset and amount against the invoice, and anchor matches
+//! on-chain with `record_payment`.
+//!
+//! ## Module layout
+//! | Module         | Responsibility                                   |
+//! |----------------|--------------------------------------------------|
+//! | `config.rs`    | `INVOISIO_*` environment configuration           |
+//! | `horizon.rs`   | Horizon payments reader                          |
+//! | `matcher.rs`   | Memo parsing, amount parsing, invoice verification |
+//! | `invoices.rs`  | [`InvoiceSource`] trait + HTTP implementation    |
+//! | `submitter.rs` | `record_payment` with exponential backoff        |
+//! | `fees.rs`      | Inclusion fee bids under surge pricing           |
+//! | `queue.rs`     | Durable SQLite outbox of pending submissions     |
+//! | `sequence.rs`  | Per-account sequence numbers for submissions     |
+//! | `cursor.rs`    | Persisted Horizon paging token                   |
+//! | `daemon.rs`    | [`Reconciler`] poll loop                         |
+//! | `health.rs`    | Recorder balance / sequence / backlog [`Monitor`] |
+
+pub mod config;
+pub mod cursor;
+pub mod daemon;
+pub mod error;
+pub mod fees;
+pub mod health;
+pub mod horizon;
+pub mod invoices;
+pub mod matcher;
+pub mod queue;
+pub mod sequence;
+pub mod submitter;
+
+pub use config::Config;
+pub use daemon::{Outcome, Reconciler};
+pub use error::{Error, Result};
+pub use fees::{FeePolicy, FeeStrategy};
+pub use health::Monitor;
+pub use invoices::{HttpInvoiceSource, InvoiceSource};
+pub use queue::SubmissionQueue;
+
+mod test;
@@ -0,0 +1,62 @@
+use invoisio_client::{InvoicePaymentClient, RpcClient, Signer};
+use invoisio_reconciler::cursor::FileCursor;
+use invoisio_reconciler::horizon::HorizonClient;
+use invoisio_reconciler::submitter::{Backoff, DEFAULT_BACKOFF};
+use invoisio_reconciler::{
+    Config, FeeStrategy, HttpInvoiceSource, Monitor, Reconciler, SubmissionQueue,
+};
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() -> invoisio_reconciler::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let config = Config::from_env()?;
+    invoisio_metrics::spawn(config.metrics_addr);
+    let client =
+        InvoicePaymentClient::new(&config.rpc_url, &config.network_passphrase, &config.contract_id)?;
+    let signer = config.signer.connect().await?;
+
+    tracing::info!(
+        contract = %config.contract_id,
+        destination = %config.destination,
+        recorder = %signer.public_key(),
+        signer = ?config.signer,
+        "starting reconciler"
+    );
+
+    let queue = SubmissionQueue::open(&config.queue_path).await?;
+    let monitor = Monitor::new(
+        HorizonClient::new(&config.horizon_url),
+        RpcClient::new(&config.rpc_url),
+        queue.clone(),
+        &signer,
+        config.thresholds.clone(),
+        config.alert_webhook_url.clone(),
+    );
+    tokio::spawn(monitor.run(config.health_interval, std::future::pending()));
+
+    let reconciler = Reconciler::new(
+        HorizonClient::new(&config.horizon_url),
+        client,
+        signer,
+        HttpInvoiceSource::new(&config.invoice_api_url),
+        FileCursor::new(&config.cursor_file),
+        queue,
+        config.destination.clone(),
+        Backoff {
+            max_attempts: config.max_attempts,
+            ..DEFAULT_BACKOFF
+        },
+    )
+    .with_fees(FeeStrategy::new(config.fees.clone()));
+
+    reconciler
+        .run(config.poll_interval, async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await;
+    Ok(())
+}
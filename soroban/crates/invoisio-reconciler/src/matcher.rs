@@ -0,0 +1,134 @@
+//! Turning Horizon operations into `record_payment` candidates.
+
+use invoisio_client::{Asset, RecordPayment};
+
+use crate::horizon::PaymentOperation;
+
+/// Every Invoisio payment carries a text memo of this form.
+pub const MEMO_PREFIX: &str = "invoisio-";
+
+/// Operation types whose `asset_*` / `amount` fields describe what the
+/// destination received.
+const PAYMENT_KINDS: [&str; 3] = [
+    "payment",
+    "path_payment_strict_receive",
+    "path_payment_strict_send",
+];
+
+/// A Horizon payment that references an Invoisio invoice.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candidate {
+    /// The full memo, e.g. `"invoisio-abc123"` — this *is* the on-chain id.
+    pub invoice_id: String,
+    pub payer: String,
+    pub asset: Asset,
+    /// Stroops / token base units.
+    pub amount: i128,
+    pub tx_hash: String,
+    pub paging_token: String,
+}
+
+impl Candidate {
+    pub fn to_record(&self) -> RecordPayment {
+        RecordPayment {
+            invoice_id: self.invoice_id.clone(),
+            payer: self.payer.clone(),
+            asset: self.asset.clone(),
+            amount: self.amount,
+        }
+    }
+}
+
+/// What the backend says an invoice should be paid with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpectedInvoice {
+    pub invoice_id: String,
+    pub asset: Asset,
+    pub amount: i128,
+}
+
+/// Outcome of checking a [`Candidate`] against its [`ExpectedInvoice`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Right asset, amount at least the invoiced amount.
+    Match,
+    WrongAsset { expected: Asset, received: Asset },
+    Underpaid { expected: i128, received: i128 },
+}
+
+/// Return the invoice id encoded in a text memo, if it is an Invoisio memo.
+pub fn invoice_id_from_memo<'a>(memo_type: &str, memo: Option<&'a str>) -> Option<&'a str> {
+    let memo = memo?;
+    if memo_type != "text" {
+        return None;
+    }
+    match memo.strip_prefix(MEMO_PREFIX) {
+        Some(rest) if !rest.is_empty() => Some(memo),
+        _ => None,
+    }
+}
+
+/// Parse a Horizon decimal amount (`"12.3400000"`) into base units (7 dp).
+pub fn parse_amount(s: &str) -> Option<i128> {
+    const DECIMALS: usize = 7;
+    let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+    if whole.is_empty() || frac.len() > DECIMALS || s.starts_with('-') {
+        return None;
+    }
+    let whole: i128 = whole.parse().ok()?;
+    let frac_digits = format!("{:0<width$}", frac, width = DECIMALS);
+    let frac: i128 = frac_digits.parse().ok()?;
+    whole.checked_mul(10_000_000)?.checked_add(frac)
+}
+
+/// The asset an operation delivered to its destination.
+pub fn asset_of(op: &PaymentOperation) -> Option<Asset> {
+    match op.asset_type.as_deref()? {
+        "native" => Some(Asset::Native),
+        "credit_alphanum4" | "credit_alphanum12" => Some(Asset::Token {
+            code: op.asset_code.clone()?,
+            issuer: op.asset_issuer.clone()?,
+        }),
+        _ => None,
+    }
+}
+
+/// Extract a [`Candidate`] from `op` if it is a successful incoming payment
+/// to `destination` carrying an Invoisio memo.
+pub fn candidate(op: &PaymentOperation, destination: &str) -> Option<Candidate> {
+    if !op.transaction_successful || !PAYMENT_KINDS.contains(&op.kind.as_str()) {
+        return None;
+    }
+    if op.to.as_deref() != Some(destination) {
+        return None;
+    }
+    let tx = op.transaction.as_ref()?;
+    let invoice_id = invoice_id_from_memo(&tx.memo_type, tx.memo.as_deref())?;
+
+    Some(Candidate {
+        invoice_id: invoice_id.to_string(),
+        payer: op.from.clone()?,
+        asset: asset_of(op)?,
+        amount: parse_amount(op.amount.as_deref()?)?,
+        tx_hash: op.transaction_hash.clone(),
+        paging_token: op.paging_token.clone(),
+    })
+}
+
+/// Check a candidate against the invoice. Overpayments are accepted; the
+/// recorded amount is always what was actually received.
+pub fn verify(candidate: &Candidate, expected: &ExpectedInvoice) -> Verdict {
+    if candidate.asset != expected.asset {
+        return Verdict::WrongAsset {
+            expected: expected.asset.clone(),
+            received: candidate.asset.clone(),
+        };
+    }
+    if candidate.amount < expected.amount {
+        return Verdict::Underpaid {
+            expected: expected.amount,
+            received: candidate.amount,
+        };
+    }
+    Verdict::Match
+}
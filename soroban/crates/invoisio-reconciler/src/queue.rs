@@ -0,0 +1,302 @@
+//! Durable outbound queue for `record_payment` submissions (SQLite).
+//!
+//! A matched payment is enqueued before the Horizon cursor moves past it and
+//! removed from the pending set only once the contract has it, so a restart
+//! or deploy can never drop an in-flight record. Rows are keyed by invoice
+//! id: enqueueing the same invoice twice is a no-op, which deduplicates
+//! before anything reaches the network.
+//!
+//! | Status    | Meaning                                               |
+//! |-----------|-------------------------------------------------------|
+//! | `pending` | Waiting for `next_attempt_at`                         |
+//! | `done`    | Recorded on-chain (by us or an earlier attempt)       |
+//! | `failed`  | Rejected by the contract or out of attempts; manual   |
+//!
+//! A signed transaction is written to `in_flight` before it is sent and
+//! removed once it has landed or can no longer land. After a timeout or a
+//! restart the next attempt picks it up from there instead of sending a
+//! second transaction for the same invoice.
+
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use invoisio_client::RecordPayment;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
+
+use crate::error::Result;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS outbox (
+    invoice_id      TEXT    PRIMARY KEY,
+    payload         TEXT    NOT NULL,
+    status          TEXT    NOT NULL DEFAULT 'pending',
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at INTEGER NOT NULL,
+    last_error      TEXT,
+    tx_hash         TEXT,
+    created_at      INTEGER NOT NULL,
+    updated_at      INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS outbox_due_idx ON outbox (status, next_attempt_at);
+CREATE TABLE IF NOT EXISTS in_flight (
+    invoice_id  TEXT    PRIMARY KEY,
+    hash        TEXT    NOT NULL,
+    envelope    TEXT    NOT NULL,
+    sequence    INTEGER NOT NULL,
+    valid_until INTEGER NOT NULL,
+    sent_at     INTEGER NOT NULL
+);
+";
+
+/// Result of [`SubmissionQueue::enqueue`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Enqueued {
+    New,
+    /// A job for this invoice already exists, in any status.
+    Duplicate,
+}
+
+/// A pending submission.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Job {
+    pub payment: RecordPayment,
+    /// Attempts made so far.
+    pub attempts: u32,
+    /// When the job was enqueued.
+    pub created_at: SystemTime,
+    /// Transaction sent by an earlier attempt whose fate is unknown.
+    pub in_flight: Option<InFlight>,
+}
+
+/// A signed `record_payment` transaction that may still land.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InFlight {
+    pub hash: String,
+    /// Base64 `TransactionEnvelope`, for sending again unchanged.
+    pub envelope: String,
+    pub sequence: i64,
+    /// The transaction's deadline; after it, the transaction cannot land.
+    pub valid_until: SystemTime,
+}
+
+/// Number of jobs per status.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Depth {
+    pub pending: u64,
+    pub done: u64,
+    pub failed: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct SubmissionQueue {
+    pool: SqlitePool,
+}
+
+impl SubmissionQueue {
+    /// Open (creating if needed) the queue database at `path`.
+    pub async fn open(path: &Path) -> Result<Self> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+        Self::connect(options).await
+    }
+
+    /// A throwaway in-memory queue, for tests.
+    pub async fn in_memory() -> Result<Self> {
+        Self::connect(SqliteConnectOptions::from_str("sqlite::memory:")?).await
+    }
+
+    async fn connect(options: SqliteConnectOptions) -> Result<Self> {
+        // One connection: the workload is tiny, and `:memory:` databases
+        // are per-connection.
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+        sqlx::raw_sql(SCHEMA).execute(&pool).await?;
+        Ok(Self { pool })
+    }
+
+    pub async fn enqueue(&self, payment: &RecordPayment) -> Result<Enqueued> {
+        let now = now();
+        let done = sqlx::query(
+            "INSERT INTO outbox (invoice_id, payload, next_attempt_at, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?3, ?3)
+             ON CONFLICT (invoice_id) DO NOTHING",
+        )
+        .bind(&payment.invoice_id)
+        .bind(serde_json::to_string(payment)?)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        Ok(if done.rows_affected() == 0 {
+            Enqueued::Duplicate
+        } else {
+            Enqueued::New
+        })
+    }
+
+    /// Pending jobs whose next attempt is due at `at`, oldest first.
+    pub async fn due(&self, at: SystemTime, limit: u32) -> Result<Vec<Job>> {
+        let rows = sqlx::query(
+            "SELECT o.payload, o.attempts, o.created_at,
+                    f.hash, f.envelope, f.sequence, f.valid_until
+             FROM outbox o LEFT JOIN in_flight f ON f.invoice_id = o.invoice_id
+             WHERE o.status = 'pending' AND o.next_attempt_at <= ?1
+             ORDER BY o.next_attempt_at, o.created_at
+             LIMIT ?2",
+        )
+        .bind(unix(at))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|r| {
+                Ok(Job {
+                    payment: serde_json::from_str(r.get::<&str, _>("payload"))?,
+                    attempts: r.get::<i64, _>("attempts") as u32,
+                    created_at: from_unix(r.get("created_at")),
+                    in_flight: in_flight(&r),
+                })
+            })
+            .collect()
+    }
+
+    /// Mark `invoice_id` as recorded.
+    pub async fn complete(&self, invoice_id: &str, tx_hash: Option<&str>) -> Result<()> {
+        self.finish(invoice_id, "done", tx_hash, None).await
+    }
+
+    /// Mark `invoice_id` as permanently failed.
+    pub async fn fail(&self, invoice_id: &str, error: &str) -> Result<()> {
+        self.finish(invoice_id, "failed", None, Some(error)).await
+    }
+
+    /// Remember `sent` as the transaction carrying `invoice_id`, replacing
+    /// any earlier one. Call before sending it.
+    pub async fn track(&self, invoice_id: &str, sent: &InFlight) -> Result<()> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO in_flight
+                 (invoice_id, hash, envelope, sequence, valid_until, sent_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )
+        .bind(invoice_id)
+        .bind(&sent.hash)
+        .bind(&sent.envelope)
+        .bind(sent.sequence)
+        .bind(unix(sent.valid_until))
+        .bind(now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Forget `invoice_id`'s transaction once it can no longer land.
+    pub async fn untrack(&self, invoice_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM in_flight WHERE invoice_id = ?1")
+            .bind(invoice_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn in_flight(&self, invoice_id: &str) -> Result<Option<InFlight>> {
+        let row = sqlx::query(
+            "SELECT hash, envelope, sequence, valid_until FROM in_flight WHERE invoice_id = ?1",
+        )
+        .bind(invoice_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.as_ref().and_then(in_flight))
+    }
+
+    /// Count one more failed attempt and schedule the next one `delay` from
+    /// now.
+    pub async fn retry_later(&self, invoice_id: &str, delay: Duration, error: &str) -> Result<()> {
+        let now = now();
+        sqlx::query(
+            "UPDATE outbox
+             SET attempts = attempts + 1, next_attempt_at = ?2, last_error = ?3, updated_at = ?4
+             WHERE invoice_id = ?1",
+        )
+        .bind(invoice_id)
+        .bind(now + delay.as_secs() as i64)
+        .bind(error)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn depth(&self) -> Result<Depth> {
+        let rows = sqlx::query("SELECT status, COUNT(*) AS n FROM outbox GROUP BY status")
+            .fetch_all(&self.pool)
+            .await?;
+        let mut depth = Depth::default();
+        for r in rows {
+            let n = r.get::<i64, _>("n") as u64;
+            match r.get::<&str, _>("status") {
+                "pending" => depth.pending = n,
+                "done" => depth.done = n,
+                "failed" => depth.failed = n,
+                _ => {}
+            }
+        }
+        Ok(depth)
+    }
+
+    /// Enqueue time of the oldest pending job, if any.
+    pub async fn oldest_pending(&self) -> Result<Option<SystemTime>> {
+        let oldest: Option<i64> =
+            sqlx::query_scalar("SELECT MIN(created_at) FROM outbox WHERE status = 'pending'")
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(oldest.map(from_unix))
+    }
+
+    async fn finish(
+        &self,
+        invoice_id: &str,
+        status: &str,
+        tx_hash: Option<&str>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE outbox
+             SET status = ?2, attempts = attempts + 1, tx_hash = ?3,
+                 last_error = COALESCE(?4, last_error), updated_at = ?5
+             WHERE invoice_id = ?1",
+        )
+        .bind(invoice_id)
+        .bind(status)
+        .bind(tx_hash)
+        .bind(error)
+        .bind(now())
+        .execute(&self.pool)
+        .await?;
+        self.untrack(invoice_id).await
+    }
+}
+
+fn in_flight(r: &SqliteRow) -> Option<InFlight> {
+    Some(InFlight {
+        hash: r.get::<Option<String>, _>("hash")?,
+        envelope: r.get("envelope"),
+        sequence: r.get("sequence"),
+        valid_until: from_unix(r.get("valid_until")),
+    })
+}
+
+fn now() -> i64 {
+    unix(SystemTime::now())
+}
+
+fn unix(t: SystemTime) -> i64 {
+    t.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64
+}
+
+fn from_unix(t: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(t.max(0) as u64)
+}
@@ -0,0 +1,73 @@
+//! Sequence numbers for the accounts that submit `record_payment`.
+//!
+//! Fetching the account sequence before every transaction costs a round
+//! trip and, worse, hands out the same number twice while an earlier
+//! transaction is still pending. [`SequenceManager`] hands out numbers per
+//! source account from a local counter instead:
+//!
+//! - The first [`next`](SequenceManager::next) for an account reads its
+//!   sequence from RPC; later calls count up from there.
+//! - A number whose transaction never reached the network is given back
+//!   with [`release`](SequenceManager::release), so the next transaction
+//!   fills the gap instead of stalling behind it.
+//! - After `txBadSeq` the counter is wrong; [`resync`](SequenceManager::resync)
+//!   drops it and the next call reads the account again.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use invoisio_client::{Result, RpcClient};
+
+#[derive(Debug, Default)]
+pub struct SequenceManager {
+    /// Last sequence number handed out, per source account.
+    last: Mutex<HashMap<[u8; 32], i64>>,
+}
+
+impl SequenceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The sequence number for `source`'s next transaction.
+    pub async fn next(&self, rpc: &RpcClient, source: [u8; 32]) -> Result<i64> {
+        if let Some(sequence) = self.advance(source) {
+            return Ok(sequence);
+        }
+        let current = rpc.get_account_sequence(source).await?;
+        Ok(self.start(source, current))
+    }
+
+    /// Hand out the next number if `source` is known.
+    pub fn advance(&self, source: [u8; 32]) -> Option<i64> {
+        let mut last = self.last.lock().unwrap();
+        let sequence = last.get_mut(&source)?;
+        *sequence += 1;
+        Some(*sequence)
+    }
+
+    /// Start counting for `source` from its on-ledger sequence `current`,
+    /// returning the first number to use. Another task may have started
+    /// meanwhile; the counter never moves backwards.
+    pub fn start(&self, source: [u8; 32], current: i64) -> i64 {
+        let mut last = self.last.lock().unwrap();
+        let sequence = last.entry(source).or_insert(current);
+        *sequence = (*sequence).max(current) + 1;
+        *sequence
+    }
+
+    /// Give back `sequence` when its transaction was never accepted. Only
+    /// the most recent number can be given back; an older one is left as a
+    /// gap for [`resync`](Self::resync) to repair.
+    pub fn release(&self, source: [u8; 32], sequence: i64) {
+        let mut last = self.last.lock().unwrap();
+        if last.get(&source) == Some(&sequence) {
+            last.insert(source, sequence - 1);
+        }
+    }
+
+    /// Forget `source`'s counter; the next number is read from RPC.
+    pub fn resync(&self, source: [u8; 32]) {
+        self.last.lock().unwrap().remove(&source);
+    }
+}
@@ -0,0 +1,330 @@
+//! `record_payment` submission with retries.
+//!
+//! [`submit_once`] and [`submit_with_retry`] are fire-and-wait helpers.
+//! The daemon uses [`Submitter`], which never has two transactions for the
+//! same invoice that could both land:
+//!
+//! 1. A job whose earlier transaction is still [in flight](InFlight) is
+//!    resumed: landed means done, pending means send the same envelope
+//!    again and keep waiting. Only once it failed, or is past its deadline
+//!    and unknown to RPC, is a new transaction built.
+//! 2. A new transaction takes its sequence number from the
+//!    [`SequenceManager`] and carries a deadline. It is recorded in the
+//!    queue before it is sent.
+//! 3. `txBadSeq` resyncs the sequence and rebuilds once straight away;
+//!    `TRY_AGAIN_LATER` and other rejections give the number back. A
+//!    timeout leaves the transaction in flight for the next attempt.
+//!
+//! A new transaction is always simulated first, so a record that landed
+//! some other way surfaces as `PaymentAlreadyRecorded` instead of a
+//! duplicate submission.
+
+use std::time::{Duration, Instant, SystemTime};
+
+use invoisio_client::client::record_payment_args;
+pub use invoisio_client::Backoff;
+use invoisio_client::{
+    tx, ContractError, Error as ClientError, InvoicePaymentClient, RecordPayment, Signer,
+};
+use stellar_xdr::curr::{Limits, ReadXdr, TransactionEnvelope, WriteXdr};
+
+use crate::error::Result;
+use crate::fees::{inclusion, FeeStrategy};
+use crate::queue::{InFlight, Job, SubmissionQueue};
+use crate::sequence::SequenceManager;
+
+/// Submission retries: 1 s doubling, capped at a minute, five tries.
+pub const DEFAULT_BACKOFF: Backoff = Backoff {
+    base: Duration::from_secs(1),
+    max: Duration::from_secs(60),
+    max_attempts: 5,
+};
+
+/// Successful outcome of a submission.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Submission {
+    Recorded { hash: String },
+    /// The contract already holds a record for this invoice — a previous
+    /// attempt (or another instance) got there first. Treated as success.
+    AlreadyRecorded,
+}
+
+/// `true` if retrying `err` might succeed.
+pub fn is_retryable(err: &ClientError) -> bool {
+    match err {
+        ClientError::Http(_)
+        | ClientError::Rpc { .. }
+        | ClientError::Timeout { .. }
+        | ClientError::TransactionFailed { .. } => true,
+        ClientError::SendRejected { status, .. } => status == "TRY_AGAIN_LATER" || err.is_bad_seq(),
+        _ => false,
+    }
+}
+
+/// Submit `payment` once, bidding `inclusion_fee` stroops. An
+/// already-recorded invoice counts as success.
+pub async fn submit_once(
+    client: &InvoicePaymentClient,
+    signer: &impl Signer,
+    payment: &RecordPayment,
+    inclusion_fee: u32,
+) -> Result<Submission, ClientError> {
+    match client
+        .record_payment_with_fee(signer, payment, inclusion_fee)
+        .await
+    {
+        Ok(outcome) => Ok(Submission::Recorded { hash: outcome.hash }),
+        Err(ClientError::Contract(ContractError::PaymentAlreadyRecorded)) => {
+            Ok(Submission::AlreadyRecorded)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Submit `payment`, retrying transient failures with `backoff` and a fee
+/// bid from `fees` that rises with each attempt.
+pub async fn submit_with_retry(
+    client: &InvoicePaymentClient,
+    signer: &impl Signer,
+    payment: &RecordPayment,
+    backoff: &Backoff,
+    fees: &FeeStrategy,
+) -> Result<Submission, ClientError> {
+    let started = Instant::now();
+    let mut attempt = 0;
+    loop {
+        fees.refresh(client.rpc()).await;
+        let bid = fees.bid(attempt, started.elapsed());
+        let result = submit_once(client, signer, payment, bid.fee).await;
+        if let Some(included) = inclusion(&result) {
+            fees.observe(included);
+        }
+        match result {
+            Ok(submission) => return Ok(submission),
+            Err(err) if is_retryable(&err) && attempt + 1 < backoff.max_attempts => {
+                let delay = backoff.delay(attempt);
+                tracing::warn!(
+                    invoice_id = %payment.invoice_id,
+                    attempt,
+                    ?delay,
+                    error = %err,
+                    "record_payment failed; retrying"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// How long a new transaction stays valid.
+pub const VALIDITY: Duration = Duration::from_secs(120);
+
+/// Allowance for ledger close times trailing our clock before a
+/// transaction past its deadline is taken to be dead.
+const DEADLINE_GRACE: Duration = Duration::from_secs(30);
+
+/// Fate of a transaction an earlier attempt sent.
+enum Resumed {
+    Landed(String),
+    /// Cannot land any more: it failed, or it is past its deadline.
+    Dead(Option<ClientError>),
+}
+
+/// Queue-aware `record_payment` submission; see the module docs.
+#[derive(Debug, Default)]
+pub struct Submitter {
+    sequences: SequenceManager,
+}
+
+impl Submitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sequences(&self) -> &SequenceManager {
+        &self.sequences
+    }
+
+    /// Make one attempt at `job`, bidding `inclusion_fee` stroops if a new
+    /// transaction is needed. The outer error is the queue's; the inner
+    /// result is the network's answer.
+    pub async fn submit(
+        &self,
+        client: &InvoicePaymentClient,
+        signer: &impl Signer,
+        queue: &SubmissionQueue,
+        job: &Job,
+        inclusion_fee: u32,
+    ) -> Result<Result<Submission, ClientError>> {
+        let invoice_id = &job.payment.invoice_id;
+        if let Some(sent) = &job.in_flight {
+            match resume(client, sent).await {
+                Ok(Resumed::Landed(hash)) => return Ok(Ok(Submission::Recorded { hash })),
+                Ok(Resumed::Dead(err)) => {
+                    tracing::info!(
+                        %invoice_id,
+                        hash = %sent.hash,
+                        ?err,
+                        "in-flight transaction cannot land; rebuilding"
+                    );
+                    // An expired transaction never used its sequence number,
+                    // so the counter is ahead of the account.
+                    let stale = match &err {
+                        Some(err) => err.is_bad_seq(),
+                        None => true,
+                    };
+                    if stale {
+                        self.sequences.resync(signer.public_key_bytes());
+                    }
+                    queue.untrack(invoice_id).await?;
+                }
+                // Still pending, or RPC could not tell: leave it in flight.
+                Err(err) => return Ok(Err(err)),
+            }
+        }
+
+        let mut result = self
+            .send_new(client, signer, queue, job, inclusion_fee)
+            .await?;
+        if result.as_ref().is_err_and(ClientError::is_bad_seq) {
+            tracing::warn!(%invoice_id, "bad sequence number; rebuilding");
+            result = self
+                .send_new(client, signer, queue, job, inclusion_fee)
+                .await?;
+        }
+        Ok(result)
+    }
+
+    /// Build, record, send and wait for a new transaction.
+    async fn send_new(
+        &self,
+        client: &InvoicePaymentClient,
+        signer: &impl Signer,
+        queue: &SubmissionQueue,
+        job: &Job,
+        inclusion_fee: u32,
+    ) -> Result<Result<Submission, ClientError>> {
+        let invoice_id = &job.payment.invoice_id;
+        let source = signer.public_key_bytes();
+        let sequence = match self.sequences.next(client.rpc(), source).await {
+            Ok(sequence) => sequence,
+            Err(err) => return Ok(Err(err)),
+        };
+        let sent = match prepare(client, signer, &job.payment, sequence, inclusion_fee).await {
+            Ok(sent) => sent,
+            Err(err) => {
+                self.sequences.release(source, sequence);
+                return Ok(match err {
+                    ClientError::Contract(ContractError::PaymentAlreadyRecorded) => {
+                        Ok(Submission::AlreadyRecorded)
+                    }
+                    err => Err(err),
+                });
+            }
+        };
+
+        queue.track(invoice_id, &sent).await?;
+        if let Err(err) = send(client, &sent).await {
+            if matches!(err, ClientError::SendRejected { .. }) {
+                // Definitely not accepted, so it can never land.
+                queue.untrack(invoice_id).await?;
+                if err.is_bad_seq() {
+                    self.sequences.resync(source);
+                } else {
+                    self.sequences.release(source, sequence);
+                }
+            }
+            return Ok(Err(err));
+        }
+
+        match client.wait_for(&sent.hash).await {
+            Ok(outcome) => Ok(Ok(Submission::Recorded { hash: outcome.hash })),
+            Err(err @ ClientError::TransactionFailed { .. }) => {
+                queue.untrack(invoice_id).await?;
+                if err.is_bad_seq() {
+                    self.sequences.resync(source);
+                }
+                Ok(Err(err))
+            }
+            Err(err) => Ok(Err(err)),
+        }
+    }
+}
+
+/// Simulate and sign `payment` at `sequence`, valid for [`VALIDITY`].
+async fn prepare(
+    client: &InvoicePaymentClient,
+    signer: &impl Signer,
+    payment: &RecordPayment,
+    sequence: i64,
+    inclusion_fee: u32,
+) -> Result<InFlight, ClientError> {
+    let valid_until = SystemTime::now() + VALIDITY;
+    let deadline = valid_until
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let args = record_payment_args(payment)?;
+    let envelope = client
+        .prepare(
+            signer,
+            "record_payment",
+            args,
+            sequence,
+            inclusion_fee,
+            deadline,
+        )
+        .await?;
+    Ok(InFlight {
+        hash: tx::envelope_hash(&envelope, client.network_passphrase())?,
+        envelope: envelope.to_xdr_base64(Limits::none())?,
+        sequence,
+        valid_until,
+    })
+}
+
+async fn send(client: &InvoicePaymentClient, sent: &InFlight) -> Result<String, ClientError> {
+    let envelope = TransactionEnvelope::from_xdr_base64(&sent.envelope, Limits::none())?;
+    client.send(&envelope).await
+}
+
+/// Find out what became of `sent`. Errors mean it may still land.
+async fn resume(client: &InvoicePaymentClient, sent: &InFlight) -> Result<Resumed, ClientError> {
+    let status = client.rpc().get_transaction(&sent.hash).await?;
+    match status.status.as_str() {
+        "SUCCESS" => return Ok(Resumed::Landed(sent.hash.clone())),
+        "FAILED" => {
+            return Ok(Resumed::Dead(Some(ClientError::TransactionFailed {
+                hash: sent.hash.clone(),
+                result_xdr: status.result_xdr,
+            })))
+        }
+        _ => {}
+    }
+    if is_expired(sent, SystemTime::now()) {
+        return Ok(Resumed::Dead(None));
+    }
+    // Unknown but within its deadline: it may be queued or dropped. The
+    // same envelope again is harmless either way.
+    send(client, sent).await?;
+    match client.wait_for(&sent.hash).await {
+        Ok(outcome) => Ok(Resumed::Landed(outcome.hash)),
+        Err(err @ ClientError::TransactionFailed { .. }) => Ok(Resumed::Dead(Some(err))),
+        Err(err) => Err(err),
+    }
+}
+
+/// `true` once `sent` is far enough past its deadline that it cannot land.
+pub fn is_expired(sent: &InFlight, now: SystemTime) -> bool {
+    now > sent.valid_until + DEADLINE_GRACE
+}
+
+/// How long from `now` until [`is_expired`] holds for `sent`.
+pub fn until_expired(sent: &InFlight, now: SystemTime) -> Duration {
+    (sent.valid_until + DEADLINE_GRACE)
+        .duration_since(now)
+        .unwrap_or_default()
+        + Duration::from_secs(1)
+}
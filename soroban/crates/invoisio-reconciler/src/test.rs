@@ -0,0 +1,623 @@
+#![cfg(test)]
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use invoisio_client::{Asset, Error as ClientError, InvoicePaymentClient, Keypair, RecordPayment};
+
+use crate::cursor::FileCursor;
+use crate::daemon::{Outcome, Reconciler};
+use crate::fees::{self, Bid, FeePolicy, FeeStrategy, NetworkFees};
+use crate::health::{
+    self, Active, Alert, Backlog, Balance, SequenceWatch, Snapshot, Status, Thresholds,
+};
+use crate::horizon::{Account, AccountBalance, HorizonClient, HorizonTransaction, PaymentOperation};
+use crate::invoices::InvoiceSource;
+use crate::matcher::{self, ExpectedInvoice, Verdict};
+use crate::queue::{Depth, Enqueued, InFlight, SubmissionQueue};
+use crate::sequence::SequenceManager;
+use crate::submitter::{is_expired, is_retryable, until_expired, Submission, DEFAULT_BACKOFF};
+
+const DESTINATION: &str = "GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5";
+const PAYER: &str = "GAP5LETOV6YIE62YAM56STDANPRDO7ZFDBGSNHJQIYGGKSMOZAHOOS2S";
+const CONTRACT_ID: &str = "CDOTTHKN4Z5RFSEDOILRIHNQRWGRCZWOMTDBCFK7W3OWRS2V3H7CEZVM";
+
+/// A successful native payment of `amount` to DESTINATION with `memo`.
+fn payment_op(memo: &str, amount: &str) -> PaymentOperation {
+    PaymentOperation {
+        id: "1".into(),
+        paging_token: "1".into(),
+        kind: "payment".into(),
+        transaction_hash: "abc".into(),
+        transaction_successful: true,
+        from: Some(PAYER.into()),
+        to: Some(DESTINATION.into()),
+        asset_type: Some("native".into()),
+        asset_code: None,
+        asset_issuer: None,
+        amount: Some(amount.into()),
+        created_at: "2026-01-01T00:00:00Z".into(),
+        transaction: Some(HorizonTransaction {
+            memo_type: "text".into(),
+            memo: Some(memo.into()),
+        }),
+    }
+}
+
+// Memo / amount parsing
+
+#[test]
+fn test_invoice_id_from_memo() {
+    assert_eq!(
+        matcher::invoice_id_from_memo("text", Some("invoisio-abc123")),
+        Some("invoisio-abc123")
+    );
+    assert_eq!(matcher::invoice_id_from_memo("text", Some("invoisio-")), None);
+    assert_eq!(matcher::invoice_id_from_memo("text", Some("rent-abc")), None);
+    assert_eq!(matcher::invoice_id_from_memo("id", Some("invoisio-1")), None);
+    assert_eq!(matcher::invoice_id_from_memo("none", None), None);
+}
+
+#[test]
+fn test_parse_amount() {
+    assert_eq!(matcher::parse_amount("10.0000000"), Some(100_000_000));
+    assert_eq!(matcher::parse_amount("0.0000001"), Some(1));
+    assert_eq!(matcher::parse_amount("5"), Some(50_000_000));
+    assert_eq!(matcher::parse_amount("1.5"), Some(15_000_000));
+    assert_eq!(matcher::parse_amount("1.00000001"), None);
+    assert_eq!(matcher::parse_amount("-1.0"), None);
+    assert_eq!(matcher::parse_amount("abc"), None);
+}
+
+#[test]
+fn test_candidate_requires_destination_and_success() {
+    let op = payment_op("invoisio-001", "1.0000000");
+    let c = matcher::candidate(&op, DESTINATION).unwrap();
+    assert_eq!(c.invoice_id, "invoisio-001");
+    assert_eq!(c.payer, PAYER);
+    assert_eq!(c.asset, Asset::Native);
+    assert_eq!(c.amount, 10_000_000);
+
+    assert!(matcher::candidate(&op, PAYER).is_none());
+
+    let mut failed = op.clone();
+    failed.transaction_successful = false;
+    assert!(matcher::candidate(&failed, DESTINATION).is_none());
+}
+
+#[test]
+fn test_candidate_token_asset() {
+    let mut op = payment_op("invoisio-usdc", "5.0000000");
+    op.asset_type = Some("credit_alphanum4".into());
+    op.asset_code = Some("USDC".into());
+    op.asset_issuer = Some(DESTINATION.into());
+
+    let c = matcher::candidate(&op, DESTINATION).unwrap();
+    assert_eq!(
+        c.asset,
+        Asset::Token {
+            code: "USDC".into(),
+            issuer: DESTINATION.into()
+        }
+    );
+}
+
+#[test]
+fn test_verify_verdicts() {
+    let c = matcher::candidate(&payment_op("invoisio-v", "1.0000000"), DESTINATION).unwrap();
+    let expected = |asset, amount| ExpectedInvoice {
+        invoice_id: "invoisio-v".into(),
+        asset,
+        amount,
+    };
+
+    assert_eq!(matcher::verify(&c, &expected(Asset::Native, 10_000_000)), Verdict::Match);
+    assert_eq!(matcher::verify(&c, &expected(Asset::Native, 5_000_000)), Verdict::Match);
+    assert_eq!(
+        matcher::verify(&c, &expected(Asset::Native, 20_000_000)),
+        Verdict::Underpaid {
+            expected: 20_000_000,
+            received: 10_000_000
+        }
+    );
+    assert!(matches!(
+        matcher::verify(
+            &c,
+            &expected(
+                Asset::Token {
+                    code: "USDC".into(),
+                    issuer: DESTINATION.into()
+                },
+                10_000_000
+            )
+        ),
+        Verdict::WrongAsset { .. }
+    ));
+}
+
+// Fees
+
+fn fee_strategy() -> FeeStrategy {
+    FeeStrategy::new(FeePolicy {
+        window: 4,
+        ..FeePolicy::default()
+    })
+}
+
+const FRESH: Duration = Duration::ZERO;
+const STALE: Duration = Duration::from_secs(600);
+
+#[test]
+fn test_fee_bid_starts_at_floor() {
+    let f = fee_strategy();
+    assert_eq!(
+        f.bid(0, FRESH),
+        Bid {
+            fee: 100,
+            urgent: false,
+            capped: false
+        }
+    );
+    assert_eq!(f.inclusion_rate(), None);
+}
+
+#[test]
+fn test_fee_bid_follows_surge_pricing() {
+    let f = fee_strategy();
+    f.set_network(NetworkFees {
+        p50: 500,
+        p90: 2_000,
+    });
+    assert_eq!(f.bid(0, FRESH).fee, 500);
+    let urgent = f.bid(0, STALE);
+    assert!(urgent.urgent);
+    assert_eq!(urgent.fee, 2_000);
+
+    // A quiet network never pulls the bid under the floor.
+    f.set_network(NetworkFees { p50: 0, p90: 50 });
+    assert_eq!(f.bid(0, STALE).fee, 100);
+}
+
+#[test]
+fn test_fee_bid_rises_with_retries_and_misses() {
+    let f = fee_strategy();
+    assert_eq!(f.bid(1, FRESH).fee, 200);
+    assert_eq!(f.bid(3, FRESH).fee, 800);
+
+    f.observe(true);
+    f.observe(true);
+    f.observe(true);
+    f.observe(false);
+    assert_eq!(f.inclusion_rate(), Some(0.75));
+    assert_eq!(f.bid(0, FRESH).fee, 200);
+
+    // The window keeps the last four: three misses out of four.
+    f.observe(false);
+    f.observe(false);
+    assert_eq!(f.inclusion_rate(), Some(0.25));
+    assert_eq!(f.bid(0, FRESH).fee, 400);
+    assert_eq!(f.bid(1, FRESH).fee, 800);
+}
+
+#[test]
+fn test_fee_bid_caps() {
+    let f = fee_strategy();
+    f.set_network(NetworkFees {
+        p50: 3_000,
+        p90: 30_000,
+    });
+    assert_eq!(
+        f.bid(4, FRESH),
+        Bid {
+            fee: 10_000,
+            urgent: false,
+            capped: true
+        }
+    );
+    assert_eq!(
+        f.bid(4, STALE),
+        Bid {
+            fee: 100_000,
+            urgent: true,
+            capped: true
+        }
+    );
+    // Huge attempt counts saturate instead of overflowing.
+    assert_eq!(f.bid(u32::MAX, STALE).fee, 100_000);
+}
+
+#[test]
+fn test_fee_inclusion_classification() {
+    let recorded = Ok(Submission::Recorded { hash: "ab".into() });
+    assert_eq!(fees::inclusion(&recorded), Some(true));
+    assert_eq!(fees::inclusion(&Ok(Submission::AlreadyRecorded)), None);
+
+    let timeout = Err(ClientError::Timeout { hash: "ab".into() });
+    assert_eq!(fees::inclusion(&timeout), Some(false));
+    let rejected = |status: &str| {
+        Err(ClientError::SendRejected {
+            hash: "ab".into(),
+            status: status.into(),
+            error_result_xdr: None,
+        })
+    };
+    assert_eq!(fees::inclusion(&rejected("TRY_AGAIN_LATER")), Some(false));
+    assert_eq!(fees::inclusion(&rejected("ERROR")), None);
+    assert_eq!(fees::inclusion(&Err(ClientError::Decode("x".into()))), None);
+}
+
+// Resubmission
+
+#[test]
+fn test_sequence_manager_counts_releases_and_resyncs() {
+    let s = SequenceManager::new();
+    let (a, b) = ([1u8; 32], [2u8; 32]);
+    assert_eq!(s.advance(a), None);
+    assert_eq!(s.start(a, 41), 42);
+    assert_eq!(s.advance(a), Some(43));
+    assert_eq!(s.start(b, 7), 8);
+
+    // Giving back the latest number reuses it; an older one leaves a gap.
+    s.release(a, 43);
+    assert_eq!(s.advance(a), Some(43));
+    assert_eq!(s.advance(a), Some(44));
+    s.release(a, 43);
+    assert_eq!(s.advance(a), Some(45));
+
+    // A lagging read never moves the counter backwards.
+    assert_eq!(s.start(a, 40), 46);
+    s.resync(a);
+    assert_eq!(s.advance(a), None);
+    assert_eq!(s.advance(b), Some(9));
+}
+
+#[test]
+fn test_bad_seq_rejection_is_retryable() {
+    use stellar_xdr::curr::{
+        Limits, TransactionResult, TransactionResultExt, TransactionResultResult, WriteXdr,
+    };
+
+    let bad_seq = TransactionResult {
+        fee_charged: 100,
+        result: TransactionResultResult::TxBadSeq,
+        ext: TransactionResultExt::V0,
+    };
+    let rejected = |status: &str, xdr: Option<String>| ClientError::SendRejected {
+        hash: "ab".into(),
+        status: status.into(),
+        error_result_xdr: xdr,
+    };
+    let xdr = bad_seq.to_xdr_base64(Limits::none()).unwrap();
+    assert!(is_retryable(&rejected("ERROR", Some(xdr))));
+    assert!(is_retryable(&rejected("TRY_AGAIN_LATER", None)));
+    assert!(!is_retryable(&rejected("ERROR", None)));
+}
+
+fn sent_tx(valid_until: SystemTime) -> InFlight {
+    InFlight {
+        hash: "ab".into(),
+        envelope: "AAAA".into(),
+        sequence: 42,
+        valid_until,
+    }
+}
+
+#[test]
+fn test_in_flight_expires_after_grace() {
+    let now = UNIX_EPOCH + Duration::from_secs(1_800_000_000);
+    let sent = sent_tx(now);
+    assert!(!is_expired(&sent, now));
+    assert!(!is_expired(&sent, now + Duration::from_secs(30)));
+
+    let wait = until_expired(&sent, now);
+    assert_eq!(wait, Duration::from_secs(31));
+    assert!(is_expired(&sent, now + wait));
+
+    let later = now + Duration::from_secs(100);
+    assert!(is_expired(&sent, later));
+    assert_eq!(until_expired(&sent, later), Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn test_queue_tracks_in_flight_transactions() {
+    let q = SubmissionQueue::in_memory().await.unwrap();
+    q.enqueue(&record("invoisio-a")).await.unwrap();
+    q.enqueue(&record("invoisio-b")).await.unwrap();
+    let sent = sent_tx(UNIX_EPOCH + Duration::from_secs(1_800_000_000));
+    q.track("invoisio-a", &sent).await.unwrap();
+
+    let due = q.due(SystemTime::now(), 10).await.unwrap();
+    let job = |id: &str| due.iter().find(|j| j.payment.invoice_id == id).unwrap();
+    assert_eq!(job("invoisio-a").in_flight, Some(sent.clone()));
+    assert_eq!(job("invoisio-b").in_flight, None);
+
+    // A rebuild replaces the earlier transaction.
+    let rebuilt = InFlight {
+        hash: "cd".into(),
+        sequence: 43,
+        ..sent.clone()
+    };
+    q.track("invoisio-a", &rebuilt).await.unwrap();
+    assert_eq!(q.in_flight("invoisio-a").await.unwrap(), Some(rebuilt));
+    q.untrack("invoisio-a").await.unwrap();
+    assert_eq!(q.in_flight("invoisio-a").await.unwrap(), None);
+
+    // Closing a job forgets its transaction.
+    q.track("invoisio-b", &sent).await.unwrap();
+    q.complete("invoisio-b", Some("ab")).await.unwrap();
+    assert_eq!(q.in_flight("invoisio-b").await.unwrap(), None);
+}
+
+// Cursor
+
+#[tokio::test]
+async fn test_file_cursor_round_trip() {
+    let path = std::env::temp_dir().join(format!("invoisio-cursor-{}", std::process::id()));
+    let cursor = FileCursor::new(&path);
+
+    assert_eq!(cursor.load().await.unwrap(), None);
+    cursor.save("12345-1").await.unwrap();
+    assert_eq!(cursor.load().await.unwrap(), Some("12345-1".into()));
+
+    let _ = std::fs::remove_file(path);
+}
+
+// Submission queue
+
+fn record(invoice_id: &str) -> RecordPayment {
+    RecordPayment {
+        invoice_id: invoice_id.into(),
+        payer: PAYER.into(),
+        asset: Asset::Native,
+        amount: 10_000_000,
+    }
+}
+
+#[tokio::test]
+async fn test_queue_deduplicates_by_invoice_id() {
+    let q = SubmissionQueue::in_memory().await.unwrap();
+    assert_eq!(q.enqueue(&record("invoisio-1")).await.unwrap(), Enqueued::New);
+    assert_eq!(q.enqueue(&record("invoisio-1")).await.unwrap(), Enqueued::Duplicate);
+    assert_eq!(q.enqueue(&record("invoisio-2")).await.unwrap(), Enqueued::New);
+
+    let due = q.due(SystemTime::now(), 10).await.unwrap();
+    assert_eq!(due.len(), 2);
+    assert_eq!(due[0].payment, record("invoisio-1"));
+    assert_eq!(due[0].attempts, 0);
+}
+
+#[tokio::test]
+async fn test_queue_retry_complete_and_fail() {
+    let q = SubmissionQueue::in_memory().await.unwrap();
+    q.enqueue(&record("invoisio-a")).await.unwrap();
+    q.enqueue(&record("invoisio-b")).await.unwrap();
+
+    q.retry_later("invoisio-a", Duration::from_secs(60), "timeout").await.unwrap();
+    let now = SystemTime::now();
+    let due = q.due(now, 10).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].payment.invoice_id, "invoisio-b");
+
+    // Due jobs come out by next attempt, so b, never retried, comes first.
+    let later = q.due(now + Duration::from_secs(120), 10).await.unwrap();
+    assert_eq!(later.len(), 2);
+    assert_eq!(later[0].payment.invoice_id, "invoisio-b");
+    assert_eq!(later[0].attempts, 0);
+    assert_eq!(later[1].payment.invoice_id, "invoisio-a");
+    assert_eq!(later[1].attempts, 1);
+
+    q.complete("invoisio-a", Some("abc")).await.unwrap();
+    q.fail("invoisio-b", "InvalidAmount").await.unwrap();
+    assert!(q.due(now + Duration::from_secs(120), 10).await.unwrap().is_empty());
+    assert_eq!(
+        q.depth().await.unwrap(),
+        Depth {
+            pending: 0,
+            done: 1,
+            failed: 1
+        }
+    );
+
+    // A closed entry still blocks re-enqueueing the invoice.
+    assert_eq!(q.enqueue(&record("invoisio-a")).await.unwrap(), Enqueued::Duplicate);
+}
+
+#[tokio::test]
+async fn test_queue_oldest_pending_ignores_closed_jobs() {
+    let q = SubmissionQueue::in_memory().await.unwrap();
+    assert_eq!(q.oldest_pending().await.unwrap(), None);
+    q.enqueue(&record("invoisio-a")).await.unwrap();
+    assert!(q.oldest_pending().await.unwrap().is_some());
+    q.complete("invoisio-a", None).await.unwrap();
+    assert_eq!(q.oldest_pending().await.unwrap(), None);
+}
+
+// Health monitor
+
+fn account(balance: &str, subentries: u32) -> Account {
+    Account {
+        sequence: "42".into(),
+        subentry_count: subentries,
+        num_sponsoring: 0,
+        num_sponsored: 0,
+        balances: vec![AccountBalance {
+            asset_type: "native".into(),
+            balance: balance.into(),
+            selling_liabilities: Some("1.0000000".into()),
+        }],
+    }
+}
+
+fn healthy() -> Snapshot {
+    Snapshot {
+        balance: Some(Balance {
+            total: 1_000_000_000,
+            spendable: 900_000_000,
+        }),
+        sequence: Some(42),
+        backlog: Backlog::default(),
+    }
+}
+
+#[test]
+fn test_balance_subtracts_reserve_and_liabilities() {
+    // (2 + 3 subentries) * 0.5 XLM reserve, plus 1 XLM of liabilities.
+    let balance = Balance::of(&account("20.0000000", 3)).unwrap();
+    assert_eq!(balance.total, 200_000_000);
+    assert_eq!(balance.spendable, 200_000_000 - 25_000_000 - 10_000_000);
+
+    let mut no_xlm = account("20.0000000", 0);
+    no_xlm.balances[0].asset_type = "credit_alphanum4".into();
+    assert_eq!(Balance::of(&no_xlm), None);
+}
+
+#[test]
+fn test_evaluate_thresholds() {
+    let t = Thresholds::default();
+    assert!(health::evaluate(&healthy(), None, &t).is_empty());
+
+    let mut poor = healthy();
+    poor.balance = Some(Balance {
+        total: 60_000_000,
+        spendable: 50_000_000,
+    });
+    assert_eq!(
+        health::evaluate(&poor, None, &t),
+        [Alert::LowBalance {
+            spendable: 50_000_000,
+            minimum: t.min_balance
+        }]
+    );
+
+    let missing = Snapshot {
+        balance: None,
+        sequence: None,
+        ..healthy()
+    };
+    assert_eq!(health::evaluate(&missing, None, &t), [Alert::AccountMissing]);
+
+    let mut stuck = healthy();
+    stuck.backlog = Backlog {
+        pending: 3,
+        failed: 0,
+        oldest_age: Some(Duration::from_secs(900)),
+    };
+    let alerts = health::evaluate(&stuck, Some(Duration::from_secs(400)), &t);
+    let names: Vec<_> = alerts.iter().map(Alert::name).collect();
+    assert_eq!(names, ["sequence_stalled", "backlog"]);
+}
+
+#[test]
+fn test_sequence_watch_counts_only_while_pending() {
+    let start = std::time::Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+    let mut watch = SequenceWatch::default();
+    assert_eq!(watch.observe(10, true, at(0)), None);
+    assert_eq!(watch.observe(10, true, at(60)), Some(Duration::from_secs(60)));
+    // Progress resets the clock.
+    assert_eq!(watch.observe(11, true, at(90)), None);
+    assert_eq!(watch.observe(11, true, at(100)), Some(Duration::from_secs(10)));
+    // So does an empty queue: an idle account is not stuck.
+    assert_eq!(watch.observe(11, false, at(200)), None);
+    assert_eq!(watch.observe(11, true, at(300)), None);
+}
+
+#[test]
+fn test_active_alerts_notify_on_changes_only() {
+    let low = Alert::LowBalance {
+        spendable: 1,
+        minimum: 2,
+    };
+    let mut active = Active::default();
+    assert_eq!(
+        active.update(vec![low.clone()]),
+        [(Status::Firing, low.clone())]
+    );
+    assert!(active.update(vec![low.clone()]).is_empty());
+    assert_eq!(
+        active.update(vec![Alert::AccountMissing]),
+        [
+            (Status::Firing, Alert::AccountMissing),
+            (Status::Resolved, low)
+        ]
+    );
+    assert_eq!(
+        active.update(Vec::new()),
+        [(Status::Resolved, Alert::AccountMissing)]
+    );
+}
+
+#[test]
+fn test_xlm_formatting() {
+    assert_eq!(health::xlm(32_000_000), "3.2000000");
+    assert_eq!(health::xlm(-5), "-0.0000005");
+    assert_eq!(
+        Alert::LowBalance {
+            spendable: 32_000_000,
+            minimum: 100_000_000
+        }
+        .message("GABC"),
+        "recorder GABC has 3.2000000 XLM above reserve (minimum 10.0000000)"
+    );
+}
+
+// Reconciler (paths that never reach the network)
+
+struct FixedInvoices(Option<ExpectedInvoice>);
+
+impl InvoiceSource for FixedInvoices {
+    async fn expected(&self, _invoice_id: &str) -> crate::Result<Option<ExpectedInvoice>> {
+        Ok(self.0.clone())
+    }
+}
+
+async fn reconciler(invoices: FixedInvoices) -> Reconciler<FixedInvoices> {
+    Reconciler::new(
+        HorizonClient::new("http://127.0.0.1:1"),
+        InvoicePaymentClient::new("http://127.0.0.1:1", "Test", CONTRACT_ID).unwrap(),
+        Keypair::from_seed([1u8; 32]).into(),
+        invoices,
+        FileCursor::new(std::env::temp_dir().join("invoisio-unused.cursor")),
+        SubmissionQueue::in_memory().await.unwrap(),
+        DESTINATION.into(),
+        DEFAULT_BACKOFF,
+    )
+}
+
+#[tokio::test]
+async fn test_handle_ignores_non_invoisio_memo() {
+    let r = reconciler(FixedInvoices(None)).await;
+    let outcome = r.handle(&payment_op("rent", "1.0")).await.unwrap();
+    assert_eq!(outcome, Outcome::Ignored);
+}
+
+#[tokio::test]
+async fn test_handle_unknown_invoice() {
+    let r = reconciler(FixedInvoices(None)).await;
+    let outcome = r.handle(&payment_op("invoisio-x", "1.0")).await.unwrap();
+    assert_eq!(outcome, Outcome::UnknownInvoice);
+}
+
+#[tokio::test]
+async fn test_handle_rejects_underpayment() {
+    let r = reconciler(FixedInvoices(Some(ExpectedInvoice {
+        invoice_id: "invoisio-x".into(),
+        asset: Asset::Native,
+        amount: 20_000_000,
+    })))
+    .await;
+    let outcome = r.handle(&payment_op("invoisio-x", "1.0")).await.unwrap();
+    assert!(matches!(outcome, Outcome::Rejected(Verdict::Underpaid { .. })));
+}
+
+#[tokio::test]
+async fn test_handle_queues_match_once() {
+    let r = reconciler(FixedInvoices(Some(ExpectedInvoice {
+        invoice_id: "invoisio-x".into(),
+        asset: Asset::Native,
+        amount: 10_000_000,
+    })))
+    .await;
+    let op = payment_op("invoisio-x", "1.0");
+    assert_eq!(r.handle(&op).await.unwrap(), Outcome::Queued);
+    assert_eq!(r.handle(&op).await.unwrap(), Outcome::AlreadyQueued);
+}
@@ -0,0 +1,103 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+/// Notifier configuration, read from environment variables.
+///
+/// | Variable                      | Required | Default               |
+/// |-------------------------------|----------|-----------------------|
+/// | `INVOISIO_RPC_URL`            | yes      |                       |
+/// | `INVOISIO_NETWORK_PASSPHRASE` | yes      |                       |
+/// | `INVOISIO_CONTRACT_ID`        | yes      |                       |
+/// | `INVOISIO_WEBHOOK_URLS`       | yes      |                       |
+/// | `INVOISIO_WEBHOOK_SECRET`     | yes      |                       |
+/// | `INVOISIO_WEBHOOK_EVENTS`     | no       | all kinds             |
+/// | `INVOISIO_START_LEDGER`       | no       | `1`                   |
+/// | `INVOISIO_CURSOR_FILE`        | no       | `webhooks.cursor`     |
+/// | `INVOISIO_DELIVERY_LOG`       | no       | `webhooks.deliveries` |
+/// | `INVOISIO_POLL_SECS`          | no       | `5`                   |
+/// | `INVOISIO_MAX_ATTEMPTS`       | no       | `8`                   |
+/// | `INVOISIO_METRICS_ADDR`       | no       | disabled              |
+///
+/// `INVOISIO_WEBHOOK_URLS` and `INVOISIO_WEBHOOK_EVENTS` are comma-separated.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub rpc_url: String,
+    pub network_passphrase: String,
+    pub contract_id: String,
+    pub endpoints: Vec<String>,
+    /// HMAC-SHA256 key for the `X-Invoisio-Signature` header.
+    pub secret: String,
+    /// Webhook kinds to deliver; empty means all.
+    pub events: Vec<String>,
+    /// Ledger to start from when no cursor has been saved yet.
+    pub start_ledger: u32,
+    pub cursor_file: PathBuf,
+    /// Deliveries settled since the cursor was last saved.
+    pub delivery_log: PathBuf,
+    pub poll_interval: Duration,
+    pub max_attempts: u32,
+    /// `host:port` for the Prometheus `/metrics` endpoint.
+    pub metrics_addr: Option<SocketAddr>,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self> {
+        let endpoints = list(&required("INVOISIO_WEBHOOK_URLS")?);
+        if endpoints.is_empty() {
+            return Err(Error::Config("INVOISIO_WEBHOOK_URLS has no URLs".into()));
+        }
+        Ok(Self {
+            rpc_url: required("INVOISIO_RPC_URL")?,
+            network_passphrase: required("INVOISIO_NETWORK_PASSPHRASE")?,
+            contract_id: required("INVOISIO_CONTRACT_ID")?,
+            endpoints,
+            secret: required("INVOISIO_WEBHOOK_SECRET")?,
+            events: optional("INVOISIO_WEBHOOK_EVENTS")
+                .map(|v| list(&v))
+                .unwrap_or_default(),
+            start_ledger: parsed("INVOISIO_START_LEDGER", 1)?,
+            cursor_file: optional("INVOISIO_CURSOR_FILE")
+                .unwrap_or_else(|| "webhooks.cursor".into())
+                .into(),
+            delivery_log: optional("INVOISIO_DELIVERY_LOG")
+                .unwrap_or_else(|| "webhooks.deliveries".into())
+                .into(),
+            poll_interval: Duration::from_secs(parsed("INVOISIO_POLL_SECS", 5)?),
+            max_attempts: parsed("INVOISIO_MAX_ATTEMPTS", 8)?,
+            metrics_addr: parsed_optional("INVOISIO_METRICS_ADDR")?,
+        })
+    }
+}
+
+/// Split a comma-separated list, dropping blanks.
+pub fn list(v: &str) -> Vec<String> {
+    v.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn required(name: &str) -> Result<String> {
+    optional(name).ok_or_else(|| Error::Config(format!("{name} is not set")))
+}
+
+fn optional(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn parsed<T: std::str::FromStr>(name: &str, default: T) -> Result<T> {
+    Ok(parsed_optional(name)?.unwrap_or(default))
+}
+
+fn parsed_optional<T: std::str::FromStr>(name: &str) -> Result<Option<T>> {
+    optional(name)
+        .map(|v| {
+            v.parse()
+                .map_err(|_| Error::Config(format!("{name}=`{v}` is not valid")))
+        })
+        .transpose()
+}
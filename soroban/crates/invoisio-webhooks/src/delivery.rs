@@ -0,0 +1,134 @@
+//! Signed HTTP delivery with exponential backoff.
+//!
+//! Each request carries `X-Invoisio-Signature: t=<unix>,v1=<hex>` where
+//! `v1 = HMAC-SHA256(secret, "<t>.<body>")`, so receivers can authenticate
+//! the payload and reject replays outside their tolerance window. The
+//! webhook id is also sent as `Idempotency-Key`; it is the same on every
+//! attempt, after restarts, and for every endpoint.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+pub use invoisio_client::Backoff;
+use reqwest::StatusCode;
+use sha2::Sha256;
+
+use crate::payload::Webhook;
+
+pub const SIGNATURE_HEADER: &str = "X-Invoisio-Signature";
+pub const ID_HEADER: &str = "X-Invoisio-Webhook-Id";
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+
+/// Delivery retries: 1 s doubling, capped at five minutes, eight tries.
+pub const DEFAULT_BACKOFF: Backoff = Backoff {
+    base: Duration::from_secs(1),
+    max: Duration::from_secs(300),
+    max_attempts: 8,
+};
+
+/// Final result of delivering one webhook to one endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Delivery {
+    Delivered { attempts: u32 },
+    /// The endpoint rejected the payload (4xx other than 408/429); retrying
+    /// would not help.
+    Rejected { status: u16 },
+    /// Retries exhausted on transient failures.
+    GaveUp { attempts: u32, last_error: String },
+}
+
+/// `true` if a response with `status` should be retried.
+pub fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+/// `t=<timestamp>,v1=<hex hmac>` for `body`.
+pub fn signature(secret: &[u8], timestamp: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("t={timestamp},v1={hex}")
+}
+
+/// POSTs webhooks to endpoints.
+#[derive(Clone, Debug)]
+pub struct Deliverer {
+    http: reqwest::Client,
+    secret: Vec<u8>,
+    backoff: Backoff,
+}
+
+impl Deliverer {
+    pub fn new(secret: impl Into<Vec<u8>>, backoff: Backoff) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("static client configuration");
+        Self {
+            http,
+            secret: secret.into(),
+            backoff,
+        }
+    }
+
+    /// Deliver `webhook` to `url`, retrying transient failures.
+    pub async fn deliver(&self, url: &str, webhook: &Webhook) -> Delivery {
+        let body = serde_json::to_vec(webhook).expect("webhook serializes");
+        let mut attempt = 0;
+        loop {
+            let last_error = match self.post(url, webhook, &body).await {
+                Ok(status) if status.is_success() => {
+                    return Delivery::Delivered {
+                        attempts: attempt + 1,
+                    }
+                }
+                Ok(status) if !is_retryable(status) => {
+                    return Delivery::Rejected {
+                        status: status.as_u16(),
+                    }
+                }
+                Ok(status) => format!("HTTP {status}"),
+                Err(err) => err.to_string(),
+            };
+
+            if attempt + 1 >= self.backoff.max_attempts {
+                return Delivery::GaveUp {
+                    attempts: attempt + 1,
+                    last_error,
+                };
+            }
+            let delay = self.backoff.delay(attempt);
+            tracing::warn!(url, webhook = %webhook.id, attempt, ?delay, error = %last_error, "delivery failed; retrying");
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn post(&self, url: &str, webhook: &Webhook, body: &[u8]) -> reqwest::Result<StatusCode> {
+        // Re-signed per attempt so the timestamp stays fresh.
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let response = self
+            .http
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(ID_HEADER, &webhook.id)
+            .header(IDEMPOTENCY_HEADER, &webhook.id)
+            .header(SIGNATURE_HEADER, signature(&self.secret, now, body))
+            .body(body.to_vec())
+            .send()
+            .await?;
+        Ok(response.status())
+    }
+}
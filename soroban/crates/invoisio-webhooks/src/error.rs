@@ -0,0 +1,21 @@
+/// Errors surfaced by the notifier.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+
+    #[error("http: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// Soroban RPC / decoding failure.
+    #[error(transparent)]
+    Client(#[from] invoisio_client::Error),
+
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
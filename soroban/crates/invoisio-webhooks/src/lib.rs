@@ -0,0 +1,31 @@
+//! # invoisio-webhooks
+//!
+//! Follows the `invoice-payment` contract's events and POSTs signed JSON
+//! webhooks to merchant endpoints, retrying with exponential backoff.
+//!
+//! ## Module layout
+//! | Module        | Responsibility                                    |
+//! |---------------|---------------------------------------------------|
+//! | `config.rs`   | Environment configuration                         |
+//! | `payload.rs`  | Contract event → [`Webhook`] mapping              |
+//! | `delivery.rs` | HMAC signing, HTTP delivery, backoff              |
+//! | `cursor.rs`   | Persisted `getEvents` cursor                      |
+//! | `state.rs`    | [`DeliveryLog`] of settled deliveries per page    |
+//! | `notifier.rs` | [`Notifier`] poll loop                            |
+
+pub mod config;
+pub mod cursor;
+pub mod delivery;
+pub mod error;
+pub mod notifier;
+pub mod payload;
+pub mod state;
+
+pub use config::Config;
+pub use delivery::{Backoff, Deliverer, Delivery};
+pub use error::{Error, Result};
+pub use notifier::Notifier;
+pub use payload::Webhook;
+pub use state::{DeliveryLog, DeliveryState};
+
+mod test;
@@ -0,0 +1,45 @@
+use invoisio_client::InvoicePaymentClient;
+use invoisio_webhooks::cursor::FileCursor;
+use invoisio_webhooks::delivery::DEFAULT_BACKOFF;
+use invoisio_webhooks::{Backoff, Config, Deliverer, DeliveryLog, Notifier};
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() -> invoisio_webhooks::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let config = Config::from_env()?;
+    invoisio_metrics::spawn(config.metrics_addr);
+    let client =
+        InvoicePaymentClient::new(&config.rpc_url, &config.network_passphrase, &config.contract_id)?;
+    let deliverer = Deliverer::new(
+        config.secret.as_bytes(),
+        Backoff {
+            max_attempts: config.max_attempts,
+            ..DEFAULT_BACKOFF
+        },
+    );
+
+    tracing::info!(
+        contract = %config.contract_id,
+        endpoints = config.endpoints.len(),
+        "starting webhook notifier"
+    );
+
+    Notifier::new(
+        client,
+        deliverer,
+        FileCursor::new(&config.cursor_file),
+        DeliveryLog::open(&config.delivery_log).await?,
+        config.endpoints.clone(),
+        config.events.clone(),
+        config.start_ledger,
+    )
+    .run(config.poll_interval, async {
+        let _ = tokio::signal::ctrl_c().await;
+    })
+    .await;
+    Ok(())
+}
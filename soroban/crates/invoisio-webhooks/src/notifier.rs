@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use invoisio_client::{EventEnvelope, EventStart, InvoicePaymentClient};
+use invoisio_metrics::metrics;
+
+use crate::cursor::FileCursor;
+use crate::delivery::{Deliverer, Delivery};
+use crate::error::Result;
+use crate::payload::{webhooks_for, Webhook};
+use crate::state::{DeliveryLog, DeliveryState};
+
+const PAGE_LIMIT: u32 = 100;
+
+/// Follows contract events and fans webhooks out to every endpoint.
+///
+/// The cursor is saved only after every webhook in a page has been settled
+/// for every endpoint, so a restart, or an endpoint that exhausted its
+/// retries, replays the page; the [`DeliveryLog`] keeps that replay from
+/// re-sending what an endpoint already settled. An endpoint that stays down
+/// therefore holds back the webhooks of later pages for every endpoint.
+pub struct Notifier {
+    client: InvoicePaymentClient,
+    deliverer: Deliverer,
+    cursor: FileCursor,
+    deliveries: DeliveryLog,
+    endpoints: Vec<String>,
+    /// Webhook kinds to send; empty means all.
+    events: Vec<String>,
+    start_ledger: u32,
+}
+
+impl Notifier {
+    pub fn new(
+        client: InvoicePaymentClient,
+        deliverer: Deliverer,
+        cursor: FileCursor,
+        deliveries: DeliveryLog,
+        endpoints: Vec<String>,
+        events: Vec<String>,
+        start_ledger: u32,
+    ) -> Self {
+        Self {
+            client,
+            deliverer,
+            cursor,
+            deliveries,
+            endpoints,
+            events,
+            start_ledger,
+        }
+    }
+
+    /// `true` if `webhook` passes the configured kind filter.
+    pub fn wants(&self, webhook: &Webhook) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == webhook.kind)
+    }
+
+    /// Fetch one page of events and deliver its webhooks. Returns the number
+    /// of contract events processed, or 0 if the page has to be replayed.
+    pub async fn poll_once(&self) -> Result<usize> {
+        let start = match self.cursor.load().await? {
+            Some(c) => EventStart::Cursor(c),
+            None => EventStart::Ledger(self.start_ledger),
+        };
+        let page = self
+            .client
+            .events(start, PAGE_LIMIT)
+            .await
+            .inspect_err(|err| metrics().rpc_error(err))?;
+
+        if !self.deliver_page(&page.events).await? {
+            tracing::warn!("deliveries outstanding; replaying the page on the next poll");
+            return Ok(0);
+        }
+        if let Some(cursor) = &page.cursor {
+            self.cursor.save(cursor).await?;
+            self.deliveries.clear().await?;
+        }
+        Ok(page.events.len())
+    }
+
+    /// Deliver the webhooks of `events` to every endpoint. Returns `true` if
+    /// all of them are settled, `false` if any gave up and must be retried.
+    pub async fn deliver_page(&self, events: &[EventEnvelope]) -> Result<bool> {
+        let mut settled = true;
+        for envelope in events {
+            for webhook in webhooks_for(envelope).iter().filter(|w| self.wants(w)) {
+                for url in &self.endpoints {
+                    settled &= self.deliver(url, webhook).await?;
+                }
+            }
+        }
+        Ok(settled)
+    }
+
+    /// Send `webhook` to `url` unless an earlier run already settled it.
+    /// Returns `true` once it is settled.
+    async fn deliver(&self, url: &str, webhook: &Webhook) -> Result<bool> {
+        if let Some(state) = self.deliveries.state(&webhook.id, url) {
+            metrics().webhooks.with_label_values(&["skipped"]).inc();
+            tracing::debug!(url, webhook = %webhook.id, ?state, "already settled; skipping");
+            return Ok(true);
+        }
+        let delivery = self.deliverer.deliver(url, webhook).await;
+        log_delivery(url, webhook, &delivery);
+        match DeliveryState::of(&delivery) {
+            Some(state) => {
+                self.deliveries.record(&webhook.id, url, state).await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Poll until `shutdown` resolves.
+    pub async fn run(&self, idle: Duration, shutdown: impl std::future::Future<Output = ()>) {
+        tokio::pin!(shutdown);
+        loop {
+            let wait = match self.poll_once().await {
+                Ok(n) if n >= PAGE_LIMIT as usize => Duration::ZERO,
+                Ok(_) => idle,
+                Err(err) => {
+                    tracing::error!(error = %err, "poll failed");
+                    idle
+                }
+            };
+            tokio::select! {
+                _ = &mut shutdown => {
+                    tracing::info!("shutting down");
+                    return;
+                }
+                _ = tokio::time::sleep(wait) => {}
+            }
+        }
+    }
+}
+
+fn log_delivery(url: &str, webhook: &Webhook, delivery: &Delivery) {
+    let result = match delivery {
+        Delivery::Delivered { .. } => "delivered",
+        Delivery::Rejected { .. } => "rejected",
+        Delivery::GaveUp { .. } => "gave_up",
+    };
+    metrics().webhooks.with_label_values(&[result]).inc();
+
+    match delivery {
+        Delivery::Delivered { attempts } => {
+            tracing::info!(url, webhook = %webhook.id, kind = webhook.kind, attempts, "delivered")
+        }
+        Delivery::Rejected { status } => {
+            tracing::error!(url, webhook = %webhook.id, status, "endpoint rejected webhook")
+        }
+        Delivery::GaveUp {
+            attempts,
+            last_error,
+        } => tracing::error!(
+            url,
+            webhook = %webhook.id,
+            attempts,
+            error = %last_error,
+            "giving up on webhook"
+        ),
+    }
+}
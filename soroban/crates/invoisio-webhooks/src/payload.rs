@@ -0,0 +1,60 @@
+//! Webhook payloads derived from contract events.
+//!
+//! | Contract event     | Webhooks                           |
+//! |--------------------|------------------------------------|
+//! | `payment_recorded` | `payment.recorded`, `invoice.paid` |
+//!
+//! `record_payment` is the only on-chain settlement step, so it yields both
+//! the payment-level and the invoice-level notification. Pending reports are
+//! not final and produce no webhook, and neither do imported payments, which
+//! the previous deployment already announced. `payment.refunded` is reserved:
+//! it is not derived from the contract's `payment_refunded` event yet.
+
+use invoisio_client::{ContractEvent, EventEnvelope, PaymentRecord};
+use serde::Serialize;
+
+pub const PAYMENT_RECORDED: &str = "payment.recorded";
+pub const INVOICE_PAID: &str = "invoice.paid";
+
+/// Body POSTed to every endpoint.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Webhook {
+    /// Stable across retries and restarts (`<event id>:<kind>`); receivers
+    /// should deduplicate on it.
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub contract_id: String,
+    pub ledger: u32,
+    pub ledger_closed_at: String,
+    pub tx_hash: Option<String>,
+    pub data: PaymentRecord,
+}
+
+impl Webhook {
+    fn new(kind: &'static str, envelope: &EventEnvelope, record: &PaymentRecord) -> Self {
+        Self {
+            id: format!("{}:{kind}", envelope.id),
+            kind,
+            contract_id: envelope.contract_id.clone(),
+            ledger: envelope.ledger,
+            ledger_closed_at: envelope.ledger_closed_at.clone(),
+            tx_hash: envelope.tx_hash.clone(),
+            data: record.clone(),
+        }
+    }
+}
+
+/// Webhooks to send for one contract event, in delivery order.
+pub fn webhooks_for(envelope: &EventEnvelope) -> Vec<Webhook> {
+    match &envelope.event {
+        ContractEvent::PaymentRecorded { record } => vec![
+            Webhook::new(PAYMENT_RECORDED, envelope, record),
+            Webhook::new(INVOICE_PAID, envelope, record),
+        ],
+        ContractEvent::PendingPaymentReported { .. }
+        | ContractEvent::PaymentImported { .. }
+        | ContractEvent::PaymentRefunded { .. }
+        | ContractEvent::Unknown { .. } => Vec::new(),
+    }
+}
@@ -0,0 +1,125 @@
+//! Per-endpoint delivery state, persisted next to the cursor.
+//!
+//! The cursor only moves once a whole page has been settled, so a restart
+//! replays the page that was in flight, and a page with a delivery that gave
+//! up is replayed on the next poll. [`DeliveryLog`] remembers which
+//! webhooks of that page each endpoint has already settled, letting the
+//! [`Notifier`](crate::Notifier) skip them instead of sending them again.
+//!
+//! Every settled delivery is appended as one JSON line and synced before the
+//! next send; the log is truncated once the cursor has moved past the page.
+//! A crash can still land between a successful POST and its log line, which
+//! is why receivers must deduplicate on the idempotency key as well.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+use crate::delivery::Delivery;
+use crate::error::Result;
+
+/// How a delivery was settled. Only final outcomes are recorded: a webhook
+/// that exhausted its retries holds the cursor and is attempted again when
+/// its page is replayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryState {
+    Delivered,
+    /// The endpoint answered with a non-retryable 4xx.
+    Rejected,
+}
+
+impl DeliveryState {
+    /// The state to record for `delivery`, if it is final.
+    pub fn of(delivery: &Delivery) -> Option<Self> {
+        match delivery {
+            Delivery::Delivered { .. } => Some(DeliveryState::Delivered),
+            Delivery::Rejected { .. } => Some(DeliveryState::Rejected),
+            Delivery::GaveUp { .. } => None,
+        }
+    }
+}
+
+/// One line of the log.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    id: String,
+    url: String,
+    state: DeliveryState,
+}
+
+/// File-backed set of settled `(webhook id, endpoint)` pairs.
+#[derive(Debug)]
+pub struct DeliveryLog {
+    path: PathBuf,
+    settled: Mutex<HashMap<(String, String), DeliveryState>>,
+}
+
+impl DeliveryLog {
+    /// Load the log at `path`, or start an empty one. A torn last line (a
+    /// crash mid-append) is ignored; that delivery is simply not settled.
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let body = match tokio::fs::read_to_string(&path).await {
+            Ok(body) => body,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let settled = body
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Entry>(line).ok())
+            .map(|e| ((e.id, e.url), e.state))
+            .collect();
+        Ok(Self {
+            path,
+            settled: Mutex::new(settled),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// How webhook `id` was settled for `url`, if it was.
+    pub fn state(&self, id: &str, url: &str) -> Option<DeliveryState> {
+        let settled = self.settled.lock().expect("delivery log lock");
+        settled.get(&(id.to_string(), url.to_string())).copied()
+    }
+
+    /// Durably record that webhook `id` was settled for `url`.
+    pub async fn record(&self, id: &str, url: &str, state: DeliveryState) -> Result<()> {
+        let entry = Entry {
+            id: id.into(),
+            url: url.into(),
+            state,
+        };
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.sync_data().await?;
+        self.settled
+            .lock()
+            .expect("delivery log lock")
+            .insert((entry.id, entry.url), state);
+        Ok(())
+    }
+
+    /// Forget everything, once the cursor has moved past the logged page.
+    pub async fn clear(&self) -> Result<()> {
+        match tokio::fs::remove_file(&self.path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        self.settled.lock().expect("delivery log lock").clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+#![cfg(test)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use invoisio_client::{Asset, ContractEvent, EventEnvelope, InvoicePaymentClient, PaymentRecord};
+use reqwest::StatusCode;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::config::list;
+use crate::cursor::FileCursor;
+use crate::delivery::{is_retryable, signature, Backoff, Deliverer, Delivery};
+use crate::notifier::Notifier;
+use crate::payload::{webhooks_for, INVOICE_PAID, PAYMENT_RECORDED};
+use crate::state::{DeliveryLog, DeliveryState};
+
+fn record() -> PaymentRecord {
+    PaymentRecord {
+        invoice_id: "invoisio-abc123".into(),
+        payer: "GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5".into(),
+        payer_mux_id: None,
+        asset: Asset::Native,
+        amount: 10_000_000,
+        timestamp: 1_700_000_000,
+    }
+}
+
+fn envelope(event: ContractEvent) -> EventEnvelope {
+    EventEnvelope {
+        id: "0000123-0000000001".into(),
+        ledger: 123,
+        ledger_closed_at: "2024-01-01T00:00:00Z".into(),
+        contract_id: "CCJZ5DGASBWQXR5MPFCJXMBI333XE5U3FSJTNQU7RIKE3P5GN2K2WYD5".into(),
+        tx_hash: None,
+        event,
+    }
+}
+
+// Payloads
+
+#[test]
+fn test_payment_recorded_yields_payment_and_invoice_webhooks() {
+    let hooks = webhooks_for(&envelope(ContractEvent::PaymentRecorded { record: record() }));
+    let kinds: Vec<_> = hooks.iter().map(|h| h.kind).collect();
+    assert_eq!(kinds, [PAYMENT_RECORDED, INVOICE_PAID]);
+    assert_eq!(hooks[0].id, "0000123-0000000001:payment.recorded");
+    assert_ne!(hooks[0].id, hooks[1].id);
+}
+
+#[test]
+fn test_pending_and_unknown_events_yield_nothing() {
+    assert!(webhooks_for(&envelope(ContractEvent::PendingPaymentReported { record: record() }))
+        .is_empty());
+    assert!(webhooks_for(&envelope(ContractEvent::Unknown {
+        topic: "x".into()
+    }))
+    .is_empty());
+}
+
+#[test]
+fn test_webhook_json_shape() {
+    let hooks = webhooks_for(&envelope(ContractEvent::PaymentRecorded { record: record() }));
+    let json = serde_json::to_value(&hooks[0]).unwrap();
+    assert_eq!(json["type"], "payment.recorded");
+    assert_eq!(json["data"]["invoice_id"], "invoisio-abc123");
+    assert_eq!(json["data"]["amount"], "10000000");
+}
+
+// Delivery
+
+#[test]
+fn test_signature_is_deterministic_and_keyed() {
+    let a = signature(b"secret", 1_700_000_000, b"{}");
+    assert!(a.starts_with("t=1700000000,v1="));
+    assert_eq!(a.len(), "t=1700000000,v1=".len() + 64);
+    assert_eq!(a, signature(b"secret", 1_700_000_000, b"{}"));
+    assert_ne!(a, signature(b"other", 1_700_000_000, b"{}"));
+    assert_ne!(a, signature(b"secret", 1_700_000_001, b"{}"));
+}
+
+#[test]
+fn test_retry_classification() {
+    assert!(is_retryable(StatusCode::INTERNAL_SERVER_ERROR));
+    assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+    assert!(is_retryable(StatusCode::REQUEST_TIMEOUT));
+    assert!(!is_retryable(StatusCode::BAD_REQUEST));
+    assert!(!is_retryable(StatusCode::GONE));
+}
+
+// Delivery state
+
+fn log_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("invoisio-{name}-{}.deliveries", std::process::id()))
+}
+
+#[tokio::test]
+async fn test_delivery_log_survives_restart() {
+    let path = log_path("restart");
+    let (recorded, paid) = ("e1:payment.recorded", "e1:invoice.paid");
+    let (a, b) = ("https://a/hook", "https://b/hook");
+    let log = DeliveryLog::open(&path).await.unwrap();
+    assert_eq!(log.state(recorded, a), None);
+    log.record(recorded, a, DeliveryState::Delivered)
+        .await
+        .unwrap();
+    log.record(paid, a, DeliveryState::Rejected).await.unwrap();
+
+    let reopened = DeliveryLog::open(&path).await.unwrap();
+    assert_eq!(reopened.state(recorded, a), Some(DeliveryState::Delivered));
+    assert_eq!(reopened.state(paid, a), Some(DeliveryState::Rejected));
+    // Settled per endpoint: another URL still gets it.
+    assert_eq!(reopened.state(recorded, b), None);
+
+    reopened.clear().await.unwrap();
+    assert_eq!(reopened.state(recorded, a), None);
+    assert!(!path.exists());
+    reopened.clear().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_delivery_log_ignores_torn_last_line() {
+    let path = log_path("torn");
+    std::fs::write(
+        &path,
+        "{\"id\":\"e1:payment.recorded\",\"url\":\"https://a/hook\",\"state\":\"delivered\"}\n{\"id\":\"e2",
+    )
+    .unwrap();
+    let log = DeliveryLog::open(&path).await.unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        log.state("e1:payment.recorded", "https://a/hook"),
+        Some(DeliveryState::Delivered)
+    );
+}
+
+#[test]
+fn test_only_final_outcomes_are_recorded() {
+    let gave_up = Delivery::GaveUp {
+        attempts: 8,
+        last_error: "HTTP 503".into(),
+    };
+    assert_eq!(
+        DeliveryState::of(&Delivery::Delivered { attempts: 1 }),
+        Some(DeliveryState::Delivered)
+    );
+    assert_eq!(
+        DeliveryState::of(&Delivery::Rejected { status: 400 }),
+        Some(DeliveryState::Rejected)
+    );
+    assert_eq!(DeliveryState::of(&gave_up), None);
+}
+
+// Notifier
+
+/// An endpoint that answers 503 to its first `failures` requests and 200
+/// afterwards. Returns its URL and the number of requests it received.
+async fn flaky_endpoint(failures: usize) -> (String, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let seen = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            // Headers, then as much body as `Content-Length` announces.
+            while !is_complete(&request) {
+                match socket.read(&mut buf).await.unwrap() {
+                    0 => break,
+                    n => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let status = if seen.fetch_add(1, Ordering::SeqCst) < failures {
+                "503 Service Unavailable"
+            } else {
+                "200 OK"
+            };
+            let response =
+                format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    (url, requests)
+}
+
+fn is_complete(request: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(request);
+    let Some(end) = text.find("\r\n\r\n") else {
+        return false;
+    };
+    let length = text[..end]
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    request.len() >= end + 4 + length
+}
+
+#[tokio::test]
+async fn test_webhook_that_gave_up_is_retried_until_the_endpoint_recovers() {
+    let (url, requests) = flaky_endpoint(3).await;
+    let log = DeliveryLog::open(log_path("recovers")).await.unwrap();
+    let notifier = Notifier::new(
+        InvoicePaymentClient::new(
+            "http://127.0.0.1:1",
+            "Test",
+            "CCJZ5DGASBWQXR5MPFCJXMBI333XE5U3FSJTNQU7RIKE3P5GN2K2WYD5",
+        )
+        .unwrap(),
+        Deliverer::new(
+            b"secret".to_vec(),
+            Backoff {
+                base: Duration::from_millis(1),
+                max: Duration::from_millis(1),
+                max_attempts: 2,
+            },
+        ),
+        FileCursor::new(std::env::temp_dir().join("invoisio-unused.cursor")),
+        log,
+        vec![url.clone()],
+        vec![PAYMENT_RECORDED.to_string()],
+        0,
+    );
+    let events = [envelope(ContractEvent::PaymentRecorded { record: record() })];
+    let id = "0000123-0000000001:payment.recorded";
+
+    // Both attempts get a 503: the page is not settled and nothing is logged.
+    assert!(!notifier.deliver_page(&events).await.unwrap());
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+    // Replayed: one more 503, then the endpoint is back.
+    assert!(notifier.deliver_page(&events).await.unwrap());
+    assert_eq!(requests.load(Ordering::SeqCst), 4);
+
+    // Settled now, so a further replay sends nothing.
+    assert!(notifier.deliver_page(&events).await.unwrap());
+    assert_eq!(requests.load(Ordering::SeqCst), 4);
+
+    let log = DeliveryLog::open(log_path("recovers")).await.unwrap();
+    assert_eq!(log.state(id, &url), Some(DeliveryState::Delivered));
+    log.clear().await.unwrap();
+}
+
+// Config
+
+#[test]
+fn test_list_splits_and_trims() {
+    assert_eq!(
+        list(" https://a/hook , ,https://b/hook"),
+        ["https://a/hook", "https://b/hook"]
+    );
+    assert!(list("").is_empty());
+}